@@ -0,0 +1,33 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Accessibility1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Accessibility1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Accessibility1 {
+    /// HighContrast property
+    #[zbus(property)]
+    fn high_contrast(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_high_contrast(&self, value: bool) -> zbus::Result<()>;
+
+    /// TextScalingFactor property
+    #[zbus(property)]
+    fn text_scaling_factor(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn set_text_scaling_factor(&self, value: f64) -> zbus::Result<()>;
+}
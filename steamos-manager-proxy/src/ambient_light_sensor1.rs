@@ -19,7 +19,14 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait AmbientLightSensor1 {
+    /// SetLuxStreaming method
+    fn set_lux_streaming(&self, enabled: bool) -> zbus::Result<()>;
+
     /// AlsCalibrationGain property
     #[zbus(property)]
     fn als_calibration_gain(&self) -> zbus::Result<Vec<f64>>;
+
+    /// Lux property
+    #[zbus(property)]
+    fn lux(&self) -> zbus::Result<f64>;
 }
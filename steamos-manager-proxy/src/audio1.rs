@@ -0,0 +1,73 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Audio1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Audio1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Audio1 {
+    /// GetProfiles method
+    fn get_profiles(&self, device: u32) -> zbus::Result<Vec<(u32, String, String, bool, bool)>>;
+
+    /// SetProfile method
+    fn set_profile(&self, device: u32, profile: u32) -> zbus::Result<()>;
+
+    /// DefaultOutput property
+    #[zbus(property)]
+    fn default_output(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_default_output(&self, value: u32) -> zbus::Result<()>;
+
+    /// Devices property
+    #[zbus(property)]
+    fn devices(&self) -> zbus::Result<Vec<(u32, String, String)>>;
+
+    /// HardwareInputMuted property
+    #[zbus(property)]
+    fn hardware_input_muted(&self) -> zbus::Result<bool>;
+
+    /// InputMuted property
+    #[zbus(property)]
+    fn input_muted(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_input_muted(&self, value: bool) -> zbus::Result<()>;
+
+    /// InputVolume property
+    #[zbus(property)]
+    fn input_volume(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn set_input_volume(&self, value: f64) -> zbus::Result<()>;
+
+    /// MaxSpeakerVolume property
+    #[zbus(property)]
+    fn max_speaker_volume(&self) -> zbus::Result<f64>;
+
+    /// OutputVolume property
+    #[zbus(property)]
+    fn output_volume(&self) -> zbus::Result<f64>;
+    #[zbus(property)]
+    fn set_output_volume(&self, value: f64) -> zbus::Result<()>;
+
+    /// Outputs property
+    #[zbus(property)]
+    fn outputs(&self) -> zbus::Result<Vec<(u32, u32, String, String)>>;
+
+    /// SpeakerVolumeLimitOverride property
+    #[zbus(property)]
+    fn speaker_volume_limit_override(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_speaker_volume_limit_override(&self, value: bool) -> zbus::Result<()>;
+}
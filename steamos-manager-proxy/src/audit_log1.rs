@@ -0,0 +1,26 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.AuditLog1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.1.0` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.AuditLog1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.AuditLog1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait AuditLog1 {
+    /// GetRecentAuditEntries method
+    fn get_recent_audit_entries(
+        &self,
+    ) -> zbus::Result<Vec<(u64, u32, u32, String, String, String, Vec<String>, String)>>;
+}
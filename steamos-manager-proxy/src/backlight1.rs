@@ -0,0 +1,46 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Backlight1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Backlight1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Backlight1 {
+    /// StepBrightness method
+    fn step_brightness(&self, steps: i32) -> zbus::Result<u32>;
+
+    /// Brightness property
+    #[zbus(property)]
+    fn brightness(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_brightness(&self, value: u32) -> zbus::Result<()>;
+
+    /// BrightnessStep property
+    #[zbus(property)]
+    fn brightness_step(&self) -> zbus::Result<u32>;
+
+    /// Device property
+    #[zbus(property)]
+    fn device(&self) -> zbus::Result<String>;
+
+    /// Devices property
+    #[zbus(property)]
+    fn devices(&self) -> zbus::Result<Vec<String>>;
+
+    /// MaxBrightness property
+    #[zbus(property)]
+    fn max_brightness(&self) -> zbus::Result<u32>;
+}
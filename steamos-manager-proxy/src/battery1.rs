@@ -0,0 +1,45 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Battery1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Battery1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Battery1 {
+    /// Capacity property
+    #[zbus(property)]
+    fn capacity(&self) -> zbus::Result<u32>;
+
+    /// ChargeFull property
+    #[zbus(property)]
+    fn charge_full(&self) -> zbus::Result<u64>;
+
+    /// ChargeNow property
+    #[zbus(property)]
+    fn charge_now(&self) -> zbus::Result<u64>;
+
+    /// ChargeState property
+    #[zbus(property)]
+    fn charge_state(&self) -> zbus::Result<u32>;
+
+    /// CycleCount property
+    #[zbus(property)]
+    fn cycle_count(&self) -> zbus::Result<u32>;
+
+    /// Health property
+    #[zbus(property)]
+    fn health(&self) -> zbus::Result<u32>;
+}
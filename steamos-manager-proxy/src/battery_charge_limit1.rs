@@ -19,6 +19,16 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait BatteryChargeLimit1 {
+    /// ChargeBypass property
+    #[zbus(property)]
+    fn charge_bypass(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_charge_bypass(&self, value: bool) -> zbus::Result<()>;
+
+    /// ChargeBypassSupported property
+    #[zbus(property)]
+    fn charge_bypass_supported(&self) -> zbus::Result<bool>;
+
     /// MaxChargeLevel property
     #[zbus(property)]
     fn max_charge_level(&self) -> zbus::Result<i32>;
@@ -0,0 +1,39 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.ColorFilters1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.ColorFilters1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait ColorFilters1 {
+    /// Filter property
+    #[zbus(property)]
+    fn filter(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_filter(&self, value: u32) -> zbus::Result<()>;
+
+    /// NightLight property
+    #[zbus(property)]
+    fn night_light(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_night_light(&self, value: bool) -> zbus::Result<()>;
+
+    /// NightLightTemperature property
+    #[zbus(property)]
+    fn night_light_temperature(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_night_light_temperature(&self, value: u32) -> zbus::Result<()>;
+}
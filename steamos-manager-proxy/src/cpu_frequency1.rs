@@ -0,0 +1,45 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.CpuFrequency1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.CpuFrequency1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait CpuFrequency1 {
+    /// AvailableFrequencies property
+    #[zbus(property)]
+    fn available_frequencies(&self) -> zbus::Result<Vec<u32>>;
+
+    /// HardwareMaxFrequency property
+    #[zbus(property)]
+    fn hardware_max_frequency(&self) -> zbus::Result<u32>;
+
+    /// HardwareMinFrequency property
+    #[zbus(property)]
+    fn hardware_min_frequency(&self) -> zbus::Result<u32>;
+
+    /// MaxFrequency property
+    #[zbus(property)]
+    fn max_frequency(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_max_frequency(&self, value: u32) -> zbus::Result<()>;
+
+    /// MinFrequency property
+    #[zbus(property)]
+    fn min_frequency(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_min_frequency(&self, value: u32) -> zbus::Result<()>;
+}
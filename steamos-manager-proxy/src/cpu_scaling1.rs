@@ -19,13 +19,44 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait CpuScaling1 {
+    /// SetCpuScalingGovernorForPolicy method
+    fn set_cpu_scaling_governor_for_policy(&self, policy: u32, governor: &str) -> zbus::Result<()>;
+
     /// AvailableCpuScalingGovernors property
     #[zbus(property)]
     fn available_cpu_scaling_governors(&self) -> zbus::Result<Vec<String>>;
 
+    /// AvailableEnergyPerformancePreferences property
+    #[zbus(property)]
+    fn available_energy_performance_preferences(&self) -> zbus::Result<Vec<String>>;
+
     /// CpuScalingGovernor property
     #[zbus(property)]
     fn cpu_scaling_governor(&self) -> zbus::Result<String>;
     #[zbus(property)]
     fn set_cpu_scaling_governor(&self, value: &str) -> zbus::Result<()>;
+
+    /// CpuScalingGovernors property
+    #[zbus(property)]
+    fn cpu_scaling_governors(&self) -> zbus::Result<std::collections::HashMap<u32, String>>;
+
+    /// EnergyPerformancePreference property
+    #[zbus(property)]
+    fn energy_performance_preference(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn set_energy_performance_preference(&self, value: &str) -> zbus::Result<()>;
+
+    /// MaxOnlineCpuCount property
+    #[zbus(property)]
+    fn max_online_cpu_count(&self) -> zbus::Result<u32>;
+
+    /// MinOnlineCpuCount property
+    #[zbus(property)]
+    fn min_online_cpu_count(&self) -> zbus::Result<u32>;
+
+    /// OnlineCpuCount property
+    #[zbus(property)]
+    fn online_cpu_count(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_online_cpu_count(&self, value: u32) -> zbus::Result<()>;
 }
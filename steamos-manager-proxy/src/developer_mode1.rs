@@ -0,0 +1,39 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.DeveloperMode1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.1.0` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.DeveloperMode1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait DeveloperMode1 {
+    /// CoreDumpsEnabled property
+    #[zbus(property)]
+    fn core_dumps_enabled(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_core_dumps_enabled(&self, value: bool) -> zbus::Result<()>;
+
+    /// PersistentJournal property
+    #[zbus(property)]
+    fn persistent_journal(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_persistent_journal(&self, value: bool) -> zbus::Result<()>;
+
+    /// SshEnabled property
+    #[zbus(property)]
+    fn ssh_enabled(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_ssh_enabled(&self, value: bool) -> zbus::Result<()>;
+}
@@ -0,0 +1,36 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.DisplayOutput1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.DisplayOutput1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait DisplayOutput1 {
+    /// ListOutputs method
+    fn list_outputs(&self) -> zbus::Result<Vec<(String, String, bool, Vec<String>)>>;
+
+    /// PreferredDockMode property
+    #[zbus(property)]
+    fn preferred_dock_mode(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn set_preferred_dock_mode(&self, value: &str) -> zbus::Result<()>;
+
+    /// RefreshRateCap property
+    #[zbus(property)]
+    fn refresh_rate_cap(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_refresh_rate_cap(&self, value: u32) -> zbus::Result<()>;
+}
@@ -0,0 +1,49 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Dock1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Dock1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Dock1 {
+    /// DockConnected signal
+    #[zbus(signal)]
+    fn dock_connected(&self, model: &str) -> zbus::Result<()>;
+
+    /// DockDisconnected signal
+    #[zbus(signal)]
+    fn dock_disconnected(&self) -> zbus::Result<()>;
+
+    /// Connected property
+    #[zbus(property)]
+    fn connected(&self) -> zbus::Result<bool>;
+
+    /// DisplayCount property
+    #[zbus(property)]
+    fn display_count(&self) -> zbus::Result<u32>;
+
+    /// FirmwareVersion property
+    #[zbus(property)]
+    fn firmware_version(&self) -> zbus::Result<String>;
+
+    /// Model property
+    #[zbus(property)]
+    fn model(&self) -> zbus::Result<String>;
+
+    /// UsbTier property
+    #[zbus(property)]
+    fn usb_tier(&self) -> zbus::Result<String>;
+}
@@ -19,6 +19,12 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait FactoryReset1 {
+    /// EstimateFactoryReset method
+    fn estimate_factory_reset(&self, kind: u32) -> zbus::Result<(Vec<String>, u64)>;
+
     /// PrepareFactoryReset method
     fn prepare_factory_reset(&self, kind: u32) -> zbus::Result<u32>;
+
+    /// StartFactoryReset method
+    fn start_factory_reset(&self, kind: u32) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
 }
@@ -0,0 +1,35 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.FirmwareInfo1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.FirmwareInfo1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait FirmwareInfo1 {
+    /// BiosVersion property
+    #[zbus(property)]
+    fn bios_version(&self) -> zbus::Result<String>;
+
+    /// ControllerFirmwareVersions property
+    #[zbus(property)]
+    fn controller_firmware_versions(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, String>>;
+
+    /// DockFirmwareVersion property
+    #[zbus(property)]
+    fn dock_firmware_version(&self) -> zbus::Result<String>;
+}
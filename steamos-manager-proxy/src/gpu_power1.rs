@@ -0,0 +1,37 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.GpuPower1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.GpuPower1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait GpuPower1 {
+    /// PowerAverage property
+    #[zbus(property)]
+    fn power_average(&self) -> zbus::Result<f64>;
+
+    /// PowerCap property
+    #[zbus(property)]
+    fn power_cap(&self) -> zbus::Result<f64>;
+
+    /// PowerCapMax property
+    #[zbus(property)]
+    fn power_cap_max(&self) -> zbus::Result<f64>;
+
+    /// PowerCapMin property
+    #[zbus(property)]
+    fn power_cap_min(&self) -> zbus::Result<f64>;
+}
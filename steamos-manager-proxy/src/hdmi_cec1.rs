@@ -19,6 +19,22 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait HdmiCec1 {
+    /// ListCecDevices method
+    fn list_cec_devices(&self) -> zbus::Result<Vec<(u32, String, String, String)>>;
+
+    /// PowerOnTv method
+    fn power_on_tv(&self) -> zbus::Result<()>;
+
+    /// SetActiveSource method
+    fn set_active_source(&self) -> zbus::Result<()>;
+
+    /// StandbyTv method
+    fn standby_tv(&self) -> zbus::Result<()>;
+
+    /// RemoteKeyPressed signal
+    #[zbus(signal)]
+    fn remote_key_pressed(&self, code: u32, key: &str) -> zbus::Result<()>;
+
     /// HdmiCecState property
     #[zbus(property)]
     fn hdmi_cec_state(&self) -> zbus::Result<u32>;
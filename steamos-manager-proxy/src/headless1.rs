@@ -0,0 +1,39 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Headless1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.1.0` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.Headless1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Headless1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Headless1 {
+    /// FanControlState property
+    #[zbus(property)]
+    fn fan_control_state(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_fan_control_state(&self, value: u32) -> zbus::Result<()>;
+
+    /// MaxChargeLevel property
+    #[zbus(property)]
+    fn max_charge_level(&self) -> zbus::Result<i32>;
+    #[zbus(property)]
+    fn set_max_charge_level(&self, value: i32) -> zbus::Result<()>;
+
+    /// TdpLimit property
+    #[zbus(property)]
+    fn tdp_limit(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_tdp_limit(&self, value: u32) -> zbus::Result<()>;
+}
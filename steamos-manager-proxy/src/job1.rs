@@ -32,4 +32,16 @@ pub trait Job1 {
 
     /// Wait method
     fn wait(&self) -> zbus::Result<i32>;
+
+    /// Progress property
+    #[zbus(property)]
+    fn progress(&self) -> zbus::Result<u32>;
+
+    /// ProgressText property
+    #[zbus(property)]
+    fn progress_text(&self) -> zbus::Result<String>;
+
+    /// Status property
+    #[zbus(property)]
+    fn status(&self) -> zbus::Result<u32>;
 }
@@ -0,0 +1,47 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.LedControl1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.LedControl1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait LedControl1 {
+    /// AvailablePatterns property
+    #[zbus(property)]
+    fn available_patterns(&self) -> zbus::Result<Vec<String>>;
+
+    /// Brightness property
+    #[zbus(property)]
+    fn brightness(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_brightness(&self, value: u32) -> zbus::Result<()>;
+
+    /// Color property
+    #[zbus(property)]
+    fn color(&self) -> zbus::Result<(u32, u32, u32)>;
+    #[zbus(property)]
+    fn set_color(&self, value: &(u32, u32, u32)) -> zbus::Result<()>;
+
+    /// MaxBrightness property
+    #[zbus(property)]
+    fn max_brightness(&self) -> zbus::Result<u32>;
+
+    /// Pattern property
+    #[zbus(property)]
+    fn pattern(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn set_pattern(&self, value: &str) -> zbus::Result<()>;
+}
@@ -14,47 +14,133 @@ mod manager;
 pub use crate::manager::ManagerProxy;
 
 // Optional interfaces
+mod accessibility1;
 mod ambient_light_sensor1;
+mod audio1;
+mod audit_log1;
+mod backlight1;
+mod battery1;
 mod battery_charge_limit1;
+mod charge_led1;
+mod color_filters1;
 mod cpu_boost1;
+mod cpu_frequency1;
 mod cpu_scaling1;
+mod developer_mode1;
+mod display_output1;
+mod dock1;
 mod factory_reset1;
 mod fan_control1;
+mod fan_curve1;
+mod firmware_info1;
+mod gpu_metrics1;
 mod gpu_performance_level1;
+mod gpu_power1;
 mod gpu_power_profile1;
+mod gpu_throttle1;
+mod haptics1;
 mod hdmi_cec1;
+mod headless1;
+mod idle_policy1;
+mod input_inhibit1;
+mod job_history1;
+mod led_control1;
 mod low_power_mode1;
 mod manager2;
+mod network_limit1;
+mod os_update1;
+mod os_update_channel1;
 mod performance_profile1;
+mod power_history1;
+mod power_presets1;
+mod power_source1;
+mod root_filesystem1;
 mod screenreader0;
+mod services1;
 mod session_management1;
+mod sleep_config1;
+mod smt1;
+mod status1;
 mod storage1;
+mod storage_health1;
+mod suspend_inhibit1;
+mod sysfs_access1;
 mod tdp_limit1;
+mod tdp_profiles1;
+mod text_input1;
+mod thermal1;
 mod update_bios1;
+mod update_controller1;
 mod update_dock1;
+mod usb_authorization1;
+mod wake_sources1;
 mod wifi_debug1;
 mod wifi_debug_dump1;
+mod wifi_info1;
 mod wifi_power_management1;
+pub use crate::accessibility1::Accessibility1Proxy;
 pub use crate::ambient_light_sensor1::AmbientLightSensor1Proxy;
+pub use crate::audio1::Audio1Proxy;
+pub use crate::audit_log1::AuditLog1Proxy;
+pub use crate::backlight1::Backlight1Proxy;
+pub use crate::battery1::Battery1Proxy;
 pub use crate::battery_charge_limit1::BatteryChargeLimit1Proxy;
+pub use crate::charge_led1::ChargeLed1Proxy;
+pub use crate::color_filters1::ColorFilters1Proxy;
 pub use crate::cpu_boost1::CpuBoost1Proxy;
+pub use crate::cpu_frequency1::CpuFrequency1Proxy;
 pub use crate::cpu_scaling1::CpuScaling1Proxy;
+pub use crate::developer_mode1::DeveloperMode1Proxy;
+pub use crate::display_output1::DisplayOutput1Proxy;
+pub use crate::dock1::Dock1Proxy;
 pub use crate::factory_reset1::FactoryReset1Proxy;
 pub use crate::fan_control1::FanControl1Proxy;
+pub use crate::fan_curve1::FanCurve1Proxy;
+pub use crate::firmware_info1::FirmwareInfo1Proxy;
+pub use crate::gpu_metrics1::GpuMetrics1Proxy;
 pub use crate::gpu_performance_level1::GpuPerformanceLevel1Proxy;
+pub use crate::gpu_power1::GpuPower1Proxy;
 pub use crate::gpu_power_profile1::GpuPowerProfile1Proxy;
+pub use crate::gpu_throttle1::GpuThrottle1Proxy;
+pub use crate::haptics1::Haptics1Proxy;
 pub use crate::hdmi_cec1::HdmiCec1Proxy;
+pub use crate::headless1::Headless1Proxy;
+pub use crate::idle_policy1::IdlePolicy1Proxy;
+pub use crate::input_inhibit1::InputInhibit1Proxy;
+pub use crate::job_history1::JobHistory1Proxy;
+pub use crate::led_control1::LedControl1Proxy;
 pub use crate::low_power_mode1::LowPowerMode1Proxy;
 pub use crate::manager2::Manager2Proxy;
+pub use crate::network_limit1::NetworkLimit1Proxy;
+pub use crate::os_update1::OsUpdate1Proxy;
+pub use crate::os_update_channel1::OsUpdateChannel1Proxy;
 pub use crate::performance_profile1::PerformanceProfile1Proxy;
+pub use crate::power_history1::PowerHistory1Proxy;
+pub use crate::power_presets1::PowerPresets1Proxy;
+pub use crate::power_source1::PowerSource1Proxy;
+pub use crate::root_filesystem1::RootFilesystem1Proxy;
 pub use crate::screenreader0::ScreenReader0Proxy;
+pub use crate::services1::Services1Proxy;
 pub use crate::session_management1::SessionManagement1Proxy;
+pub use crate::sleep_config1::SleepConfig1Proxy;
+pub use crate::smt1::Smt1Proxy;
+pub use crate::status1::Status1Proxy;
 pub use crate::storage1::Storage1Proxy;
+pub use crate::storage_health1::StorageHealth1Proxy;
+pub use crate::suspend_inhibit1::SuspendInhibit1Proxy;
+pub use crate::sysfs_access1::SysfsAccess1Proxy;
 pub use crate::tdp_limit1::TdpLimit1Proxy;
+pub use crate::tdp_profiles1::TdpProfiles1Proxy;
+pub use crate::text_input1::TextInput1Proxy;
+pub use crate::thermal1::Thermal1Proxy;
 pub use crate::update_bios1::UpdateBios1Proxy;
+pub use crate::update_controller1::UpdateController1Proxy;
 pub use crate::update_dock1::UpdateDock1Proxy;
+pub use crate::usb_authorization1::UsbAuthorization1Proxy;
+pub use crate::wake_sources1::WakeSources1Proxy;
 pub use crate::wifi_debug1::WifiDebug1Proxy;
 pub use crate::wifi_debug_dump1::WifiDebugDump1Proxy;
+pub use crate::wifi_info1::WifiInfo1Proxy;
 pub use crate::wifi_power_management1::WifiPowerManagement1Proxy;
 
 // Sub-interfaces
@@ -19,10 +19,49 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait Manager2 {
+    /// GenerateDiagnostics method
+    fn generate_diagnostics(&self) -> zbus::Result<(zbus::zvariant::OwnedObjectPath, String)>;
+
+    /// HasFeature method
+    fn has_feature(&self, name: &str) -> zbus::Result<bool>;
+
+    /// MatchDevice method
+    fn match_device(&self) -> zbus::Result<Vec<(String, String, String, bool, String)>>;
+
     /// ReloadConfig method
     fn reload_config(&self) -> zbus::Result<()>;
 
+    /// SetLogLevel method
+    fn set_log_level(&self, level: &str) -> zbus::Result<()>;
+
+    /// ValidateConfig method
+    fn validate_config(&self) -> zbus::Result<Vec<(String, bool, String)>>;
+
+    /// BackendReconnected signal
+    #[zbus(signal)]
+    fn backend_reconnected(&self) -> zbus::Result<()>;
+
+    /// ServiceRestarted signal
+    #[zbus(signal)]
+    fn service_restarted(&self, daemon: &str, service: &str, restarts: u32) -> zbus::Result<()>;
+
+    /// SysfsWriteFailed signal
+    #[zbus(signal)]
+    fn sysfs_write_failed(&self, path: &str, error: &str) -> zbus::Result<()>;
+
+    /// DeviceConfigSource property
+    #[zbus(property)]
+    fn device_config_source(&self) -> zbus::Result<Vec<String>>;
+
     /// DeviceModel property
     #[zbus(property)]
     fn device_model(&self) -> zbus::Result<(String, String)>;
+
+    /// Features property
+    #[zbus(property)]
+    fn features(&self) -> zbus::Result<Vec<String>>;
+
+    /// LogLevel property
+    #[zbus(property)]
+    fn log_level(&self) -> zbus::Result<String>;
 }
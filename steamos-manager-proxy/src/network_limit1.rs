@@ -0,0 +1,34 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.NetworkLimit1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.NetworkLimit1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait NetworkLimit1 {
+    /// LimitBandwidth method
+    fn limit_bandwidth(
+        &self,
+        identifier: &str,
+        download: u32,
+        upload: u32,
+    ) -> zbus::Result<zbus::zvariant::OwnedFd>;
+
+    /// ListBandwidthLimitHandles method
+    fn list_bandwidth_limit_handles(
+        &self,
+    ) -> zbus::Result<std::collections::HashMap<String, (u32, u32)>>;
+}
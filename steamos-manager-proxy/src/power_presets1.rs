@@ -0,0 +1,35 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.PowerPresets1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.PowerPresets1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait PowerPresets1 {
+    /// ApplyPreset method
+    fn apply_preset(&self, name: &str) -> zbus::Result<()>;
+
+    /// SavePreset method
+    fn save_preset(&self, name: &str) -> zbus::Result<()>;
+
+    /// AvailablePresets property
+    #[zbus(property)]
+    fn available_presets(&self) -> zbus::Result<Vec<String>>;
+
+    /// CurrentPreset property
+    #[zbus(property)]
+    fn current_preset(&self) -> zbus::Result<String>;
+}
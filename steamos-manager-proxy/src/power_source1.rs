@@ -0,0 +1,49 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.PowerSource1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.PowerSource1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait PowerSource1 {
+    /// ChargerChanged signal
+    #[zbus(signal)]
+    fn charger_changed(&self, voltage: u32, current: u32, power: u32) -> zbus::Result<()>;
+
+    /// PowerSourceChanged signal
+    #[zbus(signal)]
+    fn power_source_changed(&self, on_battery: bool) -> zbus::Result<()>;
+
+    /// ChargerCurrent property
+    #[zbus(property)]
+    fn charger_current(&self) -> zbus::Result<u32>;
+
+    /// ChargerPower property
+    #[zbus(property)]
+    fn charger_power(&self) -> zbus::Result<u32>;
+
+    /// ChargerType property
+    #[zbus(property)]
+    fn charger_type(&self) -> zbus::Result<String>;
+
+    /// ChargerVoltage property
+    #[zbus(property)]
+    fn charger_voltage(&self) -> zbus::Result<u32>;
+
+    /// OnBattery property
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
@@ -22,6 +22,14 @@ pub trait ScreenReader0 {
     /// TriggerAction method
     fn trigger_action(&self, action: u32, timestamp: u64) -> zbus::Result<()>;
 
+    /// SpeechStarted signal
+    #[zbus(signal)]
+    fn speech_started(&self) -> zbus::Result<()>;
+
+    /// SpeechStopped signal
+    #[zbus(signal)]
+    fn speech_stopped(&self) -> zbus::Result<()>;
+
     /// Enabled property
     #[zbus(property)]
     fn enabled(&self) -> zbus::Result<bool>;
@@ -46,12 +54,22 @@ pub trait ScreenReader0 {
     #[zbus(property)]
     fn set_rate(&self, value: f64) -> zbus::Result<()>;
 
+    /// Speaking property
+    #[zbus(property)]
+    fn speaking(&self) -> zbus::Result<bool>;
+
     /// Voice property
     #[zbus(property)]
     fn voice(&self) -> zbus::Result<String>;
     #[zbus(property)]
     fn set_voice(&self, value: &str) -> zbus::Result<()>;
 
+    /// VoiceLocale property
+    #[zbus(property)]
+    fn voice_locale(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn set_voice_locale(&self, value: &str) -> zbus::Result<()>;
+
     /// VoiceLocales property
     #[zbus(property)]
     fn voice_locales(&self) -> zbus::Result<Vec<String>>;
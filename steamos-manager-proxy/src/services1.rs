@@ -0,0 +1,40 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Services1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.1.0` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Services1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Services1 {
+    /// ListUnits method
+    fn list_units(&self) -> zbus::Result<Vec<(String, String, String)>>;
+
+    /// RestartUnit method
+    fn restart_unit(&self, unit: &str) -> zbus::Result<()>;
+
+    /// SetUnitEnabled method
+    fn set_unit_enabled(&self, unit: &str, enabled: bool) -> zbus::Result<()>;
+
+    /// StartUnit method
+    fn start_unit(&self, unit: &str) -> zbus::Result<()>;
+
+    /// StopUnit method
+    fn stop_unit(&self, unit: &str) -> zbus::Result<()>;
+
+    /// UnitStateChanged signal
+    #[zbus(signal)]
+    fn unit_state_changed(&self, unit: &str, active_state: &str) -> zbus::Result<()>;
+}
@@ -34,6 +34,18 @@ pub trait SessionManagement1 {
     /// ValidDesktopSessions method
     fn valid_desktop_sessions(&self) -> zbus::Result<Vec<String>>;
 
+    /// SessionSwitchFailed signal
+    #[zbus(signal)]
+    fn session_switch_failed(&self, login_mode: &str, reason: &str) -> zbus::Result<()>;
+
+    /// SessionSwitchStarted signal
+    #[zbus(signal)]
+    fn session_switch_started(&self, login_mode: &str) -> zbus::Result<()>;
+
+    /// AvailableDesktopSessions property
+    #[zbus(property)]
+    fn available_desktop_sessions(&self) -> zbus::Result<Vec<String>>;
+
     /// DefaultDesktopSession property
     #[zbus(property)]
     fn default_desktop_session(&self) -> zbus::Result<String>;
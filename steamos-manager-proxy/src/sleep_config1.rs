@@ -0,0 +1,53 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.SleepConfig1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.SleepConfig1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait SleepConfig1 {
+    /// HibernateDelay property
+    #[zbus(property)]
+    fn hibernate_delay(&self) -> zbus::Result<u32>;
+    #[zbus(property)]
+    fn set_hibernate_delay(&self, value: u32) -> zbus::Result<()>;
+
+    /// HibernationEnabled property
+    #[zbus(property)]
+    fn hibernation_enabled(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_hibernation_enabled(&self, value: bool) -> zbus::Result<()>;
+
+    /// SleepMode property
+    #[zbus(property)]
+    fn sleep_mode(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn set_sleep_mode(&self, value: &str) -> zbus::Result<()>;
+
+    /// SupportedSleepModes property
+    #[zbus(property)]
+    fn supported_sleep_modes(&self) -> zbus::Result<Vec<String>>;
+
+    /// SuspendThenHibernateEnabled property
+    #[zbus(property)]
+    fn suspend_then_hibernate_enabled(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_suspend_then_hibernate_enabled(&self, value: bool) -> zbus::Result<()>;
+
+    /// SwapAdequate property
+    #[zbus(property)]
+    fn swap_adequate(&self) -> zbus::Result<bool>;
+}
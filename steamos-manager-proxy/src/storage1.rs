@@ -27,6 +27,18 @@ pub trait Storage1 {
         validate: bool,
     ) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
 
+    /// FormatDevice2 method
+    fn format_device2(
+        &self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        options: std::collections::HashMap<&str, &zbus::zvariant::Value<'_>>,
+    ) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+
+    /// GetDeviceInfo method
+    fn get_device_info(&self, device: &str) -> zbus::Result<(u64, String, String, bool, String)>;
+
     /// TrimDevices method
     fn trim_devices(&self) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
 }
@@ -19,6 +19,13 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait TdpLimit1 {
+    /// SetTdpLimits method
+    fn set_tdp_limits(&self, spl: u32, sppt: u32, fppt: u32) -> zbus::Result<()>;
+
+    /// SeparateTdpLimits property
+    #[zbus(property)]
+    fn separate_tdp_limits(&self) -> zbus::Result<bool>;
+
     /// TdpLimit property
     #[zbus(property)]
     fn tdp_limit(&self) -> zbus::Result<u32>;
@@ -32,4 +39,16 @@ pub trait TdpLimit1 {
     /// TdpLimitMin property
     #[zbus(property)]
     fn tdp_limit_min(&self) -> zbus::Result<u32>;
+
+    /// TdpLimits property
+    #[zbus(property)]
+    fn tdp_limits(&self) -> zbus::Result<(u32, u32, u32)>;
+
+    /// TdpLimitsMax property
+    #[zbus(property)]
+    fn tdp_limits_max(&self) -> zbus::Result<(u32, u32, u32)>;
+
+    /// TdpLimitsMin property
+    #[zbus(property)]
+    fn tdp_limits_min(&self) -> zbus::Result<(u32, u32, u32)>;
 }
@@ -0,0 +1,33 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.TdpProfiles1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.TdpProfiles1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait TdpProfiles1 {
+    /// ClearProfileForApp method
+    fn clear_profile_for_app(&self, app_id: u32) -> zbus::Result<()>;
+
+    /// ListProfiles method
+    fn list_profiles(&self) -> zbus::Result<std::collections::HashMap<u32, u32>>;
+
+    /// SetForegroundApp method
+    fn set_foreground_app(&self, app_id: u32) -> zbus::Result<()>;
+
+    /// SetProfileForApp method
+    fn set_profile_for_app(&self, app_id: u32, limit: u32) -> zbus::Result<()>;
+}
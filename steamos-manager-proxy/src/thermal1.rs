@@ -0,0 +1,40 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.Thermal1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.Thermal1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait Thermal1 {
+    /// ListSensors method
+    fn list_sensors(&self) -> zbus::Result<Vec<(String, String, f64)>>;
+
+    /// BatteryTemperature property
+    #[zbus(property)]
+    fn battery_temperature(&self) -> zbus::Result<f64>;
+
+    /// CpuTemperature property
+    #[zbus(property)]
+    fn cpu_temperature(&self) -> zbus::Result<f64>;
+
+    /// GpuTemperature property
+    #[zbus(property)]
+    fn gpu_temperature(&self) -> zbus::Result<f64>;
+
+    /// SsdTemperature property
+    #[zbus(property)]
+    fn ssd_temperature(&self) -> zbus::Result<f64>;
+}
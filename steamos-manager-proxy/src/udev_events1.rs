@@ -19,6 +19,22 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait UdevEvents1 {
+    /// Subscribe method
+    fn subscribe(&self, subsystem: &str, tag: &str) -> zbus::Result<u32>;
+
+    /// Unsubscribe method
+    fn unsubscribe(&self, token: u32) -> zbus::Result<()>;
+
+    /// DeviceEvent signal
+    #[zbus(signal)]
+    fn device_event(
+        &self,
+        token: u32,
+        action: &str,
+        subsystem: &str,
+        devpath: &str,
+    ) -> zbus::Result<()>;
+
     /// UsbOverCurrent signal
     #[zbus(signal)]
     fn usb_over_current(&self, devpath: &str, port: &str, count: u64) -> zbus::Result<()>;
@@ -19,6 +19,16 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait UpdateBios1 {
+    /// CancelScheduledUpdateBios method
+    fn cancel_scheduled_update_bios(&self) -> zbus::Result<()>;
+
+    /// ScheduleUpdateBios method
+    fn schedule_update_bios(&self) -> zbus::Result<()>;
+
     /// UpdateBios method
     fn update_bios(&self) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+
+    /// UpdateScheduled property
+    #[zbus(property)]
+    fn update_scheduled(&self) -> zbus::Result<bool>;
 }
@@ -19,6 +19,16 @@ use zbus::proxy;
     assume_defaults = true
 )]
 pub trait UpdateDock1 {
+    /// CancelScheduledUpdateDock method
+    fn cancel_scheduled_update_dock(&self) -> zbus::Result<()>;
+
+    /// ScheduleUpdateDock method
+    fn schedule_update_dock(&self) -> zbus::Result<()>;
+
     /// UpdateDock method
     fn update_dock(&self) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+
+    /// UpdateScheduled property
+    #[zbus(property)]
+    fn update_scheduled(&self) -> zbus::Result<bool>;
 }
@@ -0,0 +1,36 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.UsbAuthorization1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.UsbAuthorization1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait UsbAuthorization1 {
+    /// AuthorizeDevice method
+    fn authorize_device(&self, device: &str) -> zbus::Result<()>;
+
+    /// DeauthorizeDevice method
+    fn deauthorize_device(&self, device: &str) -> zbus::Result<()>;
+
+    /// ListDevices method
+    fn list_devices(&self) -> zbus::Result<Vec<(String, String, String, bool)>>;
+
+    /// AuthorizedDefault property
+    #[zbus(property)]
+    fn authorized_default(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn set_authorized_default(&self, value: bool) -> zbus::Result<()>;
+}
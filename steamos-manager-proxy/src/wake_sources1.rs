@@ -0,0 +1,32 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.WakeSources1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.WakeSources1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait WakeSources1 {
+    /// SetWakeSourceEnabled method
+    fn set_wake_source_enabled(&self, source: &str, enabled: bool) -> zbus::Result<()>;
+
+    /// AvailableWakeSources property
+    #[zbus(property)]
+    fn available_wake_sources(&self) -> zbus::Result<Vec<String>>;
+
+    /// EnabledWakeSources property
+    #[zbus(property)]
+    fn enabled_wake_sources(&self) -> zbus::Result<Vec<String>>;
+}
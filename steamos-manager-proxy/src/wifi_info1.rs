@@ -0,0 +1,45 @@
+//! # D-Bus interface proxy for: `com.steampowered.SteamOSManager1.WifiInfo1`
+//!
+//! This code was generated by `zbus-xmlgen` `5.0.1` from D-Bus introspection data.
+//! Source: `com.steampowered.SteamOSManager1.xml`.
+//!
+//! You may prefer to adapt it, instead of using it verbatim.
+//!
+//! More information can be found in the [Writing a client proxy] section of the zbus
+//! documentation.
+//!
+//!
+//! [Writing a client proxy]: https://dbus2.github.io/zbus/client.html
+//! [D-Bus standard interfaces]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces,
+use zbus::proxy;
+#[proxy(
+    interface = "com.steampowered.SteamOSManager1.WifiInfo1",
+    default_service = "com.steampowered.SteamOSManager1",
+    default_path = "/com/steampowered/SteamOSManager1",
+    assume_defaults = true
+)]
+pub trait WifiInfo1 {
+    /// Bssid property
+    #[zbus(property)]
+    fn bssid(&self) -> zbus::Result<String>;
+
+    /// Frequency property
+    #[zbus(property)]
+    fn frequency(&self) -> zbus::Result<u32>;
+
+    /// RxBitrate property
+    #[zbus(property)]
+    fn rx_bitrate(&self) -> zbus::Result<f64>;
+
+    /// Signal property
+    #[zbus(property)]
+    fn signal(&self) -> zbus::Result<i32>;
+
+    /// Ssid property
+    #[zbus(property)]
+    fn ssid(&self) -> zbus::Result<String>;
+
+    /// TxBitrate property
+    #[zbus(property)]
+    fn tx_bitrate(&self) -> zbus::Result<f64>;
+}
@@ -0,0 +1,90 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+#[cfg(not(test))]
+use anyhow::anyhow;
+use anyhow::{ensure, Result};
+use gio::{prelude::SettingsExt, Settings};
+use std::ops::RangeInclusive;
+
+const A11Y_INTERFACE_SETTING: &str = "org.gnome.desktop.a11y.interface";
+const HIGH_CONTRAST_SETTING: &str = "high-contrast";
+const INTERFACE_SETTING: &str = "org.gnome.desktop.interface";
+const TEXT_SCALING_FACTOR_SETTING: &str = "text-scaling-factor";
+
+/// The range allowed by the gsettings schema
+const TEXT_SCALING_FACTOR_RANGE: RangeInclusive<f64> = 0.5..=3.0;
+
+pub(crate) struct AccessibilityManager {
+    high_contrast: bool,
+    text_scaling_factor: f64,
+}
+
+impl AccessibilityManager {
+    pub fn new() -> AccessibilityManager {
+        AccessibilityManager {
+            high_contrast: Settings::new(A11Y_INTERFACE_SETTING).boolean(HIGH_CONTRAST_SETTING),
+            text_scaling_factor: Settings::new(INTERFACE_SETTING)
+                .double(TEXT_SCALING_FACTOR_SETTING),
+        }
+    }
+
+    pub fn high_contrast(&self) -> bool {
+        self.high_contrast
+    }
+
+    pub fn set_high_contrast(&mut self, enabled: bool) -> Result<()> {
+        #[cfg(not(test))]
+        Settings::new(A11Y_INTERFACE_SETTING)
+            .set_boolean(HIGH_CONTRAST_SETTING, enabled)
+            .map_err(|e| anyhow!("Unable to set high contrast gsetting, {e}"))?;
+        self.high_contrast = enabled;
+        Ok(())
+    }
+
+    pub fn text_scaling_factor(&self) -> f64 {
+        self.text_scaling_factor
+    }
+
+    pub fn set_text_scaling_factor(&mut self, factor: f64) -> Result<()> {
+        ensure!(
+            TEXT_SCALING_FACTOR_RANGE.contains(&factor),
+            "Text scaling factor {factor} out of range"
+        );
+        #[cfg(not(test))]
+        Settings::new(INTERFACE_SETTING)
+            .set_double(TEXT_SCALING_FACTOR_SETTING, factor)
+            .map_err(|e| anyhow!("Unable to set text scaling factor gsetting, {e}"))?;
+        self.text_scaling_factor = factor;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn text_scaling_factor() {
+        let mut manager = AccessibilityManager::new();
+        manager.set_text_scaling_factor(1.5).unwrap();
+        assert_eq!(manager.text_scaling_factor(), 1.5);
+
+        assert!(manager.set_text_scaling_factor(0.25).is_err());
+        assert!(manager.set_text_scaling_factor(4.0).is_err());
+        assert!(manager.set_text_scaling_factor(f64::NAN).is_err());
+        assert_eq!(manager.text_scaling_factor(), 1.5);
+    }
+
+    #[test]
+    fn high_contrast() {
+        let mut manager = AccessibilityManager::new();
+        manager.set_high_contrast(true).unwrap();
+        assert!(manager.high_contrast());
+        manager.set_high_contrast(false).unwrap();
+        assert!(!manager.high_contrast());
+    }
+}
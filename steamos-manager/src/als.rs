@@ -0,0 +1,321 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{read_dir, read_to_string, try_exists};
+use tokio::time::sleep;
+use tracing::{debug, warn};
+use zbus::Connection;
+
+use crate::manager::user::{AmbientLightSensor1, MANAGER_PATH};
+use crate::policy::Guarded;
+use crate::{path, Service};
+
+const IIO_PREFIX: &str = "/sys/bus/iio/devices";
+const ILLUMINANCE_PREFIX: &str = "in_illuminance";
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct AlsMonitorConfig {
+    /// Polling interval in milliseconds
+    pub poll_interval: u64,
+    /// Longest polling interval in milliseconds, which polling backs off to while the
+    /// illuminance doesn't change
+    pub idle_poll_interval: u64,
+    /// Minimum relative change in illuminance that is reported
+    pub threshold: f64,
+    /// Number of consecutive readings past the threshold needed to report a change
+    pub settle_samples: u32,
+}
+
+impl Default for AlsMonitorConfig {
+    fn default() -> AlsMonitorConfig {
+        AlsMonitorConfig {
+            poll_interval: 200,
+            idle_poll_interval: 2000,
+            threshold: 0.05,
+            settle_samples: 2,
+        }
+    }
+}
+
+pub(crate) struct AlsMonitor {
+    session: Connection,
+    sensor: PathBuf,
+    config: AlsMonitorConfig,
+    lux: Option<f64>,
+    pending: u32,
+    delay: Duration,
+}
+
+async fn read_attribute(base: &Path, attribute: &str) -> Result<f64> {
+    read_to_string(base.join(format!("{ILLUMINANCE_PREFIX}_{attribute}")))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) async fn find_als() -> Result<PathBuf> {
+    let mut dir = read_dir(path(IIO_PREFIX)).await?;
+    let mut sensors = Vec::new();
+    while let Some(entry) = dir.next_entry().await? {
+        let base = entry.path();
+        for attribute in ["input", "raw"] {
+            if try_exists(base.join(format!("{ILLUMINANCE_PREFIX}_{attribute}"))).await? {
+                sensors.push(base);
+                break;
+            }
+        }
+    }
+    sensors.sort();
+    match sensors.into_iter().next() {
+        Some(sensor) => Ok(sensor),
+        None => bail!("No ambient light sensor found"),
+    }
+}
+
+pub(crate) async fn read_lux(sensor: &Path) -> Result<f64> {
+    // Processed values are already in lux, raw values need to be converted
+    if let Ok(lux) = read_attribute(sensor, "input").await {
+        return Ok(lux);
+    }
+    let raw = read_attribute(sensor, "raw").await?;
+    let offset = read_attribute(sensor, "offset").await.unwrap_or(0.0);
+    let scale = read_attribute(sensor, "scale").await.unwrap_or(1.0);
+    Ok((raw + offset) * scale)
+}
+
+impl AlsMonitor {
+    pub(crate) async fn init(
+        session: &Connection,
+        config: &AlsMonitorConfig,
+    ) -> Result<AlsMonitor> {
+        Ok(AlsMonitor {
+            session: session.clone(),
+            sensor: find_als().await?,
+            config: config.clone(),
+            lux: None,
+            pending: 0,
+            delay: Duration::from_millis(config.poll_interval.max(10)),
+        })
+    }
+
+    fn significant(&self, lux: f64) -> bool {
+        let Some(last) = self.lux else {
+            return true;
+        };
+        // Compare against at least 1 lux so that near-dark noise isn't reported
+        (lux - last).abs() > self.config.threshold * last.abs().max(1.0)
+    }
+
+    async fn poll(&mut self) -> Result<bool> {
+        let lux = read_lux(self.sensor.as_path()).await?;
+        if !self.significant(lux) {
+            self.pending = 0;
+            return Ok(false);
+        }
+        self.pending += 1;
+        if self.lux.is_some() && self.pending < self.config.settle_samples {
+            return Ok(false);
+        }
+        debug!("Ambient light changed to {lux} lux");
+        self.lux = Some(lux);
+        self.pending = 0;
+        Ok(true)
+    }
+
+    /// Poll quickly while the illuminance is changing and back off while it isn't
+    fn update_delay(&mut self) {
+        let min = Duration::from_millis(self.config.poll_interval.max(10));
+        let max = Duration::from_millis(self.config.idle_poll_interval).max(min);
+        self.delay = if self.pending > 0 {
+            min
+        } else {
+            (self.delay * 2).min(max)
+        };
+    }
+}
+
+impl Service for AlsMonitor {
+    const NAME: &'static str = "als-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let als = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<AmbientLightSensor1>>(MANAGER_PATH)
+            .await?;
+        let ctx = als.signal_emitter();
+
+        loop {
+            match self.poll().await {
+                Ok(true) => {
+                    self.delay = Duration::from_millis(self.config.poll_interval.max(10));
+                    if let Err(e) = als.get_mut().await.set_lux(self.lux, ctx).await {
+                        warn!("Failed to report ambient light change: {e}");
+                    }
+                }
+                Ok(false) => self.update_delay(),
+                Err(e) => {
+                    warn!("Failed to read ambient light sensor: {e}");
+                    self.update_delay();
+                }
+            }
+            sleep(self.delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, remove_file, write};
+
+    #[tokio::test]
+    async fn read_sensor() {
+        let _h = testing::start();
+
+        create_dir_all(path(IIO_PREFIX))
+            .await
+            .expect("create_dir_all");
+        assert!(find_als().await.is_err());
+
+        let accel = path(IIO_PREFIX).join("iio:device0");
+        create_dir_all(&accel).await.expect("create_dir_all");
+        write(accel.join("in_accel_x_raw"), "12\n")
+            .await
+            .expect("write");
+        assert!(find_als().await.is_err());
+
+        let base = path(IIO_PREFIX).join("iio:device2");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("in_illuminance_raw"), "250\n")
+            .await
+            .expect("write");
+        assert_eq!(find_als().await.unwrap(), base);
+        assert_eq!(read_lux(base.as_path()).await.unwrap(), 250.0);
+
+        write(base.join("in_illuminance_scale"), "0.5\n")
+            .await
+            .expect("write");
+        write(base.join("in_illuminance_offset"), "10\n")
+            .await
+            .expect("write");
+        assert_eq!(read_lux(base.as_path()).await.unwrap(), 130.0);
+
+        write(base.join("in_illuminance_input"), "42.5\n")
+            .await
+            .expect("write");
+        assert_eq!(read_lux(base.as_path()).await.unwrap(), 42.5);
+
+        remove_file(base.join("in_illuminance_raw"))
+            .await
+            .expect("remove_file");
+        assert_eq!(find_als().await.unwrap(), base);
+    }
+
+    #[tokio::test]
+    async fn debounce() {
+        let mut h = testing::start();
+
+        let base = path(IIO_PREFIX).join("iio:device0");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("in_illuminance_input"), "100\n")
+            .await
+            .expect("write");
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let config = AlsMonitorConfig {
+            poll_interval: 10,
+            idle_poll_interval: 40,
+            threshold: 0.1,
+            settle_samples: 2,
+        };
+        let mut monitor = AlsMonitor::init(&connection, &config).await.expect("init");
+
+        // The first reading is always reported
+        assert!(monitor.poll().await.unwrap());
+        assert!(!monitor.poll().await.unwrap());
+
+        // Small changes are ignored
+        write(base.join("in_illuminance_input"), "105\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        assert!(!monitor.poll().await.unwrap());
+
+        // A single spike is ignored
+        write(base.join("in_illuminance_input"), "300\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        write(base.join("in_illuminance_input"), "100\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+
+        // A sustained change is reported once
+        write(base.join("in_illuminance_input"), "300\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        assert!(monitor.poll().await.unwrap());
+        assert!(!monitor.poll().await.unwrap());
+        assert_eq!(monitor.lux, Some(300.0));
+
+        // Near darkness, changes are compared against 1 lux
+        write(base.join("in_illuminance_input"), "0\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        assert!(monitor.poll().await.unwrap());
+        write(base.join("in_illuminance_input"), "0.05\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        assert!(!monitor.poll().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn backoff() {
+        let mut h = testing::start();
+
+        let base = path(IIO_PREFIX).join("iio:device0");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("in_illuminance_input"), "100\n")
+            .await
+            .expect("write");
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let config = AlsMonitorConfig {
+            poll_interval: 10,
+            idle_poll_interval: 40,
+            threshold: 0.1,
+            settle_samples: 2,
+        };
+        let mut monitor = AlsMonitor::init(&connection, &config).await.expect("init");
+        assert!(monitor.poll().await.unwrap());
+
+        for expected in [20, 40, 40] {
+            assert!(!monitor.poll().await.unwrap());
+            monitor.update_delay();
+            assert_eq!(monitor.delay, Duration::from_millis(expected));
+        }
+
+        write(base.join("in_illuminance_input"), "300\n")
+            .await
+            .expect("write");
+        assert!(!monitor.poll().await.unwrap());
+        monitor.update_delay();
+        assert_eq!(monitor.delay, Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,677 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, ensure, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use tokio::fs::{read_dir, read_to_string};
+
+use crate::hardware::{device_config, SpeakerProtectionConfig};
+use crate::path;
+use crate::process::{run_script, script_output};
+
+const PW_DUMP_PATH: &str = "/usr/bin/pw-dump";
+const WPCTL_PATH: &str = "/usr/bin/wpctl";
+
+const NODE_TYPE: &str = "PipeWire:Interface:Node";
+const DEVICE_TYPE: &str = "PipeWire:Interface:Device";
+const METADATA_TYPE: &str = "PipeWire:Interface:Metadata";
+
+const AUDIO_SINK_CLASS: &str = "Audio/Sink";
+const AUDIO_DEVICE_CLASS: &str = "Audio/Device";
+
+const DEFAULT_AUDIO_SINK: &str = "@DEFAULT_AUDIO_SINK@";
+const DEFAULT_AUDIO_SOURCE: &str = "@DEFAULT_AUDIO_SOURCE@";
+
+// The kernel drives these LEDs from the state of the codec's capture switch, so they follow the
+// hardware mute key even when nothing in userspace handles it
+const LEDS_PREFIX: &str = "/sys/class/leds";
+const MICMUTE_LED_SUFFIX: &str = "::micmute";
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct AudioOutput {
+    /// PipeWire node ID of the sink
+    pub id: u32,
+    /// PipeWire ID of the device the sink belongs to, or 0 if it doesn't belong to one
+    pub device: u32,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct AudioProfile {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct AudioDevice {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    pub active_profile: Option<u32>,
+    pub profiles: Vec<AudioProfile>,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct AudioGraph {
+    pub outputs: Vec<AudioOutput>,
+    pub devices: Vec<AudioDevice>,
+    pub default_output: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct PwObject {
+    id: u32,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    info: Option<PwInfo>,
+    #[serde(default)]
+    props: HashMap<String, Value>,
+    #[serde(default)]
+    metadata: Option<Vec<PwMetadata>>,
+}
+
+#[derive(Deserialize)]
+struct PwInfo {
+    #[serde(default)]
+    props: HashMap<String, Value>,
+    #[serde(default)]
+    params: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct PwMetadata {
+    subject: u32,
+    key: String,
+    #[serde(default)]
+    value: Value,
+}
+
+#[derive(Deserialize)]
+struct PwProfile {
+    index: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    available: String,
+}
+
+fn prop_string(props: &HashMap<String, Value>, key: &str) -> String {
+    props
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Numeric properties show up as either numbers or strings depending on who set them
+fn prop_u32(props: &HashMap<String, Value>, key: &str) -> Option<u32> {
+    match props.get(key)? {
+        Value::Number(number) => number.as_u64()?.try_into().ok(),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_profiles(params: &HashMap<String, Value>, key: &str) -> Vec<PwProfile> {
+    params
+        .get(key)
+        .and_then(|profiles| serde_json::from_value(profiles.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Parse the JSON that `pw-dump` prints into the audio outputs and devices it describes
+pub(crate) fn parse_audio_graph(output: &str) -> Result<AudioGraph> {
+    let objects: Vec<PwObject> = serde_json::from_str(output)?;
+    let mut graph = AudioGraph::default();
+    let mut default_sink = None;
+
+    for object in objects {
+        match object.kind.as_str() {
+            NODE_TYPE => {
+                let Some(info) = object.info else {
+                    continue;
+                };
+                if prop_string(&info.props, "media.class") != AUDIO_SINK_CLASS {
+                    continue;
+                }
+                graph.outputs.push(AudioOutput {
+                    id: object.id,
+                    device: prop_u32(&info.props, "device.id").unwrap_or_default(),
+                    name: prop_string(&info.props, "node.name"),
+                    description: prop_string(&info.props, "node.description"),
+                });
+            }
+            DEVICE_TYPE => {
+                let Some(info) = object.info else {
+                    continue;
+                };
+                if prop_string(&info.props, "media.class") != AUDIO_DEVICE_CLASS {
+                    continue;
+                }
+                graph.devices.push(AudioDevice {
+                    id: object.id,
+                    name: prop_string(&info.props, "device.name"),
+                    description: prop_string(&info.props, "device.description"),
+                    active_profile: parse_profiles(&info.params, "Profile")
+                        .first()
+                        .map(|profile| profile.index),
+                    profiles: parse_profiles(&info.params, "EnumProfile")
+                        .into_iter()
+                        .map(|profile| AudioProfile {
+                            index: profile.index,
+                            name: profile.name,
+                            description: profile.description,
+                            available: profile.available != "no",
+                        })
+                        .collect(),
+                });
+            }
+            METADATA_TYPE if prop_string(&object.props, "metadata.name") == "default" => {
+                // The default sink is referred to by node name rather than ID
+                default_sink = object
+                    .metadata
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|entry| entry.subject == 0 && entry.key == "default.audio.sink")
+                    .and_then(|entry| {
+                        entry
+                            .value
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .map(String::from)
+                    });
+            }
+            _ => (),
+        }
+    }
+
+    graph.default_output = default_sink.and_then(|name| {
+        graph
+            .outputs
+            .iter()
+            .find(|output| output.name == name)
+            .map(|output| output.id)
+    });
+    Ok(graph)
+}
+
+pub(crate) async fn audio_graph() -> Result<AudioGraph> {
+    let output = script_output(PW_DUMP_PATH, &["--no-colors"]).await?;
+    parse_audio_graph(output.as_str())
+}
+
+pub(crate) async fn set_default_audio_output(id: u32) -> Result<()> {
+    let graph = audio_graph().await?;
+    ensure!(
+        graph.outputs.iter().any(|output| output.id == id),
+        "No audio output with ID {id}"
+    );
+    run_script(WPCTL_PATH, &["set-default", id.to_string().as_str()]).await
+}
+
+pub(crate) async fn set_audio_device_profile(device: u32, profile: u32) -> Result<()> {
+    let graph = audio_graph().await?;
+    let device_info = graph
+        .devices
+        .iter()
+        .find(|info| info.id == device)
+        .ok_or_else(|| anyhow!("No audio device with ID {device}"))?;
+    ensure!(
+        device_info
+            .profiles
+            .iter()
+            .any(|info| info.index == profile),
+        "Audio device {device} has no profile {profile}"
+    );
+    run_script(
+        WPCTL_PATH,
+        &[
+            "set-profile",
+            device.to_string().as_str(),
+            profile.to_string().as_str(),
+        ],
+    )
+    .await
+}
+
+/// Parse the output of `wpctl get-volume`, e.g. `Volume: 0.40 [MUTED]`, into the volume and
+/// whether the node is muted
+pub(crate) fn parse_volume(output: &str) -> Result<(f64, bool)> {
+    let mut words = output
+        .trim()
+        .strip_prefix("Volume:")
+        .ok_or_else(|| anyhow!("Unexpected wpctl output: {output}"))?
+        .split_whitespace();
+    let volume = words
+        .next()
+        .ok_or_else(|| anyhow!("No volume in wpctl output"))?
+        .parse()?;
+    let muted = words.any(|word| word == "[MUTED]");
+    Ok((volume, muted))
+}
+
+async fn volume_state(node: &str) -> Result<(f64, bool)> {
+    let output = script_output(WPCTL_PATH, &["get-volume", node]).await?;
+    parse_volume(output.as_str())
+}
+
+async fn set_volume(node: &str, volume: f64) -> Result<()> {
+    ensure!(
+        (0.0..=1.0).contains(&volume),
+        "Volume {volume} is out of range"
+    );
+    run_script(
+        WPCTL_PATH,
+        &["set-volume", node, format!("{volume:.2}").as_str()],
+    )
+    .await
+}
+
+pub(crate) async fn get_audio_output_volume() -> Result<f64> {
+    Ok(volume_state(DEFAULT_AUDIO_SINK).await?.0)
+}
+
+pub(crate) async fn set_audio_output_volume(volume: f64) -> Result<()> {
+    set_volume(DEFAULT_AUDIO_SINK, volume).await
+}
+
+pub(crate) async fn get_audio_input_volume() -> Result<f64> {
+    Ok(volume_state(DEFAULT_AUDIO_SOURCE).await?.0)
+}
+
+pub(crate) async fn set_audio_input_volume(volume: f64) -> Result<()> {
+    set_volume(DEFAULT_AUDIO_SOURCE, volume).await
+}
+
+pub(crate) async fn get_audio_input_muted() -> Result<bool> {
+    Ok(volume_state(DEFAULT_AUDIO_SOURCE).await?.1)
+}
+
+pub(crate) async fn set_audio_input_muted(muted: bool) -> Result<()> {
+    let muted = if muted { "1" } else { "0" };
+    run_script(WPCTL_PATH, &["set-mute", DEFAULT_AUDIO_SOURCE, muted]).await
+}
+
+async fn speaker_protection_config() -> Result<Option<SpeakerProtectionConfig>> {
+    let config = device_config().await?;
+    Ok(config
+        .as_ref()
+        .and_then(|config| config.speaker_protection.clone()))
+}
+
+/// The highest volume the built-in speakers can be set to, which is 1.0 unless the device
+/// configures a lower one
+pub(crate) async fn get_max_speaker_volume() -> Result<f64> {
+    Ok(speaker_protection_config()
+        .await?
+        .map_or(1.0, |config| config.max_volume.clamp(0.0, 1.0)))
+}
+
+/// The highest volume the default output can be set to, which is only limited when the default
+/// output is the built-in speakers
+pub(crate) async fn output_volume_limit() -> Result<Option<f64>> {
+    let Some(config) = speaker_protection_config().await? else {
+        return Ok(None);
+    };
+    let graph = audio_graph().await?;
+    let speakers = graph
+        .default_output
+        .and_then(|id| graph.outputs.iter().find(|output| output.id == id))
+        .is_some_and(|output| output.name == config.sink);
+    Ok(speakers.then_some(config.max_volume.clamp(0.0, 1.0)))
+}
+
+/// Turn the default output down to the speaker volume limit if it's currently above it
+pub(crate) async fn enforce_speaker_volume_limit() -> Result<()> {
+    let Some(limit) = output_volume_limit().await? else {
+        return Ok(());
+    };
+    if get_audio_output_volume().await? > limit {
+        set_audio_output_volume(limit).await?;
+    }
+    Ok(())
+}
+
+/// Whether the microphone is muted in hardware, as shown by the mic mute LED. Devices without
+/// one are never considered muted.
+pub(crate) async fn get_hardware_input_muted() -> Result<bool> {
+    let mut dir = match read_dir(path(LEDS_PREFIX)).await {
+        Ok(dir) => dir,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    while let Some(entry) = dir.next_entry().await? {
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .ends_with(MICMUTE_LED_SUFFIX)
+        {
+            continue;
+        }
+        let brightness = read_to_string(entry.path().join("brightness")).await?;
+        if brightness.trim().parse::<u32>()? > 0 {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::hardware::DeviceConfig;
+    use crate::testing;
+    use anyhow::bail;
+    use std::ffi::OsStr;
+    use tokio::fs::{create_dir_all, write};
+
+    pub(crate) const PW_DUMP: &str = r#"[
+        {
+            "id": 0,
+            "type": "PipeWire:Interface:Core",
+            "info": { "props": { "core.name": "pipewire-0" } }
+        },
+        {
+            "id": 45,
+            "type": "PipeWire:Interface:Device",
+            "info": {
+                "props": {
+                    "device.name": "alsa_card.pci-0000_04_00.5-platform-acp5x_mach.0",
+                    "device.description": "Speakers",
+                    "media.class": "Audio/Device"
+                },
+                "params": {
+                    "EnumProfile": [
+                        { "index": 0, "name": "off", "description": "Off", "available": "yes" },
+                        { "index": 1, "name": "HiFi", "description": "Play HiFi quality Music", "available": "yes" }
+                    ],
+                    "Profile": [
+                        { "index": 1, "name": "HiFi", "description": "Play HiFi quality Music" }
+                    ]
+                }
+            }
+        },
+        {
+            "id": 46,
+            "type": "PipeWire:Interface:Device",
+            "info": {
+                "props": {
+                    "device.name": "alsa_card.pci-0000_04_00.1",
+                    "device.description": "Rembrandt Radeon High Definition Audio Controller",
+                    "media.class": "Audio/Device"
+                },
+                "params": {
+                    "EnumProfile": [
+                        { "index": 0, "name": "off", "description": "Off", "available": "yes" },
+                        { "index": 3, "name": "output:hdmi-stereo", "description": "Digital Stereo (HDMI) Output", "available": "no" }
+                    ],
+                    "Profile": [
+                        { "index": 0, "name": "off", "description": "Off" }
+                    ]
+                }
+            }
+        },
+        {
+            "id": 52,
+            "type": "PipeWire:Interface:Node",
+            "info": {
+                "props": {
+                    "device.id": 45,
+                    "media.class": "Audio/Sink",
+                    "node.name": "alsa_output.pci-0000_04_00.5-platform-acp5x_mach.0.HiFi__hw_acp5x_1__sink",
+                    "node.description": "Speakers"
+                }
+            }
+        },
+        {
+            "id": 53,
+            "type": "PipeWire:Interface:Node",
+            "info": {
+                "props": {
+                    "device.id": "45",
+                    "media.class": "Audio/Source",
+                    "node.name": "alsa_input.pci-0000_04_00.5-platform-acp5x_mach.0.HiFi__hw_acp5x_0__source",
+                    "node.description": "Microphone"
+                }
+            }
+        },
+        {
+            "id": 60,
+            "type": "PipeWire:Interface:Node",
+            "info": {
+                "props": {
+                    "media.class": "Audio/Sink",
+                    "node.name": "virtual-sink",
+                    "node.description": "Virtual Sink"
+                }
+            }
+        },
+        {
+            "id": 31,
+            "type": "PipeWire:Interface:Metadata",
+            "props": { "metadata.name": "default" },
+            "metadata": [
+                {
+                    "subject": 0,
+                    "key": "default.configured.audio.sink",
+                    "type": "Spa:String:JSON",
+                    "value": { "name": "virtual-sink" }
+                },
+                {
+                    "subject": 0,
+                    "key": "default.audio.sink",
+                    "type": "Spa:String:JSON",
+                    "value": { "name": "alsa_output.pci-0000_04_00.5-platform-acp5x_mach.0.HiFi__hw_acp5x_1__sink" }
+                }
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn audio_graph_parsing() {
+        assert!(parse_audio_graph("not json").is_err());
+        assert_eq!(parse_audio_graph("[]").unwrap(), AudioGraph::default());
+
+        let graph = parse_audio_graph(PW_DUMP).unwrap();
+        assert_eq!(
+            graph.outputs,
+            vec![
+                AudioOutput {
+                    id: 52,
+                    device: 45,
+                    name: String::from(
+                        "alsa_output.pci-0000_04_00.5-platform-acp5x_mach.0.HiFi__hw_acp5x_1__sink"
+                    ),
+                    description: String::from("Speakers"),
+                },
+                AudioOutput {
+                    id: 60,
+                    device: 0,
+                    name: String::from("virtual-sink"),
+                    description: String::from("Virtual Sink"),
+                },
+            ]
+        );
+        assert_eq!(graph.default_output, Some(52));
+        assert_eq!(graph.devices.len(), 2);
+        assert_eq!(graph.devices[0].id, 45);
+        assert_eq!(graph.devices[0].description, "Speakers");
+        assert_eq!(graph.devices[0].active_profile, Some(1));
+        assert_eq!(graph.devices[0].profiles.len(), 2);
+        assert_eq!(graph.devices[1].active_profile, Some(0));
+        assert_eq!(
+            graph.devices[1].profiles[1],
+            AudioProfile {
+                index: 3,
+                name: String::from("output:hdmi-stereo"),
+                description: String::from("Digital Stereo (HDMI) Output"),
+                available: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn switch_output_and_profile() {
+        let h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            if executable == PW_DUMP_PATH {
+                return Ok((0, String::from(PW_DUMP)));
+            }
+            ensure!(executable == WPCTL_PATH, "Not wpctl");
+            match args {
+                [cmd, id] if *cmd == "set-default" && *id == "60" => Ok((0, String::new())),
+                [cmd, device, profile]
+                    if *cmd == "set-profile" && *device == "46" && *profile == "3" =>
+                {
+                    Ok((0, String::new()))
+                }
+                _ => bail!("Unexpected wpctl command"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        assert_eq!(audio_graph().await.unwrap().default_output, Some(52));
+
+        set_default_audio_output(60)
+            .await
+            .expect("set_default_audio_output");
+        assert!(set_default_audio_output(53).await.is_err());
+        set_audio_device_profile(46, 3)
+            .await
+            .expect("set_audio_device_profile");
+        assert!(set_audio_device_profile(46, 1).await.is_err());
+        assert!(set_audio_device_profile(52, 0).await.is_err());
+    }
+
+    #[test]
+    fn volume_parsing() {
+        assert_eq!(parse_volume("Volume: 0.40\n").unwrap(), (0.4, false));
+        assert_eq!(parse_volume("Volume: 1.00 [MUTED]\n").unwrap(), (1.0, true));
+        assert!(parse_volume("").is_err());
+        assert!(parse_volume("Volume: loud").is_err());
+        assert!(parse_volume("Translate ID error: '@DEFAULT_AUDIO_SOURCE@'").is_err());
+    }
+
+    #[tokio::test]
+    async fn input_mute_and_volume() {
+        let h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            ensure!(executable == WPCTL_PATH, "Not wpctl");
+            ensure!(
+                args.get(1) == Some(&OsStr::new(DEFAULT_AUDIO_SOURCE)),
+                "Not the default source"
+            );
+            match (args[0].to_str(), args.get(2).and_then(|arg| arg.to_str())) {
+                (Some("get-volume"), None) => Ok((0, String::from("Volume: 0.65 [MUTED]\n"))),
+                (Some("set-mute"), Some("0" | "1")) => Ok((0, String::new())),
+                (Some("set-volume"), Some("0.50")) => Ok((0, String::new())),
+                _ => bail!("Unexpected wpctl command"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        assert_eq!(get_audio_input_volume().await.unwrap(), 0.65);
+        assert!(get_audio_input_muted().await.unwrap());
+        set_audio_input_muted(false)
+            .await
+            .expect("set_audio_input_muted");
+        set_audio_input_muted(true)
+            .await
+            .expect("set_audio_input_muted");
+        set_audio_input_volume(0.5)
+            .await
+            .expect("set_audio_input_volume");
+        assert!(set_audio_input_volume(1.5).await.is_err());
+        assert!(set_audio_input_volume(-0.1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn hardware_input_mute() {
+        let _h = testing::start();
+
+        assert!(!get_hardware_input_muted().await.unwrap());
+
+        let capslock = path(LEDS_PREFIX).join("input3::capslock");
+        let micmute = path(LEDS_PREFIX).join("platform::micmute");
+        create_dir_all(&capslock).await.expect("create_dir_all");
+        create_dir_all(&micmute).await.expect("create_dir_all");
+        write(capslock.join("brightness"), "1\n")
+            .await
+            .expect("write");
+        write(micmute.join("brightness"), "0\n")
+            .await
+            .expect("write");
+        assert!(!get_hardware_input_muted().await.unwrap());
+
+        write(micmute.join("brightness"), "1\n")
+            .await
+            .expect("write");
+        assert!(get_hardware_input_muted().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn speaker_volume_limit() {
+        let h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            if executable == PW_DUMP_PATH {
+                return Ok((0, String::from(PW_DUMP)));
+            }
+            ensure!(executable == WPCTL_PATH, "Not wpctl");
+            ensure!(
+                args.get(1) == Some(&OsStr::new(DEFAULT_AUDIO_SINK)),
+                "Not the default sink"
+            );
+            match (args[0].to_str(), args.get(2).and_then(|arg| arg.to_str())) {
+                (Some("get-volume"), None) => Ok((0, String::from("Volume: 0.90\n"))),
+                (Some("set-volume"), Some("0.70")) => Ok((0, String::new())),
+                _ => bail!("Unexpected wpctl command"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        assert_eq!(get_max_speaker_volume().await.unwrap(), 1.0);
+        assert_eq!(output_volume_limit().await.unwrap(), None);
+        enforce_speaker_volume_limit()
+            .await
+            .expect("enforce_speaker_volume_limit");
+
+        h.test.device_config.replace(Some(DeviceConfig {
+            speaker_protection: Some(SpeakerProtectionConfig {
+                sink: String::from("virtual-sink"),
+                max_volume: 0.7,
+            }),
+            ..DeviceConfig::default()
+        }));
+        assert_eq!(get_max_speaker_volume().await.unwrap(), 0.7);
+        assert_eq!(output_volume_limit().await.unwrap(), None);
+
+        h.test.device_config.replace(Some(DeviceConfig {
+            speaker_protection: Some(SpeakerProtectionConfig {
+                sink: String::from(
+                    "alsa_output.pci-0000_04_00.5-platform-acp5x_mach.0.HiFi__hw_acp5x_1__sink",
+                ),
+                max_volume: 0.7,
+            }),
+            ..DeviceConfig::default()
+        }));
+        assert_eq!(output_volume_limit().await.unwrap(), Some(0.7));
+        enforce_speaker_volume_limit()
+            .await
+            .expect("enforce_speaker_volume_limit");
+    }
+}
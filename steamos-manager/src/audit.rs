@@ -0,0 +1,279 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{create_dir_all, metadata, read_to_string, rename, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::warn;
+use zbus::message::Header;
+use zbus::{fdo, Connection};
+
+use crate::caller::identify_caller;
+use crate::error::zbus_to_zbus_fdo;
+use crate::path;
+
+const AUDIT_LOG_PATH: &str = "/var/log/steamos-manager/audit.log";
+const ROTATED_AUDIT_LOG_PATH: &str = "/var/log/steamos-manager/audit.log.1";
+
+// Once the log grows past this it is moved aside, replacing the previously rotated log
+const MAX_AUDIT_LOG_SIZE: u64 = 1024 * 1024;
+
+pub(crate) const RECENT_AUDIT_ENTRIES: usize = 100;
+
+// Used when the bus can't tell us who the caller is
+const UNKNOWN_UID: u32 = u32::MAX;
+
+static AUDIT_LOG_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub(crate) struct AuditEntry {
+    pub timestamp: u64,
+    pub uid: u32,
+    /// The calling process, when the bus could tell which one it was
+    #[serde(default)]
+    pub pid: Option<u32>,
+    #[serde(default)]
+    pub exe: Option<PathBuf>,
+    pub sender: String,
+    pub method: String,
+    pub arguments: Vec<String>,
+    pub result: String,
+}
+
+impl AuditEntry {
+    fn new<T, E: Display>(
+        uid: u32,
+        sender: String,
+        method: &str,
+        arguments: Vec<String>,
+        result: &Result<T, E>,
+    ) -> AuditEntry {
+        AuditEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            uid,
+            pid: None,
+            exe: None,
+            sender,
+            method: method.to_string(),
+            arguments,
+            result: match result {
+                Ok(_) => String::from("success"),
+                Err(e) => e.to_string(),
+            },
+        }
+    }
+}
+
+async fn caller_uid(connection: &Connection, header: &Header<'_>) -> fdo::Result<u32> {
+    let Some(sender) = header.sender() else {
+        return Ok(UNKNOWN_UID);
+    };
+    fdo::DBusProxy::new(connection)
+        .await
+        .map_err(zbus_to_zbus_fdo)?
+        .get_connection_unix_user(sender.clone().into())
+        .await
+}
+
+async fn append_entry(entry: &AuditEntry) -> Result<()> {
+    let _guard = AUDIT_LOG_LOCK.lock().await;
+    let log = path(AUDIT_LOG_PATH);
+    if let Some(parent) = log.parent() {
+        create_dir_all(parent).await?;
+    }
+    match metadata(&log).await {
+        Ok(metadata) if metadata.len() >= MAX_AUDIT_LOG_SIZE => {
+            rename(&log, path(ROTATED_AUDIT_LOG_PATH)).await?;
+        }
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::NotFound => (),
+        Err(e) => return Err(e.into()),
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    Ok(file.sync_data().await?)
+}
+
+/// Record the outcome of a privileged method call. Failing to write the audit log doesn't fail
+/// the call itself, since the operation has already happened by then.
+pub(crate) async fn audit<T, E: Display>(
+    connection: &Connection,
+    header: &Header<'_>,
+    method: &str,
+    arguments: Vec<String>,
+    result: &Result<T, E>,
+) {
+    let sender = header.sender().map(ToString::to_string).unwrap_or_default();
+    let entry = match identify_caller(connection, header).await {
+        Ok(caller) => AuditEntry {
+            pid: Some(caller.pid),
+            exe: caller.exe,
+            ..AuditEntry::new(caller.uid, sender, method, arguments, result)
+        },
+        Err(e) => {
+            // Not every bus hands out the caller's process, but the uid is still worth having
+            warn!("Could not identify caller of {method} for audit log: {e}");
+            let uid = caller_uid(connection, header)
+                .await
+                .inspect_err(|e| warn!("Could not look up caller of {method} for audit log: {e}"))
+                .unwrap_or(UNKNOWN_UID);
+            AuditEntry::new(uid, sender, method, arguments, result)
+        }
+    };
+    if let Err(e) = append_entry(&entry).await {
+        warn!("Could not write audit log entry for {method}: {e}");
+    }
+}
+
+async fn read_entries(log: &Path) -> Result<Vec<AuditEntry>> {
+    let contents = match read_to_string(log).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .inspect_err(|e| warn!("Skipping malformed audit log entry: {e}"))
+                .ok()
+        })
+        .collect())
+}
+
+/// The most recent audit log entries, oldest first
+pub(crate) async fn recent_audit_entries(count: usize) -> Result<Vec<AuditEntry>> {
+    let _guard = AUDIT_LOG_LOCK.lock().await;
+    let mut entries = read_entries(&path(ROTATED_AUDIT_LOG_PATH)).await?;
+    entries.extend(read_entries(&path(AUDIT_LOG_PATH)).await?);
+    let skip = entries.len().saturating_sub(count);
+    Ok(entries.split_off(skip))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::write;
+
+    #[tokio::test]
+    async fn append_and_read() {
+        let _h = testing::start();
+
+        assert!(recent_audit_entries(10).await.unwrap().is_empty());
+
+        let ok: Result<(), String> = Ok(());
+        let err: Result<(), String> = Err(String::from("Invalid device"));
+        let first = AuditEntry::new(1000, String::from(":1.1"), "TrimDevices", Vec::new(), &ok);
+        let second = AuditEntry::new(
+            0,
+            String::from(":1.2"),
+            "FormatDevice",
+            vec![String::from("/dev/sda"), String::from("Games")],
+            &err,
+        );
+        append_entry(&first).await.unwrap();
+        append_entry(&second).await.unwrap();
+        assert_eq!(second.result, "Invalid device");
+        assert_eq!(
+            recent_audit_entries(10).await.unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+        assert_eq!(recent_audit_entries(1).await.unwrap(), vec![second.clone()]);
+
+        let contents = read_to_string(path(AUDIT_LOG_PATH)).await.unwrap();
+        write(
+            path(AUDIT_LOG_PATH),
+            format!("{contents}not json\n").as_bytes(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(recent_audit_entries(10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn entries_without_process() {
+        let _h = testing::start();
+
+        create_dir_all(path(AUDIT_LOG_PATH).parent().unwrap())
+            .await
+            .unwrap();
+        write(
+            path(AUDIT_LOG_PATH),
+            concat!(
+                r#"{"timestamp":1,"uid":1000,"sender":":1.1","method":"TrimDevices","#,
+                r#""arguments":[],"result":"success"}"#,
+                "\n",
+            ),
+        )
+        .await
+        .unwrap();
+
+        let ok: Result<(), String> = Ok(());
+        let entry = AuditEntry {
+            pid: Some(1234),
+            exe: Some(PathBuf::from("/usr/bin/steamosctl")),
+            ..AuditEntry::new(1000, String::from(":1.2"), "UpdateBios", Vec::new(), &ok)
+        };
+        append_entry(&entry).await.unwrap();
+
+        let entries = recent_audit_entries(10).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pid, None);
+        assert_eq!(entries[0].exe, None);
+        assert_eq!(entries[0].method, "TrimDevices");
+        assert_eq!(entries[1], entry);
+    }
+
+    #[tokio::test]
+    async fn rotation() {
+        let _h = testing::start();
+
+        let ok: Result<(), String> = Ok(());
+        let old = AuditEntry::new(0, String::from(":1.1"), "UpdateBios", Vec::new(), &ok);
+        let new = AuditEntry::new(0, String::from(":1.1"), "UpdateDock", Vec::new(), &ok);
+        create_dir_all(path(AUDIT_LOG_PATH).parent().unwrap())
+            .await
+            .unwrap();
+        let mut contents = String::new();
+        while (contents.len() as u64) < MAX_AUDIT_LOG_SIZE {
+            contents.push_str(&serde_json::to_string(&old).unwrap());
+            contents.push('\n');
+        }
+        write(path(AUDIT_LOG_PATH), contents.as_bytes())
+            .await
+            .unwrap();
+
+        append_entry(&new).await.unwrap();
+        assert_eq!(
+            read_to_string(path(ROTATED_AUDIT_LOG_PATH)).await.unwrap(),
+            contents
+        );
+        assert_eq!(
+            read_entries(&path(AUDIT_LOG_PATH)).await.unwrap(),
+            vec![new.clone()]
+        );
+
+        let recent = recent_audit_entries(2).await.unwrap();
+        assert_eq!(recent, vec![old, new]);
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Result};
+use std::str::FromStr;
+use strum::{Display, EnumString};
+use tokio::fs::{read_dir, read_to_string};
+use tracing::error;
+
+use crate::path;
+use crate::power::write_sysfs;
+
+const BACKLIGHT_PREFIX: &str = "/sys/class/backlight";
+
+// Number of increments between off and full brightness
+const BRIGHTNESS_STEPS: u32 = 20;
+
+// Ordered by preference, as recommended by the kernel sysfs ABI documentation
+#[derive(Display, EnumString, PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+#[strum(serialize_all = "lowercase")]
+enum BacklightType {
+    Firmware,
+    Platform,
+    Raw,
+}
+
+async fn read_backlight_attribute(device: &str, attribute: &str) -> Result<String> {
+    let contents = read_to_string(path(BACKLIGHT_PREFIX).join(device).join(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    Ok(contents.trim().to_string())
+}
+
+pub(crate) async fn list_backlights() -> Result<Vec<String>> {
+    let mut backlights = Vec::new();
+    let mut dir = read_dir(path(BACKLIGHT_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let Ok(kind) = read_backlight_attribute(name.as_str(), "type").await else {
+            continue;
+        };
+        let Ok(kind) = BacklightType::from_str(kind.as_str()) else {
+            continue;
+        };
+        backlights.push((kind, name));
+    }
+    backlights.sort();
+    Ok(backlights.into_iter().map(|(_, name)| name).collect())
+}
+
+pub(crate) async fn find_backlight() -> Result<String> {
+    match list_backlights().await?.into_iter().next() {
+        Some(device) => Ok(device),
+        None => bail!("No backlight found"),
+    }
+}
+
+pub(crate) async fn get_brightness(device: &str) -> Result<u32> {
+    read_backlight_attribute(device, "brightness")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) async fn get_max_brightness(device: &str) -> Result<u32> {
+    read_backlight_attribute(device, "max_brightness")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) fn brightness_step(max_brightness: u32) -> u32 {
+    (max_brightness / BRIGHTNESS_STEPS).max(1)
+}
+
+pub(crate) async fn set_brightness(device: &str, brightness: u32) -> Result<()> {
+    ensure!(
+        list_backlights().await?.iter().any(|name| name == device),
+        "Invalid backlight {device}"
+    );
+    ensure!(
+        brightness <= get_max_brightness(device).await?,
+        "Invalid brightness {brightness}"
+    );
+    write_sysfs(
+        path(BACKLIGHT_PREFIX).join(device).join("brightness"),
+        brightness.to_string().as_bytes(),
+    )
+    .await
+    .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+
+    async fn create_backlight(name: &str, kind: &str, max_brightness: u32) -> Result<()> {
+        let base = path(BACKLIGHT_PREFIX).join(name);
+        create_dir_all(&base).await?;
+        write(base.join("type"), format!("{kind}\n")).await?;
+        write(base.join("max_brightness"), format!("{max_brightness}\n")).await?;
+        write(base.join("brightness"), format!("{}\n", max_brightness / 2)).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        create_backlight("amdgpu_bl0", "raw", 255).await
+    }
+
+    #[tokio::test]
+    async fn enumerate_backlights() {
+        let _h = testing::start();
+
+        assert!(list_backlights().await.is_err());
+        create_dir_all(path(BACKLIGHT_PREFIX))
+            .await
+            .expect("create_dir_all");
+        assert!(list_backlights().await.unwrap().is_empty());
+        assert!(find_backlight().await.is_err());
+
+        create_nodes().await.expect("create_nodes");
+        create_backlight("acpi_video0", "firmware", 100)
+            .await
+            .expect("create_backlight");
+        create_backlight("bogus", "sideways", 100)
+            .await
+            .expect("create_backlight");
+
+        assert_eq!(
+            list_backlights().await.unwrap(),
+            vec![String::from("acpi_video0"), String::from("amdgpu_bl0")]
+        );
+        assert_eq!(find_backlight().await.unwrap(), "acpi_video0");
+    }
+
+    #[tokio::test]
+    async fn brightness() {
+        let _h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        assert_eq!(get_brightness("amdgpu_bl0").await.unwrap(), 127);
+        assert_eq!(get_max_brightness("amdgpu_bl0").await.unwrap(), 255);
+        assert_eq!(brightness_step(255), 12);
+        assert_eq!(brightness_step(7), 1);
+
+        set_brightness("amdgpu_bl0", 200).await.expect("set");
+        assert_eq!(get_brightness("amdgpu_bl0").await.unwrap(), 200);
+
+        assert!(set_brightness("amdgpu_bl0", 256).await.is_err());
+        assert!(set_brightness("../amdgpu_bl0", 100).await.is_err());
+        assert!(set_brightness("intel_backlight", 100).await.is_err());
+        assert_eq!(get_brightness("amdgpu_bl0").await.unwrap(), 200);
+    }
+}
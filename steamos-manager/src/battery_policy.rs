@@ -0,0 +1,325 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+use zbus::Connection;
+
+use crate::hardware::device_config;
+use crate::manager::user::{
+    apply_power_preset, current_power_preset, BatteryPolicy1, BatteryPolicy1Signals, MANAGER_PATH,
+};
+use crate::policy::Guarded;
+use crate::power::{
+    find_battery, get_available_platform_profiles, get_battery_capacity, is_on_battery,
+    PowerPreset, TdpManagerCommand,
+};
+use crate::Service;
+
+/// Platform profiles that save power, in order of preference
+const POWER_SAVE_PROFILES: [&str; 2] = ["low-power", "quiet"];
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct BatteryPolicyConfig {
+    /// Polling interval in seconds
+    pub poll_interval: u64,
+    /// Battery capacity in percent below which the low-battery actions are taken
+    pub low_threshold: Option<u32>,
+    /// Battery capacity in percent below which `CriticalBattery` is signalled
+    pub critical_threshold: Option<u32>,
+    /// TDP limit in watts that is enforced while the battery is low
+    pub tdp_limit: Option<u32>,
+    /// Whether to switch to the power-saving performance profile while the battery is low
+    pub power_save: bool,
+}
+
+impl Default for BatteryPolicyConfig {
+    fn default() -> BatteryPolicyConfig {
+        BatteryPolicyConfig {
+            poll_interval: 30,
+            low_threshold: None,
+            critical_threshold: None,
+            tdp_limit: None,
+            power_save: false,
+        }
+    }
+}
+
+pub(crate) enum BatteryPolicyCommand {
+    Configure(BatteryPolicyConfig),
+}
+
+#[derive(PartialEq, Debug)]
+enum BatteryPolicyAction {
+    EnterLowBattery,
+    LeaveLowBattery,
+    CriticalBattery,
+}
+
+pub(crate) struct BatteryPolicyService {
+    session: Connection,
+    channel: UnboundedReceiver<BatteryPolicyCommand>,
+    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    config: BatteryPolicyConfig,
+    interval: Interval,
+    low: bool,
+    critical: bool,
+    previous_profile: Option<String>,
+}
+
+fn poll_interval(config: &BatteryPolicyConfig) -> Interval {
+    let mut interval = interval(Duration::from_secs(config.poll_interval.max(1)));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+async fn power_save_profile() -> Option<String> {
+    let config = device_config().await.ok()?;
+    let name = config
+        .as_ref()?
+        .performance_profile
+        .as_ref()?
+        .platform_profile_name
+        .clone();
+    let available = get_available_platform_profiles(name.as_str()).await.ok()?;
+    POWER_SAVE_PROFILES
+        .into_iter()
+        .find(|profile| available.iter().any(|available| available == profile))
+        .map(ToString::to_string)
+}
+
+impl BatteryPolicyService {
+    pub(crate) fn new(
+        session: &Connection,
+        channel: UnboundedReceiver<BatteryPolicyCommand>,
+        tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    ) -> BatteryPolicyService {
+        let config = BatteryPolicyConfig::default();
+        BatteryPolicyService {
+            session: session.clone(),
+            channel,
+            tdp_manager,
+            interval: poll_interval(&config),
+            config,
+            low: false,
+            critical: false,
+            previous_profile: None,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.low_threshold.is_some() || self.config.critical_threshold.is_some()
+    }
+
+    async fn configure(&mut self, config: BatteryPolicyConfig) -> Result<()> {
+        // Start over, so that the new thresholds are checked from scratch
+        if self.low {
+            self.leave_low_battery().await?;
+        }
+        self.critical = false;
+        self.interval = poll_interval(&config);
+        self.config = config;
+        Ok(())
+    }
+
+    fn evaluate(&mut self, capacity: u32, on_battery: bool) -> Vec<BatteryPolicyAction> {
+        let mut actions = Vec::new();
+        let below =
+            |threshold: Option<u32>| threshold.is_some_and(|threshold| capacity < threshold);
+
+        // Once taken, the low-battery actions stay in effect until charging resumes
+        if self.low {
+            if !on_battery {
+                self.low = false;
+                actions.push(BatteryPolicyAction::LeaveLowBattery);
+            }
+        } else if on_battery && below(self.config.low_threshold) {
+            self.low = true;
+            actions.push(BatteryPolicyAction::EnterLowBattery);
+        }
+
+        if on_battery && below(self.config.critical_threshold) {
+            if !self.critical {
+                self.critical = true;
+                actions.push(BatteryPolicyAction::CriticalBattery);
+            }
+        } else {
+            self.critical = false;
+        }
+        actions
+    }
+
+    async fn poll(&mut self) -> Result<()> {
+        if find_battery().await.is_err() {
+            return Ok(());
+        }
+        let capacity = get_battery_capacity().await?;
+        let on_battery = is_on_battery().await?;
+        for action in self.evaluate(capacity, on_battery) {
+            debug!("Battery at {capacity}%, taking action {action:?}");
+            match action {
+                BatteryPolicyAction::EnterLowBattery => self.enter_low_battery().await?,
+                BatteryPolicyAction::LeaveLowBattery => self.leave_low_battery().await?,
+                BatteryPolicyAction::CriticalBattery => {
+                    warn!("Battery is critically low at {capacity}%");
+                    let policy = self
+                        .session
+                        .object_server()
+                        .interface::<_, Guarded<BatteryPolicy1>>(MANAGER_PATH)
+                        .await?;
+                    policy.signal_emitter().critical_battery(capacity).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn set_low_battery(&self, low_battery: bool) -> Result<()> {
+        let policy = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<BatteryPolicy1>>(MANAGER_PATH)
+            .await?;
+        policy
+            .get_mut()
+            .await
+            .set_low_battery(low_battery, policy.signal_emitter())
+            .await?;
+        Ok(())
+    }
+
+    async fn enter_low_battery(&mut self) -> Result<()> {
+        info!("Battery is low, limiting power use");
+        if self.config.power_save {
+            if let Some(profile) = power_save_profile().await {
+                let current = current_power_preset(&self.session)
+                    .await
+                    .performance_profile;
+                if current.as_deref() != Some(profile.as_str()) {
+                    let preset = PowerPreset {
+                        performance_profile: Some(profile),
+                        ..PowerPreset::default()
+                    };
+                    apply_power_preset(&self.session, &preset).await?;
+                    self.previous_profile = current;
+                }
+            }
+        }
+        if let (Some(limit), Some(tdp_manager)) = (self.config.tdp_limit, &self.tdp_manager) {
+            tdp_manager.send(TdpManagerCommand::SetBatteryLimit(Some(limit)))?;
+        }
+        self.set_low_battery(true).await
+    }
+
+    async fn leave_low_battery(&mut self) -> Result<()> {
+        info!("Battery is no longer low, lifting power limits");
+        self.low = false;
+        if let Some(tdp_manager) = self.tdp_manager.as_ref() {
+            tdp_manager.send(TdpManagerCommand::SetBatteryLimit(None))?;
+        }
+        if let Some(profile) = self.previous_profile.take() {
+            // Leave the profile alone if it's been changed since
+            let current = current_power_preset(&self.session)
+                .await
+                .performance_profile;
+            if current.is_some() && current == power_save_profile().await {
+                let preset = PowerPreset {
+                    performance_profile: Some(profile),
+                    ..PowerPreset::default()
+                };
+                apply_power_preset(&self.session, &preset).await?;
+            }
+        }
+        self.set_low_battery(false).await
+    }
+}
+
+impl Service for BatteryPolicyService {
+    const NAME: &'static str = "battery-policy";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            tokio::select! {
+                message = self.channel.recv() => {
+                    let Some(BatteryPolicyCommand::Configure(config)) = message else {
+                        bail!("Battery policy service channel broke");
+                    };
+                    self.configure(config).await?;
+                },
+                _ = self.interval.tick(), if self.is_enabled() => {
+                    if let Err(e) = self.poll().await {
+                        warn!("Failed to apply battery policy: {e}");
+                    }
+                },
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        if self.low {
+            self.leave_low_battery().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn thresholds() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let (_tx, rx) = unbounded_channel();
+        let mut service = BatteryPolicyService::new(&connection, rx, None);
+        assert!(!service.is_enabled());
+
+        service.config = BatteryPolicyConfig {
+            low_threshold: Some(20),
+            critical_threshold: Some(5),
+            ..BatteryPolicyConfig::default()
+        };
+        assert!(service.is_enabled());
+
+        assert!(service.evaluate(50, true).is_empty());
+        // Nothing happens while charging, however low the battery is
+        assert!(service.evaluate(3, false).is_empty());
+
+        assert_eq!(
+            service.evaluate(19, true),
+            [BatteryPolicyAction::EnterLowBattery]
+        );
+        assert!(service.evaluate(18, true).is_empty());
+        // The capacity fluctuating back above the threshold doesn't lift the limits
+        assert!(service.evaluate(21, true).is_empty());
+
+        assert_eq!(
+            service.evaluate(4, true),
+            [BatteryPolicyAction::CriticalBattery]
+        );
+        assert!(service.evaluate(3, true).is_empty());
+
+        assert_eq!(
+            service.evaluate(3, false),
+            [BatteryPolicyAction::LeaveLowBattery]
+        );
+        assert_eq!(
+            service.evaluate(3, true),
+            [
+                BatteryPolicyAction::EnterLowBattery,
+                BatteryPolicyAction::CriticalBattery
+            ]
+        );
+    }
+}
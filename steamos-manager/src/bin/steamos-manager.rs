@@ -9,17 +9,25 @@ use anyhow::Result;
 use clap::Parser;
 
 use steamos_manager::daemon;
+use steamos_manager::mock::{self, MockProfile};
 
 #[derive(Parser)]
 struct Args {
     /// Run the root manager daemon
     #[arg(short, long)]
     root: bool,
+
+    /// Run against fake hardware instead of the real sysfs, e.g. "jupiter" or "galileo"
+    #[arg(long, value_name = "PROFILE")]
+    mock: Option<MockProfile>,
 }
 
 #[tokio::main]
 pub async fn main() -> Result<()> {
     let args = Args::parse();
+    if let Some(profile) = args.mock {
+        mock::enable(profile).await?;
+    }
     if args.root {
         daemon::root().await
     } else {
@@ -6,43 +6,150 @@
  */
 
 use anyhow::{anyhow, Result};
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use clap_complete::{generate, Shell};
 use itertools::Itertools;
 use nix::time::{clock_gettime, ClockId};
+use serde_json::{json, Map, Value as JsonValue};
 use std::collections::HashMap;
-use std::io::Cursor;
+use std::fmt::Display;
+use std::io::{stdout, Cursor};
 use steamos_manager::cec::HdmiCecState;
+use steamos_manager::color_filters::ColorFilter;
 use steamos_manager::hardware::{FactoryResetKind, FanControlState};
-use steamos_manager::power::{CPUBoostState, CPUScalingGovernor};
+use steamos_manager::idle::{IdleAction, IdlePowerState};
+use steamos_manager::power::{
+    BatteryChargeState, BatteryHealth, CPUBoostState, CPUScalingGovernor,
+    EnergyPerformancePreference,
+};
 use steamos_manager::proxy::{
-    AmbientLightSensor1Proxy, BatteryChargeLimit1Proxy, CpuBoost1Proxy, CpuScaling1Proxy,
-    FactoryReset1Proxy, FanControl1Proxy, GpuPerformanceLevel1Proxy, GpuPowerProfile1Proxy,
-    HdmiCec1Proxy, LowPowerMode1Proxy, Manager2Proxy, PerformanceProfile1Proxy, ScreenReader0Proxy,
-    SessionManagement1Proxy, Storage1Proxy, TdpLimit1Proxy, UpdateBios1Proxy, UpdateDock1Proxy,
-    WifiDebug1Proxy, WifiDebugDump1Proxy, WifiPowerManagement1Proxy,
+    Accessibility1Proxy, AmbientLightSensor1Proxy, Audio1Proxy, AuditLog1Proxy, Backlight1Proxy,
+    Battery1Proxy, BatteryChargeLimit1Proxy, ChargeLed1Proxy, ColorFilters1Proxy, CpuBoost1Proxy,
+    CpuFrequency1Proxy, CpuScaling1Proxy, DeveloperMode1Proxy, DisplayOutput1Proxy, Dock1Proxy,
+    FactoryReset1Proxy, FanControl1Proxy, FanCurve1Proxy, FirmwareInfo1Proxy, GpuMetrics1Proxy,
+    GpuPerformanceLevel1Proxy, GpuPower1Proxy, GpuPowerProfile1Proxy, GpuThrottle1Proxy,
+    Haptics1Proxy, HdmiCec1Proxy, Headless1Proxy, IdlePolicy1Proxy, InputInhibit1Proxy,
+    JobHistory1Proxy, LedControl1Proxy, LowPowerMode1Proxy, Manager2Proxy, NetworkLimit1Proxy,
+    OsUpdate1Proxy, OsUpdateChannel1Proxy, PerformanceProfile1Proxy, PowerHistory1Proxy,
+    PowerPresets1Proxy, PowerSource1Proxy, RootFilesystem1Proxy, ScreenReader0Proxy,
+    Services1Proxy, SessionManagement1Proxy, SleepConfig1Proxy, Smt1Proxy, Status1Proxy,
+    Storage1Proxy, StorageHealth1Proxy, SuspendInhibit1Proxy, SysfsAccess1Proxy, TdpLimit1Proxy,
+    TdpProfiles1Proxy, TextInput1Proxy, Thermal1Proxy, UpdateBios1Proxy, UpdateController1Proxy,
+    UpdateDock1Proxy, UsbAuthorization1Proxy, WakeSources1Proxy, WifiDebug1Proxy,
+    WifiDebugDump1Proxy, WifiInfo1Proxy, WifiPowerManagement1Proxy,
 };
 use steamos_manager::screenreader::{ScreenReaderAction, ScreenReaderMode};
 use steamos_manager::session::LoginMode;
 use steamos_manager::wifi::{WifiBackend, WifiDebugMode, WifiPowerManagement};
-use zbus::fdo::{IntrospectableProxy, PropertiesProxy};
+use tokio_stream::StreamExt;
+use zbus::fdo::{IntrospectableProxy, ObjectManagerProxy, PropertiesProxy};
 use zbus::{zvariant, Connection};
-use zbus_xml::Node;
+use zbus_xml::{Arg, ArgDirection, Node, PropertyAccess};
+
+const MANAGER_INTERFACE_PREFIX: &str = "com.steampowered.SteamOSManager1.";
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
+    /// Print the output of get commands as a JSON object
+    #[arg(long, global = true)]
+    json: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Collects the output of a command. In text mode lines are printed as they come in, and in JSON
+/// mode values are gathered into a single object that's printed once the command is done.
+struct Output {
+    json: Option<Map<String, JsonValue>>,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Get all properties
     GetAllProperties,
 
+    /// Print the interfaces exported by the daemon in the format of the
+    /// data/interfaces XML files, without documentation
+    DumpInterfaceXml,
+
+    /// List the interfaces available on this device, and why optional ones are missing
+    ListInterfaces,
+
+    /// Print shell completions for steamosctl
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+
+    /// Watch property changes and added or removed interfaces until interrupted
+    Monitor {
+        /// Only show changes to this interface, e.g. Battery1
+        interface: Option<String>,
+    },
+
     /// Get luminance sensor calibration gain
     GetAlsCalibrationGain,
 
+    /// Get the current ambient illuminance, in lux
+    GetAmbientLight,
+
+    /// List audio devices and outputs
+    ListAudioOutputs,
+
+    /// Set the default audio output
+    SetDefaultAudioOutput {
+        /// ID of the output, as listed by list-audio-outputs
+        id: u32,
+    },
+
+    /// List the profiles of an audio device
+    GetAudioProfiles {
+        /// ID of the device, as listed by list-audio-outputs
+        device: u32,
+    },
+
+    /// Switch an audio device to a different profile
+    SetAudioProfile {
+        /// ID of the device, as listed by list-audio-outputs
+        device: u32,
+
+        /// Index of the profile, as listed by get-audio-profiles
+        profile: u32,
+    },
+
+    /// Get the volume of the default audio output
+    GetOutputVolume,
+
+    /// Set the volume of the default audio output
+    SetOutputVolume {
+        /// Valid values are 0.0 to 1.0
+        volume: f64,
+    },
+
+    /// Allow the built-in speakers to go above their volume limit. Requires
+    /// developer mode
+    SetSpeakerVolumeLimitOverride {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Get the microphone mute state and volume
+    GetMicrophone,
+
+    /// Mute or unmute the microphone
+    SetMicrophoneMuted {
+        #[arg(action = ArgAction::Set, required = true)]
+        muted: bool,
+    },
+
+    /// Set the microphone volume
+    SetMicrophoneVolume {
+        /// Valid values are 0.0 to 1.0
+        volume: f64,
+    },
+
     /// Set the fan control state
     SetFanControlState {
         /// Valid options are `bios`, `os`
@@ -52,6 +159,61 @@ enum Commands {
     /// Get the fan control state
     GetFanControlState,
 
+    /// Get the custom fan curve
+    GetFanCurve,
+
+    /// Set a custom fan curve
+    SetFanCurve {
+        /// Points of the curve, as `temperature:pwm` pairs. Temperatures are
+        /// in degrees Celsius and PWM values range from 0 to 255
+        #[arg(value_parser = parse_fan_curve_point, required = true)]
+        points: Vec<(u32, u32)>,
+    },
+
+    /// Reset the fan curve to the hardware default
+    ResetFanCurve,
+
+    /// Get the LED brightness, color and pattern
+    GetLed,
+
+    /// Set the LED brightness
+    SetLedBrightness {
+        /// Valid values are 0 to the LED's maximum brightness
+        brightness: u32,
+    },
+
+    /// Set the LED color
+    SetLedColor {
+        /// Red intensity, usually from 0 to 255
+        red: u32,
+        /// Green intensity, usually from 0 to 255
+        green: u32,
+        /// Blue intensity, usually from 0 to 255
+        blue: u32,
+    },
+
+    /// Set the LED pattern
+    SetLedPattern {
+        /// Valid patterns can be found with get-led. An empty pattern makes
+        /// the LED steady
+        pattern: String,
+    },
+
+    /// Get the rumble intensity and haptic feedback state
+    GetHaptics,
+
+    /// Set the rumble intensity
+    SetHapticsIntensity {
+        /// Valid values are 0 to 100
+        intensity: u32,
+    },
+
+    /// Enable or disable haptic feedback
+    SetHapticFeedback {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
     /// Get the available CPU scaling governors supported on this device
     GetAvailableCpuScalingGovernors,
 
@@ -64,6 +226,60 @@ enum Commands {
         governor: CPUScalingGovernor,
     },
 
+    /// Get the current CPU governor of each CPU frequency policy
+    GetCpuScalingGovernors,
+
+    /// Set the CPU scaling governor of a single CPU frequency policy
+    SetCpuScalingGovernorForPolicy {
+        /// Valid policies can be found using get-cpu-scaling-governors.
+        policy: u32,
+        /// Valid governors are get-cpu-governors.
+        governor: CPUScalingGovernor,
+    },
+
+    /// Get the energy performance preferences supported on this device
+    GetAvailableEnergyPerformancePreferences,
+
+    /// Get the current energy performance preference
+    GetEnergyPerformancePreference,
+
+    /// Set the energy performance preference
+    SetEnergyPerformancePreference {
+        /// Valid preferences are get-available-energy-performance-preferences.
+        preference: EnergyPerformancePreference,
+    },
+
+    /// Get the number of online CPUs and the range it can be set to
+    GetOnlineCpuCount,
+
+    /// Set the number of online CPUs. All CPUs come back online when the performance profile
+    /// changes
+    SetOnlineCpuCount {
+        /// Valid values can be found using get-online-cpu-count.
+        count: u32,
+    },
+
+    /// Get the range of CPU frequencies supported by the hardware, in MHz
+    GetCpuFrequencyRange,
+
+    /// Get the discrete CPU frequencies supported on this device, in MHz
+    GetAvailableCpuFrequencies,
+
+    /// Get the minimum and maximum CPU frequency limits, in MHz
+    GetCpuFrequencyLimits,
+
+    /// Set the minimum CPU frequency limit
+    SetMinCpuFrequency {
+        /// Frequency in MHz
+        freq: u32,
+    },
+
+    /// Set the maximum CPU frequency limit
+    SetMaxCpuFrequency {
+        /// Frequency in MHz
+        freq: u32,
+    },
+
     /// Get the current CPU boost state
     GetCpuBoostState,
 
@@ -73,6 +289,42 @@ enum Commands {
         state: CPUBoostState,
     },
 
+    /// Get whether simultaneous multithreading (SMT) is enabled
+    GetSmtEnabled,
+
+    /// Enable or disable simultaneous multithreading (SMT)
+    SetSmtEnabled {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Get the sleep mode, hibernation settings and whether there is enough swap to hibernate
+    GetSleepConfig,
+
+    /// Set the mode used for suspending
+    SetSleepMode {
+        /// Valid modes are `s2idle`, `shallow` and `deep`, if supported
+        mode: String,
+    },
+
+    /// Allow or prevent hibernation
+    SetHibernationEnabled {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Allow or prevent hibernating after being suspended for a while
+    SetSuspendThenHibernateEnabled {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Set how long to stay suspended before hibernating
+    SetHibernateDelay {
+        /// Delay in seconds, or 0 for the system default
+        delay: u32,
+    },
+
     /// Get the GPU power profiles supported on this device
     GetAvailableGPUPowerProfiles,
 
@@ -87,7 +339,7 @@ enum Commands {
 
     /// Set the GPU performance level
     SetGPUPerformanceLevel {
-        /// Valid levels are `auto`, `low`, `high`, `manual`, `profile_peak`
+        /// Valid levels are `auto`, `low`, `high`, `manual`, `profile_peak` (amdgpu only)
         level: String,
     },
 
@@ -109,6 +361,15 @@ enum Commands {
     /// Get the minimum allowed GPU clock frequency for the `manual` performance level
     GetManualGPUClockMin,
 
+    /// Get the current GPU utilization metrics
+    GetGPUMetrics,
+
+    /// Get the GPU power draw and power limits, in watts
+    GetGPUPower,
+
+    /// Get whether the GPU is currently being thermally throttled
+    GetGPUThermalThrottling,
+
     /// Set the TDP limit
     SetTDPLimit {
         /// TDP limit, in W
@@ -124,6 +385,62 @@ enum Commands {
     /// Get the minimum allowed TDP limit
     GetTDPLimitMin,
 
+    /// Set the sustained, slow and fast TDP limits separately
+    SetTDPLimits {
+        /// Sustained power limit (SPL), in W
+        spl: u32,
+        /// Slow package power tracking limit (SPPT), in W
+        sppt: u32,
+        /// Fast package power tracking limit (FPPT), in W
+        fppt: u32,
+    },
+
+    /// Get the sustained, slow and fast TDP limits and their allowed ranges
+    GetTDPLimits,
+
+    /// Set the preferred TDP limit for an app
+    SetTDPProfile {
+        /// Steam app ID
+        app_id: u32,
+
+        /// TDP limit, in W
+        limit: u32,
+    },
+
+    /// Clear the preferred TDP limit for an app
+    ClearTDPProfile {
+        /// Steam app ID
+        app_id: u32,
+    },
+
+    /// List the preferred TDP limits for all apps
+    ListTDPProfiles,
+
+    /// Set the foreground app, applying its TDP profile if it has one
+    SetForegroundApp {
+        /// Steam app ID, or 0 if no app is in the foreground
+        app_id: u32,
+    },
+
+    /// Type text into the focused window, as if on a US keyboard layout
+    TypeText {
+        /// Text to type
+        text: String,
+    },
+
+    /// Press a combination of keys, e.g. ctrl alt t
+    SendKeyCombo {
+        /// Names of the keys to press
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+
+    /// Get the CPU, GPU, battery and SSD temperatures, in °C
+    GetTemperatures,
+
+    /// List all temperature sensors and their current readings
+    ListThermalSensors,
+
     /// Get the performance profiles supported on this device
     GetAvailablePerformanceProfiles,
 
@@ -139,6 +456,39 @@ enum Commands {
     /// Get the suggested default performance profile
     SuggestedDefaultPerformanceProfile,
 
+    /// Get recent power consumption samples
+    GetPowerHistory {
+        /// Only show samples taken after this time, in milliseconds since the Unix epoch
+        since: Option<u64>,
+    },
+
+    /// Get the available power presets and the current one
+    GetPowerPresets,
+
+    /// Apply a power preset
+    ApplyPowerPreset {
+        /// Name of the preset, e.g. quiet, balanced or performance
+        name: String,
+    },
+
+    /// Save the current power settings as a preset
+    SavePowerPreset {
+        /// Name of the preset
+        name: String,
+    },
+
+    /// Get whether the device is running off its battery
+    GetOnBattery,
+
+    /// Get what was negotiated with the connected charger
+    GetChargerInfo,
+
+    /// Get recently completed jobs
+    GetJobHistory {
+        /// The maximum number of jobs to show, defaults to 10
+        count: Option<u32>,
+    },
+
     /// Set the Wi-Fi backend, if possible
     SetWifiBackend {
         /// Supported backends are `iwd`, `wpa_supplicant`
@@ -174,6 +524,9 @@ enum Commands {
     /// Generate a Wi-Fi debug dump
     GenerateWifiDebugDump,
 
+    /// Get information about the active Wi-Fi link
+    GetWifiInfo,
+
     /// Get the state of HDMI-CEC support
     GetHdmiCecState,
 
@@ -183,24 +536,289 @@ enum Commands {
         state: HdmiCecState,
     },
 
+    /// Turn on the TV over HDMI-CEC
+    PowerOnTv,
+
+    /// Put the TV into standby over HDMI-CEC
+    StandbyTv,
+
+    /// Switch the TV to this device's input over HDMI-CEC
+    SetActiveSource,
+
+    /// List the other devices on the HDMI-CEC bus
+    ListCecDevices,
+
+    /// List input devices that are currently inhibited
+    ListInhibitedInputDevices,
+
     /// List active low power download mode handles
     ListLowPowerDownloadModeHandles,
 
+    /// List active network bandwidth limit handles
+    ListNetworkLimitHandles,
+
+    /// List active suspend inhibit handles
+    ListSuspendInhibitHandles,
+
+    /// List the sysfs attributes that can be accessed
+    ListSysfsAttributes,
+
+    /// Read a whitelisted sysfs attribute
+    ReadSysfsAttribute {
+        /// The name of the attribute, as listed by list-sysfs-attributes
+        name: String,
+    },
+
+    /// Write a whitelisted sysfs attribute
+    WriteSysfsAttribute {
+        /// The name of the attribute, as listed by list-sysfs-attributes
+        name: String,
+        /// The value to write
+        value: String,
+    },
+
+    /// List the system services that can be controlled and their state
+    ListServices,
+
+    /// Start a system service
+    StartService {
+        /// The name of the unit, as listed by list-services
+        unit: String,
+    },
+
+    /// Stop a system service
+    StopService {
+        /// The name of the unit, as listed by list-services
+        unit: String,
+    },
+
+    /// Restart a system service
+    RestartService {
+        /// The name of the unit, as listed by list-services
+        unit: String,
+    },
+
+    /// Enable or disable a system service on boot
+    SetServiceEnabled {
+        /// The name of the unit, as listed by list-services
+        unit: String,
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// List the connected USB devices and whether they are authorized
+    ListUsbDevices,
+
+    /// Get whether newly connected USB devices are authorized automatically
+    GetUsbAuthorizedDefault,
+
+    /// Set whether newly connected USB devices are authorized automatically
+    SetUsbAuthorizedDefault {
+        #[arg(action = ArgAction::Set, required = true)]
+        authorized: bool,
+    },
+
+    /// Authorize a connected USB device
+    AuthorizeUsbDevice {
+        /// The name of the device, as listed by list-usb-devices
+        device: String,
+    },
+
+    /// Deauthorize a connected USB device
+    DeauthorizeUsbDevice {
+        /// The name of the device, as listed by list-usb-devices
+        device: String,
+    },
+
+    /// Get the available wake sources and whether they can wake the device up
+    GetWakeSources,
+
+    /// Allow or prevent a wake source from waking the device up
+    SetWakeSourceEnabled {
+        /// Valid options are `lan`, `usb`, `lid` and `power_button`, if available
+        source: String,
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Get the installed firmware versions
+    GetFirmwareInfo,
+
+    /// Get whether a dock is connected, and its details if it is
+    GetDockStatus,
+
     /// Update the BIOS, if possible
     UpdateBios,
 
     /// Update the dock, if possible
     UpdateDock,
 
+    /// Update the controller firmware, if possible
+    UpdateController,
+
+    /// Update the BIOS on next boot, if possible
+    ScheduleUpdateBios,
+
+    /// Update the dock on next boot, if possible
+    ScheduleUpdateDock,
+
+    /// Cancel a BIOS update scheduled for next boot
+    CancelScheduledUpdateBios,
+
+    /// Cancel a dock update scheduled for next boot
+    CancelScheduledUpdateDock,
+
+    /// Show which updates are scheduled for next boot
+    GetScheduledUpdates,
+
+    /// Check whether any OS updates are available
+    CheckForOsUpdates,
+
+    /// Download and apply the newest OS update
+    UpdateOs,
+
+    /// Get the OS update channels that can be selected
+    GetAvailableUpdateChannels,
+
+    /// Get the current OS update channel
+    GetUpdateChannel,
+
+    /// Set the OS update channel
+    SetUpdateChannel {
+        /// Valid channels can be found using get-available-update-channels.
+        channel: String,
+    },
+
+    /// Get whether the root filesystem is read-only
+    GetRootFilesystemReadOnly,
+
+    /// Make the root filesystem read-only or writable
+    SetRootFilesystemReadOnly {
+        #[arg(action = ArgAction::Set, required = true)]
+        read_only: bool,
+    },
+
+    /// Get the most recent privileged operations recorded by the root daemon
+    GetAuditLog,
+
+    /// Get the settings the root daemon exposes while nobody is logged in
+    GetHeadlessSettings,
+
+    /// Set the TDP limit while nobody is logged in
+    SetHeadlessTdpLimit {
+        /// TDP limit, in W
+        limit: u32,
+    },
+
+    /// Set the fan control state while nobody is logged in
+    SetHeadlessFanControlState {
+        /// Valid options are `bios`, `os`
+        state: FanControlState,
+    },
+
+    /// Set the maximum battery charge level while nobody is logged in
+    SetHeadlessMaxChargeLevel {
+        /// Valid levels are 1 - 100, or -1 to reset to default
+        level: i32,
+    },
+
+    /// Get the state of the developer mode toggles
+    GetDeveloperMode,
+
+    /// Enable or disable the SSH server
+    SetSshEnabled {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Enable or disable collecting core dumps
+    SetCoreDumpsEnabled {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Set whether the journal is kept across reboots
+    SetPersistentJournal {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
     /// Trim applicable drives
     TrimDevices,
 
+    /// Get information about a storage device
+    GetStorageDeviceInfo {
+        /// Which device to query, e.g. /dev/mmcblk0
+        device: String,
+    },
+
+    /// Get the health of storage devices
+    GetStorageHealth {
+        /// Which device to query, e.g. /dev/nvme0n1. Defaults to all devices
+        device: Option<String>,
+    },
+
     /// Factory reset the os/user partitions
     PrepareFactoryReset {
         /// Valid kind(s) are `user`, `os`, `all`
         kind: FactoryResetKind,
     },
 
+    /// Show what a factory reset would do without performing it
+    EstimateFactoryReset {
+        /// Valid kind(s) are `user`, `os`, `all`
+        kind: FactoryResetKind,
+    },
+
+    /// Get the display backlight brightness
+    GetBrightness,
+
+    /// Set the display backlight brightness
+    SetBrightness {
+        /// Valid values are between 0 and the maximum brightness
+        brightness: u32,
+    },
+
+    /// Raise or lower the display backlight brightness by a number of steps
+    StepBrightness {
+        /// Number of steps, negative to lower the brightness
+        #[arg(allow_negative_numbers = true)]
+        steps: i32,
+    },
+
+    /// List the display connectors and the modes they support
+    ListDisplayOutputs,
+
+    /// Get the preferred resolution hint for external displays when docked
+    GetPreferredDockMode,
+
+    /// Set the preferred resolution hint for external displays when docked
+    SetPreferredDockMode {
+        /// A mode such as 1920x1080 or 1920x1080@60, or an empty string to clear the hint
+        mode: String,
+    },
+
+    /// Get the refresh rate cap for the display, in Hz
+    GetRefreshRateCap,
+
+    /// Set the refresh rate cap for the display
+    SetRefreshRateCap {
+        /// Highest refresh rate in Hz, or 0 to remove the cap
+        cap: u32,
+    },
+
+    /// Get the current battery charge, in percent
+    GetBatteryCapacity,
+
+    /// Get the current battery charging state
+    GetBatteryChargeState,
+
+    /// Get the battery health
+    GetBatteryHealth,
+
+    /// Get the number of charge cycles of the battery
+    GetBatteryCycleCount,
+
     /// Get the maximum charge level set for the battery
     GetMaxChargeLevel,
 
@@ -213,12 +831,130 @@ enum Commands {
     /// Get the recommended minimum for a charge level limit
     SuggestedMinimumChargeLimit,
 
+    /// Get whether the battery is bypassed while on external power
+    GetChargeBypass,
+
+    /// Bypass the battery while on external power, so that it doesn't charge
+    SetChargeBypass {
+        #[arg(action = ArgAction::Set, required = true)]
+        enabled: bool,
+    },
+
+    /// Get the charge LED mode and the modes this device supports
+    GetChargeLedMode,
+
+    /// Set the charge LED mode
+    SetChargeLedMode {
+        /// Possible modes are `off`, `charging`, `low_battery`
+        mode: String,
+    },
+
     /// Reload the configuration from disk
     ReloadConfig,
 
+    /// Check the platform and device configuration and show which interfaces it enables
+    CheckConfig,
+
+    /// Get the health of the daemons' internal services
+    GetServiceStatus,
+
     /// Get the model and variant of this device, if known
     GetDeviceModel,
 
+    /// Get the device configuration files that matched this device
+    GetDeviceConfigSource,
+
+    /// Show which device configuration entries match this device and why
+    MatchDevice {
+        /// Also show the entries that don't match
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Collect logs, configuration and hardware state into a tarball for bug reports
+    GenerateDiagnostics,
+
+    /// List the features that are currently available
+    ListFeatures,
+
+    /// Check whether a feature is currently available
+    HasFeature {
+        /// Feature name, e.g. "tdp-profiles"
+        name: String,
+    },
+
+    /// Get the current log filter
+    GetLogLevel,
+
+    /// Set the log filter of both daemons, e.g. "debug" or "steamos_manager=trace"
+    SetLogLevel {
+        /// Filter directive in RUST_LOG format, or "" for the configured filters
+        level: String,
+    },
+
+    /// Get whether the high-contrast theme is enabled
+    GetHighContrast,
+
+    /// Enable or disable the high-contrast theme
+    SetHighContrast {
+        #[arg(action = ArgAction::Set, required = true)]
+        enable: bool,
+    },
+
+    /// Get the text scaling factor
+    GetTextScalingFactor,
+
+    /// Set the text scaling factor
+    SetTextScalingFactor {
+        /// Valid factors are between 0.5 and 3.0, with 1.0 being the default text size
+        factor: f64,
+    },
+
+    /// Get the colour blindness filter
+    GetColorFilter,
+
+    /// Set the colour blindness filter
+    SetColorFilter {
+        /// Valid filters are `none`, `protanopia`, `deuteranopia`, `tritanopia`
+        filter: ColorFilter,
+    },
+
+    /// Get whether the night light is enabled
+    GetNightLight,
+
+    /// Enable or disable the night light
+    SetNightLight {
+        #[arg(action = ArgAction::Set, required = true)]
+        enable: bool,
+    },
+
+    /// Get the night light colour temperature
+    GetNightLightTemperature,
+
+    /// Set the night light colour temperature
+    SetNightLightTemperature {
+        /// Valid temperatures are between 1000 and 6500 Kelvin
+        temperature: u32,
+    },
+
+    /// Get what happens when the session is idle in a power state
+    GetIdlePolicy {
+        /// Valid power states are `ac`, `battery`, `download`
+        power_state: IdlePowerState,
+    },
+
+    /// Set what happens when the session is idle in a power state
+    SetIdlePolicy {
+        /// Valid power states are `ac`, `battery`, `download`
+        power_state: IdlePowerState,
+
+        /// Valid actions are `nothing`, `dim`, `suspend`
+        action: IdleAction,
+
+        /// Seconds of idleness before the action is taken, or 0 to never take it
+        timeout: u32,
+    },
+
     /// Get whether screen reader is enabled or not.
     GetScreenReaderEnabled,
 
@@ -282,6 +1018,15 @@ enum Commands {
         voice: String,
     },
 
+    /// Get screen reader voice locale
+    GetScreenReaderVoiceLocale,
+
+    /// Set screen reader voice locale, switching to the voice last selected for it
+    SetScreenReaderVoiceLocale {
+        /// Valid locales can be found using get-screen-reader-locales.
+        locale: String,
+    },
+
     /// Trigger screen reader action
     TriggerScreenReaderAction {
         /// Valid actions are
@@ -337,14 +1082,100 @@ enum Commands {
     CleanTemporarySessions,
 }
 
-async fn get_all_properties(conn: &Connection) -> Result<()> {
+fn parse_fan_curve_point(point: &str) -> Result<(u32, u32)> {
+    let (temperature, pwm) = point.split_once(':').ok_or(anyhow!(
+        "Fan curve points must be formatted as temperature:pwm"
+    ))?;
+    Ok((temperature.parse()?, pwm.parse()?))
+}
+
+async fn introspect_manager(conn: &Connection) -> Result<Node<'static>> {
     let proxy = IntrospectableProxy::builder(conn)
         .destination("com.steampowered.SteamOSManager1")?
         .path("/com/steampowered/SteamOSManager1")?
         .build()
         .await?;
     let introspection = proxy.introspect().await?;
-    let introspection = Node::from_reader(Cursor::new(introspection))?;
+    Ok(Node::from_reader(Cursor::new(introspection))?)
+}
+
+impl Output {
+    fn new(json: bool) -> Output {
+        Output {
+            json: json.then(Map::new),
+        }
+    }
+
+    /// Print a line of text, or record a value in JSON mode
+    fn field(&mut self, key: &str, text: impl Display, value: impl Into<JsonValue>) {
+        match self.json {
+            Some(ref mut json) => {
+                json.insert(key.to_string(), value.into());
+            }
+            None => println!("{text}"),
+        }
+    }
+
+    /// Print a line of text that has no equivalent in JSON mode
+    fn text(&self, text: impl Display) {
+        if self.json.is_none() {
+            println!("{text}");
+        }
+    }
+
+    /// Record a value that has no equivalent in text mode
+    fn value(&mut self, key: &str, value: impl Into<JsonValue>) {
+        if let Some(ref mut json) = self.json {
+            json.insert(key.to_string(), value.into());
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(json) = self.json {
+            if !json.is_empty() {
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn value_to_json(value: &zvariant::Value<'_>) -> JsonValue {
+    match value {
+        zvariant::Value::U8(value) => JsonValue::from(*value),
+        zvariant::Value::Bool(value) => JsonValue::from(*value),
+        zvariant::Value::I16(value) => JsonValue::from(*value),
+        zvariant::Value::U16(value) => JsonValue::from(*value),
+        zvariant::Value::I32(value) => JsonValue::from(*value),
+        zvariant::Value::U32(value) => JsonValue::from(*value),
+        zvariant::Value::I64(value) => JsonValue::from(*value),
+        zvariant::Value::U64(value) => JsonValue::from(*value),
+        zvariant::Value::F64(value) => JsonValue::from(*value),
+        zvariant::Value::Str(value) => JsonValue::from(value.as_str()),
+        zvariant::Value::Signature(value) => JsonValue::from(value.to_string()),
+        zvariant::Value::ObjectPath(value) => JsonValue::from(value.as_str()),
+        zvariant::Value::Value(value) => value_to_json(value),
+        zvariant::Value::Array(array) => array.inner().iter().map(value_to_json).collect(),
+        zvariant::Value::Dict(dict) => JsonValue::Object(
+            dict.iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        zvariant::Value::Str(key) => key.as_str().to_string(),
+                        key => value_to_json(key).to_string(),
+                    };
+                    (key, value_to_json(value))
+                })
+                .collect(),
+        ),
+        zvariant::Value::Structure(structure) => {
+            structure.fields().iter().map(value_to_json).collect()
+        }
+        zvariant::Value::Fd(_) => JsonValue::Null,
+    }
+}
+
+async fn get_all_properties(conn: &Connection, out: &mut Output) -> Result<()> {
+    let introspection = introspect_manager(conn).await?;
 
     let properties_proxy = PropertiesProxy::new(
         conn,
@@ -369,11 +1200,254 @@ async fn get_all_properties(conn: &Connection) -> Result<()> {
     for key in properties.keys().sorted() {
         let value = &properties[key];
         let val = &**value;
-        println!("{key}: {val}");
+        out.field(key, format!("{key}: {val}"), value_to_json(val));
+    }
+    Ok(())
+}
+
+fn short_interface_name(name: &str) -> &str {
+    name.strip_prefix(MANAGER_INTERFACE_PREFIX).unwrap_or(name)
+}
+
+async fn monitor(conn: &Connection, interface: Option<&str>) -> Result<()> {
+    let interface = interface.map(|name| {
+        if name.contains('.') {
+            name.to_string()
+        } else {
+            format!("{MANAGER_INTERFACE_PREFIX}{name}")
+        }
+    });
+    let watched = |name: &str| {
+        interface
+            .as_deref()
+            .is_none_or(|interface| interface == name)
+    };
+
+    let properties_proxy = PropertiesProxy::new(
+        conn,
+        "com.steampowered.SteamOSManager1",
+        "/com/steampowered/SteamOSManager1",
+    )
+    .await?;
+    let object_manager =
+        ObjectManagerProxy::new(conn, "com.steampowered.SteamOSManager1", "/").await?;
+    let mut properties_changed = properties_proxy.receive_properties_changed().await?;
+    let mut interfaces_added = object_manager.receive_interfaces_added().await?;
+    let mut interfaces_removed = object_manager.receive_interfaces_removed().await?;
+
+    match interface.as_deref() {
+        Some(interface) => println!("Monitoring {}", short_interface_name(interface)),
+        None => println!("Monitoring all interfaces"),
+    }
+    loop {
+        tokio::select! {
+            Some(signal) = properties_changed.next() => {
+                let args = signal.args()?;
+                let name = args.interface_name.as_str();
+                if !watched(name) {
+                    continue;
+                }
+                let name = short_interface_name(name);
+                for key in args.changed_properties.keys().sorted() {
+                    let value = &args.changed_properties[key];
+                    println!("{name}.{key}: {value}");
+                }
+                for key in args.invalidated_properties.iter().sorted() {
+                    println!("{name}.{key}: invalidated");
+                }
+            },
+            Some(signal) = interfaces_added.next() => {
+                let args = signal.args()?;
+                if args.object_path.as_str() != "/com/steampowered/SteamOSManager1" {
+                    continue;
+                }
+                for name in args.interfaces_and_properties.keys().map(|name| name.as_str()).sorted() {
+                    if watched(name) {
+                        println!("Interface added: {}", short_interface_name(name));
+                    }
+                }
+            },
+            Some(signal) = interfaces_removed.next() => {
+                let args = signal.args()?;
+                if args.object_path.as_str() != "/com/steampowered/SteamOSManager1" {
+                    continue;
+                }
+                for name in args.interfaces.iter().map(|name| name.as_str()).sorted() {
+                    if watched(name) {
+                        println!("Interface removed: {}", short_interface_name(name));
+                    }
+                }
+            },
+            else => return Err(anyhow!("Lost connection to the daemon")),
+        }
+    }
+}
+
+async fn list_interfaces(conn: &Connection, out: &mut Output) -> Result<()> {
+    let introspection = introspect_manager(conn).await?;
+    let active: Vec<String> = introspection
+        .interfaces()
+        .iter()
+        .map(|interface| interface.name().to_string())
+        .filter(|name| name.starts_with(MANAGER_INTERFACE_PREFIX))
+        .sorted()
+        .collect();
+
+    // Interfaces that depend on the platform or device config report why they're disabled
+    let proxy = Manager2Proxy::new(conn).await?;
+    let inactive: Vec<(String, String)> = proxy
+        .validate_config()
+        .await?
+        .into_iter()
+        .filter(|(name, enabled, _)| !enabled && !active.contains(name))
+        .map(|(name, _, reason)| (name, reason))
+        .sorted()
+        .collect();
+
+    out.text("Active interfaces:");
+    for name in &active {
+        out.text(format!("  {}", short_interface_name(name)));
+    }
+    if !inactive.is_empty() {
+        out.text("Inactive interfaces:");
+        for (name, reason) in &inactive {
+            out.text(format!("  {}: {reason}", short_interface_name(name)));
+        }
+    }
+    out.value("active", active);
+    out.value(
+        "inactive",
+        inactive
+            .into_iter()
+            .map(|(name, reason)| (name, JsonValue::from(reason)))
+            .collect::<Map<_, _>>(),
+    );
+    Ok(())
+}
+
+fn format_arg(arg: &Arg) -> String {
+    let mut xml = format!("<arg type=\"{}\"", arg.ty().inner());
+    if let Some(name) = arg.name() {
+        xml.push_str(format!(" name=\"{name}\"").as_str());
+    }
+    match arg.direction() {
+        Some(ArgDirection::In) => xml.push_str(" direction=\"in\""),
+        Some(ArgDirection::Out) => xml.push_str(" direction=\"out\""),
+        None => (),
+    }
+    xml.push_str("/>");
+    xml
+}
+
+async fn dump_interface_xml(conn: &Connection) -> Result<()> {
+    let introspection = introspect_manager(conn).await?;
+
+    println!("<node name=\"/\" xmlns:doc=\"http://www.freedesktop.org/dbus/1.0/doc.dtd\">");
+    for interface in introspection
+        .interfaces()
+        .iter()
+        .filter(|interface| {
+            interface
+                .name()
+                .as_str()
+                .starts_with("com.steampowered.SteamOSManager1")
+        })
+        .sorted_by_key(|interface| interface.name().to_string())
+    {
+        println!("  <interface name=\"{}\">", interface.name());
+        for property in interface.properties() {
+            let access = match property.access() {
+                PropertyAccess::Read => "read",
+                PropertyAccess::Write => "write",
+                PropertyAccess::ReadWrite => "readwrite",
+            };
+            println!();
+            println!(
+                "    <property name=\"{}\" type=\"{}\" access=\"{access}\"/>",
+                property.name(),
+                property.ty().inner()
+            );
+        }
+        for method in interface.methods() {
+            println!();
+            if method.args().is_empty() {
+                println!("    <method name=\"{}\"/>", method.name());
+                continue;
+            }
+            println!("    <method name=\"{}\">", method.name());
+            for arg in method.args() {
+                println!("      {}", format_arg(arg));
+            }
+            println!("    </method>");
+        }
+        for signal in interface.signals() {
+            println!();
+            println!("    <signal name=\"{}\">", signal.name());
+            for arg in signal.args() {
+                println!("      {}", format_arg(arg));
+            }
+            println!("    </signal>");
+        }
+        println!();
+        println!("  </interface>");
+        println!();
     }
+    println!("</node>");
     Ok(())
 }
 
+/// Run a command against the root daemon's headless interface, returning whether the command
+/// was one
+async fn run_headless_command(command: &Commands, out: &mut Output) -> Result<bool> {
+    match command {
+        Commands::GetHeadlessSettings => {
+            let system = Connection::system().await?;
+            let proxy = Headless1Proxy::new(&system).await?;
+            if let Ok(limit) = proxy.tdp_limit().await {
+                out.field("tdp_limit", format!("TDP limit: {limit}"), limit);
+            }
+            if let Ok(state) = proxy.fan_control_state().await {
+                match FanControlState::try_from(state) {
+                    Ok(s) => out.field(
+                        "fan_control_state",
+                        format!("Fan control state: {s}"),
+                        s.to_string(),
+                    ),
+                    Err(_) => out.field(
+                        "fan_control_state",
+                        format!("Got unknown value {state} from backend"),
+                        state,
+                    ),
+                }
+            }
+            if let Ok(level) = proxy.max_charge_level().await {
+                out.field(
+                    "max_charge_level",
+                    format!("Max charge level: {level}"),
+                    level,
+                );
+            }
+        }
+        Commands::SetHeadlessTdpLimit { limit } => {
+            let system = Connection::system().await?;
+            let proxy = Headless1Proxy::new(&system).await?;
+            proxy.set_tdp_limit(*limit).await?;
+        }
+        Commands::SetHeadlessFanControlState { state } => {
+            let system = Connection::system().await?;
+            let proxy = Headless1Proxy::new(&system).await?;
+            proxy.set_fan_control_state(*state as u32).await?;
+        }
+        Commands::SetHeadlessMaxChargeLevel { level } => {
+            let system = Connection::system().await?;
+            let proxy = Headless1Proxy::new(&system).await?;
+            proxy.set_max_charge_level(*level).await?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
 #[allow(clippy::too_many_lines)]
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -382,19 +1456,184 @@ async fn main() -> Result<()> {
     // First set up which command line arguments we support
     let args = Args::parse();
 
+    // Completions don't need the service, so don't require it to be running
+    if let Commands::Completions { shell } = args.command {
+        generate(shell, &mut Args::command(), "steamosctl", &mut stdout());
+        return Ok(());
+    }
+
+    let mut out = Output::new(args.json);
+
+    // Headless commands are meant for when nobody is logged in, so there may be no session bus
+    if run_headless_command(&args.command, &mut out).await? {
+        return out.finish();
+    }
+
     // Then get a connection to the service
     let conn = Connection::session().await?;
 
     // Then process arguments
     match &args.command {
         Commands::GetAllProperties => {
-            get_all_properties(&conn).await?;
+            get_all_properties(&conn, &mut out).await?;
+        }
+        Commands::DumpInterfaceXml => {
+            dump_interface_xml(&conn).await?;
+        }
+        Commands::ListInterfaces => {
+            list_interfaces(&conn, &mut out).await?;
+        }
+        Commands::Completions { .. } => (),
+        Commands::Monitor { interface } => {
+            monitor(&conn, interface.as_deref()).await?;
         }
         Commands::GetAlsCalibrationGain => {
             let proxy = AmbientLightSensor1Proxy::new(&conn).await?;
             let gain = proxy.als_calibration_gain().await?;
-            let gains = gain.into_iter().map(|g| g.to_string()).join(", ");
-            println!("ALS calibration gain: {gains}");
+            let gains = gain.iter().map(|g| g.to_string()).join(", ");
+            out.field(
+                "als_calibration_gain",
+                format!("ALS calibration gain: {gains}"),
+                gain,
+            );
+        }
+        Commands::GetAmbientLight => {
+            let proxy = AmbientLightSensor1Proxy::new(&conn).await?;
+            let lux = proxy.lux().await?;
+            out.field("ambient_light", format!("Ambient light: {lux} lux"), lux);
+        }
+        Commands::ListAudioOutputs => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            let default_output = proxy.default_output().await?;
+            let devices = proxy.devices().await?;
+            let outputs = proxy.outputs().await?;
+            out.text("Devices:");
+            for (id, name, description) in &devices {
+                out.text(format!("  {id}: {description} ({name})"));
+            }
+            out.text("Outputs:");
+            for (id, device, name, description) in &outputs {
+                let default = if *id == default_output {
+                    " [default]"
+                } else {
+                    ""
+                };
+                out.text(format!(
+                    "  {id}: {description} ({name}), device {device}{default}"
+                ));
+            }
+            out.value(
+                "devices",
+                devices
+                    .iter()
+                    .map(|(id, name, description)| {
+                        json!({ "id": id, "name": name, "description": description })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            out.value(
+                "outputs",
+                outputs
+                    .iter()
+                    .map(|(id, device, name, description)| {
+                        json!({
+                            "id": id,
+                            "device": device,
+                            "name": name,
+                            "description": description,
+                            "default": *id == default_output,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::SetDefaultAudioOutput { id } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_default_output(*id).await?;
+        }
+        Commands::GetAudioProfiles { device } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            let profiles = proxy.get_profiles(*device).await?;
+            for (index, name, description, available, active) in &profiles {
+                let mut notes = Vec::new();
+                if *active {
+                    notes.push("active");
+                }
+                if !available {
+                    notes.push("unavailable");
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", notes.join(", "))
+                };
+                out.text(format!("{index}: {description} ({name}){notes}"));
+            }
+            out.value(
+                "profiles",
+                profiles
+                    .iter()
+                    .map(|(index, name, description, available, active)| {
+                        json!({
+                            "index": index,
+                            "name": name,
+                            "description": description,
+                            "available": available,
+                            "active": active,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::SetAudioProfile { device, profile } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_profile(*device, *profile).await?;
+        }
+        Commands::GetOutputVolume => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            let volume = proxy.output_volume().await?;
+            let max_speaker_volume = proxy.max_speaker_volume().await?;
+            let overridden = proxy.speaker_volume_limit_override().await?;
+            out.field("volume", format!("Volume: {volume:.2}"), volume);
+            out.field(
+                "max_speaker_volume",
+                format!("Max speaker volume: {max_speaker_volume:.2}"),
+                max_speaker_volume,
+            );
+            out.field(
+                "speaker_volume_limit_override",
+                format!("Speaker volume limit overridden: {overridden}"),
+                overridden,
+            );
+        }
+        Commands::SetOutputVolume { volume } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_output_volume(*volume).await?;
+        }
+        Commands::SetSpeakerVolumeLimitOverride { enabled } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_speaker_volume_limit_override(*enabled).await?;
+        }
+        Commands::GetMicrophone => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            let muted = proxy.input_muted().await?;
+            let hardware_muted = proxy.hardware_input_muted().await?;
+            let volume = proxy.input_volume().await?;
+            out.field("muted", format!("Muted: {muted}"), muted);
+            out.field(
+                "hardware_muted",
+                format!("Hardware muted: {hardware_muted}"),
+                hardware_muted,
+            );
+            out.field("volume", format!("Volume: {volume:.2}"), volume);
+        }
+        Commands::SetMicrophoneMuted { muted } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_input_muted(*muted).await?;
+        }
+        Commands::SetMicrophoneVolume { volume } => {
+            let proxy = Audio1Proxy::new(&conn).await?;
+            proxy.set_input_volume(*volume).await?;
         }
         Commands::SetFanControlState { state } => {
             let proxy = FanControl1Proxy::new(&conn).await?;
@@ -404,17 +1643,110 @@ async fn main() -> Result<()> {
             let proxy = FanControl1Proxy::new(&conn).await?;
             let state = proxy.fan_control_state().await?;
             match FanControlState::try_from(state) {
-                Ok(s) => println!("Fan control state: {s}"),
-                Err(_) => println!("Got unknown value {state} from backend"),
+                Ok(s) => out.field(
+                    "fan_control_state",
+                    format!("Fan control state: {s}"),
+                    s.to_string(),
+                ),
+                Err(_) => out.field(
+                    "fan_control_state",
+                    format!("Got unknown value {state} from backend"),
+                    state,
+                ),
+            }
+        }
+        Commands::GetFanCurve => {
+            let proxy = FanCurve1Proxy::new(&conn).await?;
+            let curve = proxy.fan_curve().await?;
+            for (temperature, pwm) in &curve {
+                out.text(format!("{temperature}°C: {pwm}"));
+            }
+            out.value(
+                "fan_curve",
+                curve
+                    .iter()
+                    .map(|(temperature, pwm)| json!({ "temperature": temperature, "pwm": pwm }))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::SetFanCurve { points } => {
+            let proxy = FanCurve1Proxy::new(&conn).await?;
+            proxy.set_fan_curve(points).await?;
+        }
+        Commands::ResetFanCurve => {
+            let proxy = FanCurve1Proxy::new(&conn).await?;
+            proxy.reset_fan_curve().await?;
+        }
+        Commands::GetLed => {
+            let proxy = LedControl1Proxy::new(&conn).await?;
+            let brightness = proxy.brightness().await?;
+            let max = proxy.max_brightness().await?;
+            let (red, green, blue) = proxy.color().await?;
+            let pattern = proxy.pattern().await?;
+            let patterns = proxy.available_patterns().await?;
+            out.field(
+                "brightness",
+                format!("Brightness: {brightness}/{max}"),
+                brightness,
+            );
+            out.value("max_brightness", max);
+            out.field(
+                "color",
+                format!("Color: {red} {green} {blue}"),
+                json!([red, green, blue]),
+            );
+            out.field("pattern", format!("Pattern: {pattern}"), pattern);
+            out.field(
+                "available_patterns",
+                format!("Available patterns: {}", patterns.join(", ")),
+                patterns,
+            );
+        }
+        Commands::SetLedBrightness { brightness } => {
+            let proxy = LedControl1Proxy::new(&conn).await?;
+            proxy.set_brightness(*brightness).await?;
+        }
+        Commands::SetLedColor { red, green, blue } => {
+            let proxy = LedControl1Proxy::new(&conn).await?;
+            proxy.set_color(&(*red, *green, *blue)).await?;
+        }
+        Commands::SetLedPattern { pattern } => {
+            let proxy = LedControl1Proxy::new(&conn).await?;
+            proxy.set_pattern(pattern.as_str()).await?;
+        }
+        Commands::GetHaptics => {
+            let proxy = Haptics1Proxy::new(&conn).await?;
+            let intensity = proxy.haptics_intensity().await?;
+            out.field("intensity", format!("Intensity: {intensity}"), intensity);
+            match proxy.haptic_feedback().await {
+                Ok(enabled) => out.field(
+                    "haptic_feedback",
+                    format!("Haptic feedback: {enabled}"),
+                    enabled,
+                ),
+                Err(_) => out.field(
+                    "haptic_feedback",
+                    "Haptic feedback: unsupported",
+                    JsonValue::Null,
+                ),
             }
         }
+        Commands::SetHapticsIntensity { intensity } => {
+            let proxy = Haptics1Proxy::new(&conn).await?;
+            proxy.set_haptics_intensity(*intensity).await?;
+        }
+        Commands::SetHapticFeedback { enabled } => {
+            let proxy = Haptics1Proxy::new(&conn).await?;
+            proxy.set_haptic_feedback(*enabled).await?;
+        }
         Commands::GetAvailableCpuScalingGovernors => {
             let proxy = CpuScaling1Proxy::new(&conn).await?;
             let governors = proxy.available_cpu_scaling_governors().await?;
-            println!("Governors:\n");
-            for name in governors {
-                println!("{name}");
+            out.text("Governors:\n");
+            for name in &governors {
+                out.text(name);
             }
+            out.value("governors", governors);
         }
         Commands::GetCpuScalingGovernor => {
             let proxy = CpuScaling1Proxy::new(&conn).await?;
@@ -422,10 +1754,18 @@ async fn main() -> Result<()> {
             let governor_type = CPUScalingGovernor::try_from(governor.as_str());
             match governor_type {
                 Ok(_) => {
-                    println!("CPU Governor: {governor}");
+                    out.field(
+                        "cpu_governor",
+                        format!("CPU Governor: {governor}"),
+                        governor,
+                    );
                 }
                 Err(_) => {
-                    println!("Unknown CPU governor or unable to get type from {governor}");
+                    out.field(
+                        "cpu_governor",
+                        format!("Unknown CPU governor or unable to get type from {governor}"),
+                        governor,
+                    );
                 }
             }
         }
@@ -435,30 +1775,215 @@ async fn main() -> Result<()> {
                 .set_cpu_scaling_governor(governor.to_string().as_str())
                 .await?;
         }
+        Commands::GetCpuScalingGovernors => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            let governors = proxy.cpu_scaling_governors().await?;
+            for (policy, governor) in governors.iter().sorted() {
+                out.text(format!("policy{policy}: {governor}"));
+            }
+            out.value(
+                "governors",
+                governors
+                    .into_iter()
+                    .map(|(policy, governor)| {
+                        (format!("policy{policy}"), JsonValue::from(governor))
+                    })
+                    .collect::<Map<_, _>>(),
+            );
+        }
+        Commands::SetCpuScalingGovernorForPolicy { policy, governor } => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            proxy
+                .set_cpu_scaling_governor_for_policy(*policy, governor.to_string().as_str())
+                .await?;
+        }
+        Commands::GetAvailableEnergyPerformancePreferences => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            let preferences = proxy.available_energy_performance_preferences().await?;
+            out.text("Preferences:\n");
+            for name in &preferences {
+                out.text(name);
+            }
+            out.value("preferences", preferences);
+        }
+        Commands::GetEnergyPerformancePreference => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            let preference = proxy.energy_performance_preference().await?;
+            out.field(
+                "energy_performance_preference",
+                format!("Energy performance preference: {preference}"),
+                preference,
+            );
+        }
+        Commands::SetEnergyPerformancePreference { preference } => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            proxy
+                .set_energy_performance_preference(preference.to_string().as_str())
+                .await?;
+        }
+        Commands::GetOnlineCpuCount => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            let count = proxy.online_cpu_count().await?;
+            let min = proxy.min_online_cpu_count().await?;
+            let max = proxy.max_online_cpu_count().await?;
+            out.field(
+                "online_cpu_count",
+                format!("Online CPUs: {count} ({min}-{max})"),
+                count,
+            );
+            out.value("min_online_cpu_count", min);
+            out.value("max_online_cpu_count", max);
+        }
+        Commands::SetOnlineCpuCount { count } => {
+            let proxy = CpuScaling1Proxy::new(&conn).await?;
+            proxy.set_online_cpu_count(*count).await?;
+        }
+        Commands::GetCpuFrequencyRange => {
+            let proxy = CpuFrequency1Proxy::new(&conn).await?;
+            let min = proxy.hardware_min_frequency().await?;
+            let max = proxy.hardware_max_frequency().await?;
+            out.field(
+                "hardware_min_frequency",
+                format!("CPU frequency range: {min} - {max} MHz"),
+                min,
+            );
+            out.value("hardware_max_frequency", max);
+        }
+        Commands::GetAvailableCpuFrequencies => {
+            let proxy = CpuFrequency1Proxy::new(&conn).await?;
+            let frequencies = proxy.available_frequencies().await?;
+            out.text("Frequencies:\n");
+            for freq in &frequencies {
+                out.text(format!("- {freq} MHz"));
+            }
+            out.value("frequencies", frequencies);
+        }
+        Commands::GetCpuFrequencyLimits => {
+            let proxy = CpuFrequency1Proxy::new(&conn).await?;
+            let min = proxy.min_frequency().await?;
+            let max = proxy.max_frequency().await?;
+            out.field(
+                "min_cpu_frequency",
+                format!("Min CPU frequency: {min} MHz"),
+                min,
+            );
+            out.field(
+                "max_cpu_frequency",
+                format!("Max CPU frequency: {max} MHz"),
+                max,
+            );
+        }
+        Commands::SetMinCpuFrequency { freq } => {
+            let proxy = CpuFrequency1Proxy::new(&conn).await?;
+            proxy.set_min_frequency(*freq).await?;
+        }
+        Commands::SetMaxCpuFrequency { freq } => {
+            let proxy = CpuFrequency1Proxy::new(&conn).await?;
+            proxy.set_max_frequency(*freq).await?;
+        }
         Commands::GetCpuBoostState => {
             let proxy = CpuBoost1Proxy::new(&conn).await?;
             let state = proxy.cpu_boost_state().await?;
             match CPUBoostState::try_from(state) {
-                Ok(s) => println!("CPU Boost State: {s}"),
-                Err(_) => println!("Got unknown value {state} from backend"),
+                Ok(s) => out.field(
+                    "cpu_boost_state",
+                    format!("CPU Boost State: {s}"),
+                    s.to_string(),
+                ),
+                Err(_) => out.field(
+                    "cpu_boost_state",
+                    format!("Got unknown value {state} from backend"),
+                    state,
+                ),
             }
         }
         Commands::SetCpuBoostState { state } => {
             let proxy = CpuBoost1Proxy::new(&conn).await?;
             proxy.set_cpu_boost_state(*state as u32).await?;
         }
+        Commands::GetSmtEnabled => {
+            let proxy = Smt1Proxy::new(&conn).await?;
+            let enabled = proxy.smt_enabled().await?;
+            out.field("smt_enabled", format!("SMT enabled: {enabled}"), enabled);
+        }
+        Commands::SetSmtEnabled { enabled } => {
+            let proxy = Smt1Proxy::new(&conn).await?;
+            proxy.set_smt_enabled(*enabled).await?;
+        }
+        Commands::GetSleepConfig => {
+            let proxy = SleepConfig1Proxy::new(&conn).await?;
+            let modes = proxy.supported_sleep_modes().await?;
+            out.field(
+                "supported_sleep_modes",
+                format!("Supported sleep modes: {}", modes.join(", ")),
+                modes,
+            );
+            let mode = proxy.sleep_mode().await?;
+            out.field("sleep_mode", format!("Sleep mode: {mode}"), mode);
+            let hibernation = proxy.hibernation_enabled().await?;
+            out.field(
+                "hibernation_enabled",
+                format!("Hibernation enabled: {hibernation}"),
+                hibernation,
+            );
+            let suspend_then_hibernate = proxy.suspend_then_hibernate_enabled().await?;
+            out.field(
+                "suspend_then_hibernate_enabled",
+                format!("Suspend-then-hibernate enabled: {suspend_then_hibernate}"),
+                suspend_then_hibernate,
+            );
+            let delay = proxy.hibernate_delay().await?;
+            let text = if delay == 0 {
+                String::from("Hibernate delay: default")
+            } else {
+                format!("Hibernate delay: {delay}s")
+            };
+            out.field("hibernate_delay", text, delay);
+            let adequate = proxy.swap_adequate().await?;
+            out.field(
+                "swap_adequate",
+                format!("Enough swap to hibernate: {adequate}"),
+                adequate,
+            );
+        }
+        Commands::SetSleepMode { mode } => {
+            let proxy = SleepConfig1Proxy::new(&conn).await?;
+            proxy.set_sleep_mode(mode.as_str()).await?;
+        }
+        Commands::SetHibernationEnabled { enabled } => {
+            let proxy = SleepConfig1Proxy::new(&conn).await?;
+            proxy.set_hibernation_enabled(*enabled).await?;
+        }
+        Commands::SetSuspendThenHibernateEnabled { enabled } => {
+            let proxy = SleepConfig1Proxy::new(&conn).await?;
+            proxy.set_suspend_then_hibernate_enabled(*enabled).await?;
+        }
+        Commands::SetHibernateDelay { delay } => {
+            let proxy = SleepConfig1Proxy::new(&conn).await?;
+            proxy.set_hibernate_delay(*delay).await?;
+        }
         Commands::GetAvailableGPUPowerProfiles => {
             let proxy = GpuPowerProfile1Proxy::new(&conn).await?;
-            let profiles = proxy.available_gpu_power_profiles().await?;
-            println!("Profiles:\n");
-            for name in profiles.into_iter().sorted() {
-                println!("- {name}");
+            let profiles: Vec<_> = proxy
+                .available_gpu_power_profiles()
+                .await?
+                .into_iter()
+                .sorted()
+                .collect();
+            out.text("Profiles:\n");
+            for name in &profiles {
+                out.text(format!("- {name}"));
             }
+            out.value("profiles", profiles);
         }
         Commands::GetGPUPowerProfile => {
             let proxy = GpuPowerProfile1Proxy::new(&conn).await?;
             let profile = proxy.gpu_power_profile().await?;
-            println!("GPU Power Profile: {profile}");
+            out.field(
+                "gpu_power_profile",
+                format!("GPU Power Profile: {profile}"),
+                profile,
+            );
         }
         Commands::SetGPUPowerProfile { profile } => {
             let proxy = GpuPowerProfile1Proxy::new(&conn).await?;
@@ -475,7 +2000,11 @@ async fn main() -> Result<()> {
         Commands::GetGPUPerformanceLevel => {
             let proxy = GpuPerformanceLevel1Proxy::new(&conn).await?;
             let level = proxy.gpu_performance_level().await?;
-            println!("GPU performance level: {level}");
+            out.field(
+                "gpu_performance_level",
+                format!("GPU performance level: {level}"),
+                level,
+            );
         }
         Commands::SetManualGPUClock { freq } => {
             let proxy = GpuPerformanceLevel1Proxy::new(&conn).await?;
@@ -484,30 +2013,87 @@ async fn main() -> Result<()> {
         Commands::GetManualGPUClock => {
             let proxy = GpuPerformanceLevel1Proxy::new(&conn).await?;
             let clock = proxy.manual_gpu_clock().await?;
-            println!("Manual GPU Clock: {clock}");
+            out.field(
+                "manual_gpu_clock",
+                format!("Manual GPU Clock: {clock}"),
+                clock,
+            );
         }
         Commands::GetManualGPUClockMax => {
             let proxy = GpuPerformanceLevel1Proxy::new(&conn).await?;
             let value = proxy.manual_gpu_clock_max().await?;
-            println!("Manual GPU Clock Max: {value}");
+            out.field(
+                "manual_gpu_clock_max",
+                format!("Manual GPU Clock Max: {value}"),
+                value,
+            );
         }
         Commands::GetManualGPUClockMin => {
             let proxy = GpuPerformanceLevel1Proxy::new(&conn).await?;
             let value = proxy.manual_gpu_clock_min().await?;
-            println!("Manual GPU Clock Min: {value}");
+            out.field(
+                "manual_gpu_clock_min",
+                format!("Manual GPU Clock Min: {value}"),
+                value,
+            );
+        }
+        Commands::GetGPUMetrics => {
+            let proxy = GpuMetrics1Proxy::new(&conn).await?;
+            let metrics = proxy.get_metrics().await?;
+            for (name, value) in metrics.iter().sorted() {
+                out.field(name, format!("{name}: {value}"), *value);
+            }
+        }
+        Commands::GetGPUPower => {
+            let proxy = GpuPower1Proxy::new(&conn).await?;
+            let average = proxy.power_average().await?;
+            let cap = proxy.power_cap().await?;
+            let min = proxy.power_cap_min().await?;
+            let max = proxy.power_cap_max().await?;
+            out.field(
+                "power_average",
+                format!("GPU power: {average:.2} W"),
+                average,
+            );
+            out.field(
+                "power_cap",
+                format!("GPU power cap: {cap:.2} W ({min:.2} W - {max:.2} W)"),
+                cap,
+            );
+            out.value("power_cap_min", min);
+            out.value("power_cap_max", max);
+        }
+        Commands::GetGPUThermalThrottling => {
+            let proxy = GpuThrottle1Proxy::new(&conn).await?;
+            let throttling = proxy.thermal_throttling().await?;
+            out.field(
+                "thermal_throttling",
+                format!("GPU thermal throttling: {throttling}"),
+                throttling,
+            );
         }
         Commands::GetAvailablePerformanceProfiles => {
             let proxy = PerformanceProfile1Proxy::new(&conn).await?;
-            let profiles = proxy.available_performance_profiles().await?;
-            println!("Profiles:\n");
-            for name in profiles.into_iter().sorted() {
-                println!("- {name}");
+            let profiles: Vec<_> = proxy
+                .available_performance_profiles()
+                .await?
+                .into_iter()
+                .sorted()
+                .collect();
+            out.text("Profiles:\n");
+            for name in &profiles {
+                out.text(format!("- {name}"));
             }
+            out.value("profiles", profiles);
         }
         Commands::GetPerformanceProfile => {
             let proxy = PerformanceProfile1Proxy::new(&conn).await?;
             let profile = proxy.performance_profile().await?;
-            println!("Performance Profile: {profile}");
+            out.field(
+                "performance_profile",
+                format!("Performance Profile: {profile}"),
+                profile,
+            );
         }
         Commands::SetPerformanceProfile { profile } => {
             let proxy = PerformanceProfile1Proxy::new(&conn).await?;
@@ -516,7 +2102,116 @@ async fn main() -> Result<()> {
         Commands::SuggestedDefaultPerformanceProfile => {
             let proxy = PerformanceProfile1Proxy::new(&conn).await?;
             let profile = proxy.suggested_default_performance_profile().await?;
-            println!("Suggested Default Performance Profile: {profile}");
+            out.field(
+                "suggested_default_performance_profile",
+                format!("Suggested Default Performance Profile: {profile}"),
+                profile,
+            );
+        }
+        Commands::GetPowerHistory { since } => {
+            let proxy = PowerHistory1Proxy::new(&conn).await?;
+            let samples = proxy.get_samples(since.unwrap_or(0)).await?;
+            for (timestamp, battery, tdp, cpu, gpu) in &samples {
+                out.text(format!("{timestamp}: battery {battery:.2} W, TDP {tdp} W, CPU {cpu} MHz, GPU {gpu} MHz"));
+            }
+            out.value(
+                "samples",
+                samples
+                    .iter()
+                    .map(|(timestamp, battery, tdp, cpu, gpu)| {
+                        json!({
+                            "timestamp": timestamp,
+                            "battery_power": battery,
+                            "tdp_limit": tdp,
+                            "cpu_frequency": cpu,
+                            "gpu_frequency": gpu,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::GetPowerPresets => {
+            let proxy = PowerPresets1Proxy::new(&conn).await?;
+            let presets = proxy.available_presets().await?;
+            out.field(
+                "available_presets",
+                format!("Available presets: {}", presets.join(", ")),
+                presets,
+            );
+            let current = proxy.current_preset().await?;
+            out.field(
+                "current_preset",
+                format!("Current preset: {current}"),
+                current,
+            );
+        }
+        Commands::ApplyPowerPreset { name } => {
+            let proxy = PowerPresets1Proxy::new(&conn).await?;
+            proxy.apply_preset(name.as_str()).await?;
+        }
+        Commands::SavePowerPreset { name } => {
+            let proxy = PowerPresets1Proxy::new(&conn).await?;
+            proxy.save_preset(name.as_str()).await?;
+        }
+        Commands::GetOnBattery => {
+            let proxy = PowerSource1Proxy::new(&conn).await?;
+            let on_battery = proxy.on_battery().await?;
+            out.field(
+                "on_battery",
+                format!("On battery: {on_battery}"),
+                on_battery,
+            );
+        }
+        Commands::GetChargerInfo => {
+            let proxy = PowerSource1Proxy::new(&conn).await?;
+            let usb_type = proxy.charger_type().await?;
+            let voltage = proxy.charger_voltage().await?;
+            let current = proxy.charger_current().await?;
+            let power = proxy.charger_power().await?;
+            if voltage == 0 && current == 0 {
+                out.text("No charger information available");
+            } else {
+                let label = if usb_type.is_empty() {
+                    "unknown"
+                } else {
+                    usb_type.as_str()
+                };
+                out.text(format!("Charger type: {label}"));
+                out.text(format!("Voltage: {voltage} mV"));
+                out.text(format!("Current: {current} mA"));
+                out.text(format!("Power: {power} mW"));
+            }
+            out.value("charger_type", usb_type);
+            out.value("voltage", voltage);
+            out.value("current", current);
+            out.value("power", power);
+        }
+        Commands::GetJobHistory { count } => {
+            let proxy = JobHistory1Proxy::new(&conn).await?;
+            let jobs = proxy.get_job_history(count.unwrap_or(10)).await?;
+            for (operation, start, end, exit_code, status, output) in &jobs {
+                out.text(format!(
+                    "{start}-{end}: {operation}, exit code {exit_code}, status {status}"
+                ));
+                for line in output.lines() {
+                    out.text(format!("    {line}"));
+                }
+            }
+            out.value(
+                "jobs",
+                jobs.iter()
+                    .map(|(operation, start, end, exit_code, status, output)| {
+                        json!({
+                            "operation": operation,
+                            "start": start,
+                            "end": end,
+                            "exit_code": exit_code,
+                            "status": status,
+                            "output": output,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
         }
         Commands::SetTDPLimit { limit } => {
             let proxy = TdpLimit1Proxy::new(&conn).await?;
@@ -525,17 +2220,108 @@ async fn main() -> Result<()> {
         Commands::GetTDPLimit => {
             let proxy = TdpLimit1Proxy::new(&conn).await?;
             let limit = proxy.tdp_limit().await?;
-            println!("TDP limit: {limit}");
+            out.field("tdp_limit", format!("TDP limit: {limit}"), limit);
         }
         Commands::GetTDPLimitMax => {
             let proxy = TdpLimit1Proxy::new(&conn).await?;
             let value = proxy.tdp_limit_max().await?;
-            println!("TDP limit max: {value}");
+            out.field("tdp_limit_max", format!("TDP limit max: {value}"), value);
         }
         Commands::GetTDPLimitMin => {
             let proxy = TdpLimit1Proxy::new(&conn).await?;
             let value = proxy.tdp_limit_min().await?;
-            println!("TDP limit min: {value}");
+            out.field("tdp_limit_min", format!("TDP limit min: {value}"), value);
+        }
+        Commands::SetTDPLimits { spl, sppt, fppt } => {
+            let proxy = TdpLimit1Proxy::new(&conn).await?;
+            proxy.set_tdp_limits(*spl, *sppt, *fppt).await?;
+        }
+        Commands::GetTDPLimits => {
+            let proxy = TdpLimit1Proxy::new(&conn).await?;
+            if proxy.separate_tdp_limits().await? {
+                let limits = proxy.tdp_limits().await?;
+                let min = proxy.tdp_limits_min().await?;
+                let max = proxy.tdp_limits_max().await?;
+                for (key, label, limit, min, max) in [
+                    ("spl", "SPL", limits.0, min.0, max.0),
+                    ("sppt", "SPPT", limits.1, min.1, max.1),
+                    ("fppt", "FPPT", limits.2, min.2, max.2),
+                ] {
+                    out.field(
+                        key,
+                        format!("{label}: {limit} ({min}-{max})"),
+                        json!({ "limit": limit, "min": min, "max": max }),
+                    );
+                }
+            } else {
+                out.field(
+                    "separate_tdp_limits",
+                    "Separate TDP limits are not supported",
+                    false,
+                );
+            }
+        }
+        Commands::SetTDPProfile { app_id, limit } => {
+            let proxy = TdpProfiles1Proxy::new(&conn).await?;
+            proxy.set_profile_for_app(*app_id, *limit).await?;
+        }
+        Commands::ClearTDPProfile { app_id } => {
+            let proxy = TdpProfiles1Proxy::new(&conn).await?;
+            proxy.clear_profile_for_app(*app_id).await?;
+        }
+        Commands::ListTDPProfiles => {
+            let proxy = TdpProfiles1Proxy::new(&conn).await?;
+            let profiles = proxy.list_profiles().await?;
+            for (app_id, limit) in profiles.iter().sorted() {
+                out.text(format!("{app_id}: {limit}"));
+            }
+            out.value(
+                "profiles",
+                profiles
+                    .into_iter()
+                    .map(|(app_id, limit)| (app_id.to_string(), JsonValue::from(limit)))
+                    .collect::<Map<_, _>>(),
+            );
+        }
+        Commands::SetForegroundApp { app_id } => {
+            let proxy = TdpProfiles1Proxy::new(&conn).await?;
+            proxy.set_foreground_app(*app_id).await?;
+        }
+        Commands::TypeText { text } => {
+            let proxy = TextInput1Proxy::new(&conn).await?;
+            proxy.type_text(text.as_str()).await?;
+        }
+        Commands::SendKeyCombo { keys } => {
+            let proxy = TextInput1Proxy::new(&conn).await?;
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            proxy.send_key_combo(keys.as_slice()).await?;
+        }
+        Commands::GetTemperatures => {
+            let proxy = Thermal1Proxy::new(&conn).await?;
+            for (key, label, temperature) in [
+                ("cpu", "CPU", proxy.cpu_temperature().await?),
+                ("gpu", "GPU", proxy.gpu_temperature().await?),
+                ("battery", "Battery", proxy.battery_temperature().await?),
+                ("ssd", "SSD", proxy.ssd_temperature().await?),
+            ] {
+                out.field(key, format!("{label}: {temperature:.1}°C"), temperature);
+            }
+        }
+        Commands::ListThermalSensors => {
+            let proxy = Thermal1Proxy::new(&conn).await?;
+            let sensors = proxy.list_sensors().await?;
+            for (name, label, value) in &sensors {
+                out.text(format!("{name} {label}: {value:.1}°C"));
+            }
+            out.value(
+                "sensors",
+                sensors
+                    .iter()
+                    .map(|(name, label, value)| {
+                        json!({ "name": name, "label": label, "temperature": value })
+                    })
+                    .collect::<Vec<_>>(),
+            );
         }
         Commands::SetWifiBackend { backend } => {
             let proxy = WifiDebug1Proxy::new(&conn).await?;
@@ -545,8 +2331,16 @@ async fn main() -> Result<()> {
             let proxy = WifiDebug1Proxy::new(&conn).await?;
             let backend = proxy.wifi_backend().await?;
             match WifiBackend::try_from(backend.as_str()) {
-                Ok(be) => println!("Wi-Fi backend: {be}"),
-                Err(_) => println!("Got unknown value {backend} from backend"),
+                Ok(be) => out.field(
+                    "wifi_backend",
+                    format!("Wi-Fi backend: {be}"),
+                    be.to_string(),
+                ),
+                Err(_) => out.field(
+                    "wifi_backend",
+                    format!("Got unknown value {backend} from backend"),
+                    backend,
+                ),
             }
         }
         Commands::SetWifiDebugMode { mode, buffer } => {
@@ -563,14 +2357,22 @@ async fn main() -> Result<()> {
             let proxy = WifiDebug1Proxy::new(&conn).await?;
             let mode = proxy.wifi_debug_mode_state().await?;
             match WifiDebugMode::try_from(mode) {
-                Ok(m) => println!("Wi-Fi debug mode: {m}"),
-                Err(_) => println!("Got unknown value {mode} from backend"),
+                Ok(m) => out.field(
+                    "wifi_debug_mode",
+                    format!("Wi-Fi debug mode: {m}"),
+                    m.to_string(),
+                ),
+                Err(_) => out.field(
+                    "wifi_debug_mode",
+                    format!("Got unknown value {mode} from backend"),
+                    mode,
+                ),
             }
         }
         Commands::CaptureWifiDebugTraceOutput => {
             let proxy = WifiDebugDump1Proxy::new(&conn).await?;
             let path = proxy.generate_debug_dump().await?;
-            println!("{path}");
+            out.field("path", &path, path.as_str());
         }
         Commands::SetWifiPowerManagementState { state } => {
             let proxy = WifiPowerManagement1Proxy::new(&conn).await?;
@@ -580,14 +2382,54 @@ async fn main() -> Result<()> {
             let proxy = WifiPowerManagement1Proxy::new(&conn).await?;
             let state = proxy.wifi_power_management_state().await?;
             match WifiPowerManagement::try_from(state) {
-                Ok(s) => println!("Wi-Fi power management state: {s}"),
-                Err(_) => println!("Got unknown value {state} from backend"),
+                Ok(s) => out.field(
+                    "wifi_power_management_state",
+                    format!("Wi-Fi power management state: {s}"),
+                    s.to_string(),
+                ),
+                Err(_) => out.field(
+                    "wifi_power_management_state",
+                    format!("Got unknown value {state} from backend"),
+                    state,
+                ),
             }
         }
         Commands::GenerateWifiDebugDump => {
             let proxy = WifiDebugDump1Proxy::new(&conn).await?;
             let path = proxy.generate_debug_dump().await?;
-            println!("{path}");
+            out.field("path", &path, path.as_str());
+        }
+        Commands::GetWifiInfo => {
+            let proxy = WifiInfo1Proxy::new(&conn).await?;
+            let ssid = proxy.ssid().await?;
+            if ssid.is_empty() {
+                out.field("connected", "Wi-Fi is not connected", false);
+            } else {
+                let bssid = proxy.bssid().await?;
+                let frequency = proxy.frequency().await?;
+                let signal = proxy.signal().await?;
+                let tx_bitrate = proxy.tx_bitrate().await?;
+                let rx_bitrate = proxy.rx_bitrate().await?;
+                out.value("connected", true);
+                out.field("ssid", format!("SSID: {ssid}"), ssid);
+                out.field("bssid", format!("BSSID: {bssid}"), bssid);
+                out.field(
+                    "frequency",
+                    format!("Frequency: {frequency} MHz"),
+                    frequency,
+                );
+                out.field("signal", format!("Signal: {signal} dBm"), signal);
+                out.field(
+                    "tx_bitrate",
+                    format!("TX bitrate: {tx_bitrate} Mbit/s"),
+                    tx_bitrate,
+                );
+                out.field(
+                    "rx_bitrate",
+                    format!("RX bitrate: {rx_bitrate} Mbit/s"),
+                    rx_bitrate,
+                );
+            }
         }
         Commands::SetHdmiCecState { state } => {
             let proxy = HdmiCec1Proxy::new(&conn).await?;
@@ -597,16 +2439,269 @@ async fn main() -> Result<()> {
             let proxy = HdmiCec1Proxy::new(&conn).await?;
             let state = proxy.hdmi_cec_state().await?;
             match HdmiCecState::try_from(state) {
-                Ok(s) => println!("HDMI-CEC state: {}", s.to_human_readable()),
-                Err(_) => println!("Got unknown value {state} from backend"),
+                Ok(s) => out.field(
+                    "hdmi_cec_state",
+                    format!("HDMI-CEC state: {}", s.to_human_readable()),
+                    s.to_human_readable(),
+                ),
+                Err(_) => out.field(
+                    "hdmi_cec_state",
+                    format!("Got unknown value {state} from backend"),
+                    state,
+                ),
+            }
+        }
+        Commands::PowerOnTv => {
+            let proxy = HdmiCec1Proxy::new(&conn).await?;
+            proxy.power_on_tv().await?;
+        }
+        Commands::StandbyTv => {
+            let proxy = HdmiCec1Proxy::new(&conn).await?;
+            proxy.standby_tv().await?;
+        }
+        Commands::SetActiveSource => {
+            let proxy = HdmiCec1Proxy::new(&conn).await?;
+            proxy.set_active_source().await?;
+        }
+        Commands::ListCecDevices => {
+            let proxy = HdmiCec1Proxy::new(&conn).await?;
+            let devices = proxy.list_cec_devices().await?;
+            for (address, name, vendor, power_status) in &devices {
+                out.text(format!(
+                    "{address}: {name}, vendor {vendor}, power {power_status}"
+                ));
             }
+            out.value(
+                "devices",
+                devices
+                    .iter()
+                    .map(|(address, name, vendor, power_status)| {
+                        json!({
+                            "address": address,
+                            "name": name,
+                            "vendor": vendor,
+                            "power_status": power_status,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
         }
         Commands::ListLowPowerDownloadModeHandles => {
             let proxy = LowPowerMode1Proxy::new(&conn).await?;
             let handles: HashMap<String, u32> = proxy.list_download_mode_handles().await?;
-            for (identifier, count) in handles.into_iter().sorted() {
-                println!("{identifier}: {count}");
+            for (identifier, count) in handles.iter().sorted() {
+                out.text(format!("{identifier}: {count}"));
+            }
+            out.value("handles", json!(handles));
+        }
+        Commands::ListSysfsAttributes => {
+            let proxy = SysfsAccess1Proxy::new(&conn).await?;
+            let attributes = proxy.list_attributes().await?;
+            for (name, writable) in attributes.iter().sorted() {
+                let access = if *writable { "read-write" } else { "read-only" };
+                out.text(format!("{name}: {access}"));
+            }
+            out.value("attributes", json!(attributes));
+        }
+        Commands::ReadSysfsAttribute { name } => {
+            let proxy = SysfsAccess1Proxy::new(&conn).await?;
+            let value = proxy.read_attribute(name.as_str()).await?;
+            out.field("value", &value, value.as_str());
+        }
+        Commands::WriteSysfsAttribute { name, value } => {
+            let proxy = SysfsAccess1Proxy::new(&conn).await?;
+            proxy.write_attribute(name.as_str(), value.as_str()).await?;
+        }
+        Commands::ListServices => {
+            let proxy = Services1Proxy::new(&conn).await?;
+            let units = proxy.list_units().await?;
+            for (unit, active, unit_file) in &units {
+                out.text(format!("{unit}: {active}, {unit_file}"));
+            }
+            out.value(
+                "units",
+                units
+                    .iter()
+                    .map(|(unit, active, unit_file)| {
+                        json!({
+                            "unit": unit,
+                            "active_state": active,
+                            "unit_file_state": unit_file,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::StartService { unit } => {
+            let proxy = Services1Proxy::new(&conn).await?;
+            proxy.start_unit(unit.as_str()).await?;
+        }
+        Commands::StopService { unit } => {
+            let proxy = Services1Proxy::new(&conn).await?;
+            proxy.stop_unit(unit.as_str()).await?;
+        }
+        Commands::RestartService { unit } => {
+            let proxy = Services1Proxy::new(&conn).await?;
+            proxy.restart_unit(unit.as_str()).await?;
+        }
+        Commands::SetServiceEnabled { unit, enabled } => {
+            let proxy = Services1Proxy::new(&conn).await?;
+            proxy.set_unit_enabled(unit.as_str(), *enabled).await?;
+        }
+        Commands::ListUsbDevices => {
+            let proxy = UsbAuthorization1Proxy::new(&conn).await?;
+            let devices = proxy.list_devices().await?;
+            for (name, id, product, authorized) in &devices {
+                let authorized = if *authorized {
+                    "authorized"
+                } else {
+                    "not authorized"
+                };
+                if product.is_empty() {
+                    out.text(format!("{name}: {id}, {authorized}"));
+                } else {
+                    out.text(format!("{name}: {id} {product}, {authorized}"));
+                }
+            }
+            out.value(
+                "devices",
+                devices
+                    .iter()
+                    .map(|(name, id, product, authorized)| {
+                        json!({
+                            "name": name,
+                            "id": id,
+                            "product": product,
+                            "authorized": authorized,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::GetUsbAuthorizedDefault => {
+            let proxy = UsbAuthorization1Proxy::new(&conn).await?;
+            let authorized = proxy.authorized_default().await?;
+            out.field(
+                "usb_devices_authorized_by_default",
+                format!("USB devices authorized by default: {authorized}"),
+                authorized,
+            );
+        }
+        Commands::SetUsbAuthorizedDefault { authorized } => {
+            let proxy = UsbAuthorization1Proxy::new(&conn).await?;
+            proxy.set_authorized_default(*authorized).await?;
+        }
+        Commands::AuthorizeUsbDevice { device } => {
+            let proxy = UsbAuthorization1Proxy::new(&conn).await?;
+            proxy.authorize_device(device.as_str()).await?;
+        }
+        Commands::DeauthorizeUsbDevice { device } => {
+            let proxy = UsbAuthorization1Proxy::new(&conn).await?;
+            proxy.deauthorize_device(device.as_str()).await?;
+        }
+        Commands::GetWakeSources => {
+            let proxy = WakeSources1Proxy::new(&conn).await?;
+            let available = proxy.available_wake_sources().await?;
+            let enabled = proxy.enabled_wake_sources().await?;
+            let sources: HashMap<String, bool> = available
+                .into_iter()
+                .map(|source| {
+                    let enabled = enabled.contains(&source);
+                    (source, enabled)
+                })
+                .collect();
+            for (source, enabled) in sources.iter().sorted() {
+                let enabled = if *enabled { "enabled" } else { "disabled" };
+                out.text(format!("{source}: {enabled}"));
+            }
+            out.value("wake_sources", json!(sources));
+        }
+        Commands::SetWakeSourceEnabled { source, enabled } => {
+            let proxy = WakeSources1Proxy::new(&conn).await?;
+            proxy
+                .set_wake_source_enabled(source.as_str(), *enabled)
+                .await?;
+        }
+        Commands::ListInhibitedInputDevices => {
+            let proxy = InputInhibit1Proxy::new(&conn).await?;
+            let devices = proxy.list_inhibited_devices().await?;
+            for device in &devices {
+                out.text(device);
             }
+            out.value("devices", devices);
+        }
+        Commands::ListNetworkLimitHandles => {
+            let proxy = NetworkLimit1Proxy::new(&conn).await?;
+            let handles: HashMap<String, (u32, u32)> = proxy.list_bandwidth_limit_handles().await?;
+            for (identifier, (download, upload)) in handles.iter().sorted() {
+                out.text(format!(
+                    "{identifier}: {download} kbit/s down, {upload} kbit/s up"
+                ));
+            }
+            out.value(
+                "handles",
+                handles
+                    .into_iter()
+                    .map(|(identifier, (download, upload))| {
+                        (
+                            identifier,
+                            json!({ "download": download, "upload": upload }),
+                        )
+                    })
+                    .collect::<Map<_, _>>(),
+            );
+        }
+        Commands::ListSuspendInhibitHandles => {
+            let proxy = SuspendInhibit1Proxy::new(&conn).await?;
+            let handles: HashMap<String, u32> = proxy.list_inhibit_handles().await?;
+            for (identifier, count) in handles.iter().sorted() {
+                out.text(format!("{identifier}: {count}"));
+            }
+            out.value("handles", json!(handles));
+        }
+        Commands::GetDockStatus => {
+            let proxy = Dock1Proxy::new(&conn).await?;
+            if proxy.connected().await? {
+                let model = proxy.model().await?;
+                let firmware_version = proxy.firmware_version().await?;
+                let usb_tier = proxy.usb_tier().await?;
+                let display_count = proxy.display_count().await?;
+                out.value("connected", true);
+                out.field("model", format!("Dock: {model}"), model);
+                out.field(
+                    "firmware_version",
+                    format!("Firmware version: {firmware_version}"),
+                    firmware_version,
+                );
+                out.field("usb_tier", format!("USB tier: {usb_tier}"), usb_tier);
+                out.field(
+                    "display_count",
+                    format!("External displays: {display_count}"),
+                    display_count,
+                );
+            } else {
+                out.field("connected", "No dock connected", false);
+            }
+        }
+        Commands::GetFirmwareInfo => {
+            let proxy = FirmwareInfo1Proxy::new(&conn).await?;
+            let bios_version = proxy.bios_version().await?;
+            let dock_firmware_version = proxy.dock_firmware_version().await?;
+            out.field(
+                "bios_version",
+                format!("BIOS version: {bios_version}"),
+                bios_version,
+            );
+            out.field(
+                "dock_firmware_version",
+                format!("Dock firmware version: {dock_firmware_version}"),
+                dock_firmware_version,
+            );
+            let controllers = proxy.controller_firmware_versions().await?;
+            for (name, version) in controllers.iter().sorted() {
+                out.text(format!("Controller firmware version ({name}): {version}"));
+            }
+            out.value("controller_firmware_versions", json!(controllers));
         }
         Commands::UpdateBios => {
             let proxy = UpdateBios1Proxy::new(&conn).await?;
@@ -616,42 +2711,648 @@ async fn main() -> Result<()> {
             let proxy = UpdateDock1Proxy::new(&conn).await?;
             let _ = proxy.update_dock().await?;
         }
+        Commands::UpdateController => {
+            let proxy = UpdateController1Proxy::new(&conn).await?;
+            let _ = proxy.update_controller().await?;
+        }
+        Commands::ScheduleUpdateBios => {
+            let proxy = UpdateBios1Proxy::new(&conn).await?;
+            proxy.schedule_update_bios().await?;
+        }
+        Commands::ScheduleUpdateDock => {
+            let proxy = UpdateDock1Proxy::new(&conn).await?;
+            proxy.schedule_update_dock().await?;
+        }
+        Commands::CancelScheduledUpdateBios => {
+            let proxy = UpdateBios1Proxy::new(&conn).await?;
+            proxy.cancel_scheduled_update_bios().await?;
+        }
+        Commands::CancelScheduledUpdateDock => {
+            let proxy = UpdateDock1Proxy::new(&conn).await?;
+            proxy.cancel_scheduled_update_dock().await?;
+        }
+        Commands::GetScheduledUpdates => {
+            let bios = UpdateBios1Proxy::new(&conn)
+                .await?
+                .update_scheduled()
+                .await?;
+            out.field("bios", format!("BIOS update scheduled: {bios}"), bios);
+            let dock = UpdateDock1Proxy::new(&conn)
+                .await?
+                .update_scheduled()
+                .await?;
+            out.field("dock", format!("Dock update scheduled: {dock}"), dock);
+        }
+        Commands::CheckForOsUpdates => {
+            let proxy = OsUpdate1Proxy::new(&conn).await?;
+            let updates = proxy.check_for_updates().await?;
+            if updates.is_empty() {
+                out.text("No updates available");
+            }
+            for (kind, version, buildid, size) in &updates {
+                if *size > 0 {
+                    out.text(format!("{kind}: {version} ({buildid}), {size} bytes"));
+                } else {
+                    out.text(format!("{kind}: {version} ({buildid})"));
+                }
+            }
+            out.value(
+                "updates",
+                updates
+                    .iter()
+                    .map(|(kind, version, buildid, size)| {
+                        json!({
+                            "kind": kind,
+                            "version": version,
+                            "buildid": buildid,
+                            "size": size,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::UpdateOs => {
+            let proxy = OsUpdate1Proxy::new(&conn).await?;
+            let _ = proxy.start_update().await?;
+        }
+        Commands::GetAvailableUpdateChannels => {
+            let proxy = OsUpdateChannel1Proxy::new(&conn).await?;
+            let channels = proxy.available_update_channels().await?;
+            out.text("Channels:\n");
+            for name in &channels {
+                out.text(format!("- {name}"));
+            }
+            out.value("channels", channels);
+        }
+        Commands::GetUpdateChannel => {
+            let proxy = OsUpdateChannel1Proxy::new(&conn).await?;
+            let channel = proxy.update_channel().await?;
+            out.field(
+                "update_channel",
+                format!("Update channel: {channel}"),
+                channel,
+            );
+        }
+        Commands::SetUpdateChannel { channel } => {
+            let proxy = OsUpdateChannel1Proxy::new(&conn).await?;
+            proxy.set_update_channel(channel.as_str()).await?;
+        }
+        Commands::GetRootFilesystemReadOnly => {
+            let system = Connection::system().await?;
+            let proxy = RootFilesystem1Proxy::new(&system).await?;
+            let read_only = proxy.read_only().await?;
+            out.field(
+                "root_filesystem_read_only",
+                format!("Root filesystem read-only: {read_only}"),
+                read_only,
+            );
+        }
+        Commands::SetRootFilesystemReadOnly { read_only } => {
+            let system = Connection::system().await?;
+            let proxy = RootFilesystem1Proxy::new(&system).await?;
+            proxy.set_read_only(*read_only).await?;
+        }
+        Commands::GetAuditLog => {
+            let system = Connection::system().await?;
+            let proxy = AuditLog1Proxy::new(&system).await?;
+            let entries = proxy.get_recent_audit_entries().await?;
+            for (timestamp, uid, pid, exe, sender, method, arguments, result) in &entries {
+                let process = if exe.is_empty() {
+                    String::new()
+                } else {
+                    format!(", {exe} pid {pid}")
+                };
+                out.text(format!(
+                    "{timestamp}: {method}({}) by uid {uid} ({sender}{process}): {result}",
+                    arguments.join(", ")
+                ));
+            }
+            out.value(
+                "entries",
+                entries
+                    .iter()
+                    .map(
+                        |(timestamp, uid, pid, exe, sender, method, arguments, result)| {
+                            json!({
+                                "timestamp": timestamp,
+                                "uid": uid,
+                                "pid": pid,
+                                "exe": exe,
+                                "sender": sender,
+                                "method": method,
+                                "arguments": arguments,
+                                "result": result,
+                            })
+                        },
+                    )
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::GetHeadlessSettings
+        | Commands::SetHeadlessTdpLimit { .. }
+        | Commands::SetHeadlessFanControlState { .. }
+        | Commands::SetHeadlessMaxChargeLevel { .. } => {
+            unreachable!("Headless commands are run before connecting to the session bus")
+        }
+        Commands::GetDeveloperMode => {
+            let proxy = DeveloperMode1Proxy::new(&conn).await?;
+            let ssh = proxy.ssh_enabled().await?;
+            let core_dumps = proxy.core_dumps_enabled().await?;
+            let journal = proxy.persistent_journal().await?;
+            out.field("ssh_enabled", format!("SSH server: {ssh}"), ssh);
+            out.field(
+                "core_dumps_enabled",
+                format!("Core dumps: {core_dumps}"),
+                core_dumps,
+            );
+            out.field(
+                "persistent_journal",
+                format!("Persistent journal: {journal}"),
+                journal,
+            );
+        }
+        Commands::SetSshEnabled { enabled } => {
+            let proxy = DeveloperMode1Proxy::new(&conn).await?;
+            proxy.set_ssh_enabled(*enabled).await?;
+        }
+        Commands::SetCoreDumpsEnabled { enabled } => {
+            let proxy = DeveloperMode1Proxy::new(&conn).await?;
+            proxy.set_core_dumps_enabled(*enabled).await?;
+        }
+        Commands::SetPersistentJournal { enabled } => {
+            let proxy = DeveloperMode1Proxy::new(&conn).await?;
+            proxy.set_persistent_journal(*enabled).await?;
+        }
         Commands::PrepareFactoryReset { kind } => {
             let proxy = FactoryReset1Proxy::new(&conn).await?;
             let _ = proxy.prepare_factory_reset(*kind as u32).await?;
         }
+        Commands::EstimateFactoryReset { kind } => {
+            let proxy = FactoryReset1Proxy::new(&conn).await?;
+            let (partitions, duration) = proxy.estimate_factory_reset(*kind as u32).await?;
+            out.field(
+                "partitions",
+                format!("Partitions: {}", partitions.join(", ")),
+                partitions,
+            );
+            out.field(
+                "estimated_duration",
+                format!("Estimated duration: {duration} seconds"),
+                duration,
+            );
+        }
         Commands::TrimDevices => {
             let proxy = Storage1Proxy::new(&conn).await?;
             let _ = proxy.trim_devices().await?;
         }
+        Commands::GetStorageDeviceInfo { device } => {
+            let proxy = Storage1Proxy::new(&conn).await?;
+            let (capacity, vendor, model, removable, speed) =
+                proxy.get_device_info(device.as_str()).await?;
+            out.field("capacity", format!("Capacity: {capacity} bytes"), capacity);
+            out.field("vendor", format!("Vendor: {vendor}"), vendor);
+            out.field("model", format!("Model: {model}"), model);
+            out.field("removable", format!("Removable: {removable}"), removable);
+            out.field("speed", format!("Speed: {speed}"), speed);
+        }
+        Commands::GetStorageHealth { device } => {
+            let proxy = StorageHealth1Proxy::new(&conn).await?;
+            let devices = match device {
+                Some(device) => vec![device.clone()],
+                None => proxy.list_devices().await?,
+            };
+            for device in devices {
+                match proxy.get_device_health(device.as_str()).await {
+                    Ok((wear_level, temperature, available_spare, passed)) => {
+                        let result = if passed { "passed" } else { "failed" };
+                        out.field(
+                            device.as_str(),
+                            format!("{device}: {result}, wear level {wear_level}%, temperature {temperature:.1}°C, available spare {available_spare}%"),
+                            json!({
+                                "passed": passed,
+                                "wear_level": wear_level,
+                                "temperature": temperature,
+                                "available_spare": available_spare,
+                            }),
+                        );
+                    }
+                    Err(e) => out.field(
+                        device.as_str(),
+                        format!("{device}: health unavailable: {e}"),
+                        json!({ "error": e.to_string() }),
+                    ),
+                }
+            }
+        }
+        Commands::GetBrightness => {
+            let proxy = Backlight1Proxy::new(&conn).await?;
+            let brightness = proxy.brightness().await?;
+            let max = proxy.max_brightness().await?;
+            out.field(
+                "brightness",
+                format!("Brightness: {brightness}/{max}"),
+                brightness,
+            );
+            out.value("max_brightness", max);
+        }
+        Commands::SetBrightness { brightness } => {
+            let proxy = Backlight1Proxy::new(&conn).await?;
+            proxy.set_brightness(*brightness).await?;
+        }
+        Commands::StepBrightness { steps } => {
+            let proxy = Backlight1Proxy::new(&conn).await?;
+            let brightness = proxy.step_brightness(*steps).await?;
+            out.field(
+                "brightness",
+                format!("Brightness: {brightness}"),
+                brightness,
+            );
+        }
+        Commands::ListDisplayOutputs => {
+            let proxy = DisplayOutput1Proxy::new(&conn).await?;
+            let outputs = proxy.list_outputs().await?;
+            for (name, status, enabled, modes) in &outputs {
+                let enabled = if *enabled { "enabled" } else { "disabled" };
+                out.text(format!("{name}: {status}, {enabled}"));
+                if !modes.is_empty() {
+                    out.text(format!("  Modes: {}", modes.join(", ")));
+                }
+            }
+            out.value(
+                "outputs",
+                outputs
+                    .iter()
+                    .map(|(name, status, enabled, modes)| {
+                        json!({
+                            "name": name,
+                            "status": status,
+                            "enabled": enabled,
+                            "modes": modes,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::GetPreferredDockMode => {
+            let proxy = DisplayOutput1Proxy::new(&conn).await?;
+            let mode = proxy.preferred_dock_mode().await?;
+            if mode.is_empty() {
+                out.field("preferred_dock_mode", "No preferred dock mode set", mode);
+            } else {
+                out.field(
+                    "preferred_dock_mode",
+                    format!("Preferred dock mode: {mode}"),
+                    mode,
+                );
+            }
+        }
+        Commands::SetPreferredDockMode { mode } => {
+            let proxy = DisplayOutput1Proxy::new(&conn).await?;
+            proxy.set_preferred_dock_mode(mode.as_str()).await?;
+        }
+        Commands::GetRefreshRateCap => {
+            let proxy = DisplayOutput1Proxy::new(&conn).await?;
+            let cap = proxy.refresh_rate_cap().await?;
+            if cap == 0 {
+                out.field("refresh_rate_cap", "No refresh rate cap set", cap);
+            } else {
+                out.field(
+                    "refresh_rate_cap",
+                    format!("Refresh rate cap: {cap} Hz"),
+                    cap,
+                );
+            }
+        }
+        Commands::SetRefreshRateCap { cap } => {
+            let proxy = DisplayOutput1Proxy::new(&conn).await?;
+            proxy.set_refresh_rate_cap(*cap).await?;
+        }
+        Commands::GetBatteryCapacity => {
+            let proxy = Battery1Proxy::new(&conn).await?;
+            let capacity = proxy.capacity().await?;
+            out.field(
+                "battery_capacity",
+                format!("Battery capacity: {capacity}%"),
+                capacity,
+            );
+        }
+        Commands::GetBatteryChargeState => {
+            let proxy = Battery1Proxy::new(&conn).await?;
+            let state = proxy.charge_state().await?;
+            match BatteryChargeState::try_from(state) {
+                Ok(s) => out.field(
+                    "battery_charge_state",
+                    format!("Battery charge state: {s}"),
+                    s.to_string(),
+                ),
+                Err(_) => out.field(
+                    "battery_charge_state",
+                    format!("Got unknown value {state} from backend"),
+                    state,
+                ),
+            }
+        }
+        Commands::GetBatteryHealth => {
+            let proxy = Battery1Proxy::new(&conn).await?;
+            let health = proxy.health().await?;
+            match BatteryHealth::try_from(health) {
+                Ok(h) => out.field(
+                    "battery_health",
+                    format!("Battery health: {h}"),
+                    h.to_string(),
+                ),
+                Err(_) => out.field(
+                    "battery_health",
+                    format!("Got unknown value {health} from backend"),
+                    health,
+                ),
+            }
+        }
+        Commands::GetBatteryCycleCount => {
+            let proxy = Battery1Proxy::new(&conn).await?;
+            let count = proxy.cycle_count().await?;
+            out.field(
+                "battery_cycle_count",
+                format!("Battery cycle count: {count}"),
+                count,
+            );
+        }
         Commands::GetMaxChargeLevel => {
             let proxy = BatteryChargeLimit1Proxy::new(&conn).await?;
             let level = proxy.max_charge_level().await?;
-            println!("Max charge level: {level}");
+            out.field(
+                "max_charge_level",
+                format!("Max charge level: {level}"),
+                level,
+            );
         }
         Commands::SetMaxChargeLevel { level } => {
             let proxy = BatteryChargeLimit1Proxy::new(&conn).await?;
             proxy.set_max_charge_level(*level).await?;
         }
+        Commands::GetChargeBypass => {
+            let proxy = BatteryChargeLimit1Proxy::new(&conn).await?;
+            if proxy.charge_bypass_supported().await? {
+                let enabled = proxy.charge_bypass().await?;
+                out.field(
+                    "charge_bypass",
+                    format!("Charge bypass: {enabled}"),
+                    enabled,
+                );
+            } else {
+                out.field(
+                    "charge_bypass",
+                    "Charge bypass: unsupported",
+                    JsonValue::Null,
+                );
+            }
+        }
+        Commands::SetChargeBypass { enabled } => {
+            let proxy = BatteryChargeLimit1Proxy::new(&conn).await?;
+            proxy.set_charge_bypass(*enabled).await?;
+        }
         Commands::SuggestedMinimumChargeLimit => {
             let proxy = BatteryChargeLimit1Proxy::new(&conn).await?;
             let limit = proxy.suggested_minimum_limit().await?;
-            println!("Suggested minimum charge limit: {limit}");
+            out.field(
+                "suggested_minimum_charge_limit",
+                format!("Suggested minimum charge limit: {limit}"),
+                limit,
+            );
+        }
+        Commands::GetChargeLedMode => {
+            let proxy = ChargeLed1Proxy::new(&conn).await?;
+            let mode = proxy.mode().await?;
+            let modes = proxy.available_modes().await?;
+            out.field("mode", format!("Charge LED mode: {mode}"), mode);
+            out.field(
+                "available_modes",
+                format!("Available modes: {}", modes.join(", ")),
+                modes,
+            );
+        }
+        Commands::SetChargeLedMode { mode } => {
+            let proxy = ChargeLed1Proxy::new(&conn).await?;
+            proxy.set_mode(mode.as_str()).await?;
         }
         Commands::ReloadConfig => {
             let proxy = Manager2Proxy::new(&conn).await?;
             proxy.reload_config().await?;
         }
+        Commands::CheckConfig => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            for (interface, enabled, reason) in proxy.validate_config().await? {
+                let state = if enabled { "enabled" } else { "disabled" };
+                out.field(
+                    interface.as_str(),
+                    format!("{interface}: {state} ({reason})"),
+                    json!({ "enabled": enabled, "reason": reason }),
+                );
+            }
+        }
+        Commands::GetServiceStatus => {
+            let proxy = Status1Proxy::new(&conn).await?;
+            let services = proxy.get_service_status().await?;
+            for (daemon, name, state, restarts, error) in &services {
+                out.text(format!(
+                    "{daemon}/{name}: {state}, restarted {restarts} times"
+                ));
+                if !error.is_empty() {
+                    out.text(format!("  Last error: {error}"));
+                }
+            }
+            out.value(
+                "services",
+                services
+                    .iter()
+                    .map(|(daemon, name, state, restarts, error)| {
+                        json!({
+                            "daemon": daemon,
+                            "name": name,
+                            "state": state,
+                            "restarts": restarts,
+                            "error": error,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
         Commands::GetDeviceModel => {
             let proxy = Manager2Proxy::new(&conn).await?;
             let (device, variant) = proxy.device_model().await?;
-            println!("Model: {device}");
-            println!("Variant: {variant}");
+            out.field("model", format!("Model: {device}"), device);
+            out.field("variant", format!("Variant: {variant}"), variant);
+        }
+        Commands::GetDeviceConfigSource => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let sources = proxy.device_config_source().await?;
+            if sources.is_empty() {
+                out.text("No device configuration matches this device");
+            } else {
+                for source in &sources {
+                    out.text(source);
+                }
+            }
+            out.value("sources", sources);
+        }
+        Commands::MatchDevice { all } => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let report = proxy.match_device().await?;
+            if !report.iter().any(|(_, _, _, matched, _)| *matched) {
+                out.text("No device configuration entry matches this device");
+            }
+            let report: Vec<_> = report
+                .into_iter()
+                .filter(|(_, _, _, matched, _)| *matched || *all)
+                .collect();
+            for (path, device, variant, matched, reason) in &report {
+                let state = if *matched { "matches" } else { "doesn't match" };
+                out.text(format!("{path}: {device}/{variant} {state} ({reason})"));
+            }
+            out.value(
+                "entries",
+                report
+                    .iter()
+                    .map(|(path, device, variant, matched, reason)| {
+                        json!({
+                            "path": path,
+                            "device": device,
+                            "variant": variant,
+                            "matched": matched,
+                            "reason": reason,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Commands::GenerateDiagnostics => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let (job, path) = proxy.generate_diagnostics().await?;
+            out.field("path", format!("Writing diagnostics to {path}"), path);
+            out.value("job", job.to_string());
+        }
+        Commands::ListFeatures => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let features = proxy.features().await?;
+            for feature in &features {
+                out.text(feature);
+            }
+            out.value("features", features);
+        }
+        Commands::HasFeature { name } => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let available = proxy.has_feature(name.as_str()).await?;
+            let state = if available {
+                "available"
+            } else {
+                "not available"
+            };
+            out.field("available", format!("{name} is {state}"), available);
+        }
+        Commands::GetLogLevel => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            let level = proxy.log_level().await?;
+            out.field("log_level", format!("Log level: {level}"), level);
+        }
+        Commands::SetLogLevel { level } => {
+            let proxy = Manager2Proxy::new(&conn).await?;
+            proxy.set_log_level(level.as_str()).await?;
+        }
+        Commands::GetHighContrast => {
+            let proxy = Accessibility1Proxy::new(&conn).await?;
+            let enabled = proxy.high_contrast().await?;
+            out.field(
+                "high_contrast",
+                format!("High contrast: {enabled}"),
+                enabled,
+            );
+        }
+        Commands::SetHighContrast { enable } => {
+            let proxy = Accessibility1Proxy::new(&conn).await?;
+            proxy.set_high_contrast(*enable).await?;
+        }
+        Commands::GetTextScalingFactor => {
+            let proxy = Accessibility1Proxy::new(&conn).await?;
+            let factor = proxy.text_scaling_factor().await?;
+            out.field(
+                "text_scaling_factor",
+                format!("Text scaling factor: {factor}"),
+                factor,
+            );
+        }
+        Commands::SetTextScalingFactor { factor } => {
+            let proxy = Accessibility1Proxy::new(&conn).await?;
+            proxy.set_text_scaling_factor(*factor).await?;
+        }
+        Commands::GetColorFilter => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            let filter = proxy.filter().await?;
+            match ColorFilter::try_from(filter) {
+                Ok(f) => out.field("filter", format!("Color filter: {f}"), f.to_string()),
+                Err(_) => out.field(
+                    "filter",
+                    format!("Got unknown color filter value {filter} from backend"),
+                    filter,
+                ),
+            }
+        }
+        Commands::SetColorFilter { filter } => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            proxy.set_filter(*filter as u32).await?;
+        }
+        Commands::GetNightLight => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            let enabled = proxy.night_light().await?;
+            out.field("night_light", format!("Night light: {enabled}"), enabled);
+        }
+        Commands::SetNightLight { enable } => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            proxy.set_night_light(*enable).await?;
+        }
+        Commands::GetNightLightTemperature => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            let temperature = proxy.night_light_temperature().await?;
+            out.field(
+                "night_light_temperature",
+                format!("Night light temperature: {temperature} K"),
+                temperature,
+            );
+        }
+        Commands::SetNightLightTemperature { temperature } => {
+            let proxy = ColorFilters1Proxy::new(&conn).await?;
+            proxy.set_night_light_temperature(*temperature).await?;
+        }
+        Commands::GetIdlePolicy { power_state } => {
+            let proxy = IdlePolicy1Proxy::new(&conn).await?;
+            let (action, timeout) = proxy.get_idle_policy(*power_state as u32).await?;
+            match IdleAction::try_from(action) {
+                Ok(action) => out.field(
+                    "action",
+                    format!("Idle action: {action} after {timeout} s"),
+                    action.to_string(),
+                ),
+                Err(_) => out.field(
+                    "action",
+                    format!("Got unknown idle action value {action} from backend"),
+                    action,
+                ),
+            }
+            out.value("timeout", timeout);
+        }
+        Commands::SetIdlePolicy {
+            power_state,
+            action,
+            timeout,
+        } => {
+            let proxy = IdlePolicy1Proxy::new(&conn).await?;
+            proxy
+                .set_idle_policy(*power_state as u32, *action as u32, *timeout)
+                .await?;
         }
         Commands::GetScreenReaderEnabled => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let enabled = proxy.enabled().await?;
-            println!("Enabled: {enabled}");
+            out.field("enabled", format!("Enabled: {enabled}"), enabled);
         }
         Commands::SetScreenReaderEnabled { enable } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
@@ -660,7 +3361,7 @@ async fn main() -> Result<()> {
         Commands::GetScreenReaderRate => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let rate = proxy.rate().await?;
-            println!("Rate: {rate}");
+            out.field("rate", format!("Rate: {rate}"), rate);
         }
         Commands::SetScreenReaderRate { rate } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
@@ -669,7 +3370,7 @@ async fn main() -> Result<()> {
         Commands::GetScreenReaderPitch => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let pitch = proxy.pitch().await?;
-            println!("Pitch: {pitch}");
+            out.field("pitch", format!("Pitch: {pitch}"), pitch);
         }
         Commands::SetScreenReaderPitch { pitch } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
@@ -678,7 +3379,7 @@ async fn main() -> Result<()> {
         Commands::GetScreenReaderVolume => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let volume = proxy.volume().await?;
-            println!("Volume: {volume}");
+            out.field("volume", format!("Volume: {volume}"), volume);
         }
         Commands::SetScreenReaderVolume { volume } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
@@ -688,8 +3389,12 @@ async fn main() -> Result<()> {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let mode = proxy.mode().await?;
             match ScreenReaderMode::try_from(mode) {
-                Ok(s) => println!("Screen Reader Mode: {s}"),
-                Err(_) => println!("Got unknown screen reader mode value {mode} from backend"),
+                Ok(s) => out.field("mode", format!("Screen Reader Mode: {s}"), s.to_string()),
+                Err(_) => out.field(
+                    "mode",
+                    format!("Got unknown screen reader mode value {mode} from backend"),
+                    mode,
+                ),
             }
         }
         Commands::SetScreenReaderMode { mode } => {
@@ -707,19 +3412,29 @@ async fn main() -> Result<()> {
         Commands::GetScreenReaderVoice => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             let voice = proxy.voice().await?;
-            println!("Voice: {voice}");
+            out.field("voice", format!("Voice: {voice}"), voice);
         }
         Commands::SetScreenReaderVoice { voice } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
             proxy.set_voice(voice).await?;
         }
+        Commands::GetScreenReaderVoiceLocale => {
+            let proxy = ScreenReader0Proxy::new(&conn).await?;
+            let locale = proxy.voice_locale().await?;
+            out.field("voice_locale", format!("Voice locale: {locale}"), locale);
+        }
+        Commands::SetScreenReaderVoiceLocale { locale } => {
+            let proxy = ScreenReader0Proxy::new(&conn).await?;
+            proxy.set_voice_locale(locale).await?;
+        }
         Commands::GetScreenReaderLocales => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
-            let locales = proxy.voice_locales().await?;
-            println!("Locales:\n");
-            for locale in locales.into_iter().sorted() {
-                println!("- {locale}");
+            let locales: Vec<_> = proxy.voice_locales().await?.into_iter().sorted().collect();
+            out.text("Locales:\n");
+            for locale in &locales {
+                out.text(format!("- {locale}"));
             }
+            out.value("locales", locales);
         }
         Commands::GetScreenReaderVoicesForLocale { locale } => {
             let proxy = ScreenReader0Proxy::new(&conn).await?;
@@ -727,10 +3442,12 @@ async fn main() -> Result<()> {
             let voices = voice_list
                 .get(locale)
                 .ok_or_else(|| anyhow!("Unable to load voices map"))?;
-            println!("Voices:\n");
-            for voice in voices.iter().sorted() {
-                println!("- {voice}");
+            let voices: Vec<_> = voices.iter().sorted().collect();
+            out.text("Voices:\n");
+            for voice in &voices {
+                out.text(format!("- {voice}"));
             }
+            out.value("voices", json!(voices));
         }
         Commands::SwitchToDesktopMode => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
@@ -753,7 +3470,7 @@ async fn main() -> Result<()> {
         Commands::GetDefaultDesktopSession => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
             let session = proxy.default_desktop_session().await?;
-            println!("{session}");
+            out.field("default_desktop_session", &session, session.as_str());
         }
         Commands::SetDefaultLoginMode { mode } => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
@@ -764,15 +3481,21 @@ async fn main() -> Result<()> {
         Commands::GetDefaultLoginMode => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
             let mode = proxy.default_login_mode().await?;
-            println!("{mode}");
+            out.field("default_login_mode", &mode, mode.as_str());
         }
         Commands::GetValidDesktopSessions => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
-            let sessions = proxy.valid_desktop_sessions().await?;
-            println!("Sessions:\n");
-            for session in sessions.into_iter().sorted() {
-                println!("- {session}");
+            let sessions: Vec<_> = proxy
+                .valid_desktop_sessions()
+                .await?
+                .into_iter()
+                .sorted()
+                .collect();
+            out.text("Sessions:\n");
+            for session in &sessions {
+                out.text(format!("- {session}"));
             }
+            out.value("sessions", sessions);
         }
         Commands::CleanTemporarySessions => {
             let proxy = SessionManagement1Proxy::new(&conn).await?;
@@ -780,5 +3503,5 @@ async fn main() -> Result<()> {
         }
     }
 
-    Ok(())
+    out.finish()
 }
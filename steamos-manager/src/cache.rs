@@ -0,0 +1,155 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex as SyncMutex;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+// Bumped whenever the hardware or configuration may have changed, which invalidates every cache
+#[cfg(not(test))]
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(test)]
+thread_local! {
+    // Tests run concurrently on separate threads, so they each get their own generation
+    static GENERATION: AtomicU64 = const { AtomicU64::new(0) };
+}
+
+#[cfg(not(test))]
+fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+#[cfg(test)]
+fn generation() -> u64 {
+    GENERATION.with(|generation| generation.load(Ordering::Acquire))
+}
+
+/// Drop every cached sysfs value, so that they get read again on next access
+#[cfg(not(test))]
+pub(crate) fn invalidate_sysfs_caches() {
+    debug!("Invalidating sysfs caches");
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+#[cfg(test)]
+pub(crate) fn invalidate_sysfs_caches() {
+    debug!("Invalidating sysfs caches");
+    GENERATION.with(|generation| generation.fetch_add(1, Ordering::AcqRel));
+}
+
+/// A value read from sysfs that doesn't change until the hardware or configuration does, such
+/// as the list of available GPU power profiles. Errors aren't cached.
+pub(crate) struct SysfsCache<T: Clone> {
+    value: Mutex<Option<(u64, T)>>,
+}
+
+impl<T: Clone> Default for SysfsCache<T> {
+    fn default() -> SysfsCache<T> {
+        SysfsCache {
+            value: Mutex::new(None),
+        }
+    }
+}
+
+impl<T: Clone> SysfsCache<T> {
+    pub async fn get_or_try_read<F, Fut>(&self, read: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let generation = generation();
+        let mut value = self.value.lock().await;
+        if let Some((cached, value)) = value.as_ref() {
+            if *cached == generation {
+                return Ok(value.clone());
+            }
+        }
+        let fresh = read().await?;
+        *value = Some((generation, fresh.clone()));
+        Ok(fresh)
+    }
+}
+
+/// Like [`SysfsCache`], but for lookups that are keyed, such as finding a hwmon directory by name
+pub(crate) struct SysfsLookupCache<K: Eq + Hash, V: Clone> {
+    values: SyncMutex<HashMap<K, (u64, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> Default for SysfsLookupCache<K, V> {
+    fn default() -> SysfsLookupCache<K, V> {
+        SysfsLookupCache {
+            values: SyncMutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> SysfsLookupCache<K, V> {
+    pub fn get(&self, key: &K) -> Option<V> {
+        let generation = generation();
+        let values = self.values.lock().unwrap();
+        match values.get(key) {
+            Some((cached, value)) if *cached == generation => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let generation = generation();
+        let mut values = self.values.lock().unwrap();
+        values.retain(|_, (cached, _)| *cached == generation);
+        values.insert(key, (generation, value));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn cache_invalidation() {
+        let cache = SysfsCache::<u32>::default();
+        let counter = AtomicU32::new(0);
+        let reads = &counter;
+        let read = move || async move { Ok(reads.fetch_add(1, Ordering::Relaxed) + 1) };
+
+        assert!(cache
+            .get_or_try_read(|| async { Err(anyhow!("not yet")) })
+            .await
+            .is_err());
+        assert_eq!(cache.get_or_try_read(read).await.unwrap(), 1);
+        assert_eq!(cache.get_or_try_read(read).await.unwrap(), 1);
+
+        invalidate_sysfs_caches();
+        assert_eq!(cache.get_or_try_read(read).await.unwrap(), 2);
+        assert_eq!(cache.get_or_try_read(read).await.unwrap(), 2);
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn lookup_cache_invalidation() {
+        let cache = SysfsLookupCache::<&str, u32>::default();
+        assert_eq!(cache.get(&"amdgpu"), None);
+
+        cache.insert("amdgpu", 1);
+        cache.insert("steamdeck_hwmon", 2);
+        assert_eq!(cache.get(&"amdgpu"), Some(1));
+        assert_eq!(cache.get(&"steamdeck_hwmon"), Some(2));
+
+        invalidate_sysfs_caches();
+        assert_eq!(cache.get(&"amdgpu"), None);
+        cache.insert("amdgpu", 3);
+        assert_eq!(cache.get(&"amdgpu"), Some(3));
+        assert_eq!(cache.get(&"steamdeck_hwmon"), None);
+    }
+}
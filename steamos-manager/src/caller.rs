@@ -0,0 +1,120 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+//! Identifying the processes that call into the daemons
+
+use anyhow::{anyhow, Result};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+use std::path::PathBuf;
+use tokio::fs::{read_link, read_to_string};
+use zbus::message::Header;
+use zbus::{fdo, Connection};
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Caller {
+    /// Unique bus name of the caller
+    pub sender: String,
+    pub uid: u32,
+    pub pid: u32,
+    /// The executable of the caller, if it could be read while the caller was known to still be
+    /// the process the bus reported
+    pub exe: Option<PathBuf>,
+    /// When the caller started, in clock ticks since boot, under the same condition as `exe`
+    pub start_time: Option<u64>,
+}
+
+/// Whether a pidfd still refers to a running process with the given pid
+async fn pidfd_is_pid(pidfd: BorrowedFd<'_>, pid: u32) -> bool {
+    let Ok(fdinfo) = read_to_string(format!("/proc/self/fdinfo/{}", pidfd.as_raw_fd())).await
+    else {
+        return false;
+    };
+    // Exited processes are reported with a pid of -1
+    let pid = pid.to_string();
+    fdinfo
+        .lines()
+        .filter_map(|line| line.strip_prefix("Pid:"))
+        .any(|value| value.trim() == pid)
+}
+
+async fn start_time(pid: u32) -> Result<u64> {
+    let stat = read_to_string(format!("/proc/{pid}/stat")).await?;
+    // The command name can contain anything, so fields are counted from its closing parenthesis
+    let (_, fields) = stat
+        .rsplit_once(')')
+        .ok_or_else(|| anyhow!("Malformed stat of process {pid}"))?;
+    let start_time = fields
+        .split_whitespace()
+        .nth(19)
+        .ok_or_else(|| anyhow!("Process {pid} has no start time"))?;
+    Ok(start_time.parse()?)
+}
+
+/// Read the executable and start time of a process, making sure its pid wasn't reused by another
+/// process in the meantime
+async fn verified_details(pidfd: BorrowedFd<'_>, pid: u32) -> (Option<PathBuf>, Option<u64>) {
+    let exe = read_link(format!("/proc/{pid}/exe")).await.ok();
+    let start_time = start_time(pid).await.ok();
+    if pidfd_is_pid(pidfd, pid).await {
+        (exe, start_time)
+    } else {
+        (None, None)
+    }
+}
+
+/// Look up who sent a message, according to the bus
+pub(crate) async fn identify_caller(
+    connection: &Connection,
+    header: &Header<'_>,
+) -> fdo::Result<Caller> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+    let credentials = fdo::DBusProxy::new(connection)
+        .await?
+        .get_connection_credentials(sender.clone().into())
+        .await?;
+    let (Some(uid), Some(pid)) = (credentials.unix_user_id(), credentials.process_id()) else {
+        return Err(fdo::Error::AccessDenied(format!(
+            "Caller {sender} has no credentials"
+        )));
+    };
+    // Without a pidfd from the bus, the pid could belong to another process by the time it's read
+    let (exe, start_time) = match credentials.process_fd() {
+        Some(pidfd) => verified_details(pidfd.as_fd(), pid).await,
+        None => (None, None),
+    };
+    Ok(Caller {
+        sender: sender.to_string(),
+        uid,
+        pid,
+        exe,
+        start_time,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env::current_exe;
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    fn pidfd_open(pid: u32) -> OwnedFd {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+        assert!(fd >= 0, "pidfd_open failed");
+        unsafe { OwnedFd::from_raw_fd(fd as i32) }
+    }
+
+    #[tokio::test]
+    async fn details_verification() {
+        let pid = std::process::id();
+        let pidfd = pidfd_open(pid);
+        let (exe, start) = verified_details(pidfd.as_fd(), pid).await;
+        assert_eq!(exe, Some(current_exe().unwrap()));
+        assert!(start.is_some_and(|start| start > 0));
+        assert_eq!(verified_details(pidfd.as_fd(), 1).await, (None, None));
+    }
+}
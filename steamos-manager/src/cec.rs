@@ -6,13 +6,41 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{bail, Error, Result};
+use anyhow::{anyhow, bail, ensure, Error, Result};
 use num_enum::TryFromPrimitive;
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
 use std::str::FromStr;
+use tokio::fs::read_dir;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::select;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+use zbus::object_server::InterfaceRef;
 use zbus::Connection;
 
+use crate::manager::user::{HdmiCec1, HdmiCec1Signals, MANAGER_PATH};
+use crate::policy::Guarded;
+use crate::process::{run_script, script_output};
 use crate::systemd::{daemon_reload, EnableState, SystemdUnit};
+use crate::{path, Service};
+
+const CEC_CTL_PATH: &str = "/usr/bin/cec-ctl";
+const DEV_PREFIX: &str = "/dev";
+
+/// Logical address of the TV
+const CEC_TV: &str = "0";
+/// Logical address for messages to all devices
+const CEC_BROADCAST: &str = "15";
+
+static CEC_CHANGED: Notify = Notify::const_new();
+
+/// Let the remote monitor know an adapter was added or removed, or HDMI-CEC was toggled
+pub(crate) fn cec_changed() {
+    CEC_CHANGED.notify_one();
+}
 
 #[derive(PartialEq, Debug, Copy, Clone, TryFromPrimitive)]
 #[repr(u32)]
@@ -55,6 +83,220 @@ impl HdmiCecState {
     }
 }
 
+async fn find_cec_device() -> Result<PathBuf> {
+    let mut dir = read_dir(path(DEV_PREFIX)).await?;
+    let mut devices = Vec::new();
+    while let Some(entry) = dir.next_entry().await? {
+        if entry.file_name().to_string_lossy().starts_with("cec") {
+            devices.push(entry.path());
+        }
+    }
+    devices.sort();
+    devices
+        .into_iter()
+        .next()
+        .ok_or(anyhow!("No HDMI-CEC adapter found"))
+}
+
+async fn cec_ctl(args: &[&str]) -> Result<()> {
+    let device = find_cec_device().await?.to_string_lossy().to_string();
+    let mut cmd = vec!["-d", device.as_str()];
+    cmd.extend_from_slice(args);
+    run_script(CEC_CTL_PATH, cmd.as_slice()).await
+}
+
+async fn physical_address() -> Result<String> {
+    let device = find_cec_device().await?.to_string_lossy().to_string();
+    let output = script_output(CEC_CTL_PATH, &["-d", device.as_str()]).await?;
+    let address = output
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "Physical Address").then(|| value.trim().to_string())
+        })
+        .ok_or(anyhow!("HDMI-CEC adapter has no physical address"))?;
+    ensure!(
+        address != "f.f.f.f",
+        "HDMI-CEC adapter isn't connected to a display"
+    );
+    Ok(address)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct CecDevice {
+    pub logical_address: u32,
+    pub name: String,
+    pub vendor: String,
+    pub power_status: String,
+}
+
+fn parse_topology(output: &str) -> Vec<CecDevice> {
+    let mut devices: Vec<CecDevice> = Vec::new();
+    for line in output.lines() {
+        // Each device starts with e.g. "System Information for device 0 (TV) from device 4 (...):"
+        if let Some(header) = line.strip_prefix("System Information for device ") {
+            let Some((address, rest)) = header.split_once(' ') else {
+                continue;
+            };
+            let Ok(logical_address) = address.parse() else {
+                continue;
+            };
+            let kind = rest
+                .strip_prefix('(')
+                .and_then(|rest| rest.split_once(')'))
+                .map(|(kind, _)| kind)
+                .unwrap_or_default();
+            devices.push(CecDevice {
+                logical_address,
+                name: kind.to_string(),
+                vendor: String::new(),
+                power_status: String::new(),
+            });
+            continue;
+        }
+        let Some(device) = devices.last_mut() else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "OSD Name" => device.name = value.trim_matches('\'').to_string(),
+            "Vendor ID" => {
+                // Prefer the vendor name over the OUI, e.g. "0x00903e (Philips)"
+                device.vendor = value
+                    .split_once(" (")
+                    .and_then(|(_, name)| name.strip_suffix(')'))
+                    .unwrap_or(value)
+                    .to_string();
+            }
+            "Power Status" => device.power_status = value.to_string(),
+            _ => (),
+        }
+    }
+    devices
+}
+
+async fn list_cec_devices() -> Result<Vec<CecDevice>> {
+    let device = find_cec_device().await?.to_string_lossy().to_string();
+    let output = script_output(CEC_CTL_PATH, &["-d", device.as_str(), "--show-topology"]).await?;
+    Ok(parse_topology(output.as_str()))
+}
+
+/// Picks remote keypresses out of the messages printed by cec-ctl
+#[derive(Default)]
+struct CecKeyParser {
+    pressed: bool,
+}
+
+impl CecKeyParser {
+    fn parse_line(&mut self, line: &str) -> Option<(u32, String)> {
+        // Messages start unindented, and their arguments follow on indented lines
+        if !line.starts_with(char::is_whitespace) {
+            self.pressed = line.contains("USER_CONTROL_PRESSED");
+            return None;
+        }
+        if !self.pressed {
+            return None;
+        }
+        let (key, value) = line.split_once(':')?;
+        if key.trim() != "ui-cmd" {
+            return None;
+        }
+        self.pressed = false;
+        // e.g. "up (0x01)"
+        let (name, code) = value.trim().rsplit_once(" (")?;
+        let code = code.strip_suffix(')')?.strip_prefix("0x")?;
+        let code = u32::from_str_radix(code, 16).ok()?;
+        Some((code, name.to_string()))
+    }
+}
+
+/// Relays remote keypresses while HDMI-CEC is enabled and an adapter is present
+pub(crate) struct CecRemoteMonitor {
+    hdmi_cec: InterfaceRef<Guarded<HdmiCec1>>,
+    control: HdmiCecControl<'static>,
+}
+
+impl CecRemoteMonitor {
+    pub(crate) async fn init(session: &Connection) -> Result<CecRemoteMonitor> {
+        Ok(CecRemoteMonitor {
+            hdmi_cec: session
+                .object_server()
+                .interface::<_, Guarded<HdmiCec1>>(MANAGER_PATH)
+                .await?,
+            control: HdmiCecControl::new(session).await?,
+        })
+    }
+
+    /// The adapter to watch, if HDMI-CEC is enabled
+    async fn device(&self) -> Option<PathBuf> {
+        match self.control.get_enabled_state().await {
+            Ok(HdmiCecState::Disabled) => return None,
+            Ok(_) => (),
+            Err(e) => {
+                warn!("Failed to get HDMI-CEC state: {e}");
+                return None;
+            }
+        }
+        find_cec_device().await.ok()
+    }
+}
+
+async fn watch_remote(
+    hdmi_cec: &InterfaceRef<Guarded<HdmiCec1>>,
+    device: &Path,
+) -> Result<ExitStatus> {
+    let mut child = Command::new(CEC_CTL_PATH)
+        .arg("-d")
+        .arg(device)
+        .arg("--wait-for-msgs")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or(anyhow!("Couldn't read cec-ctl output"))?;
+    let mut lines = BufReader::new(stdout).lines();
+    let mut parser = CecKeyParser::default();
+    while let Some(line) = lines.next_line().await? {
+        if let Some((code, key)) = parser.parse_line(line.as_str()) {
+            debug!("Got CEC keypress {key} ({code:#04x})");
+            if let Err(e) = hdmi_cec
+                .signal_emitter()
+                .remote_key_pressed(code, key.as_str())
+                .await
+            {
+                warn!("Failed to relay CEC keypress {key}: {e}");
+            }
+        }
+    }
+    Ok(child.wait().await?)
+}
+
+impl Service for CecRemoteMonitor {
+    const NAME: &'static str = "cec-remote-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            if let Some(device) = self.device().await {
+                select! {
+                    result = watch_remote(&self.hdmi_cec, &device) => match result {
+                        // cec-ctl exits when the adapter goes away
+                        Ok(status) => debug!("cec-ctl exited: {status}"),
+                        Err(e) => warn!("Failed to watch for CEC keypresses: {e}"),
+                    },
+                    () = CEC_CHANGED.notified() => continue,
+                }
+            }
+            CEC_CHANGED.notified().await;
+        }
+    }
+}
+
 pub(crate) struct HdmiCecControl<'dbus> {
     plasma_rc_unit: SystemdUnit<'dbus>,
     wakehook_unit: SystemdUnit<'dbus>,
@@ -110,15 +352,48 @@ impl<'dbus> HdmiCecControl<'dbus> {
                 self.wakehook_unit.start().await?;
             }
         }
+        cec_changed();
+
+        Ok(())
+    }
 
+    async fn ensure_enabled(&self) -> Result<()> {
+        ensure!(
+            self.get_enabled_state().await? != HdmiCecState::Disabled,
+            "HDMI-CEC is disabled"
+        );
         Ok(())
     }
+
+    pub async fn power_on_tv(&self) -> Result<()> {
+        self.ensure_enabled().await?;
+        cec_ctl(&["--to", CEC_TV, "--image-view-on"]).await
+    }
+
+    pub async fn standby_tv(&self) -> Result<()> {
+        self.ensure_enabled().await?;
+        cec_ctl(&["--to", CEC_TV, "--standby"]).await
+    }
+
+    pub async fn list_devices(&self) -> Result<Vec<CecDevice>> {
+        self.ensure_enabled().await?;
+        list_cec_devices().await
+    }
+
+    pub async fn set_active_source(&self) -> Result<()> {
+        self.ensure_enabled().await?;
+        let address = physical_address().await?;
+        let address = format!("phys-addr={address}");
+        cec_ctl(&["--to", CEC_BROADCAST, "--active-source", address.as_str()]).await
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::enum_roundtrip;
+    use crate::{enum_roundtrip, testing};
+    use std::ffi::OsStr;
+    use tokio::fs::{create_dir_all, write};
 
     #[test]
     fn hdmi_cec_state_roundtrip() {
@@ -150,4 +425,140 @@ mod test {
         assert!(HdmiCecState::try_from(3).is_err());
         assert!(HdmiCecState::from_str("working").is_err());
     }
+
+    #[tokio::test]
+    async fn cec_commands() {
+        let h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            ensure!(executable == CEC_CTL_PATH, "Not cec-ctl");
+            ensure!(args[0] == "-d", "No device");
+            ensure!(
+                args[1].to_string_lossy().ends_with("/dev/cec0"),
+                "Wrong device"
+            );
+            match args.get(2..).unwrap_or_default() {
+                [] => Ok((
+                    0,
+                    String::from(
+                        "Driver Info:\n\tDriver Name                : dw_hdmi_cec\n\
+                         Physical Address           : 1.0.0.0\n\
+                         Logical Address Mask       : 0x0010\n",
+                    ),
+                )),
+                [to, tv, cmd] if *to == "--to" && *tv == "0" => match cmd.to_str() {
+                    Some("--image-view-on" | "--standby") => Ok((0, String::new())),
+                    _ => bail!("Unknown command"),
+                },
+                [to, all, cmd, address]
+                    if *to == "--to" && *all == "15" && *cmd == "--active-source" =>
+                {
+                    ensure!(*address == "phys-addr=1.0.0.0", "Wrong address");
+                    Ok((0, String::new()))
+                }
+                _ => bail!("Unknown command"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        create_dir_all(path(DEV_PREFIX))
+            .await
+            .expect("create_dir_all");
+        assert!(find_cec_device().await.is_err());
+        assert!(cec_ctl(&["--to", CEC_TV, "--standby"]).await.is_err());
+
+        write(path(DEV_PREFIX).join("cec1"), "")
+            .await
+            .expect("write");
+        write(path(DEV_PREFIX).join("cec0"), "")
+            .await
+            .expect("write");
+        write(path(DEV_PREFIX).join("null"), "")
+            .await
+            .expect("write");
+        assert_eq!(
+            find_cec_device().await.unwrap(),
+            path(DEV_PREFIX).join("cec0")
+        );
+
+        assert_eq!(physical_address().await.unwrap(), "1.0.0.0");
+        cec_ctl(&["--to", CEC_TV, "--image-view-on"])
+            .await
+            .expect("image-view-on");
+        cec_ctl(&["--to", CEC_TV, "--standby"])
+            .await
+            .expect("standby");
+        cec_ctl(&[
+            "--to",
+            CEC_BROADCAST,
+            "--active-source",
+            "phys-addr=1.0.0.0",
+        ])
+        .await
+        .expect("active-source");
+    }
+
+    #[test]
+    fn topology() {
+        let output = "Driver Info:
+\tDriver Name                : dw_hdmi_cec
+\tPhysical Address           : 1.0.0.0
+
+System Information for device 0 (TV) from device 4 (Playback Device 1):
+\tCEC Version                : 1.4
+\tPhysical Address           : 0.0.0.0
+\tPrimary Device Type        : TV
+\tVendor ID                  : 0x00903e (Philips)
+\tOSD Name                   : 'Living Room'
+\tPower Status               : On
+
+System Information for device 5 (Audio System) from device 4 (Playback Device 1):
+\tCEC Version                : 1.4
+\tPhysical Address           : 2.0.0.0
+\tVendor ID                  : 0x123456
+\tPower Status               : Standby
+
+Topology:
+
+\t0.0.0.0: TV
+\t    1.0.0.0: Playback Device 1
+\t    2.0.0.0: Audio System
+";
+        assert_eq!(
+            parse_topology(output),
+            [
+                CecDevice {
+                    logical_address: 0,
+                    name: String::from("Living Room"),
+                    vendor: String::from("Philips"),
+                    power_status: String::from("On"),
+                },
+                CecDevice {
+                    logical_address: 5,
+                    name: String::from("Audio System"),
+                    vendor: String::from("0x123456"),
+                    power_status: String::from("Standby"),
+                },
+            ]
+        );
+        assert!(parse_topology("").is_empty());
+    }
+
+    #[test]
+    fn remote_keypresses() {
+        let output = "Received from TV to Playback Device 1 (0 to 4): USER_CONTROL_PRESSED (0x44):
+\tui-cmd: up (0x01)
+Received from TV to Playback Device 1 (0 to 4): USER_CONTROL_RELEASED (0x45)
+Received from TV to Playback Device 1 (0 to 4): GIVE_OSD_NAME (0x46)
+\tui-cmd: bogus (0x99)
+Received from TV to Playback Device 1 (0 to 4): USER_CONTROL_PRESSED (0x44):
+\tui-cmd: select (0x00)
+";
+        let mut parser = CecKeyParser::default();
+        let keys: Vec<(u32, String)> = output
+            .lines()
+            .filter_map(|line| parser.parse_line(line))
+            .collect();
+        assert_eq!(keys, [(1, String::from("up")), (0, String::from("select"))]);
+    }
 }
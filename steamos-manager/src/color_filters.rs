@@ -0,0 +1,246 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use strum::{Display, EnumString};
+use tracing::debug;
+use zbus::Connection;
+
+use crate::process::run_script;
+
+const KWRITECONFIG_PATH: &str = "/usr/bin/kwriteconfig6";
+const XPROP_PATH: &str = "/usr/bin/xprop";
+
+const GAMESCOPE_NIGHTMODE_ATOM: &str = "GAMESCOPE_COLOR_NIGHTMODE";
+/// Hue of the night mode tint, as a fraction of the colour wheel
+const GAMESCOPE_NIGHTMODE_HUE: f32 = 0.08;
+
+/// The range supported by KWin's Night Color, in Kelvin
+pub(crate) const NIGHT_LIGHT_TEMPERATURE_RANGE: RangeInclusive<u32> = 1000..=6500;
+
+#[derive(
+    Display,
+    EnumString,
+    PartialEq,
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    TryFromPrimitive,
+    Deserialize,
+    Serialize,
+)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+#[repr(u32)]
+pub enum ColorFilter {
+    #[default]
+    None = 0,
+    Protanopia = 1,
+    Deuteranopia = 2,
+    Tritanopia = 3,
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct ColorFiltersState {
+    pub filter: ColorFilter,
+    pub night_light: bool,
+    /// Colour temperature of the night light, in Kelvin
+    pub night_light_temperature: u32,
+}
+
+impl Default for ColorFiltersState {
+    fn default() -> ColorFiltersState {
+        ColorFiltersState {
+            filter: ColorFilter::None,
+            night_light: false,
+            night_light_temperature: 4500,
+        }
+    }
+}
+
+async fn write_kwin_config(group: &str, key: &str, value: &str) -> Result<()> {
+    run_script(
+        KWRITECONFIG_PATH,
+        &["--file", "kwinrc", "--group", group, "--key", key, value],
+    )
+    .await
+}
+
+async fn apply_kwin(session: &Connection, state: &ColorFiltersState) -> Result<()> {
+    // KWin's colour blindness correction effect only knows the filter types, in this order
+    let mode = match state.filter {
+        ColorFilter::None => None,
+        ColorFilter::Protanopia => Some("0"),
+        ColorFilter::Deuteranopia => Some("1"),
+        ColorFilter::Tritanopia => Some("2"),
+    };
+    write_kwin_config(
+        "Plugins",
+        "colorblindnesscorrectionEnabled",
+        if mode.is_some() { "true" } else { "false" },
+    )
+    .await?;
+    if let Some(mode) = mode {
+        write_kwin_config("Effect-colorblindnesscorrection", "Mode", mode).await?;
+    }
+
+    write_kwin_config(
+        "NightColor",
+        "Active",
+        if state.night_light { "true" } else { "false" },
+    )
+    .await?;
+    write_kwin_config("NightColor", "Mode", "Constant").await?;
+    write_kwin_config(
+        "NightColor",
+        "NightTemperature",
+        state.night_light_temperature.to_string().as_str(),
+    )
+    .await?;
+
+    // KWin only runs in desktop mode, in which case it needs to pick up the new config
+    if let Err(e) = session
+        .call_method(
+            Some("org.kde.KWin"),
+            "/KWin",
+            Some("org.kde.KWin"),
+            "reconfigure",
+            &(),
+        )
+        .await
+    {
+        debug!("Couldn't reconfigure KWin: {e}");
+    }
+    Ok(())
+}
+
+/// The night mode tint gamescope applies, as the bit patterns of its amount, hue and saturation
+fn gamescope_nightmode(state: &ColorFiltersState) -> [u32; 3] {
+    let amount = if state.night_light {
+        let (min, max) = (
+            *NIGHT_LIGHT_TEMPERATURE_RANGE.start(),
+            *NIGHT_LIGHT_TEMPERATURE_RANGE.end(),
+        );
+        let temperature = state.night_light_temperature.clamp(min, max);
+        (max - temperature) as f32 / (max - min) as f32
+    } else {
+        0.0
+    };
+    [
+        amount.to_bits(),
+        GAMESCOPE_NIGHTMODE_HUE.to_bits(),
+        1.0_f32.to_bits(),
+    ]
+}
+
+async fn apply_gamescope(state: &ColorFiltersState) -> Result<()> {
+    let value = gamescope_nightmode(state)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    run_script(
+        XPROP_PATH,
+        &[
+            "-root",
+            "-f",
+            GAMESCOPE_NIGHTMODE_ATOM,
+            "32c",
+            "-set",
+            GAMESCOPE_NIGHTMODE_ATOM,
+            value.as_str(),
+        ],
+    )
+    .await
+}
+
+/// Apply colour filters to both KWin and gamescope, so that they follow the user between
+/// desktop and game mode. Gamescope only supports the night light.
+pub(crate) async fn apply_color_filters(
+    session: &Connection,
+    state: &ColorFiltersState,
+) -> Result<()> {
+    apply_kwin(session, state).await?;
+    // Gamescope isn't running in desktop mode
+    if let Err(e) = apply_gamescope(state).await {
+        debug!("Couldn't set gamescope night mode: {e}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use anyhow::{bail, ensure};
+    use std::ffi::OsStr;
+
+    #[test]
+    fn nightmode() {
+        let mut state = ColorFiltersState::default();
+        assert_eq!(f32::from_bits(gamescope_nightmode(&state)[0]), 0.0);
+
+        state.night_light = true;
+        state.night_light_temperature = 6500;
+        assert_eq!(f32::from_bits(gamescope_nightmode(&state)[0]), 0.0);
+        state.night_light_temperature = 1000;
+        assert_eq!(f32::from_bits(gamescope_nightmode(&state)[0]), 1.0);
+        state.night_light_temperature = 3750;
+        assert_eq!(f32::from_bits(gamescope_nightmode(&state)[0]), 0.5);
+        assert_eq!(
+            gamescope_nightmode(&state)[1..],
+            [GAMESCOPE_NIGHTMODE_HUE.to_bits(), 1.0_f32.to_bits()]
+        );
+    }
+
+    #[tokio::test]
+    async fn apply() {
+        let mut h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            if executable == XPROP_PATH {
+                bail!("No gamescope");
+            }
+            ensure!(executable == KWRITECONFIG_PATH, "Unknown executable");
+            match args {
+                [_, _, _, group, _, key, value] => match (
+                    group.to_str().unwrap(),
+                    key.to_str().unwrap(),
+                    value.to_str().unwrap(),
+                ) {
+                    ("Plugins", "colorblindnesscorrectionEnabled", "true")
+                    | ("Effect-colorblindnesscorrection", "Mode", "1")
+                    | ("NightColor", "Active", "false")
+                    | ("NightColor", "Mode", "Constant")
+                    | ("NightColor", "NightTemperature", "4500") => Ok((0, String::new())),
+                    config => bail!("Unexpected config {config:?}"),
+                },
+                _ => bail!("Unknown command"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let state = ColorFiltersState {
+            filter: ColorFilter::Deuteranopia,
+            ..ColorFiltersState::default()
+        };
+        apply_color_filters(&connection, &state)
+            .await
+            .expect("apply_color_filters");
+
+        let state = ColorFiltersState {
+            night_light: true,
+            ..state
+        };
+        assert!(apply_color_filters(&connection, &state).await.is_err());
+    }
+}
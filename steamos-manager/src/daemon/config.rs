@@ -5,15 +5,29 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use config::builder::AsyncState;
-use config::{ConfigBuilder, FileFormat, FileStoredFormat};
+use config::{ConfigBuilder, FileFormat};
+use inotify::{Event, EventStream, Inotify, WatchDescriptor, WatchMask};
+use std::ffi::OsString;
+use std::fmt::Debug;
 use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
 use tokio::fs::{create_dir_all, read_to_string, write};
-use tracing::{error, info};
+use tokio::sync::mpsc::Sender;
+use tokio::time::timeout;
+use tokio_stream::StreamExt;
+use toml::{Table, Value};
+use tracing::{debug, error, info, warn};
 
-use crate::daemon::DaemonContext;
-use crate::{read_config_directory, AsyncFileSource};
+use crate::daemon::{ConfigWatcherConfig, DaemonCommand, DaemonContext};
+use crate::{read_config_directory, AsyncFileSource, Service};
+
+const STATE_VERSION_KEY: &str = "state_version";
+
+/// Extensions of the TOML fragments read from `config.toml.d`
+const CONFIG_FRAGMENT_EXTENSIONS: [&str; 2] = ["conf", "toml"];
 
 pub(in crate::daemon) async fn read_state<C: DaemonContext>(context: &C) -> Result<C::State> {
     let path = context.state_path()?;
@@ -28,7 +42,30 @@ pub(in crate::daemon) async fn read_state<C: DaemonContext>(context: &C) -> Resu
             return Err(e.into());
         }
     };
-    Ok(toml::from_str(state.as_str())?)
+    let mut state: Table = toml::from_str(state.as_str())?;
+
+    // State files from before versioning was added don't have a version, and count as version 0
+    let version = match state.remove(STATE_VERSION_KEY) {
+        Some(Value::Integer(version)) => {
+            usize::try_from(version).map_err(|_| anyhow!("Invalid state version {version}"))?
+        }
+        Some(version) => return Err(anyhow!("Invalid state version {version}")),
+        None => 0,
+    };
+    let migrations = C::STATE_MIGRATIONS;
+    if version > migrations.len() {
+        warn!(
+            "State file version {version} is newer than the supported version {}, loading it anyway",
+            migrations.len()
+        );
+    } else {
+        for (from, migration) in migrations.iter().enumerate().skip(version) {
+            info!("Migrating state from version {from} to {}", from + 1);
+            migration(&mut state)
+                .inspect_err(|e| error!("Error migrating state from version {from}: {e}"))?;
+        }
+    }
+    Ok(state.try_into()?)
 }
 
 pub(in crate::daemon) async fn write_state<C: DaemonContext>(context: &C) -> Result<()> {
@@ -38,10 +75,22 @@ pub(in crate::daemon) async fn write_state<C: DaemonContext>(context: &C) -> Res
         path.to_string_lossy()
     ))?)
     .await?;
-    let state = toml::to_string_pretty(&context.state())?;
+    let mut state = Table::try_from(context.state())?;
+    state.insert(
+        String::from(STATE_VERSION_KEY),
+        Value::Integer(C::STATE_MIGRATIONS.len().try_into()?),
+    );
+    let state = toml::to_string_pretty(&state)?;
     Ok(write(path, state.as_bytes()).await?)
 }
 
+/// Read the daemon configuration. Later sources override individual keys of earlier ones, in this
+/// order:
+///
+/// 1. `config.toml` in the system config path
+/// 2. `*.conf` and `*.toml` fragments in `config.toml.d` in the system config path, sorted by name
+/// 3. `config.toml` in the user config path
+/// 4. `*.conf` and `*.toml` fragments in `config.toml.d` in the user config path, sorted by name
 pub(in crate::daemon) async fn read_config<C: DaemonContext>(context: &C) -> Result<C::Config> {
     let builder = ConfigBuilder::<AsyncState>::default();
     let system_config_path = context.system_config_path()?;
@@ -54,7 +103,7 @@ pub(in crate::daemon) async fn read_config<C: DaemonContext>(context: &C) -> Res
     let builder = read_config_directory(
         builder,
         system_config_path.join("config.toml.d"),
-        FileFormat::Toml.file_extensions(),
+        &CONFIG_FRAGMENT_EXTENSIONS,
         FileFormat::Toml,
     )
     .await?;
@@ -66,7 +115,7 @@ pub(in crate::daemon) async fn read_config<C: DaemonContext>(context: &C) -> Res
     let builder = read_config_directory(
         builder,
         user_config_path.join("config.toml.d"),
-        FileFormat::Toml.file_extensions(),
+        &CONFIG_FRAGMENT_EXTENSIONS,
         FileFormat::Toml,
     )
     .await?;
@@ -74,14 +123,107 @@ pub(in crate::daemon) async fn read_config<C: DaemonContext>(context: &C) -> Res
     Ok(config.try_deserialize()?)
 }
 
+/// Reloads the daemon configuration when any of the files [`read_config`] reads change
+pub(in crate::daemon) struct ConfigWatcher<T: Debug + Send> {
+    inotify: EventStream<[u8; 512]>,
+    fragment_watches: Vec<WatchDescriptor>,
+    channel: Sender<DaemonCommand<T>>,
+    debounce: Duration,
+}
+
+impl<T: Debug + Send> ConfigWatcher<T> {
+    pub(in crate::daemon) fn init<C: DaemonContext<Command = T>>(
+        context: &C,
+        channel: Sender<DaemonCommand<T>>,
+        config: &ConfigWatcherConfig,
+    ) -> Result<ConfigWatcher<T>> {
+        let inotify = Inotify::init()?.into_event_stream([0; 512])?;
+        let mask = WatchMask::CLOSE_WRITE
+            | WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::MOVED_FROM
+            | WatchMask::MOVED_TO;
+        let mut fragment_watches = Vec::new();
+        let mut watching = false;
+        for base in [context.system_config_path()?, context.user_config_path()?] {
+            // Directories that don't exist yet aren't picked up until the watcher is restarted
+            match inotify.watches().add(&base, mask) {
+                Ok(_) => watching = true,
+                Err(e) => debug!("Not watching {} for config changes: {e}", base.display()),
+            }
+            let fragments = base.join("config.toml.d");
+            match inotify.watches().add(&fragments, mask) {
+                Ok(wd) => {
+                    fragment_watches.push(wd);
+                    watching = true;
+                }
+                Err(e) => debug!(
+                    "Not watching {} for config changes: {e}",
+                    fragments.display()
+                ),
+            }
+        }
+        ensure!(watching, "No config directories found");
+        Ok(ConfigWatcher {
+            inotify,
+            fragment_watches,
+            channel,
+            debounce: Duration::from_millis(config.debounce),
+        })
+    }
+
+    fn is_config_file(&self, event: &Event<OsString>) -> bool {
+        let Some(ref name) = event.name else {
+            return false;
+        };
+        if self.fragment_watches.contains(&event.wd) {
+            Path::new(name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| CONFIG_FRAGMENT_EXTENSIONS.contains(&ext))
+        } else {
+            name == "config.toml"
+        }
+    }
+
+    async fn next_change(&mut self) -> Result<()> {
+        while let Some(event) = self.inotify.next().await {
+            let event = event?;
+            if self.is_config_file(&event) {
+                debug!("Got config event {:08x} for {:?}", event.mask, event.name);
+                return Ok(());
+            }
+        }
+        bail!("Config watcher inotify stream ended");
+    }
+}
+
+impl<T: Debug + Send> Service for ConfigWatcher<T> {
+    const NAME: &'static str = "config-watcher";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            self.next_change().await?;
+            // Editors and provisioning tools often write several files, or one file several
+            // times, so wait for changes to settle before reloading
+            while let Ok(res) = timeout(self.debounce, self.next_change()).await {
+                res?;
+            }
+            info!("Configuration changed, reloading");
+            self.channel.send(DaemonCommand::ReadConfig).await?;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::daemon::Daemon;
+    use crate::daemon::{Daemon, StateMigration};
     use crate::{path, testing, write_synced};
 
     use serde::{Deserialize, Serialize};
     use std::path::PathBuf;
+    use tokio::sync::mpsc::channel;
 
     #[derive(Deserialize, Serialize, Copy, Clone, Default, PartialEq, Debug)]
     struct TestSubstate {
@@ -101,7 +243,34 @@ mod test {
         config: TestState,
     }
 
+    // Version 1 renamed old_value to value
+    fn migrate_old_value(state: &mut Table) -> Result<()> {
+        if let Some(value) = state.remove("old_value") {
+            state.insert(String::from("value"), value);
+        }
+        Ok(())
+    }
+
+    // Version 2 moved subvalue into substate
+    fn migrate_subvalue(state: &mut Table) -> Result<()> {
+        let Some(subvalue) = state.remove("subvalue") else {
+            return Ok(());
+        };
+        let Value::Table(substate) = state
+            .entry("substate")
+            .or_insert_with(|| Value::Table(Table::new()))
+        else {
+            return Err(anyhow!("substate is not a table"));
+        };
+        substate.insert(String::from("subvalue"), subvalue);
+        Ok(())
+    }
+
     impl DaemonContext for TestContext {
+        const NAME: &'static str = "test";
+        const INTERFACE: &'static str = "com.steampowered.SteamOSManager1.Test";
+        const STATE_MIGRATIONS: &'static [StateMigration] = &[migrate_old_value, migrate_subvalue];
+
         type State = TestState;
         type Config = TestState;
         type Command = ();
@@ -234,12 +403,64 @@ mod test {
 
         write_state(&context).await.expect("write_state");
         let config = read_to_string(&state_path).await.expect("read_to_string");
-        assert_eq!(config, "value = 0\n\n[substate]\nsubvalue = 0\n");
+        assert_eq!(
+            config,
+            "state_version = 2\nvalue = 0\n\n[substate]\nsubvalue = 0\n"
+        );
 
         context.state.value = 1;
         write_state(&context).await.expect("write_state");
         let config = read_to_string(&state_path).await.expect("read_to_string");
-        assert_eq!(config, "value = 1\n\n[substate]\nsubvalue = 0\n");
+        assert_eq!(
+            config,
+            "state_version = 2\nvalue = 1\n\n[substate]\nsubvalue = 0\n"
+        );
+
+        let state = read_state(&context).await.expect("read_state");
+        assert_eq!(state, context.state);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_state() {
+        let _h = testing::start();
+
+        let context = TestContext::default();
+        let state_path = context.state_path().expect("state_path");
+        create_dir_all(state_path.parent().unwrap())
+            .await
+            .expect("create_dir_all");
+
+        let expected = TestState {
+            value: 1,
+            substate: TestSubstate { subvalue: 2 },
+        };
+        for fixture in [
+            // Unversioned, from before versioning was added
+            "old_value = 1\nsubvalue = 2\n",
+            "state_version = 0\nold_value = 1\nsubvalue = 2\n",
+            // Only the migrations after the recorded version are applied
+            "state_version = 1\nold_value = 3\nvalue = 1\nsubvalue = 2\n",
+            "state_version = 2\nvalue = 1\n\n[substate]\nsubvalue = 2\n",
+            // Newer than any known version, loaded as is
+            "state_version = 3\nvalue = 1\nsubvalue = 4\n\n[substate]\nsubvalue = 2\n",
+        ] {
+            write_synced(&state_path, fixture.as_bytes())
+                .await
+                .expect("write");
+            let state = read_state(&context).await.expect("read_state");
+            assert_eq!(state, expected, "{fixture}");
+        }
+
+        for fixture in [
+            "state_version = \"2\"\nvalue = 1\n",
+            "state_version = -1\nvalue = 1\n",
+            "state_version = 1\nsubvalue = 2\nsubstate = 3\n",
+        ] {
+            write_synced(&state_path, fixture.as_bytes())
+                .await
+                .expect("write");
+            assert!(read_state(&context).await.is_err(), "{fixture}");
+        }
     }
 
     #[tokio::test]
@@ -441,6 +662,139 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_config_fragment_ordering() {
+        let _h = testing::start();
+
+        let context = TestContext::default();
+
+        let system_config_path = context.system_config_path().expect("system_config_path");
+        create_dir_all(system_config_path.join("config.toml.d"))
+            .await
+            .expect("create_dir_all");
+
+        let user_config_path = context.user_config_path().expect("user_config_path");
+        create_dir_all(user_config_path.join("config.toml.d"))
+            .await
+            .expect("create_dir_all");
+
+        write_synced(
+            system_config_path.join("config.toml"),
+            "value = 1\n\n[substate]\nsubvalue = 1\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+
+        // Fragments are applied in order of their names, regardless of the extension
+        write_synced(
+            system_config_path.join("config.toml.d/10-oem.conf"),
+            "value = 2\n\n[substate]\nsubvalue = 2\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+        write_synced(
+            system_config_path.join("config.toml.d/20-oem.toml"),
+            "[substate]\nsubvalue = 3\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+        write_synced(
+            system_config_path.join("config.toml.d/30-ignored.ini"),
+            "value = 10\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+
+        let config = read_config(&context).await.expect("read_config");
+        assert_eq!(
+            config,
+            TestState {
+                value: 2,
+                substate: TestSubstate { subvalue: 3 }
+            }
+        );
+
+        // User config overrides all system fragments, and user fragments override that
+        write_synced(
+            user_config_path.join("config.toml"),
+            "value = 4\n\n[substate]\nsubvalue = 4\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+        write_synced(
+            user_config_path.join("config.toml.d/user.conf"),
+            "value = 5\n".as_bytes(),
+        )
+        .await
+        .expect("write");
+
+        let config = read_config(&context).await.expect("read_config");
+        assert_eq!(
+            config,
+            TestState {
+                value: 5,
+                substate: TestSubstate { subvalue: 4 }
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_config_watcher() {
+        let _h = testing::start();
+
+        let context = TestContext::default();
+        let (tx, mut rx) = channel(10);
+        let config = ConfigWatcherConfig {
+            enable: true,
+            debounce: 50,
+        };
+        assert!(ConfigWatcher::init(&context, tx.clone(), &config).is_err());
+
+        let system_config_path = context.system_config_path().expect("system_config_path");
+        create_dir_all(system_config_path.join("config.toml.d"))
+            .await
+            .expect("create_dir_all");
+
+        let mut watcher = ConfigWatcher::init(&context, tx, &config).expect("init");
+        let task = tokio::spawn(async move { watcher.run().await });
+
+        // Files that aren't read as config are ignored
+        write_synced(system_config_path.join("notes.txt"), b"value = 1\n")
+            .await
+            .expect("write");
+        write_synced(system_config_path.join("config.toml.d/frag.ini"), b"")
+            .await
+            .expect("write");
+        assert!(timeout(Duration::from_millis(200), rx.recv())
+            .await
+            .is_err());
+
+        // A burst of changes only causes one reload
+        for value in 1..4 {
+            write_synced(
+                system_config_path.join("config.toml"),
+                format!("value = {value}\n").as_bytes(),
+            )
+            .await
+            .expect("write");
+        }
+        write_synced(
+            system_config_path.join("config.toml.d/frag.conf"),
+            b"value = 4\n",
+        )
+        .await
+        .expect("write");
+        assert!(matches!(
+            timeout(Duration::from_secs(1), rx.recv()).await,
+            Ok(Some(DaemonCommand::ReadConfig))
+        ));
+        assert!(timeout(Duration::from_millis(200), rx.recv())
+            .await
+            .is_err());
+
+        task.abort();
+    }
+
     #[tokio::test]
     async fn test_read_system_config_fragments() {
         let _h = testing::start();
@@ -8,19 +8,31 @@
 use anyhow::{anyhow, ensure, Result};
 use nix::time::{clock_gettime, ClockId};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fmt::Debug;
+use std::future::Future;
 use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use strum::Display;
 use tokio::net::UnixDatagram;
 use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::Mutex;
 use tokio::task::JoinSet;
+use tokio::time::{sleep, Instant};
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, trace, warn, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
 use zbus::connection::Connection;
 use zbus::fdo::ObjectManager;
 
-use crate::daemon::config::{read_config, read_state, write_state};
+use crate::daemon::config::{read_config, read_state, write_state, ConfigWatcher};
+use crate::manager::user::MANAGER_PATH;
 use crate::Service;
 
 mod config;
@@ -30,11 +42,37 @@ pub(crate) mod user;
 pub use root::daemon as root;
 pub use user::daemon as user;
 
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static LOG_LEVELS: std::sync::Mutex<LogLevels> = std::sync::Mutex::new(LogLevels {
+    configured: None,
+    runtime: None,
+});
+
+#[derive(Debug)]
+struct LogLevels {
+    /// Filter from the configuration
+    configured: Option<String>,
+    /// Filter set with `SetLogLevel`, which is kept across reloads until it is cleared
+    runtime: Option<String>,
+}
+
+/// Upgrades a state file from one version to the next, before it is deserialized
+pub(crate) type StateMigration = fn(&mut toml::Table) -> Result<()>;
+
 pub(crate) trait DaemonContext: Sized {
+    /// Name of the daemon as reported in service status and signals
+    const NAME: &'static str;
+    /// Interface that signals about the daemon itself are emitted on
+    const INTERFACE: &'static str;
+
     type State: for<'a> Deserialize<'a> + Serialize + Default + Debug;
     type Config: for<'a> Deserialize<'a> + Default + Debug;
     type Command: Send + Debug;
 
+    /// Migrations of the state file, in order. The state file records how many of them have been
+    /// applied, so once released they must never be removed or reordered, only appended to.
+    const STATE_MIGRATIONS: &'static [StateMigration] = &[];
+
     fn state_path(&self) -> Result<PathBuf> {
         let config_path = self.user_config_path()?;
         Ok(config_path.join("state.toml"))
@@ -63,6 +101,189 @@ pub(crate) struct Daemon<C: DaemonContext> {
     connection: Connection,
     channel: Receiver<DaemonCommand<C::Command>>,
     notify_socket: NotifySocket,
+    status: ServiceStatus,
+    supervision: Arc<Mutex<SupervisionConfig>>,
+    config_watcher: Option<(ConfigWatcherConfig, CancellationToken)>,
+}
+
+#[derive(Copy, Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct RestartPolicy {
+    /// Number of times a failed service is restarted before the daemon gives up
+    pub max_restarts: u32,
+    /// Time in milliseconds to wait before the first restart, doubled for each further one
+    pub initial_backoff: u64,
+    /// Maximum time in milliseconds to wait before a restart
+    pub max_backoff: u64,
+    /// Time in milliseconds a service has to run for before failing for its earlier restarts to
+    /// be forgotten
+    pub stable_period: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> RestartPolicy {
+        RestartPolicy {
+            max_restarts: 3,
+            initial_backoff: 500,
+            max_backoff: 30_000,
+            stable_period: 60_000,
+        }
+    }
+}
+
+impl RestartPolicy {
+    fn backoff(&self, restarts: u32) -> Duration {
+        let factor = 1u64.checked_shl(restarts).unwrap_or(u64::MAX);
+        Duration::from_millis(
+            self.initial_backoff
+                .saturating_mul(factor)
+                .min(self.max_backoff),
+        )
+    }
+}
+
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct SupervisionConfig {
+    /// Policy for services that don't have their own
+    pub default: RestartPolicy,
+    /// Policies keyed by service name
+    pub services: HashMap<String, RestartPolicy>,
+}
+
+impl SupervisionConfig {
+    fn policy(&self, name: &str) -> RestartPolicy {
+        self.services.get(name).copied().unwrap_or(self.default)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct ConfigWatcherConfig {
+    /// Reload the configuration automatically when its files change
+    pub enable: bool,
+    /// Time in milliseconds to wait for changes to settle before reloading
+    pub debounce: u64,
+}
+
+impl Default for ConfigWatcherConfig {
+    fn default() -> ConfigWatcherConfig {
+        ConfigWatcherConfig {
+            enable: false,
+            debounce: 500,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct LogConfig {
+    /// Level for modules that don't have their own filter
+    pub level: String,
+    /// Levels keyed by module path, e.g. `"steamos_manager::power" = "debug"`
+    pub modules: BTreeMap<String, String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> LogConfig {
+        LogConfig {
+            level: String::from("info"),
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LogConfig {
+    fn directives(&self) -> String {
+        let mut directives = vec![self.level.clone()];
+        directives.extend(
+            self.modules
+                .iter()
+                .map(|(module, level)| format!("{module}={level}")),
+        );
+        directives.join(",")
+    }
+}
+
+#[derive(Display, Copy, Clone, PartialEq, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum ServiceState {
+    Running,
+    Stopped,
+    Failed,
+}
+
+#[derive(Clone, Debug)]
+struct ServiceHealth {
+    state: ServiceState,
+    restarts: u32,
+    last_error: Option<String>,
+    // A replaced service may still be shutting down after its replacement has started
+    instances: u32,
+}
+
+/// Health of every service that has been added to a daemon, keyed by service name
+#[derive(Clone, Default, Debug)]
+pub(crate) struct ServiceStatus(Arc<Mutex<HashMap<&'static str, ServiceHealth>>>);
+
+impl ServiceStatus {
+    async fn started(&self, name: &'static str) {
+        let mut services = self.0.lock().await;
+        match services.get_mut(name) {
+            Some(health) => {
+                health.state = ServiceState::Running;
+                health.restarts += 1;
+                health.instances += 1;
+            }
+            None => {
+                services.insert(
+                    name,
+                    ServiceHealth {
+                        state: ServiceState::Running,
+                        restarts: 0,
+                        last_error: None,
+                        instances: 1,
+                    },
+                );
+            }
+        }
+    }
+
+    async fn finished(&self, name: &'static str, res: &Result<()>) {
+        let mut services = self.0.lock().await;
+        let Some(health) = services.get_mut(name) else {
+            return;
+        };
+        health.instances = health.instances.saturating_sub(1);
+        match res {
+            Ok(()) if health.instances == 0 => health.state = ServiceState::Stopped,
+            Ok(()) => (),
+            Err(e) => {
+                health.state = ServiceState::Failed;
+                health.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// A list of the name, state, restart count and last error of each service, sorted by name
+    pub(crate) async fn list(&self) -> Vec<(String, String, u32, String)> {
+        let mut list: Vec<_> = self
+            .0
+            .lock()
+            .await
+            .iter()
+            .map(|(name, health)| {
+                (
+                    name.to_string(),
+                    health.state.to_string(),
+                    health.restarts,
+                    health.last_error.clone().unwrap_or_default(),
+                )
+            })
+            .collect();
+        list.sort();
+        list
+    }
 }
 
 #[derive(Debug)]
@@ -120,19 +341,144 @@ impl<C: DaemonContext> Daemon<C> {
             connection,
             channel,
             notify_socket: NotifySocket::default(),
+            status: ServiceStatus::default(),
+            supervision: Arc::new(Mutex::new(SupervisionConfig::default())),
+            config_watcher: None,
         };
 
         Ok(daemon)
     }
 
+    /// Add a service, which is run again as is if it fails
     pub(crate) fn add_service<S: Service + 'static>(&mut self, service: S) -> CancellationToken {
+        self.supervise(service, |service: Option<S>| async move {
+            service.ok_or_else(|| anyhow!("{} is gone", S::NAME))
+        })
+    }
+
+    /// Add a service that can't be run again once it has failed, so that each restart runs a
+    /// new instance from `factory` instead
+    pub(crate) fn add_service_with<S, F, Fut>(
+        &mut self,
+        service: S,
+        mut factory: F,
+    ) -> CancellationToken
+    where
+        S: Service + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<S>> + Send + 'static,
+    {
+        self.supervise(service, move |_| factory())
+    }
+
+    fn supervise<S, F, Fut>(&mut self, service: S, mut rebuild: F) -> CancellationToken
+    where
+        S: Service + 'static,
+        F: FnMut(Option<S>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<S>> + Send + 'static,
+    {
         let token = self.token.child_token();
         let moved_token = token.clone();
-        self.services
-            .spawn(async move { service.start(moved_token).await });
+        let status = self.status.clone();
+        let supervision = self.supervision.clone();
+        let connection = self.connection.clone();
+        let (daemon, interface) = (C::NAME, C::INTERFACE);
+        self.services.spawn(async move {
+            let mut next = Ok(service);
+            let mut restarts = 0;
+            loop {
+                let started = Instant::now();
+                let (res, previous) = match next {
+                    Ok(mut service) => {
+                        status.started(S::NAME).await;
+                        let res = service.start(&moved_token).await;
+                        status.finished(S::NAME, &res).await;
+                        (res, Some(service))
+                    }
+                    Err(e) => {
+                        warn!("Failed to recreate {}: {e}", S::NAME);
+                        (Err(e), None)
+                    }
+                };
+                let Err(e) = res else {
+                    return Ok(());
+                };
+
+                let policy = supervision.lock().await.policy(S::NAME);
+                // A failure after a long healthy run shouldn't count towards giving up
+                if started.elapsed() >= Duration::from_millis(policy.stable_period) {
+                    restarts = 0;
+                }
+                if moved_token.is_cancelled() || restarts >= policy.max_restarts {
+                    return Err(e);
+                }
+                let backoff = policy.backoff(restarts);
+                restarts += 1;
+                warn!(
+                    "Restarting {} in {} ms, attempt {restarts} of {}",
+                    S::NAME,
+                    backoff.as_millis(),
+                    policy.max_restarts
+                );
+                tokio::select! {
+                    () = sleep(backoff) => (),
+                    () = moved_token.cancelled() => return Ok(()),
+                }
+                if let Err(e) = connection
+                    .emit_signal(
+                        None::<&str>,
+                        MANAGER_PATH,
+                        interface,
+                        "ServiceRestarted",
+                        &(daemon, S::NAME, restarts),
+                    )
+                    .await
+                {
+                    warn!("Failed to emit ServiceRestarted signal: {e}");
+                }
+                next = rebuild(previous).await;
+            }
+        });
         token
     }
 
+    pub(crate) async fn configure_supervision(&self, config: SupervisionConfig) {
+        *self.supervision.lock().await = config;
+    }
+
+    /// Start, stop or restart the config watcher, which sends `ReadConfig` over `channel`
+    pub(crate) fn configure_config_watcher(
+        &mut self,
+        context: &C,
+        channel: &Sender<DaemonCommand<C::Command>>,
+        config: ConfigWatcherConfig,
+    ) where
+        C::Command: 'static,
+    {
+        // Don't restart the watcher on reloads that it triggered itself
+        if self
+            .config_watcher
+            .as_ref()
+            .is_some_and(|(current, _)| *current == config)
+        {
+            return;
+        }
+        if let Some((_, handle)) = self.config_watcher.take() {
+            handle.cancel();
+        }
+        if !config.enable {
+            return;
+        }
+        match ConfigWatcher::init(context, channel.clone(), &config) {
+            Ok(watcher) => self.config_watcher = Some((config, self.add_service(watcher))),
+            Err(e) => warn!("Failed to start config watcher: {e}"),
+        }
+    }
+
+    pub(crate) fn service_status(&self) -> ServiceStatus {
+        self.status.clone()
+    }
+
     pub(crate) fn get_connection(&self) -> Connection {
         self.connection.clone()
     }
@@ -251,6 +597,80 @@ impl<C: DaemonContext> Daemon<C> {
     }
 }
 
+/// Create the subscriber for the daemon's logs. When started by systemd, records are sent to the
+/// journal with their fields and those of the spans they were logged in, such as the interface,
+/// member and sender of the D-Bus call being handled. Otherwise they are printed to stdout.
+pub(crate) fn log_subscriber() -> impl Subscriber + for<'a> LookupSpan<'a> + Send + Sync {
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = LOG_FILTER.set(handle);
+
+    let journald = env::var_os("JOURNAL_STREAM").and_then(|_| {
+        tracing_journald::layer()
+            .inspect_err(|e| eprintln!("Couldn't connect to the journal: {e}"))
+            .ok()
+    });
+    let stdout = journald.is_none().then(fmt::layer);
+    Registry::default().with(filter).with(journald).with(stdout)
+}
+
+fn apply_log_filter(level: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(level)?;
+    LOG_FILTER
+        .get()
+        .ok_or(anyhow!("Log filter not set up"))?
+        .reload(filter)?;
+    info!("Log level set to {level}");
+    Ok(())
+}
+
+/// Apply the log filters from the configuration, unless they were overridden with `RUST_LOG` or
+/// at runtime with [`set_log_level`]
+pub(crate) fn configure_logging(config: &LogConfig) -> Result<()> {
+    if env::var_os(EnvFilter::DEFAULT_ENV).is_some() || LOG_FILTER.get().is_none() {
+        return Ok(());
+    }
+    let directives = config.directives();
+    EnvFilter::try_new(directives.as_str())?;
+    let mut levels = LOG_LEVELS
+        .lock()
+        .map_err(|_| anyhow!("Log levels poisoned"))?;
+    levels.configured = Some(directives.clone());
+    if let Some(ref runtime) = levels.runtime {
+        debug!("Keeping log level {runtime} instead of configured {directives}");
+        return Ok(());
+    }
+    apply_log_filter(directives.as_str())
+}
+
+/// Override the configured log filters until the daemon exits, or until this is called with an
+/// empty filter to go back to the configured ones
+pub(crate) fn set_log_level(level: &str) -> Result<()> {
+    let mut levels = LOG_LEVELS
+        .lock()
+        .map_err(|_| anyhow!("Log levels poisoned"))?;
+    if level.is_empty() {
+        levels.runtime = None;
+        let configured = levels
+            .configured
+            .clone()
+            .unwrap_or_else(|| LogConfig::default().directives());
+        return apply_log_filter(configured.as_str());
+    }
+    apply_log_filter(level)?;
+    levels.runtime = Some(level.to_string());
+    Ok(())
+}
+
+pub(crate) fn log_level() -> Result<String> {
+    Ok(LOG_FILTER
+        .get()
+        .ok_or(anyhow!("Log filter not set up"))?
+        .with_current(ToString::to_string)?)
+}
+
 // Rust doesn't support a good way to simplify this type yet
 // See <https://github.com/rust-lang/rust/issues/8995>
 #[allow(clippy::type_complexity)]
@@ -260,3 +680,92 @@ pub(crate) fn channel<C: DaemonContext>() -> (
 ) {
     mpsc::channel(10)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn log_config() {
+        let config: LogConfig = toml::from_str(
+            r#"
+level = "warn"
+
+[modules]
+"steamos_manager::power" = "debug"
+zbus = "error"
+"#,
+        )
+        .expect("from_str");
+        assert_eq!(
+            config.directives(),
+            "warn,steamos_manager::power=debug,zbus=error"
+        );
+        assert!(EnvFilter::try_new(config.directives()).is_ok());
+
+        assert_eq!(LogConfig::default().directives(), "info");
+    }
+
+    #[tokio::test]
+    async fn service_status() {
+        let status = ServiceStatus::default();
+        assert!(status.list().await.is_empty());
+
+        status.started("a").await;
+        status.started("b").await;
+        assert_eq!(
+            status.list().await,
+            vec![
+                (String::from("a"), String::from("running"), 0, String::new()),
+                (String::from("b"), String::from("running"), 0, String::new()),
+            ]
+        );
+
+        status.finished("a", &Err(anyhow!("oops"))).await;
+        status.finished("b", &Ok(())).await;
+        assert_eq!(
+            status.list().await,
+            vec![
+                (
+                    String::from("a"),
+                    String::from("failed"),
+                    0,
+                    String::from("oops")
+                ),
+                (String::from("b"), String::from("stopped"), 0, String::new()),
+            ]
+        );
+
+        // A replaced service shutting down doesn't affect its replacement
+        status.started("b").await;
+        status.started("b").await;
+        status.finished("b", &Ok(())).await;
+        assert_eq!(status.list().await[1].1, "running");
+        assert_eq!(status.list().await[1].2, 2);
+    }
+
+    #[test]
+    fn restart_policy() {
+        let policy = RestartPolicy {
+            max_restarts: 5,
+            initial_backoff: 100,
+            max_backoff: 1000,
+            stable_period: 10_000,
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(100), Duration::from_millis(1000));
+
+        let config = SupervisionConfig {
+            default: RestartPolicy::default(),
+            services: HashMap::from([(String::from("test"), policy)]),
+        };
+        assert_eq!(config.policy("test").max_restarts, 5);
+        assert_eq!(
+            config.policy("other").max_restarts,
+            RestartPolicy::default().max_restarts
+        );
+    }
+}
@@ -7,38 +7,82 @@
 
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use tokio::sync::mpsc::Sender;
+use tokio::fs::{create_dir_all, try_exists, write};
+use tokio::sync::mpsc::{unbounded_channel, Sender};
 use tokio::sync::oneshot;
+use tokio::task::spawn;
 use tokio_util::sync::CancellationToken;
-use tracing::error;
 use tracing::subscriber::set_global_default;
+use tracing::{error, info, warn};
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::{fmt, EnvFilter, Registry};
 use zbus::connection::{Builder, Connection};
 
-use crate::daemon::{channel, Daemon, DaemonCommand, DaemonContext};
+use crate::daemon::config::write_state;
+use crate::daemon::{
+    channel, configure_logging, log_subscriber, ConfigWatcherConfig, Daemon, DaemonCommand,
+    DaemonContext, LogConfig, SupervisionConfig,
+};
 use crate::ds_inhibit::Inhibitor;
+use crate::gpu::{gpu_performance_level_driver, GpuPerformanceLevelDriver};
+use crate::haptics::{set_haptic_feedback, set_haptics_intensity};
+use crate::hardware::{set_fan_curve, FanCurvePoint};
+use crate::headless::{HeadlessConfig, HeadlessMonitor};
 use crate::inputplumber::DeckService;
-use crate::manager::root::SteamOSManager;
+use crate::job::JobRecord;
+use crate::manager::root::{AuditLog1, RootFilesystem1, SteamOSManager, STEAMOS_READONLY};
+use crate::network_limit::NetworkLimitService;
 use crate::path;
-use crate::power::SysfsWriterService;
+use crate::platform::platform_config;
+use crate::policy::{configure_policy, Guarded, PolicyConfig};
+use crate::power::{
+    configure_sysfs_writer, set_max_charge_level, SysfsWriterConfig, SysfsWriterService,
+};
+use crate::process::run_script;
 use crate::sls::ftrace::Ftrace;
 use crate::sls::{LogLayer, LogReceiver};
+use crate::wake::{set_wake_source_enabled, WakeSource};
 
-#[derive(Copy, Clone, Default, Deserialize, Debug)]
+#[derive(Clone, Default, Deserialize, Debug)]
 #[serde(default)]
 pub(crate) struct RootConfig {
     pub services: RootServicesConfig,
+    pub supervision: SupervisionConfig,
+    pub config_watcher: ConfigWatcherConfig,
+    pub log: LogConfig,
+    pub restore: RootRestoreConfig,
+    pub policy: PolicyConfig,
 }
 
+/// Settings that are reapplied from the saved state when the daemon starts
 #[derive(Copy, Clone, Default, Deserialize, Debug)]
-pub(crate) struct RootServicesConfig {}
+#[serde(default)]
+pub(crate) struct RootRestoreConfig {
+    pub gpu_performance_level: bool,
+    pub max_charge_level: bool,
+}
 
-#[derive(Copy, Clone, Default, Deserialize, Serialize, Debug)]
+#[derive(Copy, Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct RootServicesConfig {
+    pub sysfs_writer: SysfsWriterConfig,
+    pub headless: HeadlessConfig,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize, Debug)]
 #[serde(default)]
 pub(crate) struct RootState {
     pub services: RootServicesState,
+    pub fan_curve: Option<Vec<FanCurvePoint>>,
+    pub gpu_performance_level: Option<String>,
+    pub manual_gpu_clock: Option<u32>,
+    pub haptics_intensity: Option<u32>,
+    pub haptic_feedback: Option<bool>,
+    pub max_charge_level: Option<i32>,
+    pub wake_sources: HashMap<String, bool>,
+    pub job_history: VecDeque<JobRecord>,
+    pub scheduled_updates: ScheduledUpdates,
 }
 
 #[derive(Copy, Clone, Default, Deserialize, Serialize, Debug)]
@@ -46,12 +90,46 @@ pub(crate) struct RootServicesState {
     pub ds_inhibit: DsInhibit,
 }
 
+/// Updates that will be run the next time the device boots
+#[derive(Copy, Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct ScheduledUpdates {
+    pub bios: bool,
+    pub dock: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum ScheduledUpdate {
+    Bios,
+    Dock,
+}
+
 #[derive(Debug)]
 pub(crate) enum RootCommand {
     SetDsInhibit(bool),
     GetDsInhibit(oneshot::Sender<bool>),
+    SetFanCurve(Option<Vec<FanCurvePoint>>),
+    SetGpuPerformanceLevel(String),
+    SetManualGpuClock(u32),
+    SetHapticsIntensity(u32),
+    SetHapticFeedback(bool),
+    SetMaxChargeLevel(i32),
+    SetWakeSourceEnabled(WakeSource, bool),
+    RestoreWakeSources,
+    RecordJob(JobRecord),
+    GetJobHistory(u32, oneshot::Sender<Vec<JobRecord>>),
+    ScheduleUpdate(ScheduledUpdate, bool),
+    GetScheduledUpdates(oneshot::Sender<ScheduledUpdates>),
+    GetServiceStatus(oneshot::Sender<Vec<(String, String, u32, String)>>),
 }
 
+// Number of completed jobs kept in the job history
+const MAX_JOB_HISTORY: usize = 20;
+
+// Marks that scheduled updates were already handled during this boot, so that restarting the
+// daemon doesn't run updates scheduled since then
+const SCHEDULED_UPDATES_STAMP: &str = "/run/steamos-manager/scheduled-updates-checked";
+
 #[derive(Copy, Clone, Deserialize, Serialize, Debug)]
 pub(crate) struct DsInhibit {
     pub enabled: bool,
@@ -68,6 +146,7 @@ pub(crate) struct RootContext {
     channel: Sender<Command>,
 
     ds_inhibit: Option<CancellationToken>,
+    headless: Option<CancellationToken>,
 }
 
 impl RootContext {
@@ -76,6 +155,39 @@ impl RootContext {
             state: RootState::default(),
             channel,
             ds_inhibit: None,
+            headless: None,
+        }
+    }
+
+    async fn restore_hardware_state(&self, config: RootRestoreConfig) {
+        if config.gpu_performance_level {
+            if let Some(ref level) = self.state.gpu_performance_level {
+                if let Err(e) =
+                    restore_gpu_performance_level(level, self.state.manual_gpu_clock).await
+                {
+                    error!("Failed to restore GPU performance level: {e}");
+                }
+            }
+        }
+        if config.max_charge_level {
+            if let Some(level) = self.state.max_charge_level {
+                if let Err(e) = set_max_charge_level(level).await {
+                    error!("Failed to restore max charge level: {e}");
+                }
+            }
+        }
+    }
+
+    async fn restore_wake_sources(&self) {
+        for (source, enabled) in &self.state.wake_sources {
+            let Ok(source) = source.parse::<WakeSource>() else {
+                warn!("Unknown wake source {source} in saved state");
+                continue;
+            };
+            if let Err(e) = set_wake_source_enabled(source, *enabled).await {
+                // The device, e.g. the dock, may just not be connected right now
+                warn!("Failed to restore wake source {source}: {e}");
+            }
         }
     }
 
@@ -96,9 +208,100 @@ impl RootContext {
         }
         Ok(())
     }
+
+    async fn reload_headless(
+        &mut self,
+        config: HeadlessConfig,
+        daemon: &mut Daemon<RootContext>,
+    ) -> Result<()> {
+        match (config.enable, self.headless.as_ref()) {
+            (false, Some(handle)) => {
+                handle.cancel();
+                self.headless = None;
+            }
+            (true, None) => {
+                let monitor =
+                    HeadlessMonitor::init(daemon.get_connection(), self.channel.clone()).await?;
+                self.headless = Some(daemon.add_service(monitor));
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    async fn run_scheduled_updates(&mut self) {
+        let stamp = path(SCHEDULED_UPDATES_STAMP);
+        match try_exists(&stamp).await {
+            Ok(false) => (),
+            Ok(true) => return,
+            Err(e) => {
+                error!("Failed to check for scheduled update stamp: {e}");
+                return;
+            }
+        }
+        if let Some(parent) = stamp.parent() {
+            if let Err(e) = create_dir_all(parent).await {
+                error!("Failed to create scheduled update stamp directory: {e}");
+                return;
+            }
+        }
+        if let Err(e) = write(&stamp, "").await {
+            error!("Failed to write scheduled update stamp, not running updates: {e}");
+            return;
+        }
+
+        let scheduled = self.state.scheduled_updates;
+        if !scheduled.bios && !scheduled.dock {
+            return;
+        }
+
+        // Clear the schedule before running anything, as updates may reboot the device
+        // and a failing update shouldn't be retried on every boot
+        self.state.scheduled_updates = ScheduledUpdates::default();
+        if let Err(e) = write_state(self).await {
+            error!("Failed to clear scheduled updates, not running them: {e}");
+            return;
+        }
+
+        let config = match platform_config().await {
+            Ok(Some(config)) => config,
+            Ok(None) => return,
+            Err(e) => {
+                error!("Failed to load platform config for scheduled updates: {e}");
+                return;
+            }
+        };
+        let mut updates = Vec::new();
+        for (scheduled, name, config) in [
+            (scheduled.bios, "BIOS", config.update_bios.as_ref()),
+            (scheduled.dock, "dock", config.update_dock.as_ref()),
+        ] {
+            if !scheduled {
+                continue;
+            }
+            match config {
+                Some(config) => updates.push((name, config.clone())),
+                None => warn!("Scheduled {name} update is not supported on this platform"),
+            }
+        }
+
+        // Flashing can take a while, so don't keep the daemon off the bus until it's done. The
+        // updates still run one after the other.
+        spawn(async move {
+            for (name, config) in updates {
+                info!("Running scheduled {name} update");
+                if let Err(e) = run_script(&config.script, &config.script_args).await {
+                    error!("Scheduled {name} update failed: {e}");
+                }
+            }
+        });
+    }
 }
 
 impl DaemonContext for RootContext {
+    const NAME: &'static str = "system";
+    const INTERFACE: &'static str = "com.steampowered.SteamOSManager1.RootManager";
+
     type State = RootState;
     type Config = RootConfig;
     type Command = RootCommand;
@@ -118,32 +321,72 @@ impl DaemonContext for RootContext {
     async fn start(
         &mut self,
         state: RootState,
-        _config: RootConfig,
+        config: RootConfig,
         daemon: &mut Daemon<RootContext>,
     ) -> Result<()> {
         self.state = state;
+        if let Err(e) = configure_logging(&config.log) {
+            warn!("Invalid log configuration: {e}");
+        }
+        daemon.configure_supervision(config.supervision).await;
+        daemon.configure_config_watcher(self, &self.channel, config.config_watcher);
+        configure_policy(config.policy).await;
 
         let connection = daemon.get_connection();
         let ftrace = Ftrace::init(&connection).await?;
         daemon.add_service(ftrace);
 
-        let ip = DeckService::init(connection);
+        let ip = DeckService::init(connection.clone());
         daemon.add_service(ip);
 
-        let sysfs = SysfsWriterService::init()?;
+        let sysfs = SysfsWriterService::init(connection, config.services.sysfs_writer)?;
         daemon.add_service(sysfs);
 
+        self.run_scheduled_updates().await;
+
         self.reload_ds_inhibit(daemon).await?;
 
+        // Offline mode is opt-in, as it exposes settings on the system bus while nobody is around
+        if let Err(e) = self.reload_headless(config.services.headless, daemon).await {
+            error!("Failed to set up headless mode: {e}");
+        }
+
+        if let Some(ref curve) = self.state.fan_curve {
+            if let Err(e) = set_fan_curve(curve).await {
+                error!("Failed to restore fan curve: {e}");
+            }
+        }
+
+        if let Some(intensity) = self.state.haptics_intensity {
+            if let Err(e) = set_haptics_intensity(intensity).await {
+                error!("Failed to restore haptics intensity: {e}");
+            }
+        }
+
+        if let Some(enabled) = self.state.haptic_feedback {
+            if let Err(e) = set_haptic_feedback(enabled).await {
+                error!("Failed to restore haptic feedback: {e}");
+            }
+        }
+
+        self.restore_wake_sources().await;
+
+        self.restore_hardware_state(config.restore).await;
+
         Ok(())
     }
 
-    async fn reload(
-        &mut self,
-        _config: RootConfig,
-        _daemon: &mut Daemon<RootContext>,
-    ) -> Result<()> {
-        // Nothing to do yet
+    async fn reload(&mut self, config: RootConfig, daemon: &mut Daemon<RootContext>) -> Result<()> {
+        if let Err(e) = configure_logging(&config.log) {
+            warn!("Invalid log configuration: {e}");
+        }
+        daemon.configure_supervision(config.supervision).await;
+        daemon.configure_config_watcher(self, &self.channel, config.config_watcher);
+        configure_sysfs_writer(config.services.sysfs_writer).await;
+        configure_policy(config.policy).await;
+        if let Err(e) = self.reload_headless(config.services.headless, daemon).await {
+            error!("Failed to set up headless mode: {e}");
+        }
         Ok(())
     }
 
@@ -161,6 +404,66 @@ impl DaemonContext for RootContext {
             RootCommand::GetDsInhibit(sender) => {
                 let _ = sender.send(self.ds_inhibit.is_some());
             }
+            RootCommand::SetFanCurve(curve) => {
+                self.state.fan_curve = curve;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetGpuPerformanceLevel(level) => {
+                self.state.gpu_performance_level = Some(level);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetManualGpuClock(clocks) => {
+                self.state.manual_gpu_clock = Some(clocks);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetHapticsIntensity(intensity) => {
+                self.state.haptics_intensity = Some(intensity);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetHapticFeedback(enabled) => {
+                self.state.haptic_feedback = Some(enabled);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetMaxChargeLevel(level) => {
+                self.state.max_charge_level = Some(level);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::SetWakeSourceEnabled(source, enabled) => {
+                self.state.wake_sources.insert(source.to_string(), enabled);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::RestoreWakeSources => self.restore_wake_sources().await,
+            RootCommand::RecordJob(record) => {
+                self.state.job_history.push_back(record);
+                while self.state.job_history.len() > MAX_JOB_HISTORY {
+                    self.state.job_history.pop_front();
+                }
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::GetJobHistory(count, sender) => {
+                let history = self
+                    .state
+                    .job_history
+                    .iter()
+                    .rev()
+                    .take(count as usize)
+                    .cloned()
+                    .collect();
+                let _ = sender.send(history);
+            }
+            RootCommand::ScheduleUpdate(update, scheduled) => {
+                match update {
+                    ScheduledUpdate::Bios => self.state.scheduled_updates.bios = scheduled,
+                    ScheduledUpdate::Dock => self.state.scheduled_updates.dock = scheduled,
+                }
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            RootCommand::GetScheduledUpdates(sender) => {
+                let _ = sender.send(self.state.scheduled_updates);
+            }
+            RootCommand::GetServiceStatus(sender) => {
+                let _ = sender.send(daemon.service_status().list().await);
+            }
         }
         Ok(())
     }
@@ -168,30 +471,58 @@ impl DaemonContext for RootContext {
 
 pub(crate) type Command = DaemonCommand<RootCommand>;
 
-async fn create_connection(channel: Sender<Command>) -> Result<Connection> {
+async fn restore_gpu_performance_level(level: &str, clocks: Option<u32>) -> Result<()> {
+    let driver = gpu_performance_level_driver().await?;
+    let level = driver.performance_level_from_str(level)?;
+    driver.set_performance_level(level).await?;
+    // Manual clocks only take effect, and are only accepted, in the manual level
+    match clocks {
+        Some(clocks) if level.is_manual() => driver.set_clocks(clocks).await,
+        _ => Ok(()),
+    }
+}
+
+async fn create_connection(channel: Sender<Command>) -> Result<(Connection, NetworkLimitService)> {
     let connection = Builder::system()?
         .name("com.steampowered.SteamOSManager1")?
         .build()
         .await?;
-    let manager = SteamOSManager::new(connection.clone(), channel).await?;
+
+    let (network_limit_tx, rx) = unbounded_channel();
+    let network_limit_service = NetworkLimitService::new(rx);
+
+    let manager = SteamOSManager::new(connection.clone(), channel, network_limit_tx).await?;
+    connection
+        .object_server()
+        .at("/com/steampowered/SteamOSManager1", Guarded::new(manager))
+        .await?;
     connection
         .object_server()
-        .at("/com/steampowered/SteamOSManager1", manager)
+        .at(
+            "/com/steampowered/SteamOSManager1",
+            Guarded::new(AuditLog1 {}),
+        )
         .await?;
-    Ok(connection)
+    if try_exists(path(STEAMOS_READONLY)).await.unwrap_or_default() {
+        connection
+            .object_server()
+            .at(
+                "/com/steampowered/SteamOSManager1",
+                Guarded::new(RootFilesystem1 {}),
+            )
+            .await?;
+    }
+    Ok((connection, network_limit_service))
 }
 
 pub async fn daemon() -> Result<()> {
     // This daemon is responsible for creating a dbus api that steam client can use to do various OS
     // level things. It implements com.steampowered.SteamOSManager1.RootManager interface
 
-    let stdout_log = fmt::layer();
-    let subscriber = Registry::default()
-        .with(stdout_log)
-        .with(EnvFilter::from_default_env());
+    let subscriber = log_subscriber();
     let (tx, rx) = channel::<RootContext>();
 
-    let connection = match create_connection(tx.clone()).await {
+    let (connection, network_limit_service) = match create_connection(tx.clone()).await {
         Ok(c) => c,
         Err(e) => {
             let _guard = tracing::subscriber::set_default(subscriber);
@@ -207,6 +538,7 @@ pub async fn daemon() -> Result<()> {
     let context = RootContext::new(tx);
     let mut daemon = Daemon::new(connection.clone(), rx).await?;
     daemon.add_service(log_receiver);
+    daemon.add_service(network_limit_service);
 
     daemon.run(context).await
 }
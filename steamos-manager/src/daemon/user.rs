@@ -9,57 +9,206 @@
 use anyhow::anyhow;
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use tokio::sync::mpsc::{unbounded_channel, Sender};
+use tokio::sync::mpsc::{unbounded_channel, Sender, UnboundedSender};
 use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
 use tracing::subscriber::set_global_default;
-use tracing::{error, info};
-use tracing_subscriber::prelude::*;
-use tracing_subscriber::{fmt, EnvFilter, Registry};
+use tracing::{error, info, warn};
 #[cfg(not(test))]
 use xdg::BaseDirectories;
 use zbus::connection::{Builder, Connection};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 
-use crate::daemon::{channel, Daemon, DaemonCommand, DaemonContext};
+use crate::als::{AlsMonitor, AlsMonitorConfig};
+use crate::battery_policy::{BatteryPolicyCommand, BatteryPolicyConfig, BatteryPolicyService};
+use crate::cache::invalidate_sysfs_caches;
+use crate::cec::CecRemoteMonitor;
+use crate::color_filters::{apply_color_filters, ColorFiltersState};
+use crate::daemon::{
+    channel, configure_logging, log_subscriber, ConfigWatcherConfig, Daemon, DaemonCommand,
+    DaemonContext, LogConfig, SupervisionConfig,
+};
+use crate::display::DisplayState;
+use crate::dock::DockMonitor;
+use crate::gpu::GpuThrottleMonitor;
+use crate::idle::{IdlePolicyCommand, IdlePolicyService, IdlePolicyState};
+use crate::input_inhibit::{InputInhibitCommand, InputInhibitService};
 use crate::job::{JobManager, JobManagerService};
-use crate::manager::user::{create_interfaces, SignalRelayService};
+use crate::manager::user::{
+    configure_setter_throttle, create_interfaces, root_manager_proxy, AmbientLightSensor1,
+    SetterThrottleConfig, SignalRelayService, WifiInfo1, MANAGER_PATH,
+};
 use crate::path;
-use crate::power::TdpManagerService;
-use crate::session::SessionManagerState;
+use crate::policy::{configure_policy, Guarded, PolicyConfig};
+use crate::power::{PowerPresetsState, TdpLimits, TdpManagerCommand, TdpManagerService};
+use crate::power_source::PowerSourceMonitor;
+use crate::screenreader::SpeechMonitor;
+use crate::services::UnitStateMonitor;
+use crate::session::{DesktopSessionMonitor, SessionManagerState};
+use crate::suspend_inhibit::SuspendInhibitService;
+use crate::telemetry::{TelemetryCommand, TelemetryConfig, TelemetryService};
+use crate::thermal::{ThermalMonitor, ThermalMonitorConfig};
 use crate::udev::UdevMonitor;
+use crate::wifi::{WifiInfoMonitor, WifiInfoMonitorConfig};
 
-#[derive(Copy, Clone, Default, Deserialize, Debug)]
+#[derive(Clone, Default, Deserialize, Debug)]
 #[serde(default)]
 pub(crate) struct UserConfig {
     pub services: UserServicesConfig,
+    pub supervision: SupervisionConfig,
+    pub config_watcher: ConfigWatcherConfig,
+    pub log: LogConfig,
+    pub restore: UserRestoreConfig,
+    pub throttle: SetterThrottleConfig,
+    pub policy: PolicyConfig,
 }
 
+/// Settings that are reapplied from the saved state when the daemon starts
 #[derive(Copy, Clone, Default, Deserialize, Debug)]
-pub(crate) struct UserServicesConfig {}
+#[serde(default)]
+pub(crate) struct UserRestoreConfig {
+    pub tdp_limit: bool,
+}
+
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct UserServicesConfig {
+    pub als_monitor: AlsMonitorConfig,
+    pub battery_policy: BatteryPolicyConfig,
+    pub telemetry: TelemetryConfig,
+    pub thermal_monitor: ThermalMonitorConfig,
+    pub wifi_info_monitor: WifiInfoMonitorConfig,
+}
 
 #[derive(Clone, Default, Deserialize, Serialize, Debug)]
 #[serde(default)]
 pub(crate) struct UserState {
     pub services: UserServicesState,
     pub session_manager: SessionManagerState,
+    pub power_presets: PowerPresetsState,
+    pub color_filters: ColorFiltersState,
+    pub idle_policy: IdlePolicyState,
+    pub display: DisplayState,
 }
 
 #[derive(Clone, Default, Deserialize, Serialize, Debug)]
-pub(crate) struct UserServicesState {}
+#[serde(default)]
+pub(crate) struct UserServicesState {
+    /// Preferred TDP limits, keyed by Steam app ID
+    pub tdp_profiles: HashMap<String, u32>,
+    /// TDP limit most recently set by a client
+    pub tdp_limit: Option<u32>,
+    /// Separate TDP limits most recently set by a client, if they were set after `tdp_limit`
+    pub tdp_limits: Option<TdpLimits>,
+}
 
 #[derive(Debug)]
 pub(crate) enum UserCommand {
     SetSessionManagerState(SessionManagerState),
     GetSessionManagerState(oneshot::Sender<SessionManagerState>),
+    SetPowerPresets(PowerPresetsState),
+    GetPowerPresets(oneshot::Sender<PowerPresetsState>),
+    SetColorFilters(ColorFiltersState),
+    GetColorFilters(oneshot::Sender<ColorFiltersState>),
+    SetIdlePolicy(IdlePolicyState),
+    GetIdlePolicy(oneshot::Sender<IdlePolicyState>),
+    SetDisplayState(DisplayState),
+    GetDisplayState(oneshot::Sender<DisplayState>),
+    SetTdpProfiles(HashMap<u32, u32>),
+    SetTdpLimit(u32),
+    SetTdpLimits(TdpLimits),
+    SetAlsStreaming(bool),
+    GetServiceStatus(oneshot::Sender<Vec<(String, String, u32, String)>>),
 }
 
 pub(crate) struct UserContext {
     session: Connection,
+    system: Connection,
     state: UserState,
     channel: Sender<Command>,
+    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    telemetry: UnboundedSender<TelemetryCommand>,
+    battery_policy: UnboundedSender<BatteryPolicyCommand>,
+    idle_policy: UnboundedSender<IdlePolicyCommand>,
+
+    als_config: AlsMonitorConfig,
+    als_monitor: Option<CancellationToken>,
+    thermal_monitor: Option<CancellationToken>,
+    wifi_info_monitor: Option<CancellationToken>,
+}
+
+impl UserContext {
+    async fn set_als_streaming(&mut self, enabled: bool, daemon: &mut Daemon<UserContext>) {
+        if let Some(handle) = self.als_monitor.take() {
+            handle.cancel();
+        }
+        // Go back to reading the sensor directly instead of reporting a stale debounced value
+        if let Ok(als) = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<AmbientLightSensor1>>(MANAGER_PATH)
+            .await
+        {
+            let _ = als
+                .get_mut()
+                .await
+                .set_lux(None, als.signal_emitter())
+                .await;
+        }
+        if enabled {
+            match AlsMonitor::init(&self.session, &self.als_config).await {
+                Ok(monitor) => self.als_monitor = Some(daemon.add_service(monitor)),
+                Err(e) => warn!("Failed to start ambient light sensor monitor: {e}"),
+            }
+        }
+    }
+
+    fn reload_thermal_monitor(
+        &mut self,
+        config: &ThermalMonitorConfig,
+        daemon: &mut Daemon<UserContext>,
+    ) {
+        if let Some(handle) = self.thermal_monitor.take() {
+            handle.cancel();
+        }
+        let monitor = ThermalMonitor::init(&self.session, config);
+        if monitor.has_thresholds() {
+            self.thermal_monitor = Some(daemon.add_service(monitor));
+        }
+    }
+
+    async fn reload_wifi_info_monitor(
+        &mut self,
+        config: &WifiInfoMonitorConfig,
+        daemon: &mut Daemon<UserContext>,
+    ) {
+        if let Some(handle) = self.wifi_info_monitor.take() {
+            handle.cancel();
+        }
+        let proxy = match root_manager_proxy(&self.system).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                warn!("Failed to start Wi-Fi link monitor: {e}");
+                return;
+            }
+        };
+        let monitor = WifiInfoMonitor::<WifiInfo1>::init(
+            &self.session,
+            &self.system,
+            OwnedObjectPath::from(ObjectPath::from_static_str_unchecked(MANAGER_PATH)),
+            proxy,
+            config,
+        );
+        self.wifi_info_monitor = Some(daemon.add_service(monitor));
+    }
 }
 
 impl DaemonContext for UserContext {
+    const NAME: &'static str = "user";
+    const INTERFACE: &'static str = "com.steampowered.SteamOSManager1.Manager2";
+
     type State = UserState;
     type Config = UserConfig;
     type Command = UserCommand;
@@ -88,30 +237,137 @@ impl DaemonContext for UserContext {
     async fn start(
         &mut self,
         state: UserState,
-        _config: UserConfig,
+        config: UserConfig,
         daemon: &mut Daemon<UserContext>,
     ) -> Result<()> {
         self.state = state;
+        if let Err(e) = configure_logging(&config.log) {
+            warn!("Invalid log configuration: {e}");
+        }
+        daemon.configure_supervision(config.supervision).await;
+        daemon.configure_config_watcher(self, &self.channel, config.config_watcher);
+        configure_setter_throttle(config.throttle).await;
+        configure_policy(config.policy).await;
+
+        let (power_supply_tx, power_supply_rx) = unbounded_channel();
+        let udev = UdevMonitor::init(&self.session, &self.system, power_supply_tx.clone()).await?;
+        let (session, system) = (self.session.clone(), self.system.clone());
+        daemon.add_service_with(udev, move || {
+            let (session, system) = (session.clone(), system.clone());
+            let power_supply_tx = power_supply_tx.clone();
+            async move { UdevMonitor::init(&session, &system, power_supply_tx).await }
+        });
+
+        match PowerSourceMonitor::init(&self.session, power_supply_rx).await {
+            Ok(monitor) => {
+                daemon.add_service(monitor);
+            }
+            Err(e) => warn!("Failed to start power source monitor: {e}"),
+        }
+
+        // Most platforms don't have a dock config, so there's nothing to warn about
+        if let Ok(monitor) = DockMonitor::init(&self.session, &self.system).await {
+            daemon.add_service(monitor);
+        }
+
+        // HDMI-CEC isn't available everywhere, so there's nothing to warn about
+        if let Ok(monitor) = CecRemoteMonitor::init(&self.session).await {
+            daemon.add_service(monitor);
+        }
+
+        // The screen reader isn't always available, so there's nothing to warn about
+        if let Ok(monitor) = SpeechMonitor::init(&self.session).await {
+            daemon.add_service(monitor);
+        }
+
+        // Sessions are only managed on some images, so there's nothing to warn about
+        if let Ok(monitor) = DesktopSessionMonitor::init(&self.session).await {
+            daemon.add_service(monitor);
+        }
+
+        // Only some GPUs report the temperatures they throttle at, so there's nothing to warn about
+        if let Ok(monitor) = GpuThrottleMonitor::init(&self.session).await {
+            daemon.add_service(monitor);
+        }
 
-        let udev = UdevMonitor::init(&self.session).await?;
-        daemon.add_service(udev);
+        // Most platforms don't whitelist any units, so there's nothing to warn about
+        if let Ok(monitor) = UnitStateMonitor::init(&self.session, &self.system).await {
+            daemon.add_service(monitor);
+        }
+
+        self.reload_thermal_monitor(&config.services.thermal_monitor, daemon);
+        self.reload_wifi_info_monitor(&config.services.wifi_info_monitor, daemon)
+            .await;
+        self.telemetry
+            .send(TelemetryCommand::Configure(config.services.telemetry))?;
+        self.battery_policy.send(BatteryPolicyCommand::Configure(
+            config.services.battery_policy,
+        ))?;
+        self.als_config = config.services.als_monitor;
+        self.idle_policy
+            .send(IdlePolicyCommand::Configure(self.state.idle_policy))?;
+
+        // Leave the compositors alone unless colour filters were ever configured
+        if self.state.color_filters != ColorFiltersState::default() {
+            if let Err(e) = apply_color_filters(&self.session, &self.state.color_filters).await {
+                warn!("Failed to restore colour filters: {e}");
+            }
+        }
+
+        if let Some(tdp_manager) = self.tdp_manager.as_ref() {
+            if config.restore.tdp_limit {
+                if let Some(limits) = self.state.services.tdp_limits {
+                    // Failures are logged by the TDP manager, so the reply isn't needed
+                    let (tx, _) = oneshot::channel();
+                    tdp_manager.send(TdpManagerCommand::SetTdpLimits(limits, tx))?;
+                } else if let Some(limit) = self.state.services.tdp_limit {
+                    tdp_manager.send(TdpManagerCommand::SetTdpLimit(limit))?;
+                }
+            }
+
+            let mut profiles = HashMap::new();
+            for (app_id, limit) in self.state.services.tdp_profiles.iter() {
+                match app_id.parse() {
+                    Ok(app_id) => {
+                        profiles.insert(app_id, *limit);
+                    }
+                    Err(_) => warn!("Ignoring TDP profile for invalid app ID {app_id}"),
+                }
+            }
+            tdp_manager.send(TdpManagerCommand::LoadProfiles(profiles))?;
+        }
 
         Ok(())
     }
 
-    async fn reload(
-        &mut self,
-        _config: UserConfig,
-        _daemon: &mut Daemon<UserContext>,
-    ) -> Result<()> {
-        // Nothing to do yet
+    async fn reload(&mut self, config: UserConfig, daemon: &mut Daemon<UserContext>) -> Result<()> {
+        if let Err(e) = configure_logging(&config.log) {
+            warn!("Invalid log configuration: {e}");
+        }
+        daemon.configure_supervision(config.supervision).await;
+        daemon.configure_config_watcher(self, &self.channel, config.config_watcher);
+        configure_setter_throttle(config.throttle).await;
+        configure_policy(config.policy).await;
+        invalidate_sysfs_caches();
+        self.reload_thermal_monitor(&config.services.thermal_monitor, daemon);
+        self.reload_wifi_info_monitor(&config.services.wifi_info_monitor, daemon)
+            .await;
+        self.telemetry
+            .send(TelemetryCommand::Configure(config.services.telemetry))?;
+        self.battery_policy.send(BatteryPolicyCommand::Configure(
+            config.services.battery_policy,
+        ))?;
+        self.als_config = config.services.als_monitor;
+        if self.als_monitor.is_some() {
+            self.set_als_streaming(true, daemon).await;
+        }
         Ok(())
     }
 
     async fn handle_command(
         &mut self,
         cmd: Self::Command,
-        _daemon: &mut Daemon<UserContext>,
+        daemon: &mut Daemon<UserContext>,
     ) -> Result<()> {
         match cmd {
             UserCommand::SetSessionManagerState(state) => {
@@ -121,6 +377,57 @@ impl DaemonContext for UserContext {
             UserCommand::GetSessionManagerState(sender) => {
                 let _ = sender.send(self.state.session_manager.clone());
             }
+            UserCommand::SetPowerPresets(state) => {
+                self.state.power_presets = state;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::GetPowerPresets(sender) => {
+                let _ = sender.send(self.state.power_presets.clone());
+            }
+            UserCommand::SetColorFilters(state) => {
+                self.state.color_filters = state;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::GetColorFilters(sender) => {
+                let _ = sender.send(self.state.color_filters);
+            }
+            UserCommand::SetIdlePolicy(state) => {
+                self.state.idle_policy = state;
+                self.idle_policy.send(IdlePolicyCommand::Configure(state))?;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::GetIdlePolicy(sender) => {
+                let _ = sender.send(self.state.idle_policy);
+            }
+            UserCommand::SetDisplayState(state) => {
+                self.state.display = state;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::GetDisplayState(sender) => {
+                let _ = sender.send(self.state.display);
+            }
+            UserCommand::SetTdpProfiles(profiles) => {
+                self.state.services.tdp_profiles = profiles
+                    .into_iter()
+                    .map(|(app_id, limit)| (app_id.to_string(), limit))
+                    .collect();
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::SetTdpLimit(limit) => {
+                self.state.services.tdp_limit = Some(limit);
+                self.state.services.tdp_limits = None;
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::SetTdpLimits(limits) => {
+                self.state.services.tdp_limits = Some(limits);
+                self.channel.send(DaemonCommand::WriteState).await?;
+            }
+            UserCommand::SetAlsStreaming(enabled) => {
+                self.set_als_streaming(enabled, daemon).await;
+            }
+            UserCommand::GetServiceStatus(sender) => {
+                let _ = sender.send(daemon.service_status().list().await);
+            }
         }
         Ok(())
     }
@@ -130,11 +437,15 @@ pub(crate) type Command = DaemonCommand<UserCommand>;
 
 async fn create_connections(
     channel: Sender<Command>,
+    telemetry_tx: UnboundedSender<TelemetryCommand>,
+    input_inhibit_tx: UnboundedSender<InputInhibitCommand>,
 ) -> Result<(
     Connection,
     Connection,
     JobManagerService,
     Result<TdpManagerService>,
+    Option<UnboundedSender<TdpManagerCommand>>,
+    SuspendInhibitService,
     SignalRelayService,
 )> {
     let system = Connection::system().await?;
@@ -144,25 +455,39 @@ async fn create_connections(
         .await?;
 
     let (jm_tx, rx) = unbounded_channel();
-    let job_manager = JobManager::new(connection.clone()).await?;
+    let job_manager = JobManager::new(connection.clone(), None).await?;
     let jm_service = JobManagerService::new(job_manager, rx, system.clone());
 
     let (tdp_tx, rx) = unbounded_channel();
-    let tdp_service = TdpManagerService::new(rx, &system, &connection).await;
+    let tdp_service = TdpManagerService::new(rx, &system, &connection, channel.clone()).await;
     let tdp_tx = if tdp_service.is_ok() {
         Some(tdp_tx)
     } else {
         None
     };
 
-    let signal_relay_service =
-        create_interfaces(connection.clone(), system.clone(), channel, jm_tx, tdp_tx).await?;
+    let (inhibit_tx, rx) = unbounded_channel();
+    let inhibit_service = SuspendInhibitService::new(rx, &system).await?;
+
+    let signal_relay_service = create_interfaces(
+        connection.clone(),
+        system.clone(),
+        channel,
+        jm_tx,
+        tdp_tx.clone(),
+        inhibit_tx,
+        input_inhibit_tx,
+        telemetry_tx,
+    )
+    .await?;
 
     Ok((
         connection,
         system,
         jm_service,
         tdp_service,
+        tdp_tx,
+        inhibit_service,
         signal_relay_service,
     ))
 }
@@ -171,31 +496,57 @@ pub async fn daemon() -> Result<()> {
     // This daemon is responsible for creating a dbus api that steam client can use to do various OS
     // level things. It implements com.steampowered.SteamOSManager1.Manager interface
 
-    let stdout_log = fmt::layer();
-    let subscriber = Registry::default()
-        .with(stdout_log)
-        .with(EnvFilter::from_default_env());
+    let subscriber = log_subscriber();
     set_global_default(subscriber)?;
     let (tx, rx) = channel::<UserContext>();
+    let (telemetry_tx, telemetry_rx) = unbounded_channel();
+    let (input_inhibit_tx, input_inhibit_rx) = unbounded_channel();
+    let (battery_policy_tx, battery_policy_rx) = unbounded_channel();
+    let (idle_policy_tx, idle_policy_rx) = unbounded_channel();
 
-    let (session, _system, mirror_service, tdp_service, signal_relay_service) =
-        match create_connections(tx.clone()).await {
-            Ok(c) => c,
-            Err(e) => {
-                error!("Error connecting to DBus: {}", e);
-                bail!(e);
-            }
-        };
+    let (
+        session,
+        system,
+        mirror_service,
+        tdp_service,
+        tdp_manager,
+        inhibit_service,
+        signal_relay_service,
+    ) = match create_connections(tx.clone(), telemetry_tx.clone(), input_inhibit_tx).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Error connecting to DBus: {}", e);
+            bail!(e);
+        }
+    };
 
     let mut daemon = Daemon::new(session.clone(), rx).await?;
+    let battery_policy_service =
+        BatteryPolicyService::new(&session, battery_policy_rx, tdp_manager.clone());
+    let idle_policy_service =
+        IdlePolicyService::new(&system, idle_policy_rx, tdp_manager.clone()).await?;
     let context = UserContext {
         session,
+        system,
         state: UserState::default(),
         channel: tx,
+        tdp_manager,
+        telemetry: telemetry_tx,
+        battery_policy: battery_policy_tx,
+        idle_policy: idle_policy_tx,
+        als_config: AlsMonitorConfig::default(),
+        als_monitor: None,
+        thermal_monitor: None,
+        wifi_info_monitor: None,
     };
 
     daemon.add_service(signal_relay_service);
     daemon.add_service(mirror_service);
+    daemon.add_service(inhibit_service);
+    daemon.add_service(InputInhibitService::new(input_inhibit_rx));
+    daemon.add_service(TelemetryService::new(telemetry_rx).await);
+    daemon.add_service(battery_policy_service);
+    daemon.add_service(idle_policy_service);
     if let Ok(tdp_service) = tdp_service {
         daemon.add_service(tdp_service);
     } else if let Err(e) = tdp_service {
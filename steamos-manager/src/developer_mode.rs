@@ -0,0 +1,317 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use strum::{Display, EnumString};
+use tokio::fs::{create_dir_all, read_dir, read_to_string, remove_file, try_exists};
+use zbus::Connection;
+
+use crate::platform::platform_config;
+use crate::systemd::{daemon_reload, SystemdUnit};
+use crate::{path, write_synced};
+
+const JOURNAL_DROP_IN_PATH: &str = "/etc/systemd/journald.conf.d/99-steamos-manager.conf";
+const PERSISTENT_JOURNAL: &str = "[Journal]\nStorage=persistent\n";
+
+const JOURNALD_CONF: &str = "systemd/journald.conf";
+/// Where systemd looks for config files, from the highest precedence to the lowest
+const CONFIG_DIRS: [&str; 4] = ["/etc", "/run", "/usr/local/lib", "/usr/lib"];
+/// With the default storage mode, journald only keeps logs on disk if this directory exists
+const PERSISTENT_JOURNAL_PATH: &str = "/var/log/journal";
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub(crate) enum DeveloperFeature {
+    Ssh,
+    CoreDumps,
+    PersistentJournal,
+}
+
+async fn feature_unit(feature: DeveloperFeature) -> Result<Option<String>> {
+    let config = platform_config().await?;
+    Ok(config
+        .as_ref()
+        .and_then(|config| config.developer_mode.as_ref())
+        .and_then(|config| config.unit(feature))
+        .map(String::from))
+}
+
+/// The `Storage=` setting journald ends up with, following the precedence of systemd config files
+async fn journal_storage() -> Result<Option<String>> {
+    let mut files = Vec::new();
+    for dir in CONFIG_DIRS {
+        let file = path(dir).join(JOURNALD_CONF);
+        if try_exists(&file).await? {
+            files.push(file);
+            break;
+        }
+    }
+    // Drop-ins are applied in name order, and mask ones with the same name in later directories
+    let mut drop_ins: BTreeMap<OsString, PathBuf> = BTreeMap::new();
+    for dir in CONFIG_DIRS.iter().rev() {
+        let mut entries = match read_dir(path(dir).join(format!("{JOURNALD_CONF}.d"))).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            if Path::new(&name)
+                .extension()
+                .is_some_and(|ext| ext == "conf")
+            {
+                drop_ins.insert(name, entry.path());
+            }
+        }
+    }
+    files.extend(drop_ins.into_values());
+
+    let mut storage = None;
+    for file in files {
+        let mut in_journal = false;
+        for line in read_to_string(&file).await?.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_journal = line == "[Journal]";
+            } else if let Some(value) = line.strip_prefix("Storage=").filter(|_| in_journal) {
+                storage = Some(value.trim().to_string());
+            }
+        }
+    }
+    Ok(storage)
+}
+
+/// Whether a developer mode feature is turned on, which is never the case if it isn't configured
+pub(crate) async fn developer_feature_enabled(
+    connection: &Connection,
+    feature: DeveloperFeature,
+) -> Result<bool> {
+    let Some(unit) = feature_unit(feature).await? else {
+        return Ok(false);
+    };
+    match feature {
+        DeveloperFeature::Ssh | DeveloperFeature::CoreDumps => {
+            let unit = SystemdUnit::new(connection.clone(), unit.as_str()).await?;
+            unit.active().await
+        }
+        DeveloperFeature::PersistentJournal => {
+            match journal_storage().await?.as_deref().unwrap_or("auto") {
+                "persistent" => Ok(true),
+                "auto" => Ok(try_exists(path(PERSISTENT_JOURNAL_PATH)).await?),
+                _ => Ok(false),
+            }
+        }
+    }
+}
+
+/// Whether developer mode is on, i.e. any of the configured developer features is turned on
+pub(crate) async fn developer_mode_enabled(connection: &Connection) -> Result<bool> {
+    for feature in [
+        DeveloperFeature::Ssh,
+        DeveloperFeature::CoreDumps,
+        DeveloperFeature::PersistentJournal,
+    ] {
+        if developer_feature_enabled(connection, feature).await? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub(crate) async fn set_developer_feature_enabled(
+    connection: &Connection,
+    feature: DeveloperFeature,
+    enabled: bool,
+) -> Result<()> {
+    let unit = feature_unit(feature)
+        .await?
+        .ok_or_else(|| anyhow!("Developer mode feature {feature} is not configured"))?;
+    let unit = SystemdUnit::new(connection.clone(), unit.as_str()).await?;
+    match (feature, enabled) {
+        (DeveloperFeature::Ssh, true) => {
+            unit.enable().await?;
+            unit.start().await?;
+        }
+        (DeveloperFeature::Ssh, false) => {
+            unit.disable().await?;
+            unit.stop().await?;
+        }
+        (DeveloperFeature::CoreDumps, true) => {
+            unit.unmask().await?;
+            daemon_reload(connection).await?;
+            unit.start().await?;
+        }
+        // Masking keeps the socket from being started again by anything else
+        (DeveloperFeature::CoreDumps, false) => {
+            unit.mask().await?;
+            unit.stop().await?;
+            daemon_reload(connection).await?;
+        }
+        (DeveloperFeature::PersistentJournal, true) => {
+            let drop_in = path(JOURNAL_DROP_IN_PATH);
+            if let Some(parent) = drop_in.parent() {
+                create_dir_all(parent).await?;
+            }
+            write_synced(&drop_in, PERSISTENT_JOURNAL.as_bytes()).await?;
+            unit.restart().await?;
+        }
+        // Going back to whatever the system configures, rather than forcing volatile storage
+        (DeveloperFeature::PersistentJournal, false) => {
+            match remove_file(path(JOURNAL_DROP_IN_PATH)).await {
+                Ok(()) => (),
+                Err(e) if e.kind() == ErrorKind::NotFound => (),
+                Err(e) => return Err(e.into()),
+            }
+            unit.restart().await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::platform::{DeveloperModeConfig, PlatformConfig};
+    use crate::systemd::test::{MockManager, MockUnit};
+    use crate::testing;
+    use std::time::Duration;
+    use tokio::fs::write;
+    use tokio::time::sleep;
+
+    #[tokio::test]
+    async fn toggle_features() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("dbus");
+        connection
+            .request_name("org.freedesktop.systemd1")
+            .await
+            .expect("request_name");
+        let object_server = connection.object_server();
+        object_server
+            .at("/org/freedesktop/systemd1", MockManager::default())
+            .await
+            .expect("at");
+        for unit in [
+            "sshd_2eservice",
+            "systemd_2dcoredump_2esocket",
+            "systemd_2djournald_2eservice",
+        ] {
+            let mut mock = MockUnit::default();
+            mock.active = String::from("inactive");
+            mock.unit_file = String::from("disabled");
+            object_server
+                .at(format!("/org/freedesktop/systemd1/unit/{unit}"), mock)
+                .await
+                .expect("at");
+        }
+        sleep(Duration::from_millis(10)).await;
+
+        for feature in [
+            DeveloperFeature::Ssh,
+            DeveloperFeature::CoreDumps,
+            DeveloperFeature::PersistentJournal,
+        ] {
+            assert!(!developer_feature_enabled(&connection, feature)
+                .await
+                .unwrap());
+            assert!(set_developer_feature_enabled(&connection, feature, true)
+                .await
+                .is_err());
+        }
+
+        h.test.platform_config.replace(Some(PlatformConfig {
+            developer_mode: Some(DeveloperModeConfig {
+                ssh: Some(String::from("sshd.service")),
+                core_dumps: Some(String::from("systemd-coredump.socket")),
+                journal: Some(String::from("systemd-journald.service")),
+            }),
+            ..PlatformConfig::default()
+        }));
+
+        for feature in [
+            DeveloperFeature::Ssh,
+            DeveloperFeature::CoreDumps,
+            DeveloperFeature::PersistentJournal,
+        ] {
+            assert!(!developer_feature_enabled(&connection, feature)
+                .await
+                .unwrap());
+            set_developer_feature_enabled(&connection, feature, true)
+                .await
+                .unwrap();
+            assert!(developer_feature_enabled(&connection, feature)
+                .await
+                .unwrap());
+            assert!(developer_mode_enabled(&connection).await.unwrap());
+            set_developer_feature_enabled(&connection, feature, false)
+                .await
+                .unwrap();
+            assert!(!developer_feature_enabled(&connection, feature)
+                .await
+                .unwrap());
+            assert!(!developer_mode_enabled(&connection).await.unwrap());
+        }
+        assert!(!try_exists(path(JOURNAL_DROP_IN_PATH)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn effective_journal_storage() {
+        let _h = testing::start();
+
+        assert_eq!(journal_storage().await.unwrap(), None);
+
+        let vendor = path("/usr/lib/systemd/journald.conf.d");
+        create_dir_all(&vendor).await.expect("create_dir_all");
+        create_dir_all(path("/etc/systemd/journald.conf.d"))
+            .await
+            .expect("create_dir_all");
+        write(
+            path("/etc/systemd/journald.conf"),
+            "[Journal]\nStorage=volatile\n",
+        )
+        .await
+        .expect("write");
+        assert_eq!(
+            journal_storage().await.unwrap().as_deref(),
+            Some("volatile")
+        );
+
+        write(
+            vendor.join("50-vendor.conf"),
+            "[Journal]\n#Storage=none\nStorage=auto\n",
+        )
+        .await
+        .expect("write");
+        write(vendor.join("README"), "Storage=none\n")
+            .await
+            .expect("write");
+        assert_eq!(journal_storage().await.unwrap().as_deref(), Some("auto"));
+
+        // Drop-ins in /etc override ones of the same name shipped by the OS
+        write(
+            path("/etc/systemd/journald.conf.d/50-vendor.conf"),
+            "[Other]\nStorage=none\n",
+        )
+        .await
+        .expect("write");
+        assert_eq!(
+            journal_storage().await.unwrap().as_deref(),
+            Some("volatile")
+        );
+
+        write(path(JOURNAL_DROP_IN_PATH), PERSISTENT_JOURNAL)
+            .await
+            .expect("write");
+        assert_eq!(
+            journal_storage().await.unwrap().as_deref(),
+            Some("persistent")
+        );
+    }
+}
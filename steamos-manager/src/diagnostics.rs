@@ -0,0 +1,307 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempfile::Builder as TempFileBuilder;
+use tokio::fs::{metadata, read_dir, read_to_string, try_exists, write};
+
+use crate::hardware::{device_config, explain_device_match};
+use crate::path;
+use crate::platform::platform_config;
+use crate::power::HWMON_PREFIX;
+use crate::process::script_output;
+
+const DIAGNOSTICS_PREFIX: &str = "steamos-manager-diagnostics-";
+const SERVICE_UNIT: &str = "steamos-manager.service";
+
+const DRM_PREFIX: &str = "/sys/class/drm";
+const PLATFORM_PROFILE_PREFIX: &str = "/sys/class/platform-profile";
+
+const GPU_ATTRIBUTES: &[&str] = &[
+    "device/power_dpm_force_performance_level",
+    "device/pp_power_profile_mode",
+    "device/pp_od_clk_voltage",
+    "device/pp_dpm_sclk",
+    "device/pp_dpm_mclk",
+    "device/gpu_busy_percent",
+];
+const PLATFORM_PROFILE_ATTRIBUTES: &[&str] = &["name", "profile", "choices"];
+
+/// A staged diagnostics snapshot, waiting to be packed into a tarball
+pub(crate) struct DiagnosticsBundle {
+    pub staging: PathBuf,
+    pub output: PathBuf,
+}
+
+impl DiagnosticsBundle {
+    /// Arguments for `tar` to pack the staging directory into the output tarball. The staging
+    /// directory is removed once it has been archived.
+    pub fn archive_args(&self) -> Vec<OsString> {
+        let parent = self.staging.parent().unwrap_or(Path::new("/"));
+        let name = self.staging.file_name().unwrap_or_default();
+        vec![
+            OsString::from("--create"),
+            OsString::from("--gzip"),
+            OsString::from("--remove-files"),
+            OsString::from("--file"),
+            self.output.clone().into_os_string(),
+            OsString::from("--directory"),
+            parent.as_os_str().to_owned(),
+            name.to_owned(),
+        ]
+    }
+}
+
+async fn journal(user: bool) -> String {
+    let mut args = vec!["--no-pager", "--boot", "--output=short-precise"];
+    if user {
+        args.push("--user");
+    }
+    args.push("--unit");
+    args.push(SERVICE_UNIT);
+    script_output("journalctl", &args)
+        .await
+        .unwrap_or_else(|e| format!("Failed to read journal: {e}\n"))
+}
+
+async fn config_snapshot() -> String {
+    let mut text = String::new();
+    match platform_config().await {
+        Ok(config) => writeln!(text, "# Platform config\n{config:#?}\n"),
+        Err(e) => writeln!(text, "# Platform config\nFailed to load: {e}\n"),
+    }
+    .unwrap();
+    match device_config().await {
+        Ok(config) => writeln!(text, "# Device config\n{config:#?}"),
+        Err(e) => writeln!(text, "# Device config\nFailed to load: {e}"),
+    }
+    .unwrap();
+    text
+}
+
+async fn device_match_snapshot() -> String {
+    let report = match explain_device_match().await {
+        Ok(report) => report,
+        Err(e) => return format!("Failed to match device: {e}\n"),
+    };
+    let mut text = String::new();
+    for entry in report {
+        let status = if entry.matched { "matched" } else { "skipped" };
+        writeln!(
+            text,
+            "{} {}/{}: {status} ({})",
+            entry.path.display(),
+            entry.device,
+            entry.variant,
+            entry.reason
+        )
+        .unwrap();
+    }
+    text
+}
+
+fn interfaces_snapshot(interfaces: &[(String, bool, String)]) -> String {
+    let mut text = String::new();
+    for (name, enabled, reason) in interfaces {
+        if *enabled {
+            writeln!(text, "{name}: enabled").unwrap();
+        } else {
+            writeln!(text, "{name}: disabled ({reason})").unwrap();
+        }
+    }
+    text
+}
+
+async fn snapshot_attribute(text: &mut String, attribute: &Path) {
+    let value = match read_to_string(attribute).await {
+        Ok(value) => value,
+        Err(e) => format!("<{e}>"),
+    };
+    let value = value.trim_end();
+    if value.contains('\n') {
+        writeln!(text, "{}:", attribute.display()).unwrap();
+        for line in value.lines() {
+            writeln!(text, "    {line}").unwrap();
+        }
+    } else {
+        writeln!(text, "{}: {value}", attribute.display()).unwrap();
+    }
+}
+
+async fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries = Vec::new();
+    let Ok(mut dir) = read_dir(dir).await else {
+        return entries;
+    };
+    while let Ok(Some(entry)) = dir.next_entry().await {
+        entries.push(entry.path());
+    }
+    entries.sort();
+    entries
+}
+
+async fn sysfs_snapshot() -> String {
+    let mut text = String::new();
+
+    // hwmon nodes cover both the TDP limits and the temperature and fan sensors
+    for hwmon in sorted_entries(&path(HWMON_PREFIX)).await {
+        for attribute in sorted_entries(&hwmon).await {
+            let Ok(info) = metadata(&attribute).await else {
+                continue;
+            };
+            // Skip write-only attributes, which can't be snapshotted
+            if info.is_file() && info.permissions().mode() & 0o444 != 0 {
+                snapshot_attribute(&mut text, &attribute).await;
+            }
+        }
+    }
+
+    for card in sorted_entries(&path(DRM_PREFIX)).await {
+        let is_card = card
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| name.starts_with("card") && !name.contains('-'));
+        if !is_card {
+            continue;
+        }
+        for attribute in GPU_ATTRIBUTES {
+            let attribute = card.join(attribute);
+            if try_exists(&attribute).await.unwrap_or(false) {
+                snapshot_attribute(&mut text, &attribute).await;
+            }
+        }
+    }
+
+    for profile in sorted_entries(&path(PLATFORM_PROFILE_PREFIX)).await {
+        for attribute in PLATFORM_PROFILE_ATTRIBUTES {
+            snapshot_attribute(&mut text, &profile.join(attribute)).await;
+        }
+    }
+
+    text
+}
+
+/// Collect a snapshot of the daemon's state into a fresh staging directory, given the current
+/// interface report. Packing it up is left to the caller, since archiving can take a while.
+pub(crate) async fn stage_diagnostics(
+    interfaces: &[(String, bool, String)],
+) -> Result<DiagnosticsBundle> {
+    let staging = TempFileBuilder::new()
+        .prefix(DIAGNOSTICS_PREFIX)
+        .tempdir()?
+        .keep();
+    let mut output = staging.clone().into_os_string();
+    output.push(".tar.gz");
+
+    write(staging.join("system.log"), journal(false).await).await?;
+    write(staging.join("user.log"), journal(true).await).await?;
+    write(staging.join("config.txt"), config_snapshot().await).await?;
+    write(
+        staging.join("device-match.txt"),
+        device_match_snapshot().await,
+    )
+    .await?;
+    write(staging.join("sysfs.txt"), sysfs_snapshot().await).await?;
+    write(
+        staging.join("interfaces.txt"),
+        interfaces_snapshot(interfaces),
+    )
+    .await?;
+
+    Ok(DiagnosticsBundle {
+        staging,
+        output: PathBuf::from(output),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use anyhow::anyhow;
+    use tokio::fs::{create_dir_all, remove_dir_all};
+
+    #[tokio::test]
+    async fn stage_bundle() {
+        let h = testing::start();
+        h.test.process_cb.set(|exe, args| {
+            if exe != "journalctl" {
+                return Err(anyhow!("unexpected {exe:?}"));
+            }
+            if args.contains(&OsStr::new("--user")) {
+                Ok((0, String::from("user log\n")))
+            } else {
+                Ok((0, String::from("system log\n")))
+            }
+        });
+
+        let hwmon = path(HWMON_PREFIX).join("hwmon0");
+        create_dir_all(&hwmon).await.expect("create_dir_all");
+        write(hwmon.join("name"), "amdgpu\n").await.expect("write");
+        write(hwmon.join("power1_cap"), "15000000\n")
+            .await
+            .expect("write");
+        let card = path(DRM_PREFIX).join("card0/device");
+        create_dir_all(&card).await.expect("create_dir_all");
+        write(
+            card.join("pp_od_clk_voltage"),
+            "OD_SCLK:\n0: 200Mhz\n1: 1600Mhz\n",
+        )
+        .await
+        .expect("write");
+
+        let interfaces = vec![
+            (
+                String::from("com.steampowered.SteamOSManager1.Manager2"),
+                true,
+                String::new(),
+            ),
+            (
+                String::from("com.steampowered.SteamOSManager1.FanControl1"),
+                false,
+                String::from("No fan control configured"),
+            ),
+        ];
+        let bundle = stage_diagnostics(&interfaces).await.expect("stage");
+        let staging = bundle.staging.clone();
+        assert_eq!(
+            bundle.output,
+            PathBuf::from(format!("{}.tar.gz", staging.display()))
+        );
+
+        let read = |name: &str| read_to_string(staging.join(name));
+        assert_eq!(read("system.log").await.unwrap(), "system log\n");
+        assert_eq!(read("user.log").await.unwrap(), "user log\n");
+
+        let sysfs = read("sysfs.txt").await.unwrap();
+        assert!(sysfs.contains(&format!("{}: amdgpu\n", hwmon.join("name").display())));
+        assert!(sysfs.contains(&format!(
+            "{}: 15000000\n",
+            hwmon.join("power1_cap").display()
+        )));
+        assert!(sysfs.contains(&format!(
+            "{}:\n    OD_SCLK:\n    0: 200Mhz\n",
+            card.join("pp_od_clk_voltage").display()
+        )));
+
+        assert_eq!(
+            read("interfaces.txt").await.unwrap(),
+            "com.steampowered.SteamOSManager1.Manager2: enabled\n\
+             com.steampowered.SteamOSManager1.FanControl1: disabled (No fan control configured)\n"
+        );
+
+        let args = bundle.archive_args();
+        assert_eq!(args[4], bundle.output.as_os_str());
+        assert_eq!(args[6], staging.parent().unwrap().as_os_str());
+        assert_eq!(args[7], staging.file_name().unwrap());
+
+        remove_dir_all(staging).await.expect("remove_dir_all");
+    }
+}
@@ -0,0 +1,340 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::fs::{create_dir_all, read_dir, read_to_string, remove_file};
+#[cfg(not(test))]
+use xdg::BaseDirectories;
+
+use crate::{path, write_synced};
+
+const DRM_PREFIX: &str = "/sys/class/drm";
+
+// Connector types used for built-in panels
+const INTERNAL_CONNECTORS: [&str; 3] = ["eDP", "LVDS", "DSI"];
+
+const DOCK_MODE_HINT_FILE: &str = "environment.d/60-steamos-manager-dock-output.conf";
+const DOCK_WIDTH_VAR: &str = "STEAMOS_DOCK_OUTPUT_WIDTH";
+const DOCK_HEIGHT_VAR: &str = "STEAMOS_DOCK_OUTPUT_HEIGHT";
+const DOCK_REFRESH_VAR: &str = "STEAMOS_DOCK_OUTPUT_REFRESH";
+
+#[derive(Copy, Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct DisplayState {
+    /// Highest refresh rate in Hz the compositor should run the display at, or 0 for no cap
+    pub refresh_rate_cap: u32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct DisplayOutput {
+    pub name: String,
+    pub status: String,
+    pub enabled: bool,
+    /// Modes supported by the connected display, with the preferred mode first
+    pub modes: Vec<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh: Option<u32>,
+}
+
+impl FromStr for DisplayMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<DisplayMode> {
+        let (resolution, refresh) = match mode.split_once('@') {
+            Some((resolution, refresh)) => (resolution, Some(refresh.parse()?)),
+            None => (mode, None),
+        };
+        let Some((width, height)) = resolution.split_once('x') else {
+            bail!("Invalid display mode {mode}");
+        };
+        let mode = DisplayMode {
+            width: width.parse()?,
+            height: height.parse()?,
+            refresh,
+        };
+        ensure!(
+            mode.width > 0 && mode.height > 0 && mode.refresh != Some(0),
+            "Invalid display mode {mode}"
+        );
+        Ok(mode)
+    }
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)?;
+        if let Some(refresh) = self.refresh {
+            write!(f, "@{refresh}")?;
+        }
+        Ok(())
+    }
+}
+
+async fn read_connector_attribute(connector: &str, attribute: &str) -> Result<String> {
+    let contents = read_to_string(path(DRM_PREFIX).join(connector).join(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    Ok(contents.trim().to_string())
+}
+
+pub(crate) async fn list_outputs() -> Result<Vec<DisplayOutput>> {
+    let mut outputs = Vec::new();
+    let mut dir = read_dir(path(DRM_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Ok(connector) = entry.file_name().into_string() else {
+            continue;
+        };
+        // Connectors are named after their card, e.g. card0-DP-1
+        let Some((card, name)) = connector.split_once('-') else {
+            continue;
+        };
+        if !card.starts_with("card") {
+            continue;
+        }
+        let Ok(status) = read_connector_attribute(connector.as_str(), "status").await else {
+            continue;
+        };
+        let enabled = read_connector_attribute(connector.as_str(), "enabled")
+            .await
+            .is_ok_and(|enabled| enabled == "enabled");
+        let modes = read_connector_attribute(connector.as_str(), "modes")
+            .await
+            .unwrap_or_default()
+            .lines()
+            .map(ToString::to_string)
+            .collect();
+        outputs.push(DisplayOutput {
+            name: name.to_string(),
+            status,
+            enabled,
+            modes,
+        });
+    }
+    outputs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(outputs)
+}
+
+pub(crate) async fn external_display_count() -> Result<u32> {
+    let count = list_outputs()
+        .await?
+        .into_iter()
+        .filter(|output| output.status == "connected")
+        .filter(|output| {
+            !INTERNAL_CONNECTORS
+                .iter()
+                .any(|internal| output.name.starts_with(internal))
+        })
+        .count();
+    Ok(count.try_into()?)
+}
+
+#[cfg(not(test))]
+fn dock_mode_hint_path() -> Result<PathBuf> {
+    let xdg_base = BaseDirectories::new();
+    Ok(xdg_base
+        .get_config_home()
+        .ok_or(anyhow!("No XDG_CONFIG_HOME found"))?
+        .join(DOCK_MODE_HINT_FILE))
+}
+
+#[cfg(test)]
+fn dock_mode_hint_path() -> Result<PathBuf> {
+    Ok(path(DOCK_MODE_HINT_FILE))
+}
+
+pub(crate) async fn get_dock_mode_hint() -> Result<Option<DisplayMode>> {
+    let contents = match read_to_string(dock_mode_hint_path()?).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let (mut width, mut height, mut refresh) = (None, None, None);
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            DOCK_WIDTH_VAR => width = Some(value.trim().parse()?),
+            DOCK_HEIGHT_VAR => height = Some(value.trim().parse()?),
+            DOCK_REFRESH_VAR => refresh = Some(value.trim().parse()?),
+            _ => (),
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok(Some(DisplayMode {
+            width,
+            height,
+            refresh,
+        })),
+        _ => Ok(None),
+    }
+}
+
+pub(crate) async fn set_dock_mode_hint(mode: Option<DisplayMode>) -> Result<()> {
+    let hint_path = dock_mode_hint_path()?;
+    let Some(mode) = mode else {
+        return match remove_file(hint_path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    };
+    let mut contents = format!(
+        "{DOCK_WIDTH_VAR}={}\n{DOCK_HEIGHT_VAR}={}\n",
+        mode.width, mode.height
+    );
+    if let Some(refresh) = mode.refresh {
+        contents.push_str(format!("{DOCK_REFRESH_VAR}={refresh}\n").as_str());
+    }
+    if let Some(parent) = hint_path.parent() {
+        create_dir_all(parent).await?;
+    }
+    write_synced(hint_path, contents.as_bytes()).await
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::write;
+
+    async fn create_connector(
+        connector: &str,
+        status: &str,
+        enabled: &str,
+        modes: &[&str],
+    ) -> Result<()> {
+        let base = path(DRM_PREFIX).join(connector);
+        create_dir_all(&base).await?;
+        write(base.join("status"), format!("{status}\n")).await?;
+        write(base.join("enabled"), format!("{enabled}\n")).await?;
+        let mut contents = modes.join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        write(base.join("modes"), contents).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        create_connector("card0-eDP-1", "connected", "enabled", &["800x1280"]).await?;
+        create_connector(
+            "card0-DP-1",
+            "connected",
+            "enabled",
+            &["3840x2160", "2560x1440", "1920x1080"],
+        )
+        .await?;
+        // The card itself isn't a connector
+        create_dir_all(path(DRM_PREFIX).join("card0")).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn enumerate_outputs() {
+        let _h = testing::start();
+
+        assert!(list_outputs().await.is_err());
+        create_nodes().await.expect("create_nodes");
+        create_connector("card0-HDMI-A-1", "disconnected", "disabled", &[])
+            .await
+            .expect("create_connector");
+
+        assert_eq!(
+            list_outputs().await.unwrap(),
+            &[
+                DisplayOutput {
+                    name: String::from("DP-1"),
+                    status: String::from("connected"),
+                    enabled: true,
+                    modes: vec![
+                        String::from("3840x2160"),
+                        String::from("2560x1440"),
+                        String::from("1920x1080")
+                    ],
+                },
+                DisplayOutput {
+                    name: String::from("HDMI-A-1"),
+                    status: String::from("disconnected"),
+                    enabled: false,
+                    modes: Vec::new(),
+                },
+                DisplayOutput {
+                    name: String::from("eDP-1"),
+                    status: String::from("connected"),
+                    enabled: true,
+                    modes: vec![String::from("800x1280")],
+                },
+            ]
+        );
+        assert_eq!(external_display_count().await.unwrap(), 1);
+    }
+
+    #[test]
+    fn parse_modes() {
+        assert_eq!(
+            DisplayMode::from_str("1920x1080").unwrap(),
+            DisplayMode {
+                width: 1920,
+                height: 1080,
+                refresh: None
+            }
+        );
+        assert_eq!(
+            DisplayMode::from_str("2560x1440@120").unwrap(),
+            DisplayMode {
+                width: 2560,
+                height: 1440,
+                refresh: Some(120)
+            }
+        );
+        assert_eq!(
+            DisplayMode::from_str("2560x1440@120").unwrap().to_string(),
+            "2560x1440@120"
+        );
+        assert!(DisplayMode::from_str("1920").is_err());
+        assert!(DisplayMode::from_str("0x1080").is_err());
+        assert!(DisplayMode::from_str("1920x1080@0").is_err());
+        assert!(DisplayMode::from_str("1920x1080@").is_err());
+    }
+
+    #[tokio::test]
+    async fn dock_mode_hint() {
+        let _h = testing::start();
+
+        assert_eq!(get_dock_mode_hint().await.unwrap(), None);
+        // Clearing an unset hint is fine
+        set_dock_mode_hint(None).await.expect("set_dock_mode_hint");
+
+        let mode = DisplayMode::from_str("1920x1080@60").unwrap();
+        set_dock_mode_hint(Some(mode))
+            .await
+            .expect("set_dock_mode_hint");
+        assert_eq!(
+            read_to_string(path(DOCK_MODE_HINT_FILE)).await.unwrap(),
+            "STEAMOS_DOCK_OUTPUT_WIDTH=1920\nSTEAMOS_DOCK_OUTPUT_HEIGHT=1080\nSTEAMOS_DOCK_OUTPUT_REFRESH=60\n"
+        );
+        assert_eq!(get_dock_mode_hint().await.unwrap(), Some(mode));
+
+        let mode = DisplayMode::from_str("2560x1440").unwrap();
+        set_dock_mode_hint(Some(mode))
+            .await
+            .expect("set_dock_mode_hint");
+        assert_eq!(get_dock_mode_hint().await.unwrap(), Some(mode));
+
+        set_dock_mode_hint(None).await.expect("set_dock_mode_hint");
+        assert_eq!(get_dock_mode_hint().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,264 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::os::fd::AsFd;
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::select;
+use tracing::{debug, info, warn};
+use udev::MonitorBuilder;
+use zbus::{Connection, Proxy};
+
+use crate::display::external_display_count;
+use crate::manager::user::{root_manager_proxy, Dock1, Dock1Signals, FirmwareInfo1, MANAGER_PATH};
+use crate::platform::{platform_config, DockConfig};
+use crate::policy::Guarded;
+use crate::usb::{get_usb_tier, list_usb_devices};
+use crate::Service;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct DockInfo {
+    /// The sysfs name of the dock's USB device
+    pub device: String,
+    pub model: String,
+    pub firmware_version: String,
+    pub usb_tier: String,
+}
+
+/// Find a connected dock, returning its USB device name and model
+pub(crate) async fn find_dock(config: &DockConfig) -> Result<Option<(String, String)>> {
+    Ok(list_usb_devices().await?.into_iter().find_map(|device| {
+        config
+            .model(device.id.as_str())
+            .map(|model| (device.name, model.to_string()))
+    }))
+}
+
+pub(crate) struct DockMonitor {
+    session: Connection,
+    proxy: Proxy<'static>,
+    config: DockConfig,
+    device: Option<String>,
+}
+
+impl DockMonitor {
+    pub(crate) async fn init(session: &Connection, system: &Connection) -> Result<DockMonitor> {
+        let Some(config) = platform_config()
+            .await?
+            .as_ref()
+            .and_then(|config| config.dock.clone())
+            .filter(|config| !config.models.is_empty())
+        else {
+            bail!("No dock config found");
+        };
+        Ok(DockMonitor {
+            session: session.clone(),
+            proxy: root_manager_proxy(system).await?,
+            config,
+            device: None,
+        })
+    }
+
+    async fn firmware_version(&self) -> String {
+        // Querying the firmware can be slow, so only do it when a dock is connected
+        match self
+            .proxy
+            .call::<_, _, (String, String, HashMap<String, String>)>("GetFirmwareVersions", &())
+            .await
+        {
+            Ok((_, dock, _)) => dock,
+            Err(e) => {
+                warn!("Error querying dock firmware version: {e}");
+                String::new()
+            }
+        }
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        let dock = find_dock(&self.config).await?;
+        let object_server = self.session.object_server();
+        let interface = object_server
+            .interface::<_, Guarded<Dock1>>(MANAGER_PATH)
+            .await?;
+        let ctx = interface.signal_emitter();
+
+        let device = dock.as_ref().map(|(device, _)| device.clone());
+        if device != self.device {
+            self.device = device;
+            let info = match dock {
+                Some((device, model)) => {
+                    info!("Dock {model} connected");
+                    let usb_tier = get_usb_tier(device.as_str())
+                        .await
+                        .inspect_err(|e| warn!("Error reading USB speed of dock: {e}"))
+                        .unwrap_or_default();
+                    Some(DockInfo {
+                        device,
+                        model,
+                        firmware_version: self.firmware_version().await,
+                        usb_tier,
+                    })
+                }
+                None => {
+                    info!("Dock disconnected");
+                    None
+                }
+            };
+            let model = info.as_ref().map(|info| info.model.clone());
+            interface.get_mut().await.set_dock(info, ctx).await?;
+            // The dock re-enumerates after a firmware update, which also lands here
+            if let Ok(firmware) = object_server
+                .interface::<_, Guarded<FirmwareInfo1>>(MANAGER_PATH)
+                .await
+            {
+                if let Err(e) = firmware
+                    .get()
+                    .await
+                    .invalidate(firmware.signal_emitter())
+                    .await
+                {
+                    warn!("Error invalidating firmware versions: {e}");
+                }
+            }
+            match model {
+                Some(model) => {
+                    // Devices behind the dock, such as its ethernet adapter, lose their wakeup
+                    // setting whenever it's disconnected
+                    if let Err(e) = self.proxy.call::<_, _, ()>("RestoreWakeSources", &()).await {
+                        warn!("Error restoring wake sources: {e}");
+                    }
+                    interface
+                        .signal_emitter()
+                        .dock_connected(model.as_str())
+                        .await?
+                }
+                None => interface.signal_emitter().dock_disconnected().await?,
+            }
+        }
+
+        let display_count = if self.device.is_some() {
+            external_display_count().await.unwrap_or_default()
+        } else {
+            0
+        };
+        interface
+            .get_mut()
+            .await
+            .set_display_count(display_count, ctx)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Service for DockMonitor {
+    const NAME: &'static str = "dock-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let monitor = MonitorBuilder::new()?
+            .match_subsystem_devtype("usb", "usb_device")?
+            .match_subsystem("drm")?
+            .listen()?;
+        let fd = AsyncFd::new(monitor.as_fd())?;
+        let mut iter = monitor.iter();
+
+        loop {
+            if let Err(e) = self.update().await {
+                warn!("Failed to update dock state: {e}");
+            }
+            select! {
+                guard = fd.ready(Interest::READABLE) => {
+                    let mut guard = guard?;
+                    for ev in iter.by_ref() {
+                        debug!("Got dock event {ev:?}");
+                    }
+                    guard.clear_ready();
+                },
+                _ = fd.ready(Interest::ERROR) => bail!("Event poller encountered unknown flags"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path;
+    use crate::platform::PlatformConfig;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, remove_dir_all, write};
+
+    async fn dock_state(connection: &Connection) -> (Option<DockInfo>, u32) {
+        let interface = connection
+            .object_server()
+            .interface::<_, Guarded<Dock1>>(MANAGER_PATH)
+            .await
+            .expect("interface");
+        let state = interface.get().await.state();
+        state
+    }
+
+    #[tokio::test]
+    async fn connect_disconnect() {
+        let mut h = testing::start();
+
+        let usb = path("/sys/bus/usb/devices");
+        create_dir_all(&usb).await.expect("create_dir_all");
+        crate::display::test::create_nodes()
+            .await
+            .expect("create_nodes");
+
+        let mut config = PlatformConfig::default();
+        config.dock = Some(DockConfig {
+            models: HashMap::from([(
+                String::from("28DE:2001"),
+                String::from("Steam Deck Docking Station"),
+            )]),
+        });
+        h.test.platform_config.replace(Some(config));
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        connection
+            .object_server()
+            .at(MANAGER_PATH, Guarded::new(Dock1::default()))
+            .await
+            .expect("at");
+        let mut monitor = DockMonitor::init(&connection, &connection)
+            .await
+            .expect("init");
+
+        monitor.update().await.expect("update");
+        assert_eq!(dock_state(&connection).await, (None, 0));
+
+        let dock = usb.join("1-1");
+        create_dir_all(&dock).await.expect("create_dir_all");
+        write(dock.join("idVendor"), "28de\n").await.expect("write");
+        write(dock.join("idProduct"), "2001\n")
+            .await
+            .expect("write");
+        write(dock.join("authorized"), "1\n").await.expect("write");
+        write(dock.join("speed"), "10000\n").await.expect("write");
+
+        monitor.update().await.expect("update");
+        assert_eq!(
+            dock_state(&connection).await,
+            (
+                Some(DockInfo {
+                    device: String::from("1-1"),
+                    model: String::from("Steam Deck Docking Station"),
+                    firmware_version: String::new(),
+                    usb_tier: String::from("USB 3.2 Gen 2"),
+                }),
+                1
+            )
+        );
+
+        remove_dir_all(&dock).await.expect("remove_dir_all");
+        monitor.update().await.expect("update");
+        assert_eq!(dock_state(&connection).await, (None, 0));
+    }
+}
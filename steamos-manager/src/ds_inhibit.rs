@@ -16,7 +16,8 @@ use tokio::time::sleep;
 use tokio_stream::StreamExt;
 use tracing::{debug, error, info, warn};
 
-use crate::{path, write_synced, Service};
+use crate::power::write_sysfs;
+use crate::{path, Service};
 
 struct HidNode {
     id: u32,
@@ -147,7 +148,7 @@ impl HidNode {
     async fn inhibit(&self) -> Result<()> {
         let mut res = Ok(());
         for node in self.get_nodes().await? {
-            if let Err(err) = write_synced(node, b"1\n").await {
+            if let Err(err) = write_sysfs(node, b"1\n").await {
                 error!("Encountered error inhibiting: {err}");
                 res = Err(err);
             }
@@ -158,7 +159,7 @@ impl HidNode {
     async fn uninhibit(&self) -> Result<()> {
         let mut res = Ok(());
         for node in self.get_nodes().await? {
-            if let Err(err) = write_synced(node, b"0\n").await {
+            if let Err(err) = write_sysfs(node, b"0\n").await {
                 error!("Encountered error inhibiting: {err}");
                 res = Err(err);
             }
@@ -10,22 +10,46 @@ use async_trait::async_trait;
 use num_enum::TryFromPrimitive;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::ops::RangeInclusive;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::LazyLock;
+use std::time::Duration;
 use strum::{Display, EnumString, VariantNames};
 use tokio::fs::{self, try_exists, File};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing::error;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, error};
+use zbus::Connection;
 
 use crate::hardware::{device_config, device_type};
-use crate::power::find_hwmon;
-use crate::write_synced;
+use crate::manager::user::{GpuThrottle1, MANAGER_PATH};
+use crate::path;
+use crate::policy::Guarded;
+use crate::power::{find_hwmon, write_sysfs, write_sysfs_sequence};
+use crate::Service;
 
 pub(crate) const AMDGPU_HWMON_NAME: &str = "amdgpu";
 
+#[cfg(not(test))]
+const DRM_PREFIX: &str = "/sys/class/drm";
+#[cfg(test)]
+const DRM_PREFIX: &str = "drm";
+
+// amdgpu exposes the edge, junction and memory temperatures, depending on the GPU
+const AMDGPU_TEMPERATURES: &[&str] = &["temp1", "temp2", "temp3"];
+
+const THROTTLE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const AMDGPU_METRICS: &[(&str, &str)] = &[
+    ("gpu_busy_percent", "device/gpu_busy_percent"),
+    ("mem_busy_percent", "device/mem_busy_percent"),
+    ("vram_used", "device/mem_info_vram_used"),
+    ("vram_total", "device/mem_info_vram_total"),
+];
+
 static AMDGPU_POWER_PROFILE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^\s*(?<value>[0-9]+)\s+(?<name>[0-9A-Za-z_]+)(?<active>\*)?").unwrap()
 });
@@ -58,6 +82,7 @@ pub enum AmdgpuPowerProfile {
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum GpuPerformanceLevel {
     Amdgpu(AmdgpuPerformanceLevel),
+    Intel(IntelPerformanceLevel),
 }
 
 #[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
@@ -70,6 +95,15 @@ pub enum AmdgpuPerformanceLevel {
     ProfilePeak,
 }
 
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub enum IntelPerformanceLevel {
+    Auto,
+    Low,
+    High,
+    Manual,
+}
+
 #[derive(Deserialize, Display, EnumString, VariantNames, PartialEq, Debug, Clone)]
 #[strum(serialize_all = "snake_case", ascii_case_insensitive)]
 #[serde(rename_all = "snake_case")]
@@ -82,6 +116,8 @@ pub enum GpuPowerProfileDriverType {
 #[serde(rename_all = "snake_case")]
 pub enum GpuPerformanceLevelDriverType {
     Amdgpu,
+    I915,
+    Xe,
 }
 
 #[derive(Debug)]
@@ -90,6 +126,18 @@ pub(crate) struct AmdgpuPowerProfileDriver {}
 #[derive(Debug)]
 pub(crate) struct AmdgpuPerformanceLevelDriver {}
 
+#[derive(Debug)]
+pub(crate) struct IntelPerformanceLevelDriver {
+    driver: IntelDriver,
+}
+
+#[derive(Display, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+enum IntelDriver {
+    I915,
+    Xe,
+}
+
 #[async_trait]
 pub(crate) trait GpuPowerProfileDriver: Send + Sync {
     fn power_profile_from_str(&self, value: &str) -> Result<GpuPowerProfile>;
@@ -131,13 +179,31 @@ pub(crate) async fn gpu_performance_level_driver() -> Result<Box<dyn GpuPerforma
 
     Ok(match &config.driver {
         GpuPerformanceLevelDriverType::Amdgpu => Box::new(AmdgpuPerformanceLevelDriver {}),
+        GpuPerformanceLevelDriverType::I915 => Box::new(IntelPerformanceLevelDriver {
+            driver: IntelDriver::I915,
+        }),
+        GpuPerformanceLevelDriverType::Xe => Box::new(IntelPerformanceLevelDriver {
+            driver: IntelDriver::Xe,
+        }),
     })
 }
 
+impl GpuPerformanceLevel {
+    /// Whether the clocks set with [`GpuPerformanceLevelDriver::set_clocks`] are in effect
+    pub(crate) fn is_manual(&self) -> bool {
+        matches!(
+            self,
+            GpuPerformanceLevel::Amdgpu(AmdgpuPerformanceLevel::Manual)
+                | GpuPerformanceLevel::Intel(IntelPerformanceLevel::Manual)
+        )
+    }
+}
+
 impl Display for GpuPerformanceLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
             GpuPerformanceLevel::Amdgpu(v) => write!(f, "{v}"),
+            GpuPerformanceLevel::Intel(v) => write!(f, "{v}"),
         }
     }
 }
@@ -161,7 +227,7 @@ trait AmdgpuGpuPerfDriver {
 
     async fn write_sysfs_contents<S: AsRef<Path>>(suffix: S, data: &[u8]) -> Result<()> {
         let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
-        write_synced(base.join(suffix), data)
+        write_sysfs(base.join(suffix), data)
             .await
             .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
     }
@@ -288,9 +354,7 @@ impl GpuPerformanceLevelDriver for AmdgpuPerformanceLevelDriver {
     }
 
     async fn set_performance_level(&self, level: GpuPerformanceLevel) -> Result<()> {
-        #[allow(irrefutable_let_patterns)] // Remove when more values are added
-        let GpuPerformanceLevel::Amdgpu(level) = level
-        else {
+        let GpuPerformanceLevel::Amdgpu(level) = level else {
             bail!("This is not an amdgpu-compatible performance level");
         };
         let level: String = level.to_string();
@@ -334,31 +398,18 @@ impl GpuPerformanceLevelDriver for AmdgpuPerformanceLevelDriver {
         // Set GPU clocks to given value valid
         // Only used when GPU Performance Level is manual, but write whenever called.
         let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
-        let mut myfile = File::create(base.join(Self::CLOCKS_SUFFIX))
-            .await
-            .inspect_err(|message| error!("Error opening sysfs file for writing: {message}"))?;
-
-        let data = format!("s 0 {clocks}\n");
-        myfile
-            .write(data.as_bytes())
-            .await
-            .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
-        myfile.flush().await?;
-
-        let data = format!("s 1 {clocks}\n");
-        myfile
-            .write(data.as_bytes())
-            .await
-            .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
-        myfile.flush().await?;
-
-        myfile
-            .write("c\n".as_bytes())
-            .await
-            .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
-        myfile.flush().await?;
-
-        Ok(())
+        // The commands have to be written one by one to the same open file
+        let commands = [
+            format!("s 0 {clocks}\n"),
+            format!("s 1 {clocks}\n"),
+            String::from("c\n"),
+        ];
+        write_sysfs_sequence(
+            base.join(Self::CLOCKS_SUFFIX),
+            commands.into_iter().map(String::into_bytes).collect(),
+        )
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
     }
 
     async fn get_clocks(&self) -> Result<u32> {
@@ -389,13 +440,340 @@ impl GpuPerformanceLevelDriver for AmdgpuPerformanceLevelDriver {
     }
 }
 
+impl IntelPerformanceLevelDriver {
+    async fn base(&self) -> Result<PathBuf> {
+        // Find the first DRM card bound to this driver
+        let expected = format!("DRIVER={}", self.driver);
+        let mut dir = fs::read_dir(path(DRM_PREFIX)).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if !name.starts_with("card") || name.contains('-') {
+                continue;
+            }
+            let card = entry.path();
+            let Ok(uevent) = fs::read_to_string(card.join("device/uevent")).await else {
+                continue;
+            };
+            if uevent.lines().any(|line| line == expected) {
+                return Ok(match self.driver {
+                    IntelDriver::I915 => card,
+                    IntelDriver::Xe => card.join("device/tile0/gt0/freq0"),
+                });
+            }
+        }
+        bail!("No {} GPU found", self.driver)
+    }
+
+    fn min_freq_suffix(&self) -> &'static str {
+        match self.driver {
+            IntelDriver::I915 => "gt_min_freq_mhz",
+            IntelDriver::Xe => "min_freq",
+        }
+    }
+
+    fn max_freq_suffix(&self) -> &'static str {
+        match self.driver {
+            IntelDriver::I915 => "gt_max_freq_mhz",
+            IntelDriver::Xe => "max_freq",
+        }
+    }
+
+    fn boost_freq_suffix(&self) -> Option<&'static str> {
+        match self.driver {
+            IntelDriver::I915 => Some("gt_boost_freq_mhz"),
+            IntelDriver::Xe => None,
+        }
+    }
+
+    fn hardware_min_freq_suffix(&self) -> &'static str {
+        match self.driver {
+            IntelDriver::I915 => "gt_RPn_freq_mhz",
+            IntelDriver::Xe => "rpn_freq",
+        }
+    }
+
+    fn hardware_max_freq_suffix(&self) -> &'static str {
+        match self.driver {
+            IntelDriver::I915 => "gt_RP0_freq_mhz",
+            IntelDriver::Xe => "rp0_freq",
+        }
+    }
+
+    async fn read_freq(&self, suffix: &str) -> Result<u32> {
+        let base = self.base().await?;
+        fs::read_to_string(base.join(suffix))
+            .await
+            .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))?
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Error parsing value: {e}"))
+    }
+
+    async fn write_freq(&self, suffix: &str, mhz: u32) -> Result<()> {
+        let base = self.base().await?;
+        write_sysfs(base.join(suffix), mhz.to_string())
+            .await
+            .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+    }
+
+    async fn hardware_range(&self) -> Result<RangeInclusive<u32>> {
+        let min = self.read_freq(self.hardware_min_freq_suffix()).await?;
+        let max = self.read_freq(self.hardware_max_freq_suffix()).await?;
+        ensure!(min <= max, "Invalid GPU frequency range {min}-{max}");
+        Ok(min..=max)
+    }
+
+    async fn set_freq_limits(&self, min: u32, max: u32) -> Result<()> {
+        // The driver rejects a minimum above the current maximum and vice
+        // versa, so write the limits in an order that keeps them valid
+        let current_max = self.read_freq(self.max_freq_suffix()).await?;
+        if min > current_max {
+            self.write_freq(self.max_freq_suffix(), max).await?;
+            self.write_freq(self.min_freq_suffix(), min).await?;
+        } else {
+            self.write_freq(self.min_freq_suffix(), min).await?;
+            self.write_freq(self.max_freq_suffix(), max).await?;
+        }
+        if let Some(boost) = self.boost_freq_suffix() {
+            self.write_freq(boost, max).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GpuPerformanceLevelDriver for IntelPerformanceLevelDriver {
+    fn performance_level_from_str(&self, value: &str) -> Result<GpuPerformanceLevel> {
+        Ok(GpuPerformanceLevel::Intel(IntelPerformanceLevel::from_str(
+            value,
+        )?))
+    }
+
+    async fn get_available_performance_levels(&self) -> Result<Vec<GpuPerformanceLevel>> {
+        if self.hardware_range().await.is_ok() {
+            Ok(vec![
+                GpuPerformanceLevel::Intel(IntelPerformanceLevel::Auto),
+                GpuPerformanceLevel::Intel(IntelPerformanceLevel::Low),
+                GpuPerformanceLevel::Intel(IntelPerformanceLevel::High),
+                GpuPerformanceLevel::Intel(IntelPerformanceLevel::Manual),
+            ])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn get_performance_level(&self) -> Result<GpuPerformanceLevel> {
+        let range = self.hardware_range().await?;
+        let min = self.read_freq(self.min_freq_suffix()).await?;
+        let max = self.read_freq(self.max_freq_suffix()).await?;
+        let level = if min == *range.start() && max == *range.end() {
+            IntelPerformanceLevel::Auto
+        } else if min == *range.start() && max == *range.start() {
+            IntelPerformanceLevel::Low
+        } else if min == *range.end() && max == *range.end() {
+            IntelPerformanceLevel::High
+        } else {
+            IntelPerformanceLevel::Manual
+        };
+        Ok(GpuPerformanceLevel::Intel(level))
+    }
+
+    async fn set_performance_level(&self, level: GpuPerformanceLevel) -> Result<()> {
+        let GpuPerformanceLevel::Intel(level) = level else {
+            bail!("This is not an Intel-compatible performance level");
+        };
+        let range = self.hardware_range().await?;
+        match level {
+            IntelPerformanceLevel::Auto => self.set_freq_limits(*range.start(), *range.end()).await,
+            IntelPerformanceLevel::Low => {
+                self.set_freq_limits(*range.start(), *range.start()).await
+            }
+            IntelPerformanceLevel::High => self.set_freq_limits(*range.end(), *range.end()).await,
+            IntelPerformanceLevel::Manual => {
+                // Pin the clocks to the current maximum until set_clocks is called
+                let max = self.read_freq(self.max_freq_suffix()).await?;
+                self.set_freq_limits(max, max).await
+            }
+        }
+    }
+
+    async fn get_clocks_range(&self) -> Result<RangeInclusive<u32>> {
+        if let Some(range) = device_config()
+            .await?
+            .as_ref()
+            .and_then(|config| config.gpu_performance.as_ref())
+            .and_then(|config| config.clocks)
+        {
+            return Ok(range.min..=range.max);
+        }
+        self.hardware_range().await
+    }
+
+    async fn get_clocks(&self) -> Result<u32> {
+        self.read_freq(self.max_freq_suffix()).await
+    }
+
+    async fn set_clocks(&self, clocks: u32) -> Result<()> {
+        ensure!(
+            self.get_clocks_range().await?.contains(&clocks),
+            "Invalid GPU clock {clocks}"
+        );
+        self.set_freq_limits(clocks, clocks).await
+    }
+}
+
+pub(crate) async fn get_gpu_frequency() -> Result<u32> {
+    // hwmon reports the current shader clock in Hz, but we expose it in MHz
+    let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
+    let hz: u64 = fs::read_to_string(base.join("freq1_input"))
+        .await
+        .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    Ok((hz / 1_000_000) as u32)
+}
+
+async fn read_gpu_power(suffix: &str) -> Result<f64> {
+    // hwmon reports power in microwatts, but we expose it in watts
+    let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
+    let microwatts: u64 = fs::read_to_string(base.join(suffix))
+        .await
+        .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    Ok(microwatts as f64 / 1_000_000.0)
+}
+
+pub(crate) async fn get_gpu_power_cap() -> Result<f64> {
+    read_gpu_power("power1_cap").await
+}
+
+pub(crate) async fn get_gpu_power_cap_min() -> Result<f64> {
+    read_gpu_power("power1_cap_min").await
+}
+
+pub(crate) async fn get_gpu_power_cap_max() -> Result<f64> {
+    read_gpu_power("power1_cap_max").await
+}
+
+pub(crate) async fn get_gpu_power_average() -> Result<f64> {
+    match read_gpu_power("power1_average").await {
+        Ok(power) => Ok(power),
+        // Newer SMUs only report the instantaneous power draw
+        Err(_) => read_gpu_power("power1_input").await,
+    }
+}
+
+pub(crate) async fn get_gpu_metrics() -> Result<HashMap<String, u64>> {
+    let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
+    let mut metrics = HashMap::new();
+    for (name, suffix) in AMDGPU_METRICS {
+        // Not every GPU exposes every metric, e.g. APUs lack mem_busy_percent
+        let Ok(contents) = fs::read_to_string(base.join(suffix)).await else {
+            continue;
+        };
+        let value = contents
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Error parsing {name}: {e}"))?;
+        metrics.insert(name.to_string(), value);
+    }
+    ensure!(!metrics.is_empty(), "No GPU metrics available");
+    Ok(metrics)
+}
+
+async fn read_temperature(path: &Path) -> Result<i64> {
+    // Left in millidegrees, since these are only ever compared with each other
+    fs::read_to_string(path)
+        .await
+        .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+/// Whether the GPU is being throttled because it's too hot, which is the case once any of its
+/// temperatures reaches the critical point that the firmware starts throttling at
+pub(crate) async fn get_gpu_thermal_throttling() -> Result<bool> {
+    let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
+    let mut throttling = None;
+    for sensor in AMDGPU_TEMPERATURES {
+        let Ok(critical) = read_temperature(&base.join(format!("{sensor}_crit"))).await else {
+            continue;
+        };
+        let temperature = read_temperature(&base.join(format!("{sensor}_input"))).await?;
+        throttling = Some(throttling.unwrap_or(false) || temperature >= critical);
+    }
+    throttling.ok_or(anyhow!("No GPU temperature trip points available"))
+}
+
+pub(crate) struct GpuThrottleMonitor {
+    session: Connection,
+    throttling: bool,
+}
+
+impl GpuThrottleMonitor {
+    pub(crate) async fn init(session: &Connection) -> Result<GpuThrottleMonitor> {
+        Ok(GpuThrottleMonitor {
+            session: session.clone(),
+            throttling: get_gpu_thermal_throttling().await?,
+        })
+    }
+
+    /// Re-read the throttling state, returning whether it has changed
+    async fn poll(&mut self) -> bool {
+        let Ok(throttling) = get_gpu_thermal_throttling().await else {
+            return false;
+        };
+        if throttling == self.throttling {
+            return false;
+        }
+        debug!(
+            "GPU {} thermal throttling",
+            if throttling { "started" } else { "stopped" }
+        );
+        self.throttling = throttling;
+        true
+    }
+}
+
+impl Service for GpuThrottleMonitor {
+    const NAME: &'static str = "gpu-throttle-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let Ok(throttle) = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<GpuThrottle1>>(MANAGER_PATH)
+            .await
+        else {
+            return Ok(());
+        };
+        let ctx = throttle.signal_emitter();
+
+        let mut interval = interval(THROTTLE_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if self.poll().await {
+                throttle.get().await.thermal_throttling_changed(ctx).await?;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use super::*;
     use crate::hardware::test::fake_model;
     use crate::hardware::SteamDeckVariant;
     use crate::power::HWMON_PREFIX;
-    use crate::{enum_roundtrip, path, testing};
+    use crate::{enum_roundtrip, path, testing, write_synced};
     use tokio::fs::{create_dir_all, read_to_string, write};
 
     pub async fn setup() -> Result<()> {
@@ -427,6 +805,19 @@ pub(crate) mod test {
  9       UNCAPPED";
         write(filename.as_path(), contents).await?;
 
+        write(base.join("device/gpu_busy_percent"), "42\n").await?;
+        write(base.join("device/mem_info_vram_used"), "268435456\n").await?;
+        write(base.join("device/mem_info_vram_total"), "1073741824\n").await?;
+
+        write(base.join("power1_cap_min"), "0\n").await?;
+        write(base.join("power1_cap_max"), "20000000\n").await?;
+        write(base.join("power1_average"), "8250000\n").await?;
+
+        write(base.join("temp1_input"), "52000\n").await?;
+        write(base.join("temp1_crit"), "100000\n").await?;
+        write(base.join("temp2_input"), "61000\n").await?;
+        write(base.join("temp2_crit"), "110000\n").await?;
+
         Ok(())
     }
 
@@ -663,6 +1054,141 @@ CCLK_RANGE in Core0:
             "profile_peak": str = ProfilePeak,
         });
         assert!(AmdgpuPerformanceLevel::from_str("peak_performance").is_err());
+
+        enum_roundtrip!(IntelPerformanceLevel {
+            "auto": str = Auto,
+            "low": str = Low,
+            "high": str = High,
+            "manual": str = Manual,
+        });
+        assert!(IntelPerformanceLevel::from_str("profile_peak").is_err());
+    }
+
+    async fn setup_intel(driver: IntelDriver) -> Result<PathBuf> {
+        // A connector, which should be skipped
+        create_dir_all(path(DRM_PREFIX).join("card0-eDP-1")).await?;
+
+        let card = path(DRM_PREFIX).join("card1");
+        create_dir_all(card.join("device")).await?;
+        write(
+            card.join("device/uevent"),
+            format!("DRIVER={driver}\nPCI_CLASS=30000\n"),
+        )
+        .await?;
+        let base = match driver {
+            IntelDriver::I915 => card,
+            IntelDriver::Xe => card.join("device/tile0/gt0/freq0"),
+        };
+        create_dir_all(&base).await?;
+
+        let intel = IntelPerformanceLevelDriver { driver };
+        write(base.join(intel.hardware_min_freq_suffix()), "300\n").await?;
+        write(base.join(intel.hardware_max_freq_suffix()), "1300\n").await?;
+        write(base.join(intel.min_freq_suffix()), "300\n").await?;
+        write(base.join(intel.max_freq_suffix()), "1300\n").await?;
+        if let Some(boost) = intel.boost_freq_suffix() {
+            write(base.join(boost), "1300\n").await?;
+        }
+        Ok(base)
+    }
+
+    async fn test_intel_performance_level(driver: IntelDriver) {
+        let _h = testing::start();
+        let intel = IntelPerformanceLevelDriver { driver };
+        assert!(intel.get_performance_level().await.is_err());
+        assert!(intel
+            .get_available_performance_levels()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let base = setup_intel(driver).await.expect("setup");
+        let read = |suffix: &str| {
+            let file = base.join(suffix);
+            async move {
+                read_to_string(file)
+                    .await
+                    .unwrap()
+                    .trim()
+                    .parse::<u32>()
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(
+            intel
+                .get_available_performance_levels()
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(intel.get_clocks_range().await.unwrap(), 300..=1300);
+        assert_eq!(
+            intel.get_performance_level().await.unwrap(),
+            GpuPerformanceLevel::Intel(IntelPerformanceLevel::Auto)
+        );
+
+        intel
+            .set_performance_level(GpuPerformanceLevel::Intel(IntelPerformanceLevel::Low))
+            .await
+            .expect("set");
+        assert_eq!(read(intel.min_freq_suffix()).await, 300);
+        assert_eq!(read(intel.max_freq_suffix()).await, 300);
+        assert_eq!(
+            intel.get_performance_level().await.unwrap(),
+            GpuPerformanceLevel::Intel(IntelPerformanceLevel::Low)
+        );
+
+        intel
+            .set_performance_level(GpuPerformanceLevel::Intel(IntelPerformanceLevel::High))
+            .await
+            .expect("set");
+        assert_eq!(read(intel.min_freq_suffix()).await, 1300);
+        assert_eq!(read(intel.max_freq_suffix()).await, 1300);
+        assert_eq!(
+            intel.get_performance_level().await.unwrap(),
+            GpuPerformanceLevel::Intel(IntelPerformanceLevel::High)
+        );
+
+        intel.set_clocks(800).await.expect("set_clocks");
+        assert_eq!(intel.get_clocks().await.unwrap(), 800);
+        assert_eq!(read(intel.min_freq_suffix()).await, 800);
+        if let Some(boost) = intel.boost_freq_suffix() {
+            assert_eq!(read(boost).await, 800);
+        }
+        assert_eq!(
+            intel.get_performance_level().await.unwrap(),
+            GpuPerformanceLevel::Intel(IntelPerformanceLevel::Manual)
+        );
+        assert!(intel.set_clocks(1400).await.is_err());
+        assert!(intel.set_clocks(200).await.is_err());
+
+        intel
+            .set_performance_level(GpuPerformanceLevel::Intel(IntelPerformanceLevel::Auto))
+            .await
+            .expect("set");
+        assert_eq!(read(intel.min_freq_suffix()).await, 300);
+        assert_eq!(read(intel.max_freq_suffix()).await, 1300);
+        if let Some(boost) = intel.boost_freq_suffix() {
+            assert_eq!(read(boost).await, 1300);
+        }
+
+        assert!(intel
+            .set_performance_level(GpuPerformanceLevel::Amdgpu(AmdgpuPerformanceLevel::Auto))
+            .await
+            .is_err());
+        assert!(intel.performance_level_from_str("profile_peak").is_err());
+    }
+
+    #[tokio::test]
+    async fn i915_performance_level() {
+        test_intel_performance_level(IntelDriver::I915).await;
+    }
+
+    #[tokio::test]
+    async fn xe_performance_level() {
+        test_intel_performance_level(IntelDriver::Xe).await;
     }
 
     #[tokio::test]
@@ -898,4 +1424,112 @@ CCLK_RANGE in Core0:
             .expect("fake_model");
         assert!(driver.get_power_profile().await.is_err());
     }
+
+    #[tokio::test]
+    async fn gpu_power() {
+        let _h = testing::start();
+
+        assert!(get_gpu_power_cap().await.is_err());
+
+        setup().await.expect("setup");
+        let base = find_hwmon(AMDGPU_HWMON_NAME).await.unwrap();
+        assert!(get_gpu_power_average().await.is_err());
+
+        write(base.join("power1_cap"), "15000000\n")
+            .await
+            .expect("write");
+        write(base.join("power1_cap_min"), "3000000\n")
+            .await
+            .expect("write");
+        write(base.join("power1_cap_max"), "30000000\n")
+            .await
+            .expect("write");
+        write(base.join("power1_input"), "10500000\n")
+            .await
+            .expect("write");
+        assert_eq!(get_gpu_power_cap().await.unwrap(), 15.0);
+        assert_eq!(get_gpu_power_cap_min().await.unwrap(), 3.0);
+        assert_eq!(get_gpu_power_cap_max().await.unwrap(), 30.0);
+        assert_eq!(get_gpu_power_average().await.unwrap(), 10.5);
+
+        write(base.join("power1_average"), "7250000\n")
+            .await
+            .expect("write");
+        assert_eq!(get_gpu_power_average().await.unwrap(), 7.25);
+    }
+
+    #[tokio::test]
+    async fn gpu_metrics() {
+        let _h = testing::start();
+
+        assert!(get_gpu_metrics().await.is_err());
+
+        setup().await.expect("setup");
+        assert!(get_gpu_metrics().await.is_err());
+
+        let base = find_hwmon(AMDGPU_HWMON_NAME).await.unwrap();
+        write(base.join("device/gpu_busy_percent"), "37\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            get_gpu_metrics().await.unwrap(),
+            [(String::from("gpu_busy_percent"), 37)].into()
+        );
+
+        write(base.join("device/mem_busy_percent"), "12\n")
+            .await
+            .expect("write");
+        write(base.join("device/mem_info_vram_used"), "536870912\n")
+            .await
+            .expect("write");
+        write(base.join("device/mem_info_vram_total"), "1073741824\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            get_gpu_metrics().await.unwrap(),
+            [
+                (String::from("gpu_busy_percent"), 37),
+                (String::from("mem_busy_percent"), 12),
+                (String::from("vram_used"), 536870912),
+                (String::from("vram_total"), 1073741824),
+            ]
+            .into()
+        );
+
+        write(base.join("device/gpu_busy_percent"), "bogus\n")
+            .await
+            .expect("write");
+        assert!(get_gpu_metrics().await.is_err());
+    }
+    #[tokio::test]
+    async fn gpu_thermal_throttling() {
+        let mut h = testing::start();
+
+        setup().await.expect("setup");
+        let base = find_hwmon(AMDGPU_HWMON_NAME).await.unwrap();
+        write(base.join("temp1_input"), "52000\n")
+            .await
+            .expect("write");
+        assert!(get_gpu_thermal_throttling().await.is_err());
+
+        create_nodes().await.expect("create_nodes");
+        assert!(!get_gpu_thermal_throttling().await.unwrap());
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut monitor = GpuThrottleMonitor::init(&connection).await.expect("init");
+        assert!(!monitor.poll().await);
+
+        write(base.join("temp2_input"), "110000\n")
+            .await
+            .expect("write");
+        assert!(get_gpu_thermal_throttling().await.unwrap());
+        assert!(monitor.poll().await);
+        assert!(!monitor.poll().await);
+
+        write(base.join("temp2_input"), "95000\n")
+            .await
+            .expect("write");
+        assert!(monitor.poll().await);
+        assert!(!get_gpu_thermal_throttling().await.unwrap());
+    }
 }
@@ -0,0 +1,174 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, ensure, Result};
+use tokio::fs::read_to_string;
+use tracing::error;
+
+use crate::hardware::{device_config, HapticsConfig, RangeConfig};
+use crate::path;
+use crate::power::write_sysfs;
+
+/// Rumble intensity is exposed as a percentage of the range the hardware supports
+pub(crate) const MAX_HAPTICS_INTENSITY: u32 = 100;
+
+async fn haptics_config() -> Result<HapticsConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.haptics.clone())
+        .ok_or(anyhow!("No haptics configured"))
+}
+
+async fn read_haptics_attribute(attribute: &str) -> Result<u32> {
+    read_to_string(path(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+async fn write_haptics_attribute(attribute: &str, value: u32) -> Result<()> {
+    write_sysfs(path(attribute), value.to_string().as_bytes())
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+fn intensity_span(range: &RangeConfig<u32>) -> Result<u32> {
+    ensure!(
+        range.max > range.min,
+        "Invalid haptics intensity range {}-{}",
+        range.min,
+        range.max
+    );
+    Ok(range.max - range.min)
+}
+
+pub(crate) async fn get_haptics_intensity() -> Result<u32> {
+    let config = haptics_config().await?;
+    let span = intensity_span(&config.intensity_range)?;
+    let value = read_haptics_attribute(config.intensity_attribute.as_str())
+        .await?
+        .clamp(config.intensity_range.min, config.intensity_range.max)
+        - config.intensity_range.min;
+    Ok((value * MAX_HAPTICS_INTENSITY + span / 2) / span)
+}
+
+pub(crate) async fn set_haptics_intensity(intensity: u32) -> Result<()> {
+    ensure!(
+        intensity <= MAX_HAPTICS_INTENSITY,
+        "Haptics intensity {intensity} out of range"
+    );
+    let config = haptics_config().await?;
+    let span = intensity_span(&config.intensity_range)?;
+    let value = config.intensity_range.min
+        + (intensity * span + MAX_HAPTICS_INTENSITY / 2) / MAX_HAPTICS_INTENSITY;
+    write_haptics_attribute(config.intensity_attribute.as_str(), value).await
+}
+
+pub(crate) async fn get_haptic_feedback() -> Result<bool> {
+    let config = haptics_config().await?;
+    let attribute = config
+        .feedback_attribute
+        .ok_or(anyhow!("Haptic feedback can't be toggled on this device"))?;
+    Ok(read_haptics_attribute(attribute.as_str()).await? != 0)
+}
+
+pub(crate) async fn set_haptic_feedback(enabled: bool) -> Result<()> {
+    let config = haptics_config().await?;
+    let attribute = config
+        .feedback_attribute
+        .ok_or(anyhow!("Haptic feedback can't be toggled on this device"))?;
+    write_haptics_attribute(attribute.as_str(), u32::from(enabled)).await
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::hardware::DeviceConfig;
+    use crate::testing;
+    use std::path::Path;
+    use tokio::fs::{create_dir_all, write};
+
+    pub(crate) const INTENSITY_ATTRIBUTE: &str = "/sys/devices/platform/haptics/rumble_intensity";
+    pub(crate) const FEEDBACK_ATTRIBUTE: &str = "/sys/devices/platform/haptics/haptic_feedback";
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        create_dir_all(path(
+            Path::new(INTENSITY_ATTRIBUTE)
+                .parent()
+                .unwrap()
+                .to_string_lossy(),
+        ))
+        .await?;
+        write(path(INTENSITY_ATTRIBUTE), "255\n").await?;
+        write(path(FEEDBACK_ATTRIBUTE), "1\n").await?;
+        Ok(())
+    }
+
+    fn haptics_device_config(feedback: bool) -> Option<DeviceConfig> {
+        Some(DeviceConfig {
+            haptics: Some(HapticsConfig {
+                intensity_attribute: String::from(INTENSITY_ATTRIBUTE),
+                intensity_range: RangeConfig::new(0, 255),
+                feedback_attribute: feedback.then(|| String::from(FEEDBACK_ATTRIBUTE)),
+            }),
+            ..DeviceConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn intensity() {
+        let h = testing::start();
+
+        assert!(get_haptics_intensity().await.is_err());
+        assert!(set_haptics_intensity(50).await.is_err());
+
+        create_nodes().await.expect("create_nodes");
+        h.test.device_config.replace(haptics_device_config(false));
+        assert_eq!(get_haptics_intensity().await.unwrap(), 100);
+
+        set_haptics_intensity(50)
+            .await
+            .expect("set_haptics_intensity");
+        assert_eq!(
+            read_to_string(path(INTENSITY_ATTRIBUTE)).await.unwrap(),
+            "128"
+        );
+        assert_eq!(get_haptics_intensity().await.unwrap(), 50);
+
+        set_haptics_intensity(0)
+            .await
+            .expect("set_haptics_intensity");
+        assert_eq!(get_haptics_intensity().await.unwrap(), 0);
+        assert!(set_haptics_intensity(101).await.is_err());
+
+        // Values outside of the configured range are clamped
+        write(path(INTENSITY_ATTRIBUTE), "300\n")
+            .await
+            .expect("write");
+        assert_eq!(get_haptics_intensity().await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn feedback() {
+        let h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        h.test.device_config.replace(haptics_device_config(false));
+        assert!(get_haptic_feedback().await.is_err());
+        assert!(set_haptic_feedback(false).await.is_err());
+
+        h.test.device_config.replace(haptics_device_config(true));
+        assert!(get_haptic_feedback().await.unwrap());
+        set_haptic_feedback(false)
+            .await
+            .expect("set_haptic_feedback");
+        assert!(!get_haptic_feedback().await.unwrap());
+        assert_eq!(read_to_string(path(FEEDBACK_ATTRIBUTE)).await.unwrap(), "0");
+    }
+}
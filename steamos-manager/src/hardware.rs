@@ -5,23 +5,30 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{bail, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
+use itertools::Itertools;
 use num_enum::TryFromPrimitive;
+use regex::Regex;
 use serde::de::Error;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::ErrorKind;
 use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use strum::{Display, EnumString, VariantNames};
-use tokio::fs::{read_dir, read_to_string};
+use tokio::fs::{read_dir, read_to_string, try_exists};
 #[cfg(not(test))]
 use tokio::sync::OnceCell;
+use toml::{Table, Value};
 use tracing::error;
 use zbus::Connection;
 
 use crate::gpu::{GpuPerformanceLevelDriverType, GpuPowerProfileDriverType};
 use crate::path;
-use crate::platform::{platform_config, ServiceConfig};
-use crate::power::TdpLimitingMethod;
+use crate::platform::{platform_config, ScriptConfig, ServiceConfig};
+use crate::power::{find_hwmon, write_sysfs, BatteryChargeLimitMethod, TdpLimitingMethod};
 use crate::process::{run_script, script_exit_code};
 use crate::systemd::SystemdUnit;
 
@@ -31,10 +38,13 @@ static DEVICE_CONFIG: OnceCell<Option<DeviceConfig>> = OnceCell::const_new();
 const SYS_VENDOR_PATH: &str = "/sys/class/dmi/id/sys_vendor";
 const BOARD_NAME_PATH: &str = "/sys/class/dmi/id/board_name";
 const PRODUCT_NAME_PATH: &str = "/sys/class/dmi/id/product_name";
+const BIOS_VERSION_PATH: &str = "/sys/class/dmi/id/bios_version";
 #[cfg(not(test))]
 const DEVICE_CONFIG_PATH: &str = "/usr/share/steamos-manager/devices";
 #[cfg(test)]
 const DEVICE_CONFIG_PATH: &str = "../data/devices";
+const DEVICE_CONFIG_VENDOR_PATH: &str = "/usr/share/steamos-manager/devices.d";
+const DEVICE_CONFIG_ADMIN_PATH: &str = "/etc/steamos-manager/devices.d";
 
 #[derive(Display, EnumString, PartialEq, Debug, Default, Copy, Clone)]
 #[strum(serialize_all = "snake_case", ascii_case_insensitive)]
@@ -73,13 +83,50 @@ pub(crate) struct DeviceConfig {
     pub gpu_power_profile: Option<GpuPowerProfileConfig>,
     pub battery_charge_limit: Option<BatteryChargeLimitConfig>,
     pub performance_profile: Option<PerformanceProfileConfig>,
+    pub fan_curve: Option<FanCurveConfig>,
+    pub power_source: Option<PowerSourceConfig>,
+    pub update_controller: Option<ScriptConfig>,
+    pub speaker_protection: Option<SpeakerProtectionConfig>,
+    pub haptics: Option<HapticsConfig>,
+    pub led_control: Option<LedControlConfig>,
+    pub charge_led: Option<ChargeLedConfig>,
+    pub wake_sources: Option<WakeSourcesConfig>,
+    /// Files that were merged into this config, in the order they were applied
+    #[serde(skip)]
+    pub source: Vec<PathBuf>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub(crate) struct BatteryChargeLimitConfig {
     pub suggested_minimum_limit: Option<i32>,
-    pub hwmon_name: String,
+    #[serde(default, deserialize_with = "de_battery_charge_limit_method")]
+    pub method: BatteryChargeLimitMethod,
+    pub hwmon_name: Option<String>,
+    pub attribute: String,
+    pub charge_bypass: Option<ChargeBypassConfig>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct ChargeBypassConfig {
+    /// Name of the device under /sys/class/firmware-attributes
+    pub firmware_attribute: String,
+    /// Attribute that stops the battery from charging, running the device off external power
+    pub attribute: String,
+    /// Values of the attribute with the bypass turned on and off
+    pub enabled: String,
+    pub disabled: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct ChargeLedConfig {
+    /// Name of the device under /sys/class/firmware-attributes
+    pub firmware_attribute: String,
+    /// Attribute that selects how the charge LED behaves
     pub attribute: String,
+    /// Values of the attribute for each mode. Modes without a value aren't supported
+    pub off: Option<String>,
+    pub charging: Option<String>,
+    pub low_battery: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -89,17 +136,94 @@ pub(crate) struct DeviceMatch {
     pub variant: String,
 }
 
+/// DMI fields accept either a single pattern or a list of patterns. The vendor has to match and
+/// then either the board name or the product name has to match.
 #[derive(Clone, Deserialize, Debug)]
 pub(crate) struct DmiMatch {
+    #[serde(deserialize_with = "de_dmi_patterns")]
+    pub sys_vendor: Vec<DmiPattern>,
+    #[serde(default, deserialize_with = "de_dmi_patterns")]
+    pub board_name: Vec<DmiPattern>,
+    #[serde(default, deserialize_with = "de_dmi_patterns")]
+    pub product_name: Vec<DmiPattern>,
+}
+
+/// A pattern that a DMI field is matched against. Plain strings have to match exactly, strings
+/// containing `*` or `?` are shell-style globs matched against the whole field, and strings
+/// enclosed in slashes, e.g. `/^83[LN]\d$/`, are regular expressions.
+#[derive(Clone, Debug)]
+pub(crate) enum DmiPattern {
+    Exact(String),
+    Pattern(String, Regex),
+}
+
+#[derive(Debug)]
+pub(crate) struct DmiIdentity {
     pub sys_vendor: String,
-    pub board_name: Option<String>,
-    pub product_name: Option<String>,
+    pub board_name: String,
+    pub product_name: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct DeviceMatchReport {
+    pub path: PathBuf,
+    pub device: String,
+    pub variant: String,
+    pub matched: bool,
+    pub reason: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct SpeakerProtectionConfig {
+    /// PipeWire node name of the built-in speakers
+    pub sink: String,
+    /// Highest volume the built-in speakers can be set to, from 0.0 to 1.0
+    pub max_volume: f64,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct FanCurveConfig {
+    pub hwmon_name: String,
+    pub pwm: u32,
+    pub points: usize,
+    pub max_temperature: u32,
+    pub custom_enable_value: Option<u32>,
+    pub default_enable_value: Option<u32>,
+}
+
+#[derive(Copy, Clone, PartialEq, Deserialize, Serialize, Debug)]
+pub(crate) struct FanCurvePoint {
+    /// Temperature in °C
+    pub temperature: u32,
+    /// PWM duty cycle, from 0 to 255
+    pub pwm: u32,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub(crate) struct FirmwareAttributeConfig {
     pub attribute: String,
     pub performance_profile: Option<String>,
+    #[serde(default)]
+    pub limits: FirmwareAttributeLimits,
+}
+
+/// Which package power limits the firmware has besides the sustained one
+#[derive(Copy, Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct FirmwareAttributeLimits {
+    /// Slow package power tracking limit, `ppt_pl2_sppt`
+    pub sppt: bool,
+    /// Fast package power tracking limit, `ppt_pl3_fppt`
+    pub fppt: bool,
+}
+
+impl Default for FirmwareAttributeLimits {
+    fn default() -> FirmwareAttributeLimits {
+        FirmwareAttributeLimits {
+            sppt: true,
+            fppt: true,
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -113,12 +237,42 @@ pub(crate) struct GpuPowerProfileConfig {
     pub driver: GpuPowerProfileDriverType,
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct HapticsConfig {
+    /// sysfs attribute controlling the global rumble strength
+    pub intensity_attribute: String,
+    /// Raw values of the intensity attribute for no rumble and full rumble
+    pub intensity_range: RangeConfig<u32>,
+    /// sysfs attribute turning haptic feedback on and off, if the device supports it
+    pub feedback_attribute: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub(crate) struct LedControlConfig {
+    /// Name of the LED under /sys/class/leds
+    pub led: String,
+    /// Highest brightness that can be set, if lower than what the LED reports
+    pub max_brightness: Option<u32>,
+    /// Sequences for the kernel's pattern trigger, by name
+    #[serde(default)]
+    pub patterns: HashMap<String, String>,
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub(crate) struct PerformanceProfileConfig {
     pub suggested_default: String,
     pub platform_profile_name: String,
 }
 
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct PowerSourceConfig {
+    /// Power preset to apply when external power is connected
+    pub ac_preset: Option<String>,
+    /// Power preset to apply when running off the battery
+    pub battery_preset: Option<String>,
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub(crate) struct RangeConfig<T: Clone> {
     pub min: T,
@@ -143,64 +297,277 @@ pub(crate) struct TdpLimitConfig {
     pub firmware_attribute: Option<FirmwareAttributeConfig>,
 }
 
+/// sysfs device directories whose `power/wakeup` attribute controls each wake source. Devices
+/// that aren't present, such as the ethernet adapter of a disconnected dock, are skipped.
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct WakeSourcesConfig {
+    /// Network adapters, e.g. the dock's ethernet port, for wake-on-LAN
+    pub lan: Vec<String>,
+    /// USB controllers, for waking up from connected keyboards and controllers
+    pub usb: Vec<String>,
+    pub lid: Vec<String>,
+    pub power_button: Vec<String>,
+}
+
+impl DmiPattern {
+    pub(crate) fn parse(pattern: &str) -> Result<DmiPattern> {
+        if let Some(expression) = pattern
+            .strip_prefix('/')
+            .and_then(|pattern| pattern.strip_suffix('/'))
+        {
+            return Ok(DmiPattern::Pattern(
+                pattern.to_string(),
+                Regex::new(expression)
+                    .map_err(|e| anyhow!("Invalid DMI pattern {pattern}: {e}"))?,
+            ));
+        }
+        if !pattern.contains(['*', '?']) {
+            return Ok(DmiPattern::Exact(pattern.to_string()));
+        }
+        let mut expression = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => expression.push_str(".*"),
+                '?' => expression.push('.'),
+                c => expression.push_str(regex::escape(c.encode_utf8(&mut [0; 4])).as_str()),
+            }
+        }
+        expression.push('$');
+        Ok(DmiPattern::Pattern(
+            pattern.to_string(),
+            Regex::new(&expression)?,
+        ))
+    }
+
+    pub(crate) fn matches(&self, value: &str) -> bool {
+        match self {
+            DmiPattern::Exact(pattern) => pattern == value,
+            DmiPattern::Pattern(_, regex) => regex.is_match(value),
+        }
+    }
+}
+
+impl fmt::Display for DmiPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DmiPattern::Exact(pattern) | DmiPattern::Pattern(pattern, _) => {
+                write!(f, "\"{pattern}\"")
+            }
+        }
+    }
+}
+
+impl DmiIdentity {
+    pub(crate) async fn read() -> Result<DmiIdentity> {
+        Ok(DmiIdentity {
+            sys_vendor: read_to_string(path(SYS_VENDOR_PATH))
+                .await?
+                .trim_end()
+                .to_string(),
+            board_name: read_to_string(path(BOARD_NAME_PATH))
+                .await?
+                .trim_end()
+                .to_string(),
+            product_name: read_to_string(path(PRODUCT_NAME_PATH))
+                .await?
+                .trim_end()
+                .to_string(),
+        })
+    }
+}
+
+impl DmiMatch {
+    /// Check whether this matches the given identity, along with a human-readable explanation
+    pub(crate) fn check(&self, identity: &DmiIdentity) -> (bool, String) {
+        let Some(vendor) = find_pattern(&self.sys_vendor, &identity.sys_vendor) else {
+            return (
+                false,
+                format!(
+                    "sys_vendor \"{}\" doesn't match {}",
+                    identity.sys_vendor,
+                    self.sys_vendor.iter().join(", ")
+                ),
+            );
+        };
+        for (field, patterns, value) in [
+            ("board_name", &self.board_name, &identity.board_name),
+            ("product_name", &self.product_name, &identity.product_name),
+        ] {
+            if let Some(pattern) = find_pattern(patterns, value) {
+                return (
+                    true,
+                    format!(
+                        "sys_vendor \"{}\" matches {vendor} and {field} \"{value}\" matches {pattern}",
+                        identity.sys_vendor
+                    ),
+                );
+            }
+        }
+        (
+            false,
+            format!(
+                "sys_vendor \"{}\" matches {vendor} but neither board_name \"{}\" nor product_name \"{}\" match",
+                identity.sys_vendor, identity.board_name, identity.product_name
+            ),
+        )
+    }
+}
+
+impl DeviceMatch {
+    fn matches(&self, identity: &DmiIdentity) -> bool {
+        self.dmi.as_ref().is_some_and(|dmi| dmi.check(identity).0)
+    }
+}
+
 impl DeviceConfig {
     pub(crate) async fn device_match(&self) -> Result<Option<&'_ DeviceMatch>> {
-        let sys_vendor = read_to_string(path(SYS_VENDOR_PATH)).await?;
-        let sys_vendor = sys_vendor.trim_end();
-        let board_name = read_to_string(path(BOARD_NAME_PATH)).await?;
-        let board_name = board_name.trim_end();
-        let product_name = read_to_string(path(PRODUCT_NAME_PATH)).await?;
-        let product_name = product_name.trim_end();
-
-        for device in &self.device {
-            if let Some(dmi) = &device.dmi {
-                if dmi.sys_vendor != sys_vendor {
+        let identity = DmiIdentity::read().await?;
+        Ok(self.device.iter().find(|device| device.matches(&identity)))
+    }
+
+    #[cfg(not(test))]
+    fn config_dirs() -> [PathBuf; 3] {
+        [
+            PathBuf::from(DEVICE_CONFIG_PATH),
+            PathBuf::from(DEVICE_CONFIG_VENDOR_PATH),
+            PathBuf::from(DEVICE_CONFIG_ADMIN_PATH),
+        ]
+    }
+
+    #[cfg(test)]
+    fn config_dirs() -> [PathBuf; 3] {
+        [
+            PathBuf::from(DEVICE_CONFIG_PATH),
+            path(DEVICE_CONFIG_VENDOR_PATH),
+            path(DEVICE_CONFIG_ADMIN_PATH),
+        ]
+    }
+
+    /// Find all device config files, ordered by file name. A file in a later directory replaces
+    /// a file with the same name in an earlier one, so that e.g. an administrator can override a
+    /// vendor file by dropping a file with the same name into /etc.
+    async fn config_files() -> Result<Vec<PathBuf>> {
+        let mut files = BTreeMap::new();
+        for dir in Self::config_dirs() {
+            let mut entries = match read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => {
+                    error!("Failed to read config directory {}: {e}", dir.display());
                     continue;
                 }
-                if Some(board_name) == dmi.board_name.as_deref() {
-                    return Ok(Some(device));
-                }
-                if Some(product_name) == dmi.product_name.as_deref() {
-                    return Ok(Some(device));
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if !path.extension().is_some_and(|ext| ext == "toml") {
+                    continue;
                 }
+                files.insert(entry.file_name(), path);
             }
         }
-        Ok(None)
+        Ok(files.into_values().collect())
     }
 
+    async fn read_file(path: &Path) -> Result<(Table, Vec<DeviceMatch>)> {
+        let config = read_to_string(path)
+            .await
+            .map_err(|e| anyhow!("Failed to read config file {}: {e}", path.display()))?;
+        let config: Table = toml::from_str(config.as_ref())
+            .map_err(|e| anyhow!("Failed to parse config file {}: {e}", path.display()))?;
+        let devices = match config.get("device").cloned() {
+            Some(devices) => Vec::<DeviceMatch>::deserialize(devices)
+                .map_err(|e| anyhow!("Failed to parse config file {}: {e}", path.display()))?,
+            None => Vec::new(),
+        };
+        Ok((config, devices))
+    }
+
+    /// Load every config file that matches this device and merge them in order, with tables
+    /// merged recursively and later values replacing earlier ones
     async fn load() -> Result<Option<DeviceConfig>> {
-        let mut dir = read_dir(DEVICE_CONFIG_PATH).await?;
-        while let Some(config) = dir.next_entry().await? {
-            let path = config.path();
-            if let Some(ext) = path.extension() {
-                if ext != "toml" {
-                    continue;
-                }
-            } else {
-                continue;
-            }
-            let config = match read_to_string(&path).await {
+        let identity = DmiIdentity::read().await?;
+        let mut merged = Table::new();
+        let mut source = Vec::new();
+        for path in Self::config_files().await? {
+            let (config, devices) = match Self::read_file(&path).await {
                 Ok(config) => config,
                 Err(e) => {
-                    error!("Failed to read config file {}: {e}", path.display());
+                    error!("{e}");
                     continue;
                 }
             };
-            let config: DeviceConfig = match toml::from_str(config.as_ref()) {
-                Ok(config) => config,
-                Err(e) => {
-                    error!("Failed to parse config file {}: {e}", path.display());
-                    continue;
-                }
-            };
-            if config.device_match().await?.is_some() {
-                return Ok(Some(config));
+            if devices.iter().any(|device| device.matches(&identity)) {
+                merge_tables(&mut merged, config);
+                source.push(path);
+            }
+        }
+        if source.is_empty() {
+            return Ok(None);
+        }
+        let mut config: DeviceConfig = Value::Table(merged).try_into().map_err(|e| {
+            anyhow!(
+                "Failed to parse merged device config from {}: {e}",
+                source
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+        config.source = source;
+        Ok(Some(config))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_test_paths(&mut self) {
+        if let Some(ref mut update_controller) = self.update_controller {
+            if update_controller.script.as_os_str().is_empty() {
+                update_controller.script = path("exe");
             }
         }
-        Ok(None)
     }
 }
 
+fn find_pattern<'a>(patterns: &'a [DmiPattern], value: &str) -> Option<&'a DmiPattern> {
+    patterns.iter().find(|pattern| pattern.matches(value))
+}
+
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        let value = match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => {
+                merge_tables(base, overlay);
+                continue;
+            }
+            (_, value) => value,
+        };
+        base.insert(key, value);
+    }
+}
+
+fn de_dmi_patterns<'de, D>(deserializer: D) -> Result<Vec<DmiPattern>, D::Error>
+where
+    D: Deserializer<'de>,
+    D::Error: Error,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    let patterns = match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(pattern) => vec![pattern],
+        OneOrMany::Many(patterns) => patterns,
+    };
+    patterns
+        .iter()
+        .map(|pattern| DmiPattern::parse(pattern).map_err(D::Error::custom))
+        .collect()
+}
+
 fn de_tdp_limiter_method<'de, D>(deserializer: D) -> Result<TdpLimitingMethod, D::Error>
 where
     D: Deserializer<'de>,
@@ -211,6 +578,53 @@ where
         .map_err(|_| D::Error::unknown_variant(string.as_str(), TdpLimitingMethod::VARIANTS))
 }
 
+fn de_battery_charge_limit_method<'de, D>(
+    deserializer: D,
+) -> Result<BatteryChargeLimitMethod, D::Error>
+where
+    D: Deserializer<'de>,
+    D::Error: Error,
+{
+    let string = String::deserialize(deserializer)?;
+    BatteryChargeLimitMethod::try_from(string.as_str())
+        .map_err(|_| D::Error::unknown_variant(string.as_str(), BatteryChargeLimitMethod::VARIANTS))
+}
+
+/// Check every entry of every device config file against this device
+pub(crate) async fn explain_device_match() -> Result<Vec<DeviceMatchReport>> {
+    let identity = DmiIdentity::read().await?;
+    let mut report = Vec::new();
+    for path in DeviceConfig::config_files().await? {
+        let devices = match DeviceConfig::read_file(&path).await {
+            Ok((_, devices)) => devices,
+            Err(e) => {
+                report.push(DeviceMatchReport {
+                    path,
+                    device: String::new(),
+                    variant: String::new(),
+                    matched: false,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        for device in devices {
+            let (matched, reason) = match device.dmi.as_ref() {
+                Some(dmi) => dmi.check(&identity),
+                None => (false, String::from("No DMI match configured")),
+            };
+            report.push(DeviceMatchReport {
+                path: path.clone(),
+                device: device.device,
+                variant: device.variant,
+                matched,
+                reason,
+            });
+        }
+    }
+    Ok(report)
+}
+
 #[cfg(not(test))]
 pub(crate) async fn device_config() -> Result<&'static Option<DeviceConfig>> {
     DEVICE_CONFIG.get_or_try_init(DeviceConfig::load).await
@@ -223,6 +637,17 @@ pub(crate) async fn device_config() -> Result<Option<DeviceConfig>> {
     Ok(config)
 }
 
+/// Read the device config from disk instead of using the copy loaded at startup
+#[cfg(not(test))]
+pub(crate) async fn read_device_config() -> Result<Option<DeviceConfig>> {
+    DeviceConfig::load().await
+}
+
+#[cfg(test)]
+pub(crate) async fn read_device_config() -> Result<Option<DeviceConfig>> {
+    device_config().await
+}
+
 pub(crate) async fn steam_deck_variant() -> Result<SteamDeckVariant> {
     let sys_vendor = read_to_string(path(SYS_VENDOR_PATH)).await?;
     if sys_vendor.trim_end() != "Valve" {
@@ -246,6 +671,22 @@ pub(crate) async fn device_variant() -> Result<(String, String)> {
     Ok((device.device.to_string(), device.variant.to_string()))
 }
 
+pub(crate) async fn bios_version() -> Result<String> {
+    Ok(read_to_string(path(BIOS_VERSION_PATH))
+        .await?
+        .trim_end()
+        .to_string())
+}
+
+pub(crate) fn parse_firmware_versions(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, version)| (name.trim().to_string(), version.trim().to_string()))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
 pub(crate) struct FanControl {
     connection: Connection,
 }
@@ -312,11 +753,139 @@ impl FanControl {
     }
 }
 
+impl FanCurveConfig {
+    async fn base(&self) -> Result<PathBuf> {
+        find_hwmon(self.hwmon_name.as_str()).await
+    }
+
+    fn point_attribute(&self, point: usize, kind: &str) -> String {
+        // hwmon auto points are 1-indexed
+        format!("pwm{}_auto_point{}_{kind}", self.pwm, point + 1)
+    }
+
+    fn enable_attribute(&self) -> String {
+        format!("pwm{}_enable", self.pwm)
+    }
+
+    fn validate(&self, curve: &[FanCurvePoint]) -> Result<()> {
+        ensure!(
+            curve.len() == self.points,
+            "Fan curve must have exactly {} points",
+            self.points
+        );
+        for point in curve {
+            ensure!(
+                point.temperature <= self.max_temperature,
+                "Fan curve temperature {} is above the maximum of {}",
+                point.temperature,
+                self.max_temperature
+            );
+            ensure!(point.pwm <= 255, "Fan curve PWM {} is invalid", point.pwm);
+        }
+        for pair in curve.windows(2) {
+            ensure!(
+                pair[0].temperature < pair[1].temperature,
+                "Fan curve temperatures must be strictly increasing"
+            );
+            ensure!(
+                pair[0].pwm <= pair[1].pwm,
+                "Fan curve PWM values must not decrease"
+            );
+        }
+        Ok(())
+    }
+}
+
+async fn fan_curve_config() -> Result<FanCurveConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.fan_curve.clone())
+        .ok_or(anyhow!("No fan curve configured"))
+}
+
+async fn read_fan_curve_attribute(base: &Path, attribute: &str) -> Result<u32> {
+    read_to_string(base.join(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) async fn get_fan_curve() -> Result<Vec<FanCurvePoint>> {
+    let config = fan_curve_config().await?;
+    let base = config.base().await?;
+    let mut curve = Vec::with_capacity(config.points);
+    for point in 0..config.points {
+        // hwmon temperatures are in millidegrees Celsius
+        let temperature =
+            read_fan_curve_attribute(&base, config.point_attribute(point, "temp").as_str()).await?
+                / 1000;
+        let pwm =
+            read_fan_curve_attribute(&base, config.point_attribute(point, "pwm").as_str()).await?;
+        curve.push(FanCurvePoint { temperature, pwm });
+    }
+    Ok(curve)
+}
+
+pub(crate) async fn get_fan_curve_points() -> Result<usize> {
+    Ok(fan_curve_config().await?.points)
+}
+
+pub(crate) async fn set_fan_curve(curve: &[FanCurvePoint]) -> Result<()> {
+    let config = fan_curve_config().await?;
+    config.validate(curve)?;
+    let base = config.base().await?;
+    for (point, value) in curve.iter().enumerate() {
+        write_synced(
+            base.join(config.point_attribute(point, "temp")),
+            format!("{}000", value.temperature).as_bytes(),
+        )
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
+        write_synced(
+            base.join(config.point_attribute(point, "pwm")),
+            value.pwm.to_string().as_bytes(),
+        )
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
+    }
+    if let Some(value) = config.custom_enable_value {
+        write_synced(
+            base.join(config.enable_attribute()),
+            value.to_string().as_bytes(),
+        )
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn reset_fan_curve() -> Result<()> {
+    let config = fan_curve_config().await?;
+    let Some(value) = config.default_enable_value else {
+        bail!("Resetting the fan curve is not supported");
+    };
+    let base = config.base().await?;
+    ensure!(
+        try_exists(base.join(config.enable_attribute())).await?,
+        "Fan curve enable attribute not found"
+    );
+    write_synced(
+        base.join(config.enable_attribute()),
+        value.to_string().as_bytes(),
+    )
+    .await
+    .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
     use crate::error::to_zbus_fdo_error;
     use crate::platform::{PlatformConfig, ServiceConfig};
+    use crate::power::HWMON_PREFIX;
     use crate::{enum_roundtrip, testing};
     use std::time::Duration;
     use tokio::fs::{create_dir_all, write};
@@ -364,6 +933,22 @@ pub mod test {
         Ok(h)
     }
 
+    #[tokio::test]
+    async fn firmware_versions() {
+        let _h = testing::start();
+
+        assert!(bios_version().await.is_err());
+        create_dir_all(path("/sys/class/dmi/id")).await.unwrap();
+        write(path(BIOS_VERSION_PATH), "F7A0131\n").await.unwrap();
+        assert_eq!(bios_version().await.unwrap(), "F7A0131");
+
+        let versions =
+            parse_firmware_versions("controller = 0x6560B5A2\ntouchpad=1.2\ngarbage\n=3\n");
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions.get("controller").unwrap(), "0x6560B5A2");
+        assert_eq!(versions.get("touchpad").unwrap(), "1.2");
+    }
+
     #[tokio::test]
     async fn board_lookup_invalid() {
         let _h = setup_board("ASUSTeK COMPUTER INC.\n", "INVALID\n", "INVALID\n")
@@ -589,6 +1174,228 @@ pub mod test {
         );
     }
 
+    #[tokio::test]
+    async fn layered_device_config() {
+        let h = setup_board("Valve\n", "Galileo\n", "Galileo\n")
+            .await
+            .unwrap();
+        let config = h.test.device_config.borrow().clone().unwrap();
+        assert_eq!(
+            config.source,
+            vec![PathBuf::from(DEVICE_CONFIG_PATH).join("jupiter.toml")]
+        );
+        assert_eq!(config.tdp_limit.unwrap().range.unwrap().max, 15);
+
+        create_dir_all(path(DEVICE_CONFIG_VENDOR_PATH))
+            .await
+            .unwrap();
+        create_dir_all(path(DEVICE_CONFIG_ADMIN_PATH))
+            .await
+            .unwrap();
+
+        // A file with the same name replaces the shipped one
+        write(
+            path(DEVICE_CONFIG_VENDOR_PATH).join("jupiter.toml"),
+            r#"
+[[device]]
+dmi.sys_vendor = "Valve"
+dmi.board_name = "Galileo"
+device = "steam_deck"
+variant = "Galileo"
+
+[tdp_limit]
+method = "amdgpu_hwmon"
+download_mode_limit = 6
+
+[tdp_limit.range]
+min = 3
+max = 18
+"#,
+        )
+        .await
+        .unwrap();
+
+        // Matching files are merged on top of each other
+        write(
+            path(DEVICE_CONFIG_ADMIN_PATH).join("zz-galileo.toml"),
+            r#"
+[[device]]
+dmi.sys_vendor = "Valve"
+dmi.board_name = "Galileo"
+device = "steam_deck"
+variant = "Galileo"
+
+[tdp_limit.range]
+max = 20
+"#,
+        )
+        .await
+        .unwrap();
+
+        // Files for other devices and files that aren't TOML are ignored
+        write(
+            path(DEVICE_CONFIG_ADMIN_PATH).join("other.toml"),
+            r#"
+[[device]]
+dmi.sys_vendor = "Other"
+dmi.board_name = "Galileo"
+device = "other"
+variant = "other"
+
+[gpu_power_profile]
+driver = "amdgpu"
+"#,
+        )
+        .await
+        .unwrap();
+        write(path(DEVICE_CONFIG_ADMIN_PATH).join("notes.txt"), "")
+            .await
+            .unwrap();
+
+        let config = DeviceConfig::load().await.unwrap().unwrap();
+        assert_eq!(
+            config.source,
+            vec![
+                path(DEVICE_CONFIG_VENDOR_PATH).join("jupiter.toml"),
+                path(DEVICE_CONFIG_ADMIN_PATH).join("zz-galileo.toml"),
+            ]
+        );
+        let tdp_limit = config.tdp_limit.unwrap();
+        assert_eq!(tdp_limit.method, TdpLimitingMethod::AmdgpuHwmon);
+        assert_eq!(tdp_limit.range.unwrap().min, 3);
+        assert_eq!(tdp_limit.range.unwrap().max, 20);
+        assert!(config.gpu_performance.is_none());
+        assert!(config.gpu_power_profile.is_none());
+
+        write(path(SYS_VENDOR_PATH), "Other\n").await.unwrap();
+        let config = DeviceConfig::load().await.unwrap().unwrap();
+        assert_eq!(
+            config.source,
+            vec![path(DEVICE_CONFIG_ADMIN_PATH).join("other.toml")]
+        );
+
+        write(path(SYS_VENDOR_PATH), "Nobody\n").await.unwrap();
+        assert!(DeviceConfig::load().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn dmi_patterns() {
+        let exact = DmiPattern::parse("83N0").unwrap();
+        assert!(exact.matches("83N0"));
+        assert!(!exact.matches("83N01"));
+        assert!(!exact.matches("83n0"));
+
+        let glob = DmiPattern::parse("83?0*").unwrap();
+        assert!(glob.matches("83N0"));
+        assert!(glob.matches("83L0 Rev 2"));
+        assert!(!glob.matches("x83N0"));
+        assert!(!glob.matches("83N1"));
+
+        // Glob patterns don't treat anything else as special
+        let glob = DmiPattern::parse("Model (v1.0)*").unwrap();
+        assert!(glob.matches("Model (v1.0) Rev 2"));
+        assert!(!glob.matches("Model v1x0"));
+
+        let regex = DmiPattern::parse(r"/^83[LN]\d$/").unwrap();
+        assert!(regex.matches("83L3"));
+        assert!(regex.matches("83N6"));
+        assert!(!regex.matches("83Q2"));
+        assert!(DmiPattern::parse("/[/").is_err());
+    }
+
+    #[test]
+    fn dmi_pattern_lists() {
+        let devices: Table = toml::from_str(
+            r#"
+[[device]]
+dmi.sys_vendor = "LENOVO"
+dmi.product_name = ["83L3", "/^83Q[23]$/"]
+device = "legion_go_s"
+variant = "any"
+
+[[device]]
+dmi.sys_vendor = ["ASUSTeK*", "ASUS"]
+dmi.board_name = "RC7?L*"
+device = "rog_ally"
+variant = "any"
+"#,
+        )
+        .unwrap();
+        let devices =
+            Vec::<DeviceMatch>::deserialize(devices.get("device").cloned().unwrap()).unwrap();
+
+        let identity = |sys_vendor: &str, board_name: &str, product_name: &str| DmiIdentity {
+            sys_vendor: String::from(sys_vendor),
+            board_name: String::from(board_name),
+            product_name: String::from(product_name),
+        };
+        let check = |device: usize, identity: DmiIdentity| {
+            devices[device].dmi.as_ref().unwrap().check(&identity)
+        };
+
+        assert!(check(0, identity("LENOVO", "INVALID", "83L3")).0);
+        assert!(check(0, identity("LENOVO", "INVALID", "83Q3")).0);
+        assert!(!check(0, identity("LENOVO", "INVALID", "83Q4")).0);
+        assert!(check(1, identity("ASUSTeK COMPUTER INC.", "RC71L", "")).0);
+        assert!(check(1, identity("ASUS", "RC72LA", "")).0);
+        assert!(!check(1, identity("LENOVO", "RC71L", "")).0);
+
+        let (_, reason) = check(1, identity("ASUS", "RC72LA", ""));
+        assert_eq!(
+            reason,
+            r#"sys_vendor "ASUS" matches "ASUS" and board_name "RC72LA" matches "RC7?L*""#
+        );
+        let (_, reason) = check(1, identity("LENOVO", "RC71L", ""));
+        assert_eq!(
+            reason,
+            r#"sys_vendor "LENOVO" doesn't match "ASUSTeK*", "ASUS""#
+        );
+
+        let invalid: Result<DmiMatch, _> = toml::from_str(
+            r#"
+sys_vendor = "/(/"
+"#,
+        );
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn explain_device_match_report() {
+        let _h = setup_board("Valve\n", "Galileo\n", "Galileo\n")
+            .await
+            .unwrap();
+
+        let report = explain_device_match().await.unwrap();
+        let matched: Vec<_> = report.iter().filter(|entry| entry.matched).collect();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(
+            matched[0].path,
+            PathBuf::from(DEVICE_CONFIG_PATH).join("jupiter.toml")
+        );
+        assert_eq!(matched[0].device, "steam_deck");
+        assert_eq!(matched[0].variant, "Galileo");
+        assert!(report
+            .iter()
+            .any(|entry| entry.variant == "Jupiter" && !entry.matched));
+
+        create_dir_all(path(DEVICE_CONFIG_ADMIN_PATH))
+            .await
+            .unwrap();
+        write(
+            path(DEVICE_CONFIG_ADMIN_PATH).join("broken.toml"),
+            "[[device",
+        )
+        .await
+        .unwrap();
+        let report = explain_device_match().await.unwrap();
+        let broken = report
+            .iter()
+            .find(|entry| entry.path == path(DEVICE_CONFIG_ADMIN_PATH).join("broken.toml"))
+            .unwrap();
+        assert!(!broken.matched);
+        assert!(broken.reason.starts_with("Failed to parse config file"));
+    }
+
     #[test]
     fn fan_control_state_roundtrip() {
         enum_roundtrip!(FanControlState {
@@ -683,4 +1490,113 @@ pub mod test {
             FanControlState::Bios
         );
     }
+
+    #[tokio::test]
+    async fn fan_curve() {
+        let h = testing::start();
+
+        let mut config = DeviceConfig::default();
+        config.fan_curve = Some(FanCurveConfig {
+            hwmon_name: String::from("fan_curve"),
+            pwm: 1,
+            points: 3,
+            max_temperature: 100,
+            custom_enable_value: Some(1),
+            default_enable_value: Some(2),
+        });
+        h.test.device_config.replace(Some(config));
+
+        let base = path(HWMON_PREFIX).join("hwmon3");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("name"), "fan_curve\n")
+            .await
+            .expect("write");
+        write(base.join("pwm1_enable"), "2\n").await.expect("write");
+        for (point, (temp, pwm)) in [(30000, 0), (60000, 128), (90000, 255)]
+            .into_iter()
+            .enumerate()
+        {
+            write(
+                base.join(format!("pwm1_auto_point{}_temp", point + 1)),
+                format!("{temp}\n"),
+            )
+            .await
+            .expect("write");
+            write(
+                base.join(format!("pwm1_auto_point{}_pwm", point + 1)),
+                format!("{pwm}\n"),
+            )
+            .await
+            .expect("write");
+        }
+
+        assert_eq!(get_fan_curve_points().await.unwrap(), 3);
+        assert_eq!(
+            get_fan_curve().await.unwrap(),
+            vec![
+                FanCurvePoint {
+                    temperature: 30,
+                    pwm: 0
+                },
+                FanCurvePoint {
+                    temperature: 60,
+                    pwm: 128
+                },
+                FanCurvePoint {
+                    temperature: 90,
+                    pwm: 255
+                },
+            ]
+        );
+
+        let curve = vec![
+            FanCurvePoint {
+                temperature: 40,
+                pwm: 50,
+            },
+            FanCurvePoint {
+                temperature: 70,
+                pwm: 100,
+            },
+            FanCurvePoint {
+                temperature: 85,
+                pwm: 200,
+            },
+        ];
+        set_fan_curve(&curve).await.expect("set_fan_curve");
+        assert_eq!(get_fan_curve().await.unwrap(), curve);
+        assert_eq!(
+            read_to_string(base.join("pwm1_auto_point2_temp"))
+                .await
+                .unwrap(),
+            "70000"
+        );
+        assert_eq!(read_to_string(base.join("pwm1_enable")).await.unwrap(), "1");
+
+        reset_fan_curve().await.expect("reset_fan_curve");
+        assert_eq!(read_to_string(base.join("pwm1_enable")).await.unwrap(), "2");
+
+        // Wrong number of points
+        assert!(set_fan_curve(&curve[..2]).await.is_err());
+
+        // Temperatures not increasing
+        let mut invalid = curve.clone();
+        invalid[1].temperature = 40;
+        assert!(set_fan_curve(&invalid).await.is_err());
+
+        // PWM decreasing
+        let mut invalid = curve.clone();
+        invalid[2].pwm = 10;
+        assert!(set_fan_curve(&invalid).await.is_err());
+
+        // Out of range values
+        let mut invalid = curve.clone();
+        invalid[2].temperature = 110;
+        assert!(set_fan_curve(&invalid).await.is_err());
+        let mut invalid = curve.clone();
+        invalid[2].pwm = 256;
+        assert!(set_fan_curve(&invalid).await.is_err());
+
+        assert_eq!(get_fan_curve().await.unwrap(), curve);
+    }
 }
@@ -0,0 +1,269 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+//! Offline operation mode, which exposes a reduced set of settings directly on the system bus
+//! while nobody is logged in, so that fleet management tools can still adjust a device whose
+//! session isn't running.
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use tokio::fs::read_to_string;
+use tokio::sync::mpsc::Sender;
+use tokio_stream::StreamExt;
+use tracing::{debug, info, warn};
+use zbus::zvariant::OwnedObjectPath;
+use zbus::Connection;
+
+use crate::daemon::root::Command;
+use crate::manager::root::Headless1;
+use crate::policy::Guarded;
+use crate::{path, Service};
+
+const MANAGER_PATH: &str = "/com/steampowered/SteamOSManager1";
+
+#[derive(Copy, Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct HeadlessConfig {
+    /// Whether to expose the headless interface while no user session is running
+    pub enable: bool,
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Login1Sessions {
+    fn list_sessions(&self) -> zbus::Result<Vec<(String, u32, String, String, OwnedObjectPath)>>;
+
+    #[zbus(signal)]
+    fn session_new(&self, session_id: &str, object_path: OwnedObjectPath) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn session_removed(&self, session_id: &str, object_path: OwnedObjectPath) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Login1Session {
+    #[zbus(property, name = "Type")]
+    fn session_type(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn class(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn leader(&self) -> zbus::Result<u32>;
+}
+
+/// Whether a session is someone sitting in front of the device, rather than a greeter, a remote
+/// login or a session that is only lingering in the background
+fn is_graphical_session(session_type: &str, class: &str, state: &str) -> bool {
+    matches!(session_type, "x11" | "wayland") && class == "user" && state == "active"
+}
+
+/// Whether the leader of a session, which runs its compositor, hasn't exited yet
+async fn is_leader_running(pid: u32) -> bool {
+    let Ok(stat) = read_to_string(path(format!("/proc/{pid}/stat"))).await else {
+        return false;
+    };
+    // The command name can contain anything, so the state is found after its closing parenthesis
+    stat.rsplit_once(')')
+        .and_then(|(_, fields)| fields.split_whitespace().next())
+        .is_some_and(|state| !matches!(state, "Z" | "X"))
+}
+
+/// Adds the headless interface to the system bus when the last user session goes away, and
+/// removes it again as soon as someone logs in
+pub(crate) struct HeadlessMonitor {
+    connection: Connection,
+    channel: Sender<Command>,
+    login1: Login1SessionsProxy<'static>,
+}
+
+impl HeadlessMonitor {
+    pub(crate) async fn init(
+        connection: Connection,
+        channel: Sender<Command>,
+    ) -> Result<HeadlessMonitor> {
+        let login1 = Login1SessionsProxy::new(&connection).await?;
+        Ok(HeadlessMonitor {
+            connection,
+            channel,
+            login1,
+        })
+    }
+
+    async fn has_graphical_session(&self) -> Result<bool> {
+        for (id, _, _, seat, session_path) in self.login1.list_sessions().await? {
+            if seat.is_empty() {
+                continue;
+            }
+            let session = Login1SessionProxy::builder(&self.connection)
+                .path(session_path)?
+                .build()
+                .await?;
+            if is_graphical_session(
+                &session.session_type().await?,
+                &session.class().await?,
+                &session.state().await?,
+            ) && is_leader_running(session.leader().await?).await
+            {
+                debug!("Session {id} is an active graphical session");
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn update(&self) -> Result<()> {
+        let headless = !self.has_graphical_session().await?;
+        self.set_headless(headless).await
+    }
+
+    async fn set_headless(&self, headless: bool) -> Result<()> {
+        let object_server = self.connection.object_server();
+        let exposed = object_server
+            .interface::<_, Guarded<Headless1>>(MANAGER_PATH)
+            .await
+            .is_ok();
+        if headless && !exposed {
+            info!("No user session is running, exposing headless interface");
+            let interface = Headless1::new(self.connection.clone(), self.channel.clone()).await;
+            object_server
+                .at(MANAGER_PATH, Guarded::new(interface))
+                .await?;
+        } else if !headless && exposed {
+            info!("User session started, removing headless interface");
+            object_server
+                .remove::<Guarded<Headless1>, _>(MANAGER_PATH)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Service for HeadlessMonitor {
+    const NAME: &'static str = "headless-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let mut session_new = self.login1.receive_session_new().await?;
+        let mut session_removed = self.login1.receive_session_removed().await?;
+        if let Err(e) = self.update().await {
+            warn!("Couldn't update headless state: {e}");
+        }
+        loop {
+            tokio::select! {
+                Some(signal) = session_new.next() => {
+                    debug!("Session added: {:?}", signal.args()?.session_id);
+                }
+                Some(signal) = session_removed.next() => {
+                    debug!("Session removed: {:?}", signal.args()?.session_id);
+                }
+                else => bail!("Lost connection to logind"),
+            }
+            if let Err(e) = self.update().await {
+                warn!("Couldn't update headless state: {e}");
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        self.set_headless(false).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::daemon::channel;
+    use crate::daemon::root::RootContext;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.Headless1",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait Headless {
+        #[zbus(property)]
+        fn set_max_charge_level(&self, level: i32) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn graphical_sessions() {
+        assert!(is_graphical_session("wayland", "user", "active"));
+        assert!(is_graphical_session("x11", "user", "active"));
+        assert!(!is_graphical_session("tty", "user", "active"));
+        assert!(!is_graphical_session("wayland", "greeter", "active"));
+        assert!(!is_graphical_session("x11", "user", "online"));
+        assert!(!is_graphical_session("wayland", "user", "closing"));
+    }
+
+    #[tokio::test]
+    async fn leader_running() {
+        let _h = testing::start();
+        create_dir_all(path("/proc/1234"))
+            .await
+            .expect("create_dir_all");
+        write(path("/proc/1234/stat"), "1234 (gamescope) S 1 1234")
+            .await
+            .expect("write");
+        create_dir_all(path("/proc/1235"))
+            .await
+            .expect("create_dir_all");
+        write(path("/proc/1235/stat"), "1235 (a) b) Z 1 1235")
+            .await
+            .expect("write");
+
+        assert!(is_leader_running(1234).await);
+        assert!(!is_leader_running(1235).await);
+        assert!(!is_leader_running(1236).await);
+    }
+
+    #[tokio::test]
+    async fn headless_interface() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let (tx, _rx) = channel::<RootContext>();
+        let monitor = HeadlessMonitor::init(connection.clone(), tx)
+            .await
+            .expect("init");
+        let exposed = || async {
+            connection
+                .object_server()
+                .interface::<_, Guarded<Headless1>>(MANAGER_PATH)
+                .await
+                .is_ok()
+        };
+
+        monitor.set_headless(true).await.expect("set_headless");
+        assert!(exposed().await);
+        monitor.set_headless(true).await.expect("set_headless");
+        assert!(exposed().await);
+
+        // Nothing can authorize the call on the test bus
+        let name = connection.unique_name().unwrap();
+        let proxy = HeadlessProxy::builder(&connection)
+            .destination(name.clone())
+            .unwrap()
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+        assert!(proxy.set_max_charge_level(80).await.is_err());
+
+        monitor.set_headless(false).await.expect("set_headless");
+        assert!(!exposed().await);
+
+        connection.close().await.unwrap();
+    }
+}
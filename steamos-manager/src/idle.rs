@@ -0,0 +1,377 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, ensure, Result};
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use strum::{Display, EnumString};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio::time::{interval, Instant, MissedTickBehavior};
+use tokio_stream::StreamExt;
+use tracing::{debug, info, warn};
+use zbus::{zvariant, Connection, Proxy};
+
+use crate::backlight::{find_backlight, get_brightness};
+use crate::manager::user::root_manager_proxy;
+use crate::power::{is_on_battery, TdpManagerCommand};
+use crate::suspend_inhibit::{Login1ManagerProxy, INHIBIT_WHO};
+use crate::Service;
+
+/// How often the policy is re-evaluated while the session is idle
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Fraction of the current brightness that the screen is dimmed to
+const DIM_FRACTION: f64 = 0.3;
+
+#[derive(
+    Display,
+    EnumString,
+    PartialEq,
+    PartialOrd,
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    TryFromPrimitive,
+    Deserialize,
+    Serialize,
+)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+#[repr(u32)]
+pub enum IdleAction {
+    #[default]
+    Nothing = 0,
+    Dim = 1,
+    Suspend = 2,
+}
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone, TryFromPrimitive)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+#[repr(u32)]
+pub enum IdlePowerState {
+    Ac = 0,
+    Battery = 1,
+    Download = 2,
+}
+
+#[derive(Copy, Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct IdlePolicy {
+    pub action: IdleAction,
+    /// Seconds of idleness after which the action is taken, or 0 to never take it
+    pub timeout: u32,
+}
+
+#[derive(Copy, Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct IdlePolicyState {
+    pub ac: IdlePolicy,
+    pub battery: IdlePolicy,
+    /// Used instead of the other policies while a download mode handle is held
+    pub download: IdlePolicy,
+}
+
+impl IdlePolicyState {
+    pub fn policy(&self, state: IdlePowerState) -> IdlePolicy {
+        match state {
+            IdlePowerState::Ac => self.ac,
+            IdlePowerState::Battery => self.battery,
+            IdlePowerState::Download => self.download,
+        }
+    }
+
+    pub fn set_policy(&mut self, state: IdlePowerState, policy: IdlePolicy) -> Result<()> {
+        ensure!(
+            state != IdlePowerState::Download || policy.action != IdleAction::Suspend,
+            "Auto-suspend can't be enabled while downloading"
+        );
+        match state {
+            IdlePowerState::Ac => self.ac = policy,
+            IdlePowerState::Battery => self.battery = policy,
+            IdlePowerState::Download => self.download = policy,
+        }
+        Ok(())
+    }
+
+    /// The action that is due after the session has been idle for a while
+    fn due_action(&self, state: IdlePowerState, idle_for: Duration) -> IdleAction {
+        let policy = self.policy(state);
+        if policy.timeout == 0 || idle_for < Duration::from_secs(policy.timeout.into()) {
+            return IdleAction::Nothing;
+        }
+        // Suspending would interrupt the download, even if the saved state asks for it
+        if state == IdlePowerState::Download && policy.action == IdleAction::Suspend {
+            return IdleAction::Nothing;
+        }
+        policy.action
+    }
+}
+
+pub(crate) enum IdlePolicyCommand {
+    Configure(IdlePolicyState),
+}
+
+pub(crate) struct IdlePolicyService {
+    logind: Login1ManagerProxy<'static>,
+    root: Proxy<'static>,
+    channel: UnboundedReceiver<IdlePolicyCommand>,
+    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    policy: IdlePolicyState,
+    idle_since: Option<Instant>,
+    /// The most drastic action taken since the session went idle
+    taken: IdleAction,
+    /// The backlight device and brightness to restore once the session is no longer idle
+    dimmed: Option<(String, u32)>,
+    // Keeps logind's own idle action from suspending while downloading
+    inhibitor: Option<zvariant::OwnedFd>,
+}
+
+impl IdlePolicyService {
+    pub(crate) async fn new(
+        system: &Connection,
+        channel: UnboundedReceiver<IdlePolicyCommand>,
+        tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    ) -> Result<IdlePolicyService> {
+        Ok(IdlePolicyService {
+            logind: Login1ManagerProxy::new(system).await?,
+            root: root_manager_proxy(system).await?,
+            channel,
+            tdp_manager,
+            policy: IdlePolicyState::default(),
+            idle_since: None,
+            taken: IdleAction::Nothing,
+            dimmed: None,
+            inhibitor: None,
+        })
+    }
+
+    async fn downloading(&self) -> bool {
+        let Some(tdp_manager) = self.tdp_manager.as_ref() else {
+            return false;
+        };
+        let (tx, rx) = oneshot::channel();
+        if tdp_manager
+            .send(TdpManagerCommand::ListDownloadModeHandles(tx))
+            .is_err()
+        {
+            return false;
+        }
+        rx.await.is_ok_and(|handles| !handles.is_empty())
+    }
+
+    async fn power_state(&self) -> IdlePowerState {
+        if self.downloading().await {
+            IdlePowerState::Download
+        } else if is_on_battery().await.unwrap_or_default() {
+            IdlePowerState::Battery
+        } else {
+            IdlePowerState::Ac
+        }
+    }
+
+    async fn set_idle(&mut self, idle: bool) -> Result<()> {
+        match (idle, self.idle_since) {
+            (true, None) => {
+                debug!("Session went idle");
+                self.idle_since = Some(Instant::now());
+                self.taken = IdleAction::Nothing;
+                self.update().await?;
+            }
+            (false, Some(_)) => {
+                debug!("Session is no longer idle");
+                self.idle_since = None;
+                self.inhibitor = None;
+                self.undim().await?;
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        let Some(idle_since) = self.idle_since else {
+            return Ok(());
+        };
+        let state = self.power_state().await;
+
+        if state != IdlePowerState::Download {
+            self.inhibitor = None;
+        } else if self.inhibitor.is_none() {
+            debug!("Taking idle inhibitor lock while downloading");
+            match self
+                .logind
+                .inhibit("idle", INHIBIT_WHO, "Downloading", "block")
+                .await
+            {
+                Ok(lock) => self.inhibitor = Some(lock),
+                Err(e) => warn!("Failed to take idle inhibitor lock: {e}"),
+            }
+        }
+
+        let action = self.policy.due_action(state, idle_since.elapsed());
+        if action <= self.taken {
+            return Ok(());
+        }
+        // Only try each action once per idle period, even if it fails
+        self.taken = action;
+        match action {
+            IdleAction::Nothing => (),
+            IdleAction::Dim => self.dim().await?,
+            IdleAction::Suspend => {
+                info!("Suspending after being idle on {state} power");
+                self.logind.suspend(false).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn dim(&mut self) -> Result<()> {
+        let device = find_backlight().await?;
+        let brightness = get_brightness(device.as_str()).await?;
+        let dimmed = (f64::from(brightness) * DIM_FRACTION) as u32;
+        debug!("Dimming {device} from {brightness} to {dimmed}");
+        let _: () = self
+            .root
+            .call("SetBrightness", &(device.as_str(), dimmed))
+            .await?;
+        self.dimmed = Some((device, brightness));
+        Ok(())
+    }
+
+    async fn undim(&mut self) -> Result<()> {
+        let Some((device, brightness)) = self.dimmed.take() else {
+            return Ok(());
+        };
+        debug!("Restoring {device} brightness to {brightness}");
+        let _: () = self
+            .root
+            .call("SetBrightness", &(device.as_str(), brightness))
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_command(&mut self, command: IdlePolicyCommand) -> Result<()> {
+        match command {
+            IdlePolicyCommand::Configure(policy) => {
+                self.policy = policy;
+                self.update().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Service for IdlePolicyService {
+    const NAME: &'static str = "idle-policy";
+
+    async fn run(&mut self) -> Result<()> {
+        let mut idle_hint = self.logind.receive_idle_hint_changed().await;
+        let mut poll = interval(IDLE_POLL_INTERVAL);
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            let result = tokio::select! {
+                message = self.channel.recv() => {
+                    let Some(message) = message else {
+                        bail!("Idle policy service channel broke");
+                    };
+                    self.handle_command(message).await
+                },
+                Some(changed) = idle_hint.next() => match changed.get().await {
+                    Ok(idle) => self.set_idle(idle).await,
+                    Err(e) => Err(e.into()),
+                },
+                _ = poll.tick(), if self.idle_since.is_some() => self.update().await,
+            };
+            if let Err(e) = result {
+                warn!("Failed to apply idle policy: {e}");
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        self.undim().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn due_action() {
+        let mut state = IdlePolicyState::default();
+        assert_eq!(
+            state.due_action(IdlePowerState::Ac, Duration::from_secs(3600)),
+            IdleAction::Nothing
+        );
+
+        state
+            .set_policy(
+                IdlePowerState::Ac,
+                IdlePolicy {
+                    action: IdleAction::Dim,
+                    timeout: 60,
+                },
+            )
+            .unwrap();
+        state
+            .set_policy(
+                IdlePowerState::Battery,
+                IdlePolicy {
+                    action: IdleAction::Suspend,
+                    timeout: 300,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            state.due_action(IdlePowerState::Ac, Duration::from_secs(59)),
+            IdleAction::Nothing
+        );
+        assert_eq!(
+            state.due_action(IdlePowerState::Ac, Duration::from_secs(60)),
+            IdleAction::Dim
+        );
+        assert_eq!(
+            state.due_action(IdlePowerState::Battery, Duration::from_secs(60)),
+            IdleAction::Nothing
+        );
+        assert_eq!(
+            state.due_action(IdlePowerState::Battery, Duration::from_secs(300)),
+            IdleAction::Suspend
+        );
+        assert_eq!(
+            state.due_action(IdlePowerState::Download, Duration::from_secs(300)),
+            IdleAction::Nothing
+        );
+
+        // A timeout of 0 disables the action
+        state.ac.timeout = 0;
+        assert_eq!(
+            state.due_action(IdlePowerState::Ac, Duration::from_secs(3600)),
+            IdleAction::Nothing
+        );
+    }
+
+    #[test]
+    fn downloads_block_suspend() {
+        let mut state = IdlePolicyState::default();
+        let suspend = IdlePolicy {
+            action: IdleAction::Suspend,
+            timeout: 60,
+        };
+        assert!(state.set_policy(IdlePowerState::Download, suspend).is_err());
+        assert_eq!(state.download, IdlePolicy::default());
+
+        // Even if the saved state was edited by hand
+        state.download = suspend;
+        assert_eq!(
+            state.due_action(IdlePowerState::Download, Duration::from_secs(60)),
+            IdleAction::Nothing
+        );
+    }
+}
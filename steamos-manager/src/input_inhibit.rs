@@ -0,0 +1,256 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, ensure, Result};
+#[cfg(not(test))]
+use input_linux::EvdevHandle;
+use std::collections::HashMap;
+#[cfg(not(test))]
+use std::fs::OpenOptions;
+use std::os::fd::OwnedFd;
+use std::path::Path;
+use tokio::net::unix::pipe;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use tracing::{debug, error, warn};
+
+use crate::{path, wait_on_handle, Service};
+
+const INPUT_DEVICE_PREFIX: &str = "/dev/input";
+
+pub(crate) enum InputInhibitCommand {
+    Inhibit(Vec<String>, oneshot::Sender<Result<OwnedFd>>),
+    ListDevices(oneshot::Sender<Vec<String>>),
+}
+
+struct Grab {
+    device: String,
+    // The kernel releases the grab when this is closed
+    _fd: OwnedFd,
+}
+
+pub(crate) struct InputInhibitService {
+    channel: UnboundedReceiver<InputInhibitCommand>,
+    handle_set: JoinSet<String>,
+    handles: HashMap<String, Vec<Grab>>,
+    next_handle: u64,
+}
+
+/// Make sure a device is an evdev node, e.g. `/dev/input/event3`, so that clients can't open
+/// arbitrary files through us
+fn validate_device(device: &str) -> Result<()> {
+    let device = Path::new(device);
+    ensure!(
+        device.parent() == Some(Path::new(INPUT_DEVICE_PREFIX)),
+        "{} is not an input device",
+        device.display()
+    );
+    let Some(number) = device
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix("event"))
+    else {
+        bail!("{} is not an evdev device", device.display());
+    };
+    ensure!(
+        !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        "{} is not an evdev device",
+        device.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(test))]
+fn grab_device(device: &str) -> Result<OwnedFd> {
+    let file = OpenOptions::new().read(true).open(path(device))?;
+    let handle = EvdevHandle::new(OwnedFd::from(file));
+    handle.grab(true)?;
+    Ok(handle.into_inner())
+}
+
+#[cfg(test)]
+fn grab_device(device: &str) -> Result<OwnedFd> {
+    Ok(std::fs::File::open(path(device))?.into())
+}
+
+impl InputInhibitService {
+    pub fn new(channel: UnboundedReceiver<InputInhibitCommand>) -> InputInhibitService {
+        InputInhibitService {
+            channel,
+            handle_set: JoinSet::new(),
+            handles: HashMap::new(),
+            next_handle: 0,
+        }
+    }
+
+    fn is_grabbed(&self, device: &str) -> bool {
+        self.handles
+            .values()
+            .flatten()
+            .any(|grab| grab.device == device)
+    }
+
+    fn inhibit(&mut self, devices: Vec<String>) -> Result<OwnedFd> {
+        ensure!(!devices.is_empty(), "No input devices given");
+        for device in devices.iter() {
+            validate_device(device)?;
+            ensure!(!self.is_grabbed(device), "{device} is already inhibited");
+        }
+
+        // If any device can't be grabbed, the ones grabbed so far are released when this is
+        // dropped
+        let mut grabs = Vec::new();
+        for device in devices {
+            debug!("Grabbing input device {device}");
+            let fd = grab_device(device.as_str())?;
+            grabs.push(Grab { device, _fd: fd });
+        }
+
+        self.next_handle += 1;
+        let identifier = self.next_handle.to_string();
+        self.handles.insert(identifier.clone(), grabs);
+        let (send, recv) = pipe::pipe()?;
+        self.handle_set.spawn(wait_on_handle(recv, identifier));
+        Ok(send.into_blocking_fd()?)
+    }
+
+    fn release(&mut self, identifier: &str) {
+        if let Some(grabs) = self.handles.remove(identifier) {
+            for grab in grabs {
+                debug!("Releasing input device {}", grab.device);
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: InputInhibitCommand) {
+        match command {
+            InputInhibitCommand::Inhibit(devices, reply) => {
+                let _ = reply.send(self.inhibit(devices));
+            }
+            InputInhibitCommand::ListDevices(reply) => {
+                let mut devices: Vec<String> = self
+                    .handles
+                    .values()
+                    .flatten()
+                    .map(|grab| grab.device.clone())
+                    .collect();
+                devices.sort();
+                let _ = reply.send(devices);
+            }
+        }
+    }
+}
+
+impl Service for InputInhibitService {
+    const NAME: &'static str = "input-inhibit";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            tokio::select! {
+                message = self.channel.recv() => {
+                    let Some(message) = message else {
+                        bail!("Input inhibit service channel broke");
+                    };
+                    self.handle_command(message);
+                },
+                Some(identifier) = self.handle_set.join_next(), if !self.handle_set.is_empty() => {
+                    match identifier {
+                        Ok(identifier) => self.release(identifier.as_str()),
+                        Err(e) => warn!("Failed to get closed input inhibit handle: {e}"),
+                    }
+                },
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        if !self.handles.is_empty() {
+            error!("Shutting down with inhibited input devices, releasing them");
+            self.handles.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn list_devices(service: &mut InputInhibitService) -> Vec<String> {
+        let (tx, mut rx) = oneshot::channel();
+        service.handle_command(InputInhibitCommand::ListDevices(tx));
+        rx.try_recv().unwrap()
+    }
+
+    #[test]
+    fn device_validation() {
+        assert!(validate_device("/dev/input/event0").is_ok());
+        assert!(validate_device("/dev/input/event12").is_ok());
+        assert!(validate_device("/dev/input/mouse0").is_err());
+        assert!(validate_device("/dev/input/event").is_err());
+        assert!(validate_device("/dev/input/event1x").is_err());
+        assert!(validate_device("/dev/input/../sda").is_err());
+        assert!(validate_device("/dev/input/by-id/event0").is_err());
+        assert!(validate_device("/etc/shadow").is_err());
+    }
+
+    #[tokio::test]
+    async fn inhibit_and_release() {
+        let _h = testing::start();
+
+        create_dir_all(path(INPUT_DEVICE_PREFIX)).await.unwrap();
+        for device in ["event0", "event1"] {
+            write(path(format!("{INPUT_DEVICE_PREFIX}/{device}")), "")
+                .await
+                .unwrap();
+        }
+
+        let (_tx, rx) = unbounded_channel();
+        let mut service = InputInhibitService::new(rx);
+        assert!(list_devices(&mut service).is_empty());
+        assert!(service.inhibit(Vec::new()).is_err());
+
+        let handle = service
+            .inhibit(vec![
+                String::from("/dev/input/event1"),
+                String::from("/dev/input/event0"),
+            ])
+            .unwrap();
+        assert_eq!(
+            list_devices(&mut service),
+            vec![
+                String::from("/dev/input/event0"),
+                String::from("/dev/input/event1")
+            ]
+        );
+
+        // Devices can only be inhibited once at a time, and nothing is grabbed if one fails
+        assert!(service
+            .inhibit(vec![String::from("/dev/input/event0")])
+            .is_err());
+        assert!(service
+            .inhibit(vec![String::from("/dev/input/event2")])
+            .is_err());
+        assert_eq!(list_devices(&mut service).len(), 2);
+
+        drop(handle);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier.as_str());
+        assert!(list_devices(&mut service).is_empty());
+
+        let _handle = service
+            .inhibit(vec![String::from("/dev/input/event0")])
+            .unwrap();
+        assert_eq!(
+            list_devices(&mut service),
+            vec![String::from("/dev/input/event0")]
+        );
+    }
+}
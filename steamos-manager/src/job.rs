@@ -7,43 +7,101 @@
 
 use anyhow::{anyhow, bail, Result};
 use libc::pid_t;
+use nix::errno::Errno;
 use nix::sys::signal;
 use nix::sys::signal::Signal;
 use nix::unistd::Pid;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::ffi::{OsStr, OsString};
 use std::io::Cursor;
 use std::os::unix::process::ExitStatusExt;
-use std::process::ExitStatus;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::mpsc::UnboundedReceiver;
-use tokio::sync::oneshot;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tokio::sync::{oneshot, watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
 use tokio_stream::StreamExt;
-use tracing::error;
+use tracing::{error, info, warn};
 use zbus::fdo::{self, IntrospectableProxy};
 use zbus::object_server::{Interface, InterfaceRef, SignalEmitter};
 use zbus::{interface, zvariant, Connection};
 use zbus_xml::Node;
 
+use crate::daemon::root::{self, RootCommand};
+use crate::daemon::DaemonCommand;
 use crate::error::{to_zbus_fdo_error, zbus_to_zbus_fdo};
 use crate::proxy::{Job1Proxy, JobManager1Proxy};
 use crate::Service;
 
 const JOB_PREFIX: &str = "/com/steampowered/SteamOSManager1/Jobs";
 
+// Lines a job prints to stdout starting with these prefixes update its progress
+const PROGRESS_PREFIX: &str = "PROGRESS=";
+const PROGRESS_TEXT_PREFIX: &str = "PROGRESS_TEXT=";
+
+/// Extracts a progress percentage from job output that doesn't use `PROGRESS=`
+pub(crate) type ProgressParser = fn(&str) -> Option<u32>;
+
+// How long a job has to exit after SIGTERM before it gets killed
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+// Number of lines of output kept for the job history
+const OUTPUT_TAIL_LINES: usize = 20;
+
 pub struct JobManager {
     // This object manages exported jobs. It spawns processes, numbers them, and
     // keeps a handle to the zbus connection to expose the name over the bus.
     connection: Connection,
     jm_iface: InterfaceRef<JobManagerInterface>,
-    mirrored_jobs: HashMap<String, zvariant::OwnedObjectPath>,
+    // Mirrored jobs, keyed by the connection and path they were mirrored from, along with the
+    // task relaying their changes
+    mirrored_jobs: HashMap<String, (zvariant::OwnedObjectPath, JoinHandle<()>)>,
     next_job: u32,
+    history: Option<Sender<root::Command>>,
 }
 
 struct Job {
-    process: Child,
-    paused: bool,
-    exit_code: Option<i32>,
+    pid: Pid,
+    start_time: u64,
+    status: watch::Sender<JobStatus>,
+    exit_code: watch::Receiver<Option<fdo::Result<i32>>>,
+    progress: watch::Receiver<JobProgress>,
+    output: Arc<Mutex<VecDeque<String>>>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub(crate) struct JobRecord {
+    /// Description of the operation, e.g. "updating BIOS"
+    pub operation: String,
+    /// Seconds since the Unix epoch
+    pub start_time: u64,
+    /// Seconds since the Unix epoch
+    pub end_time: u64,
+    pub exit_code: i32,
+    pub status: u32,
+    /// The last lines printed by the job
+    pub output: String,
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+#[repr(u32)]
+enum JobStatus {
+    Running = 0,
+    Paused = 1,
+    Exited = 2,
+    Cancelled = 3,
+    TimedOut = 4,
+}
+
+#[derive(Clone, Default, Debug)]
+struct JobProgress {
+    percent: u32,
+    text: String,
 }
 
 struct JobManagerInterface {}
@@ -56,26 +114,32 @@ pub struct JobManagerService {
 
 struct MirroredJob {
     job: Job1Proxy<'static>,
+    // Set once the daemon running the job has gone away, taking the job with it
+    lost: bool,
 }
 
 pub enum JobManagerCommand {
     MirrorConnection(Connection),
+    RemirrorConnection(Connection),
     MirrorJob {
         connection: Connection,
         path: zvariant::OwnedObjectPath,
         reply: oneshot::Sender<fdo::Result<zvariant::OwnedObjectPath>>,
     },
-    #[allow(unused)]
     RunProcess {
         executable: String,
         args: Vec<OsString>,
         operation_name: String,
+        timeout: Option<Duration>,
         reply: oneshot::Sender<fdo::Result<zvariant::OwnedObjectPath>>,
     },
 }
 
 impl JobManager {
-    pub async fn new(connection: Connection) -> Result<JobManager> {
+    pub async fn new(
+        connection: Connection,
+        history: Option<Sender<root::Command>>,
+    ) -> Result<JobManager> {
         let jm_iface = JobManagerInterface {};
         let jm_iface: InterfaceRef<JobManagerInterface> = {
             // This object needs to be dropped to appease the borrow checker
@@ -89,6 +153,7 @@ impl JobManager {
             jm_iface,
             mirrored_jobs: HashMap::new(),
             next_job: 0,
+            history,
         })
     }
 
@@ -111,14 +176,47 @@ impl JobManager {
         executable: impl AsRef<OsStr>,
         args: &[impl AsRef<OsStr>],
         operation_name: &str,
+        timeout: Option<Duration>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        self.run_process_with_progress(executable, args, operation_name, timeout, None)
+            .await
+    }
+
+    pub(crate) async fn run_process_with_progress(
+        &mut self,
+        executable: impl AsRef<OsStr>,
+        args: &[impl AsRef<OsStr>],
+        operation_name: &str,
+        timeout: Option<Duration>,
+        progress_parser: Option<ProgressParser>,
     ) -> fdo::Result<zvariant::OwnedObjectPath> {
         // Run the given executable and give back an object path
-        let job = Job::spawn(executable, args)
+        let job = Job::spawn_with_progress(executable, args, timeout, progress_parser)
             .await
             .inspect_err(|message| error!("Error {operation_name}: {message}"))
             .map_err(to_zbus_fdo_error)?;
+        let progress = job.progress.clone();
+        let status = job.status.subscribe();
+        if let Some(history) = self.history.as_ref() {
+            tokio::spawn(record_job(
+                operation_name.to_string(),
+                job.start_time,
+                job.exit_code.clone(),
+                job.status.subscribe(),
+                job.progress.clone(),
+                job.output.clone(),
+                history.clone(),
+            ));
+        }
 
-        self.add_job(job).await
+        let object_path = self.add_job(job).await?;
+        let job = self
+            .connection
+            .object_server()
+            .interface(object_path.as_ref())
+            .await?;
+        tokio::spawn(relay_job_changes(job, progress, status));
+        Ok(object_path)
     }
 
     pub async fn mirror_job<'a, P>(
@@ -132,7 +230,7 @@ impl JobManager {
     {
         let path = path.try_into().map_err(Into::into)?.into_owned();
         let name = format!("{}:{}", connection.server_guid(), path.as_str());
-        if let Some(object_path) = self.mirrored_jobs.get(&name) {
+        if let Some((object_path, _)) = self.mirrored_jobs.get(&name) {
             return Ok(object_path.clone());
         }
 
@@ -141,13 +239,58 @@ impl JobManager {
             .path(path)?
             .build()
             .await?;
-        let job = MirroredJob { job: proxy };
+        let job = MirroredJob {
+            job: proxy.clone(),
+            lost: false,
+        };
 
         let object_path = self.add_job(job).await?;
-        self.mirrored_jobs.insert(name, object_path.clone());
+        let job = self
+            .connection
+            .object_server()
+            .interface(object_path.as_ref())
+            .await?;
+        let relay = tokio::spawn(relay_mirrored_job_changes(job, proxy));
+        self.mirrored_jobs
+            .insert(name, (object_path.clone(), relay));
         Ok(object_path)
     }
 
+    /// Drop every job mirrored from the given connection. This is needed when the peer restarts,
+    /// as its job paths get reused for unrelated jobs. Anyone watching the dropped jobs is told
+    /// that they failed, since they won't ever finish.
+    pub async fn forget_mirrored_jobs(&mut self, connection: &Connection) {
+        let prefix = format!("{}:", connection.server_guid());
+        let stale: Vec<String> = self
+            .mirrored_jobs
+            .keys()
+            .filter(|name| name.starts_with(prefix.as_str()))
+            .cloned()
+            .collect();
+        let object_server = self.connection.object_server();
+        for name in stale {
+            let Some((object_path, relay)) = self.mirrored_jobs.remove(&name) else {
+                continue;
+            };
+            relay.abort();
+            if let Ok(job) = object_server
+                .interface::<_, MirroredJob>(object_path.as_ref())
+                .await
+            {
+                job.get_mut().await.lost = true;
+                if let Err(e) = job.get().await.status_changed(job.signal_emitter()).await {
+                    warn!("Failed to signal that job {object_path} was lost: {e}");
+                }
+            }
+            if let Err(e) = object_server
+                .remove::<MirroredJob, _>(object_path.as_ref())
+                .await
+            {
+                warn!("Failed to remove lost job {object_path}: {e}");
+            }
+        }
+    }
+
     pub async fn mirror_connection(&mut self, connection: &Connection) -> fdo::Result<()> {
         let proxy = IntrospectableProxy::builder(connection)
             .destination("com.steampowered.SteamOSManager1")?
@@ -176,131 +319,426 @@ impl JobManagerInterface {
     ) -> zbus::Result<()>;
 }
 
-impl Job {
-    async fn spawn(executable: impl AsRef<OsStr>, args: &[impl AsRef<OsStr>]) -> Result<Job> {
-        let child = Command::new(executable).args(args).spawn()?;
-        Ok(Job {
-            process: child,
-            paused: false,
-            exit_code: None,
-        })
+fn exit_code(status: ExitStatus) -> Result<i32> {
+    if let Some(code) = status.code() {
+        Ok(code)
+    } else if let Some(signal) = status.signal() {
+        Ok(-signal)
+    } else {
+        bail!("Process exited without return code or signal");
     }
+}
 
-    fn send_signal(&self, signal: nix::sys::signal::Signal) -> Result<()> {
-        let Some(pid) = self.process.id() else {
-            bail!("Unable to get pid from command, it likely finished running");
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+async fn read_output<R: AsyncRead + Unpin>(
+    reader: R,
+    progress: watch::Sender<JobProgress>,
+    progress_parser: Option<ProgressParser>,
+    output: Arc<Mutex<VecDeque<String>>>,
+) {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to read job output: {e}");
+                break;
+            }
         };
-        let pid: pid_t = match pid.try_into() {
-            Ok(pid) => pid,
-            Err(message) => bail!("Unable to get pid_t from command {message}"),
+        if let Some(value) = line.strip_prefix(PROGRESS_PREFIX) {
+            match value.trim().parse::<u32>() {
+                Ok(percent) if percent <= 100 => {
+                    progress.send_modify(|progress| progress.percent = percent);
+                }
+                _ => warn!("Ignoring invalid job progress {value}"),
+            }
+        } else if let Some(text) = line.strip_prefix(PROGRESS_TEXT_PREFIX) {
+            progress.send_modify(|progress| progress.text = text.to_string());
+        } else if let Some(percent) = progress_parser.and_then(|parse| parse(line.as_str())) {
+            progress.send_modify(|progress| progress.percent = percent.min(100));
+        } else {
+            info!("{line}");
+            let mut output = output.lock().await;
+            output.push_back(line);
+            if output.len() > OUTPUT_TAIL_LINES {
+                output.pop_front();
+            }
+        }
+    }
+}
+
+async fn record_job(
+    operation: String,
+    start_time: u64,
+    mut exit_code: watch::Receiver<Option<fdo::Result<i32>>>,
+    status: watch::Receiver<JobStatus>,
+    mut progress: watch::Receiver<JobProgress>,
+    output: Arc<Mutex<VecDeque<String>>>,
+    channel: Sender<root::Command>,
+) {
+    let code = match exit_code.wait_for(Option::is_some).await {
+        Ok(code) => code.clone(),
+        Err(_) => None,
+    };
+    let Some(Ok(code)) = code else {
+        warn!("Not recording job {operation} without an exit code");
+        return;
+    };
+    let end_time = now();
+
+    // The output readers hold the progress sender, so wait for them to finish
+    // draining the pipes, unless a leftover child process keeps them open
+    let _ = timeout(Duration::from_secs(1), async {
+        while progress.changed().await.is_ok() {}
+    })
+    .await;
+
+    let record = JobRecord {
+        operation,
+        start_time,
+        end_time,
+        exit_code: code,
+        status: *status.borrow() as u32,
+        output: Vec::from(output.lock().await.clone()).join("\n"),
+    };
+    if let Err(e) = channel
+        .send(DaemonCommand::ContextCommand(RootCommand::RecordJob(
+            record,
+        )))
+        .await
+    {
+        error!("Error sending RecordJob command: {e}");
+    }
+}
+
+async fn relay_job_changes(
+    job: InterfaceRef<Job>,
+    mut progress: watch::Receiver<JobProgress>,
+    mut status: watch::Receiver<JobStatus>,
+) {
+    let mut progress_open = true;
+    let mut status_open = true;
+    while progress_open || status_open {
+        let ctx = job.signal_emitter();
+        let result = tokio::select! {
+            changed = progress.changed(), if progress_open => {
+                progress_open = changed.is_ok();
+                let iface = job.get().await;
+                match iface.progress_changed(ctx).await {
+                    Ok(()) => iface.progress_text_changed(ctx).await,
+                    Err(e) => Err(e),
+                }
+            },
+            changed = status.changed(), if status_open => {
+                status_open = changed.is_ok();
+                job.get().await.status_changed(ctx).await
+            },
         };
-        signal::kill(Pid::from_raw(pid), signal)?;
-        Ok(())
+        if let Err(e) = result {
+            warn!("Failed to emit job changes: {e}");
+        }
     }
+}
 
-    fn update_exit_code(&mut self, status: ExitStatus) -> Result<i32> {
-        if let Some(code) = status.code() {
-            self.exit_code = Some(code);
-            Ok(code)
-        } else if let Some(signal) = status.signal() {
-            self.exit_code = Some(-signal);
-            Ok(-signal)
-        } else {
-            bail!("Process exited without return code or signal");
+async fn relay_mirrored_job_changes(job: InterfaceRef<MirroredJob>, proxy: Job1Proxy<'static>) {
+    let mut progress = proxy.receive_progress_changed().await;
+    let mut progress_text = proxy.receive_progress_text_changed().await;
+    let mut status = proxy.receive_status_changed().await;
+    loop {
+        let ctx = job.signal_emitter();
+        let result = tokio::select! {
+            Some(_) = progress.next() => job.get().await.progress_changed(ctx).await,
+            Some(_) = progress_text.next() => job.get().await.progress_text_changed(ctx).await,
+            Some(_) = status.next() => job.get().await.status_changed(ctx).await,
+            else => break,
+        };
+        if let Err(e) = result {
+            warn!("Failed to relay job changes: {e}");
         }
     }
+}
 
-    fn try_wait(&mut self) -> Result<Option<i32>> {
-        if self.exit_code.is_none() {
-            // If we don't already have an exit code, try to wait for the process
-            if let Some(status) = self.process.try_wait()? {
-                self.update_exit_code(status)?;
+fn terminate(pid: Pid, exit_code: watch::Receiver<Option<fdo::Result<i32>>>) -> Result<()> {
+    signal::killpg(pid, Signal::SIGTERM)?;
+    // Stopped processes don't handle SIGTERM until they're resumed
+    signal::killpg(pid, Signal::SIGCONT)?;
+    tokio::spawn(kill_after_grace_period(pid, exit_code));
+    Ok(())
+}
+
+async fn kill_after_grace_period(
+    pid: Pid,
+    mut exit_code: watch::Receiver<Option<fdo::Result<i32>>>,
+) {
+    if timeout(TERMINATE_GRACE_PERIOD, exit_code.wait_for(Option::is_some))
+        .await
+        .is_err()
+    {
+        warn!("Job {pid} didn't exit after SIGTERM, killing it");
+        if let Err(e) = signal::killpg(pid, Signal::SIGKILL) {
+            error!("Failed to kill job {pid}: {e}");
+        }
+    }
+}
+
+async fn reap(
+    mut child: Child,
+    pid: Pid,
+    time_limit: Option<Duration>,
+    status: watch::Sender<JobStatus>,
+    exit_code: watch::Sender<Option<fdo::Result<i32>>>,
+) {
+    let mut timed_out = false;
+    let result = match time_limit {
+        Some(time_limit) => match timeout(time_limit, child.wait()).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!(
+                    "Job {pid} timed out after {}s, terminating it",
+                    time_limit.as_secs()
+                );
+                timed_out = true;
+                if let Err(e) = terminate(pid, exit_code.subscribe()) {
+                    error!("Failed to terminate job {pid}: {e}");
+                }
+                child.wait().await
             }
+        },
+        None => child.wait().await,
+    };
+    let code = match result {
+        Ok(result) => self::exit_code(result),
+        Err(e) => Err(e.into()),
+    }
+    .inspect_err(|message| error!("Unable to get exit code: {message}"))
+    .map_err(|_| fdo::Error::Failed("Unable to get exit code".to_string()));
+
+    status.send_modify(|status| {
+        *status = match *status {
+            _ if timed_out => JobStatus::TimedOut,
+            JobStatus::Cancelled => JobStatus::Cancelled,
+            _ => JobStatus::Exited,
         }
-        Ok(self.exit_code)
+    });
+    exit_code.send_replace(Some(code));
+}
+
+impl Job {
+    async fn spawn(
+        executable: impl AsRef<OsStr>,
+        args: &[impl AsRef<OsStr>],
+        time_limit: Option<Duration>,
+    ) -> Result<Job> {
+        Job::spawn_with_progress(executable, args, time_limit, None).await
     }
 
-    async fn wait_internal(&mut self) -> Result<i32> {
-        if let Some(code) = self.exit_code {
-            // Just give the exit_code if we have it already
-            Ok(code)
-        } else {
-            // Otherwise wait for the process
-            let status = self.process.wait().await?;
-            self.update_exit_code(status)
+    async fn spawn_with_progress(
+        executable: impl AsRef<OsStr>,
+        args: &[impl AsRef<OsStr>],
+        time_limit: Option<Duration>,
+        progress_parser: Option<ProgressParser>,
+    ) -> Result<Job> {
+        // Put the job in its own process group so that any processes it spawns
+        // are signalled along with it
+        let mut child = Command::new(executable)
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()?;
+        let Some(pid) = child.id() else {
+            bail!("Unable to get pid from command");
+        };
+        let pid: pid_t = match pid.try_into() {
+            Ok(pid) => pid,
+            Err(message) => bail!("Unable to get pid_t from command {message}"),
+        };
+        let pid = Pid::from_raw(pid);
+
+        let (progress_sender, progress) = watch::channel(JobProgress::default());
+        let output = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(read_output(
+                stdout,
+                progress_sender.clone(),
+                progress_parser,
+                output.clone(),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(read_output(
+                stderr,
+                progress_sender,
+                progress_parser,
+                output.clone(),
+            ));
+        }
+
+        // The process is reaped in the background so that waiting on it doesn't
+        // block the other methods and properties of the job
+        let (status, _) = watch::channel(JobStatus::Running);
+        let (exit_code_sender, exit_code) = watch::channel(None);
+        tokio::spawn(reap(
+            child,
+            pid,
+            time_limit,
+            status.clone(),
+            exit_code_sender,
+        ));
+
+        Ok(Job {
+            pid,
+            start_time: now(),
+            status,
+            exit_code,
+            progress,
+            output,
+        })
+    }
+
+    fn send_signal(&self, signal: nix::sys::signal::Signal) -> Result<()> {
+        if self.exit_code.borrow().is_some() {
+            bail!("Unable to signal process, it already finished running");
         }
+        match signal::killpg(self.pid, signal) {
+            // The process may have been reaped since we checked
+            Ok(()) | Err(Errno::ESRCH) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn try_wait(&self) -> fdo::Result<Option<i32>> {
+        self.exit_code.borrow().clone().transpose()
     }
 }
 
 #[interface(name = "com.steampowered.SteamOSManager1.Job1")]
 impl Job {
-    pub async fn pause(&mut self) -> fdo::Result<()> {
-        if self.paused {
+    pub async fn pause(&self) -> fdo::Result<()> {
+        if *self.status.borrow() == JobStatus::Paused {
             return Err(fdo::Error::Failed("Already paused".to_string()));
         }
         // Pause the given process if possible
-        // Return true on success, false otherwise
-        let result = self.send_signal(Signal::SIGSTOP).map_err(to_zbus_fdo_error);
-        self.paused = true;
-        result
+        self.send_signal(Signal::SIGSTOP)
+            .map_err(to_zbus_fdo_error)?;
+        self.status.send_replace(JobStatus::Paused);
+        Ok(())
     }
 
-    pub async fn resume(&mut self) -> fdo::Result<()> {
+    pub async fn resume(&self) -> fdo::Result<()> {
         // Resume the given process if possible
-        if !self.paused {
+        if *self.status.borrow() != JobStatus::Paused {
             return Err(fdo::Error::Failed("Not paused".to_string()));
         }
-        let result = self.send_signal(Signal::SIGCONT).map_err(to_zbus_fdo_error);
-        self.paused = false;
-        result
+        self.send_signal(Signal::SIGCONT)
+            .map_err(to_zbus_fdo_error)?;
+        self.status.send_replace(JobStatus::Running);
+        Ok(())
     }
 
-    pub async fn cancel(&mut self, force: bool) -> fdo::Result<()> {
-        if self.try_wait().map_err(to_zbus_fdo_error)?.is_none() {
-            self.send_signal(if force {
-                Signal::SIGKILL
+    pub async fn cancel(&self, force: bool) -> fdo::Result<()> {
+        if self.try_wait()?.is_none() {
+            if force {
+                self.send_signal(Signal::SIGKILL)
+                    .map_err(to_zbus_fdo_error)?;
             } else {
-                Signal::SIGTERM
-            })
-            .map_err(to_zbus_fdo_error)?;
-            if self.paused {
-                self.resume().await?;
+                self.send_signal(Signal::SIGTERM)
+                    .and_then(|()| self.send_signal(Signal::SIGCONT))
+                    .map_err(to_zbus_fdo_error)?;
+                tokio::spawn(kill_after_grace_period(self.pid, self.exit_code.clone()));
             }
+            self.status.send_replace(JobStatus::Cancelled);
         }
         Ok(())
     }
 
-    pub async fn wait(&mut self) -> fdo::Result<i32> {
-        if self.paused {
+    pub async fn wait(&self) -> fdo::Result<i32> {
+        if *self.status.borrow() == JobStatus::Paused {
             self.resume().await?;
         }
 
-        if let Ok(code) = self.wait_internal().await.map_err(to_zbus_fdo_error) {
-            self.exit_code = Some(code);
-            Ok(code)
-        } else {
-            Err(fdo::Error::Failed("Unable to get exit code".to_string()))
+        let mut exit_code = self.exit_code.clone();
+        match exit_code.wait_for(Option::is_some).await {
+            Ok(code) => code.clone().unwrap_or(Err(fdo::Error::Failed(
+                "Unable to get exit code".to_string(),
+            ))),
+            Err(_) => Err(fdo::Error::Failed("Unable to get exit code".to_string())),
         }
     }
+
+    #[zbus(property)]
+    pub async fn status(&self) -> u32 {
+        *self.status.borrow() as u32
+    }
+
+    #[zbus(property)]
+    pub async fn progress(&self) -> u32 {
+        self.progress.borrow().percent
+    }
+
+    #[zbus(property)]
+    pub async fn progress_text(&self) -> String {
+        self.progress.borrow().text.clone()
+    }
+}
+
+impl MirroredJob {
+    fn ensure_not_lost(&self) -> fdo::Result<()> {
+        if self.lost {
+            return Err(fdo::Error::Failed(String::from(
+                "Job was lost when the daemon running it went away",
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[interface(name = "com.steampowered.SteamOSManager1.Job1")]
 impl MirroredJob {
-    pub async fn pause(&mut self) -> fdo::Result<()> {
+    pub async fn pause(&self) -> fdo::Result<()> {
+        self.ensure_not_lost()?;
         self.job.pause().await.map_err(zbus_to_zbus_fdo)
     }
 
-    pub async fn resume(&mut self) -> fdo::Result<()> {
+    pub async fn resume(&self) -> fdo::Result<()> {
+        self.ensure_not_lost()?;
         self.job.resume().await.map_err(zbus_to_zbus_fdo)
     }
 
-    pub async fn cancel(&mut self, force: bool) -> fdo::Result<()> {
+    pub async fn cancel(&self, force: bool) -> fdo::Result<()> {
+        self.ensure_not_lost()?;
         self.job.cancel(force).await.map_err(zbus_to_zbus_fdo)
     }
 
-    pub async fn wait(&mut self) -> fdo::Result<i32> {
+    pub async fn wait(&self) -> fdo::Result<i32> {
+        self.ensure_not_lost()?;
         self.job.wait().await.map_err(zbus_to_zbus_fdo)
     }
+
+    #[zbus(property)]
+    pub async fn status(&self) -> fdo::Result<u32> {
+        if self.lost {
+            return Ok(JobStatus::Exited as u32);
+        }
+        self.job.status().await.map_err(zbus_to_zbus_fdo)
+    }
+
+    #[zbus(property)]
+    pub async fn progress(&self) -> fdo::Result<u32> {
+        self.job.progress().await.map_err(zbus_to_zbus_fdo)
+    }
+
+    #[zbus(property)]
+    pub async fn progress_text(&self) -> fdo::Result<String> {
+        self.job.progress_text().await.map_err(zbus_to_zbus_fdo)
+    }
 }
 
 impl JobManagerService {
@@ -321,6 +759,12 @@ impl JobManagerService {
             JobManagerCommand::MirrorConnection(connection) => {
                 self.job_manager.mirror_connection(&connection).await?;
             }
+            JobManagerCommand::RemirrorConnection(connection) => {
+                self.job_manager.forget_mirrored_jobs(&connection).await;
+                if let Err(e) = self.job_manager.mirror_connection(&connection).await {
+                    warn!("Failed to mirror jobs after reconnecting: {e}");
+                }
+            }
             JobManagerCommand::MirrorJob {
                 connection,
                 path,
@@ -335,11 +779,12 @@ impl JobManagerService {
                 executable,
                 args,
                 operation_name,
+                timeout,
                 reply,
             } => {
                 let path = self
                     .job_manager
-                    .run_process(&executable, &args, &operation_name)
+                    .run_process(&executable, &args, &operation_name, timeout)
                     .await;
                 reply
                     .send(path)
@@ -387,7 +832,7 @@ pub(crate) mod test {
     use std::time::Duration;
     use tokio::sync::{mpsc, oneshot};
     use tokio::task::JoinHandle;
-    use tokio::time::sleep;
+    use tokio::time::{sleep, timeout};
     use zbus::connection::Builder;
     use zbus::names::BusName;
 
@@ -401,7 +846,7 @@ pub(crate) mod test {
             .await
             .expect("connection");
         let sender = connection.unique_name().unwrap().to_owned();
-        let mut pm = JobManager::new(connection).await.expect("pm");
+        let mut pm = JobManager::new(connection, None).await.expect("pm");
 
         let (tx, rx) = oneshot::channel::<()>();
 
@@ -421,7 +866,7 @@ pub(crate) mod test {
         rx.await.expect("rx");
 
         let object = pm
-            .run_process("/usr/bin/true", &[] as &[&OsStr], "")
+            .run_process("/usr/bin/true", &[] as &[&OsStr], "", None)
             .await
             .expect("path");
         assert_eq!(object.as_ref(), "/com/steampowered/SteamOSManager1/Jobs/0");
@@ -437,10 +882,14 @@ pub(crate) mod test {
     async fn test_job_manager() {
         let _h = testing::start();
 
-        let mut false_process = Job::spawn("/bin/false", &[] as &[String; 0]).await.unwrap();
-        let mut true_process = Job::spawn("/bin/true", &[] as &[String; 0]).await.unwrap();
+        let false_process = Job::spawn("/bin/false", &[] as &[String; 0], None)
+            .await
+            .unwrap();
+        let true_process = Job::spawn("/bin/true", &[] as &[String; 0], None)
+            .await
+            .unwrap();
 
-        let mut pause_process = Job::spawn("/usr/bin/sleep", &["0.2"]).await.unwrap();
+        let pause_process = Job::spawn("/usr/bin/sleep", &["0.2"], None).await.unwrap();
         pause_process.pause().await.expect("pause");
 
         assert_eq!(
@@ -466,7 +915,7 @@ pub(crate) mod test {
     async fn test_multikill() {
         let _h = testing::start();
 
-        let mut sleep_process = Job::spawn("/usr/bin/sleep", &["0.1"]).await.unwrap();
+        let sleep_process = Job::spawn("/usr/bin/sleep", &["0.1"], None).await.unwrap();
         sleep_process.cancel(true).await.expect("kill");
 
         // Killing a process should be idempotent
@@ -482,7 +931,7 @@ pub(crate) mod test {
     async fn test_terminate_unpause() {
         let _h = testing::start();
 
-        let mut pause_process = Job::spawn("/usr/bin/sleep", &["0.2"]).await.unwrap();
+        let pause_process = Job::spawn("/usr/bin/sleep", &["0.2"], None).await.unwrap();
         pause_process.pause().await.expect("pause");
         assert_eq!(pause_process.try_wait().expect("try_wait"), None);
 
@@ -494,6 +943,158 @@ pub(crate) mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_job_progress() {
+        let _h = testing::start();
+
+        let job = Job::spawn(
+            "/bin/sh",
+            &[
+                "-c",
+                "echo PROGRESS=101; echo PROGRESS=42; echo PROGRESS_TEXT=Halfway there; echo done",
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.progress().await, 0);
+        assert_eq!(job.progress_text().await, "");
+        assert_eq!(job.wait().await.unwrap(), 0);
+
+        // Output may still be in flight after the process exits
+        let mut progress = job.progress.clone();
+        timeout(
+            Duration::from_secs(1),
+            progress.wait_for(|progress| !progress.text.is_empty()),
+        )
+        .await
+        .expect("timeout")
+        .expect("wait_for");
+        assert_eq!(job.progress().await, 42);
+        assert_eq!(job.progress_text().await, "Halfway there");
+    }
+
+    #[tokio::test]
+    async fn test_job_progress_parser() {
+        let _h = testing::start();
+
+        fn parse(line: &str) -> Option<u32> {
+            line.strip_suffix('%')?.parse().ok()
+        }
+
+        let job = Job::spawn_with_progress(
+            "/bin/sh",
+            &["-c", "echo 17%; echo 250%; echo PROGRESS_TEXT=Done"],
+            None,
+            Some(parse),
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.wait().await.unwrap(), 0);
+
+        let mut progress = job.progress.clone();
+        timeout(
+            Duration::from_secs(1),
+            progress.wait_for(|progress| !progress.text.is_empty()),
+        )
+        .await
+        .expect("timeout")
+        .expect("wait_for");
+        assert_eq!(job.progress().await, 100);
+        assert!(job.output.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_status() {
+        let _h = testing::start();
+
+        let true_process = Job::spawn("/bin/true", &[] as &[String; 0], None)
+            .await
+            .unwrap();
+        assert_eq!(true_process.wait().await.unwrap(), 0);
+        assert_eq!(true_process.status().await, JobStatus::Exited as u32);
+
+        let pause_process = Job::spawn("/usr/bin/sleep", &["1"], None).await.unwrap();
+        assert_eq!(pause_process.status().await, JobStatus::Running as u32);
+        pause_process.pause().await.expect("pause");
+        assert_eq!(pause_process.status().await, JobStatus::Paused as u32);
+        pause_process.cancel(true).await.expect("cancel");
+        assert_eq!(
+            pause_process.wait().await.unwrap(),
+            -(Signal::SIGKILL as i32)
+        );
+        assert_eq!(pause_process.status().await, JobStatus::Cancelled as u32);
+        assert!(pause_process.pause().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_job_timeout() {
+        let _h = testing::start();
+
+        let sleep_process = Job::spawn("/usr/bin/sleep", &["1"], Some(Duration::from_millis(100)))
+            .await
+            .unwrap();
+        assert_eq!(
+            sleep_process.wait().await.unwrap(),
+            -(Signal::SIGTERM as i32)
+        );
+        assert_eq!(sleep_process.status().await, JobStatus::TimedOut as u32);
+
+        let true_process = Job::spawn(
+            "/bin/true",
+            &[] as &[String; 0],
+            Some(Duration::from_secs(1)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(true_process.wait().await.unwrap(), 0);
+        assert_eq!(true_process.status().await, JobStatus::Exited as u32);
+    }
+
+    #[tokio::test]
+    async fn test_job_history() {
+        let _h = testing::start();
+
+        let job = Job::spawn(
+            "/bin/sh",
+            &[
+                "-c",
+                "for i in $(seq 1 25); do echo line $i; done; echo PROGRESS=50; sleep 0.1; echo oops >&2; exit 3",
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(1);
+        record_job(
+            String::from("testing"),
+            job.start_time,
+            job.exit_code.clone(),
+            job.status.subscribe(),
+            job.progress.clone(),
+            job.output.clone(),
+            tx,
+        )
+        .await;
+
+        let Some(DaemonCommand::ContextCommand(RootCommand::RecordJob(record))) = rx.recv().await
+        else {
+            panic!("Expected RecordJob command");
+        };
+        assert_eq!(record.operation, "testing");
+        assert!(record.start_time <= record.end_time);
+        assert_eq!(record.exit_code, 3);
+        assert_eq!(record.status, JobStatus::Exited as u32);
+
+        let lines: Vec<&str> = record.output.lines().collect();
+        assert_eq!(lines.len(), OUTPUT_TAIL_LINES);
+        assert!(lines.contains(&"oops"));
+        assert!(lines.contains(&"line 25"));
+        assert!(!lines.contains(&"line 1"));
+        assert!(!lines.iter().any(|line| line.starts_with(PROGRESS_PREFIX)));
+    }
+
     struct MockJob {}
 
     #[zbus::interface(name = "com.steampowered.SteamOSManager1.Job1")]
@@ -541,7 +1142,7 @@ pub(crate) mod test {
                 .build()
                 .await
                 .expect("build");
-            let mut jm = JobManager::new(connection.clone()).await.expect("jm");
+            let mut jm = JobManager::new(connection.clone(), None).await.expect("jm");
 
             sleep(Duration::from_millis(10)).await;
 
@@ -591,4 +1192,63 @@ pub(crate) mod test {
         fin_tx.send(()).expect("fin");
         job.await.expect("job").expect("job2");
     }
+    #[tokio::test]
+    async fn test_job_mirror_forget() {
+        let mut handle = testing::start();
+
+        let connection = handle.new_dbus().await.expect("connection");
+        connection
+            .request_name("com.steampowered.SteamOSManager1")
+            .await
+            .expect("reserve");
+        connection
+            .object_server()
+            .at(format!("{JOB_PREFIX}/0"), MockJob {})
+            .await
+            .expect("at");
+
+        let address = handle.dbus_address().await.unwrap();
+        let connection = Builder::address(address)
+            .expect("address")
+            .build()
+            .await
+            .expect("build");
+        let mut jm = JobManager::new(connection.clone(), None).await.expect("jm");
+        let path = jm
+            .mirror_job(&connection, format!("{JOB_PREFIX}/0"))
+            .await
+            .expect("mirror_job");
+        assert_eq!(
+            jm.mirror_job(&connection, format!("{JOB_PREFIX}/0"))
+                .await
+                .expect("mirror_job"),
+            path
+        );
+
+        let mirrored = connection
+            .object_server()
+            .interface::<_, MirroredJob>(path.as_ref())
+            .await
+            .expect("interface");
+        jm.forget_mirrored_jobs(&connection).await;
+        assert!(jm.mirrored_jobs.is_empty());
+        // Anyone still holding onto the job sees it end in failure
+        assert_eq!(
+            mirrored.get().await.status().await,
+            Ok(JobStatus::Exited as u32)
+        );
+        assert!(mirrored.get().await.wait().await.is_err());
+        assert!(connection
+            .object_server()
+            .interface::<_, MirroredJob>(path.as_ref())
+            .await
+            .is_err());
+
+        // The same remote path now belongs to a new job, so it gets mirrored afresh
+        let remirrored = jm
+            .mirror_job(&connection, format!("{JOB_PREFIX}/0"))
+            .await
+            .expect("mirror_job");
+        assert_ne!(remirrored, path);
+    }
 }
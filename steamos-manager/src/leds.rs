@@ -0,0 +1,454 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Result};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use strum::{Display, EnumString};
+use tokio::fs::read_to_string;
+use tracing::error;
+
+use crate::hardware::{device_config, ChargeLedConfig, LedControlConfig};
+use crate::path;
+use crate::power::write_sysfs;
+
+const LEDS_PREFIX: &str = "/sys/class/leds";
+const FIRMWARE_ATTRIBUTES_PREFIX: &str = "/sys/class/firmware-attributes";
+
+// Trigger that plays back the sequence written to the `pattern` attribute
+const PATTERN_TRIGGER: &str = "pattern";
+const NO_TRIGGER: &str = "none";
+
+const COLOR_CHANNELS: [&str; 3] = ["red", "green", "blue"];
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum ChargeLedMode {
+    /// The LED stays off
+    Off,
+    /// The LED is lit while the battery is charging
+    Charging,
+    /// The LED blinks when the battery is low
+    LowBattery,
+}
+
+impl ChargeLedMode {
+    const ALL: [ChargeLedMode; 3] = [
+        ChargeLedMode::Off,
+        ChargeLedMode::Charging,
+        ChargeLedMode::LowBattery,
+    ];
+}
+
+async fn led_control_config() -> Result<LedControlConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.led_control.clone())
+        .ok_or(anyhow!("No LED control configured"))
+}
+
+fn led_attribute_path(config: &LedControlConfig, attribute: &str) -> PathBuf {
+    path(LEDS_PREFIX).join(config.led.as_str()).join(attribute)
+}
+
+async fn read_led_attribute(config: &LedControlConfig, attribute: &str) -> Result<String> {
+    let contents = read_to_string(led_attribute_path(config, attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    Ok(contents.trim().to_string())
+}
+
+async fn write_led_attribute(
+    config: &LedControlConfig,
+    attribute: &str,
+    value: &str,
+) -> Result<()> {
+    write_sysfs(led_attribute_path(config, attribute), value.as_bytes())
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+fn parse_value(value: &str) -> Result<u32> {
+    value
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+/// The trigger that is currently active, which the kernel marks with brackets
+fn active_trigger(triggers: &str) -> Option<&str> {
+    triggers
+        .split_whitespace()
+        .find_map(|trigger| trigger.strip_prefix('[')?.strip_suffix(']'))
+}
+
+fn normalize_pattern(pattern: &str) -> String {
+    pattern.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Where each of the red, green and blue channels is in `multi_intensity`
+async fn color_channel_indices(config: &LedControlConfig) -> Result<[usize; 3]> {
+    let index = read_led_attribute(config, "multi_index").await?;
+    let channels: Vec<&str> = index.split_whitespace().collect();
+    let mut indices = [0; 3];
+    for (i, color) in COLOR_CHANNELS.iter().enumerate() {
+        indices[i] = channels
+            .iter()
+            .position(|channel| channel == color)
+            .ok_or(anyhow!("LED {} has no {color} channel", config.led))?;
+    }
+    Ok(indices)
+}
+
+async fn hardware_max_brightness(config: &LedControlConfig) -> Result<u32> {
+    parse_value(read_led_attribute(config, "max_brightness").await?.as_str())
+}
+
+pub(crate) async fn get_led_max_brightness() -> Result<u32> {
+    let config = led_control_config().await?;
+    let max = hardware_max_brightness(&config).await?;
+    Ok(config.max_brightness.map_or(max, |limit| limit.min(max)))
+}
+
+pub(crate) async fn get_led_brightness() -> Result<u32> {
+    let config = led_control_config().await?;
+    parse_value(read_led_attribute(&config, "brightness").await?.as_str())
+}
+
+pub(crate) async fn set_led_brightness(brightness: u32) -> Result<()> {
+    let config = led_control_config().await?;
+    ensure!(
+        brightness <= get_led_max_brightness().await?,
+        "Invalid LED brightness {brightness}"
+    );
+    write_led_attribute(&config, "brightness", brightness.to_string().as_str()).await
+}
+
+pub(crate) async fn get_led_color() -> Result<(u32, u32, u32)> {
+    let config = led_control_config().await?;
+    let indices = color_channel_indices(&config).await?;
+    let intensities = read_led_attribute(&config, "multi_intensity")
+        .await?
+        .split_whitespace()
+        .map(parse_value)
+        .collect::<Result<Vec<u32>>>()?;
+    let channel = |index: usize| {
+        intensities
+            .get(index)
+            .copied()
+            .ok_or(anyhow!("Missing intensity for LED channel {index}"))
+    };
+    Ok((
+        channel(indices[0])?,
+        channel(indices[1])?,
+        channel(indices[2])?,
+    ))
+}
+
+pub(crate) async fn set_led_color(red: u32, green: u32, blue: u32) -> Result<()> {
+    let config = led_control_config().await?;
+    let max = hardware_max_brightness(&config).await?;
+    for value in [red, green, blue] {
+        ensure!(value <= max, "Invalid LED color intensity {value}");
+    }
+    let indices = color_channel_indices(&config).await?;
+    let channels = read_led_attribute(&config, "multi_index")
+        .await?
+        .split_whitespace()
+        .count();
+    // Any channels besides red, green and blue are turned off
+    let mut intensities = vec![0; channels];
+    for (index, value) in indices.into_iter().zip([red, green, blue]) {
+        intensities[index] = value;
+    }
+    let intensities: Vec<String> = intensities.iter().map(ToString::to_string).collect();
+    write_led_attribute(&config, "multi_intensity", intensities.join(" ").as_str()).await
+}
+
+pub(crate) async fn get_available_led_patterns() -> Result<Vec<String>> {
+    let config = led_control_config().await?;
+    let mut patterns: Vec<String> = config.patterns.into_keys().collect();
+    patterns.sort();
+    Ok(patterns)
+}
+
+/// The name of the configured pattern that is playing, or an empty string if the LED is steady
+pub(crate) async fn get_led_pattern() -> Result<String> {
+    let config = led_control_config().await?;
+    let triggers = read_led_attribute(&config, "trigger").await?;
+    if active_trigger(triggers.as_str()) != Some(PATTERN_TRIGGER) {
+        return Ok(String::new());
+    }
+    let current = match read_to_string(led_attribute_path(&config, "pattern")).await {
+        Ok(pattern) => normalize_pattern(pattern.as_str()),
+        // The kernel only creates the attribute while the pattern trigger is active
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => bail!("Error reading sysfs: {e}"),
+    };
+    Ok(config
+        .patterns
+        .into_iter()
+        .find(|(_, pattern)| normalize_pattern(pattern) == current)
+        .map(|(name, _)| name)
+        .unwrap_or_default())
+}
+
+pub(crate) async fn set_led_pattern(name: &str) -> Result<()> {
+    let config = led_control_config().await?;
+    if name.is_empty() {
+        return write_led_attribute(&config, "trigger", NO_TRIGGER).await;
+    }
+    let Some(pattern) = config.patterns.get(name) else {
+        bail!("Invalid LED pattern {name}");
+    };
+    write_led_attribute(&config, "trigger", PATTERN_TRIGGER).await?;
+    write_led_attribute(&config, "pattern", normalize_pattern(pattern).as_str()).await
+}
+
+async fn charge_led_config() -> Result<ChargeLedConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.charge_led.clone())
+        .ok_or(anyhow!("No charge LED configured"))
+}
+
+fn charge_led_path(config: &ChargeLedConfig) -> PathBuf {
+    path(FIRMWARE_ATTRIBUTES_PREFIX)
+        .join(config.firmware_attribute.as_str())
+        .join("attributes")
+        .join(config.attribute.as_str())
+        .join("current_value")
+}
+
+/// The value of the firmware attribute that selects a mode, if the device supports it
+fn charge_led_value(config: &ChargeLedConfig, mode: ChargeLedMode) -> Option<&str> {
+    match mode {
+        ChargeLedMode::Off => config.off.as_deref(),
+        ChargeLedMode::Charging => config.charging.as_deref(),
+        ChargeLedMode::LowBattery => config.low_battery.as_deref(),
+    }
+}
+
+pub(crate) async fn get_available_charge_led_modes() -> Result<Vec<ChargeLedMode>> {
+    let config = charge_led_config().await?;
+    Ok(ChargeLedMode::ALL
+        .into_iter()
+        .filter(|mode| charge_led_value(&config, *mode).is_some())
+        .collect())
+}
+
+pub(crate) async fn get_charge_led_mode() -> Result<ChargeLedMode> {
+    let config = charge_led_config().await?;
+    let value = read_to_string(charge_led_path(&config))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    let value = value.trim();
+    ChargeLedMode::ALL
+        .into_iter()
+        .find(|mode| charge_led_value(&config, *mode) == Some(value))
+        .ok_or(anyhow!("Unknown charge LED mode {value}"))
+}
+
+pub(crate) async fn set_charge_led_mode(mode: ChargeLedMode) -> Result<()> {
+    let config = charge_led_config().await?;
+    let Some(value) = charge_led_value(&config, mode) else {
+        bail!("Charge LED mode {mode} is not supported");
+    };
+    write_sysfs(charge_led_path(&config), value.as_bytes())
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::hardware::DeviceConfig;
+    use crate::testing;
+    use std::collections::HashMap;
+    use tokio::fs::{create_dir_all, write};
+
+    pub(crate) const LED: &str = "ally:rgb:joystick_rings";
+
+    pub(crate) fn test_config() -> LedControlConfig {
+        LedControlConfig {
+            led: String::from(LED),
+            max_brightness: Some(200),
+            patterns: HashMap::from([
+                (String::from("pulse"), String::from("0 500 255 500")),
+                (
+                    String::from("blink"),
+                    String::from("255 250 255 0 0 250 0 0"),
+                ),
+            ]),
+        }
+    }
+
+    pub(crate) fn test_charge_led_config() -> ChargeLedConfig {
+        ChargeLedConfig {
+            firmware_attribute: String::from("asus-armoury"),
+            attribute: String::from("charge_led_mode"),
+            off: Some(String::from("0")),
+            charging: Some(String::from("1")),
+            low_battery: None,
+        }
+    }
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        let charge_led = charge_led_path(&test_charge_led_config());
+        create_dir_all(charge_led.parent().unwrap()).await?;
+        write(charge_led, "1\n").await?;
+
+        let base = path(LEDS_PREFIX).join(LED);
+        create_dir_all(&base).await?;
+        write(base.join("brightness"), "128\n").await?;
+        write(base.join("max_brightness"), "255\n").await?;
+        write(base.join("multi_index"), "red green blue\n").await?;
+        write(base.join("multi_intensity"), "255 255 255\n").await?;
+        write(base.join("trigger"), "[none] timer heartbeat pattern\n").await?;
+        Ok(())
+    }
+
+    async fn start() -> testing::TestHandle {
+        let h = testing::start();
+        create_nodes().await.expect("create_nodes");
+        h.test.device_config.replace(Some(DeviceConfig {
+            led_control: Some(test_config()),
+            ..DeviceConfig::default()
+        }));
+        h
+    }
+
+    #[test]
+    fn trigger_parsing() {
+        assert_eq!(active_trigger("none [pattern] timer"), Some("pattern"));
+        assert_eq!(active_trigger("[none] pattern"), Some("none"));
+        assert_eq!(active_trigger("none pattern"), None);
+        assert_eq!(normalize_pattern(" 0  500\t255 500 \n"), "0 500 255 500");
+    }
+
+    #[tokio::test]
+    async fn brightness() {
+        let _h = start().await;
+
+        assert_eq!(get_led_brightness().await.unwrap(), 128);
+        assert_eq!(get_led_max_brightness().await.unwrap(), 200);
+
+        set_led_brightness(200).await.expect("set_led_brightness");
+        assert_eq!(get_led_brightness().await.unwrap(), 200);
+        assert!(set_led_brightness(201).await.is_err());
+        assert_eq!(get_led_brightness().await.unwrap(), 200);
+    }
+
+    #[tokio::test]
+    async fn color() {
+        let _h = start().await;
+
+        assert_eq!(get_led_color().await.unwrap(), (255, 255, 255));
+        set_led_color(255, 128, 0).await.expect("set_led_color");
+        assert_eq!(get_led_color().await.unwrap(), (255, 128, 0));
+        assert!(set_led_color(256, 0, 0).await.is_err());
+
+        // Channels can be in any order, and extra ones are turned off
+        let base = path(LEDS_PREFIX).join(LED);
+        write(base.join("multi_index"), "blue white green red\n")
+            .await
+            .expect("write");
+        write(base.join("multi_intensity"), "1 2 3 4\n")
+            .await
+            .expect("write");
+        assert_eq!(get_led_color().await.unwrap(), (4, 3, 1));
+        set_led_color(10, 20, 30).await.expect("set_led_color");
+        assert_eq!(
+            read_to_string(base.join("multi_intensity")).await.unwrap(),
+            "30 0 20 10"
+        );
+
+        write(base.join("multi_index"), "red green\n")
+            .await
+            .expect("write");
+        assert!(get_led_color().await.is_err());
+        assert!(set_led_color(10, 20, 30).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn patterns() {
+        let _h = start().await;
+        let base = path(LEDS_PREFIX).join(LED);
+
+        assert_eq!(
+            get_available_led_patterns().await.unwrap(),
+            vec![String::from("blink"), String::from("pulse")]
+        );
+        assert_eq!(get_led_pattern().await.unwrap(), "");
+
+        set_led_pattern("pulse").await.expect("set_led_pattern");
+        assert_eq!(
+            read_to_string(base.join("trigger")).await.unwrap(),
+            "pattern"
+        );
+        assert_eq!(
+            read_to_string(base.join("pattern")).await.unwrap(),
+            "0 500 255 500"
+        );
+        assert!(set_led_pattern("rainbow").await.is_err());
+
+        write(base.join("trigger"), "none timer heartbeat [pattern]\n")
+            .await
+            .expect("write");
+        write(base.join("pattern"), "0 500 255 500 \n")
+            .await
+            .expect("write");
+        assert_eq!(get_led_pattern().await.unwrap(), "pulse");
+        write(base.join("pattern"), "0 100 255 100\n")
+            .await
+            .expect("write");
+        assert_eq!(get_led_pattern().await.unwrap(), "");
+
+        set_led_pattern("").await.expect("set_led_pattern");
+        assert_eq!(read_to_string(base.join("trigger")).await.unwrap(), "none");
+    }
+
+    #[tokio::test]
+    async fn charge_led() {
+        let h = testing::start();
+
+        assert!(get_charge_led_mode().await.is_err());
+        create_nodes().await.expect("create_nodes");
+        h.test.device_config.replace(Some(DeviceConfig {
+            charge_led: Some(test_charge_led_config()),
+            ..DeviceConfig::default()
+        }));
+
+        assert_eq!(
+            get_available_charge_led_modes().await.unwrap(),
+            vec![ChargeLedMode::Off, ChargeLedMode::Charging]
+        );
+        assert_eq!(
+            get_charge_led_mode().await.unwrap(),
+            ChargeLedMode::Charging
+        );
+
+        set_charge_led_mode(ChargeLedMode::Off)
+            .await
+            .expect("set_charge_led_mode");
+        let charge_led = charge_led_path(&test_charge_led_config());
+        assert_eq!(read_to_string(&charge_led).await.unwrap(), "0");
+        assert_eq!(get_charge_led_mode().await.unwrap(), ChargeLedMode::Off);
+
+        assert!(set_charge_led_mode(ChargeLedMode::LowBattery)
+            .await
+            .is_err());
+        write(&charge_led, "7\n").await.expect("write");
+        assert!(get_charge_led_mode().await.is_err());
+
+        assert_eq!(
+            ChargeLedMode::try_from("low_battery").unwrap(),
+            ChargeLedMode::LowBattery
+        );
+        assert_eq!(ChargeLedMode::Charging.to_string(), "charging");
+    }
+}
@@ -14,28 +14,62 @@ use std::future::Future;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use tokio::fs::{read_dir, read_to_string, File};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWriteExt, Interest};
+use tokio::net::unix::pipe;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 pub use steamos_manager_proxy as proxy;
 
+mod accessibility;
+mod als;
+mod audio;
+mod audit;
+mod backlight;
+mod battery_policy;
+mod cache;
+mod caller;
+mod developer_mode;
+mod diagnostics;
+mod display;
+mod dock;
 mod ds_inhibit;
 mod error;
+mod haptics;
+mod headless;
+mod input_inhibit;
 mod inputplumber;
 mod job;
+mod leds;
 mod manager;
+mod network_limit;
+mod os_update;
 mod platform;
+mod policy;
+mod polkit;
+mod power_source;
 mod process;
+mod services;
+mod sleep;
 mod sls;
+mod storage;
+mod suspend_inhibit;
 mod systemd;
+mod telemetry;
+mod text_input;
+mod thermal;
 mod udev;
 mod uinput;
+mod usb;
+mod wake;
 
 pub mod cec;
+pub mod color_filters;
 pub mod daemon;
 pub mod gpu;
 pub mod hardware;
+pub mod idle;
+pub mod mock;
 pub mod power;
 pub mod screenreader;
 pub mod session;
@@ -44,7 +78,7 @@ pub mod wifi;
 #[cfg(test)]
 mod testing;
 
-const API_VERSION: u32 = 9;
+const API_VERSION: u32 = 10;
 
 pub trait Service
 where
@@ -58,7 +92,7 @@ where
         async { Ok(()) }
     }
 
-    fn start(mut self, token: CancellationToken) -> impl Future<Output = Result<()>> + Send {
+    fn start(&mut self, token: &CancellationToken) -> impl Future<Output = Result<()>> + Send {
         async move {
             info!("Starting {}", Self::NAME);
             let res = tokio::select! {
@@ -71,7 +105,6 @@ where
                     Self::NAME,
                     res.as_ref().unwrap_err()
                 );
-                token.cancel();
             }
             info!("Shutting down {}", Self::NAME);
             self.shutdown().await.and(res)
@@ -116,7 +149,10 @@ impl<F: Format + Send + Sync + Debug, P: AsRef<Path> + Sized + Send + Sync + Deb
 
 #[cfg(not(test))]
 pub fn path<S: AsRef<str>>(path: S) -> PathBuf {
-    PathBuf::from(path.as_ref())
+    match mock::root() {
+        Some(root) => root.join(path.as_ref().trim_start_matches('/')),
+        None => PathBuf::from(path.as_ref()),
+    }
 }
 
 #[cfg(test)]
@@ -133,6 +169,26 @@ pub(crate) async fn write_synced<P: AsRef<Path>>(path: P, bytes: &[u8]) -> Resul
     Ok(file.sync_data().await?)
 }
 
+pub(crate) async fn wait_on_handle(recv: pipe::Receiver, identifier: String) -> String {
+    loop {
+        let mut buf = [0; 1024];
+        let read = match recv.ready(Interest::READABLE).await {
+            Ok(r) if r.is_read_closed() => break,
+            Ok(r) if r.is_readable() => recv.try_read(&mut buf),
+            Err(e) => Err(e),
+            Ok(e) => {
+                warn!("Handle {identifier} received unexpected event: {e:?}");
+                break;
+            }
+        };
+        if let Err(e) = read {
+            warn!("Handle {identifier} received unexpected error: {e:?}");
+            break;
+        }
+    }
+    identifier
+}
+
 pub(crate) fn read_comm(pid: u32) -> Result<String> {
     let comm = std::fs::read_to_string(path(format!("/proc/{pid}/comm")))?;
     Ok(comm.trim_end().to_string())
@@ -6,39 +6,68 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::future::Future;
+use std::time::Duration;
 use tokio::fs::File;
 use tokio::spawn;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{Sender, UnboundedSender};
 use tokio::sync::oneshot;
-use tracing::{error, info};
+use tracing::{error, info, warn, Instrument};
+use zbus::message::Header;
 use zbus::object_server::SignalEmitter;
 use zbus::zvariant::{self, Fd};
 use zbus::{fdo, interface, proxy, Connection};
 
-use crate::daemon::root::{Command, RootCommand};
-use crate::daemon::DaemonCommand;
-use crate::error::{to_zbus_error, to_zbus_fdo_error};
+use crate::audit::{audit, recent_audit_entries, RECENT_AUDIT_ENTRIES};
+use crate::backlight::set_brightness;
+use crate::daemon::root::{Command, RootCommand, ScheduledUpdate};
+use crate::daemon::{log_level, set_log_level, DaemonCommand};
+use crate::developer_mode::{set_developer_feature_enabled, DeveloperFeature};
+use crate::error::{to_zbus_error, to_zbus_fdo_error, zbus_to_zbus_fdo};
 use crate::gpu::{
     gpu_performance_level_driver, gpu_power_profile_driver, GpuPerformanceLevelDriver,
     GpuPowerProfileDriver,
 };
+use crate::haptics::{set_haptic_feedback, set_haptics_intensity};
 use crate::hardware::{
-    device_config, steam_deck_variant, FactoryResetKind, FanControl, FanControlState,
+    bios_version, device_config, parse_firmware_versions, reset_fan_curve, set_fan_curve,
+    steam_deck_variant, FactoryResetKind, FanControl, FanControlState, FanCurvePoint,
     SteamDeckVariant,
 };
 use crate::job::JobManager;
-use crate::platform::platform_config;
+use crate::leds::{
+    set_charge_led_mode, set_led_brightness, set_led_color, set_led_pattern, ChargeLedMode,
+};
+use crate::network_limit::NetworkLimitCommand;
+use crate::os_update::{parse_update_candidates, parse_update_progress};
+use crate::platform::{
+    parse_reset_estimate, platform_config, ScriptConfig, SysfsAttributeConfig, UpdateChannelConfig,
+    UsbAuthorizationConfig,
+};
+use crate::policy::{call_span, Guarded};
+use crate::polkit::{
+    authorize_privileged_call, check_authorization, FACTORY_RESET_ACTION, FORMAT_DEVICE_ACTION,
+    MANAGE_UNITS_ACTION, SET_DEVELOPER_MODE_ACTION, SET_FAN_CONTROL_STATE_ACTION,
+};
 use crate::power::{
-    set_cpu_boost_state, set_cpu_scaling_governor, set_max_charge_level, set_platform_profile,
-    tdp_limit_manager, CPUBoostState, CPUScalingGovernor, SysfsWritten, TdpLimitManager,
+    get_max_charge_level, set_charge_bypass, set_cpu_boost_state, set_cpu_scaling_governor,
+    set_cpu_scaling_governor_for_policy, set_energy_performance_preference, set_max_charge_level,
+    set_max_cpu_frequency, set_min_cpu_frequency, set_online_cpu_count, set_platform_profile,
+    set_smt_enabled, tdp_limit_manager, CPUBoostState, CPUScalingGovernor,
+    EnergyPerformancePreference, SysfsWritten, TdpLimitManager, TdpLimits,
 };
 use crate::process::{run_script, script_output};
+use crate::services::{run_unit_action, UnitAction};
 use crate::session::root::{clean_temporary_sessions, set_default_session, set_temporary_session};
+use crate::sleep::{set_sleep_settings, SleepMode, SleepSettings};
+use crate::storage::{parse_storage_health, validate_block_device, FormatOptions};
+use crate::usb::{set_usb_authorized_default, set_usb_device_authorized};
+use crate::wake::{set_wake_source_enabled, WakeSource};
 use crate::wifi::{
-    extract_wifi_trace, generate_wifi_dump, set_wifi_backend, set_wifi_debug_mode,
+    extract_wifi_trace, generate_wifi_dump, get_wifi_link, set_wifi_backend, set_wifi_debug_mode,
     set_wifi_power_management_state, WifiBackend, WifiDebugMode, WifiPowerManagement,
 };
 use crate::{path, API_VERSION};
@@ -46,12 +75,29 @@ use crate::{path, API_VERSION};
 #[derive(PartialEq, Debug, Copy, Clone)]
 #[repr(u32)]
 enum PrepareFactoryResetResult {
-    // NOTE: both old PrepareFactoryReset and new PrepareFactoryResetExt use these
-    // result values.
     Unknown = 0,
     RebootRequired = 1,
 }
 
+/// Run the body of a privileged method and record its outcome in the audit log, along with who
+/// called it. Every method that changes something goes through here, so none can be left out.
+/// Records logged meanwhile carry the interface, method and caller, see [`call_span`].
+macro_rules! audited {
+    ($connection:expr, $header:expr, $method:expr, [$($argument:expr),* $(,)?], $body:expr) => {{
+        let arguments: Vec<String> = vec![$($argument.to_string()),*];
+        let span = call_span(
+            <Self as zbus::object_server::Interface>::name().as_str(),
+            $method,
+            Some(&$header),
+        );
+        let result = $body.instrument(span.clone()).await;
+        audit($connection, &$header, $method, arguments, &result)
+            .instrument(span)
+            .await;
+        result
+    }};
+}
+
 pub struct SteamOSManager {
     connection: Connection,
     channel: Sender<Command>,
@@ -64,10 +110,15 @@ pub struct SteamOSManager {
     // True on galileo devices, false otherwise
     should_trace: bool,
     job_manager: JobManager,
+    network_limit: UnboundedSender<NetworkLimitCommand>,
 }
 
 impl SteamOSManager {
-    pub async fn new(connection: Connection, channel: Sender<Command>) -> Result<Self> {
+    pub async fn new(
+        connection: Connection,
+        channel: Sender<Command>,
+        network_limit: UnboundedSender<NetworkLimitCommand>,
+    ) -> Result<Self> {
         Ok(SteamOSManager {
             fan_control: FanControl::new(connection.clone()),
             wifi_debug_mode: WifiDebugMode::Off,
@@ -84,11 +135,163 @@ impl SteamOSManager {
                 .inspect_err(|e| info!("Could not set up GPU power profile management: {e}"))
                 .ok(),
             should_trace: steam_deck_variant().await? == SteamDeckVariant::Galileo,
-            job_manager: JobManager::new(connection.clone()).await?,
+            job_manager: JobManager::new(connection.clone(), Some(channel.clone())).await?,
+            network_limit,
             connection,
             channel,
         })
     }
+
+    async fn schedule_update(&self, update: ScheduledUpdate) -> fdo::Result<()> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        let (config, method) = match update {
+            ScheduledUpdate::Bios => (
+                config
+                    .as_ref()
+                    .and_then(|config| config.update_bios.as_ref()),
+                "ScheduleUpdateBios",
+            ),
+            ScheduledUpdate::Dock => (
+                config
+                    .as_ref()
+                    .and_then(|config| config.update_dock.as_ref()),
+                "ScheduleUpdateDock",
+            ),
+        };
+        if config.is_none() {
+            return Err(fdo::Error::NotSupported(format!(
+                "{method} is not supported on this platform"
+            )));
+        }
+        self.set_update_scheduled(update, true).await
+    }
+
+    async fn set_update_scheduled(
+        &self,
+        update: ScheduledUpdate,
+        scheduled: bool,
+    ) -> fdo::Result<()> {
+        self.channel
+            .send(DaemonCommand::ContextCommand(RootCommand::ScheduleUpdate(
+                update, scheduled,
+            )))
+            .await
+            .inspect_err(|message| error!("Error sending ScheduleUpdate command: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn sysfs_attribute(name: &str) -> fdo::Result<SysfsAttributeConfig> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        config
+            .as_ref()
+            .and_then(|config| config.sysfs_access.get(name))
+            .filter(|attribute| attribute.is_valid())
+            .cloned()
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown sysfs attribute {name}")))
+    }
+
+    async fn usb_authorization_config() -> fdo::Result<UsbAuthorizationConfig> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        config
+            .as_ref()
+            .and_then(|config| config.usb_authorization.clone())
+            .ok_or_else(|| {
+                fdo::Error::NotSupported(String::from(
+                    "USB authorization is not supported on this platform",
+                ))
+            })
+    }
+
+    async fn update_channel_config() -> fdo::Result<UpdateChannelConfig> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        config
+            .as_ref()
+            .and_then(|config| config.update_channel.clone())
+            .ok_or_else(|| {
+                fdo::Error::NotSupported(String::from(
+                    "Update channel selection is not supported on this platform",
+                ))
+            })
+    }
+
+    async fn run_format_device(
+        &mut self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        options: &FormatOptions,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        let Some(config) = config.as_ref().and_then(|config| config.storage.as_ref()) else {
+            return Err(fdo::Error::NotSupported(String::from(
+                "FormatDevice is not supported on this platform",
+            )));
+        };
+        let config = &config.format_device;
+        let args = config
+            .args(device, label, validate, options)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+
+        self.job_manager
+            .run_process(
+                &config.script,
+                &args,
+                format!("formatting {device}").as_str(),
+                config.timeout.map(Duration::from_secs),
+            )
+            .await
+    }
+}
+
+async fn factory_reset_script(kind: u32, method: &str) -> fdo::Result<ScriptConfig> {
+    let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+    let Some(config) = config
+        .as_ref()
+        .and_then(|config| config.factory_reset.as_ref())
+    else {
+        return Err(fdo::Error::NotSupported(format!(
+            "{method} is not supported on this platform"
+        )));
+    };
+    let kind =
+        FactoryResetKind::try_from(kind).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+    Ok(config.script(kind).clone())
+}
+
+/// Wait for a new charge limit to reach sysfs, then remember it and let RootManager's clients
+/// know. A limit replaced by a newer one before it was written counts as a failure.
+async fn commit_max_charge_level(
+    connection: &Connection,
+    channel: &Sender<Command>,
+    level: i32,
+    written: oneshot::Receiver<SysfsWritten>,
+) -> fdo::Result<()> {
+    match written.await.map_err(to_zbus_fdo_error)? {
+        SysfsWritten::Written(res) => res.map_err(to_zbus_fdo_error)?,
+        SysfsWritten::Superseded => {
+            return Err(fdo::Error::Failed(String::from(
+                "Charge limit was superseded by a newer one",
+            )))
+        }
+    }
+    channel
+        .send(DaemonCommand::ContextCommand(
+            RootCommand::SetMaxChargeLevel(level),
+        ))
+        .await
+        .inspect_err(|message| error!("Error sending SetMaxChargeLevel command: {message}"))
+        .map_err(to_zbus_fdo_error)?;
+    if let Ok(interface) = connection
+        .object_server()
+        .interface::<_, Guarded<SteamOSManager>>("/com/steampowered/SteamOSManager1")
+        .await
+    {
+        interface
+            .signal_emitter()
+            .max_charge_level_changed()
+            .await?;
+    }
+    Ok(())
 }
 
 #[proxy(
@@ -98,49 +301,129 @@ impl SteamOSManager {
 )]
 pub(crate) trait RootManager {
     fn set_tdp_limit(&self, limit: u32) -> zbus::Result<()>;
+    fn set_tdp_limits(&self, spl: u32, sppt: u32, fppt: u32) -> zbus::Result<()>;
     fn set_temporary_session(&self, session: &str) -> zbus::Result<()>;
     fn set_default_session(&self, session: &str) -> zbus::Result<()>;
 }
 
 #[interface(name = "com.steampowered.SteamOSManager1.RootManager")]
 impl SteamOSManager {
-    async fn prepare_factory_reset(&self, kind: u32) -> fdo::Result<u32> {
-        // Run steamos-reset with arguments based on flags passed and return 1 on success
+    async fn estimate_factory_reset(&self, kind: u32) -> fdo::Result<(Vec<String>, u64)> {
         let config = platform_config().await.map_err(to_zbus_fdo_error)?;
         let Some(config) = config
             .as_ref()
             .and_then(|config| config.factory_reset.as_ref())
         else {
             return Err(fdo::Error::NotSupported(String::from(
-                "PrepareFactoryReset is not supported on this platform",
+                "EstimateFactoryReset is not supported on this platform",
+            )));
+        };
+        let Some(estimate) = config.estimate.as_ref() else {
+            return Err(fdo::Error::NotSupported(String::from(
+                "EstimateFactoryReset is not supported on this platform",
             )));
         };
-        let res = match FactoryResetKind::try_from(kind) {
-            Ok(FactoryResetKind::User) => {
-                run_script(&config.user.script, &config.user.script_args).await
+        let kind =
+            FactoryResetKind::try_from(kind).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let args: Vec<&String> = estimate
+            .script_args
+            .iter()
+            .chain(config.script(kind).script_args.iter())
+            .collect();
+        let output = script_output(&estimate.script, &args)
+            .await
+            .inspect_err(|message| error!("Error estimating factory reset: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let estimate = parse_reset_estimate(output.as_str())
+            .inspect_err(|message| error!("Error parsing factory reset estimate: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok((estimate.partitions, estimate.duration))
+    }
+
+    async fn prepare_factory_reset(
+        &self,
+        kind: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<u32> {
+        // Run steamos-reset with arguments based on flags passed and return 1 on success
+        audited!(
+            &self.connection,
+            header,
+            "PrepareFactoryReset",
+            [kind],
+            async {
+                authorize_privileged_call(&self.connection, &header, FACTORY_RESET_ACTION).await?;
+                let config = factory_reset_script(kind, "PrepareFactoryReset").await?;
+                Ok(
+                    match run_script(&config.script, &config.script_args).await {
+                        Ok(()) => PrepareFactoryResetResult::RebootRequired as u32,
+                        Err(_) => PrepareFactoryResetResult::Unknown as u32,
+                    },
+                )
             }
-            Ok(FactoryResetKind::OS) => run_script(&config.os.script, &config.os.script_args).await,
-            Ok(FactoryResetKind::All) => {
-                run_script(&config.all.script, &config.all.script_args).await
+        )
+    }
+
+    async fn start_factory_reset(
+        &mut self,
+        kind: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(
+            &self.connection,
+            header,
+            "StartFactoryReset",
+            [kind],
+            async {
+                authorize_privileged_call(&self.connection, &header, FACTORY_RESET_ACTION).await?;
+                let config = factory_reset_script(kind, "StartFactoryReset").await?;
+                self.job_manager
+                    .run_process(
+                        &config.script,
+                        &config.script_args,
+                        "preparing factory reset",
+                        config.timeout.map(Duration::from_secs),
+                    )
+                    .await
             }
-            Err(_) => Err(anyhow!(
-                "Unable to generate command arguments for steamos-reset-tool script"
-            )),
-        };
-        Ok(match res {
-            Ok(()) => PrepareFactoryResetResult::RebootRequired as u32,
-            Err(_) => PrepareFactoryResetResult::Unknown as u32,
-        })
+        )
     }
 
-    async fn set_wifi_power_management_state(&self, state: u32) -> fdo::Result<()> {
-        let state = match WifiPowerManagement::try_from(state) {
-            Ok(state) => state,
-            Err(err) => return Err(to_zbus_fdo_error(err)),
-        };
-        set_wifi_power_management_state(state)
+    async fn set_wifi_power_management_state(
+        &self,
+        state: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetWifiPowerManagementState",
+            [state],
+            async {
+                let state = match WifiPowerManagement::try_from(state) {
+                    Ok(state) => state,
+                    Err(err) => return Err(to_zbus_fdo_error(err)),
+                };
+                set_wifi_power_management_state(state)
+                    .await
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn get_wifi_link(&self) -> fdo::Result<(String, String, u32, i32, f64, f64)> {
+        let link = get_wifi_link()
             .await
-            .map_err(to_zbus_fdo_error)
+            .inspect_err(|message| error!("Error reading Wi-Fi link information: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok((
+            link.ssid,
+            link.bssid,
+            link.frequency,
+            link.signal,
+            link.tx_bitrate,
+            link.rx_bitrate,
+        ))
     }
 
     #[zbus(property(emits_changed_signal = "false"))]
@@ -153,16 +436,32 @@ impl SteamOSManager {
     }
 
     #[zbus(property)]
-    async fn set_fan_control_state(&self, state: u32) -> zbus::Result<()> {
-        let state = match FanControlState::try_from(state) {
-            Ok(state) => state,
-            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string()).into()),
-        };
-        // Run what steamos-polkit-helpers/jupiter-fan-control does
-        self.fan_control
-            .set_state(state)
-            .await
-            .map_err(to_zbus_error)
+    async fn set_fan_control_state(
+        &self,
+        state: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        let header =
+            header.ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+        audited!(
+            &self.connection,
+            header,
+            "SetFanControlState",
+            [state],
+            async {
+                authorize_privileged_call(&self.connection, &header, SET_FAN_CONTROL_STATE_ACTION)
+                    .await?;
+                let state = match FanControlState::try_from(state) {
+                    Ok(state) => state,
+                    Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string()).into()),
+                };
+                // Run what steamos-polkit-helpers/jupiter-fan-control does
+                self.fan_control
+                    .set_state(state)
+                    .await
+                    .map_err(to_zbus_error)
+            }
+        )
     }
 
     #[zbus(property(emits_changed_signal = "false"))]
@@ -213,255 +512,1246 @@ impl SteamOSManager {
         }
     }
 
-    async fn update_bios(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        // Update the bios as needed
-        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
-        let Some(config) = config
-            .as_ref()
-            .and_then(|config| config.update_bios.as_ref())
-        else {
-            return Err(fdo::Error::NotSupported(String::from(
-                "UpdateBios is not supported on this platform",
-            )));
-        };
-        self.job_manager
-            .run_process(&config.script, &config.script_args, "updating BIOS")
-            .await
+    async fn update_bios(
+        &mut self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(&self.connection, header, "UpdateBios", [], async {
+            // Update the bios as needed
+            let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+            let Some(config) = config
+                .as_ref()
+                .and_then(|config| config.update_bios.as_ref())
+            else {
+                return Err(fdo::Error::NotSupported(String::from(
+                    "UpdateBios is not supported on this platform",
+                )));
+            };
+            self.job_manager
+                .run_process(
+                    &config.script,
+                    &config.script_args,
+                    "updating BIOS",
+                    config.timeout.map(Duration::from_secs),
+                )
+                .await
+        })
     }
 
-    async fn update_dock(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        // Update the dock firmware as needed
-        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
-        let Some(config) = config
-            .as_ref()
-            .and_then(|config| config.update_dock.as_ref())
-        else {
-            return Err(fdo::Error::NotSupported(String::from(
-                "UpdateDock is not supported on this platform",
-            )));
-        };
-        self.job_manager
-            .run_process(&config.script, &config.script_args, "updating dock")
-            .await
+    async fn update_dock(
+        &mut self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(&self.connection, header, "UpdateDock", [], async {
+            // Update the dock firmware as needed
+            let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+            let Some(config) = config
+                .as_ref()
+                .and_then(|config| config.update_dock.as_ref())
+            else {
+                return Err(fdo::Error::NotSupported(String::from(
+                    "UpdateDock is not supported on this platform",
+                )));
+            };
+            self.job_manager
+                .run_process(
+                    &config.script,
+                    &config.script_args,
+                    "updating dock",
+                    config.timeout.map(Duration::from_secs),
+                )
+                .await
+        })
     }
 
-    async fn trim_devices(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        // Run steamos-trim-devices script
+    async fn update_controller(
+        &mut self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(&self.connection, header, "UpdateController", [], async {
+            // Update the controller firmware as needed
+            let config = device_config().await.map_err(to_zbus_fdo_error)?;
+            let Some(config) = config
+                .as_ref()
+                .and_then(|config| config.update_controller.as_ref())
+            else {
+                return Err(fdo::Error::NotSupported(String::from(
+                    "UpdateController is not supported on this device",
+                )));
+            };
+            self.job_manager
+                .run_process(
+                    &config.script,
+                    &config.script_args,
+                    "updating controller",
+                    config.timeout.map(Duration::from_secs),
+                )
+                .await
+        })
+    }
+
+    async fn check_for_os_updates(&self) -> fdo::Result<Vec<(String, String, String, u64)>> {
         let config = platform_config().await.map_err(to_zbus_fdo_error)?;
-        let Some(config) = config.as_ref().and_then(|config| config.storage.as_ref()) else {
+        let Some(config) = config.as_ref().and_then(|config| config.os_update.as_ref()) else {
             return Err(fdo::Error::NotSupported(String::from(
-                "TrimDevices is not supported on this platform",
+                "CheckForOsUpdates is not supported on this platform",
             )));
         };
-        self.job_manager
-            .run_process(
-                &config.trim_devices.script,
-                config.trim_devices.script_args.as_ref(),
-                "trimming devices",
-            )
+        let output = script_output(&config.check.script, &config.check.script_args)
             .await
+            .inspect_err(|message| error!("Error checking for OS updates: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let candidates = parse_update_candidates(output.as_str())
+            .inspect_err(|message| error!("Error parsing OS updates: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(candidates
+            .into_iter()
+            .map(|update| {
+                (
+                    update.kind,
+                    update.version,
+                    update.buildid,
+                    update.estimated_size,
+                )
+            })
+            .collect())
     }
 
-    async fn format_device(
+    async fn start_os_update(
         &mut self,
-        device: &str,
-        label: &str,
-        validate: bool,
+        #[zbus(header)] header: Header<'_>,
     ) -> fdo::Result<zvariant::OwnedObjectPath> {
-        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
-        let Some(config) = config.as_ref().and_then(|config| config.storage.as_ref()) else {
-            return Err(fdo::Error::NotSupported(String::from(
-                "FormatDevice is not supported on this platform",
-            )));
-        };
-        let config = &config.format_device;
-        let mut args: Vec<&OsStr> = config.script_args.iter().map(AsRef::as_ref).collect();
-
-        args.extend_from_slice(&[OsStr::new(config.label_flag.as_str()), OsStr::new(label)]);
-
-        match (validate, &config.validate_flag, &config.no_validate_flag) {
-            (true, Some(validate_flag), _) => args.push(OsStr::new(validate_flag)),
-            (false, _, Some(no_validate_flag)) => args.push(OsStr::new(no_validate_flag)),
-            _ => (),
-        }
-
-        if let Some(device_flag) = &config.device_flag {
-            args.push(OsStr::new(device_flag));
-        }
-        args.push(OsStr::new(device));
+        audited!(&self.connection, header, "StartOsUpdate", [], async {
+            let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+            let Some(config) = config.as_ref().and_then(|config| config.os_update.as_ref()) else {
+                return Err(fdo::Error::NotSupported(String::from(
+                    "StartOsUpdate is not supported on this platform",
+                )));
+            };
+            self.job_manager
+                .run_process_with_progress(
+                    &config.update.script,
+                    &config.update.script_args,
+                    "updating OS",
+                    config.update.timeout.map(Duration::from_secs),
+                    Some(parse_update_progress),
+                )
+                .await
+        })
+    }
 
-        self.job_manager
-            .run_process(
-                &config.script,
-                &args,
-                format!("formatting {device}").as_str(),
-            )
-            .await
+    async fn list_sysfs_attributes(&self) -> fdo::Result<HashMap<String, bool>> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        Ok(config
+            .as_ref()
+            .map(|config| {
+                config
+                    .sysfs_access
+                    .iter()
+                    .filter(|(_, attribute)| attribute.is_valid())
+                    .map(|(name, attribute)| (name.clone(), attribute.writable))
+                    .collect()
+            })
+            .unwrap_or_default())
     }
 
-    async fn set_gpu_power_profile(&self, value: &str) -> fdo::Result<()> {
-        let Some(ref driver) = self.gpu_power_profile else {
-            return Err(fdo::Error::Failed(String::from(
-                "GPU power profile settings not configured",
-            )));
-        };
-        let profile = driver
-            .power_profile_from_str(value)
-            .map_err(to_zbus_fdo_error)?;
-        driver
-            .set_power_profile(profile)
+    async fn read_sysfs_attribute(&self, name: &str) -> fdo::Result<String> {
+        let attribute = SteamOSManager::sysfs_attribute(name).await?;
+        attribute
+            .read()
             .await
-            .inspect_err(|message| error!("Error setting GPU power profile: {message}"))
+            .inspect_err(|message| error!("Error reading sysfs attribute {name}: {message}"))
             .map_err(to_zbus_fdo_error)
     }
 
-    async fn set_cpu_scaling_governor(&self, governor: String) -> fdo::Result<()> {
-        let g = CPUScalingGovernor::try_from(governor.as_str()).map_err(to_zbus_fdo_error)?;
-        set_cpu_scaling_governor(g)
-            .await
-            .inspect_err(|message| error!("Error setting CPU scaling governor: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn write_sysfs_attribute(
+        &self,
+        name: &str,
+        value: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "WriteSysfsAttribute",
+            [name, value],
+            async {
+                let attribute = SteamOSManager::sysfs_attribute(name).await?;
+                if !attribute.writable {
+                    return Err(fdo::Error::AccessDenied(format!(
+                        "Sysfs attribute {name} is not writable"
+                    )));
+                }
+                if !attribute.values.is_empty()
+                    && !attribute.values.iter().any(|allowed| allowed == value)
+                {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "Value {value} is not allowed for sysfs attribute {name}"
+                    )));
+                }
+                attribute
+                    .write(value)
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error writing sysfs attribute {name}: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
     }
 
-    async fn set_cpu_boost_state(&self, state: u32) -> fdo::Result<()> {
-        let state = match CPUBoostState::try_from(state) {
-            Ok(state) => state,
-            Err(err) => return Err(to_zbus_fdo_error(err)),
-        };
-        set_cpu_boost_state(state)
-            .await
-            .inspect_err(|message| error!("Error setting CPU boost state: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn set_usb_authorized_default(
+        &self,
+        authorized: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetUsbAuthorizedDefault",
+            [authorized],
+            async {
+                SteamOSManager::usb_authorization_config().await?;
+                set_usb_authorized_default(authorized)
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error setting USB authorized default: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
     }
 
-    async fn set_gpu_performance_level(&self, level: &str) -> fdo::Result<()> {
-        let Some(ref driver) = self.gpu_performance_level else {
-            return Err(fdo::Error::Failed(String::from(
-                "GPU performance settings not configured",
-            )));
-        };
-        let level = match driver.performance_level_from_str(level) {
-            Ok(level) => level,
-            Err(e) => return Err(to_zbus_fdo_error(e)),
-        };
-        driver
-            .set_performance_level(level)
-            .await
-            .inspect_err(|message| error!("Error setting GPU performance level: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn set_usb_device_authorized(
+        &self,
+        device: &str,
+        authorized: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetUsbDeviceAuthorized",
+            [device, authorized],
+            async {
+                let config = SteamOSManager::usb_authorization_config().await?;
+                set_usb_device_authorized(device, authorized, &config)
+                    .await
+                    .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))
+            }
+        )
     }
 
-    async fn set_manual_gpu_clock(&self, clocks: u32) -> fdo::Result<()> {
-        let Some(ref driver) = self.gpu_performance_level else {
-            return Err(fdo::Error::Failed(String::from(
-                "GPU performance settings not configured",
-            )));
-        };
-        driver
-            .set_clocks(clocks)
-            .await
-            .inspect_err(|message| error!("Error setting manual GPU clock: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn set_developer_mode_feature(
+        &self,
+        feature: &str,
+        enabled: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetDeveloperModeFeature",
+            [feature, enabled],
+            async {
+                authorize_privileged_call(&self.connection, &header, SET_DEVELOPER_MODE_ACTION)
+                    .await?;
+                let feature = DeveloperFeature::try_from(feature)
+                    .map_err(|_| fdo::Error::InvalidArgs(format!("Unknown feature {feature}")))?;
+                set_developer_feature_enabled(&self.connection, feature, enabled)
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error setting developer mode {feature}: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
     }
 
-    async fn set_tdp_limit(&self, limit: u32) -> fdo::Result<()> {
-        let Some(ref manager) = self.tdp_limit_manager else {
-            return Err(fdo::Error::Failed(String::from(
-                "TDP limiting not configured",
-            )));
-        };
-        manager
-            .set_tdp_limit(limit)
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn manage_unit(
+        &self,
+        unit: &str,
+        action: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "ManageUnit",
+            [unit, action],
+            async {
+                authorize_privileged_call(&self.connection, &header, MANAGE_UNITS_ACTION).await?;
+                let action = UnitAction::try_from(action).map_err(|_| {
+                    fdo::Error::InvalidArgs(format!("Unknown unit action {action}"))
+                })?;
+                run_unit_action(&self.connection, unit, action)
+                    .await
+                    .inspect_err(|message| error!("Error running {action} on {unit}: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
     }
 
-    #[zbus(property)]
-    async fn wifi_debug_mode_state(&self) -> u32 {
-        // Get the wifi debug mode
-        self.wifi_debug_mode as u32
+    async fn get_update_channel(&self) -> fdo::Result<String> {
+        let config = SteamOSManager::update_channel_config().await?;
+        let output = script_output(&config.get.script, &config.get.script_args)
+            .await
+            .inspect_err(|message| error!("Error getting update channel: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let channel = output.trim();
+        if !config.is_allowed(channel) {
+            error!("Update channel script reported unknown channel {channel}");
+            return Err(fdo::Error::Failed(format!(
+                "Update channel {channel} is not one of {}",
+                config.branches.join(", ")
+            )));
+        }
+        Ok(channel.to_string())
     }
 
-    async fn set_wifi_debug_mode(
-        &mut self,
-        mode: u32,
-        options: HashMap<&str, zvariant::Value<'_>>,
-        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    async fn set_update_channel(
+        &self,
+        channel: &str,
+        #[zbus(header)] header: Header<'_>,
     ) -> fdo::Result<()> {
-        // Set the wifi debug mode to mode, using an int for flexibility going forward but only
-        // doing things on 0 or 1 for now
-        let wanted_mode = match WifiDebugMode::try_from(mode) {
-            Ok(mode) => mode,
-            Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string())),
-        };
-
-        if self.wifi_debug_mode == wanted_mode {
-            info!("Not changing wifi debug mode since it's already set to {wanted_mode}");
-            return Ok(());
-        }
+        audited!(
+            &self.connection,
+            header,
+            "SetUpdateChannel",
+            [channel],
+            async {
+                let config = SteamOSManager::update_channel_config().await?;
+                if !config.is_allowed(channel) {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "Update channel {channel} is not one of {}",
+                        config.branches.join(", ")
+                    )));
+                }
+                let mut args: Vec<&OsStr> =
+                    config.set.script_args.iter().map(AsRef::as_ref).collect();
+                args.push(OsStr::new(channel));
+                run_script(&config.set.script, &args)
+                    .await
+                    .inspect_err(|message| error!("Error setting update channel: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
 
-        let buffer_size = match options
-            .get("buffer_size")
-            .map(zbus::zvariant::Value::downcast_ref)
-        {
-            Some(Ok(v)) => v,
-            None => 20000,
-            Some(Err(e)) => return Err(fdo::Error::InvalidArgs(e.to_string())),
+    async fn get_firmware_versions(
+        &self,
+    ) -> fdo::Result<(String, String, HashMap<String, String>)> {
+        let bios = bios_version()
+            .await
+            .inspect_err(|message| warn!("Error reading BIOS version: {message}"))
+            .unwrap_or_default();
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        let Some(config) = config.as_ref().and_then(|config| config.firmware.as_ref()) else {
+            return Ok((bios, String::new(), HashMap::new()));
         };
-        match set_wifi_debug_mode(
-            wanted_mode,
-            buffer_size,
-            self.should_trace,
-            self.connection.clone(),
-        )
-        .await
-        {
-            Ok(()) => {
-                self.wifi_debug_mode = wanted_mode;
-                self.wifi_debug_mode_state_changed(&ctx).await?;
-                Ok(())
+
+        let mut dock = String::new();
+        if let Some(ref config) = config.dock {
+            match script_output(&config.script, &config.script_args).await {
+                Ok(output) => dock = output.trim().to_string(),
+                Err(e) => warn!("Error querying dock firmware version: {e}"),
             }
-            Err(e) => {
-                error!("Error setting wifi debug mode: {e}");
-                Err(to_zbus_fdo_error(e))
+        }
+        let mut controllers = HashMap::new();
+        if let Some(ref config) = config.controller {
+            match script_output(&config.script, &config.script_args).await {
+                Ok(output) => controllers = parse_firmware_versions(output.as_str()),
+                Err(e) => warn!("Error querying controller firmware versions: {e}"),
             }
         }
+        Ok((bios, dock, controllers))
     }
 
-    async fn set_wifi_backend(&mut self, backend: u32) -> fdo::Result<()> {
-        if self.wifi_debug_mode == WifiDebugMode::Tracing {
-            return Err(fdo::Error::Failed(String::from(
-                "operation not supported when wifi_debug_mode=tracing",
-            )));
-        }
-        let backend = match WifiBackend::try_from(backend) {
-            Ok(backend) => backend,
-            Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string())),
-        };
-        set_wifi_backend(backend)
-            .await
-            .inspect_err(|message| error!("Error setting wifi backend: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn schedule_update_bios(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        audited!(&self.connection, header, "ScheduleUpdateBios", [], async {
+            self.schedule_update(ScheduledUpdate::Bios).await
+        })
     }
 
-    async fn capture_debug_trace_output(&self) -> fdo::Result<String> {
-        Ok(extract_wifi_trace()
-            .await
-            .inspect_err(|message| error!("Error capturing trace output: {message}"))
-            .map_err(to_zbus_fdo_error)?
-            .into_os_string()
-            .to_string_lossy()
-            .into())
+    async fn schedule_update_dock(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        audited!(&self.connection, header, "ScheduleUpdateDock", [], async {
+            self.schedule_update(ScheduledUpdate::Dock).await
+        })
     }
 
-    async fn generate_debug_dump(&self) -> fdo::Result<String> {
-        Ok(generate_wifi_dump()
+    async fn cancel_scheduled_update_bios(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "CancelScheduledUpdateBios",
+            [],
+            async {
+                self.set_update_scheduled(ScheduledUpdate::Bios, false)
+                    .await
+            }
+        )
+    }
+
+    async fn cancel_scheduled_update_dock(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "CancelScheduledUpdateDock",
+            [],
+            async {
+                self.set_update_scheduled(ScheduledUpdate::Dock, false)
+                    .await
+            }
+        )
+    }
+
+    async fn get_scheduled_updates(&self) -> fdo::Result<(bool, bool)> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(
+                RootCommand::GetScheduledUpdates(tx),
+            ))
+            .await
+            .inspect_err(|message| error!("Error sending GetScheduledUpdates command: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let scheduled = rx.await.map_err(to_zbus_fdo_error)?;
+        Ok((scheduled.bios, scheduled.dock))
+    }
+
+    async fn trim_devices(
+        &mut self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(&self.connection, header, "TrimDevices", [], async {
+            // Run steamos-trim-devices script
+            let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+            let Some(config) = config.as_ref().and_then(|config| config.storage.as_ref()) else {
+                return Err(fdo::Error::NotSupported(String::from(
+                    "TrimDevices is not supported on this platform",
+                )));
+            };
+            self.job_manager
+                .run_process(
+                    &config.trim_devices.script,
+                    config.trim_devices.script_args.as_ref(),
+                    "trimming devices",
+                    config.trim_devices.timeout.map(Duration::from_secs),
+                )
+                .await
+        })
+    }
+
+    async fn format_device(
+        &mut self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(
+            &self.connection,
+            header,
+            "FormatDevice",
+            [device, label, validate],
+            async {
+                authorize_privileged_call(&self.connection, &header, FORMAT_DEVICE_ACTION).await?;
+                self.run_format_device(device, label, validate, &FormatOptions::default())
+                    .await
+            }
+        )
+    }
+
+    async fn format_device2(
+        &mut self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        options: HashMap<String, zvariant::OwnedValue>,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        audited!(
+            &self.connection,
+            header,
+            "FormatDevice2",
+            [device, label, validate, format!("{options:?}")],
+            async {
+                authorize_privileged_call(&self.connection, &header, FORMAT_DEVICE_ACTION).await?;
+                let options = FormatOptions::try_from(&options)
+                    .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+                self.run_format_device(device, label, validate, &options)
+                    .await
+            }
+        )
+    }
+
+    async fn get_storage_health(&self, device: &str) -> fdo::Result<(u32, f64, u32, bool)> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        let Some(config) = config
+            .as_ref()
+            .and_then(|config| config.storage.as_ref())
+            .and_then(|config| config.health.as_ref())
+        else {
+            return Err(fdo::Error::NotSupported(String::from(
+                "GetStorageHealth is not supported on this platform",
+            )));
+        };
+        validate_block_device(device)
+            .await
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+
+        let mut args: Vec<&OsStr> = config.script_args.iter().map(AsRef::as_ref).collect();
+        args.push(OsStr::new(device));
+        let output = script_output(&config.script, &args)
             .await
-            .inspect_err(|message| error!("Error capturing dump output: {message}"))
-            .map_err(to_zbus_fdo_error)?
-            .into_os_string()
-            .to_string_lossy()
-            .into())
+            .inspect_err(|message| error!("Error reading health of {device}: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let health = parse_storage_health(output.as_str())
+            .inspect_err(|message| error!("Error parsing health of {device}: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok((
+            health.wear_level,
+            health.temperature,
+            health.available_spare,
+            health.passed,
+        ))
+    }
+
+    async fn set_gpu_power_profile(
+        &self,
+        value: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetGpuPowerProfile",
+            [value],
+            async {
+                let Some(ref driver) = self.gpu_power_profile else {
+                    return Err(fdo::Error::Failed(String::from(
+                        "GPU power profile settings not configured",
+                    )));
+                };
+                let profile = driver
+                    .power_profile_from_str(value)
+                    .map_err(to_zbus_fdo_error)?;
+                driver
+                    .set_power_profile(profile)
+                    .await
+                    .inspect_err(|message| error!("Error setting GPU power profile: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_cpu_scaling_governor(
+        &self,
+        governor: String,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetCpuScalingGovernor",
+            [governor],
+            async {
+                let g =
+                    CPUScalingGovernor::try_from(governor.as_str()).map_err(to_zbus_fdo_error)?;
+                set_cpu_scaling_governor(g)
+                    .await
+                    .inspect_err(|message| error!("Error setting CPU scaling governor: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_energy_performance_preference(
+        &self,
+        preference: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetEnergyPerformancePreference",
+            [preference],
+            async {
+                let preference = EnergyPerformancePreference::try_from(preference)
+                    .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+                set_energy_performance_preference(preference)
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error setting energy performance preference: {message}");
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_cpu_scaling_governor_for_policy(
+        &self,
+        policy: u32,
+        governor: String,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetCpuScalingGovernorForPolicy",
+            [policy, governor],
+            async {
+                let g =
+                    CPUScalingGovernor::try_from(governor.as_str()).map_err(to_zbus_fdo_error)?;
+                set_cpu_scaling_governor_for_policy(policy, g)
+                    .await
+                    .inspect_err(|message| error!("Error setting CPU scaling governor: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_min_cpu_frequency(
+        &self,
+        frequency: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetMinCpuFrequency",
+            [frequency],
+            async {
+                set_min_cpu_frequency(frequency)
+                    .await
+                    .inspect_err(|message| error!("Error setting minimum CPU frequency: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_max_cpu_frequency(
+        &self,
+        frequency: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetMaxCpuFrequency",
+            [frequency],
+            async {
+                set_max_cpu_frequency(frequency)
+                    .await
+                    .inspect_err(|message| error!("Error setting maximum CPU frequency: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_fan_curve(
+        &self,
+        curve: Vec<(u32, u32)>,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetFanCurve",
+            [format!("{curve:?}")],
+            async {
+                let curve: Vec<FanCurvePoint> = curve
+                    .into_iter()
+                    .map(|(temperature, pwm)| FanCurvePoint { temperature, pwm })
+                    .collect();
+                set_fan_curve(&curve)
+                    .await
+                    .inspect_err(|message| error!("Error setting fan curve: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(RootCommand::SetFanCurve(
+                        Some(curve),
+                    )))
+                    .await
+                    .inspect_err(|message| error!("Error sending SetFanCurve command: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn reset_fan_curve(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        audited!(&self.connection, header, "ResetFanCurve", [], async {
+            reset_fan_curve()
+                .await
+                .inspect_err(|message| error!("Error resetting fan curve: {message}"))
+                .map_err(to_zbus_fdo_error)?;
+            self.channel
+                .send(DaemonCommand::ContextCommand(RootCommand::SetFanCurve(
+                    None,
+                )))
+                .await
+                .inspect_err(|message| error!("Error sending SetFanCurve command: {message}"))
+                .map_err(to_zbus_fdo_error)
+        })
+    }
+
+    async fn set_haptics_intensity(
+        &self,
+        intensity: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetHapticsIntensity",
+            [intensity],
+            async {
+                set_haptics_intensity(intensity)
+                    .await
+                    .inspect_err(|message| error!("Error setting haptics intensity: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(
+                        RootCommand::SetHapticsIntensity(intensity),
+                    ))
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error sending SetHapticsIntensity command: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_haptic_feedback(
+        &self,
+        enabled: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetHapticFeedback",
+            [enabled],
+            async {
+                set_haptic_feedback(enabled)
+                    .await
+                    .inspect_err(|message| error!("Error setting haptic feedback: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(
+                        RootCommand::SetHapticFeedback(enabled),
+                    ))
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error sending SetHapticFeedback command: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_charge_bypass(
+        &self,
+        enabled: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetChargeBypass",
+            [enabled],
+            async {
+                set_charge_bypass(enabled)
+                    .await
+                    .inspect_err(|message| error!("Error setting charge bypass: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_wake_source_enabled(
+        &self,
+        source: &str,
+        enabled: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetWakeSourceEnabled",
+            [source, enabled],
+            async {
+                let source = WakeSource::try_from(source).map_err(|_| {
+                    fdo::Error::InvalidArgs(format!("Unknown wake source {source}"))
+                })?;
+                set_wake_source_enabled(source, enabled)
+                    .await
+                    .inspect_err(|message| error!("Error setting wake source {source}: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(
+                        RootCommand::SetWakeSourceEnabled(source, enabled),
+                    ))
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error sending SetWakeSourceEnabled command: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn restore_wake_sources(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        audited!(&self.connection, header, "RestoreWakeSources", [], async {
+            self.channel
+                .send(DaemonCommand::ContextCommand(
+                    RootCommand::RestoreWakeSources,
+                ))
+                .await
+                .inspect_err(|message| {
+                    error!("Error sending RestoreWakeSources command: {message}")
+                })
+                .map_err(to_zbus_fdo_error)
+        })
+    }
+
+    async fn set_sleep_config(
+        &self,
+        mode: &str,
+        hibernation: bool,
+        suspend_then_hibernate: bool,
+        hibernate_delay: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetSleepConfig",
+            [mode, hibernation, suspend_then_hibernate, hibernate_delay],
+            async {
+                let mode = match mode {
+                    "" => None,
+                    mode => Some(SleepMode::try_from(mode).map_err(|_| {
+                        fdo::Error::InvalidArgs(format!("Unknown sleep mode {mode}"))
+                    })?),
+                };
+                let settings = SleepSettings {
+                    mode,
+                    hibernation,
+                    suspend_then_hibernate,
+                    hibernate_delay,
+                };
+                set_sleep_settings(&settings)
+                    .await
+                    .inspect_err(|message| error!("Error setting sleep config: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_brightness(
+        &self,
+        device: &str,
+        brightness: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetBrightness",
+            [device, brightness],
+            async {
+                set_brightness(device, brightness)
+                    .await
+                    .inspect_err(|message| error!("Error setting backlight brightness: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_led_brightness(
+        &self,
+        brightness: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetLedBrightness",
+            [brightness],
+            async {
+                set_led_brightness(brightness)
+                    .await
+                    .inspect_err(|message| error!("Error setting LED brightness: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_led_color(
+        &self,
+        red: u32,
+        green: u32,
+        blue: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetLedColor",
+            [red, green, blue],
+            async {
+                set_led_color(red, green, blue)
+                    .await
+                    .inspect_err(|message| error!("Error setting LED color: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_led_pattern(
+        &self,
+        pattern: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetLedPattern",
+            [pattern],
+            async {
+                set_led_pattern(pattern)
+                    .await
+                    .inspect_err(|message| error!("Error setting LED pattern: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_charge_led_mode(
+        &self,
+        mode: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetChargeLedMode",
+            [mode],
+            async {
+                let mode = ChargeLedMode::try_from(mode)
+                    .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+                set_charge_led_mode(mode)
+                    .await
+                    .inspect_err(|message| error!("Error setting charge LED mode: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_cpu_boost_state(
+        &self,
+        state: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetCpuBoostState",
+            [state],
+            async {
+                let state = match CPUBoostState::try_from(state) {
+                    Ok(state) => state,
+                    Err(err) => return Err(to_zbus_fdo_error(err)),
+                };
+                set_cpu_boost_state(state)
+                    .await
+                    .inspect_err(|message| error!("Error setting CPU boost state: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_online_cpu_count(
+        &self,
+        count: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetOnlineCpuCount",
+            [count],
+            async {
+                set_online_cpu_count(count)
+                    .await
+                    .inspect_err(|message| error!("Error setting online CPU count: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_smt_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetSmtEnabled",
+            [enabled],
+            async {
+                set_smt_enabled(enabled)
+                    .await
+                    .inspect_err(|message| error!("Error setting SMT state: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_gpu_performance_level(
+        &self,
+        level: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetGpuPerformanceLevel",
+            [level],
+            async {
+                let Some(ref driver) = self.gpu_performance_level else {
+                    return Err(fdo::Error::Failed(String::from(
+                        "GPU performance settings not configured",
+                    )));
+                };
+                let level = match driver.performance_level_from_str(level) {
+                    Ok(level) => level,
+                    Err(e) => return Err(to_zbus_fdo_error(e)),
+                };
+                driver
+                    .set_performance_level(level)
+                    .await
+                    .inspect_err(|message| error!("Error setting GPU performance level: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(
+                        RootCommand::SetGpuPerformanceLevel(level.to_string()),
+                    ))
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error sending SetGpuPerformanceLevel command: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_manual_gpu_clock(
+        &self,
+        clocks: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetManualGpuClock",
+            [clocks],
+            async {
+                let Some(ref driver) = self.gpu_performance_level else {
+                    return Err(fdo::Error::Failed(String::from(
+                        "GPU performance settings not configured",
+                    )));
+                };
+                driver
+                    .set_clocks(clocks)
+                    .await
+                    .inspect_err(|message| error!("Error setting manual GPU clock: {message}"))
+                    .map_err(to_zbus_fdo_error)?;
+                self.channel
+                    .send(DaemonCommand::ContextCommand(
+                        RootCommand::SetManualGpuClock(clocks),
+                    ))
+                    .await
+                    .inspect_err(|message| {
+                        error!("Error sending SetManualGpuClock command: {message}")
+                    })
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_tdp_limit(
+        &self,
+        limit: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(&self.connection, header, "SetTdpLimit", [limit], async {
+            let Some(ref manager) = self.tdp_limit_manager else {
+                return Err(fdo::Error::Failed(String::from(
+                    "TDP limiting not configured",
+                )));
+            };
+            manager
+                .set_tdp_limit(limit)
+                .await
+                .map_err(to_zbus_fdo_error)
+        })
+    }
+
+    async fn set_tdp_limits(
+        &self,
+        spl: u32,
+        sppt: u32,
+        fppt: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetTdpLimits",
+            [spl, sppt, fppt],
+            async {
+                let Some(ref manager) = self.tdp_limit_manager else {
+                    return Err(fdo::Error::Failed(String::from(
+                        "TDP limiting not configured",
+                    )));
+                };
+                manager
+                    .set_tdp_limits(TdpLimits { spl, sppt, fppt })
+                    .await
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    #[zbus(property)]
+    async fn wifi_debug_mode_state(&self) -> u32 {
+        // Get the wifi debug mode
+        self.wifi_debug_mode as u32
+    }
+
+    async fn set_wifi_debug_mode(
+        &mut self,
+        mode: u32,
+        options: HashMap<&str, zvariant::Value<'_>>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetWifiDebugMode",
+            [mode, format!("{options:?}")],
+            async {
+                // Set the wifi debug mode to mode, using an int for flexibility going forward but
+                // only doing things on 0 or 1 for now
+                let wanted_mode = match WifiDebugMode::try_from(mode) {
+                    Ok(mode) => mode,
+                    Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string())),
+                };
+
+                if self.wifi_debug_mode == wanted_mode {
+                    info!("Not changing wifi debug mode since it's already set to {wanted_mode}");
+                    return Ok(());
+                }
+
+                let buffer_size = match options
+                    .get("buffer_size")
+                    .map(zbus::zvariant::Value::downcast_ref)
+                {
+                    Some(Ok(v)) => v,
+                    None => 20000,
+                    Some(Err(e)) => return Err(fdo::Error::InvalidArgs(e.to_string())),
+                };
+                match set_wifi_debug_mode(
+                    wanted_mode,
+                    buffer_size,
+                    self.should_trace,
+                    self.connection.clone(),
+                )
+                .await
+                {
+                    Ok(()) => {
+                        self.wifi_debug_mode = wanted_mode;
+                        self.wifi_debug_mode_state_changed(&ctx).await?;
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!("Error setting wifi debug mode: {e}");
+                        Err(to_zbus_fdo_error(e))
+                    }
+                }
+            }
+        )
+    }
+
+    async fn set_wifi_backend(
+        &mut self,
+        backend: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetWifiBackend",
+            [backend],
+            async {
+                if self.wifi_debug_mode == WifiDebugMode::Tracing {
+                    return Err(fdo::Error::Failed(String::from(
+                        "operation not supported when wifi_debug_mode=tracing",
+                    )));
+                }
+                let backend = match WifiBackend::try_from(backend) {
+                    Ok(backend) => backend,
+                    Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string())),
+                };
+                set_wifi_backend(backend)
+                    .await
+                    .inspect_err(|message| error!("Error setting wifi backend: {message}"))
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn capture_debug_trace_output(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<String> {
+        audited!(
+            &self.connection,
+            header,
+            "CaptureDebugTraceOutput",
+            [],
+            async {
+                Ok(extract_wifi_trace()
+                    .await
+                    .inspect_err(|message| error!("Error capturing trace output: {message}"))
+                    .map_err(to_zbus_fdo_error)?
+                    .into_os_string()
+                    .to_string_lossy()
+                    .into())
+            }
+        )
+    }
+
+    async fn generate_debug_dump(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<String> {
+        audited!(&self.connection, header, "GenerateDebugDump", [], async {
+            Ok(generate_wifi_dump()
+                .await
+                .inspect_err(|message| error!("Error capturing dump output: {message}"))
+                .map_err(to_zbus_fdo_error)?
+                .into_os_string()
+                .to_string_lossy()
+                .into())
+        })
     }
 
     #[zbus(property)]
@@ -478,87 +1768,466 @@ impl SteamOSManager {
     }
 
     #[zbus(property)]
-    async fn set_inhibit_ds(&self, enable: bool) -> zbus::Result<()> {
+    async fn set_inhibit_ds(
+        &self,
+        enable: bool,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        let header =
+            header.ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+        audited!(&self.connection, header, "SetInhibitDs", [enable], async {
+            self.channel
+                .send(DaemonCommand::ContextCommand(RootCommand::SetDsInhibit(
+                    enable,
+                )))
+                .await
+                .inspect_err(|message| error!("Error sending SetDsInhibit command: {message}"))
+                .map_err(to_zbus_error)
+        })
+    }
+
+    async fn reload_config(&self, #[zbus(header)] header: Header<'_>) -> fdo::Result<()> {
+        audited!(&self.connection, header, "ReloadConfig", [], async {
+            self.channel
+                .send(DaemonCommand::ReadConfig)
+                .await
+                .inspect_err(|message| error!("Error sending ReadConfig command: {message}"))
+                .map_err(to_zbus_fdo_error)
+        })
+    }
+
+    async fn set_log_level(
+        &self,
+        level: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(&self.connection, header, "SetLogLevel", [level], async {
+            set_log_level(level).map_err(to_zbus_fdo_error)
+        })
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn log_level(&self) -> fdo::Result<String> {
+        log_level().map_err(to_zbus_fdo_error)
+    }
+
+    async fn limit_network_bandwidth(
+        &self,
+        identifier: &str,
+        download: u32,
+        upload: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<Fd> {
+        audited!(
+            &self.connection,
+            header,
+            "LimitNetworkBandwidth",
+            [identifier, download, upload],
+            async {
+                let (tx, rx) = oneshot::channel();
+                self.network_limit
+                    .send(NetworkLimitCommand::Limit(
+                        identifier.to_string(),
+                        download,
+                        upload,
+                        tx,
+                    ))
+                    .map_err(|_| {
+                        fdo::Error::Failed(String::from("Failed to obtain network limit handle"))
+                    })?;
+                Ok(rx
+                    .await
+                    .map_err(to_zbus_fdo_error)?
+                    .inspect_err(|message| error!("Error limiting network bandwidth: {message}"))
+                    .map_err(to_zbus_fdo_error)?
+                    .into())
+            }
+        )
+    }
+
+    async fn list_network_limit_handles(&self) -> fdo::Result<HashMap<String, (u32, u32)>> {
+        let (tx, rx) = oneshot::channel();
+        self.network_limit
+            .send(NetworkLimitCommand::ListHandles(tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain network limit handle list"))
+            })?;
+        rx.await.map_err(to_zbus_fdo_error)
+    }
+
+    async fn get_job_history(
+        &self,
+        count: u32,
+    ) -> fdo::Result<Vec<(String, u64, u64, i32, u32, String)>> {
+        let (tx, rx) = oneshot::channel();
         self.channel
-            .send(DaemonCommand::ContextCommand(RootCommand::SetDsInhibit(
-                enable,
+            .send(DaemonCommand::ContextCommand(RootCommand::GetJobHistory(
+                count, tx,
             )))
             .await
-            .inspect_err(|message| error!("Error sending SetDsInhibit command: {message}"))
-            .map_err(to_zbus_error)
+            .inspect_err(|message| error!("Error sending GetJobHistory command: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let history = rx
+            .await
+            .inspect_err(|message| error!("Error receiving GetJobHistory reply: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(history
+            .into_iter()
+            .map(|record| {
+                (
+                    record.operation,
+                    record.start_time,
+                    record.end_time,
+                    record.exit_code,
+                    record.status,
+                    record.output,
+                )
+            })
+            .collect())
     }
 
-    async fn reload_config(&self) -> fdo::Result<()> {
+    async fn get_service_status(&self) -> fdo::Result<Vec<(String, String, u32, String)>> {
+        let (tx, rx) = oneshot::channel();
         self.channel
-            .send(DaemonCommand::ReadConfig)
+            .send(DaemonCommand::ContextCommand(
+                RootCommand::GetServiceStatus(tx),
+            ))
             .await
-            .inspect_err(|message| error!("Error sending ReadConfig command: {message}"))
+            .inspect_err(|message| error!("Error sending GetServiceStatus command: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        rx.await
+            .inspect_err(|message| error!("Error receiving GetServiceStatus reply: {message}"))
             .map_err(to_zbus_fdo_error)
     }
 
-    #[zbus(signal)]
-    async fn max_charge_level_changed(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+    #[zbus(signal)]
+    async fn max_charge_level_changed(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn sysfs_write_failed(
+        signal_emitter: &SignalEmitter<'_>,
+        path: &str,
+        error: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn service_restarted(
+        signal_emitter: &SignalEmitter<'_>,
+        daemon: &str,
+        service: &str,
+        restarts: u32,
+    ) -> zbus::Result<()>;
+
+    async fn set_max_charge_level(
+        &self,
+        level: i32,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetMaxChargeLevel",
+            [level],
+            async {
+                let level = if level == -1 { 0 } else { level };
+                let written = set_max_charge_level(level)
+                    .await
+                    .map_err(to_zbus_fdo_error)?;
+                // Rapid changes are coalesced by the write queue, so don't hold the caller up
+                let connection = connection.clone();
+                let channel = self.channel.clone();
+                spawn(async move {
+                    commit_max_charge_level(&connection, &channel, level, written).await
+                });
+                Ok(())
+            }
+        )
+    }
+
+    async fn set_performance_profile(
+        &self,
+        profile: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetPerformanceProfile",
+            [profile],
+            async {
+                let config = device_config().await.map_err(to_zbus_fdo_error)?;
+                let config = config
+                    .as_ref()
+                    .and_then(|config| config.performance_profile.as_ref())
+                    .ok_or(fdo::Error::Failed(String::from(
+                        "No performance platform-profile configured",
+                    )))?;
+                set_platform_profile(&config.platform_profile_name, profile)
+                    .await
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_temporary_session(
+        &self,
+        session: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetTemporarySession",
+            [session],
+            async {
+                set_temporary_session(session)
+                    .await
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn set_default_session(
+        &self,
+        session: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "SetDefaultSession",
+            [session],
+            async {
+                set_default_session(session)
+                    .await
+                    .map_err(to_zbus_fdo_error)
+            }
+        )
+    }
+
+    async fn clean_temporary_sessions(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        audited!(
+            &self.connection,
+            header,
+            "CleanTemporarySessions",
+            [],
+            async { clean_temporary_sessions().await.map_err(to_zbus_fdo_error) }
+        )
+    }
+
+    /// A version property.
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn version(&self) -> u32 {
+        API_VERSION
+    }
+}
+
+pub(crate) const STEAMOS_READONLY: &str = "/usr/bin/steamos-readonly";
+const SET_READ_ONLY_ACTION: &str = "com.steampowered.SteamOSManager1.set-root-filesystem-read-only";
+
+pub(crate) struct RootFilesystem1 {}
+
+#[interface(name = "com.steampowered.SteamOSManager1.RootFilesystem1")]
+impl RootFilesystem1 {
+    #[zbus(property)]
+    async fn read_only(&self) -> fdo::Result<bool> {
+        let output = script_output(STEAMOS_READONLY, &["status"])
+            .await
+            .inspect_err(|message| error!("Error getting root filesystem state: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        match output.trim() {
+            "enabled" => Ok(true),
+            "disabled" => Ok(false),
+            status => Err(fdo::Error::Failed(format!(
+                "Unknown root filesystem state {status}"
+            ))),
+        }
+    }
+
+    async fn set_read_only(
+        &self,
+        read_only: bool,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        audited!(connection, header, "SetReadOnly", [read_only], async {
+            check_authorization(connection, &header, SET_READ_ONLY_ACTION).await?;
+            let command = if read_only { "enable" } else { "disable" };
+            run_script(STEAMOS_READONLY, &[command])
+                .await
+                .inspect_err(|message| error!("Error setting root filesystem read-only: {message}"))
+                .map_err(to_zbus_fdo_error)
+        })?;
+        self.read_only_changed(&ctx).await.map_err(zbus_to_zbus_fdo)
+    }
+}
+
+const READ_AUDIT_LOG_ACTION: &str = "com.steampowered.SteamOSManager1.read-audit-log";
+
+pub(crate) struct AuditLog1 {}
+
+#[interface(name = "com.steampowered.SteamOSManager1.AuditLog1")]
+impl AuditLog1 {
+    async fn get_recent_audit_entries(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<Vec<(u64, u32, u32, String, String, String, Vec<String>, String)>> {
+        authorize_privileged_call(connection, &header, READ_AUDIT_LOG_ACTION).await?;
+        let entries = recent_audit_entries(RECENT_AUDIT_ENTRIES)
+            .await
+            .inspect_err(|message| error!("Error reading audit log: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.timestamp,
+                    entry.uid,
+                    entry.pid.unwrap_or_default(),
+                    entry
+                        .exe
+                        .map(|exe| exe.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    entry.sender,
+                    entry.method,
+                    entry.arguments,
+                    entry.result,
+                )
+            })
+            .collect())
+    }
+}
+
+const HEADLESS_SETTINGS_ACTION: &str = "com.steampowered.SteamOSManager1.headless-settings";
+
+/// A reduced set of settings exposed directly on the system bus while no user session is running
+pub(crate) struct Headless1 {
+    connection: Connection,
+    channel: Sender<Command>,
+    fan_control: FanControl,
+    tdp_limit_manager: Option<Box<dyn TdpLimitManager>>,
+}
+
+impl Headless1 {
+    pub(crate) async fn new(connection: Connection, channel: Sender<Command>) -> Headless1 {
+        Headless1 {
+            fan_control: FanControl::new(connection.clone()),
+            tdp_limit_manager: tdp_limit_manager()
+                .await
+                .inspect_err(|e| info!("Could not set up headless TDP limiting: {e}"))
+                .ok(),
+            connection,
+            channel,
+        }
+    }
+
+    fn tdp_limit_manager(&self) -> fdo::Result<&dyn TdpLimitManager> {
+        self.tdp_limit_manager
+            .as_deref()
+            .ok_or_else(|| fdo::Error::Failed(String::from("TDP limiting not configured")))
+    }
 
-    async fn set_max_charge_level(
+    async fn authorized_write<F>(
         &self,
-        level: i32,
-        #[zbus(connection)] connection: &Connection,
-    ) -> fdo::Result<()> {
-        let written = set_max_charge_level(if level == -1 { 0 } else { level })
-            .await
-            .map_err(to_zbus_fdo_error)?;
-        let connection = connection.clone();
-        spawn(async move {
-            match written.await {
-                Ok(SysfsWritten::Written(res)) => {
-                    if let Ok(interface) = connection
-                        .object_server()
-                        .interface::<_, Self>("/com/steampowered/SteamOSManager1")
-                        .await
-                    {
-                        interface.max_charge_level_changed().await?;
-                    }
-                    res
-                }
-                _ => Ok(()),
+        header: Option<Header<'_>>,
+        method: &str,
+        argument: String,
+        write: F,
+    ) -> zbus::Result<()>
+    where
+        F: Future<Output = fdo::Result<()>>,
+    {
+        let header =
+            header.ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+        Ok(audited!(
+            &self.connection,
+            header,
+            method,
+            [argument],
+            async {
+                check_authorization(&self.connection, &header, HEADLESS_SETTINGS_ACTION).await?;
+                write.await
             }
-        });
-        Ok(())
+        )?)
     }
+}
 
-    async fn set_performance_profile(&self, profile: &str) -> fdo::Result<()> {
-        let config = device_config().await.map_err(to_zbus_fdo_error)?;
-        let config = config
-            .as_ref()
-            .and_then(|config| config.performance_profile.as_ref())
-            .ok_or(fdo::Error::Failed(String::from(
-                "No performance platform-profile configured",
-            )))?;
-        set_platform_profile(&config.platform_profile_name, profile)
+#[interface(name = "com.steampowered.SteamOSManager1.Headless1")]
+impl Headless1 {
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn tdp_limit(&self) -> fdo::Result<u32> {
+        self.tdp_limit_manager()?
+            .get_tdp_limit()
             .await
             .map_err(to_zbus_fdo_error)
     }
 
-    async fn set_temporary_session(&self, session: &str) -> fdo::Result<()> {
-        set_temporary_session(session)
-            .await
-            .map_err(to_zbus_fdo_error)
+    #[zbus(property)]
+    async fn set_tdp_limit(
+        &self,
+        limit: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        self.authorized_write(header, "SetTdpLimit", limit.to_string(), async {
+            self.tdp_limit_manager()?
+                .set_tdp_limit(limit)
+                .await
+                .map_err(to_zbus_fdo_error)
+        })
+        .await
     }
 
-    async fn set_default_session(&self, session: &str) -> fdo::Result<()> {
-        set_default_session(session)
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn fan_control_state(&self) -> fdo::Result<u32> {
+        Ok(self
+            .fan_control
+            .get_state()
             .await
-            .map_err(to_zbus_fdo_error)
+            .map_err(to_zbus_fdo_error)? as u32)
+    }
+
+    #[zbus(property)]
+    async fn set_fan_control_state(
+        &self,
+        state: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        self.authorized_write(header, "SetFanControlState", state.to_string(), async {
+            let state = FanControlState::try_from(state)
+                .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+            self.fan_control
+                .set_state(state)
+                .await
+                .map_err(to_zbus_fdo_error)
+        })
+        .await
     }
 
-    async fn clean_temporary_sessions(&self) -> fdo::Result<()> {
-        clean_temporary_sessions().await.map_err(to_zbus_fdo_error)
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn max_charge_level(&self) -> fdo::Result<i32> {
+        get_max_charge_level().await.map_err(to_zbus_fdo_error)
     }
 
-    /// A version property.
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn version(&self) -> u32 {
-        API_VERSION
+    #[zbus(property)]
+    async fn set_max_charge_level(
+        &self,
+        level: i32,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        self.authorized_write(header, "SetMaxChargeLevel", level.to_string(), async {
+            let level = if level == -1 { 0 } else { level };
+            let written = set_max_charge_level(level)
+                .await
+                .map_err(to_zbus_fdo_error)?;
+            commit_max_charge_level(&self.connection, &self.channel, level, written).await
+        })
+        .await
     }
 }
 
@@ -572,17 +2241,20 @@ mod test {
         self, AmdgpuPerformanceLevel, AmdgpuPerformanceLevelDriver, GpuPerformanceLevel,
     };
     use crate::hardware::test::fake_model;
-    use crate::platform::{PlatformConfig, ResetConfig};
+    use crate::platform::{OsUpdateConfig, PlatformConfig, ResetConfig};
     use crate::process::test::{code, exit, ok};
     use crate::testing;
+    use std::path::PathBuf;
     use std::time::Duration;
     use tokio::fs::{create_dir_all, write};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
     use tokio::time::sleep;
     use zbus::Connection;
 
     struct TestHandle {
         h: testing::TestHandle,
         connection: Connection,
+        rx: UnboundedReceiver<Command>,
     }
 
     async fn start() -> Result<TestHandle> {
@@ -596,12 +2268,23 @@ mod test {
         .await?;
         gpu::test::create_nodes().await.expect("setup");
 
-        let (tx, _rx) = channel::<RootContext>();
+        let (tx, mut daemon_rx) = channel::<RootContext>();
+        // Forward the daemon commands to an unbounded channel, so that tests which don't look at
+        // them can't fill it up and block the methods sending them
+        let (forward_tx, rx) = unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(command) = daemon_rx.recv().await {
+                if forward_tx.send(command).is_err() {
+                    break;
+                }
+            }
+        });
+        let (network_limit_tx, _network_limit_rx) = unbounded_channel();
         let connection = handle.new_dbus().await?;
-        let manager = SteamOSManager::new(connection.clone(), tx).await?;
+        let manager = SteamOSManager::new(connection.clone(), tx, network_limit_tx).await?;
         connection
             .object_server()
-            .at("/com/steampowered/SteamOSManager1", manager)
+            .at("/com/steampowered/SteamOSManager1", Guarded::new(manager))
             .await?;
 
         sleep(Duration::from_millis(1)).await;
@@ -609,6 +2292,7 @@ mod test {
         Ok(TestHandle {
             h: handle,
             connection,
+            rx,
         })
     }
 
@@ -616,8 +2300,24 @@ mod test {
         interface = "com.steampowered.SteamOSManager1.RootManager",
         default_path = "/com/steampowered/SteamOSManager1"
     )]
-    trait PrepareFactoryReset {
+    trait FactoryReset {
+        fn estimate_factory_reset(&self, kind: u32) -> zbus::Result<(Vec<String>, u64)>;
         fn prepare_factory_reset(&self, kind: u32) -> zbus::Result<u32>;
+        fn start_factory_reset(&self, kind: u32) -> zbus::Result<zvariant::OwnedObjectPath>;
+    }
+
+    fn reset_config() -> ResetConfig {
+        let script = |args: &[&str]| ScriptConfig {
+            script: PathBuf::from("/bin/true"),
+            script_args: args.iter().map(|arg| arg.to_string()).collect(),
+            timeout: None,
+        };
+        ResetConfig {
+            all: script(&["factory-reset", "--reset-all"]),
+            os: script(&["factory-reset", "--reset-os"]),
+            user: script(&["factory-reset", "--reset-user-data"]),
+            estimate: None,
+        }
     }
 
     #[tokio::test]
@@ -629,91 +2329,327 @@ mod test {
         test.h.test.platform_config.replace(Some(config));
 
         let name = test.connection.unique_name().unwrap();
-        let proxy = PrepareFactoryResetProxy::new(&test.connection, name.clone())
+        let proxy = FactoryResetProxy::new(&test.connection, name.clone())
             .await
             .unwrap();
 
-        test.h.test.process_cb.set(ok);
-        assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::All as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::RebootRequired as u32
-        );
+        for kind in [
+            FactoryResetKind::All,
+            FactoryResetKind::OS,
+            FactoryResetKind::User,
+        ] {
+            test.h.test.process_cb.set(ok);
+            assert_eq!(
+                proxy.prepare_factory_reset(kind as u32).await.unwrap(),
+                PrepareFactoryResetResult::RebootRequired as u32
+            );
+
+            test.h.test.process_cb.set(code);
+            assert_eq!(
+                proxy.prepare_factory_reset(kind as u32).await.unwrap(),
+                PrepareFactoryResetResult::Unknown as u32
+            );
+
+            test.h.test.process_cb.set(exit);
+            assert_eq!(
+                proxy.prepare_factory_reset(kind as u32).await.unwrap(),
+                PrepareFactoryResetResult::Unknown as u32
+            );
+        }
 
-        test.h.test.process_cb.set(code);
-        assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::All as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
-        );
+        test.connection.close().await.unwrap();
+    }
 
-        test.h.test.process_cb.set(exit);
-        assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::All as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
-        );
+    #[tokio::test]
+    async fn start_factory_reset() {
+        let test = start().await.expect("start");
 
-        test.h.test.process_cb.set(ok);
-        assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::OS as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::RebootRequired as u32
-        );
+        let name = test.connection.unique_name().unwrap();
+        let proxy = FactoryResetProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+
+        assert!(proxy
+            .start_factory_reset(FactoryResetKind::All as u32)
+            .await
+            .is_err());
+
+        let mut config = PlatformConfig::default();
+        config.factory_reset = Some(reset_config());
+        test.h.test.platform_config.replace(Some(config));
+
+        for kind in [
+            FactoryResetKind::User,
+            FactoryResetKind::OS,
+            FactoryResetKind::All,
+        ] {
+            let path = proxy.start_factory_reset(kind as u32).await.unwrap();
+            assert!(path
+                .as_str()
+                .starts_with("/com/steampowered/SteamOSManager1/Jobs/"));
+        }
+
+        assert!(proxy.start_factory_reset(0).await.is_err());
+        assert!(proxy.start_factory_reset(4).await.is_err());
+
+        test.connection.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn estimate_factory_reset() {
+        let test = start().await.expect("start");
+
+        let name = test.connection.unique_name().unwrap();
+        let proxy = FactoryResetProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+
+        let mut config = PlatformConfig::default();
+        config.factory_reset = Some(reset_config());
+        test.h.test.platform_config.replace(Some(config.clone()));
+        assert!(proxy
+            .estimate_factory_reset(FactoryResetKind::All as u32)
+            .await
+            .is_err());
+
+        config.factory_reset.as_mut().unwrap().estimate = Some(ScriptConfig {
+            script: PathBuf::from("/usr/bin/steamos-reset-tool"),
+            script_args: vec![String::from("--dry-run")],
+            timeout: None,
+        });
+        test.h.test.platform_config.replace(Some(config));
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::ensure!(executable == "/usr/bin/steamos-reset-tool");
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect();
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            match args.as_slice() {
+                ["--dry-run", "factory-reset", "--reset-user-data"] => {
+                    Ok((0, String::from("partition=home\nduration=30\n")))
+                }
+                ["--dry-run", "factory-reset", "--reset-all"] => Ok((
+                    0,
+                    String::from("partition=home\npartition=rootfs\nduration=120\n"),
+                )),
+                _ => anyhow::bail!("Unexpected arguments {args:?}"),
+            }
+        }
+        test.h.test.process_cb.set(process_output);
 
-        test.h.test.process_cb.set(code);
         assert_eq!(
             proxy
-                .prepare_factory_reset(FactoryResetKind::OS as u32)
+                .estimate_factory_reset(FactoryResetKind::User as u32)
                 .await
                 .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
+            (vec![String::from("home")], 30)
         );
-
-        test.h.test.process_cb.set(exit);
         assert_eq!(
             proxy
-                .prepare_factory_reset(FactoryResetKind::OS as u32)
+                .estimate_factory_reset(FactoryResetKind::All as u32)
                 .await
                 .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
+            (vec![String::from("home"), String::from("rootfs")], 120)
         );
+        assert!(proxy
+            .estimate_factory_reset(FactoryResetKind::OS as u32)
+            .await
+            .is_err());
+        assert!(proxy.estimate_factory_reset(0).await.is_err());
+
+        test.connection.close().await.unwrap();
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.RootManager",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait UpdateChannel {
+        fn get_update_channel(&self) -> zbus::Result<String>;
+        fn set_update_channel(&self, channel: &str) -> zbus::Result<()>;
+    }
+
+    #[tokio::test]
+    async fn update_channel() {
+        let test = start().await.expect("start");
+
+        let name = test.connection.unique_name().unwrap();
+        let proxy = UpdateChannelProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+
+        assert!(proxy.get_update_channel().await.is_err());
+        assert!(proxy.set_update_channel("beta").await.is_err());
+
+        let script = PathBuf::from("/usr/bin/steamos-select-branch");
+        let mut config = PlatformConfig::default();
+        config.update_channel = Some(UpdateChannelConfig {
+            branches: vec![String::from("stable"), String::from("beta")],
+            get: ScriptConfig {
+                script: script.clone(),
+                script_args: vec![String::from("-c")],
+                timeout: None,
+            },
+            set: ScriptConfig {
+                script,
+                script_args: Vec::new(),
+                timeout: None,
+            },
+        });
+        test.h.test.platform_config.replace(Some(config));
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::ensure!(executable == "/usr/bin/steamos-select-branch");
+            match args {
+                [arg] if *arg == "-c" => Ok((0, String::from("stable\n"))),
+                [arg] if *arg == "beta" => Ok((0, String::new())),
+                _ => anyhow::bail!("Unexpected arguments {args:?}"),
+            }
+        }
+        test.h.test.process_cb.set(process_output);
+
+        assert_eq!(proxy.get_update_channel().await.unwrap(), "stable");
+        proxy.set_update_channel("beta").await.unwrap();
+        assert!(proxy.set_update_channel("preview").await.is_err());
+        assert!(proxy.set_update_channel("").await.is_err());
+
+        fn unknown_output(_executable: &OsStr, _args: &[&OsStr]) -> Result<(i32, String)> {
+            Ok((0, String::from("main\n")))
+        }
+        test.h.test.process_cb.set(unknown_output);
+        assert!(proxy.get_update_channel().await.is_err());
+
+        test.connection.close().await.unwrap();
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.RootManager",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait OsUpdate {
+        fn check_for_os_updates(&self) -> zbus::Result<Vec<(String, String, String, u64)>>;
+    }
+
+    #[tokio::test]
+    async fn check_for_os_updates() {
+        let test = start().await.expect("start");
+
+        let name = test.connection.unique_name().unwrap();
+        let proxy = OsUpdateProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+
+        assert!(proxy.check_for_os_updates().await.is_err());
+
+        let mut config = PlatformConfig::default();
+        config.os_update = Some(OsUpdateConfig {
+            check: ScriptConfig {
+                script: PathBuf::from("/usr/bin/steamos-atomupd-client"),
+                script_args: vec![String::from("--query-only")],
+                timeout: None,
+            },
+            update: ScriptConfig::default(),
+        });
+        test.h.test.platform_config.replace(Some(config));
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::ensure!(executable == "/usr/bin/steamos-atomupd-client");
+            anyhow::ensure!(args == ["--query-only"]);
+            Ok((
+                0,
+                String::from(
+                    r#"{"minor": {"candidates": [{"image": {"version": "3.7.8", "buildid": "20250601.1"}}]}}"#,
+                ),
+            ))
+        }
+        test.h.test.process_cb.set(process_output);
 
-        test.h.test.process_cb.set(ok);
         assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::User as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::RebootRequired as u32
+            proxy.check_for_os_updates().await.unwrap(),
+            vec![(
+                String::from("minor"),
+                String::from("3.7.8"),
+                String::from("20250601.1"),
+                0
+            )]
         );
 
-        test.h.test.process_cb.set(code);
-        assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::User as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
+        test.connection.close().await.unwrap();
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.RootManager",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait SysfsAccess {
+        fn list_sysfs_attributes(&self) -> zbus::Result<HashMap<String, bool>>;
+        fn read_sysfs_attribute(&self, name: &str) -> zbus::Result<String>;
+        fn write_sysfs_attribute(&self, name: &str, value: &str) -> zbus::Result<()>;
+    }
+
+    #[tokio::test]
+    async fn sysfs_access() {
+        let test = start().await.expect("start");
+
+        let base = crate::path("/sys/devices/platform/test");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("mode"), "quiet\n").await.expect("write");
+        write(base.join("version"), "3\n").await.expect("write");
+
+        let mut config = PlatformConfig::default();
+        config.sysfs_access.insert(
+            String::from("mode"),
+            SysfsAttributeConfig {
+                path: PathBuf::from("/sys/devices/platform/test/mode"),
+                writable: true,
+                values: vec![String::from("quiet"), String::from("loud")],
+            },
+        );
+        config.sysfs_access.insert(
+            String::from("version"),
+            SysfsAttributeConfig {
+                path: PathBuf::from("/sys/devices/platform/test/version"),
+                writable: false,
+                values: Vec::new(),
+            },
         );
+        config.sysfs_access.insert(
+            String::from("escape"),
+            SysfsAttributeConfig {
+                path: PathBuf::from("/etc/shadow"),
+                writable: true,
+                values: Vec::new(),
+            },
+        );
+        test.h.test.platform_config.replace(Some(config));
+
+        let name = test.connection.unique_name().unwrap();
+        let proxy = SysfsAccessProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
 
-        test.h.test.process_cb.set(exit);
         assert_eq!(
-            proxy
-                .prepare_factory_reset(FactoryResetKind::User as u32)
-                .await
-                .unwrap(),
-            PrepareFactoryResetResult::Unknown as u32
+            proxy.list_sysfs_attributes().await.unwrap(),
+            HashMap::from([
+                (String::from("mode"), true),
+                (String::from("version"), false)
+            ])
         );
 
+        assert_eq!(proxy.read_sysfs_attribute("mode").await.unwrap(), "quiet");
+        assert_eq!(proxy.read_sysfs_attribute("version").await.unwrap(), "3");
+        assert!(proxy.read_sysfs_attribute("escape").await.is_err());
+        assert!(proxy.read_sysfs_attribute("missing").await.is_err());
+
+        proxy.write_sysfs_attribute("mode", "loud").await.unwrap();
+        assert_eq!(proxy.read_sysfs_attribute("mode").await.unwrap(), "loud");
+        assert!(proxy.write_sysfs_attribute("mode", "off").await.is_err());
+        assert!(proxy.write_sysfs_attribute("version", "4").await.is_err());
+        assert!(proxy.write_sysfs_attribute("escape", "").await.is_err());
+        assert_eq!(proxy.read_sysfs_attribute("version").await.unwrap(), "3");
+
         test.connection.close().await.unwrap();
     }
 
@@ -783,7 +2719,7 @@ mod test {
 
     #[tokio::test]
     async fn gpu_performance_level() {
-        let test = start().await.expect("start");
+        let mut test = start().await.expect("start");
         let driver = AmdgpuPerformanceLevelDriver {};
 
         let name = test.connection.unique_name().unwrap();
@@ -800,6 +2736,12 @@ mod test {
             driver.get_performance_level().await.unwrap(),
             GpuPerformanceLevel::Amdgpu(AmdgpuPerformanceLevel::Low)
         );
+        let Some(DaemonCommand::ContextCommand(RootCommand::SetGpuPerformanceLevel(level))) =
+            test.rx.recv().await
+        else {
+            panic!("GPU performance level not saved");
+        };
+        assert_eq!(level, "low");
 
         test.connection.close().await.unwrap();
     }
@@ -814,7 +2756,7 @@ mod test {
 
     #[tokio::test]
     async fn manual_gpu_clock() {
-        let test = start().await.expect("start");
+        let mut test = start().await.expect("start");
 
         let name = test.connection.unique_name().unwrap();
         let proxy = ManualGpuClockProxy::new(&test.connection, name.clone())
@@ -823,6 +2765,11 @@ mod test {
 
         proxy.set_manual_gpu_clock(200).await.expect("proxy_set");
         assert_eq!(read_clocks().await.unwrap(), format_clocks(200));
+        let Some(DaemonCommand::ContextCommand(RootCommand::SetManualGpuClock(200))) =
+            test.rx.recv().await
+        else {
+            panic!("Manual GPU clock not saved");
+        };
 
         test.connection.close().await.unwrap();
     }
@@ -836,6 +2783,125 @@ mod test {
         fn version(&self) -> zbus::Result<u32>;
     }
 
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.RootFilesystem1",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait RootFilesystem {
+        fn set_read_only(&self, read_only: bool) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn read_only(&self) -> zbus::Result<bool>;
+    }
+
+    #[tokio::test]
+    async fn root_filesystem_read_only() {
+        let test = start().await.expect("start");
+        test.connection
+            .object_server()
+            .at(
+                "/com/steampowered/SteamOSManager1",
+                Guarded::new(RootFilesystem1 {}),
+            )
+            .await
+            .expect("at");
+
+        let name = test.connection.unique_name().unwrap();
+        let proxy = RootFilesystemProxy::builder(&test.connection)
+            .destination(name.clone())
+            .unwrap()
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        fn enabled(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::ensure!(executable == STEAMOS_READONLY && args == ["status"]);
+            Ok((0, String::from("enabled\n")))
+        }
+        test.h.test.process_cb.set(enabled);
+        assert!(proxy.read_only().await.unwrap());
+
+        fn disabled(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::ensure!(executable == STEAMOS_READONLY && args == ["status"]);
+            Ok((1, String::from("disabled\n")))
+        }
+        test.h.test.process_cb.set(disabled);
+        assert!(!proxy.read_only().await.unwrap());
+
+        // Nothing can authorize the call on the test bus, so the script must not run
+        fn unreachable(_: &OsStr, _: &[&OsStr]) -> Result<(i32, String)> {
+            anyhow::bail!("Script ran without authorization")
+        }
+        test.h.test.process_cb.set(unreachable);
+        assert!(proxy.set_read_only(true).await.is_err());
+
+        test.connection.close().await.unwrap();
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.AuditLog1",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait AuditLog {
+        fn get_recent_audit_entries(
+            &self,
+        ) -> zbus::Result<Vec<(u64, u32, u32, String, String, String, Vec<String>, String)>>;
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.RootManager",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait AuditedMethods {
+        fn set_update_channel(&self, channel: &str) -> zbus::Result<()>;
+        fn trim_devices(&self) -> zbus::Result<zvariant::OwnedObjectPath>;
+    }
+
+    #[tokio::test]
+    async fn audit_log() {
+        let test = start().await.expect("start");
+        test.connection
+            .object_server()
+            .at(
+                "/com/steampowered/SteamOSManager1",
+                Guarded::new(AuditLog1 {}),
+            )
+            .await
+            .expect("at");
+
+        let name = test.connection.unique_name().unwrap();
+        let audit_log = AuditLogProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+        let proxy = AuditedMethodsProxy::new(&test.connection, name.clone())
+            .await
+            .unwrap();
+        assert!(audit_log
+            .get_recent_audit_entries()
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(proxy.trim_devices().await.is_err());
+        assert!(proxy.set_update_channel("beta").await.is_err());
+
+        let entries = audit_log.get_recent_audit_entries().await.unwrap();
+        assert_eq!(entries.len(), 2);
+        let uid = nix::unistd::getuid().as_raw();
+        let (_, entry_uid, _, _, sender, method, arguments, result) = &entries[0];
+        assert_eq!(*entry_uid, uid);
+        assert_eq!(sender, name.as_str());
+        assert_eq!(method, "TrimDevices");
+        assert!(arguments.is_empty());
+        assert!(result.contains("not supported"));
+        let (_, _, _, _, _, method, arguments, _) = &entries[1];
+        assert_eq!(method, "SetUpdateChannel");
+        assert_eq!(arguments, &[String::from("beta")]);
+
+        test.connection.close().await.unwrap();
+    }
+
     #[tokio::test]
     async fn version() {
         let test = start().await.expect("start");
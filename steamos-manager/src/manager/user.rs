@@ -7,39 +7,106 @@
  */
 
 use anyhow::{Error, Result};
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::pin::Pin;
+use std::sync::LazyLock;
+use std::time::Duration;
 use tokio::fs::try_exists;
 use tokio::sync::mpsc::{Sender, UnboundedSender};
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, Mutex};
+use tokio::time::{sleep_until, Instant};
+use tokio::{join, try_join};
 use tokio_stream::StreamExt;
-use tracing::{error, warn};
-use zbus::object_server::SignalEmitter;
-use zbus::proxy::{Builder, CacheProperties};
+use tracing::{debug, error, info, warn};
+use zbus::message::Header;
+use zbus::names::InterfaceName;
+use zbus::object_server::{Interface, SignalEmitter};
+use zbus::proxy::{Builder, CacheProperties, OwnerChangedStream};
 use zbus::zvariant::Fd;
 use zbus::{fdo, interface, zvariant, Connection, ObjectServer, Proxy};
 
+use crate::accessibility::AccessibilityManager;
+use crate::als::{find_als, read_lux};
+use crate::audio::{
+    audio_graph, enforce_speaker_volume_limit, get_audio_input_muted, get_audio_input_volume,
+    get_audio_output_volume, get_hardware_input_muted, get_max_speaker_volume, output_volume_limit,
+    set_audio_device_profile, set_audio_input_muted, set_audio_input_volume,
+    set_audio_output_volume, set_default_audio_output,
+};
+use crate::backlight::{
+    brightness_step, find_backlight, get_brightness, get_max_brightness, list_backlights,
+};
+use crate::cache::SysfsCache;
 use crate::cec::{HdmiCecControl, HdmiCecState};
-use crate::daemon::user::Command;
-use crate::daemon::DaemonCommand;
+use crate::color_filters::{
+    apply_color_filters, ColorFilter, ColorFiltersState, NIGHT_LIGHT_TEMPERATURE_RANGE,
+};
+use crate::daemon::user::{Command, UserCommand};
+use crate::daemon::{log_level, set_log_level, DaemonCommand};
+use crate::developer_mode::{developer_feature_enabled, developer_mode_enabled, DeveloperFeature};
+use crate::diagnostics::stage_diagnostics;
+use crate::display::{
+    get_dock_mode_hint, list_outputs, set_dock_mode_hint, DisplayMode, DisplayState,
+};
+use crate::dock::DockInfo;
 use crate::error::{to_zbus_error, to_zbus_fdo_error, zbus_to_zbus_fdo};
 use crate::gpu::{
-    gpu_performance_level_driver, gpu_power_profile_driver, GpuPerformanceLevelDriver,
-    GpuPowerProfileDriver,
+    get_gpu_metrics, get_gpu_power_average, get_gpu_power_cap, get_gpu_power_cap_max,
+    get_gpu_power_cap_min, get_gpu_thermal_throttling, gpu_performance_level_driver,
+    gpu_power_profile_driver, GpuPerformanceLevelDriver, GpuPowerProfileDriver,
 };
+use crate::haptics::{get_haptic_feedback, get_haptics_intensity, MAX_HAPTICS_INTENSITY};
 use crate::hardware::{
-    device_config, device_type, device_variant, steam_deck_variant, SteamDeckVariant,
+    device_config, device_type, device_variant, explain_device_match, get_fan_curve,
+    get_fan_curve_points, read_device_config, steam_deck_variant, DeviceConfig, SteamDeckVariant,
 };
+use crate::idle::{IdleAction, IdlePolicy, IdlePolicyState, IdlePowerState};
+use crate::input_inhibit::InputInhibitCommand;
 use crate::job::JobManagerCommand;
+use crate::leds::{
+    get_available_charge_led_modes, get_available_led_patterns, get_charge_led_mode,
+    get_led_brightness, get_led_color, get_led_max_brightness, get_led_pattern, ChargeLedMode,
+};
+use crate::network_limit::TC_PATH;
 use crate::path;
-use crate::platform::platform_config;
+use crate::platform::{platform_config, read_platform_config, DeveloperModeConfig, PlatformConfig};
+use crate::policy::Guarded;
+use crate::polkit::{
+    authorize_relayed_call, FACTORY_RESET_ACTION, FORMAT_DEVICE_ACTION, MANAGE_UNITS_ACTION,
+    SET_DEVELOPER_MODE_ACTION, SET_FAN_CONTROL_STATE_ACTION,
+};
 use crate::power::{
-    get_available_cpu_scaling_governors, get_available_platform_profiles, get_cpu_boost_state,
-    get_cpu_scaling_governor, get_max_charge_level, get_platform_profile, TdpManagerCommand,
+    find_battery, get_available_cpu_frequencies, get_available_cpu_scaling_governors,
+    get_available_energy_performance_preferences, get_available_platform_profiles,
+    get_battery_capacity, get_battery_charge_full, get_battery_charge_now,
+    get_battery_charge_state, get_battery_cycle_count, get_battery_health, get_charge_bypass,
+    get_charger_info, get_cpu_boost_state, get_cpu_frequency_range, get_cpu_scaling_governor,
+    get_cpu_scaling_governors, get_energy_performance_preference, get_max_charge_level,
+    get_max_cpu_frequency, get_max_online_cpu_count, get_min_cpu_frequency,
+    get_min_online_cpu_count, get_online_cpu_count, get_platform_profile, get_smt_enabled,
+    is_charge_bypass_supported, is_on_battery, PowerPreset, PowerPresetsState, TdpLimits,
+    TdpManagerCommand,
 };
 use crate::screenreader::{OrcaManager, ScreenReaderAction, ScreenReaderMode};
+use crate::services::{available_units, configured_units, unit_states, UnitAction};
 use crate::session::{is_session_managed, valid_desktop_sessions, LoginMode, SessionManager};
+use crate::sleep::{
+    get_sleep_mode, get_sleep_settings, get_supported_sleep_modes, is_swap_adequate, SleepMode,
+    SleepSettings, MAX_HIBERNATE_DELAY, MIN_HIBERNATE_DELAY,
+};
+use crate::storage::{get_device_info, list_block_devices};
+use crate::suspend_inhibit::SuspendInhibitCommand;
+use crate::telemetry::TelemetryCommand;
+use crate::text_input::{combo_keys, Locale1Proxy, TextInput};
+use crate::thermal::{get_temperature, list_sensors, SensorKind};
+use crate::usb::{get_usb_authorized_default, list_host_controllers, list_usb_devices};
+use crate::wake::{available_wake_sources, enabled_wake_sources, WakeSource};
 use crate::wifi::{
-    get_wifi_backend, get_wifi_power_management_state, list_wifi_interfaces, WifiBackend,
+    get_wifi_backend, get_wifi_power_management_state, list_wifi_interfaces, WifiBackend, WifiLink,
+    WifiLinkReceiver,
 };
 use crate::{Service, API_VERSION};
 
@@ -85,6 +152,25 @@ macro_rules! job_method {
     };
 }
 
+macro_rules! optional_interfaces {
+    (@config) => { None };
+    (@config $config:ident) => { Some(ConfigSource::$config) };
+    ($($feature:literal => $iface:ident $(: $config:ident)?,)+) => {
+        fn optional_interfaces() -> Vec<OptionalInterface> {
+            vec![
+                $(
+                    OptionalInterface {
+                        feature: $feature,
+                        name: $iface::name(),
+                        config: optional_interfaces!(@config $($config)?),
+                        present: interface_present::<$iface>,
+                    },
+                )+
+            ]
+        }
+    };
+}
+
 macro_rules! getter {
     ($self:expr, $prop:expr) => {
         $self
@@ -105,60 +191,301 @@ macro_rules! setter {
     };
 }
 
+// Throttle state for clients that haven't set a property in this long is dropped
+const THROTTLE_STATE_LIFETIME: Duration = Duration::from_secs(60);
+
+// Packing up the diagnostics bundle shouldn't take anywhere near this long
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(120);
+
+static SETTER_THROTTLE: LazyLock<SetterThrottle> = LazyLock::new(SetterThrottle::default);
+
+/// Nothing is throttled unless configured
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct SetterThrottleConfig {
+    /// Minimum time in milliseconds between applying values from the same client, for interfaces
+    /// that don't have their own, or 0 to not throttle
+    pub default: u64,
+    /// Windows keyed by interface name, e.g. `"com.steampowered.SteamOSManager1.TdpLimit1" = 200`
+    pub interfaces: HashMap<String, u64>,
+}
+
+impl SetterThrottleConfig {
+    fn window(&self, interface: &str) -> Duration {
+        Duration::from_millis(
+            self.interfaces
+                .get(interface)
+                .copied()
+                .unwrap_or(self.default),
+        )
+    }
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    last_applied: Option<Instant>,
+    generation: u64,
+}
+
+type ThrottleKey = (String, String, &'static str);
+
+#[derive(Default, Debug)]
+struct SetterThrottle {
+    config: Mutex<SetterThrottleConfig>,
+    states: Mutex<HashMap<ThrottleKey, ThrottleState>>,
+}
+
+impl SetterThrottle {
+    /// Wait until the client is allowed to set the property again. Returns false if the client
+    /// set it again in the meantime, in which case only the newest value gets applied.
+    async fn wait(&self, sender: &str, interface: &str, property: &'static str) -> bool {
+        let window = self.config.lock().await.window(interface);
+        if window.is_zero() {
+            return true;
+        }
+        let key = (sender.to_string(), interface.to_string(), property);
+        let (generation, deadline) = {
+            let mut states = self.states.lock().await;
+            states.retain(|_, state| {
+                state
+                    .last_applied
+                    .is_some_and(|last| last.elapsed() < THROTTLE_STATE_LIFETIME.max(window))
+            });
+            let now = Instant::now();
+            let state = states.entry(key.clone()).or_insert(ThrottleState {
+                last_applied: None,
+                generation: 0,
+            });
+            // Bumping the generation makes anyone still waiting give up in favour of this value
+            state.generation += 1;
+            match state.last_applied.map(|last| last + window) {
+                Some(deadline) if deadline > now => (state.generation, deadline),
+                _ => {
+                    state.last_applied = Some(now);
+                    return true;
+                }
+            }
+        };
+
+        sleep_until(deadline).await;
+        let mut states = self.states.lock().await;
+        let state = states.entry(key).or_insert(ThrottleState {
+            last_applied: None,
+            generation,
+        });
+        if state.generation != generation {
+            return false;
+        }
+        state.last_applied = Some(Instant::now());
+        true
+    }
+}
+
+pub(crate) async fn configure_setter_throttle(config: SetterThrottleConfig) {
+    *SETTER_THROTTLE.config.lock().await = config;
+}
+
+/// Coalesce rapid changes to a property from the same client, such as from dragging a slider, so
+/// they don't flood the root daemon. The newest value is applied once the window is over, and
+/// the writes it replaced fail, so their clients don't take them for applied.
+async fn throttle_setter<I: Interface>(
+    header: Option<&Header<'_>>,
+    property: &'static str,
+) -> fdo::Result<()> {
+    let sender = header
+        .and_then(|header| header.sender())
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    if SETTER_THROTTLE
+        .wait(sender.as_str(), I::name().as_str(), property)
+        .await
+    {
+        Ok(())
+    } else {
+        Err(fdo::Error::Failed(format!(
+            "{property} was set again before this value could be applied"
+        )))
+    }
+}
+
 struct SteamOSManager {
     proxy: Proxy<'static>,
     _job_manager: UnboundedSender<JobManagerCommand>,
 }
 
-struct AmbientLightSensor1 {
+struct Accessibility1 {
+    manager: AccessibilityManager,
+}
+
+pub(crate) struct AmbientLightSensor1 {
     proxy: Proxy<'static>,
+    channel: Sender<Command>,
+    /// Debounced illuminance while streaming is enabled
+    lux: Option<f64>,
+}
+
+struct Audio1 {
+    system: Connection,
+    speaker_limit_override: bool,
 }
 
+struct Backlight1 {
+    proxy: Proxy<'static>,
+    device: String,
+}
+
+pub(crate) struct Battery1 {}
+
 struct BatteryChargeLimit1 {
     proxy: Proxy<'static>,
 }
 
+#[derive(Default)]
+pub(crate) struct BatteryPolicy1 {
+    low_battery: bool,
+}
+
+struct ChargeLed1 {
+    proxy: Proxy<'static>,
+}
+
+struct ColorFilters1 {
+    session: Connection,
+    channel: Sender<Command>,
+}
+
 struct CpuBoost1 {
     proxy: Proxy<'static>,
 }
 
+struct CpuFrequency1 {
+    proxy: Proxy<'static>,
+}
+
 struct CpuScaling1 {
     proxy: Proxy<'static>,
 }
 
+struct DeveloperMode1 {
+    proxy: Proxy<'static>,
+    system: Connection,
+}
+
+struct DisplayOutput1 {
+    channel: Sender<Command>,
+}
+
+#[derive(Default)]
+pub(crate) struct Dock1 {
+    dock: Option<DockInfo>,
+    display_count: u32,
+}
+
 struct FactoryReset1 {
     proxy: Proxy<'static>,
+    job_manager: UnboundedSender<JobManagerCommand>,
 }
 
 struct FanControl1 {
     proxy: Proxy<'static>,
 }
 
+struct FanCurve1 {
+    proxy: Proxy<'static>,
+}
+
+pub(crate) struct FirmwareInfo1 {
+    proxy: Proxy<'static>,
+    versions: Mutex<Option<(String, String, HashMap<String, String>)>>,
+}
+
+struct GpuMetrics1 {}
+
 struct GpuPerformanceLevel1 {
     proxy: Proxy<'static>,
     driver: Box<dyn GpuPerformanceLevelDriver>,
+    available_levels: SysfsCache<Vec<String>>,
+    clocks_range: SysfsCache<RangeInclusive<u32>>,
 }
 
+struct GpuPower1 {}
+
 struct GpuPowerProfile1 {
     proxy: Proxy<'static>,
     driver: Box<dyn GpuPowerProfileDriver>,
+    available_profiles: SysfsCache<Vec<(u32, String)>>,
+}
+
+pub(crate) struct GpuThrottle1 {}
+
+struct Haptics1 {
+    proxy: Proxy<'static>,
+}
+
+struct IdlePolicy1 {
+    channel: Sender<Command>,
+}
+
+pub(crate) struct Thermal1 {}
+
+struct SuspendInhibit1 {
+    manager: UnboundedSender<SuspendInhibitCommand>,
+}
+
+struct SysfsAccess1 {
+    proxy: Proxy<'static>,
 }
 
 pub(crate) struct TdpLimit1 {
     manager: UnboundedSender<TdpManagerCommand>,
 }
 
-struct HdmiCec1 {
+struct TdpProfiles1 {
+    manager: UnboundedSender<TdpManagerCommand>,
+}
+
+struct TextInput1 {
+    input: TextInput,
+    locale: Locale1Proxy<'static>,
+}
+
+pub(crate) struct HdmiCec1 {
     hdmi_cec: HdmiCecControl<'static>,
 }
 
+struct InputInhibit1 {
+    manager: UnboundedSender<InputInhibitCommand>,
+}
+
+struct JobHistory1 {
+    proxy: Proxy<'static>,
+}
+
+struct LedControl1 {
+    proxy: Proxy<'static>,
+}
+
 struct LowPowerMode1 {
     manager: UnboundedSender<TdpManagerCommand>,
 }
 
+struct NetworkLimit1 {
+    proxy: Proxy<'static>,
+}
+
 struct Manager2 {
     proxy: Proxy<'static>,
     channel: Sender<Command>,
+    job_manager: UnboundedSender<JobManagerCommand>,
+}
+
+struct OsUpdate1 {
+    proxy: Proxy<'static>,
+    job_manager: UnboundedSender<JobManagerCommand>,
+}
+
+struct OsUpdateChannel1 {
+    proxy: Proxy<'static>,
 }
 
 struct PerformanceProfile1 {
@@ -166,13 +493,41 @@ struct PerformanceProfile1 {
     tdp_limit_manager: Option<UnboundedSender<TdpManagerCommand>>,
 }
 
-struct ScreenReader0 {
+struct PowerHistory1 {
+    manager: UnboundedSender<TelemetryCommand>,
+}
+
+pub(crate) struct PowerPresets1 {
+    channel: Sender<Command>,
+    // Held while a preset is applied or saved so that concurrent requests don't interleave
+    lock: Mutex<()>,
+}
+
+pub(crate) struct PowerSource1 {}
+
+pub(crate) struct ScreenReader0 {
     screen_reader: OrcaManager<'static>,
+    speaking: bool,
 }
 
-struct SessionManagement1 {
+pub(crate) struct SessionManagement1 {
     proxy: Proxy<'static>,
     manager: SessionManager,
+    // Only cached while the desktop session monitor keeps it up to date
+    desktop_sessions: Option<Vec<String>>,
+}
+
+pub(crate) struct Services1 {
+    proxy: Proxy<'static>,
+    system: Connection,
+}
+
+struct SleepConfig1 {
+    proxy: Proxy<'static>,
+}
+
+struct Smt1 {
+    proxy: Proxy<'static>,
 }
 
 struct Storage1 {
@@ -180,20 +535,47 @@ struct Storage1 {
     job_manager: UnboundedSender<JobManagerCommand>,
 }
 
+struct StorageHealth1 {
+    proxy: Proxy<'static>,
+}
+
+struct Status1 {
+    proxy: Proxy<'static>,
+    channel: Sender<Command>,
+}
+
 struct UpdateBios1 {
     proxy: Proxy<'static>,
     job_manager: UnboundedSender<JobManagerCommand>,
 }
 
+struct UpdateController1 {
+    proxy: Proxy<'static>,
+    job_manager: UnboundedSender<JobManagerCommand>,
+}
+
 struct UpdateDock1 {
     proxy: Proxy<'static>,
     job_manager: UnboundedSender<JobManagerCommand>,
 }
 
+struct UsbAuthorization1 {
+    proxy: Proxy<'static>,
+}
+
+struct WakeSources1 {
+    proxy: Proxy<'static>,
+}
+
 struct WifiDebug1 {
     proxy: Proxy<'static>,
 }
 
+#[derive(Default)]
+pub(crate) struct WifiInfo1 {
+    link: WifiLink,
+}
+
 struct WifiDebugDump1 {
     proxy: Proxy<'static>,
 }
@@ -205,6 +587,7 @@ struct WifiPowerManagement1 {
 pub(crate) struct SignalRelayService {
     proxy: Proxy<'static>,
     session: Connection,
+    job_manager: UnboundedSender<JobManagerCommand>,
 }
 
 impl SteamOSManager {
@@ -272,1106 +655,4793 @@ impl SteamOSManager {
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.AmbientLightSensor1")]
-impl AmbientLightSensor1 {
-    #[zbus(property(emits_changed_signal = "false"))]
-    async fn als_calibration_gain(&self) -> fdo::Result<Vec<f64>> {
-        getter!(self, "AlsCalibrationGain")
+#[interface(name = "com.steampowered.SteamOSManager1.Accessibility1")]
+impl Accessibility1 {
+    #[zbus(property)]
+    async fn high_contrast(&self) -> bool {
+        self.manager.high_contrast()
     }
-}
 
-impl BatteryChargeLimit1 {
-    const DEFAULT_SUGGESTED_MINIMUM_LIMIT: i32 = 10;
-}
+    #[zbus(property)]
+    async fn set_high_contrast(
+        &mut self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.manager
+            .set_high_contrast(enabled)
+            .map_err(to_zbus_fdo_error)?;
+        self.high_contrast_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
 
-#[interface(name = "com.steampowered.SteamOSManager1.BatteryChargeLimit1")]
-impl BatteryChargeLimit1 {
     #[zbus(property)]
-    async fn max_charge_level(&self) -> fdo::Result<i32> {
-        let level = get_max_charge_level().await.map_err(to_zbus_fdo_error)?;
-        if level <= 0 {
-            Ok(-1)
-        } else {
-            Ok(level)
-        }
+    async fn text_scaling_factor(&self) -> f64 {
+        self.manager.text_scaling_factor()
     }
 
     #[zbus(property)]
-    async fn set_max_charge_level(&self, limit: i32) -> zbus::Result<()> {
-        self.proxy.call("SetMaxChargeLevel", &(limit)).await
+    async fn set_text_scaling_factor(
+        &mut self,
+        factor: f64,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.manager
+            .set_text_scaling_factor(factor)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.text_scaling_factor_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)
     }
+}
 
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn suggested_minimum_limit(&self) -> i32 {
-        let Ok(Some(ref config)) = device_config().await else {
-            return BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT;
-        };
-        let Some(ref config) = config.battery_charge_limit else {
-            return BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT;
-        };
-        config
-            .suggested_minimum_limit
-            .unwrap_or(BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT)
+impl AmbientLightSensor1 {
+    pub(crate) async fn set_lux(
+        &mut self,
+        lux: Option<f64>,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        self.lux = lux;
+        if lux.is_none() {
+            return Ok(());
+        }
+        self.lux_changed(ctx).await
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.CpuBoost1")]
-impl CpuBoost1 {
+#[interface(name = "com.steampowered.SteamOSManager1.AmbientLightSensor1")]
+impl AmbientLightSensor1 {
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn als_calibration_gain(&self) -> fdo::Result<Vec<f64>> {
+        getter!(self, "AlsCalibrationGain")
+    }
+
     #[zbus(property)]
-    async fn cpu_boost_state(&self) -> fdo::Result<u32> {
-        match get_cpu_boost_state().await {
-            Ok(state) => Ok(state as u32),
-            Err(e) => Err(to_zbus_fdo_error(e)),
+    async fn lux(&self) -> fdo::Result<f64> {
+        if let Some(lux) = self.lux {
+            return Ok(lux);
         }
+        let sensor = find_als().await.map_err(to_zbus_fdo_error)?;
+        read_lux(sensor.as_path()).await.map_err(to_zbus_fdo_error)
     }
 
-    #[zbus(property)]
-    async fn set_cpu_boost_state(
-        &self,
-        state: u32,
-        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
-    ) -> zbus::Result<()> {
-        let _: () = self
-            .proxy
-            .call("SetCpuBoostState", &(state))
+    async fn set_lux_streaming(&self, enabled: bool) -> fdo::Result<()> {
+        if enabled {
+            find_als().await.map_err(to_zbus_fdo_error)?;
+        }
+        self.channel
+            .send(DaemonCommand::ContextCommand(UserCommand::SetAlsStreaming(
+                enabled,
+            )))
             .await
-            .map_err(to_zbus_fdo_error)?;
-        self.cpu_boost_state_changed(&ctx).await
+            .inspect_err(|message| error!("Error sending SetAlsStreaming command: {message}"))
+            .map_err(to_zbus_fdo_error)
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.CpuScaling1")]
-impl CpuScaling1 {
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn available_cpu_scaling_governors(&self) -> fdo::Result<Vec<String>> {
-        let governors = get_available_cpu_scaling_governors()
+impl Audio1 {
+    async fn limit_speaker_volume(&self, ctx: &SignalEmitter<'_>) -> zbus::Result<()> {
+        if self.speaker_limit_override {
+            return Ok(());
+        }
+        enforce_speaker_volume_limit()
             .await
-            .map_err(to_zbus_fdo_error)?;
-        Ok(governors.into_iter().map(|g| g.to_string()).collect())
+            .inspect_err(|message| error!("Error limiting speaker volume: {message}"))
+            .map_err(to_zbus_error)?;
+        self.output_volume_changed(ctx).await
     }
+}
 
+#[interface(name = "com.steampowered.SteamOSManager1.Audio1")]
+impl Audio1 {
     #[zbus(property)]
-    async fn cpu_scaling_governor(&self) -> fdo::Result<String> {
-        let governor = get_cpu_scaling_governor()
-            .await
-            .map_err(to_zbus_fdo_error)?;
-        Ok(governor.to_string())
+    async fn devices(&self) -> fdo::Result<Vec<(u32, String, String)>> {
+        let graph = audio_graph().await.map_err(to_zbus_fdo_error)?;
+        Ok(graph
+            .devices
+            .into_iter()
+            .map(|device| (device.id, device.name, device.description))
+            .collect())
     }
 
     #[zbus(property)]
-    async fn set_cpu_scaling_governor(
+    async fn outputs(&self) -> fdo::Result<Vec<(u32, u32, String, String)>> {
+        let graph = audio_graph().await.map_err(to_zbus_fdo_error)?;
+        Ok(graph
+            .outputs
+            .into_iter()
+            .map(|output| (output.id, output.device, output.name, output.description))
+            .collect())
+    }
+
+    #[zbus(property)]
+    async fn default_output(&self) -> fdo::Result<u32> {
+        let graph = audio_graph().await.map_err(to_zbus_fdo_error)?;
+        Ok(graph.default_output.unwrap_or_default())
+    }
+
+    #[zbus(property)]
+    async fn set_default_output(
         &self,
-        governor: String,
+        id: u32,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let _: () = self
-            .proxy
-            .call("SetCpuScalingGovernor", &(governor))
-            .await?;
-        self.cpu_scaling_governor_changed(&ctx).await
-    }
-}
-
-#[interface(name = "com.steampowered.SteamOSManager1.FactoryReset1")]
-impl FactoryReset1 {
-    async fn prepare_factory_reset(&self, flags: u32) -> fdo::Result<u32> {
-        method!(self, "PrepareFactoryReset", flags)
+        set_default_audio_output(id)
+            .await
+            .inspect_err(|message| error!("Error setting default audio output: {message}"))
+            .map_err(to_zbus_error)?;
+        self.default_output_changed(&ctx).await?;
+        self.limit_speaker_volume(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.FanControl1")]
-impl FanControl1 {
     #[zbus(property)]
-    async fn fan_control_state(&self) -> fdo::Result<u32> {
-        getter!(self, "FanControlState")
+    async fn output_volume(&self) -> fdo::Result<f64> {
+        get_audio_output_volume().await.map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn set_fan_control_state(
+    async fn set_output_volume(
         &self,
-        state: u32,
+        volume: f64,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let _: () = setter!(self, "FanControlState", state)?;
-        self.fan_control_state_changed(&ctx).await
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(
+                fdo::Error::InvalidArgs(format!("Output volume {volume} is out of range")).into(),
+            );
+        }
+        let volume = if self.speaker_limit_override {
+            volume
+        } else {
+            match output_volume_limit().await.map_err(to_zbus_error)? {
+                Some(limit) => volume.min(limit),
+                None => volume,
+            }
+        };
+        set_audio_output_volume(volume)
+            .await
+            .inspect_err(|message| error!("Error setting output volume: {message}"))
+            .map_err(to_zbus_error)?;
+        self.output_volume_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.GpuPerformanceLevel1")]
-impl GpuPerformanceLevel1 {
     #[zbus(property(emits_changed_signal = "const"))]
-    async fn available_gpu_performance_levels(&self) -> fdo::Result<Vec<String>> {
-        self.driver
-            .get_available_performance_levels()
-            .await
-            .inspect_err(|message| error!("Error getting GPU performance levels: {message}"))
-            .map(|levels| levels.into_iter().map(|level| level.to_string()).collect())
-            .map_err(to_zbus_fdo_error)
+    async fn max_speaker_volume(&self) -> fdo::Result<f64> {
+        get_max_speaker_volume().await.map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn gpu_performance_level(&self) -> fdo::Result<String> {
-        match self.driver.get_performance_level().await {
-            Ok(level) => Ok(level.to_string()),
-            Err(e) => {
-                error!("Error getting GPU performance level: {e}");
-                Err(to_zbus_fdo_error(e))
-            }
-        }
+    async fn speaker_volume_limit_override(&self) -> bool {
+        self.speaker_limit_override
     }
 
     #[zbus(property)]
-    async fn set_gpu_performance_level(
-        &self,
-        level: &str,
+    async fn set_speaker_volume_limit_override(
+        &mut self,
+        enabled: bool,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let _: () = self.proxy.call("SetGpuPerformanceLevel", &(level)).await?;
-        self.gpu_performance_level_changed(&ctx).await
+        if enabled
+            && !developer_mode_enabled(&self.system)
+                .await
+                .map_err(to_zbus_error)?
+        {
+            return Err(fdo::Error::AccessDenied(String::from(
+                "Overriding the speaker volume limit requires developer mode",
+            ))
+            .into());
+        }
+        self.speaker_limit_override = enabled;
+        self.speaker_volume_limit_override_changed(&ctx).await?;
+        self.limit_speaker_volume(&ctx).await
     }
 
     #[zbus(property)]
-    async fn manual_gpu_clock(&self) -> fdo::Result<u32> {
-        self.driver
-            .get_clocks()
-            .await
-            .inspect_err(|message| error!("Error getting manual GPU clock: {message}"))
-            .map_err(to_zbus_fdo_error)
+    async fn input_muted(&self) -> fdo::Result<bool> {
+        get_audio_input_muted().await.map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn set_manual_gpu_clock(
+    async fn set_input_muted(
         &self,
-        clocks: u32,
+        muted: bool,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let _: () = self.proxy.call("SetManualGpuClock", &(clocks)).await?;
-        self.manual_gpu_clock_changed(&ctx).await
+        set_audio_input_muted(muted)
+            .await
+            .inspect_err(|message| error!("Error setting microphone mute: {message}"))
+            .map_err(to_zbus_error)?;
+        self.input_muted_changed(&ctx).await
     }
 
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn manual_gpu_clock_min(&self) -> fdo::Result<u32> {
-        Ok(*self
-            .driver
-            .get_clocks_range()
-            .await
-            .map_err(to_zbus_fdo_error)?
-            .start())
+    #[zbus(property)]
+    async fn input_volume(&self) -> fdo::Result<f64> {
+        get_audio_input_volume().await.map_err(to_zbus_fdo_error)
     }
 
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn manual_gpu_clock_max(&self) -> fdo::Result<u32> {
-        Ok(*self
-            .driver
-            .get_clocks_range()
+    #[zbus(property)]
+    async fn set_input_volume(
+        &self,
+        volume: f64,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(
+                fdo::Error::InvalidArgs(format!("Input volume {volume} is out of range")).into(),
+            );
+        }
+        set_audio_input_volume(volume)
             .await
-            .map_err(to_zbus_fdo_error)?
-            .end())
+            .inspect_err(|message| error!("Error setting microphone volume: {message}"))
+            .map_err(to_zbus_error)?;
+        self.input_volume_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.GpuPowerProfile1")]
-impl GpuPowerProfile1 {
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn available_gpu_power_profiles(&self) -> fdo::Result<Vec<String>> {
-        let (_, names): (Vec<u32>, Vec<String>) = self
-            .driver
-            .get_available_power_profiles()
-            .await
-            .map_err(to_zbus_fdo_error)?
-            .into_iter()
-            .unzip();
-        Ok(names)
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn hardware_input_muted(&self) -> fdo::Result<bool> {
+        get_hardware_input_muted().await.map_err(to_zbus_fdo_error)
     }
 
-    #[zbus(property)]
-    async fn gpu_power_profile(&self) -> fdo::Result<String> {
-        match self.driver.get_power_profile().await {
-            Ok(profile) => Ok(profile.to_string()),
-            Err(e) => {
-                error!("Error getting GPU power profile: {e}");
-                Err(to_zbus_fdo_error(e))
-            }
-        }
+    async fn get_profiles(
+        &self,
+        device: u32,
+    ) -> fdo::Result<Vec<(u32, String, String, bool, bool)>> {
+        let graph = audio_graph().await.map_err(to_zbus_fdo_error)?;
+        let device = graph
+            .devices
+            .into_iter()
+            .find(|info| info.id == device)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("No audio device with ID {device}")))?;
+        Ok(device
+            .profiles
+            .into_iter()
+            .map(|profile| {
+                let active = device.active_profile == Some(profile.index);
+                (
+                    profile.index,
+                    profile.name,
+                    profile.description,
+                    profile.available,
+                    active,
+                )
+            })
+            .collect())
     }
 
-    #[zbus(property)]
-    async fn set_gpu_power_profile(
+    async fn set_profile(
         &self,
-        profile: &str,
+        device: u32,
+        profile: u32,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
-    ) -> zbus::Result<()> {
-        let _: () = self.proxy.call("SetGpuPowerProfile", &(profile)).await?;
-        self.gpu_power_profile_changed(&ctx).await
+    ) -> fdo::Result<()> {
+        set_audio_device_profile(device, profile)
+            .await
+            .inspect_err(|message| error!("Error setting audio profile: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        // Switching profiles adds and removes outputs, which can move the default one
+        self.outputs_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.default_output_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.limit_speaker_volume(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)
     }
 }
 
-impl HdmiCec1 {
-    async fn new(connection: &Connection) -> Result<HdmiCec1> {
-        let hdmi_cec = HdmiCecControl::new(connection).await?;
-        Ok(HdmiCec1 { hdmi_cec })
+impl Battery1 {
+    /// Emit changes for every property on its own, since not every battery reports all of them
+    pub(crate) async fn battery_changed(&self, ctx: &SignalEmitter<'_>) {
+        let results = [
+            ("Capacity", self.capacity_changed(ctx).await),
+            ("ChargeState", self.charge_state_changed(ctx).await),
+            ("Health", self.health_changed(ctx).await),
+            ("CycleCount", self.cycle_count_changed(ctx).await),
+            ("ChargeNow", self.charge_now_changed(ctx).await),
+            ("ChargeFull", self.charge_full_changed(ctx).await),
+        ];
+        for (property, result) in results {
+            if let Err(e) = result {
+                debug!("Could not emit change of battery {property}: {e}");
+            }
+        }
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.HdmiCec1")]
-impl HdmiCec1 {
+#[interface(name = "com.steampowered.SteamOSManager1.Backlight1")]
+impl Backlight1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn devices(&self) -> fdo::Result<Vec<String>> {
+        list_backlights().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn device(&self) -> String {
+        self.device.clone()
+    }
+
     #[zbus(property)]
-    async fn hdmi_cec_state(&self) -> fdo::Result<u32> {
-        match self.hdmi_cec.get_enabled_state().await {
-            Ok(state) => Ok(state as u32),
-            Err(e) => Err(to_zbus_fdo_error(e)),
-        }
+    async fn brightness(&self) -> fdo::Result<u32> {
+        get_brightness(self.device.as_str())
+            .await
+            .map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn set_hdmi_cec_state(
+    async fn set_brightness(
         &self,
-        state: u32,
+        brightness: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let state = match HdmiCecState::try_from(state) {
-            Ok(state) => state,
-            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string()).into()),
-        };
+        throttle_setter::<Self>(header.as_ref(), "Brightness").await?;
         let _: () = self
-            .hdmi_cec
-            .set_enabled_state(state)
-            .await
-            .inspect_err(|message| error!("Error setting CEC state: {message}"))
-            .map_err(to_zbus_error)?;
-        self.hdmi_cec_state_changed(&ctx).await
+            .proxy
+            .call("SetBrightness", &(self.device.as_str(), brightness))
+            .await?;
+        self.brightness_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.LowPowerMode1")]
-impl LowPowerMode1 {
-    async fn enter_download_mode(&self, identifier: &str) -> fdo::Result<Fd> {
-        let (tx, rx) = oneshot::channel();
-        self.manager
-            .send(TdpManagerCommand::EnterDownloadMode(
-                identifier.to_string(),
-                tx,
-            ))
-            .map_err(|_| {
-                fdo::Error::Failed(String::from("Failed to obtain download mode handle"))
-            })?;
-        Ok(rx
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn max_brightness(&self) -> fdo::Result<u32> {
+        get_max_brightness(self.device.as_str())
             .await
-            .map_err(to_zbus_fdo_error)?
-            .map_err(to_zbus_fdo_error)?
-            .ok_or(fdo::Error::Failed(String::from(
-                "Download mode not configured",
-            )))?
-            .into())
+            .map_err(to_zbus_fdo_error)
     }
 
-    async fn list_download_mode_handles(&self) -> fdo::Result<HashMap<String, u32>> {
-        let (tx, rx) = oneshot::channel();
-        self.manager
-            .send(TdpManagerCommand::ListDownloadModeHandles(tx))
-            .map_err(|_| {
-                fdo::Error::Failed(String::from("Failed to obtain download mode handle list"))
-            })?;
-        rx.await.map_err(to_zbus_fdo_error)
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn brightness_step(&self) -> fdo::Result<u32> {
+        let max = get_max_brightness(self.device.as_str())
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(brightness_step(max))
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.Manager2")]
-impl Manager2 {
-    async fn reload_config(&self) -> fdo::Result<()> {
-        self.channel
-            .send(DaemonCommand::ReadConfig)
+    async fn step_brightness(
+        &self,
+        steps: i32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<u32> {
+        let max = get_max_brightness(self.device.as_str())
             .await
-            .inspect_err(|message| error!("Error sending ReadConfig command: {message}"))
             .map_err(to_zbus_fdo_error)?;
-        method!(self, "ReloadConfig")
+        let current = get_brightness(self.device.as_str())
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        let delta = i64::from(steps) * i64::from(brightness_step(max));
+        let brightness = (i64::from(current) + delta).clamp(0, i64::from(max)) as u32;
+        let _: () = method!(self, "SetBrightness", self.device.as_str(), brightness)?;
+        self.brightness_changed(&ctx).await?;
+        Ok(brightness)
     }
+}
 
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn device_model(&self) -> fdo::Result<(String, String)> {
-        let (device, variant) = device_variant().await.map_err(to_zbus_fdo_error)?;
-        Ok((device.to_string(), variant))
+#[interface(name = "com.steampowered.SteamOSManager1.Battery1")]
+impl Battery1 {
+    #[zbus(property)]
+    async fn capacity(&self) -> fdo::Result<u32> {
+        get_battery_capacity().await.map_err(to_zbus_fdo_error)
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.PerformanceProfile1")]
-impl PerformanceProfile1 {
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn available_performance_profiles(&self) -> fdo::Result<Vec<String>> {
-        let config = device_config().await.map_err(to_zbus_fdo_error)?;
-        let config = config
-            .as_ref()
-            .and_then(|config| config.performance_profile.as_ref())
-            .ok_or(fdo::Error::Failed(String::from(
-                "No performance platform-profile configured",
-            )))?;
-        get_available_platform_profiles(&config.platform_profile_name)
-            .await
-            .map_err(to_zbus_fdo_error)
+    #[zbus(property)]
+    async fn charge_state(&self) -> fdo::Result<u32> {
+        match get_battery_charge_state().await {
+            Ok(state) => Ok(state as u32),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
     }
 
     #[zbus(property)]
-    async fn performance_profile(&self) -> fdo::Result<String> {
-        let config = device_config().await.map_err(to_zbus_fdo_error)?;
-        let config = config
-            .as_ref()
-            .and_then(|config| config.performance_profile.as_ref())
-            .ok_or(fdo::Error::Failed(String::from(
-                "No performance platform-profile configured",
-            )))?;
-        get_platform_profile(&config.platform_profile_name)
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn health(&self) -> fdo::Result<u32> {
+        match get_battery_health().await {
+            Ok(health) => Ok(health as u32),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
     }
 
     #[zbus(property)]
-    async fn set_performance_profile(
-        &self,
-        profile: &str,
-        #[zbus(connection)] connection: &Connection,
-        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
-    ) -> zbus::Result<()> {
-        let _: () = self.proxy.call("SetPerformanceProfile", &(profile)).await?;
-        self.performance_profile_changed(&ctx).await?;
-        let connection = connection.clone();
-        if let Some(manager) = self.tdp_limit_manager.as_ref() {
-            let manager = manager.clone();
-            let _ = manager.send(TdpManagerCommand::UpdateDownloadMode);
-            tokio::spawn(async move {
-                let (tx, rx) = oneshot::channel();
-                manager.send(TdpManagerCommand::IsActive(tx))?;
-                if rx.await?? {
-                    let tdp_limit = TdpLimit1 { manager };
-                    connection
-                        .object_server()
-                        .at(MANAGER_PATH, tdp_limit)
-                        .await?;
-                } else {
-                    connection
-                        .object_server()
-                        .remove::<TdpLimit1, _>(MANAGER_PATH)
-                        .await?;
-                }
-                Ok::<(), Error>(())
-            });
-        }
-        Ok(())
+    async fn cycle_count(&self) -> fdo::Result<u32> {
+        get_battery_cycle_count().await.map_err(to_zbus_fdo_error)
     }
 
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn suggested_default_performance_profile(&self) -> fdo::Result<String> {
-        let config = device_config().await.map_err(to_zbus_fdo_error)?;
-        let config = config
-            .as_ref()
-            .and_then(|config| config.performance_profile.as_ref())
-            .ok_or(fdo::Error::Failed(String::from(
-                "No performance platform-profile configured",
-            )))?;
-        Ok(config.suggested_default.to_string())
+    #[zbus(property)]
+    async fn charge_now(&self) -> fdo::Result<u64> {
+        get_battery_charge_now().await.map_err(to_zbus_fdo_error)
     }
-}
 
-impl ScreenReader0 {
-    async fn new(connection: &Connection) -> Result<ScreenReader0> {
-        let screen_reader = OrcaManager::new(connection).await?;
-        Ok(ScreenReader0 { screen_reader })
+    #[zbus(property)]
+    async fn charge_full(&self) -> fdo::Result<u64> {
+        get_battery_charge_full().await.map_err(to_zbus_fdo_error)
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.ScreenReader0")]
-impl ScreenReader0 {
-    #[zbus(property)]
-    async fn enabled(&self) -> bool {
-        self.screen_reader.enabled()
-    }
+impl BatteryChargeLimit1 {
+    const DEFAULT_SUGGESTED_MINIMUM_LIMIT: i32 = 10;
+}
 
+#[interface(name = "com.steampowered.SteamOSManager1.BatteryChargeLimit1")]
+impl BatteryChargeLimit1 {
     #[zbus(property)]
-    async fn set_enabled(&mut self, enabled: bool) -> fdo::Result<()> {
-        self.screen_reader
-            .set_enabled(enabled)
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn max_charge_level(&self) -> fdo::Result<i32> {
+        let level = get_max_charge_level().await.map_err(to_zbus_fdo_error)?;
+        if level <= 0 {
+            Ok(-1)
+        } else {
+            Ok(level)
+        }
     }
 
     #[zbus(property)]
-    async fn rate(&self) -> f64 {
-        self.screen_reader.rate()
+    async fn set_max_charge_level(&self, limit: i32) -> zbus::Result<()> {
+        self.proxy.call("SetMaxChargeLevel", &(limit)).await
     }
 
     #[zbus(property)]
-    async fn set_rate(&mut self, rate: f64) -> fdo::Result<()> {
-        self.screen_reader
-            .set_rate(rate)
+    async fn charge_bypass(&self) -> fdo::Result<bool> {
+        if !is_charge_bypass_supported().await {
+            return Ok(false);
+        }
+        get_charge_bypass()
             .await
+            .inspect_err(|message| error!("Error getting charge bypass: {message}"))
             .map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn pitch(&self) -> f64 {
-        self.screen_reader.pitch()
+    async fn set_charge_bypass(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetChargeBypass", &(enabled)).await?;
+        self.charge_bypass_changed(&ctx).await
     }
 
-    #[zbus(property)]
-    async fn set_pitch(&mut self, pitch: f64) -> fdo::Result<()> {
-        self.screen_reader
-            .set_pitch(pitch)
-            .await
-            .map_err(to_zbus_fdo_error)
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn charge_bypass_supported(&self) -> bool {
+        is_charge_bypass_supported().await
     }
 
-    #[zbus(property)]
-    async fn volume(&self) -> f64 {
-        self.screen_reader.volume()
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn suggested_minimum_limit(&self) -> i32 {
+        let Ok(Some(ref config)) = device_config().await else {
+            return BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT;
+        };
+        let Some(ref config) = config.battery_charge_limit else {
+            return BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT;
+        };
+        config
+            .suggested_minimum_limit
+            .unwrap_or(BatteryChargeLimit1::DEFAULT_SUGGESTED_MINIMUM_LIMIT)
+    }
+}
+
+impl BatteryPolicy1 {
+    pub(crate) async fn set_low_battery(
+        &mut self,
+        low_battery: bool,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if self.low_battery == low_battery {
+            return Ok(());
+        }
+        self.low_battery = low_battery;
+        self.low_battery_changed(ctx).await
     }
+}
 
+#[interface(name = "com.steampowered.SteamOSManager1.BatteryPolicy1")]
+impl BatteryPolicy1 {
     #[zbus(property)]
-    async fn set_volume(&mut self, volume: f64) -> fdo::Result<()> {
-        self.screen_reader
-            .set_volume(volume)
+    async fn low_battery(&self) -> bool {
+        self.low_battery
+    }
+
+    #[zbus(signal)]
+    async fn critical_battery(
+        signal_emitter: &SignalEmitter<'_>,
+        capacity: u32,
+    ) -> zbus::Result<()>;
+}
+
+impl ColorFilters1 {
+    async fn state(&self) -> Result<ColorFiltersState> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(UserCommand::GetColorFilters(
+                tx,
+            )))
+            .await?;
+        Ok(rx.await?)
+    }
+
+    async fn set_state(&self, state: ColorFiltersState) -> Result<()> {
+        apply_color_filters(&self.session, &state).await?;
+        Ok(self
+            .channel
+            .send(DaemonCommand::ContextCommand(UserCommand::SetColorFilters(
+                state,
+            )))
+            .await?)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.ChargeLed1")]
+impl ChargeLed1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_modes(&self) -> fdo::Result<Vec<String>> {
+        Ok(get_available_charge_led_modes()
             .await
-            .map_err(to_zbus_fdo_error)
+            .map_err(to_zbus_fdo_error)?
+            .into_iter()
+            .map(|mode| mode.to_string())
+            .collect())
     }
 
     #[zbus(property)]
-    async fn mode(&self) -> u32 {
-        self.screen_reader.mode() as u32
+    async fn mode(&self) -> fdo::Result<String> {
+        get_charge_led_mode()
+            .await
+            .inspect_err(|message| error!("Error getting charge LED mode: {message}"))
+            .map(|mode| mode.to_string())
+            .map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
     async fn set_mode(
-        &mut self,
-        m: u32,
+        &self,
+        mode: &str,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
-    ) -> fdo::Result<()> {
-        let mode = match ScreenReaderMode::try_from(m) {
-            Ok(mode) => mode,
-            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string())),
-        };
-        self.screen_reader
-            .set_mode(mode)
-            .await
-            .map_err(to_zbus_fdo_error)?;
-        self.mode_changed(&ctx).await.map_err(to_zbus_fdo_error)
+    ) -> zbus::Result<()> {
+        let mode = ChargeLedMode::try_from(mode)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?
+            .to_string();
+        let _: () = self.proxy.call("SetChargeLedMode", &(mode)).await?;
+        self.mode_changed(&ctx).await
     }
+}
 
+#[interface(name = "com.steampowered.SteamOSManager1.ColorFilters1")]
+impl ColorFilters1 {
     #[zbus(property)]
-    async fn voice(&self) -> &str {
-        self.screen_reader.voice()
+    async fn filter(&self) -> fdo::Result<u32> {
+        Ok(self.state().await.map_err(to_zbus_fdo_error)?.filter as u32)
     }
 
     #[zbus(property)]
-    async fn set_voice(
-        &mut self,
-        voice: &str,
+    async fn set_filter(
+        &self,
+        filter: u32,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
-    ) -> fdo::Result<()> {
-        self.screen_reader
-            .set_voice(voice)
-            .await
-            .map_err(to_zbus_fdo_error)?;
-        self.voice_changed(&ctx).await.map_err(to_zbus_fdo_error)
+    ) -> zbus::Result<()> {
+        let filter =
+            ColorFilter::try_from(filter).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let mut state = self.state().await.map_err(to_zbus_error)?;
+        state.filter = filter;
+        self.set_state(state).await.map_err(to_zbus_error)?;
+        self.filter_changed(&ctx).await
     }
 
     #[zbus(property)]
-    async fn voice_locales(&self) -> Vec<&str> {
-        self.screen_reader.get_voice_locales()
+    async fn night_light(&self) -> fdo::Result<bool> {
+        Ok(self.state().await.map_err(to_zbus_fdo_error)?.night_light)
     }
 
     #[zbus(property)]
-    async fn voices_for_locale(&self) -> HashMap<String, Vec<String>> {
-        self.screen_reader.get_voices().clone()
-    }
-
-    async fn trigger_action(&mut self, a: u32, timestamp: u64) -> fdo::Result<()> {
-        let action = match ScreenReaderAction::try_from(a) {
-            Ok(action) => action,
-            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string())),
-        };
-        self.screen_reader
-            .trigger_action(action, timestamp)
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn set_night_light(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mut state = self.state().await.map_err(to_zbus_error)?;
+        state.night_light = enabled;
+        self.set_state(state).await.map_err(to_zbus_error)?;
+        self.night_light_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.SessionManagement1")]
-impl SessionManagement1 {
     #[zbus(property)]
-    async fn default_login_mode(&self) -> fdo::Result<String> {
+    async fn night_light_temperature(&self) -> fdo::Result<u32> {
         Ok(self
-            .manager
-            .default_login_mode()
+            .state()
             .await
             .map_err(to_zbus_fdo_error)?
-            .to_string())
+            .night_light_temperature)
     }
 
     #[zbus(property)]
-    async fn set_default_login_mode(&mut self, login_mode: &str) -> fdo::Result<()> {
-        let login_mode = LoginMode::try_from(login_mode).map_err(to_zbus_fdo_error)?;
-        self.manager
-            .set_default_login_mode(login_mode)
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn set_night_light_temperature(
+        &self,
+        temperature: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if !NIGHT_LIGHT_TEMPERATURE_RANGE.contains(&temperature) {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Night light temperature {temperature} out of range"
+            ))
+            .into());
+        }
+        let mut state = self.state().await.map_err(to_zbus_error)?;
+        state.night_light_temperature = temperature;
+        self.set_state(state).await.map_err(to_zbus_error)?;
+        self.night_light_temperature_changed(&ctx).await
     }
+}
 
+#[interface(name = "com.steampowered.SteamOSManager1.CpuBoost1")]
+impl CpuBoost1 {
     #[zbus(property)]
-    async fn default_desktop_session(&self) -> fdo::Result<String> {
-        self.manager
-            .default_desktop_session()
-            .await
-            .map_err(to_zbus_fdo_error)
+    async fn cpu_boost_state(&self) -> fdo::Result<u32> {
+        match get_cpu_boost_state().await {
+            Ok(state) => Ok(state as u32),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
     }
 
     #[zbus(property)]
-    async fn set_default_desktop_session(&mut self, session: &str) -> fdo::Result<()> {
-        self.manager
-            .set_default_desktop_session(session)
+    async fn set_cpu_boost_state(
+        &self,
+        state: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self
+            .proxy
+            .call("SetCpuBoostState", &(state))
             .await
-            .map_err(to_zbus_fdo_error)
+            .map_err(to_zbus_fdo_error)?;
+        self.cpu_boost_state_changed(&ctx).await
     }
+}
 
-    async fn switch_to_login_mode(&self, login_mode: &str) -> fdo::Result<()> {
-        let login_mode = LoginMode::try_from(login_mode).map_err(to_zbus_fdo_error)?;
-        self.manager
-            .switch_to_login_mode(login_mode)
+#[interface(name = "com.steampowered.SteamOSManager1.CpuFrequency1")]
+impl CpuFrequency1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_frequencies(&self) -> fdo::Result<Vec<u32>> {
+        get_available_cpu_frequencies()
             .await
             .map_err(to_zbus_fdo_error)
     }
 
-    async fn switch_to_game_mode(&self) -> fdo::Result<()> {
-        self.manager
-            .switch_to_login_mode(LoginMode::Game)
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn hardware_min_frequency(&self) -> fdo::Result<u32> {
+        Ok(*get_cpu_frequency_range()
             .await
-            .map_err(to_zbus_fdo_error)
+            .map_err(to_zbus_fdo_error)?
+            .start())
     }
 
-    async fn switch_to_desktop_mode(&self) -> fdo::Result<()> {
-        self.manager
-            .switch_to_login_mode(LoginMode::Desktop)
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn hardware_max_frequency(&self) -> fdo::Result<u32> {
+        Ok(*get_cpu_frequency_range()
             .await
-            .map_err(to_zbus_fdo_error)
+            .map_err(to_zbus_fdo_error)?
+            .end())
     }
 
-    async fn valid_desktop_sessions(&self) -> fdo::Result<Vec<String>> {
-        valid_desktop_sessions().await.map_err(to_zbus_fdo_error)
+    #[zbus(property)]
+    async fn min_frequency(&self) -> fdo::Result<u32> {
+        get_min_cpu_frequency().await.map_err(to_zbus_fdo_error)
     }
 
-    async fn clean_temporary_sessions(&self) -> fdo::Result<()> {
-        method!(self, "CleanTemporarySessions")
+    #[zbus(property)]
+    async fn set_min_frequency(
+        &self,
+        frequency: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        throttle_setter::<Self>(header.as_ref(), "MinFrequency").await?;
+        let _: () = self.proxy.call("SetMinCpuFrequency", &(frequency)).await?;
+        self.min_frequency_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.Storage1")]
-impl Storage1 {
-    async fn format_device(
-        &mut self,
-        device: &str,
-        label: &str,
-        validate: bool,
-    ) -> fdo::Result<zvariant::OwnedObjectPath> {
-        job_method!(self, "FormatDevice", device, label, validate)
+    #[zbus(property)]
+    async fn max_frequency(&self) -> fdo::Result<u32> {
+        get_max_cpu_frequency().await.map_err(to_zbus_fdo_error)
     }
 
-    async fn trim_devices(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        job_method!(self, "TrimDevices")
+    #[zbus(property)]
+    async fn set_max_frequency(
+        &self,
+        frequency: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        throttle_setter::<Self>(header.as_ref(), "MaxFrequency").await?;
+        let _: () = self.proxy.call("SetMaxCpuFrequency", &(frequency)).await?;
+        self.max_frequency_changed(&ctx).await
     }
 }
 
-#[interface(name = "com.steampowered.SteamOSManager1.TdpLimit1")]
-impl TdpLimit1 {
-    #[zbus(property)]
-    async fn tdp_limit(&self) -> u32 {
-        let (tx, rx) = oneshot::channel();
-        if self
-            .manager
-            .send(TdpManagerCommand::GetTdpLimit(tx))
-            .is_err()
-        {
-            return 0;
-        }
-        rx.await.unwrap_or(Ok(0)).unwrap_or(0)
-    }
-
-    #[zbus(property)]
-    async fn set_tdp_limit(&self, limit: u32) -> zbus::Result<()> {
-        self.manager
-            .send(TdpManagerCommand::SetTdpLimit(limit))
-            .map_err(|_| zbus::Error::Failure(String::from("Failed to set TDP limit")))
-    }
-
-    #[zbus(property(emits_changed_signal = "const"))]
-    async fn tdp_limit_min(&self) -> u32 {
-        let (tx, rx) = oneshot::channel();
-        if self
-            .manager
-            .send(TdpManagerCommand::GetTdpLimitRange(tx))
-            .is_err()
-        {
-            return 0;
-        }
-        if let Ok(range) = rx.await {
-            range.map(|r| *r.start()).unwrap_or(0)
-        } else {
-            0
-        }
-    }
-
+#[interface(name = "com.steampowered.SteamOSManager1.CpuScaling1")]
+impl CpuScaling1 {
     #[zbus(property(emits_changed_signal = "const"))]
-    async fn tdp_limit_max(&self) -> u32 {
-        let (tx, rx) = oneshot::channel();
-        if self
-            .manager
-            .send(TdpManagerCommand::GetTdpLimitRange(tx))
-            .is_err()
-        {
-            return 0;
-        }
-        if let Ok(range) = rx.await {
-            range.map(|r| *r.end()).unwrap_or(0)
-        } else {
-            0
-        }
+    async fn available_cpu_scaling_governors(&self) -> fdo::Result<Vec<String>> {
+        let governors = get_available_cpu_scaling_governors()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(governors.into_iter().map(|g| g.to_string()).collect())
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.UpdateBios1")]
-impl UpdateBios1 {
-    async fn update_bios(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        job_method!(self, "UpdateBios")
+    #[zbus(property)]
+    async fn cpu_scaling_governor(&self) -> fdo::Result<String> {
+        let governor = get_cpu_scaling_governor()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(governor.to_string())
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.UpdateDock1")]
-impl UpdateDock1 {
-    async fn update_dock(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
-        job_method!(self, "UpdateDock")
+    #[zbus(property)]
+    async fn set_cpu_scaling_governor(
+        &self,
+        governor: String,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self
+            .proxy
+            .call("SetCpuScalingGovernor", &(governor))
+            .await?;
+        self.cpu_scaling_governor_changed(&ctx).await?;
+        self.cpu_scaling_governors_changed(&ctx).await
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.WifiDebug1")]
-impl WifiDebug1 {
     #[zbus(property)]
-    async fn wifi_debug_mode_state(&self) -> fdo::Result<u32> {
-        getter!(self, "WifiDebugModeState")
+    async fn cpu_scaling_governors(&self) -> fdo::Result<HashMap<u32, String>> {
+        let governors = get_cpu_scaling_governors()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(governors
+            .into_iter()
+            .map(|(policy, governor)| (policy, governor.to_string()))
+            .collect())
     }
 
-    async fn set_wifi_debug_mode(
+    async fn set_cpu_scaling_governor_for_policy(
         &self,
-        mode: u32,
-        options: HashMap<&str, zvariant::Value<'_>>,
+        policy: u32,
+        governor: String,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> fdo::Result<()> {
-        let _: () = method!(self, "SetWifiDebugMode", mode, options)?;
-        self.wifi_debug_mode_state_changed(&ctx)
+        let _: () = method!(self, "SetCpuScalingGovernorForPolicy", policy, governor)?;
+        self.cpu_scaling_governor_changed(&ctx)
             .await
             .map_err(zbus_to_zbus_fdo)?;
-        Ok(())
+        self.cpu_scaling_governors_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_energy_performance_preferences(&self) -> fdo::Result<Vec<String>> {
+        let preferences = get_available_energy_performance_preferences()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(preferences.into_iter().map(|p| p.to_string()).collect())
     }
 
     #[zbus(property)]
-    async fn wifi_backend(&self) -> fdo::Result<String> {
-        match get_wifi_backend().await {
-            Ok(backend) => Ok(backend.to_string()),
-            Err(e) => Err(to_zbus_fdo_error(e)),
-        }
+    async fn energy_performance_preference(&self) -> fdo::Result<String> {
+        let preference = get_energy_performance_preference()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(preference.to_string())
     }
 
     #[zbus(property)]
-    async fn set_wifi_backend(
+    async fn set_energy_performance_preference(
         &self,
-        backend: &str,
+        preference: String,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let backend = match WifiBackend::try_from(backend) {
-            Ok(backend) => backend,
-            Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string()).into()),
-        };
-        let _: () = self.proxy.call("SetWifiBackend", &(backend as u32)).await?;
-        self.wifi_backend_changed(&ctx).await
+        let _: () = self
+            .proxy
+            .call("SetEnergyPerformancePreference", &(preference))
+            .await?;
+        self.energy_performance_preference_changed(&ctx).await
     }
 
-    async fn capture_debug_trace_output(&self) -> fdo::Result<String> {
-        method!(self, "CaptureDebugTraceOutput")
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn max_online_cpu_count(&self) -> fdo::Result<u32> {
+        get_max_online_cpu_count().await.map_err(to_zbus_fdo_error)
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.WifiDebugDump1")]
-impl WifiDebugDump1 {
-    async fn generate_debug_dump(&self) -> fdo::Result<String> {
-        method!(self, "GenerateDebugDump")
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn min_online_cpu_count(&self) -> fdo::Result<u32> {
+        get_min_online_cpu_count().await.map_err(to_zbus_fdo_error)
     }
-}
 
-#[interface(name = "com.steampowered.SteamOSManager1.WifiPowerManagement1")]
-impl WifiPowerManagement1 {
     #[zbus(property)]
-    async fn wifi_power_management_state(&self) -> fdo::Result<u32> {
-        match get_wifi_power_management_state().await {
-            Ok(state) => Ok(state as u32),
-            Err(e) => Err(to_zbus_fdo_error(e)),
-        }
+    async fn online_cpu_count(&self) -> fdo::Result<u32> {
+        get_online_cpu_count().await.map_err(to_zbus_fdo_error)
     }
 
     #[zbus(property)]
-    async fn set_wifi_power_management_state(
+    async fn set_online_cpu_count(
         &self,
-        state: u32,
+        count: u32,
         #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
     ) -> zbus::Result<()> {
-        let _: () = self
-            .proxy
-            .call("SetWifiPowerManagementState", &(state))
-            .await?;
-        self.wifi_power_management_state_changed(&ctx).await
+        let _: () = self.proxy.call("SetOnlineCpuCount", &(count)).await?;
+        self.online_cpu_count_changed(&ctx).await
     }
 }
 
-impl Service for SignalRelayService {
-    const NAME: &'static str = "signal-relay";
-
-    async fn run(&mut self) -> Result<()> {
-        let Ok(battery_charge_limit) = self
-            .session
-            .object_server()
-            .interface::<_, BatteryChargeLimit1>(MANAGER_PATH)
+impl DeveloperMode1 {
+    async fn enabled(&self, feature: DeveloperFeature) -> fdo::Result<bool> {
+        developer_feature_enabled(&self.system, feature)
             .await
-        else {
-            return Ok(());
-        };
-        let ctx = battery_charge_limit.signal_emitter();
+            .inspect_err(|message| error!("Error getting developer mode {feature}: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
 
-        let mut max_charge_level_changed =
-            self.proxy.receive_signal("MaxChargeLevelChanged").await?;
-        loop {
-            max_charge_level_changed.next().await;
-            battery_charge_limit
-                .get()
-                .await
-                .max_charge_level_changed(ctx)
-                .await?;
-        }
+    async fn set_enabled(&self, feature: DeveloperFeature, enabled: bool) -> zbus::Result<()> {
+        self.proxy
+            .call("SetDeveloperModeFeature", &(feature.to_string(), enabled))
+            .await
     }
 }
 
-async fn create_platform_interfaces(
-    proxy: &Proxy<'static>,
-    object_server: &ObjectServer,
-    connection: &Connection,
-    job_manager: &UnboundedSender<JobManagerCommand>,
-) -> Result<()> {
-    let Some(config) = platform_config().await? else {
-        return Ok(());
-    };
+#[interface(name = "com.steampowered.SteamOSManager1.DeveloperMode1")]
+impl DeveloperMode1 {
+    #[zbus(property)]
+    async fn core_dumps_enabled(&self) -> fdo::Result<bool> {
+        self.enabled(DeveloperFeature::CoreDumps).await
+    }
 
-    let factory_reset = FactoryReset1 {
-        proxy: proxy.clone(),
-    };
-    let fan_control = FanControl1 {
-        proxy: proxy.clone(),
-    };
-    let storage = Storage1 {
-        proxy: proxy.clone(),
-        job_manager: job_manager.clone(),
-    };
-    let update_bios = UpdateBios1 {
-        proxy: proxy.clone(),
-        job_manager: job_manager.clone(),
-    };
-    let update_dock = UpdateDock1 {
-        proxy: proxy.clone(),
-        job_manager: job_manager.clone(),
-    };
+    #[zbus(property)]
+    async fn set_core_dumps_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            header.as_ref(),
+            SET_DEVELOPER_MODE_ACTION,
+        )
+        .await?;
+        self.set_enabled(DeveloperFeature::CoreDumps, enabled)
+            .await?;
+        self.core_dumps_enabled_changed(&ctx).await
+    }
 
-    if let Some(config) = config.factory_reset.as_ref() {
-        match config.is_valid(true).await {
-            Ok(true) => {
-                object_server.at(MANAGER_PATH, factory_reset).await?;
-            }
-            Ok(false) => (),
-            Err(e) => error!("Failed to verify if factory reset config is valid: {e}"),
-        }
+    #[zbus(property)]
+    async fn persistent_journal(&self) -> fdo::Result<bool> {
+        self.enabled(DeveloperFeature::PersistentJournal).await
     }
 
-    if let Some(config) = config.fan_control.as_ref() {
-        match config.is_valid(connection, true).await {
-            Ok(true) => {
-                object_server.at(MANAGER_PATH, fan_control).await?;
-            }
-            Ok(false) => (),
-            Err(e) => error!("Failed to verify if fan control config is valid: {e}"),
-        }
+    #[zbus(property)]
+    async fn set_persistent_journal(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            header.as_ref(),
+            SET_DEVELOPER_MODE_ACTION,
+        )
+        .await?;
+        self.set_enabled(DeveloperFeature::PersistentJournal, enabled)
+            .await?;
+        self.persistent_journal_changed(&ctx).await
     }
 
-    if let Some(config) = config.storage.as_ref() {
-        match config.is_valid(true).await {
-            Ok(true) => {
-                object_server.at(MANAGER_PATH, storage).await?;
-            }
-            Ok(false) => (),
-            Err(e) => error!("Failed to verify if storage config is valid: {e}"),
-        }
+    #[zbus(property)]
+    async fn ssh_enabled(&self) -> fdo::Result<bool> {
+        self.enabled(DeveloperFeature::Ssh).await
     }
 
-    if let Some(config) = config.update_bios.as_ref() {
-        match config.is_valid(true).await {
-            Ok(true) => {
-                object_server.at(MANAGER_PATH, update_bios).await?;
-            }
-            Ok(false) => (),
-            Err(e) => error!("Failed to verify if BIOS update config is valid: {e}"),
-        }
+    #[zbus(property)]
+    async fn set_ssh_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            header.as_ref(),
+            SET_DEVELOPER_MODE_ACTION,
+        )
+        .await?;
+        self.set_enabled(DeveloperFeature::Ssh, enabled).await?;
+        self.ssh_enabled_changed(&ctx).await
     }
+}
 
-    if let Some(config) = config.update_dock.as_ref() {
-        match config.is_valid(true).await {
-            Ok(true) => {
-                object_server.at(MANAGER_PATH, update_dock).await?;
-            }
-            Ok(false) => (),
-            Err(e) => error!("Failed to verify if dock update config is valid: {e}"),
-        }
+impl DisplayOutput1 {
+    async fn state(&self) -> Result<DisplayState> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(UserCommand::GetDisplayState(
+                tx,
+            )))
+            .await?;
+        Ok(rx.await?)
     }
 
-    Ok(())
+    async fn set_state(&self, state: DisplayState) -> Result<()> {
+        Ok(self
+            .channel
+            .send(DaemonCommand::ContextCommand(UserCommand::SetDisplayState(
+                state,
+            )))
+            .await?)
+    }
 }
 
-async fn create_device_interfaces(
-    proxy: &Proxy<'static>,
-    object_server: &ObjectServer,
-    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
-) -> Result<()> {
-    let Some(config) = device_config().await? else {
-        return Ok(());
-    };
+#[interface(name = "com.steampowered.SteamOSManager1.DisplayOutput1")]
+impl DisplayOutput1 {
+    async fn list_outputs(&self) -> fdo::Result<Vec<(String, String, bool, Vec<String>)>> {
+        let outputs = list_outputs()
+            .await
+            .inspect_err(|message| error!("Error listing display outputs: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(outputs
+            .into_iter()
+            .map(|output| (output.name, output.status, output.enabled, output.modes))
+            .collect())
+    }
 
-    let performance_profile = PerformanceProfile1 {
-        proxy: proxy.clone(),
-        tdp_limit_manager: tdp_manager.clone(),
-    };
+    #[zbus(property)]
+    async fn preferred_dock_mode(&self) -> fdo::Result<String> {
+        let mode = get_dock_mode_hint().await.map_err(to_zbus_fdo_error)?;
+        Ok(mode.map(|mode| mode.to_string()).unwrap_or_default())
+    }
 
-    if let Some(manager) = tdp_manager {
-        let low_power_mode = LowPowerMode1 {
-            manager: manager.clone(),
+    #[zbus(property)]
+    async fn set_preferred_dock_mode(
+        &self,
+        mode: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mode = if mode.is_empty() {
+            None
+        } else {
+            match mode.parse::<DisplayMode>() {
+                Ok(mode) => Some(mode),
+                Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string()).into()),
+            }
         };
-        if config
-            .tdp_limit
-            .as_ref()
-            .and_then(|config| config.download_mode_limit)
-            .is_some()
-        {
-            object_server.at(MANAGER_PATH, low_power_mode).await?;
+        set_dock_mode_hint(mode).await.map_err(to_zbus_error)?;
+        self.preferred_dock_mode_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn refresh_rate_cap(&self) -> fdo::Result<u32> {
+        Ok(self
+            .state()
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .refresh_rate_cap)
+    }
+
+    #[zbus(property)]
+    async fn set_refresh_rate_cap(
+        &self,
+        cap: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mut state = self.state().await.map_err(to_zbus_error)?;
+        if state.refresh_rate_cap == cap {
+            return Ok(());
         }
+        state.refresh_rate_cap = cap;
+        self.set_state(state).await.map_err(to_zbus_error)?;
+        self.refresh_rate_cap_changed(&ctx).await
+    }
+}
 
-        let object_server = object_server.clone();
-        tokio::spawn(async move {
-            let (tx, rx) = oneshot::channel();
-            manager.send(TdpManagerCommand::IsActive(tx))?;
-            if rx.await?? {
-                let tdp_limit = TdpLimit1 { manager };
-                object_server.at(MANAGER_PATH, tdp_limit).await?;
-            }
-            Ok::<(), Error>(())
-        });
+impl Dock1 {
+    #[cfg(test)]
+    pub(crate) fn state(&self) -> (Option<DockInfo>, u32) {
+        (self.dock.clone(), self.display_count)
     }
 
-    if let Some(config) = config.performance_profile.as_ref() {
-        if !get_available_platform_profiles(&config.platform_profile_name)
-            .await
-            .unwrap_or_default()
-            .is_empty()
-        {
-            object_server.at(MANAGER_PATH, performance_profile).await?;
+    pub(crate) async fn set_dock(
+        &mut self,
+        dock: Option<DockInfo>,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if self.dock == dock {
+            return Ok(());
         }
+        self.dock = dock;
+        self.connected_changed(ctx).await?;
+        self.model_changed(ctx).await?;
+        self.firmware_version_changed(ctx).await?;
+        self.usb_tier_changed(ctx).await
     }
 
-    Ok(())
+    pub(crate) async fn set_display_count(
+        &mut self,
+        display_count: u32,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if self.display_count == display_count {
+            return Ok(());
+        }
+        self.display_count = display_count;
+        self.display_count_changed(ctx).await
+    }
 }
 
-pub(crate) async fn create_interfaces(
-    session: Connection,
-    system: Connection,
-    daemon: Sender<Command>,
-    job_manager: UnboundedSender<JobManagerCommand>,
-    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
-) -> Result<SignalRelayService> {
-    let proxy = Builder::<Proxy>::new(&system)
-        .destination("com.steampowered.SteamOSManager1")?
-        .path("/com/steampowered/SteamOSManager1")?
-        .interface("com.steampowered.SteamOSManager1.RootManager")?
-        .cache_properties(CacheProperties::No)
-        .build()
-        .await?;
+#[interface(name = "com.steampowered.SteamOSManager1.Dock1")]
+impl Dock1 {
+    #[zbus(property)]
+    async fn connected(&self) -> bool {
+        self.dock.is_some()
+    }
 
-    let manager = SteamOSManager::new(system.clone(), proxy.clone(), job_manager.clone()).await?;
+    #[zbus(property)]
+    async fn model(&self) -> String {
+        self.dock
+            .as_ref()
+            .map(|dock| dock.model.clone())
+            .unwrap_or_default()
+    }
 
-    let als = AmbientLightSensor1 {
-        proxy: proxy.clone(),
-    };
-    let battery_charge_limit = BatteryChargeLimit1 {
-        proxy: proxy.clone(),
-    };
-    let cpu_boost = CpuBoost1 {
-        proxy: proxy.clone(),
-    };
-    let cpu_scaling = CpuScaling1 {
-        proxy: proxy.clone(),
-    };
-    let hdmi_cec = HdmiCec1::new(&session).await?;
-    let manager2 = Manager2 {
-        proxy: proxy.clone(),
-        channel: daemon.clone(),
-    };
-    let screen_reader = ScreenReader0::new(&session).await?;
-    let session_management = SessionManagement1 {
-        proxy: proxy.clone(),
-        manager: SessionManager::new(session.clone(), &system, daemon).await?,
-    };
-    let wifi_power_management = WifiPowerManagement1 {
-        proxy: proxy.clone(),
-    };
+    #[zbus(property)]
+    async fn firmware_version(&self) -> String {
+        self.dock
+            .as_ref()
+            .map(|dock| dock.firmware_version.clone())
+            .unwrap_or_default()
+    }
 
-    let object_server = session.object_server();
-    object_server.at(MANAGER_PATH, manager).await?;
+    #[zbus(property)]
+    async fn display_count(&self) -> u32 {
+        self.display_count
+    }
 
-    create_device_interfaces(&proxy, object_server, tdp_manager).await?;
-    create_platform_interfaces(&proxy, object_server, &system, &job_manager).await?;
+    #[zbus(property)]
+    async fn usb_tier(&self) -> String {
+        self.dock
+            .as_ref()
+            .map(|dock| dock.usb_tier.clone())
+            .unwrap_or_default()
+    }
 
-    if device_type().await.unwrap_or_default() == "steam_deck" {
-        object_server.at(MANAGER_PATH, als).await?;
+    #[zbus(signal)]
+    async fn dock_connected(signal_emitter: &SignalEmitter<'_>, model: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn dock_disconnected(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.FactoryReset1")]
+impl FactoryReset1 {
+    async fn estimate_factory_reset(&self, flags: u32) -> fdo::Result<(Vec<String>, u64)> {
+        method!(self, "EstimateFactoryReset", flags)
     }
-    if steam_deck_variant().await.unwrap_or_default() == SteamDeckVariant::Galileo {
-        let wifi_debug = WifiDebug1 {
-            proxy: proxy.clone(),
-        };
-        let wifi_debug_dump = WifiDebugDump1 {
-            proxy: proxy.clone(),
-        };
-        object_server.at(MANAGER_PATH, wifi_debug).await?;
-        object_server.at(MANAGER_PATH, wifi_debug_dump).await?;
+
+    async fn prepare_factory_reset(
+        &self,
+        flags: u32,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<u32> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            Some(&header),
+            FACTORY_RESET_ACTION,
+        )
+        .await?;
+        method!(self, "PrepareFactoryReset", flags)
+    }
+
+    async fn start_factory_reset(
+        &mut self,
+        flags: u32,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            Some(&header),
+            FACTORY_RESET_ACTION,
+        )
+        .await?;
+        job_method!(self, "StartFactoryReset", flags)
     }
+}
 
-    if get_max_charge_level().await.is_ok() {
-        object_server.at(MANAGER_PATH, battery_charge_limit).await?;
+#[interface(name = "com.steampowered.SteamOSManager1.FanControl1")]
+impl FanControl1 {
+    #[zbus(property)]
+    async fn fan_control_state(&self) -> fdo::Result<u32> {
+        getter!(self, "FanControlState")
     }
 
-    if get_cpu_boost_state().await.is_ok() {
-        object_server.at(MANAGER_PATH, cpu_boost).await?;
+    #[zbus(property)]
+    async fn set_fan_control_state(
+        &self,
+        state: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            header.as_ref(),
+            SET_FAN_CONTROL_STATE_ACTION,
+        )
+        .await?;
+        let _: () = setter!(self, "FanControlState", state)?;
+        self.fan_control_state_changed(&ctx).await
     }
+}
 
-    object_server.at(MANAGER_PATH, cpu_scaling).await?;
+#[interface(name = "com.steampowered.SteamOSManager1.FanCurve1")]
+impl FanCurve1 {
+    #[zbus(property)]
+    async fn fan_curve(&self) -> fdo::Result<Vec<(u32, u32)>> {
+        let curve = get_fan_curve()
+            .await
+            .inspect_err(|message| error!("Error getting fan curve: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(curve
+            .into_iter()
+            .map(|point| (point.temperature, point.pwm))
+            .collect())
+    }
 
-    match gpu_performance_level_driver().await {
-        Ok(driver) => {
-            object_server
-                .at(
-                    MANAGER_PATH,
-                    GpuPerformanceLevel1 {
-                        proxy: proxy.clone(),
-                        driver,
-                    },
-                )
-                .await?;
-        }
-        Err(e) => warn!("Can't add GpuPerformanceLevel1 interface: {e}"),
+    #[zbus(property)]
+    async fn set_fan_curve(
+        &self,
+        curve: Vec<(u32, u32)>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetFanCurve", &(curve)).await?;
+        self.fan_curve_changed(&ctx).await
     }
 
-    match gpu_power_profile_driver().await {
-        Ok(driver) => {
-            object_server
-                .at(
-                    MANAGER_PATH,
-                    GpuPowerProfile1 {
-                        proxy: proxy.clone(),
-                        driver,
-                    },
-                )
-                .await?;
-        }
-        Err(e) => warn!("Can't add GpuPowerProfile1 interface: {e}"),
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn fan_curve_points(&self) -> fdo::Result<u32> {
+        let points = get_fan_curve_points().await.map_err(to_zbus_fdo_error)?;
+        u32::try_from(points).map_err(to_zbus_fdo_error)
     }
 
-    if hdmi_cec.hdmi_cec.get_enabled_state().await.is_ok() {
-        object_server.at(MANAGER_PATH, hdmi_cec).await?;
+    async fn reset_fan_curve(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let _: () = method!(self, "ResetFanCurve")?;
+        self.fan_curve_changed(&ctx).await.map_err(zbus_to_zbus_fdo)
     }
+}
 
-    object_server.at(MANAGER_PATH, manager2).await?;
+impl FirmwareInfo1 {
+    async fn versions(&self) -> fdo::Result<(String, String, HashMap<String, String>)> {
+        // Querying the firmware can be slow, so only do it until something changes
+        let mut versions = self.versions.lock().await;
+        if let Some(versions) = versions.as_ref() {
+            return Ok(versions.clone());
+        }
+        let queried: (String, String, HashMap<String, String>) =
+            method!(self, "GetFirmwareVersions")?;
+        *versions = Some(queried.clone());
+        Ok(queried)
+    }
 
-    if session_management.manager.current_login_mode().await? == LoginMode::Game
-        && try_exists(path("/usr/bin/orca")).await?
-    {
-        object_server.at(MANAGER_PATH, screen_reader).await?;
+    /// Forget the cached versions after hardware was connected, disconnected or updated
+    pub(crate) async fn invalidate(&self, ctx: &SignalEmitter<'_>) -> zbus::Result<()> {
+        *self.versions.lock().await = None;
+        self.dock_firmware_version_invalidate(ctx).await?;
+        self.controller_firmware_versions_invalidate(ctx).await
     }
+}
 
-    if is_session_managed().await? {
-        object_server.at(MANAGER_PATH, session_management).await?;
+#[interface(name = "com.steampowered.SteamOSManager1.FirmwareInfo1")]
+impl FirmwareInfo1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn bios_version(&self) -> fdo::Result<String> {
+        Ok(self.versions().await?.0)
     }
 
-    if !list_wifi_interfaces().await.unwrap_or_default().is_empty() {
-        object_server
-            .at(MANAGER_PATH, wifi_power_management)
-            .await?;
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    async fn dock_firmware_version(&self) -> fdo::Result<String> {
+        Ok(self.versions().await?.1)
     }
 
-    Ok(SignalRelayService { proxy, session })
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    async fn controller_firmware_versions(&self) -> fdo::Result<HashMap<String, String>> {
+        Ok(self.versions().await?.2)
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::daemon::channel;
-    use crate::daemon::user::{UserCommand, UserContext};
-    use crate::gpu::{GpuPerformanceLevelDriverType, GpuPowerProfileDriverType};
-    use crate::hardware::test::fake_model;
-    use crate::hardware::{
-        BatteryChargeLimitConfig, DeviceConfig, DeviceMatch, DmiMatch, GpuPerformanceConfig,
-        GpuPowerProfileConfig, PerformanceProfileConfig, RangeConfig, SteamDeckVariant,
-        TdpLimitConfig,
-    };
-    use crate::platform::{
-        FormatDeviceConfig, PlatformConfig, ResetConfig, ScriptConfig, ServiceConfig, StorageConfig,
-    };
-    use crate::power::TdpLimitingMethod;
-    use crate::session::{make_managed, SessionManagerState};
-    use crate::systemd::test::{MockManager, MockUnit};
-    use crate::{path, testing};
+#[interface(name = "com.steampowered.SteamOSManager1.GpuMetrics1")]
+impl GpuMetrics1 {
+    async fn get_metrics(&self) -> fdo::Result<HashMap<String, u64>> {
+        get_gpu_metrics()
+            .await
+            .inspect_err(|message| error!("Error reading GPU metrics: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+}
 
-    use std::num::NonZeroU32;
-    use std::os::unix::fs::PermissionsExt;
-    use std::path::PathBuf;
-    use std::time::Duration;
-    use tokio::fs::{create_dir_all, set_permissions, write};
-    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
-    use tokio::time::sleep;
-    use zbus::object_server::Interface;
-    use zbus::Connection;
+#[interface(name = "com.steampowered.SteamOSManager1.GpuPerformanceLevel1")]
+impl GpuPerformanceLevel1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_gpu_performance_levels(&self) -> fdo::Result<Vec<String>> {
+        self.available_levels
+            .get_or_try_read(|| async {
+                let levels = self.driver.get_available_performance_levels().await?;
+                Ok(levels.into_iter().map(|level| level.to_string()).collect())
+            })
+            .await
+            .inspect_err(|message| error!("Error getting GPU performance levels: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
 
-    struct TestHandle {
-        _handle: testing::TestHandle,
-        connection: Connection,
-        _rx_job: UnboundedReceiver<JobManagerCommand>,
-        rx_tdp: Option<UnboundedReceiver<TdpManagerCommand>>,
+    #[zbus(property)]
+    async fn gpu_performance_level(&self) -> fdo::Result<String> {
+        match self.driver.get_performance_level().await {
+            Ok(level) => Ok(level.to_string()),
+            Err(e) => {
+                error!("Error getting GPU performance level: {e}");
+                Err(to_zbus_fdo_error(e))
+            }
+        }
     }
 
-    fn all_platform_config() -> Option<PlatformConfig> {
-        Some(PlatformConfig {
-            factory_reset: Some(ResetConfig::default()),
-            update_bios: Some(ScriptConfig::default()),
-            update_dock: Some(ScriptConfig::default()),
-            storage: Some(StorageConfig::default()),
-            fan_control: Some(ServiceConfig::Systemd(String::from(
-                "jupiter-fan-control.service",
-            ))),
-        })
+    #[zbus(property)]
+    async fn set_gpu_performance_level(
+        &self,
+        level: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetGpuPerformanceLevel", &(level)).await?;
+        self.gpu_performance_level_changed(&ctx).await
     }
 
-    fn all_device_config() -> Option<DeviceConfig> {
-        Some(DeviceConfig {
+    #[zbus(property)]
+    async fn manual_gpu_clock(&self) -> fdo::Result<u32> {
+        self.driver
+            .get_clocks()
+            .await
+            .inspect_err(|message| error!("Error getting manual GPU clock: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_manual_gpu_clock(
+        &self,
+        clocks: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        throttle_setter::<Self>(header.as_ref(), "ManualGpuClock").await?;
+        let _: () = self.proxy.call("SetManualGpuClock", &(clocks)).await?;
+        self.manual_gpu_clock_changed(&ctx).await
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn manual_gpu_clock_min(&self) -> fdo::Result<u32> {
+        Ok(*self
+            .clocks_range
+            .get_or_try_read(|| self.driver.get_clocks_range())
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .start())
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn manual_gpu_clock_max(&self) -> fdo::Result<u32> {
+        Ok(*self
+            .clocks_range
+            .get_or_try_read(|| self.driver.get_clocks_range())
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .end())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.GpuPower1")]
+impl GpuPower1 {
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn power_average(&self) -> fdo::Result<f64> {
+        get_gpu_power_average().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn power_cap(&self) -> fdo::Result<f64> {
+        get_gpu_power_cap().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn power_cap_max(&self) -> fdo::Result<f64> {
+        get_gpu_power_cap_max().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn power_cap_min(&self) -> fdo::Result<f64> {
+        get_gpu_power_cap_min().await.map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.GpuPowerProfile1")]
+impl GpuPowerProfile1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_gpu_power_profiles(&self) -> fdo::Result<Vec<String>> {
+        let (_, names): (Vec<u32>, Vec<String>) = self
+            .available_profiles
+            .get_or_try_read(|| self.driver.get_available_power_profiles())
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .into_iter()
+            .unzip();
+        Ok(names)
+    }
+
+    #[zbus(property)]
+    async fn gpu_power_profile(&self) -> fdo::Result<String> {
+        match self.driver.get_power_profile().await {
+            Ok(profile) => Ok(profile.to_string()),
+            Err(e) => {
+                error!("Error getting GPU power profile: {e}");
+                Err(to_zbus_fdo_error(e))
+            }
+        }
+    }
+
+    #[zbus(property)]
+    async fn set_gpu_power_profile(
+        &self,
+        profile: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetGpuPowerProfile", &(profile)).await?;
+        self.gpu_power_profile_changed(&ctx).await
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.GpuThrottle1")]
+impl GpuThrottle1 {
+    #[zbus(property)]
+    async fn thermal_throttling(&self) -> fdo::Result<bool> {
+        get_gpu_thermal_throttling()
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Haptics1")]
+impl Haptics1 {
+    #[zbus(property)]
+    async fn haptics_intensity(&self) -> fdo::Result<u32> {
+        get_haptics_intensity()
+            .await
+            .inspect_err(|message| error!("Error getting haptics intensity: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_haptics_intensity(
+        &self,
+        intensity: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if intensity > MAX_HAPTICS_INTENSITY {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Haptics intensity {intensity} is out of range"
+            ))
+            .into());
+        }
+        let _: () = self.proxy.call("SetHapticsIntensity", &(intensity)).await?;
+        self.haptics_intensity_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn haptic_feedback(&self) -> fdo::Result<bool> {
+        get_haptic_feedback()
+            .await
+            .inspect_err(|message| error!("Error getting haptic feedback: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_haptic_feedback(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetHapticFeedback", &(enabled)).await?;
+        self.haptic_feedback_changed(&ctx).await
+    }
+}
+
+impl IdlePolicy1 {
+    async fn state(&self) -> Result<IdlePolicyState> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(UserCommand::GetIdlePolicy(
+                tx,
+            )))
+            .await?;
+        Ok(rx.await?)
+    }
+
+    async fn set_state(&self, state: IdlePolicyState) -> Result<()> {
+        Ok(self
+            .channel
+            .send(DaemonCommand::ContextCommand(UserCommand::SetIdlePolicy(
+                state,
+            )))
+            .await?)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.IdlePolicy1")]
+impl IdlePolicy1 {
+    async fn get_idle_policy(&self, power_state: u32) -> fdo::Result<(u32, u32)> {
+        let power_state = IdlePowerState::try_from(power_state)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let policy = self
+            .state()
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .policy(power_state);
+        Ok((policy.action as u32, policy.timeout))
+    }
+
+    async fn set_idle_policy(
+        &self,
+        power_state: u32,
+        action: u32,
+        timeout: u32,
+    ) -> fdo::Result<()> {
+        let power_state = IdlePowerState::try_from(power_state)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let action =
+            IdleAction::try_from(action).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let mut state = self.state().await.map_err(to_zbus_fdo_error)?;
+        state
+            .set_policy(power_state, IdlePolicy { action, timeout })
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.set_state(state).await.map_err(to_zbus_fdo_error)
+    }
+}
+
+impl HdmiCec1 {
+    async fn new(connection: &Connection) -> Result<HdmiCec1> {
+        let hdmi_cec = HdmiCecControl::new(connection).await?;
+        Ok(HdmiCec1 { hdmi_cec })
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.HdmiCec1")]
+impl HdmiCec1 {
+    #[zbus(property)]
+    async fn hdmi_cec_state(&self) -> fdo::Result<u32> {
+        match self.hdmi_cec.get_enabled_state().await {
+            Ok(state) => Ok(state as u32),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
+    }
+
+    #[zbus(property)]
+    async fn set_hdmi_cec_state(
+        &self,
+        state: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let state = match HdmiCecState::try_from(state) {
+            Ok(state) => state,
+            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string()).into()),
+        };
+        let _: () = self
+            .hdmi_cec
+            .set_enabled_state(state)
+            .await
+            .inspect_err(|message| error!("Error setting CEC state: {message}"))
+            .map_err(to_zbus_error)?;
+        self.hdmi_cec_state_changed(&ctx).await
+    }
+
+    async fn power_on_tv(&self) -> fdo::Result<()> {
+        self.hdmi_cec
+            .power_on_tv()
+            .await
+            .inspect_err(|message| error!("Error powering on TV: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn standby_tv(&self) -> fdo::Result<()> {
+        self.hdmi_cec
+            .standby_tv()
+            .await
+            .inspect_err(|message| error!("Error putting TV in standby: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn set_active_source(&self) -> fdo::Result<()> {
+        self.hdmi_cec
+            .set_active_source()
+            .await
+            .inspect_err(|message| error!("Error setting active source: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn list_cec_devices(&self) -> fdo::Result<Vec<(u32, String, String, String)>> {
+        let devices = self
+            .hdmi_cec
+            .list_devices()
+            .await
+            .inspect_err(|message| error!("Error listing CEC devices: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(devices
+            .into_iter()
+            .map(|device| {
+                (
+                    device.logical_address,
+                    device.name,
+                    device.vendor,
+                    device.power_status,
+                )
+            })
+            .collect())
+    }
+
+    #[zbus(signal)]
+    async fn remote_key_pressed(
+        signal_emitter: &SignalEmitter<'_>,
+        code: u32,
+        key: &str,
+    ) -> zbus::Result<()>;
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.InputInhibit1")]
+impl InputInhibit1 {
+    async fn inhibit_input(&self, devices: Vec<String>) -> fdo::Result<Fd> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(InputInhibitCommand::Inhibit(devices, tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain input inhibit handle"))
+            })?;
+        Ok(rx
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?
+            .into())
+    }
+
+    async fn list_inhibited_devices(&self) -> fdo::Result<Vec<String>> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(InputInhibitCommand::ListDevices(tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain inhibited input devices"))
+            })?;
+        rx.await.map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.JobHistory1")]
+impl JobHistory1 {
+    async fn get_job_history(
+        &self,
+        count: u32,
+    ) -> fdo::Result<Vec<(String, u64, u64, i32, u32, String)>> {
+        method!(self, "GetJobHistory", count)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.LedControl1")]
+impl LedControl1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_patterns(&self) -> fdo::Result<Vec<String>> {
+        get_available_led_patterns()
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn brightness(&self) -> fdo::Result<u32> {
+        get_led_brightness().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_brightness(
+        &self,
+        brightness: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetLedBrightness", &(brightness)).await?;
+        self.brightness_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn color(&self) -> fdo::Result<(u32, u32, u32)> {
+        get_led_color().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_color(
+        &self,
+        color: (u32, u32, u32),
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetLedColor", &color).await?;
+        self.color_changed(&ctx).await
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn max_brightness(&self) -> fdo::Result<u32> {
+        get_led_max_brightness().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn pattern(&self) -> fdo::Result<String> {
+        get_led_pattern().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_pattern(
+        &self,
+        pattern: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetLedPattern", &(pattern)).await?;
+        self.pattern_changed(&ctx).await
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.LowPowerMode1")]
+impl LowPowerMode1 {
+    async fn enter_download_mode(&self, identifier: &str) -> fdo::Result<Fd> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::EnterDownloadMode(
+                identifier.to_string(),
+                tx,
+            ))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain download mode handle"))
+            })?;
+        Ok(rx
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .map_err(to_zbus_fdo_error)?
+            .ok_or(fdo::Error::Failed(String::from(
+                "Download mode not configured",
+            )))?
+            .into())
+    }
+
+    async fn list_download_mode_handles(&self) -> fdo::Result<HashMap<String, u32>> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::ListDownloadModeHandles(tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain download mode handle list"))
+            })?;
+        rx.await.map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Manager2")]
+impl Manager2 {
+    async fn reload_config(&self) -> fdo::Result<()> {
+        self.channel
+            .send(DaemonCommand::ReadConfig)
+            .await
+            .inspect_err(|message| error!("Error sending ReadConfig command: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        method!(self, "ReloadConfig")
+    }
+
+    async fn validate_config(&self) -> Vec<(String, bool, String)> {
+        validate_config(self.proxy.connection()).await
+    }
+
+    async fn match_device(&self) -> fdo::Result<Vec<(String, String, String, bool, String)>> {
+        let report = explain_device_match().await.map_err(to_zbus_fdo_error)?;
+        Ok(report
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.path.display().to_string(),
+                    entry.device,
+                    entry.variant,
+                    entry.matched,
+                    entry.reason,
+                )
+            })
+            .collect())
+    }
+
+    async fn generate_diagnostics(&self) -> fdo::Result<(zvariant::OwnedObjectPath, String)> {
+        let interfaces = validate_config(self.proxy.connection()).await;
+        let bundle = stage_diagnostics(&interfaces)
+            .await
+            .inspect_err(|message| error!("Error collecting diagnostics: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let (tx, rx) = oneshot::channel();
+        self.job_manager
+            .send(JobManagerCommand::RunProcess {
+                executable: String::from("tar"),
+                args: bundle.archive_args(),
+                operation_name: String::from("generating diagnostics"),
+                timeout: Some(DIAGNOSTICS_TIMEOUT),
+                reply: tx,
+            })
+            .map_err(to_zbus_fdo_error)?;
+        let job = rx.await.map_err(to_zbus_fdo_error)??;
+        Ok((job, bundle.output.to_string_lossy().into()))
+    }
+
+    async fn has_feature(
+        &self,
+        name: &str,
+        #[zbus(object_server)] object_server: &ObjectServer,
+    ) -> bool {
+        available_features(object_server)
+            .await
+            .iter()
+            .any(|feature| feature == name)
+    }
+
+    async fn set_log_level(
+        &self,
+        level: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        set_log_level(level).map_err(to_zbus_fdo_error)?;
+        let _: () = method!(self, "SetLogLevel", level)?;
+        self.log_level_changed(&ctx).await?;
+        Ok(())
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn device_model(&self) -> fdo::Result<(String, String)> {
+        let (device, variant) = device_variant().await.map_err(to_zbus_fdo_error)?;
+        Ok((device.to_string(), variant))
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn device_config_source(&self) -> fdo::Result<Vec<String>> {
+        let config = device_config().await.map_err(to_zbus_fdo_error)?;
+        Ok(config
+            .as_ref()
+            .map(|config| {
+                config
+                    .source
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    #[zbus(property)]
+    async fn log_level(&self) -> fdo::Result<String> {
+        log_level().map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn features(&self, #[zbus(object_server)] object_server: &ObjectServer) -> Vec<String> {
+        available_features(object_server).await
+    }
+
+    #[zbus(signal)]
+    async fn backend_reconnected(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn service_restarted(
+        signal_emitter: &SignalEmitter<'_>,
+        daemon: &str,
+        service: &str,
+        restarts: u32,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn sysfs_write_failed(
+        signal_emitter: &SignalEmitter<'_>,
+        path: &str,
+        error: &str,
+    ) -> zbus::Result<()>;
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.NetworkLimit1")]
+impl NetworkLimit1 {
+    async fn limit_bandwidth(
+        &self,
+        identifier: &str,
+        download: u32,
+        upload: u32,
+    ) -> fdo::Result<Fd> {
+        let fd: zvariant::OwnedFd =
+            method!(self, "LimitNetworkBandwidth", identifier, download, upload)?;
+        Ok(fd.into())
+    }
+
+    async fn list_bandwidth_limit_handles(&self) -> fdo::Result<HashMap<String, (u32, u32)>> {
+        method!(self, "ListNetworkLimitHandles")
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.OsUpdate1")]
+impl OsUpdate1 {
+    async fn check_for_updates(&self) -> fdo::Result<Vec<(String, String, String, u64)>> {
+        method!(self, "CheckForOsUpdates")
+    }
+
+    async fn start_update(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
+        job_method!(self, "StartOsUpdate")
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.OsUpdateChannel1")]
+impl OsUpdateChannel1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_update_channels(&self) -> fdo::Result<Vec<String>> {
+        let config = platform_config().await.map_err(to_zbus_fdo_error)?;
+        let config = config
+            .as_ref()
+            .and_then(|config| config.update_channel.as_ref())
+            .ok_or(fdo::Error::Failed(String::from(
+                "No update channels configured",
+            )))?;
+        Ok(config.branches.clone())
+    }
+
+    #[zbus(property)]
+    async fn update_channel(&self) -> fdo::Result<String> {
+        method!(self, "GetUpdateChannel")
+    }
+
+    #[zbus(property)]
+    async fn set_update_channel(
+        &self,
+        channel: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetUpdateChannel", &(channel)).await?;
+        self.update_channel_changed(&ctx).await
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.PerformanceProfile1")]
+impl PerformanceProfile1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_performance_profiles(&self) -> fdo::Result<Vec<String>> {
+        let config = device_config().await.map_err(to_zbus_fdo_error)?;
+        let config = config
+            .as_ref()
+            .and_then(|config| config.performance_profile.as_ref())
+            .ok_or(fdo::Error::Failed(String::from(
+                "No performance platform-profile configured",
+            )))?;
+        get_available_platform_profiles(&config.platform_profile_name)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn performance_profile(&self) -> fdo::Result<String> {
+        let config = device_config().await.map_err(to_zbus_fdo_error)?;
+        let config = config
+            .as_ref()
+            .and_then(|config| config.performance_profile.as_ref())
+            .ok_or(fdo::Error::Failed(String::from(
+                "No performance platform-profile configured",
+            )))?;
+        get_platform_profile(&config.platform_profile_name)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_performance_profile(
+        &self,
+        profile: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self.proxy.call("SetPerformanceProfile", &(profile)).await?;
+        self.performance_profile_changed(&ctx).await?;
+        let connection = connection.clone();
+        if let Some(manager) = self.tdp_limit_manager.as_ref() {
+            let manager = manager.clone();
+            let _ = manager.send(TdpManagerCommand::UpdateDownloadMode);
+            tokio::spawn(async move {
+                let (tx, rx) = oneshot::channel();
+                manager.send(TdpManagerCommand::IsActive(tx))?;
+                let active = rx.await??;
+                let tdp_limit = TdpLimit1 { manager };
+                set_interface_available(connection.object_server(), tdp_limit, active).await?;
+                Ok::<(), Error>(())
+            });
+        }
+        Ok(())
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn suggested_default_performance_profile(&self) -> fdo::Result<String> {
+        let config = device_config().await.map_err(to_zbus_fdo_error)?;
+        let config = config
+            .as_ref()
+            .and_then(|config| config.performance_profile.as_ref())
+            .ok_or(fdo::Error::Failed(String::from(
+                "No performance platform-profile configured",
+            )))?;
+        Ok(config.suggested_default.to_string())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.PowerHistory1")]
+impl PowerHistory1 {
+    async fn get_samples(&self, since: u64) -> fdo::Result<Vec<(u64, f64, u32, u32, u32)>> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TelemetryCommand::GetSamples(since, tx))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to obtain power history")))?;
+        Ok(rx
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .into_iter()
+            .map(|sample| {
+                (
+                    sample.timestamp,
+                    sample.battery_power,
+                    sample.tdp_limit,
+                    sample.cpu_frequency,
+                    sample.gpu_frequency,
+                )
+            })
+            .collect())
+    }
+}
+
+impl PowerPresets1 {
+    async fn state(&self) -> Result<PowerPresetsState> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(UserCommand::GetPowerPresets(
+                tx,
+            )))
+            .await?;
+        Ok(rx.await?)
+    }
+
+    async fn set_state(&self, state: PowerPresetsState) -> Result<()> {
+        Ok(self
+            .channel
+            .send(DaemonCommand::ContextCommand(UserCommand::SetPowerPresets(
+                state,
+            )))
+            .await?)
+    }
+
+    pub(crate) async fn apply(
+        &self,
+        name: &str,
+        connection: &Connection,
+        ctx: &SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut state = self.state().await.map_err(to_zbus_fdo_error)?;
+        let preset = state
+            .preset(name)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown power preset {name}")))?;
+        apply_power_preset(connection, &preset)
+            .await
+            .inspect_err(|message| error!("Error applying power preset {name}: {message}"))?;
+        state.current = name.to_string();
+        self.set_state(state).await.map_err(to_zbus_fdo_error)?;
+        self.current_preset_changed(ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+}
+
+pub(crate) async fn apply_power_preset(
+    connection: &Connection,
+    preset: &PowerPreset,
+) -> fdo::Result<()> {
+    let object_server = connection.object_server();
+
+    // The performance profile goes first, as it can change whether the TDP limit can be set
+    if let Some(profile) = preset.performance_profile.as_deref() {
+        if let Ok(iface) = object_server
+            .interface::<_, Guarded<PerformanceProfile1>>(MANAGER_PATH)
+            .await
+        {
+            let performance_profile = iface.get().await;
+            if performance_profile
+                .available_performance_profiles()
+                .await?
+                .iter()
+                .any(|available| available == profile)
+            {
+                performance_profile
+                    .set_performance_profile(profile, connection, iface.signal_emitter().clone())
+                    .await
+                    .map_err(zbus_to_zbus_fdo)?;
+            } else {
+                warn!("Skipping unavailable performance profile {profile}");
+            }
+        }
+    }
+
+    if let Some(governor) = preset.cpu_scaling_governor.as_ref() {
+        let iface = object_server
+            .interface::<_, Guarded<CpuScaling1>>(MANAGER_PATH)
+            .await
+            .map_err(zbus_to_zbus_fdo)?;
+        let cpu_scaling = iface.get().await;
+        if cpu_scaling
+            .available_cpu_scaling_governors()
+            .await?
+            .contains(governor)
+        {
+            cpu_scaling
+                .set_cpu_scaling_governor(governor.clone(), iface.signal_emitter().clone())
+                .await
+                .map_err(zbus_to_zbus_fdo)?;
+        } else {
+            warn!("Skipping unavailable CPU scaling governor {governor}");
+        }
+    }
+
+    if let Some(level) = preset.gpu_performance_level.as_ref() {
+        if let Ok(iface) = object_server
+            .interface::<_, Guarded<GpuPerformanceLevel1>>(MANAGER_PATH)
+            .await
+        {
+            let gpu_performance_level = iface.get().await;
+            if gpu_performance_level
+                .available_gpu_performance_levels()
+                .await?
+                .contains(level)
+            {
+                gpu_performance_level
+                    .set_gpu_performance_level(level, iface.signal_emitter().clone())
+                    .await
+                    .map_err(zbus_to_zbus_fdo)?;
+            } else {
+                warn!("Skipping unavailable GPU performance level {level}");
+            }
+        }
+    }
+
+    if let Some(limit) = preset.tdp_limit {
+        if let Ok(iface) = object_server
+            .interface::<_, Guarded<TdpLimit1>>(MANAGER_PATH)
+            .await
+        {
+            let tdp_limit = iface.get().await;
+            let limit = limit.clamp(
+                tdp_limit.tdp_limit_min().await,
+                tdp_limit.tdp_limit_max().await,
+            );
+            tdp_limit
+                .set_tdp_limit(limit, None)
+                .await
+                .map_err(zbus_to_zbus_fdo)?;
+        }
+    }
+
+    if let Some(cap) = preset.refresh_rate_cap {
+        if let Ok(iface) = object_server
+            .interface::<_, Guarded<DisplayOutput1>>(MANAGER_PATH)
+            .await
+        {
+            iface
+                .get()
+                .await
+                .set_refresh_rate_cap(cap, iface.signal_emitter().clone())
+                .await
+                .map_err(zbus_to_zbus_fdo)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn current_power_preset(connection: &Connection) -> PowerPreset {
+    let object_server = connection.object_server();
+    let mut preset = PowerPreset {
+        cpu_scaling_governor: get_cpu_scaling_governor()
+            .await
+            .ok()
+            .map(|governor| governor.to_string()),
+        ..PowerPreset::default()
+    };
+    if let Ok(iface) = object_server
+        .interface::<_, Guarded<PerformanceProfile1>>(MANAGER_PATH)
+        .await
+    {
+        preset.performance_profile = iface.get().await.performance_profile().await.ok();
+    }
+    if let Ok(iface) = object_server
+        .interface::<_, Guarded<GpuPerformanceLevel1>>(MANAGER_PATH)
+        .await
+    {
+        preset.gpu_performance_level = iface.get().await.gpu_performance_level().await.ok();
+    }
+    if let Ok(iface) = object_server
+        .interface::<_, Guarded<TdpLimit1>>(MANAGER_PATH)
+        .await
+    {
+        // A limit of 0 means it couldn't be read
+        preset.tdp_limit = Some(iface.get().await.tdp_limit().await).filter(|limit| *limit > 0);
+    }
+    if let Ok(iface) = object_server
+        .interface::<_, Guarded<DisplayOutput1>>(MANAGER_PATH)
+        .await
+    {
+        preset.refresh_rate_cap = iface.get().await.refresh_rate_cap().await.ok();
+    }
+    preset
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.PowerPresets1")]
+impl PowerPresets1 {
+    #[zbus(property)]
+    async fn available_presets(&self) -> fdo::Result<Vec<String>> {
+        Ok(self.state().await.map_err(to_zbus_fdo_error)?.names())
+    }
+
+    #[zbus(property)]
+    async fn current_preset(&self) -> fdo::Result<String> {
+        Ok(self.state().await.map_err(to_zbus_fdo_error)?.current)
+    }
+
+    async fn apply_preset(
+        &self,
+        name: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.apply(name, connection, &ctx).await
+    }
+
+    async fn save_preset(
+        &self,
+        name: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        if name.is_empty() {
+            return Err(fdo::Error::InvalidArgs(String::from(
+                "Power preset name can't be empty",
+            )));
+        }
+        let _guard = self.lock.lock().await;
+        let mut state = self.state().await.map_err(to_zbus_fdo_error)?;
+        state
+            .presets
+            .insert(name.to_string(), current_power_preset(connection).await);
+        state.current = name.to_string();
+        self.set_state(state).await.map_err(to_zbus_fdo_error)?;
+        self.available_presets_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)?;
+        self.current_preset_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.PowerSource1")]
+impl PowerSource1 {
+    #[zbus(property)]
+    async fn on_battery(&self) -> fdo::Result<bool> {
+        is_on_battery().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn charger_type(&self) -> fdo::Result<String> {
+        let charger = get_charger_info().await.map_err(to_zbus_fdo_error)?;
+        Ok(charger.map(|charger| charger.usb_type).unwrap_or_default())
+    }
+
+    #[zbus(property)]
+    async fn charger_voltage(&self) -> fdo::Result<u32> {
+        let charger = get_charger_info().await.map_err(to_zbus_fdo_error)?;
+        Ok(charger.map_or(0, |charger| charger.voltage))
+    }
+
+    #[zbus(property)]
+    async fn charger_current(&self) -> fdo::Result<u32> {
+        let charger = get_charger_info().await.map_err(to_zbus_fdo_error)?;
+        Ok(charger.map_or(0, |charger| charger.current))
+    }
+
+    #[zbus(property)]
+    async fn charger_power(&self) -> fdo::Result<u32> {
+        let charger = get_charger_info().await.map_err(to_zbus_fdo_error)?;
+        Ok(charger.map_or(0, |charger| charger.power()))
+    }
+
+    #[zbus(signal)]
+    async fn power_source_changed(
+        signal_emitter: &SignalEmitter<'_>,
+        on_battery: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn charger_changed(
+        signal_emitter: &SignalEmitter<'_>,
+        voltage: u32,
+        current: u32,
+        power: u32,
+    ) -> zbus::Result<()>;
+}
+
+impl ScreenReader0 {
+    async fn new(connection: &Connection) -> Result<ScreenReader0> {
+        let screen_reader = OrcaManager::new(connection).await?;
+        Ok(ScreenReader0 {
+            screen_reader,
+            speaking: false,
+        })
+    }
+
+    pub(crate) async fn set_speaking(
+        &mut self,
+        speaking: bool,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if self.speaking == speaking {
+            return Ok(());
+        }
+        self.speaking = speaking;
+        self.speaking_changed(ctx).await?;
+        if speaking {
+            Self::speech_started(ctx).await
+        } else {
+            Self::speech_stopped(ctx).await
+        }
+    }
+
+    pub(crate) fn screen_reader_enabled(&self) -> bool {
+        self.screen_reader.enabled()
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.ScreenReader0")]
+impl ScreenReader0 {
+    #[zbus(property)]
+    async fn enabled(&self) -> bool {
+        self.screen_reader.enabled()
+    }
+
+    #[zbus(property)]
+    async fn set_enabled(&mut self, enabled: bool) -> fdo::Result<()> {
+        self.screen_reader
+            .set_enabled(enabled)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn rate(&self) -> f64 {
+        self.screen_reader.rate()
+    }
+
+    #[zbus(property)]
+    async fn set_rate(&mut self, rate: f64) -> fdo::Result<()> {
+        self.screen_reader
+            .set_rate(rate)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn pitch(&self) -> f64 {
+        self.screen_reader.pitch()
+    }
+
+    #[zbus(property)]
+    async fn set_pitch(&mut self, pitch: f64) -> fdo::Result<()> {
+        self.screen_reader
+            .set_pitch(pitch)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn volume(&self) -> f64 {
+        self.screen_reader.volume()
+    }
+
+    #[zbus(property)]
+    async fn set_volume(&mut self, volume: f64) -> fdo::Result<()> {
+        self.screen_reader
+            .set_volume(volume)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn mode(&self) -> u32 {
+        self.screen_reader.mode() as u32
+    }
+
+    #[zbus(property)]
+    async fn set_mode(
+        &mut self,
+        m: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let mode = match ScreenReaderMode::try_from(m) {
+            Ok(mode) => mode,
+            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string())),
+        };
+        self.screen_reader
+            .set_mode(mode)
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.mode_changed(&ctx).await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn voice(&self) -> &str {
+        self.screen_reader.voice()
+    }
+
+    #[zbus(property)]
+    async fn set_voice(
+        &mut self,
+        voice: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.screen_reader
+            .set_voice(voice)
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.voice_changed(&ctx).await.map_err(to_zbus_fdo_error)?;
+        self.voice_locale_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn voice_locale(&self) -> &str {
+        self.screen_reader.voice_locale()
+    }
+
+    #[zbus(property)]
+    async fn set_voice_locale(
+        &mut self,
+        locale: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.screen_reader
+            .set_voice_locale(locale)
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.voice_changed(&ctx).await.map_err(to_zbus_fdo_error)?;
+        self.voice_locale_changed(&ctx)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn voice_locales(&self) -> Vec<&str> {
+        self.screen_reader.get_voice_locales()
+    }
+
+    #[zbus(property)]
+    async fn voices_for_locale(&self) -> HashMap<String, Vec<String>> {
+        self.screen_reader.get_voices().clone()
+    }
+
+    async fn trigger_action(&mut self, a: u32, timestamp: u64) -> fdo::Result<()> {
+        let action = match ScreenReaderAction::try_from(a) {
+            Ok(action) => action,
+            Err(err) => return Err(fdo::Error::InvalidArgs(err.to_string())),
+        };
+        self.screen_reader
+            .trigger_action(action, timestamp)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn speaking(&self) -> bool {
+        self.speaking
+    }
+
+    #[zbus(signal)]
+    async fn speech_started(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn speech_stopped(signal_emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+}
+
+impl SessionManagement1 {
+    pub(crate) async fn set_desktop_sessions(
+        &mut self,
+        sessions: Vec<String>,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if self.desktop_sessions.as_ref() == Some(&sessions) {
+            return Ok(());
+        }
+        self.desktop_sessions = Some(sessions);
+        self.available_desktop_sessions_changed(ctx).await
+    }
+
+    async fn desktop_sessions(&self) -> Result<Vec<String>> {
+        match self.desktop_sessions.as_ref() {
+            Some(sessions) => Ok(sessions.clone()),
+            None => valid_desktop_sessions().await,
+        }
+    }
+
+    async fn switch(&self, login_mode: LoginMode, ctx: &SignalEmitter<'_>) -> fdo::Result<()> {
+        let name = login_mode.to_string();
+        Self::session_switch_started(ctx, name.as_str()).await?;
+        if let Err(e) = self.manager.switch_to_login_mode(login_mode).await {
+            error!("Failed to switch to {name} mode: {e}");
+            Self::session_switch_failed(ctx, name.as_str(), e.to_string().as_str()).await?;
+            return Err(to_zbus_fdo_error(e));
+        }
+        Ok(())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.SessionManagement1")]
+impl SessionManagement1 {
+    #[zbus(property)]
+    async fn default_login_mode(&self) -> fdo::Result<String> {
+        Ok(self
+            .manager
+            .default_login_mode()
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_default_login_mode(&mut self, login_mode: &str) -> fdo::Result<()> {
+        let login_mode = LoginMode::try_from(login_mode).map_err(to_zbus_fdo_error)?;
+        self.manager
+            .set_default_login_mode(login_mode)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn default_desktop_session(&self) -> fdo::Result<String> {
+        self.manager
+            .default_desktop_session()
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_default_desktop_session(&mut self, session: &str) -> fdo::Result<()> {
+        self.manager
+            .set_default_desktop_session(session)
+            .await
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn switch_to_login_mode(
+        &self,
+        login_mode: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let login_mode = LoginMode::try_from(login_mode).map_err(to_zbus_fdo_error)?;
+        self.switch(login_mode, &ctx).await
+    }
+
+    async fn switch_to_game_mode(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.switch(LoginMode::Game, &ctx).await
+    }
+
+    async fn switch_to_desktop_mode(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        self.switch(LoginMode::Desktop, &ctx).await
+    }
+
+    #[zbus(property)]
+    async fn available_desktop_sessions(&self) -> fdo::Result<Vec<String>> {
+        self.desktop_sessions().await.map_err(to_zbus_fdo_error)
+    }
+
+    async fn valid_desktop_sessions(&self) -> fdo::Result<Vec<String>> {
+        self.desktop_sessions().await.map_err(to_zbus_fdo_error)
+    }
+
+    async fn clean_temporary_sessions(&self) -> fdo::Result<()> {
+        method!(self, "CleanTemporarySessions")
+    }
+
+    #[zbus(signal)]
+    async fn session_switch_started(
+        signal_emitter: &SignalEmitter<'_>,
+        login_mode: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn session_switch_failed(
+        signal_emitter: &SignalEmitter<'_>,
+        login_mode: &str,
+        reason: &str,
+    ) -> zbus::Result<()>;
+}
+
+impl Services1 {
+    async fn manage(
+        &self,
+        unit: &str,
+        action: UnitAction,
+        connection: &Connection,
+        header: &Header<'_>,
+    ) -> fdo::Result<()> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            Some(header),
+            MANAGE_UNITS_ACTION,
+        )
+        .await?;
+        method!(self, "ManageUnit", unit, action.to_string())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Services1")]
+impl Services1 {
+    async fn list_units(&self) -> fdo::Result<Vec<(String, String, String)>> {
+        unit_states(&self.system)
+            .await
+            .inspect_err(|message| error!("Error listing managed units: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn start_unit(
+        &self,
+        unit: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        self.manage(unit, UnitAction::Start, connection, &header)
+            .await
+    }
+
+    async fn stop_unit(
+        &self,
+        unit: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        self.manage(unit, UnitAction::Stop, connection, &header)
+            .await
+    }
+
+    async fn restart_unit(
+        &self,
+        unit: &str,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        self.manage(unit, UnitAction::Restart, connection, &header)
+            .await
+    }
+
+    async fn set_unit_enabled(
+        &self,
+        unit: &str,
+        enabled: bool,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        let action = if enabled {
+            UnitAction::Enable
+        } else {
+            UnitAction::Disable
+        };
+        self.manage(unit, action, connection, &header).await
+    }
+
+    #[zbus(signal)]
+    async fn unit_state_changed(
+        signal_emitter: &SignalEmitter<'_>,
+        unit: &str,
+        active_state: &str,
+    ) -> zbus::Result<()>;
+}
+
+impl SleepConfig1 {
+    async fn settings(&self) -> fdo::Result<SleepSettings> {
+        get_sleep_settings()
+            .await
+            .inspect_err(|message| error!("Error getting sleep config: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn set_settings(&self, settings: SleepSettings) -> fdo::Result<()> {
+        let mode = settings
+            .mode
+            .map(|mode| mode.to_string())
+            .unwrap_or_default();
+        method!(
+            self,
+            "SetSleepConfig",
+            mode,
+            settings.hibernation,
+            settings.suspend_then_hibernate,
+            settings.hibernate_delay
+        )
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.SleepConfig1")]
+impl SleepConfig1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn supported_sleep_modes(&self) -> fdo::Result<Vec<String>> {
+        let modes = get_supported_sleep_modes()
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        Ok(modes.iter().map(ToString::to_string).collect())
+    }
+
+    #[zbus(property)]
+    async fn sleep_mode(&self) -> fdo::Result<String> {
+        let mode = get_sleep_mode()
+            .await
+            .inspect_err(|message| error!("Error getting sleep mode: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(mode.to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_sleep_mode(
+        &self,
+        mode: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mode = SleepMode::try_from(mode)
+            .map_err(|_| fdo::Error::InvalidArgs(format!("Unknown sleep mode {mode}")))?;
+        let mut settings = self.settings().await?;
+        settings.mode = Some(mode);
+        self.set_settings(settings).await?;
+        self.sleep_mode_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn hibernation_enabled(&self) -> fdo::Result<bool> {
+        Ok(self.settings().await?.hibernation)
+    }
+
+    #[zbus(property)]
+    async fn set_hibernation_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mut settings = self.settings().await?;
+        settings.hibernation = enabled;
+        self.set_settings(settings).await?;
+        self.hibernation_enabled_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn suspend_then_hibernate_enabled(&self) -> fdo::Result<bool> {
+        Ok(self.settings().await?.suspend_then_hibernate)
+    }
+
+    #[zbus(property)]
+    async fn set_suspend_then_hibernate_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let mut settings = self.settings().await?;
+        settings.suspend_then_hibernate = enabled;
+        self.set_settings(settings).await?;
+        self.suspend_then_hibernate_enabled_changed(&ctx).await
+    }
+
+    #[zbus(property)]
+    async fn hibernate_delay(&self) -> fdo::Result<u32> {
+        Ok(self.settings().await?.hibernate_delay)
+    }
+
+    #[zbus(property)]
+    async fn set_hibernate_delay(
+        &self,
+        delay: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        if delay != 0 && !(MIN_HIBERNATE_DELAY..=MAX_HIBERNATE_DELAY).contains(&delay) {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Hibernate delay {delay} is out of range"
+            ))
+            .into());
+        }
+        let mut settings = self.settings().await?;
+        settings.hibernate_delay = delay;
+        self.set_settings(settings).await?;
+        self.hibernate_delay_changed(&ctx).await
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    async fn swap_adequate(&self) -> fdo::Result<bool> {
+        is_swap_adequate()
+            .await
+            .inspect_err(|message| error!("Error checking swap space: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Smt1")]
+impl Smt1 {
+    #[zbus(property)]
+    async fn smt_enabled(&self) -> fdo::Result<bool> {
+        get_smt_enabled().await.map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_smt_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self
+            .proxy
+            .call("SetSmtEnabled", &(enabled))
+            .await
+            .map_err(to_zbus_fdo_error)?;
+        self.smt_enabled_changed(&ctx).await
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Storage1")]
+impl Storage1 {
+    async fn format_device(
+        &mut self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            Some(&header),
+            FORMAT_DEVICE_ACTION,
+        )
+        .await?;
+        job_method!(self, "FormatDevice", device, label, validate)
+    }
+
+    async fn format_device2(
+        &mut self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        options: HashMap<String, zvariant::OwnedValue>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        authorize_relayed_call(
+            connection,
+            self.proxy.connection(),
+            Some(&header),
+            FORMAT_DEVICE_ACTION,
+        )
+        .await?;
+        job_method!(self, "FormatDevice2", device, label, validate, &options)
+    }
+
+    async fn get_device_info(
+        &self,
+        device: &str,
+    ) -> fdo::Result<(u64, String, String, bool, String)> {
+        let info = get_device_info(device)
+            .await
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        Ok((
+            info.capacity,
+            info.vendor,
+            info.model,
+            info.removable,
+            info.speed,
+        ))
+    }
+
+    async fn trim_devices(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
+        job_method!(self, "TrimDevices")
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.StorageHealth1")]
+impl StorageHealth1 {
+    async fn list_devices(&self) -> fdo::Result<Vec<String>> {
+        Ok(list_block_devices()
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .into_iter()
+            .map(|name| format!("/dev/{name}"))
+            .collect())
+    }
+
+    async fn get_device_health(&self, device: &str) -> fdo::Result<(u32, f64, u32, bool)> {
+        method!(self, "GetStorageHealth", device)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Status1")]
+impl Status1 {
+    async fn get_service_status(&self) -> fdo::Result<Vec<(String, String, String, u32, String)>> {
+        let (tx, rx) = oneshot::channel();
+        self.channel
+            .send(DaemonCommand::ContextCommand(
+                UserCommand::GetServiceStatus(tx),
+            ))
+            .await
+            .inspect_err(|message| error!("Error sending GetServiceStatus command: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let user = rx
+            .await
+            .inspect_err(|message| error!("Error receiving GetServiceStatus reply: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        // Still report the user daemon's services if the system daemon can't be reached
+        let system: Vec<(String, String, u32, String)> = method!(self, "GetServiceStatus")
+            .inspect_err(|e| warn!("Failed to get system daemon service status: {e}"))
+            .unwrap_or_default();
+        Ok(user
+            .into_iter()
+            .map(|service| ("user", service))
+            .chain(system.into_iter().map(|service| ("system", service)))
+            .map(|(daemon, (name, state, restarts, error))| {
+                (daemon.to_string(), name, state, restarts, error)
+            })
+            .collect())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.SuspendInhibit1")]
+impl SuspendInhibit1 {
+    async fn inhibit_suspend(&self, identifier: &str) -> fdo::Result<Fd> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(SuspendInhibitCommand::Inhibit(identifier.to_string(), tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain suspend inhibit handle"))
+            })?;
+        Ok(rx
+            .await
+            .map_err(to_zbus_fdo_error)?
+            .inspect_err(|message| error!("Error inhibiting suspend: {message}"))
+            .map_err(to_zbus_fdo_error)?
+            .into())
+    }
+
+    async fn list_inhibit_handles(&self) -> fdo::Result<HashMap<String, u32>> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(SuspendInhibitCommand::ListHandles(tx))
+            .map_err(|_| {
+                fdo::Error::Failed(String::from("Failed to obtain suspend inhibit handle list"))
+            })?;
+        rx.await.map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.SysfsAccess1")]
+impl SysfsAccess1 {
+    async fn list_attributes(&self) -> fdo::Result<HashMap<String, bool>> {
+        method!(self, "ListSysfsAttributes")
+    }
+
+    async fn read_attribute(&self, name: &str) -> fdo::Result<String> {
+        method!(self, "ReadSysfsAttribute", name)
+    }
+
+    async fn write_attribute(&self, name: &str, value: &str) -> fdo::Result<()> {
+        method!(self, "WriteSysfsAttribute", name, value)
+    }
+}
+
+impl TextInput1 {
+    /// The system keyboard layout, or none if it can't be read
+    async fn keyboard_layout(&self) -> (String, String) {
+        match (
+            self.locale.x11_layout().await,
+            self.locale.x11_variant().await,
+        ) {
+            (Ok(layout), Ok(variant)) => (layout, variant),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to read the keyboard layout: {e}");
+                (String::new(), String::new())
+            }
+        }
+    }
+}
+
+/// Driving the session's input is only allowed to callers named by a policy rule
+#[interface(name = "com.steampowered.SteamOSManager1.TextInput1")]
+impl TextInput1 {
+    async fn type_text(&mut self, text: &str) -> fdo::Result<()> {
+        let (layout, variant) = self.keyboard_layout().await;
+        self.input
+            .use_layout(layout.as_str(), variant.as_str())
+            .await;
+        let keys = self
+            .input
+            .text_keys(text)
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        if !self.input.allow(keys.len()) {
+            return Err(fdo::Error::LimitsExceeded(String::from(
+                "Too much input sent too quickly",
+            )));
+        }
+        self.input
+            .type_keys(keys.as_slice())
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn send_key_combo(&mut self, keys: Vec<String>) -> fdo::Result<()> {
+        let keys =
+            combo_keys(keys.as_slice()).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        if !self.input.allow(keys.len()) {
+            return Err(fdo::Error::LimitsExceeded(String::from(
+                "Too much input sent too quickly",
+            )));
+        }
+        self.input
+            .press_combo(keys.as_slice())
+            .map_err(to_zbus_fdo_error)
+    }
+}
+
+impl Thermal1 {
+    pub(crate) async fn temperature_changed(
+        &self,
+        kind: SensorKind,
+        ctx: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        match kind {
+            SensorKind::Cpu => self.cpu_temperature_changed(ctx).await,
+            SensorKind::Gpu => self.gpu_temperature_changed(ctx).await,
+            SensorKind::Battery => self.battery_temperature_changed(ctx).await,
+            SensorKind::Ssd => self.ssd_temperature_changed(ctx).await,
+            SensorKind::Other => Ok(()),
+        }
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.Thermal1")]
+impl Thermal1 {
+    #[zbus(property)]
+    async fn cpu_temperature(&self) -> f64 {
+        get_temperature(SensorKind::Cpu).await.unwrap_or(f64::NAN)
+    }
+
+    #[zbus(property)]
+    async fn gpu_temperature(&self) -> f64 {
+        get_temperature(SensorKind::Gpu).await.unwrap_or(f64::NAN)
+    }
+
+    #[zbus(property)]
+    async fn battery_temperature(&self) -> f64 {
+        get_temperature(SensorKind::Battery)
+            .await
+            .unwrap_or(f64::NAN)
+    }
+
+    #[zbus(property)]
+    async fn ssd_temperature(&self) -> f64 {
+        get_temperature(SensorKind::Ssd).await.unwrap_or(f64::NAN)
+    }
+
+    async fn list_sensors(&self) -> fdo::Result<Vec<(String, String, f64)>> {
+        let sensors = list_sensors()
+            .await
+            .inspect_err(|message| error!("Error listing thermal sensors: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        let mut list = Vec::with_capacity(sensors.len());
+        for sensor in sensors {
+            match sensor.read().await {
+                Ok(value) => list.push((sensor.name, sensor.label, value)),
+                Err(e) => warn!(
+                    "Error reading thermal sensor {} {}: {e}",
+                    sensor.name, sensor.label
+                ),
+            }
+        }
+        Ok(list)
+    }
+}
+
+impl TdpLimit1 {
+    async fn tdp_limits_range(&self) -> Option<(TdpLimits, TdpLimits)> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::GetTdpLimitsRange(tx))
+            .ok()?;
+        rx.await.ok()?.ok()
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.TdpLimit1")]
+impl TdpLimit1 {
+    #[zbus(property)]
+    async fn tdp_limit(&self) -> u32 {
+        let (tx, rx) = oneshot::channel();
+        if self
+            .manager
+            .send(TdpManagerCommand::GetTdpLimit(tx))
+            .is_err()
+        {
+            return 0;
+        }
+        rx.await.unwrap_or(Ok(0)).unwrap_or(0)
+    }
+
+    #[zbus(property)]
+    async fn set_tdp_limit(
+        &self,
+        limit: u32,
+        #[zbus(header)] header: Option<Header<'_>>,
+    ) -> zbus::Result<()> {
+        throttle_setter::<Self>(header.as_ref(), "TdpLimit").await?;
+        self.manager
+            .send(TdpManagerCommand::SetTdpLimit(limit))
+            .map_err(|_| zbus::Error::Failure(String::from("Failed to set TDP limit")))
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn tdp_limit_min(&self) -> u32 {
+        let (tx, rx) = oneshot::channel();
+        if self
+            .manager
+            .send(TdpManagerCommand::GetTdpLimitRange(tx))
+            .is_err()
+        {
+            return 0;
+        }
+        if let Ok(range) = rx.await {
+            range.map(|r| *r.start()).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn tdp_limit_max(&self) -> u32 {
+        let (tx, rx) = oneshot::channel();
+        if self
+            .manager
+            .send(TdpManagerCommand::GetTdpLimitRange(tx))
+            .is_err()
+        {
+            return 0;
+        }
+        if let Ok(range) = rx.await {
+            range.map(|r| *r.end()).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn separate_tdp_limits(&self) -> bool {
+        self.tdp_limits_range().await.is_some()
+    }
+
+    #[zbus(property)]
+    async fn tdp_limits(&self) -> (u32, u32, u32) {
+        let (tx, rx) = oneshot::channel();
+        if self
+            .manager
+            .send(TdpManagerCommand::GetTdpLimits(tx))
+            .is_err()
+        {
+            return (0, 0, 0);
+        }
+        match rx.await {
+            Ok(Ok(limits)) => (limits.spl, limits.sppt, limits.fppt),
+            _ => (0, 0, 0),
+        }
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn tdp_limits_min(&self) -> (u32, u32, u32) {
+        self.tdp_limits_range()
+            .await
+            .map_or((0, 0, 0), |(min, _)| (min.spl, min.sppt, min.fppt))
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn tdp_limits_max(&self) -> (u32, u32, u32) {
+        self.tdp_limits_range()
+            .await
+            .map_or((0, 0, 0), |(_, max)| (max.spl, max.sppt, max.fppt))
+    }
+
+    async fn set_tdp_limits(&self, spl: u32, sppt: u32, fppt: u32) -> fdo::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::SetTdpLimits(
+                TdpLimits { spl, sppt, fppt },
+                tx,
+            ))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to set TDP limits")))?;
+        rx.await
+            .map_err(to_zbus_fdo_error)?
+            .inspect_err(|message| error!("Error setting TDP limits: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.TdpProfiles1")]
+impl TdpProfiles1 {
+    async fn set_profile_for_app(&self, app_id: u32, limit: u32) -> fdo::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::SetProfileForApp(app_id, limit, tx))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to set TDP profile")))?;
+        rx.await
+            .map_err(to_zbus_fdo_error)?
+            .inspect_err(|message| error!("Error setting TDP profile: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    async fn clear_profile_for_app(&self, app_id: u32) -> fdo::Result<()> {
+        self.manager
+            .send(TdpManagerCommand::ClearProfileForApp(app_id))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to clear TDP profile")))
+    }
+
+    async fn list_profiles(&self) -> fdo::Result<HashMap<u32, u32>> {
+        let (tx, rx) = oneshot::channel();
+        self.manager
+            .send(TdpManagerCommand::ListProfiles(tx))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to obtain TDP profile list")))?;
+        rx.await.map_err(to_zbus_fdo_error)
+    }
+
+    async fn set_foreground_app(&self, app_id: u32) -> fdo::Result<()> {
+        self.manager
+            .send(TdpManagerCommand::SetForegroundApp(app_id))
+            .map_err(|_| fdo::Error::Failed(String::from("Failed to set foreground app")))
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.UpdateBios1")]
+impl UpdateBios1 {
+    async fn update_bios(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
+        job_method!(self, "UpdateBios")
+    }
+
+    async fn schedule_update_bios(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        method!(self, "ScheduleUpdateBios")?;
+        self.update_scheduled_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+
+    async fn cancel_scheduled_update_bios(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        method!(self, "CancelScheduledUpdateBios")?;
+        self.update_scheduled_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+
+    #[zbus(property)]
+    async fn update_scheduled(&self) -> fdo::Result<bool> {
+        let scheduled: (bool, bool) = method!(self, "GetScheduledUpdates")?;
+        Ok(scheduled.0)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.UpdateController1")]
+impl UpdateController1 {
+    async fn update_controller(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
+        job_method!(self, "UpdateController")
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.UpdateDock1")]
+impl UpdateDock1 {
+    async fn update_dock(&mut self) -> fdo::Result<zvariant::OwnedObjectPath> {
+        job_method!(self, "UpdateDock")
+    }
+
+    async fn schedule_update_dock(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        method!(self, "ScheduleUpdateDock")?;
+        self.update_scheduled_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+
+    async fn cancel_scheduled_update_dock(
+        &self,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        method!(self, "CancelScheduledUpdateDock")?;
+        self.update_scheduled_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+
+    #[zbus(property)]
+    async fn update_scheduled(&self) -> fdo::Result<bool> {
+        let scheduled: (bool, bool) = method!(self, "GetScheduledUpdates")?;
+        Ok(scheduled.1)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.UsbAuthorization1")]
+impl UsbAuthorization1 {
+    #[zbus(property)]
+    async fn authorized_default(&self) -> fdo::Result<bool> {
+        get_usb_authorized_default()
+            .await
+            .inspect_err(|message| error!("Error getting USB authorized default: {message}"))
+            .map_err(to_zbus_fdo_error)
+    }
+
+    #[zbus(property)]
+    async fn set_authorized_default(
+        &self,
+        authorized: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self
+            .proxy
+            .call("SetUsbAuthorizedDefault", &(authorized))
+            .await?;
+        self.authorized_default_changed(&ctx).await
+    }
+
+    async fn list_devices(&self) -> fdo::Result<Vec<(String, String, String, bool)>> {
+        let devices = list_usb_devices()
+            .await
+            .inspect_err(|message| error!("Error listing USB devices: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(devices
+            .into_iter()
+            .map(|device| (device.name, device.id, device.product, device.authorized))
+            .collect())
+    }
+
+    async fn authorize_device(&self, device: &str) -> fdo::Result<()> {
+        method!(self, "SetUsbDeviceAuthorized", device, true)
+    }
+
+    async fn deauthorize_device(&self, device: &str) -> fdo::Result<()> {
+        method!(self, "SetUsbDeviceAuthorized", device, false)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.WakeSources1")]
+impl WakeSources1 {
+    #[zbus(property(emits_changed_signal = "const"))]
+    async fn available_wake_sources(&self) -> fdo::Result<Vec<String>> {
+        let sources = available_wake_sources().await.map_err(to_zbus_fdo_error)?;
+        Ok(sources.iter().map(ToString::to_string).collect())
+    }
+
+    #[zbus(property)]
+    async fn enabled_wake_sources(&self) -> fdo::Result<Vec<String>> {
+        let sources = enabled_wake_sources()
+            .await
+            .inspect_err(|message| error!("Error getting enabled wake sources: {message}"))
+            .map_err(to_zbus_fdo_error)?;
+        Ok(sources.iter().map(ToString::to_string).collect())
+    }
+
+    async fn set_wake_source_enabled(
+        &self,
+        source: &str,
+        enabled: bool,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let source = WakeSource::try_from(source)
+            .map_err(|_| fdo::Error::InvalidArgs(format!("Unknown wake source {source}")))?;
+        let _: () = method!(self, "SetWakeSourceEnabled", source.to_string(), enabled)?;
+        self.enabled_wake_sources_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.WifiDebug1")]
+impl WifiDebug1 {
+    #[zbus(property)]
+    async fn wifi_debug_mode_state(&self) -> fdo::Result<u32> {
+        getter!(self, "WifiDebugModeState")
+    }
+
+    async fn set_wifi_debug_mode(
+        &self,
+        mode: u32,
+        options: HashMap<&str, zvariant::Value<'_>>,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let _: () = method!(self, "SetWifiDebugMode", mode, options)?;
+        self.wifi_debug_mode_state_changed(&ctx)
+            .await
+            .map_err(zbus_to_zbus_fdo)?;
+        Ok(())
+    }
+
+    #[zbus(property)]
+    async fn wifi_backend(&self) -> fdo::Result<String> {
+        match get_wifi_backend().await {
+            Ok(backend) => Ok(backend.to_string()),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
+    }
+
+    #[zbus(property)]
+    async fn set_wifi_backend(
+        &self,
+        backend: &str,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let backend = match WifiBackend::try_from(backend) {
+            Ok(backend) => backend,
+            Err(e) => return Err(fdo::Error::InvalidArgs(e.to_string()).into()),
+        };
+        let _: () = self.proxy.call("SetWifiBackend", &(backend as u32)).await?;
+        self.wifi_backend_changed(&ctx).await
+    }
+
+    async fn capture_debug_trace_output(&self) -> fdo::Result<String> {
+        method!(self, "CaptureDebugTraceOutput")
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.WifiDebugDump1")]
+impl WifiDebugDump1 {
+    async fn generate_debug_dump(&self) -> fdo::Result<String> {
+        method!(self, "GenerateDebugDump")
+    }
+}
+
+impl WifiLinkReceiver for WifiInfo1 {
+    async fn update_link(&mut self, link: WifiLink, ctx: &SignalEmitter<'_>) -> zbus::Result<()> {
+        let old = std::mem::replace(&mut self.link, link);
+        if old.ssid != self.link.ssid {
+            self.ssid_changed(ctx).await?;
+        }
+        if old.bssid != self.link.bssid {
+            self.bssid_changed(ctx).await?;
+        }
+        if old.frequency != self.link.frequency {
+            self.frequency_changed(ctx).await?;
+        }
+        if old.signal != self.link.signal {
+            self.signal_changed(ctx).await?;
+        }
+        if old.tx_bitrate != self.link.tx_bitrate {
+            self.tx_bitrate_changed(ctx).await?;
+        }
+        if old.rx_bitrate != self.link.rx_bitrate {
+            self.rx_bitrate_changed(ctx).await?;
+        }
+        Ok(())
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.WifiInfo1")]
+impl WifiInfo1 {
+    #[zbus(property)]
+    async fn ssid(&self) -> String {
+        self.link.ssid.clone()
+    }
+
+    #[zbus(property)]
+    async fn bssid(&self) -> String {
+        self.link.bssid.clone()
+    }
+
+    #[zbus(property)]
+    async fn frequency(&self) -> u32 {
+        self.link.frequency
+    }
+
+    #[zbus(property)]
+    async fn signal(&self) -> i32 {
+        self.link.signal
+    }
+
+    #[zbus(property)]
+    async fn tx_bitrate(&self) -> f64 {
+        self.link.tx_bitrate
+    }
+
+    #[zbus(property)]
+    async fn rx_bitrate(&self) -> f64 {
+        self.link.rx_bitrate
+    }
+}
+
+#[interface(name = "com.steampowered.SteamOSManager1.WifiPowerManagement1")]
+impl WifiPowerManagement1 {
+    #[zbus(property)]
+    async fn wifi_power_management_state(&self) -> fdo::Result<u32> {
+        match get_wifi_power_management_state().await {
+            Ok(state) => Ok(state as u32),
+            Err(e) => Err(to_zbus_fdo_error(e)),
+        }
+    }
+
+    #[zbus(property)]
+    async fn set_wifi_power_management_state(
+        &self,
+        state: u32,
+        #[zbus(signal_emitter)] ctx: SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let _: () = self
+            .proxy
+            .call("SetWifiPowerManagementState", &(state))
+            .await?;
+        self.wifi_power_management_state_changed(&ctx).await
+    }
+}
+
+impl SignalRelayService {
+    async fn relay_max_charge_level_changed(&self) -> Result<()> {
+        // The interface may come and go with hotplugged hardware, so look it up every time
+        let Ok(battery_charge_limit) = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<BatteryChargeLimit1>>(MANAGER_PATH)
+            .await
+        else {
+            return Ok(());
+        };
+        let ctx = battery_charge_limit.signal_emitter();
+        battery_charge_limit
+            .get()
+            .await
+            .max_charge_level_changed(ctx)
+            .await?;
+        Ok(())
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        // Every interface's proxy addresses the root daemon by its well-known name rather than
+        // its unique name, so they all follow it to the new process without being rebuilt. Jobs
+        // that were running in the old one are gone though, and their paths will be reused by the
+        // new one, so they have to be mirrored from scratch.
+        let system = self.proxy.connection().clone();
+        self.job_manager
+            .send(JobManagerCommand::RemirrorConnection(system))?;
+        let manager = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<Manager2>>(MANAGER_PATH)
+            .await?;
+        Manager2::backend_reconnected(manager.signal_emitter()).await?;
+        Ok(())
+    }
+
+    /// Relay signals from the root daemon, returning true once it has come back after going away,
+    /// so that the signals get subscribed to again
+    async fn relay_signals(&self, owner_changed: &mut OwnerChangedStream<'_>) -> Result<bool> {
+        let mut max_charge_level_changed =
+            self.proxy.receive_signal("MaxChargeLevelChanged").await?;
+        let mut sysfs_write_failed = self.proxy.receive_signal("SysfsWriteFailed").await?;
+        let mut service_restarted = self.proxy.receive_signal("ServiceRestarted").await?;
+        loop {
+            tokio::select! {
+                Some(owner) = owner_changed.next() => {
+                    match owner {
+                        Some(owner) => {
+                            info!("Root daemon is back as {owner}, reconnecting");
+                            self.reconnect().await?;
+                            return Ok(true);
+                        }
+                        None => warn!("Root daemon went away"),
+                    }
+                }
+                Some(_) = max_charge_level_changed.next() => {
+                    self.relay_max_charge_level_changed().await?;
+                }
+                Some(message) = sysfs_write_failed.next() => {
+                    let (path, error): (String, String) = message.body().deserialize()?;
+                    let manager = self
+                        .session
+                        .object_server()
+                        .interface::<_, Guarded<Manager2>>(MANAGER_PATH)
+                        .await?;
+                    Manager2::sysfs_write_failed(manager.signal_emitter(), &path, &error).await?;
+                }
+                Some(message) = service_restarted.next() => {
+                    let (daemon, service, restarts): (String, String, u32) =
+                        message.body().deserialize()?;
+                    let manager = self
+                        .session
+                        .object_server()
+                        .interface::<_, Guarded<Manager2>>(MANAGER_PATH)
+                        .await?;
+                    Manager2::service_restarted(
+                        manager.signal_emitter(),
+                        &daemon,
+                        &service,
+                        restarts,
+                    )
+                    .await?;
+                }
+                else => return Ok(false),
+            }
+        }
+    }
+}
+
+impl Service for SignalRelayService {
+    const NAME: &'static str = "signal-relay";
+
+    async fn run(&mut self) -> Result<()> {
+        let mut owner_changed = self.proxy.receive_owner_changed().await?;
+        while self.relay_signals(&mut owner_changed).await? {}
+        Ok(())
+    }
+}
+
+async fn set_interface_available<I: Interface>(
+    object_server: &ObjectServer,
+    interface: I,
+    available: bool,
+) -> Result<()> {
+    let changed = if available {
+        let added = object_server
+            .at(MANAGER_PATH, Guarded::new(interface))
+            .await?;
+        if added {
+            debug!("Adding {} interface", I::name());
+        }
+        added
+    } else {
+        let removed = object_server.remove::<Guarded<I>, _>(MANAGER_PATH).await?;
+        if removed {
+            debug!("Removing {} interface", I::name());
+        }
+        removed
+    };
+    if changed {
+        if let Ok(manager) = object_server
+            .interface::<_, Guarded<Manager2>>(MANAGER_PATH)
+            .await
+        {
+            manager
+                .get()
+                .await
+                .features_changed(manager.signal_emitter())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn has_interface<I: Interface>(object_server: &ObjectServer) -> bool {
+    object_server
+        .interface::<_, Guarded<I>>(MANAGER_PATH)
+        .await
+        .is_ok()
+}
+
+/// Which config file decides whether an optional interface is available
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum ConfigSource {
+    Platform,
+    Device,
+}
+
+type InterfacePresent =
+    for<'a> fn(&'a ObjectServer) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
+
+/// An interface that is only exported when the device supports it. Each one provides a named
+/// capability, so that clients don't have to probe for the interfaces themselves.
+struct OptionalInterface {
+    feature: &'static str,
+    name: InterfaceName<'static>,
+    /// The config file that enables the interface, if any
+    config: Option<ConfigSource>,
+    present: InterfacePresent,
+}
+
+fn interface_present<I: Interface>(
+    object_server: &ObjectServer,
+) -> Pin<Box<dyn Future<Output = bool> + Send + '_>> {
+    Box::pin(has_interface::<I>(object_server))
+}
+
+optional_interfaces! {
+    "accessibility" => Accessibility1,
+    "ambient-light" => AmbientLightSensor1,
+    "audio" => Audio1,
+    "backlight" => Backlight1,
+    "battery" => Battery1,
+    "battery-charge-limit" => BatteryChargeLimit1,
+    "battery-policy" => BatteryPolicy1,
+    "charge-led" => ChargeLed1: Device,
+    "color-filters" => ColorFilters1,
+    "cpu-boost" => CpuBoost1,
+    "cpu-frequency" => CpuFrequency1,
+    "cpu-scaling" => CpuScaling1,
+    "developer-mode" => DeveloperMode1: Platform,
+    "display-output" => DisplayOutput1,
+    "dock" => Dock1: Platform,
+    "factory-reset" => FactoryReset1: Platform,
+    "fan-control" => FanControl1: Platform,
+    "fan-curve" => FanCurve1: Device,
+    "firmware-info" => FirmwareInfo1,
+    "gpu-metrics" => GpuMetrics1,
+    "gpu-performance-level" => GpuPerformanceLevel1,
+    "gpu-power" => GpuPower1,
+    "gpu-power-profile" => GpuPowerProfile1,
+    "gpu-throttle" => GpuThrottle1,
+    "haptics" => Haptics1: Device,
+    "hdmi-cec" => HdmiCec1,
+    "idle-policy" => IdlePolicy1,
+    "input-inhibit" => InputInhibit1,
+    "led-control" => LedControl1: Device,
+    "low-power-mode" => LowPowerMode1: Device,
+    "network-limit" => NetworkLimit1,
+    "os-update" => OsUpdate1: Platform,
+    "os-update-channel" => OsUpdateChannel1: Platform,
+    "performance-profile" => PerformanceProfile1: Device,
+    "power-history" => PowerHistory1,
+    "power-presets" => PowerPresets1,
+    "power-source" => PowerSource1,
+    "screen-reader" => ScreenReader0,
+    "services" => Services1: Platform,
+    "session-management" => SessionManagement1,
+    "sleep-config" => SleepConfig1,
+    "smt" => Smt1,
+    "storage" => Storage1: Platform,
+    "storage-health" => StorageHealth1: Platform,
+    "suspend-inhibit" => SuspendInhibit1,
+    "sysfs-access" => SysfsAccess1: Platform,
+    "tdp-limit" => TdpLimit1: Device,
+    "tdp-profiles" => TdpProfiles1: Device,
+    "text-input" => TextInput1,
+    "thermal" => Thermal1,
+    "update-bios" => UpdateBios1: Platform,
+    "update-controller" => UpdateController1: Device,
+    "update-dock" => UpdateDock1: Platform,
+    "usb-authorization" => UsbAuthorization1: Platform,
+    "wake-sources" => WakeSources1: Device,
+    "wifi-debug" => WifiDebug1,
+    "wifi-debug-dump" => WifiDebugDump1,
+    "wifi-info" => WifiInfo1,
+    "wifi-power-management" => WifiPowerManagement1,
+}
+
+/// The capabilities that are currently available
+async fn available_features(object_server: &ObjectServer) -> Vec<String> {
+    let mut features = Vec::new();
+    for interface in optional_interfaces() {
+        if (interface.present)(object_server).await {
+            features.push(String::from(interface.feature));
+        }
+    }
+    features
+}
+
+pub(crate) async fn update_hotplug_interfaces(
+    proxy: &Proxy<'static>,
+    object_server: &ObjectServer,
+) -> Result<()> {
+    let battery_charge_limit = BatteryChargeLimit1 {
+        proxy: proxy.clone(),
+    };
+    let wifi_power_management = WifiPowerManagement1 {
+        proxy: proxy.clone(),
+    };
+
+    set_interface_available(object_server, Battery1 {}, find_battery().await.is_ok()).await?;
+    set_interface_available(
+        object_server,
+        battery_charge_limit,
+        get_max_charge_level().await.is_ok(),
+    )
+    .await?;
+    set_interface_available(
+        object_server,
+        Thermal1 {},
+        !list_sensors().await.unwrap_or_default().is_empty(),
+    )
+    .await?;
+    let has_wifi = !list_wifi_interfaces().await.unwrap_or_default().is_empty();
+    set_interface_available(object_server, WifiInfo1::default(), has_wifi).await?;
+    set_interface_available(object_server, wifi_power_management, has_wifi).await?;
+
+    Ok(())
+}
+
+pub(crate) async fn root_manager_proxy(system: &Connection) -> Result<Proxy<'static>> {
+    Ok(Builder::<Proxy>::new(system)
+        .destination("com.steampowered.SteamOSManager1")?
+        .path("/com/steampowered/SteamOSManager1")?
+        .interface("com.steampowered.SteamOSManager1.RootManager")?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?)
+}
+
+async fn create_platform_interfaces(
+    proxy: &Proxy<'static>,
+    object_server: &ObjectServer,
+    connection: &Connection,
+    job_manager: &UnboundedSender<JobManagerCommand>,
+) -> Result<()> {
+    let config = platform_config().await?;
+    let report = validate_platform_config(config.as_ref(), connection).await;
+    log_unavailable(&report);
+
+    if config_enabled::<FactoryReset1>(&report) {
+        let factory_reset = FactoryReset1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(factory_reset))
+            .await?;
+    }
+
+    if config_enabled::<FanControl1>(&report) {
+        let fan_control = FanControl1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(fan_control))
+            .await?;
+    }
+
+    if config_enabled::<Storage1>(&report) {
+        let storage = Storage1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(storage))
+            .await?;
+    }
+
+    if config_enabled::<StorageHealth1>(&report) {
+        let storage_health = StorageHealth1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(storage_health))
+            .await?;
+    }
+
+    if config_enabled::<UpdateBios1>(&report) {
+        let update_bios = UpdateBios1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(update_bios))
+            .await?;
+    }
+
+    if config_enabled::<UpdateDock1>(&report) {
+        let update_dock = UpdateDock1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(update_dock))
+            .await?;
+    }
+
+    if config_enabled::<SysfsAccess1>(&report) {
+        let sysfs_access = SysfsAccess1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(sysfs_access))
+            .await?;
+    }
+
+    if config_enabled::<Dock1>(&report) {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(Dock1::default()))
+            .await?;
+    }
+
+    if config_enabled::<UsbAuthorization1>(&report) {
+        let usb_authorization = UsbAuthorization1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(usb_authorization))
+            .await?;
+    }
+
+    if config_enabled::<DeveloperMode1>(&report) {
+        let developer_mode = DeveloperMode1 {
+            proxy: proxy.clone(),
+            system: connection.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(developer_mode))
+            .await?;
+    }
+
+    if config_enabled::<Services1>(&report) {
+        let services = Services1 {
+            proxy: proxy.clone(),
+            system: connection.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(services))
+            .await?;
+    }
+
+    if config_enabled::<OsUpdate1>(&report) {
+        let os_update = OsUpdate1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(os_update))
+            .await?;
+    }
+
+    if config_enabled::<OsUpdateChannel1>(&report) {
+        let update_channel = OsUpdateChannel1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(update_channel))
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn available_developer_features(
+    config: &DeveloperModeConfig,
+    connection: &Connection,
+) -> Vec<DeveloperFeature> {
+    let mut features = Vec::new();
+    for feature in [
+        DeveloperFeature::Ssh,
+        DeveloperFeature::CoreDumps,
+        DeveloperFeature::PersistentJournal,
+    ] {
+        match config.is_valid(connection, feature).await {
+            Ok(true) => features.push(feature),
+            Ok(false) => (),
+            Err(e) => error!("Failed to verify if developer mode {feature} config is valid: {e}"),
+        }
+    }
+    features
+}
+
+async fn create_device_interfaces(
+    proxy: &Proxy<'static>,
+    object_server: &ObjectServer,
+    job_manager: &UnboundedSender<JobManagerCommand>,
+    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+) -> Result<()> {
+    let config = device_config().await?;
+    let report = validate_device_config(config.as_ref()).await;
+    log_unavailable(&report);
+
+    let performance_profile = PerformanceProfile1 {
+        proxy: proxy.clone(),
+        tdp_limit_manager: tdp_manager.clone(),
+    };
+
+    if let Some(manager) = tdp_manager {
+        if config_enabled::<LowPowerMode1>(&report) {
+            let low_power_mode = LowPowerMode1 {
+                manager: manager.clone(),
+            };
+            object_server
+                .at(MANAGER_PATH, Guarded::new(low_power_mode))
+                .await?;
+        }
+
+        if config_enabled::<TdpProfiles1>(&report) {
+            let tdp_profiles = TdpProfiles1 {
+                manager: manager.clone(),
+            };
+            object_server
+                .at(MANAGER_PATH, Guarded::new(tdp_profiles))
+                .await?;
+        }
+
+        if config_enabled::<TdpLimit1>(&report) {
+            let object_server = object_server.clone();
+            tokio::spawn(async move {
+                let (tx, rx) = oneshot::channel();
+                manager.send(TdpManagerCommand::IsActive(tx))?;
+                if rx.await?? {
+                    let tdp_limit = TdpLimit1 { manager };
+                    object_server
+                        .at(MANAGER_PATH, Guarded::new(tdp_limit))
+                        .await?;
+                }
+                Ok::<(), Error>(())
+            });
+        }
+    }
+
+    if config_enabled::<PerformanceProfile1>(&report) {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(performance_profile))
+            .await?;
+    }
+
+    if config_enabled::<FanCurve1>(&report) {
+        let fan_curve = FanCurve1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(fan_curve))
+            .await?;
+    }
+
+    if config_enabled::<Haptics1>(&report) {
+        let haptics = Haptics1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(haptics))
+            .await?;
+    }
+
+    if config_enabled::<ChargeLed1>(&report) {
+        let charge_led = ChargeLed1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(charge_led))
+            .await?;
+    }
+
+    if config_enabled::<LedControl1>(&report) {
+        let led_control = LedControl1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(led_control))
+            .await?;
+    }
+
+    if config_enabled::<WakeSources1>(&report) {
+        let wake_sources = WakeSources1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(wake_sources))
+            .await?;
+    }
+
+    if config_enabled::<UpdateController1>(&report) {
+        let update_controller = UpdateController1 {
+            proxy: proxy.clone(),
+            job_manager: job_manager.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(update_controller))
+            .await?;
+    }
+
+    Ok(())
+}
+
+type ConfigReport = Vec<(String, bool, String)>;
+
+fn config_entry<I: Interface>(enabled: bool, reason: impl Into<String>) -> (String, bool, String) {
+    (I::name().to_string(), enabled, reason.into())
+}
+
+fn checked_config_entry<I: Interface>(what: &str, valid: Result<bool>) -> (String, bool, String) {
+    match valid {
+        Ok(true) => config_entry::<I>(true, format!("{what} is configured")),
+        Ok(false) => config_entry::<I>(
+            false,
+            format!("{what} script or service is missing or not executable"),
+        ),
+        Err(e) => config_entry::<I>(false, format!("Failed to verify {what} config: {e}")),
+    }
+}
+
+/// Report every interface enabled by the given config as disabled
+fn unavailable(source: ConfigSource, reason: &str) -> ConfigReport {
+    optional_interfaces()
+        .into_iter()
+        .filter(|interface| interface.config == Some(source))
+        .map(|interface| (interface.name.to_string(), false, String::from(reason)))
+        .collect()
+}
+
+fn config_enabled<I: Interface>(report: &ConfigReport) -> bool {
+    let name = I::name();
+    report
+        .iter()
+        .any(|(interface, enabled, _)| *enabled && interface == name.as_str())
+}
+
+fn log_unavailable(report: &ConfigReport) {
+    for (interface, _, reason) in report.iter().filter(|(_, enabled, _)| !enabled) {
+        debug!("{interface} is unavailable: {reason}");
+    }
+}
+
+async fn validate_platform_config(
+    config: Option<&PlatformConfig>,
+    connection: &Connection,
+) -> ConfigReport {
+    let Some(config) = config else {
+        return unavailable(ConfigSource::Platform, "No platform config found");
+    };
+    let missing = |what: &str| format!("No {what} config in the platform config");
+
+    let mut report = Vec::new();
+    report.push(match config.developer_mode.as_ref() {
+        Some(config) => {
+            let features = available_developer_features(config, connection).await;
+            if features.is_empty() {
+                config_entry::<DeveloperMode1>(false, "No developer mode units are installed")
+            } else {
+                let features: Vec<String> = features.iter().map(ToString::to_string).collect();
+                config_entry::<DeveloperMode1>(
+                    true,
+                    format!("Developer mode supports {}", features.join(", ")),
+                )
+            }
+        }
+        None => config_entry::<DeveloperMode1>(false, missing("developer mode")),
+    });
+    report.push(match config.dock.as_ref() {
+        Some(config) if config.models.is_empty() => {
+            config_entry::<Dock1>(false, "No dock models in the dock config")
+        }
+        Some(_) => config_entry::<Dock1>(true, "Dock models are configured"),
+        None => config_entry::<Dock1>(false, missing("dock")),
+    });
+    report.push(match config.factory_reset.as_ref() {
+        Some(config) => {
+            checked_config_entry::<FactoryReset1>("factory reset", config.is_valid(true).await)
+        }
+        None => config_entry::<FactoryReset1>(false, missing("factory reset")),
+    });
+    report.push(match config.fan_control.as_ref() {
+        Some(config) => checked_config_entry::<FanControl1>(
+            "fan control",
+            config.is_valid(connection, true).await,
+        ),
+        None => config_entry::<FanControl1>(false, missing("fan control")),
+    });
+    report.push(if configured_units(config).is_empty() {
+        config_entry::<Services1>(false, "No managed units in the platform config")
+    } else {
+        match available_units(connection).await {
+            Ok(units) if units.is_empty() => {
+                config_entry::<Services1>(false, "No managed units are installed")
+            }
+            Ok(units) => config_entry::<Services1>(true, format!("Managing {}", units.join(", "))),
+            Err(e) => config_entry::<Services1>(false, format!("Failed to look up units: {e}")),
+        }
+    });
+    match config.storage.as_ref() {
+        Some(config) => {
+            report.push(checked_config_entry::<Storage1>(
+                "storage",
+                config.is_valid(true).await,
+            ));
+            report.push(match config.health.as_ref() {
+                Some(health) => checked_config_entry::<StorageHealth1>(
+                    "storage health",
+                    health.is_valid(true).await,
+                ),
+                None => config_entry::<StorageHealth1>(false, missing("storage health")),
+            });
+        }
+        None => {
+            report.push(config_entry::<Storage1>(false, missing("storage")));
+            report.push(config_entry::<StorageHealth1>(false, missing("storage")));
+        }
+    }
+    let valid_attributes = config
+        .sysfs_access
+        .values()
+        .filter(|attribute| attribute.is_valid())
+        .count();
+    report.push(if config.sysfs_access.is_empty() {
+        config_entry::<SysfsAccess1>(false, missing("sysfs access"))
+    } else {
+        config_entry::<SysfsAccess1>(
+            true,
+            format!(
+                "{valid_attributes} of {} sysfs attributes are valid",
+                config.sysfs_access.len()
+            ),
+        )
+    });
+    report.push(match config.update_bios.as_ref() {
+        Some(config) => {
+            checked_config_entry::<UpdateBios1>("BIOS update", config.is_valid(true).await)
+        }
+        None => config_entry::<UpdateBios1>(false, missing("BIOS update")),
+    });
+    report.push(match config.update_dock.as_ref() {
+        Some(config) => {
+            checked_config_entry::<UpdateDock1>("dock update", config.is_valid(true).await)
+        }
+        None => config_entry::<UpdateDock1>(false, missing("dock update")),
+    });
+    report.push(match config.usb_authorization.as_ref() {
+        Some(_) => match list_host_controllers().await {
+            Ok(controllers) if !controllers.is_empty() => {
+                config_entry::<UsbAuthorization1>(true, "USB authorization is configured")
+            }
+            Ok(_) => config_entry::<UsbAuthorization1>(false, "No USB host controllers found"),
+            Err(e) => config_entry::<UsbAuthorization1>(
+                false,
+                format!("Failed to list USB host controllers: {e}"),
+            ),
+        },
+        None => config_entry::<UsbAuthorization1>(false, missing("USB authorization")),
+    });
+    report.push(match config.os_update.as_ref() {
+        Some(config) => checked_config_entry::<OsUpdate1>("OS update", config.is_valid(true).await),
+        None => config_entry::<OsUpdate1>(false, missing("OS update")),
+    });
+    report.push(match config.update_channel.as_ref() {
+        Some(config) => {
+            checked_config_entry::<OsUpdateChannel1>("update channel", config.is_valid(true).await)
+        }
+        None => config_entry::<OsUpdateChannel1>(false, missing("update channel")),
+    });
+    report
+}
+
+async fn validate_device_config(config: Option<&DeviceConfig>) -> ConfigReport {
+    let Some(config) = config else {
+        return unavailable(ConfigSource::Device, "No device config matches this device");
+    };
+    let missing = |what: &str| format!("No {what} config in the device config");
+
+    let mut report = Vec::new();
+    report.push(match config.fan_curve.as_ref() {
+        Some(_) => match get_fan_curve().await {
+            Ok(_) => config_entry::<FanCurve1>(true, "Fan curve is configured"),
+            Err(e) => config_entry::<FanCurve1>(false, format!("Failed to read fan curve: {e}")),
+        },
+        None => config_entry::<FanCurve1>(false, missing("fan curve")),
+    });
+    report.push(match config.haptics.as_ref() {
+        Some(_) => match get_haptics_intensity().await {
+            Ok(_) => config_entry::<Haptics1>(true, "Haptics are configured"),
+            Err(e) => {
+                config_entry::<Haptics1>(false, format!("Failed to read haptics intensity: {e}"))
+            }
+        },
+        None => config_entry::<Haptics1>(false, missing("haptics")),
+    });
+    report.push(match config.charge_led.as_ref() {
+        Some(_) => match get_charge_led_mode().await {
+            Ok(_) => config_entry::<ChargeLed1>(true, "Charge LED is configured"),
+            Err(e) => {
+                config_entry::<ChargeLed1>(false, format!("Failed to read charge LED mode: {e}"))
+            }
+        },
+        None => config_entry::<ChargeLed1>(false, missing("charge LED")),
+    });
+    report.push(match config.led_control.as_ref() {
+        Some(led_control) => match get_led_max_brightness().await {
+            Ok(_) => config_entry::<LedControl1>(true, "LED control is configured"),
+            Err(e) => config_entry::<LedControl1>(
+                false,
+                format!("Failed to read LED {}: {e}", led_control.led),
+            ),
+        },
+        None => config_entry::<LedControl1>(false, missing("LED control")),
+    });
+    match config.tdp_limit.as_ref() {
+        Some(tdp_limit) => {
+            report.push(if tdp_limit.download_mode_limit.is_some() {
+                config_entry::<LowPowerMode1>(true, "Download mode limit is configured")
+            } else {
+                config_entry::<LowPowerMode1>(
+                    false,
+                    "No download mode limit in the TDP limit config",
+                )
+            });
+            report.push(config_entry::<TdpLimit1>(
+                true,
+                "TDP limiting is configured, and is available while the limiter is active",
+            ));
+            report.push(config_entry::<TdpProfiles1>(
+                true,
+                "TDP limiting is configured",
+            ));
+        }
+        None => {
+            report.push(config_entry::<LowPowerMode1>(false, missing("TDP limit")));
+            report.push(config_entry::<TdpLimit1>(false, missing("TDP limit")));
+            report.push(config_entry::<TdpProfiles1>(false, missing("TDP limit")));
+        }
+    }
+    report.push(match config.performance_profile.as_ref() {
+        Some(config) => {
+            let name = config.platform_profile_name.as_str();
+            if get_available_platform_profiles(name)
+                .await
+                .unwrap_or_default()
+                .is_empty()
+            {
+                config_entry::<PerformanceProfile1>(
+                    false,
+                    format!("Platform profile {name} has no available profiles"),
+                )
+            } else {
+                config_entry::<PerformanceProfile1>(true, "Performance profile is configured")
+            }
+        }
+        None => config_entry::<PerformanceProfile1>(false, missing("performance profile")),
+    });
+    report.push(match config.update_controller.as_ref() {
+        Some(config) => checked_config_entry::<UpdateController1>(
+            "controller update",
+            config.is_valid(true).await,
+        ),
+        None => config_entry::<UpdateController1>(false, missing("controller update")),
+    });
+    report.push(match config.wake_sources.as_ref() {
+        Some(_) => match available_wake_sources().await {
+            Ok(sources) if sources.is_empty() => {
+                config_entry::<WakeSources1>(false, "No wake source has any devices configured")
+            }
+            Ok(sources) => config_entry::<WakeSources1>(
+                true,
+                format!(
+                    "Wake sources {} are configured",
+                    sources
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            Err(e) => {
+                config_entry::<WakeSources1>(false, format!("Failed to read wake sources: {e}"))
+            }
+        },
+        None => config_entry::<WakeSources1>(false, missing("wake sources")),
+    });
+    report
+}
+
+/// Check which config-dependent interfaces are enabled, and why the others aren't. The config
+/// files are read afresh, so that edits can be checked without restarting the daemons.
+pub(crate) async fn validate_config(connection: &Connection) -> ConfigReport {
+    let mut report = match read_platform_config().await {
+        Ok(config) => validate_platform_config(config.as_ref(), connection).await,
+        Err(e) => unavailable(
+            ConfigSource::Platform,
+            &format!("Failed to load platform config: {e}"),
+        ),
+    };
+    report.extend(match read_device_config().await {
+        Ok(config) => validate_device_config(config.as_ref()).await,
+        Err(e) => unavailable(
+            ConfigSource::Device,
+            &format!("Failed to load device config: {e}"),
+        ),
+    });
+    report.sort();
+    report
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn timed_probe<T>(what: &str, probe: impl Future<Output = Result<T>>) -> Result<T> {
+    let started = Instant::now();
+    let result = probe.await;
+    debug!("Probing {what} took {:?}", started.elapsed());
+    result
+}
+
+pub(crate) async fn create_interfaces(
+    session: Connection,
+    system: Connection,
+    daemon: Sender<Command>,
+    job_manager: UnboundedSender<JobManagerCommand>,
+    tdp_manager: Option<UnboundedSender<TdpManagerCommand>>,
+    suspend_inhibit: UnboundedSender<SuspendInhibitCommand>,
+    input_inhibit: UnboundedSender<InputInhibitCommand>,
+    telemetry: UnboundedSender<TelemetryCommand>,
+) -> Result<SignalRelayService> {
+    let proxy = root_manager_proxy(&system).await?;
+
+    let manager = SteamOSManager::new(system.clone(), proxy.clone(), job_manager.clone()).await?;
+
+    let als = AmbientLightSensor1 {
+        proxy: proxy.clone(),
+        channel: daemon.clone(),
+        lux: None,
+    };
+    let cpu_boost = CpuBoost1 {
+        proxy: proxy.clone(),
+    };
+    let cpu_frequency = CpuFrequency1 {
+        proxy: proxy.clone(),
+    };
+    let cpu_scaling = CpuScaling1 {
+        proxy: proxy.clone(),
+    };
+    let firmware_info = FirmwareInfo1 {
+        proxy: proxy.clone(),
+        versions: Mutex::new(None),
+    };
+    let hdmi_cec = HdmiCec1::new(&session).await?;
+    let input_inhibit = InputInhibit1 {
+        manager: input_inhibit,
+    };
+    let job_history = JobHistory1 {
+        proxy: proxy.clone(),
+    };
+    let manager2 = Manager2 {
+        proxy: proxy.clone(),
+        channel: daemon.clone(),
+        job_manager: job_manager.clone(),
+    };
+    let network_limit = NetworkLimit1 {
+        proxy: proxy.clone(),
+    };
+    let power_history = PowerHistory1 { manager: telemetry };
+    let power_presets = PowerPresets1 {
+        channel: daemon.clone(),
+        lock: Mutex::new(()),
+    };
+    let color_filters = ColorFilters1 {
+        session: session.clone(),
+        channel: daemon.clone(),
+    };
+    let idle_policy = IdlePolicy1 {
+        channel: daemon.clone(),
+    };
+    let screen_reader = ScreenReader0::new(&session).await?;
+    let status = Status1 {
+        proxy: proxy.clone(),
+        channel: daemon.clone(),
+    };
+    let session_management = SessionManagement1 {
+        proxy: proxy.clone(),
+        manager: SessionManager::new(session.clone(), &system, daemon).await?,
+        desktop_sessions: None,
+    };
+    let sleep_config = SleepConfig1 {
+        proxy: proxy.clone(),
+    };
+    let smt = Smt1 {
+        proxy: proxy.clone(),
+    };
+    let suspend_inhibit = SuspendInhibit1 {
+        manager: suspend_inhibit,
+    };
+
+    let object_server = session.object_server();
+    object_server
+        .at(MANAGER_PATH, Guarded::new(manager))
+        .await?;
+
+    // Each probe scans sysfs or asks another service, so run them all at once rather than making
+    // session startup wait on each of them in turn
+    let started = Instant::now();
+    let ((), (), (), probes) = try_join!(
+        timed_probe(
+            "device interfaces",
+            create_device_interfaces(&proxy, object_server, &job_manager, tdp_manager),
+        ),
+        timed_probe(
+            "platform interfaces",
+            create_platform_interfaces(&proxy, object_server, &system, &job_manager),
+        ),
+        timed_probe(
+            "hotplug interfaces",
+            update_hotplug_interfaces(&proxy, object_server),
+        ),
+        timed_probe("system interfaces", async {
+            Ok::<_, Error>(join!(
+                device_type(),
+                steam_deck_variant(),
+                find_backlight(),
+                async {
+                    list_outputs()
+                        .await
+                        .is_ok_and(|outputs| !outputs.is_empty())
+                },
+                async { get_cpu_boost_state().await.is_ok() },
+                async { get_cpu_frequency_range().await.is_ok() },
+                async { get_gpu_metrics().await.is_ok() },
+                async { get_gpu_power_cap().await.is_ok() },
+                gpu_performance_level_driver(),
+                gpu_power_profile_driver(),
+                async { get_gpu_thermal_throttling().await.is_ok() },
+                async { hdmi_cec.hdmi_cec.get_enabled_state().await.is_ok() },
+                try_exists(path(TC_PATH)),
+                session_management.manager.current_login_mode(),
+                try_exists(path("/usr/bin/orca")),
+                is_session_managed(),
+                async { get_smt_enabled().await.is_ok() },
+                async {
+                    get_supported_sleep_modes()
+                        .await
+                        .is_ok_and(|modes| !modes.is_empty())
+                },
+            ))
+        }),
+    )?;
+    let (
+        device,
+        variant,
+        backlight,
+        has_outputs,
+        has_cpu_boost,
+        has_cpu_frequency,
+        has_gpu_metrics,
+        has_gpu_power,
+        gpu_performance_driver,
+        gpu_profile_driver,
+        has_gpu_throttle,
+        has_hdmi_cec,
+        has_tc,
+        login_mode,
+        has_orca,
+        session_managed,
+        has_smt,
+        has_sleep_modes,
+    ) = probes;
+    debug!("Probed interfaces in {:?}", started.elapsed());
+
+    let accessibility = Accessibility1 {
+        manager: AccessibilityManager::new(),
+    };
+    object_server
+        .at(MANAGER_PATH, Guarded::new(accessibility))
+        .await?;
+
+    if device.unwrap_or_default() == "steam_deck" {
+        object_server.at(MANAGER_PATH, Guarded::new(als)).await?;
+    }
+    if variant.unwrap_or_default() == SteamDeckVariant::Galileo {
+        let wifi_debug = WifiDebug1 {
+            proxy: proxy.clone(),
+        };
+        let wifi_debug_dump = WifiDebugDump1 {
+            proxy: proxy.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(wifi_debug))
+            .await?;
+        object_server
+            .at(MANAGER_PATH, Guarded::new(wifi_debug_dump))
+            .await?;
+    }
+
+    if let Ok(device) = backlight {
+        let backlight = Backlight1 {
+            proxy: proxy.clone(),
+            device,
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(backlight))
+            .await?;
+    }
+
+    if has_outputs {
+        let display_output = DisplayOutput1 {
+            channel: daemon.clone(),
+        };
+        object_server
+            .at(MANAGER_PATH, Guarded::new(display_output))
+            .await?;
+    }
+
+    let audio = Audio1 {
+        system: system.clone(),
+        speaker_limit_override: false,
+    };
+    object_server.at(MANAGER_PATH, Guarded::new(audio)).await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(BatteryPolicy1::default()))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(color_filters))
+        .await?;
+
+    if has_cpu_boost {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(cpu_boost))
+            .await?;
+    }
+
+    if has_cpu_frequency {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(cpu_frequency))
+            .await?;
+    }
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(cpu_scaling))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(firmware_info))
+        .await?;
+
+    if has_gpu_metrics {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(GpuMetrics1 {}))
+            .await?;
+    }
+
+    if has_gpu_power {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(GpuPower1 {}))
+            .await?;
+    }
+
+    match gpu_performance_driver {
+        Ok(driver) => {
+            object_server
+                .at(
+                    MANAGER_PATH,
+                    Guarded::new(GpuPerformanceLevel1 {
+                        proxy: proxy.clone(),
+                        driver,
+                        available_levels: SysfsCache::default(),
+                        clocks_range: SysfsCache::default(),
+                    }),
+                )
+                .await?;
+        }
+        Err(e) => warn!("Can't add GpuPerformanceLevel1 interface: {e}"),
+    }
+
+    match gpu_profile_driver {
+        Ok(driver) => {
+            object_server
+                .at(
+                    MANAGER_PATH,
+                    Guarded::new(GpuPowerProfile1 {
+                        proxy: proxy.clone(),
+                        driver,
+                        available_profiles: SysfsCache::default(),
+                    }),
+                )
+                .await?;
+        }
+        Err(e) => warn!("Can't add GpuPowerProfile1 interface: {e}"),
+    }
+
+    if has_gpu_throttle {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(GpuThrottle1 {}))
+            .await?;
+    }
+
+    if has_hdmi_cec {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(hdmi_cec))
+            .await?;
+    }
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(idle_policy))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(job_history))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(manager2))
+        .await?;
+
+    if has_tc.unwrap_or(false) {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(network_limit))
+            .await?;
+    }
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(power_history))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(power_presets))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(PowerSource1 {}))
+        .await?;
+
+    if login_mode? == LoginMode::Game && has_orca? {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(screen_reader))
+            .await?;
+    }
+
+    if session_managed? {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(session_management))
+            .await?;
+    }
+
+    if has_sleep_modes {
+        object_server
+            .at(MANAGER_PATH, Guarded::new(sleep_config))
+            .await?;
+    }
+
+    if has_smt {
+        object_server.at(MANAGER_PATH, Guarded::new(smt)).await?;
+    }
+
+    object_server.at(MANAGER_PATH, Guarded::new(status)).await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(suspend_inhibit))
+        .await?;
+
+    object_server
+        .at(MANAGER_PATH, Guarded::new(input_inhibit))
+        .await?;
+
+    match TextInput::new() {
+        Ok(input) => {
+            let locale = Locale1Proxy::new(&system).await?;
+            object_server
+                .at(
+                    MANAGER_PATH,
+                    Guarded::restricted(TextInput1 { input, locale }),
+                )
+                .await?;
+        }
+        Err(e) => warn!("Can't add TextInput1 interface: {e}"),
+    }
+
+    Ok(SignalRelayService {
+        proxy,
+        session,
+        job_manager,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::daemon::channel;
+    use crate::daemon::user::{UserCommand, UserContext};
+    use crate::gpu::{GpuPerformanceLevelDriverType, GpuPowerProfileDriverType};
+    use crate::hardware::test::fake_model;
+    use crate::hardware::{
+        BatteryChargeLimitConfig, DeviceConfig, DeviceMatch, DmiMatch, DmiPattern, FanCurveConfig,
+        GpuPerformanceConfig, GpuPowerProfileConfig, HapticsConfig, PerformanceProfileConfig,
+        PowerSourceConfig, RangeConfig, SpeakerProtectionConfig, SteamDeckVariant, TdpLimitConfig,
+    };
+    use crate::platform::{
+        DockConfig, FirmwareConfig, FormatDeviceConfig, OsUpdateConfig, PlatformConfig,
+        ResetConfig, ScriptConfig, ServiceConfig, StorageConfig, SysfsAttributeConfig,
+        UpdateChannelConfig, UsbAuthorizationConfig,
+    };
+    use crate::power::{BatteryChargeLimitMethod, TdpLimitingMethod};
+    use crate::session::{make_managed, SessionManagerState};
+    use crate::systemd::test::{MockManager, MockUnit};
+    use crate::{path, testing};
+
+    use std::num::NonZeroU32;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tokio::fs::{create_dir_all, remove_dir_all, set_permissions, write};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+    use tokio::time::sleep;
+    use zbus::Connection;
+
+    struct TestHandle {
+        _handle: testing::TestHandle,
+        connection: Connection,
+        _rx_job: UnboundedReceiver<JobManagerCommand>,
+        _rx_inhibit: UnboundedReceiver<SuspendInhibitCommand>,
+        _rx_input_inhibit: UnboundedReceiver<InputInhibitCommand>,
+        _rx_telemetry: UnboundedReceiver<TelemetryCommand>,
+        rx_tdp: Option<UnboundedReceiver<TdpManagerCommand>>,
+    }
+
+    fn all_platform_config() -> Option<PlatformConfig> {
+        Some(PlatformConfig {
+            factory_reset: Some(ResetConfig::default()),
+            update_bios: Some(ScriptConfig::default()),
+            update_dock: Some(ScriptConfig::default()),
+            dock: Some(DockConfig {
+                models: HashMap::from([(String::from("28de:2001"), String::from("Dock"))]),
+            }),
+            storage: Some(StorageConfig::default()),
+            fan_control: Some(ServiceConfig::Systemd(String::from(
+                "jupiter-fan-control.service",
+            ))),
+            firmware: Some(FirmwareConfig::default()),
+            sysfs_access: HashMap::from([(String::from("test"), SysfsAttributeConfig::default())]),
+            usb_authorization: Some(UsbAuthorizationConfig::default()),
+            update_channel: Some(UpdateChannelConfig {
+                branches: vec![String::from("stable"), String::from("beta")],
+                ..UpdateChannelConfig::default()
+            }),
+            os_update: Some(OsUpdateConfig::default()),
+            developer_mode: Some(DeveloperModeConfig {
+                ssh: Some(String::from("sshd.service")),
+                core_dumps: Some(String::from("systemd-coredump.socket")),
+                journal: Some(String::from("systemd-journald.service")),
+            }),
+            managed_units: vec![String::from("avahi-daemon.service")],
+        })
+    }
+
+    fn all_device_config() -> Option<DeviceConfig> {
+        Some(DeviceConfig {
             device: vec![DeviceMatch {
                 dmi: Some(DmiMatch {
-                    sys_vendor: String::from("Valve"),
-                    board_name: Some(String::from("Galileo")),
-                    product_name: None,
+                    sys_vendor: vec![DmiPattern::Exact(String::from("Valve"))],
+                    board_name: vec![DmiPattern::Exact(String::from("Galileo"))],
+                    product_name: Vec::new(),
                 }),
                 device: String::from("steam_deck"),
                 variant: String::from("Galileo"),
@@ -1391,402 +5461,1054 @@ mod test {
             }),
             battery_charge_limit: Some(BatteryChargeLimitConfig {
                 suggested_minimum_limit: Some(10),
-                hwmon_name: String::from("steamdeck_hwmon"),
+                method: BatteryChargeLimitMethod::Hwmon,
+                hwmon_name: Some(String::from("steamdeck_hwmon")),
                 attribute: String::from("max_battery_charge_level"),
+                charge_bypass: None,
             }),
             performance_profile: Some(PerformanceProfileConfig {
                 platform_profile_name: String::from("power-driver"),
                 suggested_default: String::from("balanced"),
             }),
+            fan_curve: Some(FanCurveConfig {
+                hwmon_name: String::from("steamdeck_hwmon"),
+                pwm: 1,
+                points: 2,
+                max_temperature: 100,
+                custom_enable_value: None,
+                default_enable_value: None,
+            }),
+            power_source: Some(PowerSourceConfig {
+                ac_preset: Some(String::from("balanced")),
+                battery_preset: Some(String::from("quiet")),
+            }),
+            update_controller: Some(ScriptConfig::default()),
+            speaker_protection: Some(SpeakerProtectionConfig {
+                sink: String::from("alsa_output.speakers"),
+                max_volume: 0.8,
+            }),
+            haptics: Some(HapticsConfig {
+                intensity_attribute: String::from(crate::haptics::test::INTENSITY_ATTRIBUTE),
+                intensity_range: RangeConfig::new(0, 255),
+                feedback_attribute: Some(String::from(crate::haptics::test::FEEDBACK_ATTRIBUTE)),
+            }),
+            led_control: Some(crate::leds::test::test_config()),
+            charge_led: Some(crate::leds::test::test_charge_led_config()),
+            wake_sources: Some(crate::wake::test::test_config()),
+            source: vec![PathBuf::from(
+                "/usr/share/steamos-manager/devices/jupiter.toml",
+            )],
+        })
+    }
+
+    async fn start(
+        mut platform_config: Option<PlatformConfig>,
+        mut device_config: Option<DeviceConfig>,
+    ) -> Result<TestHandle> {
+        let mut handle = testing::start();
+        let (tx_ctx, mut rx_ctx) = channel::<UserContext>();
+        let (tx_job, rx_job) = unbounded_channel::<JobManagerCommand>();
+        let (tx_inhibit, rx_inhibit) = unbounded_channel::<SuspendInhibitCommand>();
+        let (tx_input_inhibit, rx_input_inhibit) = unbounded_channel::<InputInhibitCommand>();
+        let (tx_telemetry, rx_telemetry) = unbounded_channel::<TelemetryCommand>();
+        let (tx_tdp, rx_tdp) = {
+            if device_config
+                .as_ref()
+                .and_then(|config| config.tdp_limit.as_ref())
+                .is_some()
+            {
+                let (tx_tdp, rx_tdp) = unbounded_channel::<TdpManagerCommand>();
+                (Some(tx_tdp), Some(rx_tdp))
+            } else {
+                (None, None)
+            }
+        };
+
+        if let Some(ref mut config) = platform_config {
+            config.set_test_paths();
+        }
+        if let Some(ref mut config) = device_config {
+            config.set_test_paths();
+        }
+
+        fake_model(SteamDeckVariant::Galileo).await?;
+        handle.test.platform_config.replace(platform_config);
+        handle.test.device_config.replace(device_config);
+        let connection = handle.new_dbus().await?;
+        connection.request_name("org.freedesktop.systemd1").await?;
+        sleep(Duration::from_millis(10)).await;
+        {
+            let object_server = connection.object_server();
+            object_server
+                .at("/org/freedesktop/systemd1", MockManager::default())
+                .await?;
+
+            let mut prc = MockUnit::default();
+            prc.unit_file = String::from("disabled");
+            object_server
+                .at(
+                    "/org/freedesktop/systemd1/unit/plasma_2dremotecontrollers_2eservice",
+                    prc,
+                )
+                .await?;
+        }
+
+        let exe_path = path("exe");
+        write(&exe_path, "").await?;
+        set_permissions(&exe_path, PermissionsExt::from_mode(0o700)).await?;
+
+        create_dir_all(path("/usr/bin")).await?;
+        write(path("/usr/bin/orca"), "").await?;
+        write(path(TC_PATH), "").await?;
+
+        make_managed().await?;
+
+        handle
+            .test
+            .process_cb
+            .set(|_, _| Ok((0, String::from("Interface wlan0"))));
+        crate::backlight::test::create_nodes().await?;
+        crate::display::test::create_nodes().await?;
+        crate::gpu::test::create_nodes().await?;
+        crate::haptics::test::create_nodes().await?;
+        crate::leds::test::create_nodes().await?;
+        crate::power::test::create_nodes().await?;
+        crate::sleep::test::create_nodes(0).await?;
+        crate::thermal::test::create_nodes().await?;
+        crate::usb::test::create_nodes().await?;
+        crate::wake::test::create_nodes().await?;
+        create_interfaces(
+            connection.clone(),
+            connection.clone(),
+            tx_ctx,
+            tx_job,
+            tx_tdp,
+            tx_inhibit,
+            tx_input_inhibit,
+            tx_telemetry,
+        )
+        .await?;
+
+        tokio::spawn(async move {
+            while let Some(command) = rx_ctx.recv().await {
+                match command {
+                    DaemonCommand::ContextCommand(UserCommand::GetSessionManagerState(sender)) => {
+                        _ = sender.send(SessionManagerState::default())
+                    }
+                    _ => (),
+                }
+            }
+            Ok::<_, Error>(())
+        });
+
+        sleep(Duration::from_millis(1)).await;
+
+        Ok(TestHandle {
+            _handle: handle,
+            connection,
+            _rx_job: rx_job,
+            _rx_inhibit: rx_inhibit,
+            _rx_input_inhibit: rx_input_inhibit,
+            _rx_telemetry: rx_telemetry,
+            rx_tdp,
         })
     }
 
-    async fn start(
-        mut platform_config: Option<PlatformConfig>,
-        device_config: Option<DeviceConfig>,
-    ) -> Result<TestHandle> {
-        let mut handle = testing::start();
-        let (tx_ctx, mut rx_ctx) = channel::<UserContext>();
-        let (tx_job, rx_job) = unbounded_channel::<JobManagerCommand>();
-        let (tx_tdp, rx_tdp) = {
-            if device_config
-                .as_ref()
-                .and_then(|config| config.tdp_limit.as_ref())
-                .is_some()
-            {
-                let (tx_tdp, rx_tdp) = unbounded_channel::<TdpManagerCommand>();
-                (Some(tx_tdp), Some(rx_tdp))
-            } else {
-                (None, None)
-            }
-        };
+    #[tokio::test]
+    async fn interface_matches() {
+        let test = start(None, None).await.expect("start");
+
+        let remote = testing::InterfaceIntrospection::from_remote::<Guarded<SteamOSManager>, _>(
+            &test.connection,
+            MANAGER_PATH,
+        )
+        .await
+        .expect("remote");
+        let local = testing::InterfaceIntrospection::from_local(
+            "../data/interfaces/com.steampowered.SteamOSManager1.Manager.xml",
+            "com.steampowered.SteamOSManager1.Manager",
+        )
+        .await
+        .expect("local");
+        assert!(remote.compare(&local));
+    }
+
+    async fn test_interface_matches<I: Interface>(connection: &Connection) -> Result<bool> {
+        let remote =
+            testing::InterfaceIntrospection::from_remote::<Guarded<I>, _>(connection, MANAGER_PATH)
+                .await?;
+        let local = testing::InterfaceIntrospection::from_local(
+            "../data/interfaces/com.steampowered.SteamOSManager1.xml",
+            I::name().to_string(),
+        )
+        .await?;
+        Ok(remote.compare(&local))
+    }
+
+    async fn test_interface_missing<I: Interface>(connection: &Connection) -> bool {
+        let remote =
+            testing::InterfaceIntrospection::from_remote::<Guarded<I>, _>(connection, MANAGER_PATH)
+                .await;
+        remote.is_err()
+    }
+
+    #[tokio::test]
+    async fn all_interfaces_documented() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        let remote = testing::InterfaceIntrospection::from_object(&test.connection, MANAGER_PATH)
+            .await
+            .expect("remote");
+        let mut checked = 0;
+        for interface in remote {
+            let name = interface.name();
+            // The legacy interface is described in its own file and covered by interface_matches
+            if !name.starts_with("com.steampowered.SteamOSManager1.")
+                || name == SteamOSManager::name().as_str()
+            {
+                continue;
+            }
+            let local = testing::InterfaceIntrospection::from_local(
+                "../data/interfaces/com.steampowered.SteamOSManager1.xml",
+                name.as_str(),
+            )
+            .await
+            .unwrap_or_else(|_| panic!("{name} is missing from the interface XML"));
+            assert!(
+                interface.compare(&local),
+                "{name} does not match the interface XML"
+            );
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_accessibility1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Accessibility1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_ambient_light_sensor1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(
+            test_interface_matches::<AmbientLightSensor1>(&test.connection)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn interface_matches_audio1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Audio1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_backlight1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Backlight1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_battery1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Battery1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn hotplug_battery1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+        let proxy = root_manager_proxy(&test.connection)
+            .await
+            .expect("root_manager_proxy");
+        let battery = find_battery().await.expect("find_battery");
+
+        remove_dir_all(&battery).await.expect("remove_dir_all");
+        update_hotplug_interfaces(&proxy, test.connection.object_server())
+            .await
+            .expect("update_hotplug_interfaces");
+        assert!(test_interface_missing::<Battery1>(&test.connection).await);
+
+        create_dir_all(&battery).await.expect("create_dir_all");
+        write(battery.join("type"), "Battery\n")
+            .await
+            .expect("write");
+        update_hotplug_interfaces(&proxy, test.connection.object_server())
+            .await
+            .expect("update_hotplug_interfaces");
+        assert!(test_interface_matches::<Battery1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn toggle_interface_available() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+        let object_server = test.connection.object_server();
+        assert!(has_interface::<Battery1>(object_server).await);
+
+        set_interface_available(object_server, Battery1 {}, false)
+            .await
+            .expect("set_interface_available");
+        assert!(!has_interface::<Battery1>(object_server).await);
+        assert!(test_interface_missing::<Battery1>(&test.connection).await);
+
+        set_interface_available(object_server, Battery1 {}, true)
+            .await
+            .expect("set_interface_available");
+        assert!(has_interface::<Battery1>(object_server).await);
+        assert!(test_interface_matches::<Battery1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_battery_charge_limit() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(
+            test_interface_matches::<BatteryChargeLimit1>(&test.connection)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn interface_matches_battery_policy1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<BatteryPolicy1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_charge_led1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<ChargeLed1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_charge_led1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<ChargeLed1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_color_filters1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<ColorFilters1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_cpu_boost1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<CpuBoost1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_cpu_frequency1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<CpuFrequency1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_cpu_scaling1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<CpuScaling1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_developer_mode1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<DeveloperMode1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_developer_mode1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<DeveloperMode1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_display_output1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<DisplayOutput1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_dock1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Dock1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_dock1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<Dock1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_factory_reset1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<FactoryReset1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_factory_reset1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_missing_invalid_all_factory_reset1() {
+        let mut config = all_platform_config().unwrap();
+        config.factory_reset.as_mut().unwrap().all = ScriptConfig {
+            script: PathBuf::from("oxo"),
+            script_args: Vec::new(),
+            timeout: None,
+        };
+        let test = start(Some(config), None).await.expect("start");
+
+        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_missing_invalid_os_factory_reset1() {
+        let mut config = all_platform_config().unwrap();
+        config.factory_reset.as_mut().unwrap().os = ScriptConfig {
+            script: PathBuf::from("oxo"),
+            script_args: Vec::new(),
+            timeout: None,
+        };
+        let test = start(Some(config), None).await.expect("start");
+
+        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_missing_invalid_user_factory_reset1() {
+        let mut config = all_platform_config().unwrap();
+        config.factory_reset.as_mut().unwrap().user = ScriptConfig {
+            script: PathBuf::from("oxo"),
+            script_args: Vec::new(),
+            timeout: None,
+        };
+        let test = start(Some(config), None).await.expect("start");
+
+        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_fan_control1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<FanControl1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_fan_control1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<FanControl1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_fan_curve1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<FanCurve1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_fan_curve1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<FanCurve1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_haptics1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Haptics1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_haptics1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<Haptics1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_gpu_metrics1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<GpuMetrics1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_gpu_performance_level1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(
+            test_interface_matches::<GpuPerformanceLevel1>(&test.connection)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn interface_matches_gpu_power1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<GpuPower1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_gpu_power_profile1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        if let Some(ref mut config) = platform_config {
-            config.set_test_paths();
-        }
+        assert!(test_interface_matches::<GpuPowerProfile1>(&test.connection)
+            .await
+            .unwrap());
+    }
 
-        fake_model(SteamDeckVariant::Galileo).await?;
-        handle.test.platform_config.replace(platform_config);
-        handle.test.device_config.replace(device_config);
-        let connection = handle.new_dbus().await?;
-        connection.request_name("org.freedesktop.systemd1").await?;
-        sleep(Duration::from_millis(10)).await;
-        {
-            let object_server = connection.object_server();
-            object_server
-                .at("/org/freedesktop/systemd1", MockManager::default())
-                .await?;
+    #[tokio::test]
+    async fn interface_matches_gpu_throttle1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-            let mut prc = MockUnit::default();
-            prc.unit_file = String::from("disabled");
-            object_server
-                .at(
-                    "/org/freedesktop/systemd1/unit/plasma_2dremotecontrollers_2eservice",
-                    prc,
-                )
-                .await?;
-        }
+        assert!(test_interface_matches::<GpuThrottle1>(&test.connection)
+            .await
+            .unwrap());
+    }
 
-        let exe_path = path("exe");
-        write(&exe_path, "").await?;
-        set_permissions(&exe_path, PermissionsExt::from_mode(0o700)).await?;
+    #[tokio::test]
+    async fn interface_matches_input_inhibit1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        create_dir_all(path("/usr/bin")).await?;
-        write(path("/usr/bin/orca"), "").await?;
+        assert!(test_interface_matches::<InputInhibit1>(&test.connection)
+            .await
+            .unwrap());
+    }
 
-        make_managed().await?;
+    #[tokio::test]
+    async fn interface_matches_suspend_inhibit1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        handle
-            .test
-            .process_cb
-            .set(|_, _| Ok((0, String::from("Interface wlan0"))));
-        crate::gpu::test::create_nodes().await?;
-        crate::power::test::create_nodes().await?;
-        create_interfaces(
-            connection.clone(),
-            connection.clone(),
-            tx_ctx,
-            tx_job,
-            tx_tdp,
-        )
-        .await?;
+        assert!(test_interface_matches::<SuspendInhibit1>(&test.connection)
+            .await
+            .unwrap());
+    }
 
-        tokio::spawn(async move {
-            while let Some(command) = rx_ctx.recv().await {
-                match command {
-                    DaemonCommand::ContextCommand(UserCommand::GetSessionManagerState(sender)) => {
-                        _ = sender.send(SessionManagerState::default())
-                    }
-                    _ => (),
-                }
-            }
-            Ok::<_, Error>(())
-        });
+    #[tokio::test]
+    async fn interface_matches_tdp_limit1() {
+        let mut test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
+        let TdpManagerCommand::IsActive(reply) =
+            test.rx_tdp.as_mut().unwrap().recv().await.unwrap()
+        else {
+            panic!();
+        };
+        reply.send(Ok(true)).unwrap();
         sleep(Duration::from_millis(1)).await;
 
-        Ok(TestHandle {
-            _handle: handle,
-            connection,
-            _rx_job: rx_job,
-            rx_tdp,
-        })
+        assert!(test_interface_matches::<TdpLimit1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches() {
+    async fn interface_missing_tdp_limit1() {
         let test = start(None, None).await.expect("start");
 
-        let remote = testing::InterfaceIntrospection::from_remote::<SteamOSManager, _>(
-            &test.connection,
-            MANAGER_PATH,
-        )
-        .await
-        .expect("remote");
-        let local = testing::InterfaceIntrospection::from_local(
-            "../data/interfaces/com.steampowered.SteamOSManager1.Manager.xml",
-            "com.steampowered.SteamOSManager1.Manager",
-        )
-        .await
-        .expect("local");
-        assert!(remote.compare(&local));
+        assert!(test_interface_missing::<TdpLimit1>(&test.connection).await);
     }
 
-    async fn test_interface_matches<I: Interface>(connection: &Connection) -> Result<bool> {
-        let remote =
-            testing::InterfaceIntrospection::from_remote::<I, _>(connection, MANAGER_PATH).await?;
-        let local = testing::InterfaceIntrospection::from_local(
-            "../data/interfaces/com.steampowered.SteamOSManager1.xml",
-            I::name().to_string(),
-        )
-        .await?;
-        Ok(remote.compare(&local))
+    #[tokio::test]
+    async fn interface_inactive_tdp_limit1() {
+        let mut test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        let TdpManagerCommand::IsActive(reply) =
+            test.rx_tdp.as_mut().unwrap().recv().await.unwrap()
+        else {
+            panic!();
+        };
+        reply.send(Ok(false)).unwrap();
+        sleep(Duration::from_millis(1)).await;
+
+        assert!(test_interface_missing::<TdpLimit1>(&test.connection).await);
     }
 
-    async fn test_interface_missing<I: Interface>(connection: &Connection) -> bool {
-        let remote =
-            testing::InterfaceIntrospection::from_remote::<I, _>(connection, MANAGER_PATH).await;
-        remote.is_err()
+    #[tokio::test]
+    async fn interface_matches_tdp_profiles1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<TdpProfiles1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_ambient_light_sensor1() {
+    async fn interface_missing_tdp_profiles1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<TdpProfiles1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_hdmi_cec1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(
-            test_interface_matches::<AmbientLightSensor1>(&test.connection)
-                .await
-                .unwrap()
-        );
+        assert!(test_interface_matches::<HdmiCec1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_battery_charge_limit() {
+    async fn interface_matches_firmware_info1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(
-            test_interface_matches::<BatteryChargeLimit1>(&test.connection)
-                .await
-                .unwrap()
-        );
+        assert!(test_interface_matches::<FirmwareInfo1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_cpu_boost1() {
+    async fn interface_matches_idle_policy1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<CpuBoost1>(&test.connection)
+        assert!(test_interface_matches::<IdlePolicy1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_cpu_scaling1() {
+    async fn interface_matches_job_history1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<CpuScaling1>(&test.connection)
+        assert!(test_interface_matches::<JobHistory1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_factory_reset1() {
+    async fn interface_matches_led_control1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<FactoryReset1>(&test.connection)
+        assert!(test_interface_matches::<LedControl1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_missing_factory_reset1() {
+    async fn interface_missing_led_control1() {
         let test = start(None, None).await.expect("start");
 
-        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+        assert!(test_interface_missing::<LedControl1>(&test.connection).await);
     }
 
     #[tokio::test]
-    async fn interface_missing_invalid_all_factory_reset1() {
-        let mut config = all_platform_config().unwrap();
-        config.factory_reset.as_mut().unwrap().all = ScriptConfig {
-            script: PathBuf::from("oxo"),
-            script_args: Vec::new(),
-        };
-        let test = start(Some(config), None).await.expect("start");
+    async fn interface_matches_low_power_mode1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+        assert!(test_interface_matches::<LowPowerMode1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_missing_invalid_os_factory_reset1() {
-        let mut config = all_platform_config().unwrap();
-        config.factory_reset.as_mut().unwrap().os = ScriptConfig {
-            script: PathBuf::from("oxo"),
-            script_args: Vec::new(),
-        };
-        let test = start(Some(config), None).await.expect("start");
+    async fn interface_missing_low_power_mode1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<LowPowerMode1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_manager2() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Manager2>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn validate_all_config() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        let report: HashMap<String, bool> = validate_config(&test.connection)
+            .await
+            .into_iter()
+            .map(|(interface, enabled, _)| (interface, enabled))
+            .collect();
+        let enabled = |name: InterfaceName<'static>| report[name.as_str()];
+        assert!(enabled(ChargeLed1::name()));
+        assert!(enabled(DeveloperMode1::name()));
+        assert!(enabled(Dock1::name()));
+        assert!(enabled(FactoryReset1::name()));
+        assert!(enabled(FanControl1::name()));
+        assert!(enabled(FanCurve1::name()));
+        assert!(enabled(Haptics1::name()));
+        assert!(enabled(LedControl1::name()));
+        assert!(enabled(LowPowerMode1::name()));
+        assert!(enabled(OsUpdate1::name()));
+        assert!(enabled(OsUpdateChannel1::name()));
+        assert!(enabled(PerformanceProfile1::name()));
+        assert!(enabled(Storage1::name()));
+        assert!(!enabled(StorageHealth1::name()));
+        assert!(enabled(SysfsAccess1::name()));
+        assert!(enabled(UpdateBios1::name()));
+        assert!(enabled(UpdateController1::name()));
+        assert!(enabled(UpdateDock1::name()));
+        assert!(enabled(UsbAuthorization1::name()));
+    }
+
+    #[tokio::test]
+    async fn validate_missing_config() {
+        let test = start(None, None).await.expect("start");
+
+        let report = validate_config(&test.connection).await;
+        assert!(!report.is_empty());
+        assert!(report.iter().all(|(_, enabled, _)| !enabled));
+    }
 
-        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+    #[tokio::test]
+    async fn features_all_config() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        let features = available_features(test.connection.object_server()).await;
+        assert!(features.contains(&String::from("fan-curve")));
+        assert!(features.contains(&String::from("tdp-profiles")));
+        assert!(features.contains(&String::from("update-bios")));
+        assert!(!features.contains(&String::from("storage-health")));
     }
 
     #[tokio::test]
-    async fn interface_missing_invalid_user_factory_reset1() {
-        let mut config = all_platform_config().unwrap();
-        config.factory_reset.as_mut().unwrap().user = ScriptConfig {
-            script: PathBuf::from("oxo"),
-            script_args: Vec::new(),
-        };
-        let test = start(Some(config), None).await.expect("start");
+    async fn features_missing_config() {
+        let test = start(None, None).await.expect("start");
 
-        assert!(test_interface_missing::<FactoryReset1>(&test.connection).await);
+        let features = available_features(test.connection.object_server()).await;
+        assert!(!features.contains(&String::from("fan-curve")));
+        assert!(!features.contains(&String::from("tdp-profiles")));
+        assert!(!features.contains(&String::from("update-bios")));
+    }
+
+    async fn assert_report_covers_optional_interfaces(connection: &Connection) {
+        let mut reported: Vec<String> = validate_config(connection)
+            .await
+            .into_iter()
+            .map(|(interface, _, _)| interface)
+            .collect();
+        reported.sort();
+        let mut expected: Vec<String> = optional_interfaces()
+            .into_iter()
+            .filter(|interface| interface.config.is_some())
+            .map(|interface| interface.name.to_string())
+            .collect();
+        expected.sort();
+        assert_eq!(reported, expected);
     }
 
     #[tokio::test]
-    async fn interface_matches_fan_control1() {
+    async fn validate_all_config_covers_interfaces() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<FanControl1>(&test.connection)
-            .await
-            .unwrap());
+        assert_report_covers_optional_interfaces(&test.connection).await;
     }
 
     #[tokio::test]
-    async fn interface_missing_fan_control1() {
+    async fn validate_missing_config_covers_interfaces() {
         let test = start(None, None).await.expect("start");
 
-        assert!(test_interface_missing::<FanControl1>(&test.connection).await);
+        assert_report_covers_optional_interfaces(&test.connection).await;
     }
 
     #[tokio::test]
-    async fn interface_matches_gpu_performance_level1() {
+    async fn interface_matches_session_management1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
         assert!(
-            test_interface_matches::<GpuPerformanceLevel1>(&test.connection)
+            test_interface_matches::<SessionManagement1>(&test.connection)
                 .await
                 .unwrap()
         );
     }
 
     #[tokio::test]
-    async fn interface_matches_gpu_power_profile1() {
+    async fn interface_matches_network_limit1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<GpuPowerProfile1>(&test.connection)
+        assert!(test_interface_matches::<NetworkLimit1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_tdp_limit1() {
-        let mut test = start(all_platform_config(), all_device_config())
+    async fn interface_matches_os_update1() {
+        let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        let TdpManagerCommand::IsActive(reply) =
-            test.rx_tdp.as_mut().unwrap().recv().await.unwrap()
-        else {
-            panic!();
-        };
-        reply.send(Ok(true)).unwrap();
-        sleep(Duration::from_millis(1)).await;
-
-        assert!(test_interface_matches::<TdpLimit1>(&test.connection)
+        assert!(test_interface_matches::<OsUpdate1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_missing_tdp_limit1() {
+    async fn interface_missing_os_update1() {
         let test = start(None, None).await.expect("start");
 
-        assert!(test_interface_missing::<TdpLimit1>(&test.connection).await);
+        assert!(test_interface_missing::<OsUpdate1>(&test.connection).await);
     }
 
     #[tokio::test]
-    async fn interface_inactive_tdp_limit1() {
-        let mut test = start(all_platform_config(), all_device_config())
+    async fn interface_matches_os_update_channel1() {
+        let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        let TdpManagerCommand::IsActive(reply) =
-            test.rx_tdp.as_mut().unwrap().recv().await.unwrap()
-        else {
-            panic!();
-        };
-        reply.send(Ok(false)).unwrap();
-        sleep(Duration::from_millis(1)).await;
+        assert!(test_interface_matches::<OsUpdateChannel1>(&test.connection)
+            .await
+            .unwrap());
+    }
 
-        assert!(test_interface_missing::<TdpLimit1>(&test.connection).await);
+    #[tokio::test]
+    async fn interface_missing_os_update_channel1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<OsUpdateChannel1>(&test.connection).await);
     }
 
     #[tokio::test]
-    async fn interface_matches_hdmi_cec1() {
+    async fn interface_matches_performance_profile1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<HdmiCec1>(&test.connection)
-            .await
-            .unwrap());
+        assert!(
+            test_interface_matches::<PerformanceProfile1>(&test.connection)
+                .await
+                .unwrap()
+        );
     }
 
     #[tokio::test]
-    async fn interface_matches_low_power_mode1() {
+    async fn interface_missing_performance_profile1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<PerformanceProfile1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_power_history1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<LowPowerMode1>(&test.connection)
+        assert!(test_interface_matches::<PowerHistory1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_missing_low_power_mode1() {
-        let test = start(None, None).await.expect("start");
+    async fn interface_matches_power_presets1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        assert!(test_interface_missing::<LowPowerMode1>(&test.connection).await);
+        assert!(test_interface_matches::<PowerPresets1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_manager2() {
+    async fn interface_matches_power_source1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(test_interface_matches::<Manager2>(&test.connection)
+        assert!(test_interface_matches::<PowerSource1>(&test.connection)
             .await
             .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_session_management1() {
+    async fn interface_matches_status1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(
-            test_interface_matches::<SessionManagement1>(&test.connection)
-                .await
-                .unwrap()
-        );
+        assert!(test_interface_matches::<Status1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_matches_performance_profile1() {
+    async fn interface_matches_sleep_config1() {
         let test = start(all_platform_config(), all_device_config())
             .await
             .expect("start");
 
-        assert!(
-            test_interface_matches::<PerformanceProfile1>(&test.connection)
-                .await
-                .unwrap()
-        );
+        assert!(test_interface_matches::<SleepConfig1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
-    async fn interface_missing_performance_profile1() {
-        let test = start(None, None).await.expect("start");
+    async fn interface_matches_smt1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
 
-        assert!(test_interface_missing::<PerformanceProfile1>(&test.connection).await);
+        assert!(test_interface_matches::<Smt1>(&test.connection)
+            .await
+            .unwrap());
     }
 
     #[tokio::test]
@@ -1813,6 +6535,7 @@ mod test {
         config.storage.as_mut().unwrap().trim_devices = ScriptConfig {
             script: PathBuf::from("oxo"),
             script_args: Vec::new(),
+            timeout: None,
         };
         let test = start(Some(config), all_device_config())
             .await
@@ -1834,6 +6557,94 @@ mod test {
         assert!(test_interface_missing::<Storage1>(&test.connection).await);
     }
 
+    #[tokio::test]
+    async fn interface_matches_storage_health1() {
+        let mut config = all_platform_config().unwrap();
+        config.storage.as_mut().unwrap().health = Some(ScriptConfig::default());
+        let test = start(Some(config), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<StorageHealth1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_storage_health1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_missing::<StorageHealth1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_services1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Services1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_services1() {
+        let mut config = all_platform_config().unwrap();
+        config.managed_units.clear();
+        let test = start(Some(config), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_missing::<Services1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_sysfs_access1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<SysfsAccess1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_sysfs_access1() {
+        let mut config = all_platform_config().unwrap();
+        config.sysfs_access.clear();
+        let test = start(Some(config), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_missing::<SysfsAccess1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_text_input1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<TextInput1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_matches_thermal1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<Thermal1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn interface_matches_update_bios1() {
         let test = start(all_platform_config(), all_device_config())
@@ -1858,6 +6669,7 @@ mod test {
         config.update_bios = Some(ScriptConfig {
             script: PathBuf::from("oxo"),
             script_args: Vec::new(),
+            timeout: None,
         });
         let test = start(Some(config), all_device_config())
             .await
@@ -1866,6 +6678,41 @@ mod test {
         assert!(test_interface_missing::<UpdateBios1>(&test.connection).await);
     }
 
+    #[tokio::test]
+    async fn interface_matches_update_controller1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(
+            test_interface_matches::<UpdateController1>(&test.connection)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn interface_missing_update_controller1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<UpdateController1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_missing_invalid_update_controller1() {
+        let mut config = all_device_config().unwrap();
+        config.update_controller = Some(ScriptConfig {
+            script: PathBuf::from("oxo"),
+            script_args: Vec::new(),
+            timeout: None,
+        });
+        let test = start(all_platform_config(), Some(config))
+            .await
+            .expect("start");
+
+        assert!(test_interface_missing::<UpdateController1>(&test.connection).await);
+    }
+
     #[tokio::test]
     async fn interface_matches_update_dock1() {
         let test = start(all_platform_config(), all_device_config())
@@ -1890,6 +6737,7 @@ mod test {
         config.update_dock = Some(ScriptConfig {
             script: PathBuf::from("oxo"),
             script_args: Vec::new(),
+            timeout: None,
         });
         let test = start(Some(config), all_device_config())
             .await
@@ -1898,6 +6746,55 @@ mod test {
         assert!(test_interface_missing::<UpdateDock1>(&test.connection).await);
     }
 
+    #[tokio::test]
+    async fn interface_matches_usb_authorization1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(
+            test_interface_matches::<UsbAuthorization1>(&test.connection)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn interface_missing_usb_authorization1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<UsbAuthorization1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_wake_sources1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<WakeSources1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn interface_missing_wake_sources1() {
+        let test = start(None, None).await.expect("start");
+
+        assert!(test_interface_missing::<WakeSources1>(&test.connection).await);
+    }
+
+    #[tokio::test]
+    async fn interface_matches_wifi_info1() {
+        let test = start(all_platform_config(), all_device_config())
+            .await
+            .expect("start");
+
+        assert!(test_interface_matches::<WifiInfo1>(&test.connection)
+            .await
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn interface_matches_wifi_power_management1() {
         let test = start(all_platform_config(), all_device_config())
@@ -1932,4 +6829,38 @@ mod test {
             .await
             .unwrap());
     }
+
+    #[tokio::test]
+    async fn setter_throttle() {
+        assert!(SetterThrottleConfig::default()
+            .window("Throttled1")
+            .is_zero());
+
+        let throttle = SetterThrottle::default();
+        *throttle.config.lock().await = SetterThrottleConfig {
+            default: 50,
+            interfaces: HashMap::from([(String::from("Unthrottled1"), 0)]),
+        };
+
+        let start = Instant::now();
+        assert!(throttle.wait(":1.1", "Throttled1", "Value").await);
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // Only the newest of two values set within the window gets applied
+        let (first, second) = tokio::join!(throttle.wait(":1.1", "Throttled1", "Value"), async {
+            sleep(Duration::from_millis(10)).await;
+            throttle.wait(":1.1", "Throttled1", "Value").await
+        });
+        assert!(!first);
+        assert!(second);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // Other clients, properties and unthrottled interfaces don't have to wait
+        let start = Instant::now();
+        assert!(throttle.wait(":1.2", "Throttled1", "Value").await);
+        assert!(throttle.wait(":1.1", "Throttled1", "Other").await);
+        assert!(throttle.wait(":1.1", "Unthrottled1", "Value").await);
+        assert!(throttle.wait(":1.1", "Unthrottled1", "Value").await);
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
 }
@@ -0,0 +1,209 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+//! Mock hardware mode, which redirects every sysfs access to a fake tree so that the daemons can
+//! run on a desktop PC without Steam Deck hardware.
+
+use anyhow::{anyhow, ensure, Result};
+use nix::unistd::geteuid;
+use std::env;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use strum::{Display, EnumString};
+use tokio::fs::{create_dir_all, symlink_metadata, write, DirBuilder};
+use tracing::info;
+
+use crate::hardware::SteamDeckVariant;
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+// Just enough of a Steam Deck for the daemons to find the hardware they expect. Paths are
+// relative to the root of the fake tree.
+const MOCK_NODES: &[(&str, &str)] = &[
+    ("sys/class/hwmon/hwmon5/name", "amdgpu\n"),
+    (
+        "sys/class/hwmon/hwmon5/device/power_dpm_force_performance_level",
+        "auto\n",
+    ),
+    ("sys/class/hwmon/hwmon5/device/gpu_busy_percent", "0\n"),
+    ("sys/class/hwmon/hwmon5/power1_cap", "15000000\n"),
+    ("sys/class/hwmon/hwmon5/power1_cap_min", "3000000\n"),
+    ("sys/class/hwmon/hwmon5/power1_cap_max", "15000000\n"),
+    ("sys/class/hwmon/hwmon6/name", "steamdeck_hwmon\n"),
+    ("sys/class/hwmon/hwmon6/max_battery_charge_level", "0\n"),
+    ("sys/class/backlight/amdgpu_bl0/type", "raw\n"),
+    ("sys/class/backlight/amdgpu_bl0/max_brightness", "255\n"),
+    ("sys/class/backlight/amdgpu_bl0/brightness", "128\n"),
+    ("sys/class/power_supply/BAT1/type", "Battery\n"),
+    ("sys/class/power_supply/BAT1/capacity", "80\n"),
+    ("sys/class/power_supply/BAT1/status", "Discharging\n"),
+    ("sys/devices/system/cpu/cpufreq/boost", "1\n"),
+    ("sys/devices/system/cpu/smt/control", "on\n"),
+];
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum MockProfile {
+    Jupiter,
+    Galileo,
+}
+
+impl From<MockProfile> for SteamDeckVariant {
+    fn from(profile: MockProfile) -> SteamDeckVariant {
+        match profile {
+            MockProfile::Jupiter => SteamDeckVariant::Jupiter,
+            MockProfile::Galileo => SteamDeckVariant::Galileo,
+        }
+    }
+}
+
+/// The root of the fake sysfs tree, if mock hardware mode is enabled
+pub fn root() -> Option<&'static Path> {
+    ROOT.get().map(PathBuf::as_path)
+}
+
+/// Where the fake tree lives. Everything the daemons read from it is trusted, so it has to be
+/// somewhere other users can't get at first.
+fn mock_root(profile: MockProfile) -> PathBuf {
+    let base = if geteuid().is_root() {
+        PathBuf::from("/run")
+    } else {
+        env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir)
+    };
+    base.join(format!("steamos-manager-mock-{profile}"))
+}
+
+/// Make sure an existing tree is a real directory that only we can write to
+async fn check_root(root: &Path) -> Result<()> {
+    let metadata = symlink_metadata(root).await?;
+    ensure!(metadata.is_dir(), "{} is not a directory", root.display());
+    ensure!(
+        metadata.uid() == geteuid().as_raw(),
+        "{} is owned by another user",
+        root.display()
+    );
+    ensure!(
+        metadata.mode() & 0o077 == 0,
+        "{} is accessible by other users",
+        root.display()
+    );
+    Ok(())
+}
+
+/// Enable mock hardware mode for the given profile. This must be called before the daemon is
+/// started, as every path looked up afterwards is redirected into the fake tree.
+///
+/// Each user gets their own tree, which is kept across restarts so that values written to it
+/// persist. Whichever daemon running as that user starts first populates it.
+pub async fn enable(profile: MockProfile) -> Result<PathBuf> {
+    let root = mock_root(profile);
+    match DirBuilder::new().mode(0o700).create(&root).await {
+        Ok(()) => {
+            info!("Creating mock {profile} hardware in {}", root.display());
+            check_root(&root).await?;
+            seed(&root, profile).await?;
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            check_root(&root).await?;
+            info!("Using mock {profile} hardware in {}", root.display());
+        }
+        Err(e) => return Err(e.into()),
+    }
+    ROOT.set(root.clone())
+        .map_err(|_| anyhow!("Mock hardware mode is already enabled"))?;
+    Ok(root)
+}
+
+async fn seed(root: &Path, profile: MockProfile) -> Result<()> {
+    let model = match profile {
+        MockProfile::Jupiter => "Jupiter\n",
+        MockProfile::Galileo => "Galileo\n",
+    };
+    let dmi = [
+        ("sys/class/dmi/id/sys_vendor", "Valve\n"),
+        ("sys/class/dmi/id/board_name", model),
+        ("sys/class/dmi/id/product_name", model),
+    ];
+    for (node, contents) in dmi.iter().chain(MOCK_NODES) {
+        let node = root.join(node);
+        if let Some(parent) = node.parent() {
+            create_dir_all(parent).await?;
+        }
+        write(node, contents).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hardware::steam_deck_variant;
+    use crate::{path, testing};
+    use std::fs::Permissions;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    use tokio::fs::{read_to_string, set_permissions};
+
+    #[tokio::test]
+    async fn mock_tree() {
+        let _h = testing::start();
+
+        seed(&path("/"), MockProfile::Galileo).await.expect("seed");
+        assert_eq!(
+            steam_deck_variant().await.unwrap(),
+            SteamDeckVariant::Galileo
+        );
+        assert_eq!(
+            read_to_string(path("/sys/class/hwmon/hwmon5/name"))
+                .await
+                .unwrap(),
+            "amdgpu\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn root_ownership() {
+        let _h = testing::start();
+
+        let root = path("/mock");
+        assert!(check_root(&root).await.is_err());
+        DirBuilder::new()
+            .mode(0o700)
+            .create(&root)
+            .await
+            .expect("create");
+        assert!(check_root(&root).await.is_ok());
+
+        set_permissions(&root, Permissions::from_mode(0o755))
+            .await
+            .expect("set_permissions");
+        assert!(check_root(&root).await.is_err());
+
+        set_permissions(&root, Permissions::from_mode(0o700))
+            .await
+            .expect("set_permissions");
+        let link = path("/link");
+        symlink(&root, &link).expect("symlink");
+        assert!(check_root(&link).await.is_err());
+    }
+
+    #[test]
+    fn profile_names() {
+        assert_eq!(
+            "jupiter".parse::<MockProfile>().unwrap(),
+            MockProfile::Jupiter
+        );
+        assert_eq!(
+            "Galileo".parse::<MockProfile>().unwrap(),
+            MockProfile::Galileo
+        );
+        assert!("unknown".parse::<MockProfile>().is_err());
+        assert_eq!(MockProfile::Galileo.to_string(), "galileo");
+    }
+}
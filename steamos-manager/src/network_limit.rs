@@ -0,0 +1,575 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, Result};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::os::fd::{AsFd, OwnedFd};
+use tokio::fs::{read_dir, read_to_string, try_exists};
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+use tokio::net::unix::pipe;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use tracing::{debug, error, warn};
+use udev::{EventType, MonitorBuilder};
+
+use crate::process::run_script;
+use crate::{path, wait_on_handle, Service};
+
+pub(crate) const TC_PATH: &str = "/usr/bin/tc";
+const IP_PATH: &str = "/usr/bin/ip";
+const NET_PREFIX: &str = "/sys/class/net";
+
+// Incoming traffic is redirected to an intermediate functional block device
+// so that it can be shaped as if it was outgoing
+const IFB_PREFIX: &str = "ifb4-";
+const INGRESS_HANDLE: &str = "ffff:";
+
+pub(crate) enum NetworkLimitCommand {
+    Limit(String, u32, u32, oneshot::Sender<Result<OwnedFd>>),
+    ListHandles(oneshot::Sender<HashMap<String, (u32, u32)>>),
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+struct Limit {
+    count: u32,
+    /// Download limit in kbit/s, or 0 if unlimited
+    download: u32,
+    /// Upload limit in kbit/s, or 0 if unlimited
+    upload: u32,
+}
+
+pub(crate) struct NetworkLimitService {
+    channel: UnboundedReceiver<NetworkLimitCommand>,
+    handle_set: JoinSet<String>,
+    limits: HashMap<String, Limit>,
+    applied: (u32, u32),
+}
+
+async fn list_network_interfaces() -> Result<Vec<String>> {
+    let mut interfaces = Vec::new();
+    let mut dir = read_dir(path(NET_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if is_physical_interface(name.as_str()).await? {
+            interfaces.push(name);
+        }
+    }
+    interfaces.sort();
+    Ok(interfaces)
+}
+
+async fn is_physical_interface(interface: &str) -> Result<bool> {
+    // Only physical interfaces have a backing device
+    Ok(try_exists(path(NET_PREFIX).join(interface).join("device")).await?)
+}
+
+/// Interface names can't be longer than 15 characters, so the block device is named after the
+/// index of the interface instead of its name
+async fn ifb_name(interface: &str) -> Result<String> {
+    let ifindex = read_to_string(path(NET_PREFIX).join(interface).join("ifindex")).await?;
+    Ok(ifb_for_index(ifindex.trim()))
+}
+
+fn ifb_for_index(ifindex: &str) -> String {
+    format!("{IFB_PREFIX}{ifindex}")
+}
+
+async fn set_upload_limit(interface: &str, limit: u32) -> Result<()> {
+    if limit == 0 {
+        return run_script(TC_PATH, &["qdisc", "del", "dev", interface, "root"]).await;
+    }
+    let bandwidth = format!("{limit}kbit");
+    run_script(
+        TC_PATH,
+        &[
+            "qdisc",
+            "replace",
+            "dev",
+            interface,
+            "root",
+            "cake",
+            "bandwidth",
+            bandwidth.as_str(),
+        ],
+    )
+    .await
+}
+
+async fn set_download_limit(interface: &str, limit: u32) -> Result<()> {
+    let ifb = ifb_name(interface).await?;
+    if limit == 0 {
+        let ingress = run_script(
+            TC_PATH,
+            &[
+                "qdisc",
+                "del",
+                "dev",
+                interface,
+                "handle",
+                INGRESS_HANDLE,
+                "ingress",
+            ],
+        )
+        .await;
+        // The block device has to go even if the redirect is already gone, or it would be picked
+        // up by whichever interface gets this index next
+        let ifb = run_script(IP_PATH, &["link", "del", "dev", ifb.as_str()]).await;
+        return ingress.and(ifb);
+    }
+
+    let bandwidth = format!("{limit}kbit");
+    if !try_exists(path(NET_PREFIX).join(ifb.as_str())).await? {
+        run_script(
+            IP_PATH,
+            &["link", "add", "name", ifb.as_str(), "type", "ifb"],
+        )
+        .await?;
+        run_script(IP_PATH, &["link", "set", "dev", ifb.as_str(), "up"]).await?;
+        run_script(
+            TC_PATH,
+            &[
+                "qdisc",
+                "add",
+                "dev",
+                interface,
+                "handle",
+                INGRESS_HANDLE,
+                "ingress",
+            ],
+        )
+        .await?;
+        run_script(
+            TC_PATH,
+            &[
+                "filter",
+                "add",
+                "dev",
+                interface,
+                "parent",
+                INGRESS_HANDLE,
+                "matchall",
+                "action",
+                "mirred",
+                "egress",
+                "redirect",
+                "dev",
+                ifb.as_str(),
+            ],
+        )
+        .await?;
+    }
+    run_script(
+        TC_PATH,
+        &[
+            "qdisc",
+            "replace",
+            "dev",
+            ifb.as_str(),
+            "root",
+            "cake",
+            "bandwidth",
+            bandwidth.as_str(),
+            "ingress",
+        ],
+    )
+    .await
+}
+
+impl NetworkLimitService {
+    pub fn new(channel: UnboundedReceiver<NetworkLimitCommand>) -> NetworkLimitService {
+        NetworkLimitService {
+            channel,
+            handle_set: JoinSet::new(),
+            limits: HashMap::new(),
+            applied: (0, 0),
+        }
+    }
+
+    fn effective_limits(&self) -> (u32, u32) {
+        // The strictest limit wins, ignoring handles that don't limit a direction
+        let strictest = |limit: fn(&Limit) -> u32| {
+            self.limits
+                .values()
+                .map(limit)
+                .filter(|limit| *limit > 0)
+                .min()
+                .unwrap_or(0)
+        };
+        (
+            strictest(|limit| limit.download),
+            strictest(|limit| limit.upload),
+        )
+    }
+
+    async fn apply(&mut self) -> Result<()> {
+        let (download, upload) = self.effective_limits();
+        let (applied_download, applied_upload) = self.applied;
+        if (download, upload) == self.applied {
+            return Ok(());
+        }
+        debug!("Limiting network bandwidth to {download} kbit/s down, {upload} kbit/s up");
+        for interface in list_network_interfaces().await? {
+            if upload != applied_upload {
+                if let Err(e) = set_upload_limit(interface.as_str(), upload).await {
+                    warn!("Failed to set upload limit on {interface}: {e}");
+                }
+            }
+            if download != applied_download {
+                if let Err(e) = set_download_limit(interface.as_str(), download).await {
+                    warn!("Failed to set download limit on {interface}: {e}");
+                }
+            }
+        }
+        self.applied = (download, upload);
+        Ok(())
+    }
+
+    /// Shape interfaces that show up while a limit is in place, e.g. a USB Ethernet adapter
+    async fn interface_added(&self, interface: &str) -> Result<()> {
+        let (download, upload) = self.applied;
+        if (download, upload) == (0, 0) || !is_physical_interface(interface).await? {
+            return Ok(());
+        }
+        debug!("Limiting network bandwidth of new interface {interface}");
+        if upload > 0 {
+            set_upload_limit(interface, upload).await?;
+        }
+        if download > 0 {
+            set_download_limit(interface, download).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete the block device of an interface that went away. Interface indices get reused, and a
+    /// leftover device would keep a new interface with the same index from being set up.
+    async fn interface_removed(&self, interface: &str, ifindex: &str) -> Result<()> {
+        if interface.starts_with(IFB_PREFIX) {
+            return Ok(());
+        }
+        let ifb = ifb_for_index(ifindex);
+        if !try_exists(path(NET_PREFIX).join(ifb.as_str())).await? {
+            return Ok(());
+        }
+        debug!("Removing {ifb} of removed interface {interface}");
+        run_script(IP_PATH, &["link", "del", "dev", ifb.as_str()]).await
+    }
+
+    async fn limit(&mut self, identifier: String, download: u32, upload: u32) -> Result<OwnedFd> {
+        if download == 0 && upload == 0 {
+            bail!("No bandwidth limit given");
+        }
+        match self.limits.entry(identifier.clone()) {
+            Entry::Occupied(mut e) => {
+                let limit = e.get_mut();
+                limit.count += 1;
+                limit.download = download;
+                limit.upload = upload;
+            }
+            Entry::Vacant(e) => {
+                e.insert(Limit {
+                    count: 1,
+                    download,
+                    upload,
+                });
+            }
+        }
+        // If applying the limit fails, dropping the handle releases it again
+        let (send, recv) = pipe::pipe()?;
+        self.handle_set.spawn(wait_on_handle(recv, identifier));
+        self.apply().await?;
+        Ok(send.into_blocking_fd()?)
+    }
+
+    async fn release(&mut self, identifier: String) -> Result<()> {
+        match self.limits.entry(identifier) {
+            Entry::Occupied(e) if e.get().count == 1 => {
+                debug!("Releasing network limit for {}", e.key());
+                e.remove();
+            }
+            Entry::Occupied(mut e) => e.get_mut().count -= 1,
+            Entry::Vacant(_) => (),
+        }
+        self.apply().await
+    }
+
+    async fn handle_command(&mut self, command: NetworkLimitCommand) {
+        match command {
+            NetworkLimitCommand::Limit(identifier, download, upload, reply) => {
+                let fd = self.limit(identifier, download, upload).await;
+                let _ = reply.send(fd);
+            }
+            NetworkLimitCommand::ListHandles(reply) => {
+                let handles = self
+                    .limits
+                    .iter()
+                    .map(|(identifier, limit)| (identifier.clone(), (limit.download, limit.upload)))
+                    .collect();
+                let _ = reply.send(handles);
+            }
+        }
+    }
+}
+
+impl Service for NetworkLimitService {
+    const NAME: &'static str = "network-limit";
+
+    async fn run(&mut self) -> Result<()> {
+        let net_monitor = MonitorBuilder::new()?.match_subsystem("net")?.listen()?;
+        let net_fd = AsyncFd::new(net_monitor.as_fd())?;
+        let mut net_iter = net_monitor.iter();
+        loop {
+            tokio::select! {
+                guard = net_fd.ready(Interest::READABLE) => {
+                    let mut guard = guard?;
+                    let mut added = Vec::new();
+                    let mut removed = Vec::new();
+                    for ev in net_iter.by_ref() {
+                        let interface = ev.sysname().to_string_lossy().to_string();
+                        match ev.event_type() {
+                            EventType::Add => added.push(interface),
+                            // The interface is already gone from sysfs, so its index only comes
+                            // with the event
+                            EventType::Remove => {
+                                if let Some(ifindex) = ev.property_value("IFINDEX") {
+                                    let ifindex = ifindex.to_string_lossy().to_string();
+                                    removed.push((interface, ifindex));
+                                }
+                            }
+                            _ => (),
+                        }
+                    }
+                    guard.clear_ready();
+                    for interface in added {
+                        if let Err(e) = self.interface_added(interface.as_str()).await {
+                            warn!("Failed to limit network bandwidth of {interface}: {e}");
+                        }
+                    }
+                    for (interface, ifindex) in removed {
+                        let cleanup = self.interface_removed(interface.as_str(), ifindex.as_str());
+                        if let Err(e) = cleanup.await {
+                            warn!("Failed to clean up after removed interface {interface}: {e}");
+                        }
+                    }
+                },
+                message = self.channel.recv() => {
+                    let Some(message) = message else {
+                        bail!("Network limit service channel broke");
+                    };
+                    self.handle_command(message).await;
+                },
+                Some(identifier) = self.handle_set.join_next(), if !self.handle_set.is_empty() => {
+                    match identifier {
+                        Ok(identifier) => {
+                            if let Err(e) = self.release(identifier).await {
+                                error!("Failed to release network limit: {e}");
+                            }
+                        }
+                        Err(e) => warn!("Failed to get closed network limit handle: {e}"),
+                    }
+                },
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        if !self.limits.is_empty() {
+            self.limits.clear();
+            self.apply().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use std::ffi::OsStr;
+    use std::io::Write;
+    use tokio::fs::{create_dir_all, write};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn log_command(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+        let mut log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path("commands.log"))?;
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+        writeln!(log, "{} {}", executable.to_string_lossy(), args.join(" "))?;
+        Ok((0, String::new()))
+    }
+
+    async fn take_commands() -> Vec<String> {
+        let commands = read_to_string(path("commands.log"))
+            .await
+            .unwrap_or_default();
+        let _ = tokio::fs::remove_file(path("commands.log")).await;
+        commands.lines().map(String::from).collect()
+    }
+
+    async fn list_handles(service: &mut NetworkLimitService) -> HashMap<String, (u32, u32)> {
+        let (tx, rx) = oneshot::channel();
+        service
+            .handle_command(NetworkLimitCommand::ListHandles(tx))
+            .await;
+        rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn enumerate_interfaces() {
+        let _h = testing::start();
+
+        create_dir_all(path(NET_PREFIX).join("lo"))
+            .await
+            .expect("create_dir_all");
+        create_dir_all(path(NET_PREFIX).join("wlan0/device"))
+            .await
+            .expect("create_dir_all");
+        create_dir_all(path(NET_PREFIX).join("enp3s0/device"))
+            .await
+            .expect("create_dir_all");
+        assert_eq!(
+            list_network_interfaces().await.unwrap(),
+            vec![String::from("enp3s0"), String::from("wlan0")]
+        );
+    }
+
+    #[tokio::test]
+    async fn limit_refcounting() {
+        let h = testing::start();
+        h.test.process_cb.set(log_command);
+
+        create_dir_all(path(NET_PREFIX).join("wlan0/device"))
+            .await
+            .expect("create_dir_all");
+        write(path(NET_PREFIX).join("wlan0/ifindex"), "3\n")
+            .await
+            .expect("write");
+
+        let (_tx, rx) = unbounded_channel();
+        let mut service = NetworkLimitService::new(rx);
+        assert!(list_handles(&mut service).await.is_empty());
+        assert!(service.limit(String::from("bogus"), 0, 0).await.is_err());
+
+        let steam = service
+            .limit(String::from("steam"), 10000, 0)
+            .await
+            .unwrap();
+        assert_eq!(service.applied, (10000, 0));
+        assert_eq!(
+            take_commands().await,
+            vec![
+                "/usr/bin/ip link add name ifb4-3 type ifb",
+                "/usr/bin/ip link set dev ifb4-3 up",
+                "/usr/bin/tc qdisc add dev wlan0 handle ffff: ingress",
+                "/usr/bin/tc filter add dev wlan0 parent ffff: matchall action mirred egress redirect dev ifb4-3",
+                "/usr/bin/tc qdisc replace dev ifb4-3 root cake bandwidth 10000kbit ingress",
+            ]
+        );
+
+        let game = service
+            .limit(String::from("game"), 5000, 1000)
+            .await
+            .unwrap();
+        assert_eq!(service.applied, (5000, 1000));
+        assert_eq!(
+            list_handles(&mut service).await,
+            [
+                (String::from("steam"), (10000, 0)),
+                (String::from("game"), (5000, 1000))
+            ]
+            .into()
+        );
+        take_commands().await;
+
+        // Interfaces that show up later are limited too
+        let usb = path(NET_PREFIX).join("enx00e04c680123");
+        create_dir_all(usb.join("device"))
+            .await
+            .expect("create_dir_all");
+        write(usb.join("ifindex"), "12\n").await.expect("write");
+        service
+            .interface_added("enx00e04c680123")
+            .await
+            .expect("interface_added");
+        assert_eq!(
+            take_commands().await,
+            vec![
+                "/usr/bin/tc qdisc replace dev enx00e04c680123 root cake bandwidth 1000kbit",
+                "/usr/bin/ip link add name ifb4-12 type ifb",
+                "/usr/bin/ip link set dev ifb4-12 up",
+                "/usr/bin/tc qdisc add dev enx00e04c680123 handle ffff: ingress",
+                "/usr/bin/tc filter add dev enx00e04c680123 parent ffff: matchall action mirred egress redirect dev ifb4-12",
+                "/usr/bin/tc qdisc replace dev ifb4-12 root cake bandwidth 5000kbit ingress",
+            ]
+        );
+        service
+            .interface_added("ifb4-12")
+            .await
+            .expect("interface_added");
+        assert!(take_commands().await.is_empty());
+
+        drop(game);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier).await.unwrap();
+        assert_eq!(service.applied, (10000, 0));
+        assert!(take_commands()
+            .await
+            .contains(&String::from("/usr/bin/tc qdisc del dev wlan0 root")));
+
+        drop(steam);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier).await.unwrap();
+        assert_eq!(service.applied, (0, 0));
+        assert_eq!(
+            take_commands().await,
+            vec![
+                "/usr/bin/tc qdisc del dev enx00e04c680123 handle ffff: ingress",
+                "/usr/bin/ip link del dev ifb4-12",
+                "/usr/bin/tc qdisc del dev wlan0 handle ffff: ingress",
+                "/usr/bin/ip link del dev ifb4-3",
+            ]
+        );
+        assert!(list_handles(&mut service).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn removed_interface() {
+        let h = testing::start();
+        h.test.process_cb.set(log_command);
+
+        let (_tx, rx) = unbounded_channel();
+        let service = NetworkLimitService::new(rx);
+        create_dir_all(path(NET_PREFIX).join("ifb4-12"))
+            .await
+            .expect("create_dir_all");
+
+        // Block devices don't have block devices of their own
+        service
+            .interface_removed("ifb4-12", "13")
+            .await
+            .expect("interface_removed");
+        service
+            .interface_removed("wlan0", "3")
+            .await
+            .expect("interface_removed");
+        assert!(take_commands().await.is_empty());
+
+        service
+            .interface_removed("enx00e04c680123", "12")
+            .await
+            .expect("interface_removed");
+        assert_eq!(
+            take_commands().await,
+            vec!["/usr/bin/ip link del dev ifb4-12"]
+        );
+    }
+}
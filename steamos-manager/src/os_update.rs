@@ -0,0 +1,152 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct OsUpdateCandidate {
+    /// Either `minor` for an update within the current release or `major` for a new release
+    pub kind: String,
+    pub version: String,
+    pub buildid: String,
+    /// Estimated download size in bytes, or 0 if unknown
+    pub estimated_size: u64,
+}
+
+#[derive(Deserialize)]
+struct UpdateManifest {
+    #[serde(default)]
+    minor: Option<UpdateRelease>,
+    #[serde(default)]
+    major: Option<UpdateRelease>,
+}
+
+#[derive(Deserialize)]
+struct UpdateRelease {
+    #[serde(default)]
+    candidates: Vec<UpdateCandidate>,
+}
+
+#[derive(Deserialize)]
+struct UpdateCandidate {
+    image: UpdateImage,
+}
+
+#[derive(Deserialize)]
+struct UpdateImage {
+    version: String,
+    buildid: String,
+    #[serde(default)]
+    estimated_size: u64,
+}
+
+/// Parse the JSON that `steamos-atomupd-client --query-only` prints, which is
+/// empty when there are no updates available
+pub(crate) fn parse_update_candidates(output: &str) -> Result<Vec<OsUpdateCandidate>> {
+    let output = output.trim();
+    if output.is_empty() {
+        return Ok(Vec::new());
+    }
+    let manifest: UpdateManifest = serde_json::from_str(output)?;
+    let mut candidates = Vec::new();
+    for (kind, release) in [("minor", manifest.minor), ("major", manifest.major)] {
+        let Some(release) = release else {
+            continue;
+        };
+        candidates.extend(
+            release
+                .candidates
+                .into_iter()
+                .map(|candidate| OsUpdateCandidate {
+                    kind: kind.to_string(),
+                    version: candidate.image.version,
+                    buildid: candidate.image.buildid,
+                    estimated_size: candidate.image.estimated_size,
+                }),
+        );
+    }
+    Ok(candidates)
+}
+
+/// Parse a progress line printed by steamos-atomupd-client while applying an
+/// update, e.g. `42.50% 03m21s`
+pub(crate) fn parse_update_progress(line: &str) -> Option<u32> {
+    let percent = line.split_whitespace().next()?.strip_suffix('%')?;
+    let percent: f64 = percent.parse().ok()?;
+    if !(0.0..=100.0).contains(&percent) {
+        return None;
+    }
+    Some(percent as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn update_candidates() {
+        assert!(parse_update_candidates("").unwrap().is_empty());
+        assert!(parse_update_candidates("{}\n").unwrap().is_empty());
+        assert!(parse_update_candidates("not json").is_err());
+
+        let output = r#"{
+            "minor": {
+                "release": "holo",
+                "candidates": [{
+                    "image": {
+                        "product": "steamos",
+                        "release": "holo",
+                        "variant": "steamdeck",
+                        "arch": "amd64",
+                        "version": "3.7.8",
+                        "buildid": "20250601.1",
+                        "checkpoint": false,
+                        "estimated_size": 1048576
+                    },
+                    "update_path": "steamdeck/20250601.1/steamdeck-20250601.1-3.7.8.raucb"
+                }]
+            },
+            "major": {
+                "release": "holo",
+                "candidates": [{
+                    "image": {
+                        "version": "3.8.0",
+                        "buildid": "20250610.100"
+                    },
+                    "update_path": "steamdeck/20250610.100/steamdeck-20250610.100-3.8.0.raucb"
+                }]
+            }
+        }"#;
+        assert_eq!(
+            parse_update_candidates(output).unwrap(),
+            vec![
+                OsUpdateCandidate {
+                    kind: String::from("minor"),
+                    version: String::from("3.7.8"),
+                    buildid: String::from("20250601.1"),
+                    estimated_size: 1048576,
+                },
+                OsUpdateCandidate {
+                    kind: String::from("major"),
+                    version: String::from("3.8.0"),
+                    buildid: String::from("20250610.100"),
+                    estimated_size: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn update_progress() {
+        assert_eq!(parse_update_progress("0.00% 10m00s"), Some(0));
+        assert_eq!(parse_update_progress("42.50% 03m21s"), Some(42));
+        assert_eq!(parse_update_progress("100%"), Some(100));
+        assert_eq!(parse_update_progress("101.00% 00m00s"), None);
+        assert_eq!(parse_update_progress("Downloading 42%"), None);
+        assert_eq!(parse_update_progress(""), None);
+    }
+}
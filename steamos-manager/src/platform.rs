@@ -7,20 +7,25 @@
 
 #[cfg(not(test))]
 use anyhow::Context;
-use anyhow::Result;
+use anyhow::{anyhow, bail, ensure, Result};
 use nix::errno::Errno;
 use nix::unistd::{access, AccessFlags};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Component, PathBuf};
 use tokio::fs::{metadata, read_to_string};
 #[cfg(not(test))]
 use tokio::sync::OnceCell;
 use tokio::task::spawn_blocking;
 use zbus::Connection;
 
-#[cfg(test)]
+use crate::developer_mode::DeveloperFeature;
+use crate::hardware::FactoryResetKind;
+use crate::power::write_sysfs;
+use crate::storage::FormatOptions;
+
 use crate::path;
 use crate::systemd::SystemdUnit;
 
@@ -33,8 +38,18 @@ pub(crate) struct PlatformConfig {
     pub factory_reset: Option<ResetConfig>,
     pub update_bios: Option<ScriptConfig>,
     pub update_dock: Option<ScriptConfig>,
+    pub dock: Option<DockConfig>,
     pub storage: Option<StorageConfig>,
     pub fan_control: Option<ServiceConfig>,
+    pub firmware: Option<FirmwareConfig>,
+    /// Sysfs attributes the user daemon may access, keyed by name
+    pub sysfs_access: HashMap<String, SysfsAttributeConfig>,
+    pub usb_authorization: Option<UsbAuthorizationConfig>,
+    pub update_channel: Option<UpdateChannelConfig>,
+    pub os_update: Option<OsUpdateConfig>,
+    pub developer_mode: Option<DeveloperModeConfig>,
+    /// Systemd units that may be started, stopped and enabled through the Services1 interface
+    pub managed_units: Vec<String>,
 }
 
 #[derive(Clone, Default, Deserialize, Debug)]
@@ -42,6 +57,9 @@ pub(crate) struct ScriptConfig {
     pub script: PathBuf,
     #[serde(default)]
     pub script_args: Vec<String>,
+    /// Time in seconds after which a job running the script is terminated
+    #[serde(default)]
+    pub timeout: Option<u64>,
 }
 
 impl ScriptConfig {
@@ -79,6 +97,10 @@ pub(crate) struct ResetConfig {
     pub all: ScriptConfig,
     pub os: ScriptConfig,
     pub user: ScriptConfig,
+    /// Script that reports what a reset would do without doing it. It is passed
+    /// the arguments of the reset kind being estimated after its own arguments.
+    #[serde(default)]
+    pub estimate: Option<ScriptConfig>,
 }
 
 impl ResetConfig {
@@ -87,6 +109,43 @@ impl ResetConfig {
             && self.os.is_valid(root).await?
             && self.user.is_valid(root).await?)
     }
+
+    pub(crate) fn script(&self, kind: FactoryResetKind) -> &ScriptConfig {
+        match kind {
+            FactoryResetKind::User => &self.user,
+            FactoryResetKind::OS => &self.os,
+            FactoryResetKind::All => &self.all,
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct ResetEstimate {
+    pub partitions: Vec<String>,
+    /// Approximate duration in seconds
+    pub duration: u64,
+}
+
+pub(crate) fn parse_reset_estimate(output: &str) -> Result<ResetEstimate> {
+    let mut partitions = Vec::new();
+    let mut duration = None;
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "partition" => partitions.push(value.to_string()),
+            "duration" => duration = Some(value.parse()?),
+            _ => (),
+        }
+    }
+
+    Ok(ResetEstimate {
+        partitions,
+        duration: duration.ok_or(anyhow!("Missing duration"))?,
+    })
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -119,6 +178,9 @@ impl ServiceConfig {
 pub(crate) struct StorageConfig {
     pub trim_devices: ScriptConfig,
     pub format_device: FormatDeviceConfig,
+    /// Script printing health data for the device given as its last argument
+    #[serde(default)]
+    pub health: Option<ScriptConfig>,
 }
 
 impl StorageConfig {
@@ -127,6 +189,83 @@ impl StorageConfig {
     }
 }
 
+#[derive(Clone, Default, Deserialize, Debug)]
+pub(crate) struct SysfsAttributeConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub writable: bool,
+    /// Values that may be written. Any value may be written if this is empty
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl SysfsAttributeConfig {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.path.starts_with("/sys")
+            && !self
+                .path
+                .components()
+                .any(|component| component == Component::ParentDir)
+    }
+
+    pub(crate) async fn read(&self) -> Result<String> {
+        ensure!(
+            self.is_valid(),
+            "Invalid sysfs path {}",
+            self.path.display()
+        );
+        Ok(read_to_string(path(self.path.to_string_lossy()))
+            .await?
+            .trim_end()
+            .to_string())
+    }
+
+    pub(crate) async fn write(&self, value: &str) -> Result<()> {
+        ensure!(
+            self.is_valid(),
+            "Invalid sysfs path {}",
+            self.path.display()
+        );
+        ensure!(self.writable, "Attribute is not writable");
+        ensure!(
+            self.values.is_empty() || self.values.iter().any(|allowed| allowed == value),
+            "Value {value} is not allowed"
+        );
+        write_sysfs(
+            path(self.path.to_string_lossy()),
+            format!("{value}\n").as_bytes(),
+        )
+        .await
+    }
+}
+
+/// USB devices that identify a dock
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct DockConfig {
+    /// Dock model names, keyed by the `vendor:product` ID of the dock's USB device
+    pub models: HashMap<String, String>,
+}
+
+impl DockConfig {
+    pub(crate) fn model(&self, id: &str) -> Option<&str> {
+        self.models
+            .iter()
+            .find(|(model_id, _)| model_id.eq_ignore_ascii_case(id))
+            .map(|(_, model)| model.as_str())
+    }
+}
+
+/// Scripts that print the installed firmware versions without updating anything
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct FirmwareConfig {
+    /// Prints the dock firmware version
+    pub dock: Option<ScriptConfig>,
+    /// Prints one `name=version` line per controller
+    pub controller: Option<ScriptConfig>,
+}
+
 #[derive(Clone, Default, Deserialize, Debug)]
 pub(crate) struct FormatDeviceConfig {
     pub script: PathBuf,
@@ -139,6 +278,21 @@ pub(crate) struct FormatDeviceConfig {
     pub validate_flag: Option<String>,
     #[serde(default)]
     pub no_validate_flag: Option<String>,
+    /// Time in seconds after which a job running the script is terminated
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub filesystem_flag: Option<String>,
+    /// Filesystems that can be selected when formatting
+    #[serde(default)]
+    pub filesystems: Vec<String>,
+    #[serde(default)]
+    pub encrypt_flag: Option<String>,
+    #[serde(default)]
+    pub cluster_size_flag: Option<String>,
+    /// Cluster sizes in bytes that can be selected when formatting
+    #[serde(default)]
+    pub cluster_sizes: Vec<u32>,
 }
 
 impl FormatDeviceConfig {
@@ -169,6 +323,143 @@ impl FormatDeviceConfig {
         }
         Ok(true)
     }
+
+    pub(crate) fn args(
+        &self,
+        device: &str,
+        label: &str,
+        validate: bool,
+        options: &FormatOptions,
+    ) -> Result<Vec<String>> {
+        let mut args = self.script_args.clone();
+
+        args.extend([self.label_flag.clone(), label.to_string()]);
+
+        match (validate, &self.validate_flag, &self.no_validate_flag) {
+            (true, Some(validate_flag), _) => args.push(validate_flag.clone()),
+            (false, _, Some(no_validate_flag)) => args.push(no_validate_flag.clone()),
+            _ => (),
+        }
+
+        if let Some(filesystem) = options.filesystem.as_ref() {
+            match &self.filesystem_flag {
+                Some(flag) if self.filesystems.contains(filesystem) => {
+                    args.extend([flag.clone(), filesystem.clone()]);
+                }
+                _ => bail!("Unsupported filesystem {filesystem}"),
+            }
+        }
+
+        if options.encrypt {
+            let Some(flag) = &self.encrypt_flag else {
+                bail!("Encryption is not supported");
+            };
+            args.push(flag.clone());
+        }
+
+        if let Some(cluster_size) = options.cluster_size {
+            match &self.cluster_size_flag {
+                Some(flag) if self.cluster_sizes.contains(&cluster_size) => {
+                    args.extend([flag.clone(), cluster_size.to_string()]);
+                }
+                _ => bail!("Unsupported cluster size {cluster_size}"),
+            }
+        }
+
+        if let Some(device_flag) = &self.device_flag {
+            args.push(device_flag.clone());
+        }
+        args.push(device.to_string());
+
+        Ok(args)
+    }
+}
+
+/// Policy for authorizing and deauthorizing USB devices
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct UsbAuthorizationConfig {
+    /// Devices that may never be deauthorized, as `vendor:product` IDs, e.g. built-in controllers
+    pub protected_devices: Vec<String>,
+}
+
+impl UsbAuthorizationConfig {
+    pub(crate) fn is_protected(&self, id: &str) -> bool {
+        self.protected_devices
+            .iter()
+            .any(|protected| protected.eq_ignore_ascii_case(id))
+    }
+}
+
+/// Selection of the branch that steamos-atomupd pulls OS updates from
+#[derive(Clone, Default, Deserialize, Debug)]
+pub(crate) struct UpdateChannelConfig {
+    /// Branches that may be selected, e.g. `stable`, `beta` or `preview`
+    pub branches: Vec<String>,
+    /// Prints the currently selected branch
+    pub get: ScriptConfig,
+    /// Selects the branch passed as its last argument
+    pub set: ScriptConfig,
+}
+
+impl UpdateChannelConfig {
+    pub(crate) async fn is_valid(&self, root: bool) -> Result<bool> {
+        Ok(!self.branches.is_empty()
+            && self.get.is_valid(root).await?
+            && self.set.is_valid(root).await?)
+    }
+
+    pub(crate) fn is_allowed(&self, branch: &str) -> bool {
+        self.branches.iter().any(|allowed| allowed == branch)
+    }
+}
+
+/// OS updates applied through steamos-atomupd-client
+#[derive(Clone, Default, Deserialize, Debug)]
+pub(crate) struct OsUpdateConfig {
+    /// Prints the available updates as JSON without applying them
+    pub check: ScriptConfig,
+    /// Downloads and applies the newest update, printing its progress
+    pub update: ScriptConfig,
+}
+
+impl OsUpdateConfig {
+    pub(crate) async fn is_valid(&self, root: bool) -> Result<bool> {
+        Ok(self.check.is_valid(root).await? && self.update.is_valid(root).await?)
+    }
+}
+
+/// Systemd units behind the developer mode toggles, any of which may be left out
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct DeveloperModeConfig {
+    /// The SSH server, e.g. `sshd.service`
+    pub ssh: Option<String>,
+    /// The core dump collector, e.g. `systemd-coredump.socket`
+    pub core_dumps: Option<String>,
+    /// The journal daemon, restarted after changing where it stores the journal
+    pub journal: Option<String>,
+}
+
+impl DeveloperModeConfig {
+    pub(crate) fn unit(&self, feature: DeveloperFeature) -> Option<&str> {
+        match feature {
+            DeveloperFeature::Ssh => self.ssh.as_deref(),
+            DeveloperFeature::CoreDumps => self.core_dumps.as_deref(),
+            DeveloperFeature::PersistentJournal => self.journal.as_deref(),
+        }
+    }
+
+    pub(crate) async fn is_valid(
+        &self,
+        connection: &Connection,
+        feature: DeveloperFeature,
+    ) -> Result<bool> {
+        match self.unit(feature) {
+            Some(unit) => SystemdUnit::exists(connection, unit).await,
+            None => Ok(false),
+        }
+    }
 }
 
 impl PlatformConfig {
@@ -193,6 +484,11 @@ impl PlatformConfig {
             if factory_reset.user.script.as_os_str().is_empty() {
                 factory_reset.user.script = path("exe");
             }
+            if let Some(ref mut estimate) = factory_reset.estimate {
+                if estimate.script.as_os_str().is_empty() {
+                    estimate.script = path("exe");
+                }
+            }
         }
         if let Some(ref mut storage) = self.storage {
             if storage.trim_devices.script.as_os_str().is_empty() {
@@ -201,6 +497,11 @@ impl PlatformConfig {
             if storage.format_device.script.as_os_str().is_empty() {
                 storage.format_device.script = path("exe");
             }
+            if let Some(ref mut health) = storage.health {
+                if health.script.as_os_str().is_empty() {
+                    health.script = path("exe");
+                }
+            }
         }
         if let Some(ref mut update_bios) = self.update_bios {
             if update_bios.script.as_os_str().is_empty() {
@@ -212,6 +513,30 @@ impl PlatformConfig {
                 update_dock.script = path("exe");
             }
         }
+        if let Some(ref mut firmware) = self.firmware {
+            for config in [firmware.dock.as_mut(), firmware.controller.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                if config.script.as_os_str().is_empty() {
+                    config.script = path("exe");
+                }
+            }
+        }
+        if let Some(ref mut update_channel) = self.update_channel {
+            for config in [&mut update_channel.get, &mut update_channel.set] {
+                if config.script.as_os_str().is_empty() {
+                    config.script = path("exe");
+                }
+            }
+        }
+        if let Some(ref mut os_update) = self.os_update {
+            for config in [&mut os_update.check, &mut os_update.update] {
+                if config.script.as_os_str().is_empty() {
+                    config.script = path("exe");
+                }
+            }
+        }
     }
 }
 
@@ -227,12 +552,57 @@ pub(crate) async fn platform_config() -> Result<Option<PlatformConfig>> {
     Ok(config)
 }
 
+/// Read the platform config from disk instead of using the copy loaded at startup
+#[cfg(not(test))]
+pub(crate) async fn read_platform_config() -> Result<Option<PlatformConfig>> {
+    PlatformConfig::load().await
+}
+
+#[cfg(test)]
+pub(crate) async fn read_platform_config() -> Result<Option<PlatformConfig>> {
+    platform_config().await
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{path, testing};
+    use crate::testing;
     use std::os::unix::fs::PermissionsExt;
-    use tokio::fs::{set_permissions, write};
+    use tokio::fs::{create_dir_all, set_permissions, write};
+
+    #[tokio::test]
+    async fn sysfs_attribute_access() {
+        let _h = testing::start();
+
+        let base = path("/sys/devices/platform/test");
+        create_dir_all(&base).await.unwrap();
+        write(base.join("mode"), "quiet\n").await.unwrap();
+
+        let mut config = SysfsAttributeConfig {
+            path: PathBuf::from("/sys/devices/platform/test/mode"),
+            writable: false,
+            values: Vec::new(),
+        };
+        assert!(config.is_valid());
+        assert_eq!(config.read().await.unwrap(), "quiet");
+        assert!(config.write("loud").await.is_err());
+
+        config.writable = true;
+        config.write("loud").await.unwrap();
+        assert_eq!(config.read().await.unwrap(), "loud");
+
+        config.values = vec![String::from("quiet"), String::from("balanced")];
+        assert!(config.write("loud").await.is_err());
+        config.write("balanced").await.unwrap();
+        assert_eq!(config.read().await.unwrap(), "balanced");
+
+        config.path = PathBuf::from("/sys/devices/../../etc/shadow");
+        assert!(!config.is_valid());
+        assert!(config.read().await.is_err());
+        config.path = PathBuf::from("/etc/shadow");
+        assert!(!config.is_valid());
+        assert!(config.read().await.is_err());
+    }
 
     #[tokio::test]
     async fn script_config_valid_no_path() {
@@ -249,6 +619,7 @@ mod test {
         assert!(!ScriptConfig {
             script: PathBuf::from("/"),
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(false)
         .await
@@ -260,6 +631,7 @@ mod test {
         assert!(!ScriptConfig {
             script: PathBuf::from("/"),
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(true)
         .await
@@ -278,6 +650,7 @@ mod test {
         assert!(!ScriptConfig {
             script: exe_path,
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(false)
         .await
@@ -296,6 +669,7 @@ mod test {
         assert!(!ScriptConfig {
             script: exe_path,
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(true)
         .await
@@ -314,6 +688,7 @@ mod test {
         assert!(ScriptConfig {
             script: exe_path,
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(false)
         .await
@@ -332,12 +707,104 @@ mod test {
         assert!(ScriptConfig {
             script: exe_path,
             script_args: Vec::new(),
+            timeout: None,
         }
         .is_valid(true)
         .await
         .unwrap());
     }
 
+    #[test]
+    fn reset_estimate() {
+        assert_eq!(
+            parse_reset_estimate("partition=home\npartition=var\nduration=90\n").unwrap(),
+            ResetEstimate {
+                partitions: vec![String::from("home"), String::from("var")],
+                duration: 90,
+            }
+        );
+        assert_eq!(
+            parse_reset_estimate("duration = 5\nunknown=1\ngarbage\n").unwrap(),
+            ResetEstimate {
+                partitions: Vec::new(),
+                duration: 5,
+            }
+        );
+        assert!(parse_reset_estimate("partition=home\n").is_err());
+        assert!(parse_reset_estimate("duration=soon\n").is_err());
+    }
+
+    #[test]
+    fn format_device_args() {
+        let config = FormatDeviceConfig {
+            script: PathBuf::from("format-device.sh"),
+            script_args: vec![String::from("--quiet")],
+            label_flag: String::from("--label"),
+            device_flag: Some(String::from("--device")),
+            no_validate_flag: Some(String::from("--skip-validation")),
+            filesystem_flag: Some(String::from("--fstype")),
+            filesystems: vec![String::from("ext4"), String::from("exfat")],
+            cluster_size_flag: Some(String::from("--cluster-size")),
+            cluster_sizes: vec![4096, 32768],
+            ..FormatDeviceConfig::default()
+        };
+
+        assert_eq!(
+            config
+                .args("/dev/mmcblk0", "SD", false, &FormatOptions::default())
+                .unwrap(),
+            vec![
+                "--quiet",
+                "--label",
+                "SD",
+                "--skip-validation",
+                "--device",
+                "/dev/mmcblk0"
+            ]
+        );
+        assert_eq!(
+            config
+                .args(
+                    "/dev/mmcblk0",
+                    "SD",
+                    true,
+                    &FormatOptions {
+                        filesystem: Some(String::from("exfat")),
+                        encrypt: false,
+                        cluster_size: Some(32768),
+                    }
+                )
+                .unwrap(),
+            vec![
+                "--quiet",
+                "--label",
+                "SD",
+                "--fstype",
+                "exfat",
+                "--cluster-size",
+                "32768",
+                "--device",
+                "/dev/mmcblk0"
+            ]
+        );
+
+        let options = FormatOptions {
+            filesystem: Some(String::from("btrfs")),
+            ..FormatOptions::default()
+        };
+        assert!(config.args("/dev/mmcblk0", "SD", true, &options).is_err());
+        let options = FormatOptions {
+            cluster_size: Some(512),
+            ..FormatOptions::default()
+        };
+        assert!(config.args("/dev/mmcblk0", "SD", true, &options).is_err());
+        let options = FormatOptions {
+            encrypt: true,
+            ..FormatOptions::default()
+        };
+        assert!(config.args("/dev/mmcblk0", "SD", true, &options).is_err());
+    }
+
     #[tokio::test]
     async fn jupiter_valid() {
         let config = read_to_string("../data/devices/jupiter.toml")
@@ -346,4 +813,19 @@ mod test {
         let res = toml::from_str::<PlatformConfig>(config.as_ref());
         assert!(res.is_ok(), "{res:?}");
     }
+
+    #[tokio::test]
+    async fn update_channel_config_valid() {
+        let config = read_to_string("../data/platform.toml")
+            .await
+            .expect("read_to_string");
+        let config = toml::from_str::<PlatformConfig>(config.as_ref()).expect("from_str");
+        let update_channel = config.update_channel.expect("update_channel");
+        assert!(update_channel.is_allowed("beta"));
+        assert!(!update_channel.is_allowed("main"));
+        assert!(config.os_update.is_some());
+        assert!(config.developer_mode.is_some());
+        // Units with their own interface mustn't be reachable through Services1 as well
+        assert!(config.managed_units.is_empty());
+    }
 }
@@ -0,0 +1,567 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+//! Restricting which callers may use the methods and property setters of the daemons
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::pin::Pin;
+use tokio::sync::Mutex;
+use tracing::{debug, info_span, Instrument, Span};
+use zbus::message::Header;
+use zbus::names::{InterfaceName, MemberName};
+use zbus::object_server::{DispatchResult, Interface, SignalEmitter};
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::{fdo, Connection, Message, ObjectServer};
+
+use crate::caller::{identify_caller, Caller};
+
+type DispatchFuture<'call> = Pin<Box<dyn Future<Output = zbus::Result<()>> + Send + 'call>>;
+
+static POLICY: Mutex<PolicyConfig> = Mutex::const_new(PolicyConfig { rules: Vec::new() });
+
+/// A rule limiting an interface, or one of its methods or properties, to certain callers. A
+/// caller is allowed if it matches every list, where an empty list matches anyone.
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PolicyRule {
+    /// Full interface name, e.g. `com.steampowered.SteamOSManager1.LowPowerMode1`
+    pub interface: String,
+    /// Method or property the rule applies to, or all of them if unset
+    #[serde(default)]
+    pub member: Option<String>,
+    #[serde(default)]
+    pub uids: Vec<u32>,
+    /// Absolute paths of executables, e.g. `/usr/bin/steam-runtime-launcher`
+    #[serde(default)]
+    pub executables: Vec<PathBuf>,
+}
+
+impl PolicyRule {
+    fn allows(&self, caller: &Caller) -> bool {
+        if !self.uids.is_empty() && !self.uids.contains(&caller.uid) {
+            return false;
+        }
+        // The executable is only known if the bus vouched for the process with a pidfd
+        self.executables.is_empty()
+            || caller
+                .exe
+                .as_ref()
+                .is_some_and(|exe| self.executables.contains(exe))
+    }
+}
+
+/// Property reads are never restricted, so settings stay visible to every client
+#[derive(Clone, Default, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct PolicyConfig {
+    pub rules: Vec<PolicyRule>,
+}
+
+impl PolicyConfig {
+    /// The rules governing a member. Rules naming it take precedence over ones covering its
+    /// whole interface.
+    fn rules_for(&self, interface: &str, member: &str) -> Vec<PolicyRule> {
+        let rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.interface == interface)
+            .filter(|rule| rule.member.as_deref() == Some(member))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !rules.is_empty() {
+            return rules;
+        }
+        self.rules
+            .iter()
+            .filter(|rule| rule.interface == interface && rule.member.is_none())
+            .cloned()
+            .collect()
+    }
+}
+
+pub(crate) async fn configure_policy(config: PolicyConfig) {
+    *POLICY.lock().await = config;
+}
+
+/// A span covering the handling of a call, so records logged meanwhile carry the interface,
+/// member and sender. The journal stores them as `INTERFACE=`, `MEMBER=` and `SENDER=` fields.
+pub(crate) fn call_span(interface: &str, member: &str, header: Option<&Header<'_>>) -> Span {
+    let sender = header
+        .and_then(Header::sender)
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    info_span!("dbus_call", interface, member, sender = sender.as_str())
+}
+
+/// Make sure the caller is allowed to call a method, or set a property, of an interface. Without
+/// a header the call comes from within the daemon and is always allowed.
+async fn enforce_policy(
+    connection: &Connection,
+    header: Option<&Header<'_>>,
+    interface: &str,
+    member: &str,
+    allow_unlisted: bool,
+) -> zbus::Result<()> {
+    let Some(header) = header else {
+        return Ok(());
+    };
+    let rules = POLICY.lock().await.rules_for(interface, member);
+    if rules.is_empty() && allow_unlisted {
+        return Ok(());
+    }
+    let caller = identify_caller(connection, header).await?;
+    if rules.iter().any(|rule| rule.allows(&caller)) {
+        return Ok(());
+    }
+    debug!(
+        "Denied {member} of {interface} to {} (uid {}, executable {:?})",
+        caller.sender, caller.uid, caller.exe
+    );
+    Err(fdo::Error::AccessDenied(format!(
+        "Caller {} is not allowed to use {member} of {interface}",
+        caller.sender
+    ))
+    .into())
+}
+
+/// An interface whose method calls and property writes are checked against the policy before
+/// they are dispatched to it. Everything exported at the manager path of either daemon is
+/// registered through this, so lookups must name `Guarded<I>` rather than `I`.
+pub(crate) struct Guarded<I> {
+    inner: I,
+    /// Whether members without a rule of their own are refused instead of allowed
+    restricted: bool,
+}
+
+impl<I: Interface> Guarded<I> {
+    pub(crate) fn new(inner: I) -> Guarded<I> {
+        Guarded {
+            inner,
+            restricted: false,
+        }
+    }
+
+    /// Like [`Guarded::new`], but for interfaces nobody may use unless a rule allows them to
+    pub(crate) fn restricted(inner: I) -> Guarded<I> {
+        Guarded {
+            inner,
+            restricted: true,
+        }
+    }
+
+    async fn check(
+        restricted: bool,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        member: &str,
+    ) -> zbus::Result<()> {
+        enforce_policy(connection, header, I::name().as_str(), member, !restricted).await
+    }
+
+    /// Hold a dispatched method call back until the caller is known to be allowed to make it
+    fn guard_call<'call>(
+        restricted: bool,
+        connection: &'call Connection,
+        msg: &'call Message,
+        member: String,
+        call: DispatchFuture<'call>,
+    ) -> DispatchResult<'call> {
+        let header = msg.header();
+        let span = call_span(I::name().as_str(), member.as_str(), Some(&header));
+        DispatchResult::Async(Box::pin(
+            async move {
+                Self::check(restricted, connection, Some(&header), member.as_str()).await?;
+                call.await
+            }
+            .instrument(span),
+        ))
+    }
+}
+
+impl<I> Deref for Guarded<I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        &self.inner
+    }
+}
+
+impl<I> DerefMut for Guarded<I> {
+    fn deref_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+}
+
+#[async_trait]
+impl<I: Interface> Interface for Guarded<I> {
+    fn name() -> InterfaceName<'static> {
+        I::name()
+    }
+
+    fn spawn_tasks_for_methods(&self) -> bool {
+        self.inner.spawn_tasks_for_methods()
+    }
+
+    async fn get(
+        &self,
+        property_name: &str,
+        server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>> {
+        self.inner
+            .get(property_name, server, connection, header, emitter)
+            .await
+    }
+
+    async fn get_all(
+        &self,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        self.inner
+            .get_all(object_server, connection, header, emitter)
+            .await
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        object_server: &'call ObjectServer,
+        connection: &'call Connection,
+        header: Option<&'call Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call> {
+        let restricted = self.restricted;
+        match self.inner.set(
+            property_name,
+            value,
+            object_server,
+            connection,
+            header,
+            emitter,
+        ) {
+            DispatchResult::Async(set) => DispatchResult::Async(Box::pin(
+                async move {
+                    Self::check(restricted, connection, header, property_name).await?;
+                    set.await
+                }
+                .instrument(call_span(I::name().as_str(), property_name, header)),
+            )),
+            result => result,
+        }
+    }
+
+    async fn set_mut(
+        &mut self,
+        property_name: &str,
+        value: &Value<'_>,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>> {
+        let span = call_span(I::name().as_str(), property_name, header);
+        if let Err(e) = Self::check(self.restricted, connection, header, property_name)
+            .instrument(span.clone())
+            .await
+        {
+            return Some(Err(e.into()));
+        }
+        self.inner
+            .set_mut(
+                property_name,
+                value,
+                object_server,
+                connection,
+                header,
+                emitter,
+            )
+            .instrument(span)
+            .await
+    }
+
+    fn call<'call>(
+        &'call self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let member = name.to_string();
+        match self.inner.call(server, connection, msg, name) {
+            DispatchResult::Async(call) => {
+                Self::guard_call(self.restricted, connection, msg, member, call)
+            }
+            result => result,
+        }
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let restricted = self.restricted;
+        let member = name.to_string();
+        match self.inner.call_mut(server, connection, msg, name) {
+            DispatchResult::Async(call) => {
+                Self::guard_call(restricted, connection, msg, member, call)
+            }
+            result => result,
+        }
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        self.inner.introspect_to_writer(writer, level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex as StdMutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::{Layer, Registry};
+    use zbus::proxy::CacheProperties;
+
+    fn rule(member: Option<&str>, uids: &[u32], executables: &[&str]) -> PolicyRule {
+        PolicyRule {
+            interface: String::from("com.steampowered.SteamOSManager1.Test1"),
+            member: member.map(ToString::to_string),
+            uids: uids.to_vec(),
+            executables: executables.iter().map(PathBuf::from).collect(),
+        }
+    }
+
+    fn caller(uid: u32, exe: Option<&str>) -> Caller {
+        Caller {
+            sender: String::from(":1.42"),
+            uid,
+            pid: 4242,
+            exe: exe.map(PathBuf::from),
+            start_time: None,
+        }
+    }
+
+    #[test]
+    fn rule_matching() {
+        let unrestricted = rule(None, &[], &[]);
+        assert!(unrestricted.allows(&caller(1000, None)));
+
+        let steam = rule(None, &[1000], &["/usr/bin/steam"]);
+        assert!(steam.allows(&caller(1000, Some("/usr/bin/steam"))));
+        assert!(!steam.allows(&caller(1000, Some("/usr/bin/python3"))));
+        assert!(!steam.allows(&caller(1000, None)));
+        assert!(!steam.allows(&caller(1001, Some("/usr/bin/steam"))));
+
+        let config = PolicyConfig {
+            rules: vec![
+                rule(None, &[1000], &[]),
+                rule(Some("SetFoo"), &[], &["/usr/bin/steam"]),
+                rule(Some("SetFoo"), &[0], &[]),
+            ],
+        };
+        let interface = "com.steampowered.SteamOSManager1.Test1";
+        assert_eq!(
+            config.rules_for(interface, "SetFoo"),
+            vec![config.rules[1].clone(), config.rules[2].clone()]
+        );
+        assert_eq!(
+            config.rules_for(interface, "SetBar"),
+            vec![config.rules[0].clone()]
+        );
+        assert!(config
+            .rules_for("com.steampowered.SteamOSManager1.Other1", "SetFoo")
+            .is_empty());
+    }
+
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<StdMutex<Vec<(String, String)>>>);
+
+    impl Visit for SpanFields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    #[test]
+    fn call_span_fields() {
+        let message = Message::method_call("/com/steampowered/SteamOSManager1", "StoreFoo")
+            .unwrap()
+            .sender(":1.42")
+            .unwrap()
+            .interface("com.steampowered.SteamOSManager1.Test1")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        let fields = SpanFields::default();
+        let subscriber = Registry::default().with(fields.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = call_span(
+                "com.steampowered.SteamOSManager1.Test1",
+                "StoreFoo",
+                Some(&message.header()),
+            );
+        });
+        assert_eq!(
+            *fields.0.lock().unwrap(),
+            vec![
+                (
+                    String::from("interface"),
+                    String::from("\"com.steampowered.SteamOSManager1.Test1\"")
+                ),
+                (String::from("member"), String::from("\"StoreFoo\"")),
+                (String::from("sender"), String::from("\":1.42\"")),
+            ]
+        );
+    }
+
+    #[test]
+    fn process_names_rejected() {
+        let config = r#"
+            [[rules]]
+            interface = "com.steampowered.SteamOSManager1.Test1"
+            processes = ["steam"]
+        "#;
+        assert!(toml::from_str::<PolicyConfig>(config).is_err());
+    }
+
+    #[derive(Default)]
+    struct Test1 {
+        foo: u32,
+    }
+
+    #[zbus::interface(name = "com.steampowered.SteamOSManager1.Test1")]
+    impl Test1 {
+        async fn store_foo(&mut self, foo: u32) {
+            self.foo = foo;
+        }
+
+        async fn reset(&self) {}
+
+        #[zbus(property)]
+        async fn foo(&self) -> u32 {
+            self.foo
+        }
+
+        #[zbus(property)]
+        async fn set_foo(&mut self, foo: u32) {
+            self.foo = foo;
+        }
+    }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.Test1",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait Test {
+        fn store_foo(&self, foo: u32) -> zbus::Result<()>;
+
+        fn reset(&self) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn foo(&self) -> zbus::Result<u32>;
+
+        #[zbus(property)]
+        fn set_foo(&self, foo: u32) -> zbus::Result<()>;
+    }
+
+    #[tokio::test]
+    async fn dispatch() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("new_dbus");
+        connection
+            .object_server()
+            .at(
+                "/com/steampowered/SteamOSManager1",
+                Guarded::new(Test1::default()),
+            )
+            .await
+            .expect("at");
+        let proxy = TestProxy::builder(&connection)
+            .destination(connection.unique_name().unwrap().to_owned())
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        configure_policy(PolicyConfig::default()).await;
+        proxy.store_foo(1).await.expect("store_foo");
+        proxy.set_foo(2).await.expect("set_foo");
+        assert_eq!(proxy.foo().await.unwrap(), 2);
+
+        // Nothing the test runs as is installed there
+        configure_policy(PolicyConfig {
+            rules: vec![
+                rule(Some("StoreFoo"), &[], &["/nonexistent"]),
+                rule(Some("Foo"), &[], &["/nonexistent"]),
+            ],
+        })
+        .await;
+        assert!(proxy.store_foo(3).await.is_err());
+        assert!(proxy.set_foo(4).await.is_err());
+        proxy.reset().await.expect("reset");
+        assert_eq!(proxy.foo().await.unwrap(), 2);
+
+        // The daemon itself isn't subject to the policy
+        let interface = connection
+            .object_server()
+            .interface::<_, Guarded<Test1>>("/com/steampowered/SteamOSManager1")
+            .await
+            .expect("interface");
+        interface.get_mut().await.store_foo(5).await;
+        assert_eq!(proxy.foo().await.unwrap(), 5);
+
+        configure_policy(PolicyConfig::default()).await;
+        let object_server = connection.object_server();
+        object_server
+            .remove::<Guarded<Test1>, _>("/com/steampowered/SteamOSManager1")
+            .await
+            .expect("remove");
+        object_server
+            .at(
+                "/com/steampowered/SteamOSManager1",
+                Guarded::restricted(Test1::default()),
+            )
+            .await
+            .expect("at");
+        assert!(proxy.store_foo(1).await.is_err());
+        assert!(proxy.set_foo(1).await.is_err());
+        assert!(proxy.reset().await.is_err());
+        // Reading properties is never restricted
+        assert_eq!(proxy.foo().await.unwrap(), 0);
+
+        configure_policy(PolicyConfig::default()).await;
+        connection.close().await.unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use std::collections::HashMap;
+use std::env::current_exe;
+use tokio::fs::{read, read_link, read_to_string};
+use zbus::message::Header;
+use zbus::{fdo, zvariant, Connection};
+
+use crate::caller::identify_caller;
+use crate::error::zbus_to_zbus_fdo;
+
+// Lets polkit ask the caller to authenticate instead of failing straight away
+const ALLOW_USER_INTERACTION: u32 = 1;
+
+pub(crate) const FACTORY_RESET_ACTION: &str = "com.steampowered.SteamOSManager1.factory-reset";
+pub(crate) const FORMAT_DEVICE_ACTION: &str = "com.steampowered.SteamOSManager1.format-device";
+pub(crate) const MANAGE_UNITS_ACTION: &str = "com.steampowered.SteamOSManager1.manage-units";
+pub(crate) const SET_DEVELOPER_MODE_ACTION: &str =
+    "com.steampowered.SteamOSManager1.set-developer-mode";
+pub(crate) const SET_FAN_CONTROL_STATE_ACTION: &str =
+    "com.steampowered.SteamOSManager1.set-fan-control-state";
+
+#[zbus::proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait PolicyKit1Authority {
+    async fn check_authorization(
+        &self,
+        subject: &(&str, HashMap<&str, zvariant::Value<'_>>),
+        action_id: &str,
+        details: &HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, String>)>;
+}
+
+type Subject<'a> = (&'a str, HashMap<&'a str, zvariant::Value<'a>>);
+
+async fn check_subject(
+    system: &Connection,
+    subject: &Subject<'_>,
+    caller: &str,
+    action: &str,
+) -> fdo::Result<()> {
+    let (authorized, _, _) = PolicyKit1AuthorityProxy::new(system)
+        .await
+        .map_err(zbus_to_zbus_fdo)?
+        .check_authorization(
+            &subject,
+            action,
+            &HashMap::new(),
+            ALLOW_USER_INTERACTION,
+            "",
+        )
+        .await
+        .map_err(zbus_to_zbus_fdo)?;
+    if !authorized {
+        return Err(fdo::Error::AccessDenied(format!(
+            "Caller {caller} is not authorized to perform {action}"
+        )));
+    }
+    Ok(())
+}
+
+/// Make sure the caller of a method is allowed to perform a polkit action
+pub(crate) async fn check_authorization(
+    connection: &Connection,
+    header: &Header<'_>,
+    action: &str,
+) -> fdo::Result<()> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+    let subject = (
+        "system-bus-name",
+        HashMap::from([("name", zvariant::Value::from(sender.as_str()))]),
+    );
+    check_subject(connection, &subject, sender.as_str(), action).await
+}
+
+/// Make sure the caller of a user daemon method is allowed to perform a polkit action, before the
+/// call is relayed to the root daemon. Without a header the call comes from within the daemon and
+/// is always allowed.
+pub(crate) async fn authorize_relayed_call(
+    session: &Connection,
+    system: &Connection,
+    header: Option<&Header<'_>>,
+    action: &str,
+) -> fdo::Result<()> {
+    let Some(header) = header else {
+        return Ok(());
+    };
+    let caller = identify_caller(session, header).await?;
+    // Callers on the session bus have no name on the system bus, so polkit is told about the
+    // process instead. Without a start time, polkit looks it up itself.
+    let subject = (
+        "unix-process",
+        HashMap::from([
+            ("pid", zvariant::Value::from(caller.pid)),
+            (
+                "start-time",
+                zvariant::Value::from(caller.start_time.unwrap_or_default()),
+            ),
+            ("uid", zvariant::Value::from(caller.uid as i32)),
+        ]),
+    );
+    check_subject(system, &subject, caller.sender.as_str(), action).await
+}
+
+/// Whether a process is running the same executable as this one
+async fn runs_own_exe(pid: u32) -> bool {
+    let Ok(exe) = read_link(format!("/proc/{pid}/exe")).await else {
+        return false;
+    };
+    current_exe().is_ok_and(|own| own == exe)
+}
+
+/// Whether a process was started with code of its user loaded through the dynamic loader, or is
+/// being traced by a debugger, either of which could make it call anything in its name
+fn runs_foreign_code(environ: &[u8], status: &str) -> bool {
+    if environ
+        .split(|byte| *byte == 0)
+        .any(|var| var.starts_with(b"LD_"))
+    {
+        return true;
+    }
+    status
+        .lines()
+        .filter_map(|line| line.strip_prefix("TracerPid:"))
+        .any(|tracer| tracer.trim() != "0")
+}
+
+/// Whether a process is an unaltered instance of this executable, i.e. the user daemon
+async fn is_steamos_manager(pid: u32) -> bool {
+    if !runs_own_exe(pid).await {
+        return false;
+    }
+    let (Ok(environ), Ok(status)) = (
+        read(format!("/proc/{pid}/environ")).await,
+        read_to_string(format!("/proc/{pid}/status")).await,
+    ) else {
+        return false;
+    };
+    !runs_foreign_code(environ.as_slice(), status.as_str())
+}
+
+/// Make sure the caller of a privileged method is allowed to perform a polkit action. Calls
+/// relayed by the user daemon are let through, since it checks polkit for its own callers
+/// before relaying them.
+pub(crate) async fn authorize_privileged_call(
+    connection: &Connection,
+    header: &Header<'_>,
+    action: &str,
+) -> fdo::Result<()> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::AccessDenied(String::from("Unknown caller")))?;
+    let pid = fdo::DBusProxy::new(connection)
+        .await
+        .map_err(zbus_to_zbus_fdo)?
+        .get_connection_unix_process_id(sender.clone().into())
+        .await?;
+    if is_steamos_manager(pid).await {
+        return Ok(());
+    }
+    check_authorization(connection, header, action).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::process::Command;
+
+    #[tokio::test]
+    async fn steamos_manager_process() {
+        assert!(runs_own_exe(std::process::id()).await);
+
+        let mut child = Command::new("/usr/bin/sleep").arg("1").spawn().unwrap();
+        assert!(!runs_own_exe(child.id().unwrap()).await);
+        assert!(!is_steamos_manager(child.id().unwrap()).await);
+        child.kill().await.unwrap();
+    }
+
+    #[test]
+    fn foreign_code() {
+        let status = "Name:\tsteamos-manager\nTracerPid:\t0\nUid:\t1000\n";
+        assert!(!runs_foreign_code(
+            b"HOME=/home/deck\0PATH=/usr/bin\0",
+            status
+        ));
+        assert!(runs_foreign_code(
+            b"HOME=/home/deck\0LD_PRELOAD=/tmp/evil.so\0",
+            status
+        ));
+        assert!(runs_foreign_code(b"LD_LIBRARY_PATH=/tmp\0", status));
+        assert!(runs_foreign_code(
+            b"HOME=/home/deck\0",
+            "Name:\tsteamos-manager\nTracerPid:\t4242\n"
+        ));
+    }
+}
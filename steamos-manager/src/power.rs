@@ -5,36 +5,47 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{anyhow, bail, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Error, Result};
 use async_trait::async_trait;
 use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::mem;
 use std::num::NonZeroU32;
 use std::ops::RangeInclusive;
 use std::os::fd::OwnedFd;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 use strum::{Display, EnumString, VariantNames};
 use tokio::fs::{self, try_exists, File};
-use tokio::io::{AsyncWriteExt, Interest};
+use tokio::io::AsyncWriteExt;
 use tokio::net::unix::pipe;
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
 use tokio::sync::{oneshot, Mutex, Notify, OnceCell};
 use tokio::task::JoinSet;
+use tokio::time::{sleep_until, Instant};
 use tracing::{debug, error, warn};
 use zbus::Connection;
 
+use crate::cache::SysfsLookupCache;
+use crate::daemon::user::{Command, UserCommand};
+use crate::daemon::DaemonCommand;
 use crate::gpu::AMDGPU_HWMON_NAME;
-use crate::hardware::device_config;
-use crate::manager::root::RootManagerProxy;
+use crate::hardware::{
+    device_config, BatteryChargeLimitConfig, ChargeBypassConfig, FirmwareAttributeLimits,
+};
+use crate::manager::root::{RootManagerProxy, SteamOSManager, SteamOSManagerSignals};
 use crate::manager::user::{TdpLimit1, MANAGER_PATH};
+use crate::policy::Guarded;
 use crate::Service;
-use crate::{path, write_synced};
+use crate::{path, wait_on_handle};
 
 #[cfg(not(test))]
-const HWMON_PREFIX: &str = "/sys/class/hwmon";
+pub(crate) const HWMON_PREFIX: &str = "/sys/class/hwmon";
 #[cfg(test)]
 pub const HWMON_PREFIX: &str = "hwmon";
 
@@ -43,6 +54,12 @@ const CPUFREQ_PREFIX: &str = "cpufreq";
 const CPUFREQ_BOOST_SUFFIX: &str = "boost";
 const INTEL_PSTATE_PREFIX: &str = "intel_pstate";
 const INTEL_PSTATE_NO_TURBO_SUFFIX: &str = "no_turbo";
+const CPU_SMT_CONTROL_SUFFIX: &str = "smt/control";
+const CPU_ONLINE_SUFFIX: &str = "online";
+const CPU_THREAD_SIBLINGS_SUFFIX: &str = "topology/thread_siblings_list";
+
+/// Never take the system below this many online CPUs, so it stays responsive enough to undo it
+pub(crate) const MIN_ONLINE_CPUS: u32 = 2;
 
 const CPU0_NAME: &str = "policy0";
 const CPU_POLICY_NAME: &str = "policy";
@@ -50,13 +67,28 @@ const CPU_POLICY_NAME: &str = "policy";
 const CPU_SCALING_GOVERNOR_SUFFIX: &str = "scaling_governor";
 const CPU_SCALING_AVAILABLE_GOVERNORS_SUFFIX: &str = "scaling_available_governors";
 
+const CPU_SCALING_MIN_FREQ_SUFFIX: &str = "scaling_min_freq";
+const CPU_SCALING_MAX_FREQ_SUFFIX: &str = "scaling_max_freq";
+const CPU_SCALING_CUR_FREQ_SUFFIX: &str = "scaling_cur_freq";
+const CPU_SCALING_AVAILABLE_FREQUENCIES_SUFFIX: &str = "scaling_available_frequencies";
+const CPU_EPP_SUFFIX: &str = "energy_performance_preference";
+const CPU_AVAILABLE_EPP_SUFFIX: &str = "energy_performance_available_preferences";
+const CPUINFO_MIN_FREQ_SUFFIX: &str = "cpuinfo_min_freq";
+const CPUINFO_MAX_FREQ_SUFFIX: &str = "cpuinfo_max_freq";
+
 const PLATFORM_PROFILE_PREFIX: &str = "/sys/class/platform-profile";
 
+const POWER_SUPPLY_PREFIX: &str = "/sys/class/power_supply";
+
 const TDP_LIMIT1: &str = "power1_cap";
 const TDP_LIMIT2: &str = "power2_cap";
 
 static SYSFS_WRITER: OnceCell<Arc<SysfsWriterQueue>> = OnceCell::const_new();
 
+// Directories found by find_sysdir(), keyed by the prefix and name they were looked up with
+static SYSDIRS: LazyLock<SysfsLookupCache<(PathBuf, String), PathBuf>> =
+    LazyLock::new(SysfsLookupCache::default);
+
 #[derive(Display, EnumString, Hash, Eq, PartialEq, Debug, Copy, Clone)]
 #[strum(serialize_all = "lowercase")]
 pub enum CPUScalingGovernor {
@@ -68,6 +100,17 @@ pub enum CPUScalingGovernor {
     SchedUtil,
 }
 
+/// Energy performance preference hint of the amd-pstate and intel_pstate drivers
+#[derive(Display, EnumString, Hash, Eq, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub enum EnergyPerformancePreference {
+    Default,
+    Performance,
+    BalancePerformance,
+    BalancePower,
+    Power,
+}
+
 #[derive(PartialEq, Debug, Copy, Clone)]
 enum CpuBoostDriver {
     IntelPstate,
@@ -94,6 +137,45 @@ pub enum CPUBoostState {
     Enabled = 1,
 }
 
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone, TryFromPrimitive)]
+#[repr(u32)]
+pub enum BatteryChargeState {
+    Unknown = 0,
+    Charging = 1,
+    Discharging = 2,
+    #[strum(to_string = "Not charging")]
+    NotCharging = 3,
+    Full = 4,
+}
+
+// These mirror the kernel's POWER_SUPPLY_HEALTH_* values and strings
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone, TryFromPrimitive)]
+#[repr(u32)]
+pub enum BatteryHealth {
+    Unknown = 0,
+    Good = 1,
+    Overheat = 2,
+    Dead = 3,
+    #[strum(to_string = "Over voltage")]
+    OverVoltage = 4,
+    #[strum(to_string = "Unspecified failure")]
+    UnspecifiedFailure = 5,
+    Cold = 6,
+    #[strum(to_string = "Watchdog timer expire")]
+    WatchdogTimerExpire = 7,
+    #[strum(to_string = "Safety timer expire")]
+    SafetyTimerExpire = 8,
+    #[strum(to_string = "Over current")]
+    OverCurrent = 9,
+    #[strum(to_string = "Calibration required")]
+    CalibrationRequired = 10,
+    Warm = 11,
+    Cool = 12,
+    Hot = 13,
+    #[strum(to_string = "No battery")]
+    NoBattery = 14,
+}
+
 #[derive(Display, EnumString, VariantNames, PartialEq, Debug, Clone)]
 #[strum(serialize_all = "snake_case")]
 pub enum TdpLimitingMethod {
@@ -101,6 +183,14 @@ pub enum TdpLimitingMethod {
     FirmwareAttribute,
 }
 
+#[derive(Display, EnumString, VariantNames, PartialEq, Debug, Clone, Default)]
+#[strum(serialize_all = "snake_case")]
+pub enum BatteryChargeLimitMethod {
+    #[default]
+    Hwmon,
+    PowerSupply,
+}
+
 #[derive(Debug)]
 pub(crate) struct AmdgpuHwmonTdpLimitManager {}
 
@@ -108,6 +198,16 @@ pub(crate) struct AmdgpuHwmonTdpLimitManager {}
 pub(crate) struct FirmwareAttributeLimitManager {
     attribute: String,
     performance_profile: Option<String>,
+    limits: FirmwareAttributeLimits,
+}
+
+/// Sustained (SPL), slow (SPPT) and fast (FPPT) package power limits, in watts. Limits the
+/// hardware doesn't have are reported as 0
+#[derive(Copy, Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+pub(crate) struct TdpLimits {
+    pub spl: u32,
+    pub sppt: u32,
+    pub fppt: u32,
 }
 
 #[async_trait]
@@ -118,6 +218,17 @@ pub(crate) trait TdpLimitManager: Send + Sync {
     async fn is_active(&self) -> Result<bool> {
         Ok(true)
     }
+
+    async fn get_tdp_limits(&self) -> Result<TdpLimits> {
+        bail!("Separate TDP limits are not supported");
+    }
+    async fn set_tdp_limits(&self, _limits: TdpLimits) -> Result<()> {
+        bail!("Separate TDP limits are not supported");
+    }
+    /// The lowest and highest values of each limit
+    async fn get_tdp_limits_range(&self) -> Result<(TdpLimits, TdpLimits)> {
+        bail!("Separate TDP limits are not supported");
+    }
 }
 
 pub(crate) async fn tdp_limit_manager() -> Result<Box<dyn TdpLimitManager>> {
@@ -135,6 +246,7 @@ pub(crate) async fn tdp_limit_manager() -> Result<Box<dyn TdpLimitManager>> {
             Box::new(FirmwareAttributeLimitManager {
                 attribute: firmware_attribute.attribute.clone(),
                 performance_profile: firmware_attribute.performance_profile.clone(),
+                limits: firmware_attribute.limits,
             })
         }
         TdpLimitingMethod::AmdgpuHwmon => Box::new(AmdgpuHwmonTdpLimitManager {}),
@@ -145,10 +257,19 @@ pub(crate) struct TdpManagerService {
     proxy: RootManagerProxy<'static>,
     session: Connection,
     channel: UnboundedReceiver<TdpManagerCommand>,
+    daemon: Sender<Command>,
     download_set: JoinSet<String>,
     download_handles: HashMap<String, u32>,
     download_mode_limit: Option<NonZeroU32>,
     previous_limit: Option<NonZeroU32>,
+    profiles: HashMap<u32, u32>,
+    foreground_app: u32,
+    unprofiled_limit: Option<NonZeroU32>,
+    /// Limit set by the user while an app profile was in effect, which takes precedence over
+    /// profiles until no profiled app is in the foreground anymore
+    manual_limit: Option<NonZeroU32>,
+    battery_limit: Option<NonZeroU32>,
+    requested_limit: Option<u32>,
     manager: Box<dyn TdpLimitManager>,
 }
 
@@ -156,10 +277,98 @@ pub(crate) enum TdpManagerCommand {
     SetTdpLimit(u32),
     GetTdpLimit(oneshot::Sender<Result<u32>>),
     GetTdpLimitRange(oneshot::Sender<Result<RangeInclusive<u32>>>),
+    SetTdpLimits(TdpLimits, oneshot::Sender<Result<()>>),
+    GetTdpLimits(oneshot::Sender<Result<TdpLimits>>),
+    GetTdpLimitsRange(oneshot::Sender<Result<(TdpLimits, TdpLimits)>>),
     IsActive(oneshot::Sender<Result<bool>>),
     UpdateDownloadMode,
     EnterDownloadMode(String, oneshot::Sender<Result<Option<OwnedFd>>>),
     ListDownloadModeHandles(oneshot::Sender<HashMap<String, u32>>),
+    LoadProfiles(HashMap<u32, u32>),
+    SetProfileForApp(u32, u32, oneshot::Sender<Result<()>>),
+    ClearProfileForApp(u32),
+    ListProfiles(oneshot::Sender<HashMap<u32, u32>>),
+    SetForegroundApp(u32),
+    SetBatteryLimit(Option<u32>),
+}
+
+/// Names of the presets that are available even if the user hasn't saved any
+const BUILTIN_POWER_PRESETS: [&str; 3] = ["quiet", "balanced", "performance"];
+
+/// A set of power settings that are applied together. Settings that are `None` are left as is.
+#[derive(Clone, Default, Deserialize, Serialize, PartialEq, Debug)]
+#[serde(default)]
+pub(crate) struct PowerPreset {
+    pub performance_profile: Option<String>,
+    pub cpu_scaling_governor: Option<String>,
+    pub gpu_performance_level: Option<String>,
+    pub tdp_limit: Option<u32>,
+    pub refresh_rate_cap: Option<u32>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize, Debug)]
+#[serde(default)]
+pub(crate) struct PowerPresetsState {
+    /// Name of the preset that was most recently applied or saved
+    pub current: String,
+    /// Presets saved by the user, which take precedence over built-in ones of the same name
+    pub presets: HashMap<String, PowerPreset>,
+}
+
+impl PowerPreset {
+    fn builtin(name: &str) -> Option<PowerPreset> {
+        let (profile, governor, level) = match name {
+            "quiet" => ("low-power", CPUScalingGovernor::PowerSave, "low"),
+            "balanced" => ("balanced", CPUScalingGovernor::SchedUtil, "auto"),
+            "performance" => ("performance", CPUScalingGovernor::Performance, "high"),
+            _ => return None,
+        };
+        Some(PowerPreset {
+            performance_profile: Some(profile.to_string()),
+            cpu_scaling_governor: Some(governor.to_string()),
+            gpu_performance_level: Some(level.to_string()),
+            tdp_limit: None,
+            refresh_rate_cap: None,
+        })
+    }
+}
+
+impl PowerPresetsState {
+    pub(crate) fn preset(&self, name: &str) -> Option<PowerPreset> {
+        self.presets
+            .get(name)
+            .cloned()
+            .or_else(|| PowerPreset::builtin(name))
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_POWER_PRESETS
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.presets.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct SysfsWriterConfig {
+    /// Time in milliseconds to wait for further writes to the same file before writing it
+    pub coalesce_window: u64,
+    /// Maximum number of writes per second across all files, or 0 to not limit the rate
+    pub max_write_rate: u32,
+}
+
+impl Default for SysfsWriterConfig {
+    fn default() -> SysfsWriterConfig {
+        SysfsWriterConfig {
+            coalesce_window: 50,
+            max_write_rate: 20,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -168,42 +377,86 @@ pub(crate) enum SysfsWritten {
     Superseded,
 }
 
-type SysfsQueue = (Vec<u8>, oneshot::Sender<SysfsWritten>);
-type SysfsQueueMap = HashMap<PathBuf, SysfsQueue>;
+#[derive(Debug)]
+struct SysfsWrite {
+    contents: Vec<Vec<u8>>,
+    due: Instant,
+    tx: oneshot::Sender<SysfsWritten>,
+}
+
+type SysfsQueueMap = HashMap<PathBuf, SysfsWrite>;
 
 #[derive(Debug)]
 struct SysfsWriterQueue {
     values: Mutex<SysfsQueueMap>,
+    config: Mutex<SysfsWriterConfig>,
     notify: Notify,
+    writing: AtomicBool,
 }
 
 impl SysfsWriterQueue {
-    fn new() -> SysfsWriterQueue {
+    fn new(config: SysfsWriterConfig) -> SysfsWriterQueue {
         SysfsWriterQueue {
             values: Mutex::new(HashMap::new()),
+            config: Mutex::new(config),
             notify: Notify::new(),
+            writing: AtomicBool::new(false),
         }
     }
 
-    async fn send(&self, path: PathBuf, contents: Vec<u8>) -> oneshot::Receiver<SysfsWritten> {
+    async fn send(&self, path: PathBuf, contents: Vec<Vec<u8>>) -> oneshot::Receiver<SysfsWritten> {
         let (tx, rx) = oneshot::channel();
-        if let Some((_, old_tx)) = self.values.lock().await.insert(path, (contents, tx)) {
-            let _ = old_tx.send(SysfsWritten::Superseded);
+        let window = Duration::from_millis(self.config.lock().await.coalesce_window);
+        let mut values = self.values.lock().await;
+        // Only hold a write back for coalescing while the writer is busy; a lone write to an
+        // idle writer has nothing to be coalesced with
+        let idle = values.is_empty() && !self.writing.load(Ordering::Acquire);
+        match values.entry(path) {
+            Entry::Occupied(mut entry) => {
+                // Keep the original due time so that a steady stream of writes to the same
+                // file can't postpone it forever
+                let write = entry.get_mut();
+                write.contents = contents;
+                let old_tx = mem::replace(&mut write.tx, tx);
+                let _ = old_tx.send(SysfsWritten::Superseded);
+            }
+            Entry::Vacant(entry) => {
+                let now = Instant::now();
+                entry.insert(SysfsWrite {
+                    contents,
+                    due: if idle { now } else { now + window },
+                    tx,
+                });
+            }
         }
+        drop(values);
         self.notify.notify_one();
         rx
     }
 
-    async fn recv(&self) -> Option<(PathBuf, Vec<u8>, oneshot::Sender<SysfsWritten>)> {
-        // Take an arbitrary file from the map
-        self.notify.notified().await;
-        let mut values = self.values.lock().await;
-        if let Some(path) = values.keys().next().cloned() {
-            values
-                .remove_entry(&path)
-                .map(|(path, (contents, tx))| (path, contents, tx))
-        } else {
-            None
+    async fn recv(&self) -> (PathBuf, SysfsWrite) {
+        loop {
+            let mut values = self.values.lock().await;
+            let next = values
+                .iter()
+                .min_by_key(|(_, write)| write.due)
+                .map(|(path, write)| (path.clone(), write.due));
+            match next {
+                None => {
+                    drop(values);
+                    self.notify.notified().await;
+                }
+                Some((_, deadline)) if deadline > Instant::now() => {
+                    drop(values);
+                    sleep_until(deadline).await;
+                }
+                Some((path, _)) => {
+                    if let Some(entry) = values.remove_entry(&path) {
+                        self.writing.store(true, Ordering::Release);
+                        return entry;
+                    }
+                }
+            }
         }
     }
 }
@@ -211,14 +464,28 @@ impl SysfsWriterQueue {
 #[derive(Debug)]
 pub(crate) struct SysfsWriterService {
     queue: Arc<SysfsWriterQueue>,
+    connection: Connection,
 }
 
 impl SysfsWriterService {
-    pub fn init() -> Result<SysfsWriterService> {
+    pub fn init(connection: Connection, config: SysfsWriterConfig) -> Result<SysfsWriterService> {
         ensure!(!SYSFS_WRITER.initialized(), "sysfs writer already active");
-        let queue = Arc::new(SysfsWriterQueue::new());
+        let queue = Arc::new(SysfsWriterQueue::new(config));
         SYSFS_WRITER.set(queue.clone())?;
-        Ok(SysfsWriterService { queue })
+        Ok(SysfsWriterService { queue, connection })
+    }
+
+    async fn report_failure(&self, path: &Path, error: &Error) -> Result<()> {
+        let interface = self
+            .connection
+            .object_server()
+            .interface::<_, Guarded<SteamOSManager>>(MANAGER_PATH)
+            .await?;
+        interface
+            .signal_emitter()
+            .sysfs_write_failed(&path.to_string_lossy(), &error.to_string())
+            .await?;
+        Ok(())
     }
 }
 
@@ -226,18 +493,75 @@ impl Service for SysfsWriterService {
     const NAME: &'static str = "sysfs-writer";
 
     async fn run(&mut self) -> Result<()> {
+        let mut last_write: Option<Instant> = None;
         loop {
-            let Some((path, contents, tx)) = self.queue.recv().await else {
-                continue;
-            };
-            let res = write_synced(path, &contents)
+            // Wait out the rate limit before picking a file, so that writes queued in the
+            // meantime can still be coalesced
+            let rate = self.queue.config.lock().await.max_write_rate;
+            if let (Some(last_write), Some(rate)) = (last_write, NonZeroU32::new(rate)) {
+                sleep_until(last_write + Duration::from_secs(1) / rate.get()).await;
+            }
+
+            let (path, write) = self.queue.recv().await;
+            last_write = Some(Instant::now());
+            let res = write_sysfs_now(&path, &write.contents)
                 .await
-                .inspect_err(|message| error!("Error writing to sysfs file: {message}"));
-            let _ = tx.send(SysfsWritten::Written(res));
+                .inspect_err(|message| {
+                    error!("Error writing to sysfs file {}: {message}", path.display());
+                });
+            if let Err(ref e) = res {
+                if let Err(e) = self.report_failure(&path, e).await {
+                    warn!("Failed to report sysfs write failure: {e}");
+                }
+            }
+            self.queue.writing.store(false, Ordering::Release);
+            let _ = write.tx.send(SysfsWritten::Written(res));
         }
     }
 }
 
+pub(crate) async fn configure_sysfs_writer(config: SysfsWriterConfig) {
+    if let Some(queue) = SYSFS_WRITER.get() {
+        *queue.config.lock().await = config;
+    }
+}
+
+async fn write_sysfs_now(path: &Path, contents: &[Vec<u8>]) -> Result<()> {
+    let mut file = File::create(path).await?;
+    for chunk in contents {
+        file.write_all(chunk).await?;
+        file.flush().await?;
+    }
+    Ok(file.sync_data().await?)
+}
+
+/// Write a sequence of chunks to a sysfs file, each as a separate write to the same open file.
+/// Use this for files that take a series of commands that must be applied together.
+pub(crate) async fn write_sysfs_sequence(
+    path: impl Into<PathBuf>,
+    contents: Vec<Vec<u8>>,
+) -> Result<()> {
+    let path = path.into();
+    let Some(queue) = SYSFS_WRITER.get() else {
+        // Only the root daemon runs the writer
+        return write_sysfs_now(&path, &contents).await;
+    };
+    match queue.send(path.clone(), contents).await.await? {
+        SysfsWritten::Written(res) => res,
+        SysfsWritten::Superseded => Err(anyhow!(
+            "Write to sysfs file {} was superseded by a newer value",
+            path.display()
+        )),
+    }
+}
+
+pub(crate) async fn write_sysfs(
+    path: impl Into<PathBuf>,
+    contents: impl Into<Vec<u8>>,
+) -> Result<()> {
+    write_sysfs_sequence(path, vec![contents.into()]).await
+}
+
 async fn read_cpu_sysfs_contents<S: AsRef<Path>>(suffix: S) -> Result<String> {
     let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX).join(CPU0_NAME);
     fs::read_to_string(base.join(suffix.as_ref()))
@@ -245,7 +569,10 @@ async fn read_cpu_sysfs_contents<S: AsRef<Path>>(suffix: S) -> Result<String> {
         .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))
 }
 
-async fn write_cpu_governor_sysfs_contents(contents: String) -> Result<()> {
+async fn write_cpu_policy_sysfs_contents<S: AsRef<Path>>(
+    suffix: S,
+    contents: String,
+) -> Result<()> {
     // Iterate over all policyX paths
     let mut dir = fs::read_dir(path(CPU_PREFIX).join(CPUFREQ_PREFIX)).await?;
     let mut wrote_stuff = false;
@@ -267,7 +594,7 @@ async fn write_cpu_governor_sysfs_contents(contents: String) -> Result<()> {
         let base = entry.path();
         // Write contents to each one
         wrote_stuff = true;
-        write_synced(base.join(CPU_SCALING_GOVERNOR_SUFFIX), contents.as_bytes())
+        write_sysfs(base.join(suffix.as_ref()), contents.as_bytes())
             .await
             .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
     }
@@ -304,7 +631,149 @@ pub(crate) async fn get_cpu_scaling_governor() -> Result<CPUScalingGovernor> {
 pub(crate) async fn set_cpu_scaling_governor(governor: CPUScalingGovernor) -> Result<()> {
     // Set the given governor on all cpus
     let name = governor.to_string();
-    write_cpu_governor_sysfs_contents(name).await
+    write_cpu_policy_sysfs_contents(CPU_SCALING_GOVERNOR_SUFFIX, name).await
+}
+
+pub(crate) async fn get_cpu_scaling_governors() -> Result<HashMap<u32, CPUScalingGovernor>> {
+    // Get the current governor of each policyX path
+    let mut dir = fs::read_dir(path(CPU_PREFIX).join(CPUFREQ_PREFIX)).await?;
+    let mut result = HashMap::new();
+    while let Some(entry) = dir.next_entry().await? {
+        let file_name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| anyhow!("Unable to convert path to string"))?;
+        let Some(Ok(policy)) = file_name
+            .strip_prefix(CPU_POLICY_NAME)
+            .map(str::parse::<u32>)
+        else {
+            continue;
+        };
+        let contents = fs::read_to_string(entry.path().join(CPU_SCALING_GOVERNOR_SUFFIX))
+            .await
+            .map_err(|message| anyhow!("Error opening sysfs file for reading {message}"))?;
+        match CPUScalingGovernor::from_str(contents.trim()) {
+            Ok(governor) => {
+                result.insert(policy, governor);
+            }
+            Err(message) => warn!("Error parsing governor for {file_name}: {message}"),
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) async fn set_cpu_scaling_governor_for_policy(
+    policy: u32,
+    governor: CPUScalingGovernor,
+) -> Result<()> {
+    let base = path(CPU_PREFIX)
+        .join(CPUFREQ_PREFIX)
+        .join(format!("{CPU_POLICY_NAME}{policy}"));
+    ensure!(try_exists(&base).await?, "Invalid CPU policy {policy}");
+    write_sysfs(base.join(CPU_SCALING_GOVERNOR_SUFFIX), governor.to_string())
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+pub(crate) async fn get_available_energy_performance_preferences(
+) -> Result<Vec<EnergyPerformancePreference>> {
+    let contents = read_cpu_sysfs_contents(CPU_AVAILABLE_EPP_SUFFIX).await?;
+    let mut result = Vec::new();
+    for word in contents.split_whitespace() {
+        match EnergyPerformancePreference::from_str(word) {
+            Ok(preference) => result.push(preference),
+            Err(message) => warn!("Error parsing energy performance preference {word}: {message}"),
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) async fn get_energy_performance_preference() -> Result<EnergyPerformancePreference> {
+    // Like the governor, assume all policies have the same preference as policy0
+    let contents = read_cpu_sysfs_contents(CPU_EPP_SUFFIX).await?;
+    EnergyPerformancePreference::from_str(contents.trim())
+        .map_err(|message| anyhow!("Error parsing energy performance preference: {message}"))
+}
+
+pub(crate) async fn set_energy_performance_preference(
+    preference: EnergyPerformancePreference,
+) -> Result<()> {
+    ensure!(
+        get_available_energy_performance_preferences()
+            .await?
+            .contains(&preference),
+        "Energy performance preference {preference} not available"
+    );
+    write_cpu_policy_sysfs_contents(CPU_EPP_SUFFIX, preference.to_string()).await
+}
+
+async fn read_cpu_frequency<S: AsRef<Path>>(suffix: S) -> Result<u32> {
+    // cpufreq reports frequencies in kHz, but we expose them in MHz
+    let khz: u32 = read_cpu_sysfs_contents(suffix)
+        .await?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    Ok(khz / 1000)
+}
+
+pub(crate) async fn get_cpu_frequency_range() -> Result<RangeInclusive<u32>> {
+    let min = read_cpu_frequency(CPUINFO_MIN_FREQ_SUFFIX).await?;
+    let max = read_cpu_frequency(CPUINFO_MAX_FREQ_SUFFIX).await?;
+    Ok(min..=max)
+}
+
+pub(crate) async fn get_available_cpu_frequencies() -> Result<Vec<u32>> {
+    // Not all cpufreq drivers provide a list of frequencies, e.g. amd-pstate
+    let Ok(contents) = read_cpu_sysfs_contents(CPU_SCALING_AVAILABLE_FREQUENCIES_SUFFIX).await
+    else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    for word in contents.split_whitespace() {
+        match word.parse::<u32>() {
+            Ok(khz) => result.push(khz / 1000),
+            Err(message) => warn!("Error parsing frequency {word}: {message}"),
+        }
+    }
+    result.sort_unstable();
+    Ok(result)
+}
+
+pub(crate) async fn get_min_cpu_frequency() -> Result<u32> {
+    read_cpu_frequency(CPU_SCALING_MIN_FREQ_SUFFIX).await
+}
+
+pub(crate) async fn get_max_cpu_frequency() -> Result<u32> {
+    read_cpu_frequency(CPU_SCALING_MAX_FREQ_SUFFIX).await
+}
+
+pub(crate) async fn get_cpu_frequency() -> Result<u32> {
+    read_cpu_frequency(CPU_SCALING_CUR_FREQ_SUFFIX).await
+}
+
+pub(crate) async fn set_min_cpu_frequency(mhz: u32) -> Result<()> {
+    ensure!(
+        get_cpu_frequency_range().await?.contains(&mhz),
+        "Invalid frequency"
+    );
+    ensure!(
+        mhz <= get_max_cpu_frequency().await?,
+        "Minimum frequency must not exceed maximum frequency"
+    );
+    write_cpu_policy_sysfs_contents(CPU_SCALING_MIN_FREQ_SUFFIX, format!("{mhz}000")).await
+}
+
+pub(crate) async fn set_max_cpu_frequency(mhz: u32) -> Result<()> {
+    ensure!(
+        get_cpu_frequency_range().await?.contains(&mhz),
+        "Invalid frequency"
+    );
+    ensure!(
+        mhz >= get_min_cpu_frequency().await?,
+        "Maximum frequency must not be below minimum frequency"
+    );
+    write_cpu_policy_sysfs_contents(CPU_SCALING_MAX_FREQ_SUFFIX, format!("{mhz}000")).await
 }
 
 async fn find_cpu_boost_driver() -> Result<(PathBuf, CpuBoostDriver)> {
@@ -358,27 +827,196 @@ pub(crate) async fn set_cpu_boost_state(state: CPUBoostState) -> Result<()> {
         (CpuBoostDriver::IntelPstate, CPUBoostState::Enabled) => "0",
         (CpuBoostDriver::IntelPstate, CPUBoostState::Disabled) => "1",
     };
-    write_synced(path, contents.as_bytes())
+    write_sysfs(path, contents)
         .await
         .inspect_err(|message| error!("Error writing to CPU boost sysfs file: {message}"))
 }
 
-async fn find_sysdir(prefix: impl AsRef<Path>, expected: &str) -> Result<PathBuf> {
-    let mut dir = fs::read_dir(prefix.as_ref()).await?;
+pub(crate) async fn get_smt_enabled() -> Result<bool> {
+    let contents = fs::read_to_string(path(CPU_PREFIX).join(CPU_SMT_CONTROL_SUFFIX))
+        .await
+        .map_err(|message| {
+            anyhow!("Error opening SMT control sysfs file for reading: {message}")
+        })?;
+    match contents.trim() {
+        "on" => Ok(true),
+        // forceoff means SMT was disabled on the kernel command line and can't be turned back on
+        "off" | "forceoff" => Ok(false),
+        "notsupported" | "notimplemented" => bail!("SMT is not supported on this system"),
+        _ => Err(anyhow!("Invalid SMT control state: {contents}")),
+    }
+}
+
+pub(crate) async fn set_smt_enabled(enabled: bool) -> Result<()> {
+    // Make sure SMT can be controlled at all before writing anything
+    get_smt_enabled().await?;
+    let contents = if enabled { "on" } else { "off" };
+    write_sysfs(path(CPU_PREFIX).join(CPU_SMT_CONTROL_SUFFIX), contents)
+        .await
+        .inspect_err(|message| error!("Error writing to SMT control sysfs file: {message}"))
+}
+
+/// List the logical CPUs as their `online` files, in order. CPUs that can't be hotplugged, usually
+/// just cpu0, have no `online` file and are always online.
+async fn list_cpus() -> Result<Vec<(u32, Option<PathBuf>)>> {
+    let mut cpus = Vec::new();
+    let mut dir = fs::read_dir(path(CPU_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Some(index) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("cpu"))
+            .and_then(|index| index.parse().ok())
+        else {
+            continue;
+        };
+        let online = entry.path().join(CPU_ONLINE_SUFFIX);
+        let online = try_exists(&online).await?.then_some(online);
+        cpus.push((index, online));
+    }
+    ensure!(!cpus.is_empty(), "No CPUs found");
+    cpus.sort_by_key(|(index, _)| *index);
+    Ok(cpus)
+}
+
+async fn is_cpu_online(online: Option<&PathBuf>) -> Result<bool> {
+    let Some(online) = online else {
+        return Ok(true);
+    };
+    match fs::read_to_string(online).await?.trim() {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        contents => bail!("Invalid CPU online state: {contents}"),
+    }
+}
+
+/// Whether a CPU is the second thread of a core, which the kernel refuses to bring online while
+/// SMT is disabled
+async fn is_smt_sibling(index: u32) -> bool {
+    let siblings = path(CPU_PREFIX)
+        .join(format!("cpu{index}"))
+        .join(CPU_THREAD_SIBLINGS_SUFFIX);
+    let Ok(siblings) = fs::read_to_string(siblings).await else {
+        return false;
+    };
+    // The list is sorted, e.g. "0-1" or "2,10", and the core's first thread comes first
+    siblings
+        .trim()
+        .split([',', '-'])
+        .next()
+        .and_then(|first| first.parse::<u32>().ok())
+        .is_some_and(|first| first != index)
+}
+
+/// List the CPUs that can be online, leaving out SMT siblings while SMT is disabled
+async fn list_available_cpus() -> Result<Vec<(u32, Option<PathBuf>)>> {
+    let cpus = list_cpus().await?;
+    if get_smt_enabled().await.unwrap_or(true) {
+        return Ok(cpus);
+    }
+    let mut available = Vec::new();
+    for (index, online) in cpus {
+        if !is_smt_sibling(index).await {
+            available.push((index, online));
+        }
+    }
+    Ok(available)
+}
+
+pub(crate) async fn get_max_online_cpu_count() -> Result<u32> {
+    Ok(list_available_cpus().await?.len().try_into()?)
+}
+
+pub(crate) async fn get_min_online_cpu_count() -> Result<u32> {
+    let cpus = list_available_cpus().await?;
+    let fixed = cpus.iter().filter(|(_, online)| online.is_none()).count();
+    Ok(MIN_ONLINE_CPUS
+        .max(fixed.try_into()?)
+        .min(cpus.len().try_into()?))
+}
+
+pub(crate) async fn get_online_cpu_count() -> Result<u32> {
+    let mut count = 0;
+    for (_, online) in list_cpus().await? {
+        if is_cpu_online(online.as_ref()).await? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+pub(crate) async fn set_online_cpu_count(count: u32) -> Result<()> {
+    let min = get_min_online_cpu_count().await?;
+    let max = get_max_online_cpu_count().await?;
+    ensure!(
+        (min..=max).contains(&count),
+        "Online CPU count {count} out of range {min}-{max}"
+    );
+
+    // CPUs that are always online count first, then the lowest numbered ones are kept online
+    let hotpluggable: Vec<(u32, PathBuf)> = list_available_cpus()
+        .await?
+        .into_iter()
+        .filter_map(|(index, online)| Some((index, online?)))
+        .collect();
+    let keep = hotpluggable.len() - (max - count) as usize;
+    // Keep going past CPUs that fail so the rest still end up as requested
+    let mut failed = Vec::new();
+    for (position, (index, online)) in hotpluggable.into_iter().enumerate() {
+        let wanted = position < keep;
+        let result = match is_cpu_online(Some(&online)).await {
+            Ok(state) if state == wanted => Ok(()),
+            Ok(_) => write_sysfs(online, if wanted { "1" } else { "0" }).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Error setting cpu{index} online state: {e}");
+            failed.push(format!("cpu{index}"));
+        }
+    }
+    ensure!(
+        failed.is_empty(),
+        "Couldn't change the online state of {}",
+        failed.join(", ")
+    );
+    Ok(())
+}
+
+async fn read_sysdir_name(base: &Path) -> Result<String> {
+    Ok(fs::read_to_string(base.join("name"))
+        .await?
+        .trim()
+        .to_string())
+}
+
+async fn scan_sysdir(prefix: &Path, expected: &str) -> Result<PathBuf> {
+    let mut dir = fs::read_dir(prefix).await?;
     loop {
         let base = match dir.next_entry().await? {
             Some(entry) => entry.path(),
             None => bail!("prefix not found"),
         };
-        let file_name = base.join("name");
-        let name = fs::read_to_string(file_name.as_path())
-            .await?
-            .trim()
-            .to_string();
-        if name == expected {
+        if read_sysdir_name(&base).await? == expected {
+            return Ok(base);
+        }
+    }
+}
+
+async fn find_sysdir(prefix: impl AsRef<Path>, expected: &str) -> Result<PathBuf> {
+    let key = (prefix.as_ref().to_path_buf(), expected.to_string());
+    if let Some(base) = SYSDIRS.get(&key) {
+        // Devices can be renumbered without a udev event having reached us yet, so make sure the
+        // cached directory is still the one we're after before skipping the scan
+        if read_sysdir_name(&base)
+            .await
+            .is_ok_and(|name| name == expected)
+        {
             return Ok(base);
         }
     }
+    let base = scan_sysdir(&key.0, expected).await?;
+    SYSDIRS.insert(key, base.clone());
+    Ok(base)
 }
 
 pub(crate) async fn find_hwmon(hwmon: &str) -> Result<PathBuf> {
@@ -407,18 +1045,18 @@ impl TdpLimitManager for AmdgpuHwmonTdpLimitManager {
         let data = format!("{limit}000000");
 
         let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
-        write_synced(base.join(TDP_LIMIT1), data.as_bytes())
+        write_sysfs(base.join(TDP_LIMIT1), data.as_bytes())
             .await
             .inspect_err(|message| {
                 error!("Error opening sysfs power1_cap file for writing TDP limits {message}");
             })?;
 
-        if let Ok(mut power2file) = File::create(base.join(TDP_LIMIT2)).await {
-            power2file
-                .write(data.as_bytes())
+        // power2_cap isn't available on all devices
+        let power2 = base.join(TDP_LIMIT2);
+        if fs::metadata(&power2).await.is_ok_and(|meta| meta.is_file()) {
+            write_sysfs(power2, data)
                 .await
                 .inspect_err(|message| error!("Error writing to power2_cap file: {message}"))?;
-            power2file.flush().await?;
         }
         Ok(())
     }
@@ -442,15 +1080,16 @@ impl FirmwareAttributeLimitManager {
     const SPL_SUFFIX: &str = "ppt_pl1_spl";
     const SPPT_SUFFIX: &str = "ppt_pl2_sppt";
     const FPPT_SUFFIX: &str = "ppt_pl3_fppt";
-}
 
-#[async_trait]
-impl TdpLimitManager for FirmwareAttributeLimitManager {
-    async fn get_tdp_limit(&self) -> Result<u32> {
-        ensure!(self.is_active().await?, "TDP limiting not active");
-        let base = path(Self::PREFIX).join(&self.attribute).join("attributes");
+    fn limit_base(&self, suffix: &str) -> PathBuf {
+        path(Self::PREFIX)
+            .join(&self.attribute)
+            .join("attributes")
+            .join(suffix)
+    }
 
-        fs::read_to_string(base.join(Self::SPL_SUFFIX).join("current_value"))
+    async fn read_limit_attribute(&self, suffix: &str, attribute: &str) -> Result<u32> {
+        fs::read_to_string(self.limit_base(suffix).join(attribute))
             .await
             .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
             .trim()
@@ -458,6 +1097,38 @@ impl TdpLimitManager for FirmwareAttributeLimitManager {
             .map_err(|e| anyhow!("Error parsing value: {e}"))
     }
 
+    async fn read_limit_range(&self, suffix: &str) -> Result<RangeInclusive<u32>> {
+        let min = self.read_limit_attribute(suffix, "min_value").await?;
+        let max = self.read_limit_attribute(suffix, "max_value").await?;
+        Ok(min..=max)
+    }
+
+    async fn write_limit(&self, suffix: &str, limit: u32) -> Result<()> {
+        write_sysfs(
+            self.limit_base(suffix).join("current_value"),
+            limit.to_string().as_bytes(),
+        )
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+    }
+
+    /// The attributes of the slow and fast limits, if the firmware has them
+    fn extra_limits(&self) -> [Option<&'static str>; 2] {
+        [
+            self.limits.sppt.then_some(Self::SPPT_SUFFIX),
+            self.limits.fppt.then_some(Self::FPPT_SUFFIX),
+        ]
+    }
+}
+
+#[async_trait]
+impl TdpLimitManager for FirmwareAttributeLimitManager {
+    async fn get_tdp_limit(&self) -> Result<u32> {
+        ensure!(self.is_active().await?, "TDP limiting not active");
+        self.read_limit_attribute(Self::SPL_SUFFIX, "current_value")
+            .await
+    }
+
     async fn set_tdp_limit(&self, limit: u32) -> Result<()> {
         ensure!(self.is_active().await?, "TDP limiting not active");
         ensure!(
@@ -465,47 +1136,15 @@ impl TdpLimitManager for FirmwareAttributeLimitManager {
             "Invalid limit"
         );
 
-        let limit = limit.to_string();
-        let base = path(Self::PREFIX).join(&self.attribute).join("attributes");
-        write_synced(
-            base.join(Self::SPL_SUFFIX).join("current_value"),
-            limit.as_bytes(),
-        )
-        .await
-        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
-        write_synced(
-            base.join(Self::SPPT_SUFFIX).join("current_value"),
-            limit.as_bytes(),
-        )
-        .await
-        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
-        write_synced(
-            base.join(Self::FPPT_SUFFIX).join("current_value"),
-            limit.as_bytes(),
-        )
-        .await
-        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+        self.write_limit(Self::SPL_SUFFIX, limit).await?;
+        for suffix in self.extra_limits().into_iter().flatten() {
+            self.write_limit(suffix, limit).await?;
+        }
+        Ok(())
     }
 
     async fn get_tdp_limit_range(&self) -> Result<RangeInclusive<u32>> {
-        let base = path(Self::PREFIX)
-            .join(&self.attribute)
-            .join("attributes")
-            .join(Self::SPL_SUFFIX);
-
-        let min: u32 = fs::read_to_string(base.join("min_value"))
-            .await
-            .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
-            .trim()
-            .parse()
-            .map_err(|e| anyhow!("Error parsing value: {e}"))?;
-        let max: u32 = fs::read_to_string(base.join("max_value"))
-            .await
-            .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
-            .trim()
-            .parse()
-            .map_err(|e| anyhow!("Error parsing value: {e}"))?;
-        Ok(min..=max)
+        self.read_limit_range(Self::SPL_SUFFIX).await
     }
 
     async fn is_active(&self) -> Result<bool> {
@@ -522,44 +1161,371 @@ impl TdpLimitManager for FirmwareAttributeLimitManager {
             Ok(true)
         }
     }
-}
-
-pub(crate) async fn get_max_charge_level() -> Result<i32> {
-    let config = device_config().await?;
-    let config = config
-        .as_ref()
-        .and_then(|config| config.battery_charge_limit.as_ref())
-        .ok_or(anyhow!("No battery charge limit configured"))?;
-    let base = find_hwmon(config.hwmon_name.as_str()).await?;
 
-    fs::read_to_string(base.join(config.attribute.as_str()))
-        .await
-        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
-        .trim()
-        .parse()
-        .map_err(|e| anyhow!("Error parsing value: {e}"))
-}
+    async fn get_tdp_limits(&self) -> Result<TdpLimits> {
+        ensure!(self.is_active().await?, "TDP limiting not active");
+        let [sppt, fppt] = self.extra_limits();
+        let mut limits = TdpLimits {
+            spl: self.get_tdp_limit().await?,
+            ..TdpLimits::default()
+        };
+        if let Some(suffix) = sppt {
+            limits.sppt = self.read_limit_attribute(suffix, "current_value").await?;
+        }
+        if let Some(suffix) = fppt {
+            limits.fppt = self.read_limit_attribute(suffix, "current_value").await?;
+        }
+        Ok(limits)
+    }
 
-pub(crate) async fn set_max_charge_level(limit: i32) -> Result<oneshot::Receiver<SysfsWritten>> {
-    ensure!((0..=100).contains(&limit), "Invalid limit");
-    let data = limit.to_string();
+    async fn set_tdp_limits(&self, limits: TdpLimits) -> Result<()> {
+        ensure!(self.is_active().await?, "TDP limiting not active");
+        let [sppt, fppt] = self.extra_limits();
+        let writes: Vec<(&str, u32)> = [
+            Some((Self::SPL_SUFFIX, limits.spl)),
+            sppt.map(|suffix| (suffix, limits.sppt)),
+            fppt.map(|suffix| (suffix, limits.fppt)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        // Check every limit before writing any, so a bad one doesn't leave them half applied
+        for (suffix, limit) in writes.iter() {
+            ensure!(
+                self.read_limit_range(suffix).await?.contains(limit),
+                "Invalid limit {limit} for {suffix}"
+            );
+        }
+        for (suffix, limit) in writes {
+            self.write_limit(suffix, limit).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_tdp_limits_range(&self) -> Result<(TdpLimits, TdpLimits)> {
+        let [sppt, fppt] = self.extra_limits();
+        let spl = self.read_limit_range(Self::SPL_SUFFIX).await?;
+        let mut min = TdpLimits {
+            spl: *spl.start(),
+            ..TdpLimits::default()
+        };
+        let mut max = TdpLimits {
+            spl: *spl.end(),
+            ..TdpLimits::default()
+        };
+        if let Some(suffix) = sppt {
+            let range = self.read_limit_range(suffix).await?;
+            min.sppt = *range.start();
+            max.sppt = *range.end();
+        }
+        if let Some(suffix) = fppt {
+            let range = self.read_limit_range(suffix).await?;
+            min.fppt = *range.start();
+            max.fppt = *range.end();
+        }
+        Ok((min, max))
+    }
+}
+
+pub(crate) async fn find_battery() -> Result<PathBuf> {
+    let mut dir = fs::read_dir(path(POWER_SUPPLY_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let base = entry.path();
+        let Ok(supply_type) = fs::read_to_string(base.join("type")).await else {
+            continue;
+        };
+        if supply_type.trim() != "Battery" {
+            continue;
+        }
+        // Skip batteries of peripherals, e.g. controllers, which are scoped to their device
+        if let Ok(scope) = fs::read_to_string(base.join("scope")).await {
+            if scope.trim() == "Device" {
+                continue;
+            }
+        }
+        return Ok(base);
+    }
+    bail!("No system battery found");
+}
+
+pub(crate) async fn is_on_battery() -> Result<bool> {
+    if find_battery().await.is_err() {
+        return Ok(false);
+    }
+    let mut dir = fs::read_dir(path(POWER_SUPPLY_PREFIX)).await?;
+    let mut has_external = false;
+    while let Some(entry) = dir.next_entry().await? {
+        let base = entry.path();
+        let Ok(supply_type) = fs::read_to_string(base.join("type")).await else {
+            continue;
+        };
+        if !matches!(supply_type.trim(), "Mains" | "USB") {
+            continue;
+        }
+        if let Ok(scope) = fs::read_to_string(base.join("scope")).await {
+            if scope.trim() == "Device" {
+                continue;
+            }
+        }
+        has_external = true;
+        if let Ok(online) = fs::read_to_string(base.join("online")).await {
+            if online.trim() == "1" {
+                return Ok(false);
+            }
+        }
+    }
+    if has_external {
+        return Ok(true);
+    }
+    // Without an external supply to ask, fall back to whether the battery is draining
+    Ok(get_battery_charge_state().await? == BatteryChargeState::Discharging)
+}
+
+/// What was negotiated with the connected charger. Values the charger's driver doesn't report
+/// are empty or 0
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct ChargerInfo {
+    /// USB charging protocol in use, e.g. `PD` or `PD_PPS`
+    pub usb_type: String,
+    /// Voltage in mV
+    pub voltage: u32,
+    /// Maximum current in mA
+    pub current: u32,
+}
+
+impl ChargerInfo {
+    /// Maximum power in mW
+    pub(crate) fn power(&self) -> u32 {
+        (u64::from(self.voltage) * u64::from(self.current) / 1000)
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+}
+
+async fn read_supply_value(base: &Path, attribute: &str) -> u32 {
+    // Values are in µV and µA
+    match fs::read_to_string(base.join(attribute)).await {
+        Ok(value) => value.trim().parse::<u32>().map_or(0, |value| value / 1000),
+        Err(_) => 0,
+    }
+}
+
+/// The charger that is currently connected, if any
+pub(crate) async fn get_charger_info() -> Result<Option<ChargerInfo>> {
+    let mut dir = fs::read_dir(path(POWER_SUPPLY_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let base = entry.path();
+        let Ok(supply_type) = fs::read_to_string(base.join("type")).await else {
+            continue;
+        };
+        if !matches!(supply_type.trim(), "Mains" | "USB") {
+            continue;
+        }
+        if let Ok(scope) = fs::read_to_string(base.join("scope")).await {
+            if scope.trim() == "Device" {
+                continue;
+            }
+        }
+        match fs::read_to_string(base.join("online")).await {
+            Ok(online) if online.trim() == "1" => (),
+            _ => continue,
+        }
+        // The protocol in use is marked with brackets, e.g. "C [PD] PD_PPS"
+        let usb_type = fs::read_to_string(base.join("usb_type"))
+            .await
+            .ok()
+            .and_then(|types| {
+                types
+                    .split_whitespace()
+                    .find_map(|usb_type| usb_type.strip_prefix('[')?.strip_suffix(']'))
+                    .map(ToString::to_string)
+            })
+            .unwrap_or_default();
+        return Ok(Some(ChargerInfo {
+            usb_type,
+            voltage: read_supply_value(&base, "voltage_now").await,
+            current: read_supply_value(&base, "current_max").await,
+        }));
+    }
+    Ok(None)
+}
+
+async fn read_battery_sysfs_contents(attribute: &str) -> Result<String> {
+    let base = find_battery().await?;
+    Ok(fs::read_to_string(base.join(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+        .trim()
+        .to_string())
+}
+
+pub(crate) async fn get_battery_capacity() -> Result<u32> {
+    read_battery_sysfs_contents("capacity")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) async fn get_battery_charge_state() -> Result<BatteryChargeState> {
+    let status = read_battery_sysfs_contents("status").await?;
+    Ok(BatteryChargeState::from_str(status.as_str()).unwrap_or(BatteryChargeState::Unknown))
+}
+
+pub(crate) async fn get_battery_health() -> Result<BatteryHealth> {
+    let base = find_battery().await?;
+    // Not all batteries report their health, so treat that as unknown instead of an error
+    let Ok(health) = fs::read_to_string(base.join("health")).await else {
+        return Ok(BatteryHealth::Unknown);
+    };
+    Ok(BatteryHealth::from_str(health.trim()).unwrap_or(BatteryHealth::Unknown))
+}
+
+pub(crate) async fn get_battery_cycle_count() -> Result<u32> {
+    read_battery_sysfs_contents("cycle_count")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+/// Read a charge attribute, falling back to its energy counterpart for fuel gauges that only
+/// report energy
+async fn read_battery_charge(charge: &str, energy: &str) -> Result<u64> {
+    let value = match read_battery_sysfs_contents(charge).await {
+        Ok(value) => value,
+        Err(_) => read_battery_sysfs_contents(energy).await?,
+    };
+    value
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))
+}
+
+pub(crate) async fn get_battery_charge_now() -> Result<u64> {
+    read_battery_charge("charge_now", "energy_now").await
+}
+
+pub(crate) async fn get_battery_charge_full() -> Result<u64> {
+    read_battery_charge("charge_full", "energy_full").await
+}
+
+pub(crate) async fn get_battery_power_now() -> Result<u64> {
+    // Some fuel gauges only report current and voltage, in µA and µV
+    if let Ok(power) = read_battery_sysfs_contents("power_now").await {
+        return power
+            .parse()
+            .map_err(|e| anyhow!("Error parsing value: {e}"));
+    }
+    let current: u64 = read_battery_sysfs_contents("current_now")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    let voltage: u64 = read_battery_sysfs_contents("voltage_now")
+        .await?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    Ok(current * voltage / 1_000_000)
+}
+
+async fn max_charge_level_path(config: &BatteryChargeLimitConfig) -> Result<PathBuf> {
+    let base = match config.method {
+        BatteryChargeLimitMethod::Hwmon => {
+            let Some(hwmon_name) = config.hwmon_name.as_ref() else {
+                bail!("No hwmon configured for battery charge limit");
+            };
+            find_hwmon(hwmon_name.as_str()).await?
+        }
+        BatteryChargeLimitMethod::PowerSupply => find_battery().await?,
+    };
+    Ok(base.join(config.attribute.as_str()))
+}
+
+pub(crate) async fn get_max_charge_level() -> Result<i32> {
+    let config = device_config().await?;
+    let config = config
+        .as_ref()
+        .and_then(|config| config.battery_charge_limit.as_ref())
+        .ok_or(anyhow!("No battery charge limit configured"))?;
+    let path = max_charge_level_path(config).await?;
+
+    let limit = fs::read_to_string(path)
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    // power_supply thresholds have no "disabled" value, charging to 100% means no limit
+    match (&config.method, limit) {
+        (BatteryChargeLimitMethod::PowerSupply, 100) => Ok(0),
+        _ => Ok(limit),
+    }
+}
+
+pub(crate) async fn set_max_charge_level(limit: i32) -> Result<oneshot::Receiver<SysfsWritten>> {
+    ensure!((0..=100).contains(&limit), "Invalid limit");
     let config = device_config().await?;
     let config = config
         .as_ref()
         .and_then(|config| config.battery_charge_limit.as_ref())
         .ok_or(anyhow!("No battery charge limit configured"))?;
-    let base = find_hwmon(config.hwmon_name.as_str()).await?;
+    let path = max_charge_level_path(config).await?;
+    let data = match (&config.method, limit) {
+        (BatteryChargeLimitMethod::PowerSupply, 0) => 100,
+        _ => limit,
+    }
+    .to_string();
 
     Ok(SYSFS_WRITER
         .get()
         .ok_or(anyhow!("sysfs writer not running"))?
-        .send(
-            base.join(config.attribute.clone()),
-            data.as_bytes().to_owned(),
-        )
+        .send(path, vec![data.into_bytes()])
         .await)
 }
 
+async fn charge_bypass_config() -> Result<ChargeBypassConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.battery_charge_limit.as_ref())
+        .and_then(|config| config.charge_bypass.clone())
+        .ok_or(anyhow!("Charge bypass is not supported"))
+}
+
+fn charge_bypass_path(config: &ChargeBypassConfig) -> PathBuf {
+    path(FirmwareAttributeLimitManager::PREFIX)
+        .join(config.firmware_attribute.as_str())
+        .join("attributes")
+        .join(config.attribute.as_str())
+        .join("current_value")
+}
+
+pub(crate) async fn is_charge_bypass_supported() -> bool {
+    charge_bypass_config().await.is_ok()
+}
+
+pub(crate) async fn get_charge_bypass() -> Result<bool> {
+    let config = charge_bypass_config().await?;
+    let value = fs::read_to_string(charge_bypass_path(&config))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    match value.trim() {
+        value if value == config.enabled => Ok(true),
+        value if value == config.disabled => Ok(false),
+        value => bail!("Unknown charge bypass value {value}"),
+    }
+}
+
+pub(crate) async fn set_charge_bypass(enabled: bool) -> Result<()> {
+    let config = charge_bypass_config().await?;
+    let value = if enabled {
+        config.enabled.as_str()
+    } else {
+        config.disabled.as_str()
+    };
+    write_sysfs(charge_bypass_path(&config), value)
+        .await
+        .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
 pub(crate) async fn get_available_platform_profiles(name: &str) -> Result<Vec<String>> {
     let base = find_platform_profile(name).await?;
     Ok(fs::read_to_string(base.join("choices"))
@@ -582,7 +1548,7 @@ pub(crate) async fn get_platform_profile(name: &str) -> Result<String> {
 
 pub(crate) async fn set_platform_profile(name: &str, profile: &str) -> Result<()> {
     let base = find_platform_profile(name).await?;
-    fs::write(base.join("profile"), profile.as_bytes())
+    write_sysfs(base.join("profile"), profile.as_bytes())
         .await
         .map_err(|message| anyhow!("Error writing to sysfs: {message}"))
 }
@@ -592,6 +1558,7 @@ impl TdpManagerService {
         channel: UnboundedReceiver<TdpManagerCommand>,
         system: &Connection,
         session: &Connection,
+        daemon: Sender<Command>,
     ) -> Result<TdpManagerService> {
         let config = device_config().await?;
         let config = config
@@ -606,14 +1573,101 @@ impl TdpManagerService {
             proxy,
             session: session.clone(),
             channel,
+            daemon,
             download_set: JoinSet::new(),
             download_handles: HashMap::new(),
             previous_limit: None,
             download_mode_limit: config.download_mode_limit,
+            profiles: HashMap::new(),
+            foreground_app: 0,
+            unprofiled_limit: None,
+            manual_limit: None,
+            battery_limit: None,
+            requested_limit: None,
             manager,
         })
     }
 
+    async fn apply_profile(&mut self) -> Result<()> {
+        if !self.manager.is_active().await? {
+            return Ok(());
+        }
+
+        let limit = match self.profiles.get(&self.foreground_app) {
+            Some(_) if self.manual_limit.is_some() => {
+                debug!(
+                    "Keeping manually set TDP limit instead of profile for app {}",
+                    self.foreground_app
+                );
+                return Ok(());
+            }
+            Some(limit) => {
+                if self.unprofiled_limit.is_none() {
+                    self.unprofiled_limit = match self.previous_limit {
+                        Some(limit) => Some(limit),
+                        None => NonZeroU32::new(self.unclamped_tdp_limit().await?),
+                    };
+                }
+                NonZeroU32::new(*limit)
+            }
+            None => {
+                let unprofiled_limit = self.unprofiled_limit.take();
+                self.manual_limit.take().or(unprofiled_limit)
+            }
+        };
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+
+        if self.download_handles.is_empty() {
+            debug!(
+                "Applying TDP limit of {limit} for app {}",
+                self.foreground_app
+            );
+            self.set_tdp_limit(limit.get()).await
+        } else {
+            // Restore this limit once download mode is over
+            self.previous_limit = Some(limit);
+            Ok(())
+        }
+    }
+
+    async fn set_profile_for_app(&mut self, app_id: u32, limit: u32) -> Result<()> {
+        ensure!(app_id != 0, "Invalid app ID");
+        ensure!(
+            self.manager.get_tdp_limit_range().await?.contains(&limit),
+            "Invalid limit"
+        );
+        self.profiles.insert(app_id, limit);
+        self.write_profiles().await?;
+        if app_id == self.foreground_app {
+            // A new profile for the running app replaces whatever the user set before
+            self.manual_limit = None;
+            self.apply_profile().await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_profile_for_app(&mut self, app_id: u32) -> Result<()> {
+        if self.profiles.remove(&app_id).is_none() {
+            return Ok(());
+        }
+        self.write_profiles().await?;
+        if app_id == self.foreground_app {
+            self.apply_profile().await?;
+        }
+        Ok(())
+    }
+
+    async fn write_profiles(&self) -> Result<()> {
+        self.daemon
+            .send(DaemonCommand::ContextCommand(UserCommand::SetTdpProfiles(
+                self.profiles.clone(),
+            )))
+            .await?;
+        Ok(())
+    }
+
     async fn update_download_mode(&mut self) -> Result<()> {
         if !self.manager.is_active().await? {
             return Ok(());
@@ -638,8 +1692,10 @@ impl TdpManagerService {
             }
         } else {
             if self.previous_limit.is_none() {
-                debug!("Entering download mode, caching TDP limit of {current_limit}");
-                self.previous_limit = Some(current_limit);
+                let previous_limit =
+                    NonZeroU32::new(self.unclamped_tdp_limit().await?).unwrap_or(current_limit);
+                debug!("Entering download mode, caching TDP limit of {previous_limit}");
+                self.previous_limit = Some(previous_limit);
             }
             if current_limit != download_mode_limit {
                 self.set_tdp_limit(download_mode_limit.get()).await?;
@@ -662,33 +1718,42 @@ impl TdpManagerService {
             .entry(identifier.clone())
             .and_modify(|count| *count += 1)
             .or_insert(1);
-        self.download_set
-            .spawn(TdpManagerService::wait_on_handle(recv, identifier));
+        self.download_set.spawn(wait_on_handle(recv, identifier));
         self.update_download_mode().await?;
         Ok(Some(send.into_blocking_fd()?))
     }
 
-    async fn wait_on_handle(recv: pipe::Receiver, identifier: String) -> String {
-        loop {
-            let mut buf = [0; 1024];
-            let read = match recv.ready(Interest::READABLE).await {
-                Ok(r) if r.is_read_closed() => break,
-                Ok(r) if r.is_readable() => recv.try_read(&mut buf),
-                Err(e) => Err(e),
-                Ok(e) => {
-                    warn!("Download mode handle received unexpected event: {e:?}");
-                    break;
-                }
-            };
-            if let Err(e) = read {
-                warn!("Download mode handle received unexpected error: {e:?}");
-                break;
-            }
+    /// The limit that was last asked for, which is above the applied one while it's clamped
+    async fn unclamped_tdp_limit(&self) -> Result<u32> {
+        match (self.battery_limit, self.requested_limit) {
+            (Some(_), Some(limit)) => Ok(limit),
+            _ => self.manager.get_tdp_limit().await,
         }
-        identifier
     }
 
-    async fn set_tdp_limit(&self, limit: u32) -> Result<()> {
+    async fn set_battery_limit(&mut self, limit: Option<u32>) -> Result<()> {
+        self.battery_limit = limit.and_then(NonZeroU32::new);
+        if !self.manager.is_active().await? {
+            return Ok(());
+        }
+        let limit = match self.requested_limit {
+            Some(limit) => limit,
+            None => self.manager.get_tdp_limit().await?,
+        };
+        if limit == 0 {
+            return Ok(());
+        }
+        debug!("Updating low battery TDP limit to {:?}", self.battery_limit);
+        self.set_tdp_limit(limit).await
+    }
+
+    async fn set_tdp_limit(&mut self, limit: u32) -> Result<()> {
+        self.requested_limit = Some(limit);
+        // While the battery is low, limits above the clamp are only applied once it's lifted
+        let limit = match self.battery_limit {
+            Some(battery_limit) => limit.min(battery_limit.get()),
+            None => limit,
+        };
         self.proxy
             .set_tdp_limit(limit)
             .await
@@ -697,12 +1762,49 @@ impl TdpManagerService {
         if let Ok(interface) = self
             .session
             .object_server()
-            .interface::<_, TdpLimit1>(MANAGER_PATH)
+            .interface::<_, Guarded<TdpLimit1>>(MANAGER_PATH)
+            .await
+        {
+            tokio::spawn(async move {
+                let ctx = interface.signal_emitter();
+                let interface = interface.get().await;
+                interface.tdp_limit_changed(ctx).await?;
+                interface.tdp_limits_changed(ctx).await
+            });
+        }
+        Ok(())
+    }
+
+    async fn set_tdp_limits(&mut self, limits: TdpLimits) -> Result<()> {
+        ensure!(
+            self.download_handles.is_empty(),
+            "TDP limits can't be changed while download mode is active"
+        );
+        self.requested_limit = Some(limits.spl);
+        let limits = match self.battery_limit {
+            Some(battery_limit) => TdpLimits {
+                spl: limits.spl.min(battery_limit.get()),
+                sppt: limits.sppt.min(battery_limit.get()),
+                fppt: limits.fppt.min(battery_limit.get()),
+            },
+            None => limits,
+        };
+        self.proxy
+            .set_tdp_limits(limits.spl, limits.sppt, limits.fppt)
+            .await
+            .inspect_err(|e| error!("Failed to set TDP limits: {e}"))?;
+
+        if let Ok(interface) = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<TdpLimit1>>(MANAGER_PATH)
             .await
         {
             tokio::spawn(async move {
                 let ctx = interface.signal_emitter();
-                interface.get().await.tdp_limit_changed(ctx).await
+                let interface = interface.get().await;
+                interface.tdp_limit_changed(ctx).await?;
+                interface.tdp_limits_changed(ctx).await
             });
         }
         Ok(())
@@ -712,7 +1814,17 @@ impl TdpManagerService {
         match command {
             TdpManagerCommand::SetTdpLimit(limit) => {
                 if self.download_handles.is_empty() {
+                    self.manual_limit = if self.profiles.contains_key(&self.foreground_app) {
+                        NonZeroU32::new(limit)
+                    } else {
+                        None
+                    };
                     self.set_tdp_limit(limit).await?;
+                    self.daemon
+                        .send(DaemonCommand::ContextCommand(UserCommand::SetTdpLimit(
+                            limit,
+                        )))
+                        .await?;
                 }
             }
             TdpManagerCommand::GetTdpLimit(reply) => {
@@ -721,6 +1833,24 @@ impl TdpManagerService {
             TdpManagerCommand::GetTdpLimitRange(reply) => {
                 let _ = reply.send(self.manager.get_tdp_limit_range().await);
             }
+            TdpManagerCommand::SetTdpLimits(limits, reply) => {
+                let res = self.set_tdp_limits(limits).await;
+                let saved = res.is_ok();
+                let _ = reply.send(res);
+                if saved {
+                    self.daemon
+                        .send(DaemonCommand::ContextCommand(UserCommand::SetTdpLimits(
+                            limits,
+                        )))
+                        .await?;
+                }
+            }
+            TdpManagerCommand::GetTdpLimits(reply) => {
+                let _ = reply.send(self.manager.get_tdp_limits().await);
+            }
+            TdpManagerCommand::GetTdpLimitsRange(reply) => {
+                let _ = reply.send(self.manager.get_tdp_limits_range().await);
+            }
             TdpManagerCommand::IsActive(reply) => {
                 let _ = reply.send(self.manager.is_active().await);
             }
@@ -734,6 +1864,26 @@ impl TdpManagerService {
             TdpManagerCommand::ListDownloadModeHandles(reply) => {
                 let _ = reply.send(self.download_handles.clone());
             }
+            TdpManagerCommand::LoadProfiles(profiles) => {
+                self.profiles = profiles;
+                self.apply_profile().await?;
+            }
+            TdpManagerCommand::SetProfileForApp(app_id, limit, reply) => {
+                let _ = reply.send(self.set_profile_for_app(app_id, limit).await);
+            }
+            TdpManagerCommand::ClearProfileForApp(app_id) => {
+                self.clear_profile_for_app(app_id).await?;
+            }
+            TdpManagerCommand::ListProfiles(reply) => {
+                let _ = reply.send(self.profiles.clone());
+            }
+            TdpManagerCommand::SetForegroundApp(app_id) => {
+                self.foreground_app = app_id;
+                self.apply_profile().await?;
+            }
+            TdpManagerCommand::SetBatteryLimit(limit) => {
+                self.set_battery_limit(limit).await?;
+            }
         }
         Ok(())
     }
@@ -793,15 +1943,18 @@ impl Service for TdpManagerService {
 #[cfg(test)]
 pub(crate) mod test {
     use super::*;
+    use crate::daemon::{self, user::UserContext};
     use crate::error::to_zbus_fdo_error;
     use crate::hardware::{
         BatteryChargeLimitConfig, DeviceConfig, FirmwareAttributeConfig, PerformanceProfileConfig,
         RangeConfig, TdpLimitConfig,
     };
-    use crate::{enum_on_off, enum_roundtrip, testing};
+    use crate::{enum_on_off, enum_roundtrip, testing, write_synced};
     use anyhow::anyhow;
     use std::time::Duration;
-    use tokio::fs::{create_dir_all, read_to_string, remove_dir, write};
+    use tokio::fs::{
+        create_dir_all, read_to_string, remove_dir, remove_dir_all, remove_file, write,
+    };
     use tokio::sync::mpsc::{channel, unbounded_channel, Sender};
     use tokio::time::sleep;
     use zbus::{fdo, interface};
@@ -825,6 +1978,21 @@ pub(crate) mod test {
         create_dir_all(&cpufreq_base).await?;
         write(cpufreq_base.join(CPUFREQ_BOOST_SUFFIX), b"1\n").await?;
 
+        let smt_control = base.join(CPU_SMT_CONTROL_SUFFIX);
+        create_dir_all(smt_control.parent().unwrap()).await?;
+        write(smt_control, b"on\n").await?;
+
+        create_dir_all(base.join("cpu0")).await?;
+        for cpu in ["cpu1", "cpu2", "cpu3"] {
+            create_dir_all(base.join(cpu)).await?;
+            write(base.join(cpu).join(CPU_ONLINE_SUFFIX), b"1\n").await?;
+        }
+
+        let policy_base = cpufreq_base.join(CPU0_NAME);
+        create_dir_all(&policy_base).await?;
+        write(policy_base.join(CPUINFO_MIN_FREQ_SUFFIX), b"400000\n").await?;
+        write(policy_base.join(CPUINFO_MAX_FREQ_SUFFIX), b"3500000\n").await?;
+
         let base = find_hwmon(AMDGPU_HWMON_NAME).await?;
 
         let filename = base.join(TDP_LIMIT1);
@@ -837,11 +2005,22 @@ pub(crate) mod test {
 
         write(base.join("max_battery_charge_level"), "10\n").await?;
 
+        write(base.join("pwm1_auto_point1_temp"), "40000\n").await?;
+        write(base.join("pwm1_auto_point1_pwm"), "64\n").await?;
+        write(base.join("pwm1_auto_point2_temp"), "80000\n").await?;
+        write(base.join("pwm1_auto_point2_pwm"), "255\n").await?;
+
         let base = path(PLATFORM_PROFILE_PREFIX).join("platform-profile0");
         create_dir_all(&base).await?;
         write_synced(base.join("name"), b"power-driver\n").await?;
         write_synced(base.join("choices"), b"a b c\n").await?;
 
+        let base = path(POWER_SUPPLY_PREFIX).join("BAT1");
+        create_dir_all(&base).await?;
+        write(base.join("type"), "Battery\n").await?;
+        write(base.join("capacity"), "80\n").await?;
+        write(base.join("status"), "Discharging\n").await?;
+
         Ok(())
     }
 
@@ -858,6 +2037,39 @@ pub(crate) mod test {
         assert!(CPUScalingGovernor::from_str("usersave").is_err());
     }
 
+    #[tokio::test]
+    async fn find_hwmon_cached() {
+        let _h = testing::start();
+
+        let hwmon = path(HWMON_PREFIX);
+        create_dir_all(hwmon.join("hwmon0"))
+            .await
+            .expect("create_dir_all");
+        write(hwmon.join("hwmon0/name"), "steamdeck_hwmon\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            find_hwmon("steamdeck_hwmon").await.unwrap(),
+            hwmon.join("hwmon0")
+        );
+
+        // A renumbered device is found again even without an invalidation
+        remove_dir_all(hwmon.join("hwmon0"))
+            .await
+            .expect("remove_dir_all");
+        assert!(find_hwmon("steamdeck_hwmon").await.is_err());
+        create_dir_all(hwmon.join("hwmon3"))
+            .await
+            .expect("create_dir_all");
+        write(hwmon.join("hwmon3/name"), "steamdeck_hwmon\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            find_hwmon("steamdeck_hwmon").await.unwrap(),
+            hwmon.join("hwmon3")
+        );
+    }
+
     #[tokio::test]
     async fn test_gpu_hwmon_get_tdp_limit() {
         let handle = testing::start();
@@ -1048,15 +2260,209 @@ pub(crate) mod test {
         assert!(get_cpu_scaling_governor().await.is_err());
     }
 
+    #[test]
+    fn energy_performance_preference_roundtrip() {
+        enum_roundtrip!(EnergyPerformancePreference {
+            "default": str = Default,
+            "performance": str = Performance,
+            "balance_performance": str = BalancePerformance,
+            "balance_power": str = BalancePower,
+            "power": str = Power,
+        });
+        assert!(EnergyPerformancePreference::from_str("balance").is_err());
+    }
+
     #[tokio::test]
-    async fn read_cpu_boost_state_cpufreq() {
+    async fn read_write_energy_performance_preference() {
         let _h = testing::start();
 
-        let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX);
-        let boost_path = base.join(CPUFREQ_BOOST_SUFFIX);
-        create_dir_all(boost_path.parent().unwrap())
+        assert!(get_energy_performance_preference().await.is_err());
+        assert!(get_available_energy_performance_preferences()
             .await
-            .expect("create_dir_all");
+            .is_err());
+
+        let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX);
+        for policy in ["policy0", "policy4"] {
+            create_dir_all(base.join(policy))
+                .await
+                .expect("create_dir_all");
+            write(
+                base.join(policy).join(CPU_EPP_SUFFIX),
+                "balance_performance\n",
+            )
+            .await
+            .expect("write");
+        }
+        write(
+            base.join(CPU0_NAME).join(CPU_AVAILABLE_EPP_SUFFIX),
+            "default performance balance_performance balance_power power bogus\n",
+        )
+        .await
+        .expect("write");
+
+        assert_eq!(
+            get_available_energy_performance_preferences()
+                .await
+                .unwrap(),
+            vec![
+                EnergyPerformancePreference::Default,
+                EnergyPerformancePreference::Performance,
+                EnergyPerformancePreference::BalancePerformance,
+                EnergyPerformancePreference::BalancePower,
+                EnergyPerformancePreference::Power,
+            ]
+        );
+        assert_eq!(
+            get_energy_performance_preference().await.unwrap(),
+            EnergyPerformancePreference::BalancePerformance
+        );
+
+        set_energy_performance_preference(EnergyPerformancePreference::Power)
+            .await
+            .expect("set_energy_performance_preference");
+        for policy in ["policy0", "policy4"] {
+            assert_eq!(
+                read_to_string(base.join(policy).join(CPU_EPP_SUFFIX))
+                    .await
+                    .unwrap(),
+                "power"
+            );
+        }
+
+        write(
+            base.join(CPU0_NAME).join(CPU_AVAILABLE_EPP_SUFFIX),
+            "performance\n",
+        )
+        .await
+        .expect("write");
+        assert!(
+            set_energy_performance_preference(EnergyPerformancePreference::Default)
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            get_energy_performance_preference().await.unwrap(),
+            EnergyPerformancePreference::Power
+        );
+    }
+
+    #[tokio::test]
+    async fn read_write_cpu_policy_governors() {
+        let _h = testing::start();
+
+        let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX);
+        for (policy, governor) in [("policy0", "powersave\n"), ("policy4", "performance\n")] {
+            create_dir_all(base.join(policy))
+                .await
+                .expect("create_dir_all");
+            write(
+                base.join(policy).join(CPU_SCALING_GOVERNOR_SUFFIX),
+                governor,
+            )
+            .await
+            .expect("write");
+        }
+        create_dir_all(base.join("policyX"))
+            .await
+            .expect("create_dir_all");
+
+        assert_eq!(
+            get_cpu_scaling_governors().await.unwrap(),
+            HashMap::from([
+                (0, CPUScalingGovernor::PowerSave),
+                (4, CPUScalingGovernor::Performance)
+            ])
+        );
+
+        set_cpu_scaling_governor_for_policy(4, CPUScalingGovernor::SchedUtil)
+            .await
+            .expect("set");
+        assert_eq!(
+            read_to_string(base.join("policy4").join(CPU_SCALING_GOVERNOR_SUFFIX))
+                .await
+                .unwrap(),
+            "schedutil"
+        );
+        assert_eq!(
+            get_cpu_scaling_governor().await.unwrap(),
+            CPUScalingGovernor::PowerSave
+        );
+
+        assert!(
+            set_cpu_scaling_governor_for_policy(2, CPUScalingGovernor::SchedUtil)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn read_write_cpu_frequency() {
+        let _h = testing::start();
+
+        let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX);
+        for policy in ["policy0", "policy1"] {
+            let base = base.join(policy);
+            create_dir_all(&base).await.expect("create_dir_all");
+            write(base.join(CPUINFO_MIN_FREQ_SUFFIX), "400000\n")
+                .await
+                .expect("write");
+            write(base.join(CPUINFO_MAX_FREQ_SUFFIX), "3500000\n")
+                .await
+                .expect("write");
+            write(base.join(CPU_SCALING_MIN_FREQ_SUFFIX), "400000\n")
+                .await
+                .expect("write");
+            write(base.join(CPU_SCALING_MAX_FREQ_SUFFIX), "3500000\n")
+                .await
+                .expect("write");
+        }
+
+        assert_eq!(get_cpu_frequency_range().await.unwrap(), 400..=3500);
+        assert_eq!(get_min_cpu_frequency().await.unwrap(), 400);
+        assert_eq!(get_max_cpu_frequency().await.unwrap(), 3500);
+        assert!(get_available_cpu_frequencies().await.unwrap().is_empty());
+
+        set_max_cpu_frequency(2000).await.expect("set");
+        for policy in ["policy0", "policy1"] {
+            assert_eq!(
+                read_to_string(base.join(policy).join(CPU_SCALING_MAX_FREQ_SUFFIX))
+                    .await
+                    .unwrap(),
+                "2000000"
+            );
+        }
+        assert_eq!(get_max_cpu_frequency().await.unwrap(), 2000);
+
+        set_min_cpu_frequency(1000).await.expect("set");
+        assert_eq!(get_min_cpu_frequency().await.unwrap(), 1000);
+
+        assert!(set_min_cpu_frequency(2500).await.is_err());
+        assert!(set_max_cpu_frequency(800).await.is_err());
+        assert!(set_max_cpu_frequency(4000).await.is_err());
+        assert!(set_min_cpu_frequency(200).await.is_err());
+
+        write(
+            base.join("policy0")
+                .join(CPU_SCALING_AVAILABLE_FREQUENCIES_SUFFIX),
+            "2800000 1400000 400000\n",
+        )
+        .await
+        .expect("write");
+        assert_eq!(
+            get_available_cpu_frequencies().await.unwrap(),
+            vec![400, 1400, 2800]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_cpu_boost_state_cpufreq() {
+        let _h = testing::start();
+
+        let base = path(CPU_PREFIX).join(CPUFREQ_PREFIX);
+        let boost_path = base.join(CPUFREQ_BOOST_SUFFIX);
+        create_dir_all(boost_path.parent().unwrap())
+            .await
+            .expect("create_dir_all");
 
         write(&boost_path, b"1\n").await.expect("write");
         assert_eq!(get_cpu_boost_state().await.unwrap(), CPUBoostState::Enabled);
@@ -1126,6 +2532,167 @@ pub(crate) mod test {
         assert!(get_cpu_boost_state().await.is_err());
     }
 
+    #[tokio::test]
+    async fn smt_control() {
+        let _h = testing::start();
+
+        let smt_control = path(CPU_PREFIX).join(CPU_SMT_CONTROL_SUFFIX);
+        assert!(get_smt_enabled().await.is_err());
+        assert!(set_smt_enabled(false).await.is_err());
+
+        create_dir_all(smt_control.parent().unwrap())
+            .await
+            .expect("create_dir_all");
+        write(&smt_control, b"on\n").await.expect("write");
+        assert!(get_smt_enabled().await.unwrap());
+
+        set_smt_enabled(false).await.expect("set_smt_enabled");
+        assert_eq!(read_to_string(&smt_control).await.unwrap(), "off");
+        assert!(!get_smt_enabled().await.unwrap());
+
+        set_smt_enabled(true).await.expect("set_smt_enabled");
+        assert_eq!(read_to_string(&smt_control).await.unwrap(), "on");
+
+        write(&smt_control, b"forceoff\n").await.expect("write");
+        assert!(!get_smt_enabled().await.unwrap());
+
+        write(&smt_control, b"notsupported\n").await.expect("write");
+        assert!(get_smt_enabled().await.is_err());
+        assert!(set_smt_enabled(true).await.is_err());
+        assert_eq!(
+            read_to_string(&smt_control).await.unwrap(),
+            "notsupported\n"
+        );
+
+        write(&smt_control, b"bogus\n").await.expect("write");
+        assert!(get_smt_enabled().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn online_cpu_count() {
+        let _h = testing::start();
+
+        assert!(get_online_cpu_count().await.is_err());
+
+        let base = path(CPU_PREFIX);
+        create_dir_all(base.join("cpu0"))
+            .await
+            .expect("create_dir_all");
+        for cpu in 1..6 {
+            let cpu = base.join(format!("cpu{cpu}"));
+            create_dir_all(&cpu).await.expect("create_dir_all");
+            write(cpu.join(CPU_ONLINE_SUFFIX), b"1\n")
+                .await
+                .expect("write");
+        }
+        create_dir_all(base.join("cpufreq"))
+            .await
+            .expect("create_dir_all");
+        create_dir_all(base.join("cpuidle"))
+            .await
+            .expect("create_dir_all");
+
+        assert_eq!(get_max_online_cpu_count().await.unwrap(), 6);
+        assert_eq!(get_min_online_cpu_count().await.unwrap(), MIN_ONLINE_CPUS);
+        assert_eq!(get_online_cpu_count().await.unwrap(), 6);
+
+        set_online_cpu_count(4).await.expect("set_online_cpu_count");
+        assert_eq!(get_online_cpu_count().await.unwrap(), 4);
+        for (cpu, expected) in [(3, "1\n"), (4, "0"), (5, "0")] {
+            assert_eq!(
+                read_to_string(base.join(format!("cpu{cpu}")).join(CPU_ONLINE_SUFFIX))
+                    .await
+                    .unwrap(),
+                expected
+            );
+        }
+
+        assert!(set_online_cpu_count(1).await.is_err());
+        assert!(set_online_cpu_count(7).await.is_err());
+        assert_eq!(get_online_cpu_count().await.unwrap(), 4);
+
+        set_online_cpu_count(2).await.expect("set_online_cpu_count");
+        assert_eq!(get_online_cpu_count().await.unwrap(), 2);
+
+        set_online_cpu_count(6).await.expect("set_online_cpu_count");
+        assert_eq!(get_online_cpu_count().await.unwrap(), 6);
+
+        // A CPU that can't be written doesn't keep the others from changing
+        remove_file(base.join("cpu3").join(CPU_ONLINE_SUFFIX))
+            .await
+            .expect("remove_file");
+        create_dir_all(base.join("cpu3").join(CPU_ONLINE_SUFFIX))
+            .await
+            .expect("create_dir_all");
+        assert!(set_online_cpu_count(3).await.is_err());
+        for (cpu, expected) in [(2, "1"), (4, "0"), (5, "0")] {
+            assert_eq!(
+                read_to_string(base.join(format!("cpu{cpu}")).join(CPU_ONLINE_SUFFIX))
+                    .await
+                    .unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn online_cpu_count_without_smt() {
+        let _h = testing::start();
+
+        // Two cores of two threads each, numbered the way AMD CPUs are
+        let base = path(CPU_PREFIX);
+        for cpu in 0..4 {
+            let dir = base.join(format!("cpu{cpu}"));
+            create_dir_all(dir.join("topology"))
+                .await
+                .expect("create_dir_all");
+            let first = cpu - cpu % 2;
+            write(
+                dir.join(CPU_THREAD_SIBLINGS_SUFFIX),
+                format!("{first}-{}\n", first + 1),
+            )
+            .await
+            .expect("write");
+            if cpu > 0 {
+                write(dir.join(CPU_ONLINE_SUFFIX), b"1\n")
+                    .await
+                    .expect("write");
+            }
+        }
+        create_dir_all(base.join("smt"))
+            .await
+            .expect("create_dir_all");
+        write(base.join(CPU_SMT_CONTROL_SUFFIX), b"on\n")
+            .await
+            .expect("write");
+        assert_eq!(get_max_online_cpu_count().await.unwrap(), 4);
+
+        write(base.join(CPU_SMT_CONTROL_SUFFIX), b"off\n")
+            .await
+            .expect("write");
+        for cpu in [1, 3] {
+            write(
+                base.join(format!("cpu{cpu}")).join(CPU_ONLINE_SUFFIX),
+                b"0\n",
+            )
+            .await
+            .expect("write");
+        }
+        assert_eq!(get_max_online_cpu_count().await.unwrap(), 2);
+        assert_eq!(get_min_online_cpu_count().await.unwrap(), 2);
+        assert!(set_online_cpu_count(3).await.is_err());
+
+        set_online_cpu_count(2).await.expect("set_online_cpu_count");
+        for (cpu, expected) in [(1, "0\n"), (2, "1\n"), (3, "0\n")] {
+            assert_eq!(
+                read_to_string(base.join(format!("cpu{cpu}")).join(CPU_ONLINE_SUFFIX))
+                    .await
+                    .unwrap(),
+                expected
+            );
+        }
+    }
+
     #[tokio::test]
     async fn read_max_charge_level() {
         let handle = testing::start();
@@ -1133,8 +2700,10 @@ pub(crate) mod test {
         let mut config = DeviceConfig::default();
         config.battery_charge_limit = Some(BatteryChargeLimitConfig {
             suggested_minimum_limit: Some(10),
-            hwmon_name: String::from("steamdeck_hwmon"),
+            method: BatteryChargeLimitMethod::Hwmon,
+            hwmon_name: Some(String::from("steamdeck_hwmon")),
             attribute: String::from("max_battery_charge_level"),
+            charge_bypass: None,
         });
         handle.test.device_config.replace(Some(config));
 
@@ -1153,17 +2722,254 @@ pub(crate) mod test {
         write(base.join("max_battery_charge_level"), "10\n")
             .await
             .expect("write");
-
-        assert_eq!(get_max_charge_level().await.unwrap(), 10);
-
-        write(base.join("max_battery_charge_level"), "99\n")
+
+        assert_eq!(get_max_charge_level().await.unwrap(), 10);
+
+        write(base.join("max_battery_charge_level"), "99\n")
+            .await
+            .expect("write");
+
+        assert_eq!(get_max_charge_level().await.unwrap(), 99);
+
+        assert!(set_max_charge_level(101).await.is_err());
+        assert!(set_max_charge_level(-1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_max_charge_level_power_supply() {
+        let handle = testing::start();
+
+        let mut config = DeviceConfig::default();
+        config.battery_charge_limit = Some(BatteryChargeLimitConfig {
+            suggested_minimum_limit: None,
+            method: BatteryChargeLimitMethod::PowerSupply,
+            hwmon_name: None,
+            attribute: String::from("charge_control_end_threshold"),
+            charge_bypass: None,
+        });
+        handle.test.device_config.replace(Some(config));
+
+        assert!(get_max_charge_level().await.is_err());
+
+        let base = path(POWER_SUPPLY_PREFIX).join("BAT0");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("type"), "Battery\n").await.expect("write");
+        write(base.join("charge_control_end_threshold"), "80\n")
+            .await
+            .expect("write");
+
+        assert_eq!(get_max_charge_level().await.unwrap(), 80);
+
+        write(base.join("charge_control_end_threshold"), "100\n")
+            .await
+            .expect("write");
+
+        assert_eq!(get_max_charge_level().await.unwrap(), 0);
+
+        assert!(set_max_charge_level(101).await.is_err());
+        assert!(set_max_charge_level(-1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn charge_bypass() {
+        let handle = testing::start();
+
+        let mut config = DeviceConfig::default();
+        config.battery_charge_limit = Some(BatteryChargeLimitConfig {
+            suggested_minimum_limit: None,
+            method: BatteryChargeLimitMethod::PowerSupply,
+            hwmon_name: None,
+            attribute: String::from("charge_control_end_threshold"),
+            charge_bypass: None,
+        });
+        handle.test.device_config.replace(Some(config.clone()));
+
+        assert!(!is_charge_bypass_supported().await);
+        assert!(get_charge_bypass().await.is_err());
+        assert!(set_charge_bypass(true).await.is_err());
+
+        let bypass = ChargeBypassConfig {
+            firmware_attribute: String::from("lenovo-wmi-other-0"),
+            attribute: String::from("charge_mode"),
+            enabled: String::from("bypass"),
+            disabled: String::from("standard"),
+        };
+        let attribute = charge_bypass_path(&bypass);
+        config.battery_charge_limit.as_mut().unwrap().charge_bypass = Some(bypass);
+        handle.test.device_config.replace(Some(config));
+        assert!(is_charge_bypass_supported().await);
+
+        create_dir_all(attribute.parent().unwrap())
+            .await
+            .expect("create_dir_all");
+        write(&attribute, "standard\n").await.expect("write");
+        assert!(!get_charge_bypass().await.unwrap());
+
+        set_charge_bypass(true).await.expect("set_charge_bypass");
+        assert_eq!(read_to_string(&attribute).await.unwrap(), "bypass");
+        assert!(get_charge_bypass().await.unwrap());
+
+        set_charge_bypass(false).await.expect("set_charge_bypass");
+        assert!(!get_charge_bypass().await.unwrap());
+
+        write(&attribute, "fast\n").await.expect("write");
+        assert!(get_charge_bypass().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_battery() {
+        let _h = testing::start();
+
+        assert!(find_battery().await.is_err());
+
+        let controller = path(POWER_SUPPLY_PREFIX).join("controller0");
+        create_dir_all(&controller).await.expect("create_dir_all");
+        write(controller.join("type"), "Battery\n")
+            .await
+            .expect("write");
+        write(controller.join("scope"), "Device\n")
+            .await
+            .expect("write");
+        let ac = path(POWER_SUPPLY_PREFIX).join("ACAD");
+        create_dir_all(&ac).await.expect("create_dir_all");
+        write(ac.join("type"), "Mains\n").await.expect("write");
+        assert!(find_battery().await.is_err());
+
+        let base = path(POWER_SUPPLY_PREFIX).join("BAT1");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("type"), "Battery\n").await.expect("write");
+        assert_eq!(find_battery().await.unwrap(), base);
+
+        write(base.join("capacity"), "57\n").await.expect("write");
+        write(base.join("status"), "Not charging\n")
+            .await
+            .expect("write");
+        write(base.join("cycle_count"), "12\n")
+            .await
+            .expect("write");
+        write(base.join("charge_now"), "2850000\n")
+            .await
+            .expect("write");
+        write(base.join("charge_full"), "5000000\n")
+            .await
+            .expect("write");
+
+        assert_eq!(get_battery_capacity().await.unwrap(), 57);
+        assert_eq!(
+            get_battery_charge_state().await.unwrap(),
+            BatteryChargeState::NotCharging
+        );
+        assert_eq!(get_battery_cycle_count().await.unwrap(), 12);
+        assert_eq!(get_battery_charge_now().await.unwrap(), 2_850_000);
+        assert_eq!(get_battery_charge_full().await.unwrap(), 5_000_000);
+        assert_eq!(get_battery_health().await.unwrap(), BatteryHealth::Unknown);
+
+        remove_file(base.join("charge_now"))
+            .await
+            .expect("remove_file");
+        remove_file(base.join("charge_full"))
+            .await
+            .expect("remove_file");
+        assert!(get_battery_charge_now().await.is_err());
+        write(base.join("energy_now"), "22000000\n")
+            .await
+            .expect("write");
+        write(base.join("energy_full"), "40000000\n")
+            .await
+            .expect("write");
+        assert_eq!(get_battery_charge_now().await.unwrap(), 22_000_000);
+        assert_eq!(get_battery_charge_full().await.unwrap(), 40_000_000);
+
+        write(base.join("health"), "Overheat\n")
+            .await
+            .expect("write");
+        assert_eq!(get_battery_health().await.unwrap(), BatteryHealth::Overheat);
+
+        write(base.join("status"), "Sideways\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            get_battery_charge_state().await.unwrap(),
+            BatteryChargeState::Unknown
+        );
+
+        assert!(get_battery_power_now().await.is_err());
+        write(base.join("current_now"), "1500000\n")
+            .await
+            .expect("write");
+        write(base.join("voltage_now"), "7800000\n")
+            .await
+            .expect("write");
+        assert_eq!(get_battery_power_now().await.unwrap(), 11_700_000);
+        write(base.join("power_now"), "9250000\n")
+            .await
+            .expect("write");
+        assert_eq!(get_battery_power_now().await.unwrap(), 9_250_000);
+    }
+
+    #[tokio::test]
+    async fn on_battery() {
+        let _h = testing::start();
+
+        create_dir_all(path(POWER_SUPPLY_PREFIX))
+            .await
+            .expect("create_dir_all");
+        assert!(!is_on_battery().await.unwrap());
+
+        let base = path(POWER_SUPPLY_PREFIX).join("BAT1");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("type"), "Battery\n").await.expect("write");
+        write(base.join("status"), "Charging\n")
+            .await
+            .expect("write");
+        assert!(!is_on_battery().await.unwrap());
+        write(base.join("status"), "Discharging\n")
+            .await
+            .expect("write");
+        assert!(is_on_battery().await.unwrap());
+
+        // A controller's charger doesn't power the system
+        let controller = path(POWER_SUPPLY_PREFIX).join("controller0-usb");
+        create_dir_all(&controller).await.expect("create_dir_all");
+        write(controller.join("type"), "USB\n")
+            .await
+            .expect("write");
+        write(controller.join("scope"), "Device\n")
+            .await
+            .expect("write");
+        write(controller.join("online"), "1\n")
             .await
             .expect("write");
+        assert!(is_on_battery().await.unwrap());
+
+        // An external supply takes precedence over the battery status
+        let ac = path(POWER_SUPPLY_PREFIX).join("ACAD");
+        create_dir_all(&ac).await.expect("create_dir_all");
+        write(ac.join("type"), "Mains\n").await.expect("write");
+        write(ac.join("online"), "0\n").await.expect("write");
+        write(base.join("status"), "Not charging\n")
+            .await
+            .expect("write");
+        assert!(is_on_battery().await.unwrap());
+        write(ac.join("online"), "1\n").await.expect("write");
+        assert!(!is_on_battery().await.unwrap());
+    }
 
-        assert_eq!(get_max_charge_level().await.unwrap(), 99);
-
-        assert!(set_max_charge_level(101).await.is_err());
-        assert!(set_max_charge_level(-1).await.is_err());
+    #[test]
+    fn battery_charge_state_roundtrip() {
+        enum_roundtrip!(BatteryChargeState {
+            0: u32 = Unknown,
+            1: u32 = Charging,
+            2: u32 = Discharging,
+            3: u32 = NotCharging,
+            4: u32 = Full,
+            "Unknown": str = Unknown,
+            "Charging": str = Charging,
+            "Discharging": str = Discharging,
+            "Not charging": str = NotCharging,
+            "Full": str = Full,
+        });
+        assert!(BatteryChargeState::try_from(5).is_err());
     }
 
     #[tokio::test]
@@ -1250,7 +3056,8 @@ pub(crate) mod test {
             .await
             .expect("at");
 
-        let mut service = TdpManagerService::new(rx, &connection, &connection)
+        let (daemon_tx, _daemon_rx) = daemon::channel::<UserContext>();
+        let mut service = TdpManagerService::new(rx, &connection, &connection, daemon_tx)
             .await
             .expect("service");
         let task = tokio::spawn(async move {
@@ -1346,7 +3153,8 @@ pub(crate) mod test {
             .await
             .expect("at");
 
-        let mut service = TdpManagerService::new(rx, &connection, &connection)
+        let (daemon_tx, _daemon_rx) = daemon::channel::<UserContext>();
+        let mut service = TdpManagerService::new(rx, &connection, &connection, daemon_tx)
             .await
             .expect("service");
         let task = tokio::spawn(async move {
@@ -1383,6 +3191,229 @@ pub(crate) mod test {
         task.await.expect("exit").expect("exit2");
     }
 
+    #[tokio::test]
+    async fn test_app_profiles() {
+        let mut h = testing::start();
+        setup().await.expect("setup");
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let (tx, rx) = unbounded_channel();
+        let (fin_tx, fin_rx) = oneshot::channel();
+        let (start_tx, start_rx) = oneshot::channel();
+        let (reply_tx, mut reply_rx) = channel(1);
+
+        let iface = MockTdpLimit { queue: reply_tx };
+
+        let mut config = DeviceConfig::default();
+        config.tdp_limit = Some(TdpLimitConfig {
+            method: TdpLimitingMethod::AmdgpuHwmon,
+            range: Some(RangeConfig { min: 3, max: 15 }),
+            download_mode_limit: None,
+            firmware_attribute: None,
+        });
+        h.test.device_config.replace(Some(config));
+        let manager = tdp_limit_manager().await.unwrap();
+
+        connection
+            .request_name("com.steampowered.SteamOSManager1")
+            .await
+            .expect("reserve_name");
+        let object_server = connection.object_server();
+        object_server
+            .at("/com/steampowered/SteamOSManager1", iface)
+            .await
+            .expect("at");
+
+        let (daemon_tx, mut daemon_rx) = daemon::channel::<UserContext>();
+        let mut service = TdpManagerService::new(rx, &connection, &connection, daemon_tx)
+            .await
+            .expect("service");
+        let task = tokio::spawn(async move {
+            start_tx.send(()).unwrap();
+            tokio::select! {
+                r = service.run() => r,
+                _ = fin_rx => Ok(()),
+            }
+        });
+        start_rx.await.expect("start_rx");
+
+        sleep(Duration::from_millis(1)).await;
+
+        tx.send(TdpManagerCommand::LoadProfiles([(100, 5)].into()))
+            .unwrap();
+        tx.send(TdpManagerCommand::SetTdpLimit(12)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 12);
+        let Some(DaemonCommand::ContextCommand(UserCommand::SetTdpLimit(12))) =
+            daemon_rx.recv().await
+        else {
+            panic!("TDP limit not written");
+        };
+
+        let (os_tx, os_rx) = oneshot::channel();
+        tx.send(TdpManagerCommand::SetProfileForApp(200, 20, os_tx))
+            .unwrap();
+        assert!(os_rx.await.unwrap().is_err());
+
+        let (os_tx, os_rx) = oneshot::channel();
+        tx.send(TdpManagerCommand::SetProfileForApp(200, 8, os_tx))
+            .unwrap();
+        os_rx.await.unwrap().expect("set_profile_for_app");
+        let Some(DaemonCommand::ContextCommand(UserCommand::SetTdpProfiles(profiles))) =
+            daemon_rx.recv().await
+        else {
+            panic!("Profiles not written");
+        };
+        assert_eq!(profiles, [(100, 5), (200, 8)].into());
+
+        let (os_tx, os_rx) = oneshot::channel();
+        tx.send(TdpManagerCommand::ListProfiles(os_tx)).unwrap();
+        assert_eq!(os_rx.await.unwrap(), [(100, 5), (200, 8)].into());
+
+        tx.send(TdpManagerCommand::SetForegroundApp(100)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 5);
+
+        tx.send(TdpManagerCommand::SetForegroundApp(200)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+
+        tx.send(TdpManagerCommand::SetForegroundApp(300)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 12);
+
+        tx.send(TdpManagerCommand::SetForegroundApp(100)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 5);
+
+        tx.send(TdpManagerCommand::ClearProfileForApp(100)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 12);
+        let Some(DaemonCommand::ContextCommand(UserCommand::SetTdpProfiles(profiles))) =
+            daemon_rx.recv().await
+        else {
+            panic!("Profiles not written");
+        };
+        assert_eq!(profiles, [(200, 8)].into());
+
+        // A manual limit outlasts switching between profiled apps
+        tx.send(TdpManagerCommand::SetForegroundApp(200)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+
+        tx.send(TdpManagerCommand::SetTdpLimit(10)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 10);
+        let Some(DaemonCommand::ContextCommand(UserCommand::SetTdpLimit(10))) =
+            daemon_rx.recv().await
+        else {
+            panic!("TDP limit not written");
+        };
+
+        let (os_tx, os_rx) = oneshot::channel();
+        tx.send(TdpManagerCommand::SetProfileForApp(400, 6, os_tx))
+            .unwrap();
+        os_rx.await.unwrap().expect("set_profile_for_app");
+        let Some(DaemonCommand::ContextCommand(UserCommand::SetTdpProfiles(_))) =
+            daemon_rx.recv().await
+        else {
+            panic!("Profiles not written");
+        };
+
+        tx.send(TdpManagerCommand::SetForegroundApp(400)).unwrap();
+        let (os_tx, os_rx) = oneshot::channel();
+        tx.send(TdpManagerCommand::ListProfiles(os_tx)).unwrap();
+        os_rx.await.unwrap();
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 10);
+
+        tx.send(TdpManagerCommand::SetForegroundApp(300)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 10);
+
+        tx.send(TdpManagerCommand::SetForegroundApp(200)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+
+        fin_tx.send(()).expect("fin");
+        task.await.expect("exit").expect("exit2");
+    }
+
+    #[tokio::test]
+    async fn test_battery_limit() {
+        let mut h = testing::start();
+        setup().await.expect("setup");
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let (tx, rx) = unbounded_channel();
+        let (fin_tx, fin_rx) = oneshot::channel();
+        let (start_tx, start_rx) = oneshot::channel();
+        let (reply_tx, mut reply_rx) = channel(1);
+
+        let iface = MockTdpLimit { queue: reply_tx };
+
+        let mut config = DeviceConfig::default();
+        config.tdp_limit = Some(TdpLimitConfig {
+            method: TdpLimitingMethod::AmdgpuHwmon,
+            range: Some(RangeConfig { min: 3, max: 15 }),
+            download_mode_limit: None,
+            firmware_attribute: None,
+        });
+        h.test.device_config.replace(Some(config));
+        let manager = tdp_limit_manager().await.unwrap();
+
+        connection
+            .request_name("com.steampowered.SteamOSManager1")
+            .await
+            .expect("reserve_name");
+        let object_server = connection.object_server();
+        object_server
+            .at("/com/steampowered/SteamOSManager1", iface)
+            .await
+            .expect("at");
+
+        let (daemon_tx, _daemon_rx) = daemon::channel::<UserContext>();
+        let mut service = TdpManagerService::new(rx, &connection, &connection, daemon_tx)
+            .await
+            .expect("service");
+        let task = tokio::spawn(async move {
+            start_tx.send(()).unwrap();
+            tokio::select! {
+                r = service.run() => r,
+                _ = fin_rx => Ok(()),
+            }
+        });
+        start_rx.await.expect("start_rx");
+
+        sleep(Duration::from_millis(1)).await;
+
+        tx.send(TdpManagerCommand::SetTdpLimit(12)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 12);
+
+        tx.send(TdpManagerCommand::SetBatteryLimit(Some(8)))
+            .unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+
+        // Limits above the clamp are held back, lower ones go through
+        tx.send(TdpManagerCommand::SetTdpLimit(15)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+        tx.send(TdpManagerCommand::SetTdpLimit(5)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 5);
+        tx.send(TdpManagerCommand::SetTdpLimit(14)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 8);
+
+        tx.send(TdpManagerCommand::SetBatteryLimit(None)).unwrap();
+        reply_rx.recv().await;
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 14);
+
+        fin_tx.send(()).expect("fin");
+        task.await.expect("exit").expect("exit2");
+    }
+
     #[tokio::test]
     async fn test_firmware_attribute_tdp_limiter() {
         let h = testing::start();
@@ -1400,6 +3431,7 @@ pub(crate) mod test {
             firmware_attribute: Some(FirmwareAttributeConfig {
                 attribute: String::from("tdp0"),
                 performance_profile: Some(String::from("custom")),
+                limits: FirmwareAttributeLimits::default(),
             }),
         });
         h.test.device_config.replace(Some(config));
@@ -1491,6 +3523,7 @@ pub(crate) mod test {
             firmware_attribute: Some(FirmwareAttributeConfig {
                 attribute: String::from("tdp0"),
                 performance_profile: None,
+                limits: FirmwareAttributeLimits::default(),
             }),
         });
         h.test.device_config.replace(Some(config));
@@ -1553,4 +3586,236 @@ pub(crate) mod test {
         manager.set_tdp_limit(2).await.unwrap_err();
         assert_eq!(manager.get_tdp_limit().await.unwrap(), 15);
     }
+
+    #[tokio::test]
+    async fn test_firmware_attribute_separate_tdp_limits() {
+        let h = testing::start();
+        setup().await.expect("setup");
+
+        let mut config = DeviceConfig::default();
+        config.tdp_limit = Some(TdpLimitConfig {
+            method: TdpLimitingMethod::FirmwareAttribute,
+            range: Some(RangeConfig { min: 3, max: 15 }),
+            download_mode_limit: None,
+            firmware_attribute: Some(FirmwareAttributeConfig {
+                attribute: String::from("tdp0"),
+                performance_profile: None,
+                limits: FirmwareAttributeLimits::default(),
+            }),
+        });
+        h.test.device_config.replace(Some(config.clone()));
+
+        let attributes_base = path(FirmwareAttributeLimitManager::PREFIX)
+            .join("tdp0")
+            .join("attributes");
+        for (suffix, min, max) in [
+            (FirmwareAttributeLimitManager::SPL_SUFFIX, "6\n", "20\n"),
+            (FirmwareAttributeLimitManager::SPPT_SUFFIX, "8\n", "25\n"),
+            (FirmwareAttributeLimitManager::FPPT_SUFFIX, "10\n", "30\n"),
+        ] {
+            let base = attributes_base.join(suffix);
+            create_dir_all(&base).await.unwrap();
+            write_synced(base.join("current_value"), b"10\n")
+                .await
+                .unwrap();
+            write_synced(base.join("min_value"), min.as_bytes())
+                .await
+                .unwrap();
+            write_synced(base.join("max_value"), max.as_bytes())
+                .await
+                .unwrap();
+        }
+
+        let manager = tdp_limit_manager().await.unwrap();
+        assert_eq!(
+            manager.get_tdp_limits_range().await.unwrap(),
+            (
+                TdpLimits {
+                    spl: 6,
+                    sppt: 8,
+                    fppt: 10
+                },
+                TdpLimits {
+                    spl: 20,
+                    sppt: 25,
+                    fppt: 30
+                }
+            )
+        );
+
+        let limits = TdpLimits {
+            spl: 12,
+            sppt: 18,
+            fppt: 24,
+        };
+        manager.set_tdp_limits(limits).await.unwrap();
+        assert_eq!(manager.get_tdp_limits().await.unwrap(), limits);
+        assert_eq!(manager.get_tdp_limit().await.unwrap(), 12);
+
+        // Nothing is written if any of the limits is out of range
+        manager
+            .set_tdp_limits(TdpLimits {
+                spl: 15,
+                sppt: 15,
+                fppt: 35,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(manager.get_tdp_limits().await.unwrap(), limits);
+
+        config
+            .tdp_limit
+            .as_mut()
+            .unwrap()
+            .firmware_attribute
+            .as_mut()
+            .unwrap()
+            .limits
+            .sppt = false;
+        h.test.device_config.replace(Some(config));
+
+        let manager = tdp_limit_manager().await.unwrap();
+        manager
+            .set_tdp_limits(TdpLimits {
+                spl: 14,
+                sppt: 0,
+                fppt: 20,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.get_tdp_limits().await.unwrap(),
+            TdpLimits {
+                spl: 14,
+                sppt: 0,
+                fppt: 20
+            }
+        );
+        assert_eq!(
+            read_to_string(
+                attributes_base
+                    .join(FirmwareAttributeLimitManager::SPPT_SUFFIX)
+                    .join("current_value")
+            )
+            .await
+            .unwrap(),
+            "18"
+        );
+        assert_eq!(manager.get_tdp_limits_range().await.unwrap().1.sppt, 0);
+    }
+
+    #[tokio::test]
+    async fn sysfs_writer_coalesce() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("new_dbus");
+
+        let queue = Arc::new(SysfsWriterQueue::new(SysfsWriterConfig {
+            coalesce_window: 200,
+            max_write_rate: 0,
+        }));
+        let mut service = SysfsWriterService {
+            queue: queue.clone(),
+            connection,
+        };
+        let task = tokio::spawn(async move { service.run().await });
+
+        let base = path("/sys/test");
+        create_dir_all(&base).await.expect("create_dir_all");
+        let file = base.join("value");
+
+        // Writes are only held back while the writer is busy
+        queue.writing.store(true, Ordering::Release);
+        let first = queue.send(file.clone(), vec![b"1".to_vec()]).await;
+        let second = queue.send(file.clone(), vec![b"2".to_vec()]).await;
+        assert!(matches!(first.await, Ok(SysfsWritten::Superseded)));
+        assert!(matches!(second.await, Ok(SysfsWritten::Written(Ok(())))));
+        assert_eq!(read_to_string(&file).await.unwrap(), "2");
+        assert!(!queue.writing.load(Ordering::Acquire));
+
+        let start = Instant::now();
+        let written = queue.send(file.clone(), vec![b"3".to_vec()]).await;
+        assert!(matches!(written.await, Ok(SysfsWritten::Written(Ok(())))));
+        assert!(start.elapsed() < Duration::from_millis(200));
+        assert_eq!(read_to_string(&file).await.unwrap(), "3");
+
+        let written = queue
+            .send(file.clone(), vec![b"a\n".to_vec(), b"b\n".to_vec()])
+            .await;
+        assert!(matches!(written.await, Ok(SysfsWritten::Written(Ok(())))));
+        assert_eq!(read_to_string(&file).await.unwrap(), "a\nb\n");
+
+        let written = queue
+            .send(base.join("missing").join("value"), vec![b"1".to_vec()])
+            .await;
+        assert!(matches!(written.await, Ok(SysfsWritten::Written(Err(_)))));
+
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn sysfs_writer_rate_limit() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("new_dbus");
+
+        let queue = Arc::new(SysfsWriterQueue::new(SysfsWriterConfig {
+            coalesce_window: 0,
+            max_write_rate: 10,
+        }));
+        let mut service = SysfsWriterService {
+            queue: queue.clone(),
+            connection,
+        };
+        let task = tokio::spawn(async move { service.run().await });
+
+        let base = path("/sys/test");
+        create_dir_all(&base).await.expect("create_dir_all");
+
+        let start = Instant::now();
+        let first = queue.send(base.join("a"), vec![b"1".to_vec()]).await;
+        let second = queue.send(base.join("b"), vec![b"1".to_vec()]).await;
+        assert!(matches!(first.await, Ok(SysfsWritten::Written(Ok(())))));
+        assert!(matches!(second.await, Ok(SysfsWritten::Written(Ok(())))));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        task.abort();
+    }
+
+    #[test]
+    fn power_presets() {
+        let mut state = PowerPresetsState::default();
+        assert_eq!(state.names(), vec!["balanced", "performance", "quiet"]);
+        assert_eq!(
+            state.preset("quiet"),
+            Some(PowerPreset {
+                performance_profile: Some(String::from("low-power")),
+                cpu_scaling_governor: Some(String::from("powersave")),
+                gpu_performance_level: Some(String::from("low")),
+                tdp_limit: None,
+                refresh_rate_cap: None,
+            })
+        );
+        assert!(state.preset("custom").is_none());
+
+        let custom = PowerPreset {
+            tdp_limit: Some(12),
+            refresh_rate_cap: Some(40),
+            ..PowerPreset::default()
+        };
+        state.presets.insert(String::from("custom"), custom.clone());
+        state.presets.insert(String::from("quiet"), custom.clone());
+        assert_eq!(
+            state.names(),
+            vec!["balanced", "custom", "performance", "quiet"]
+        );
+        assert_eq!(state.preset("custom"), Some(custom.clone()));
+        assert_eq!(state.preset("quiet"), Some(custom));
+
+        let state: PowerPresetsState =
+            toml::from_str(toml::to_string(&state).expect("to_string").as_str()).expect("from_str");
+        assert_eq!(state.preset("custom").and_then(|p| p.tdp_limit), Some(12));
+        assert_eq!(
+            state.preset("custom").and_then(|p| p.refresh_rate_cap),
+            Some(40)
+        );
+    }
 }
@@ -0,0 +1,263 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::Result;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tracing::{debug, error, info, warn};
+use zbus::Connection;
+
+use crate::hardware::{device_config, PowerSourceConfig};
+use crate::manager::user::{PowerPresets1, PowerSource1, PowerSource1Signals, MANAGER_PATH};
+use crate::policy::Guarded;
+use crate::power::{get_charger_info, is_on_battery, ChargerInfo};
+use crate::Service;
+
+pub(crate) struct PowerSourceMonitor {
+    session: Connection,
+    config: PowerSourceConfig,
+    on_battery: Option<bool>,
+    charger: Option<ChargerInfo>,
+    events: UnboundedReceiver<()>,
+}
+
+impl PowerSourceMonitor {
+    /// `events` is fed a message for every power supply event seen by the udev monitor
+    pub(crate) async fn init(
+        session: &Connection,
+        events: UnboundedReceiver<()>,
+    ) -> Result<PowerSourceMonitor> {
+        let config = device_config()
+            .await?
+            .as_ref()
+            .and_then(|config| config.power_source.clone())
+            .unwrap_or_default();
+        Ok(PowerSourceMonitor {
+            session: session.clone(),
+            config,
+            on_battery: None,
+            charger: None,
+            events,
+        })
+    }
+
+    fn preset(&self, on_battery: bool) -> Option<&str> {
+        if on_battery {
+            self.config.battery_preset.as_deref()
+        } else {
+            self.config.ac_preset.as_deref()
+        }
+    }
+
+    /// Re-read the power source, returning the new state if it has changed
+    async fn update(&mut self) -> Result<Option<bool>> {
+        let on_battery = is_on_battery().await?;
+        let previous = self.on_battery.replace(on_battery);
+        // The initial state is only recorded, so that a preset picked by the user isn't overridden
+        match previous {
+            Some(previous) if previous != on_battery => Ok(Some(on_battery)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Re-read what was negotiated with the charger, returning whether it has changed
+    async fn update_charger(&mut self) -> Result<bool> {
+        let charger = get_charger_info().await?;
+        if charger == self.charger {
+            return Ok(false);
+        }
+        self.charger = charger;
+        Ok(true)
+    }
+
+    async fn charger_changed(&self) -> Result<()> {
+        let charger = self.charger.clone().unwrap_or_default();
+        debug!(
+            "Charger now provides {} mV at up to {} mA",
+            charger.voltage, charger.current
+        );
+        let power_source = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<PowerSource1>>(MANAGER_PATH)
+            .await?;
+        let ctx = power_source.signal_emitter();
+        {
+            let power_source = power_source.get().await;
+            power_source.charger_type_changed(ctx).await?;
+            power_source.charger_voltage_changed(ctx).await?;
+            power_source.charger_current_changed(ctx).await?;
+            power_source.charger_power_changed(ctx).await?;
+        }
+        ctx.charger_changed(charger.voltage, charger.current, charger.power())
+            .await?;
+        Ok(())
+    }
+
+    async fn switch(&self, on_battery: bool) -> Result<()> {
+        info!(
+            "Switched to {} power",
+            if on_battery { "battery" } else { "AC" }
+        );
+        let object_server = self.session.object_server();
+        let power_source = object_server
+            .interface::<_, Guarded<PowerSource1>>(MANAGER_PATH)
+            .await?;
+        let ctx = power_source.signal_emitter();
+        power_source.get().await.on_battery_changed(ctx).await?;
+        ctx.power_source_changed(on_battery).await?;
+
+        let Some(name) = self.preset(on_battery) else {
+            return Ok(());
+        };
+        let power_presets = object_server
+            .interface::<_, Guarded<PowerPresets1>>(MANAGER_PATH)
+            .await?;
+        if let Err(e) = power_presets
+            .get()
+            .await
+            .apply(name, &self.session, power_presets.signal_emitter())
+            .await
+        {
+            error!("Failed to apply power preset {name}: {e}");
+        }
+        Ok(())
+    }
+}
+
+impl Service for PowerSourceMonitor {
+    const NAME: &'static str = "power-source-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        if let Err(e) = self.update().await {
+            warn!("Failed to read the power source: {e}");
+        }
+        if let Err(e) = self.update_charger().await {
+            warn!("Failed to read the charger info: {e}");
+        }
+        while self.events.recv().await.is_some() {
+            // A single plug or unplug raises a burst of events, which only need one update
+            while self.events.try_recv().is_ok() {}
+            match self.update().await {
+                Ok(Some(on_battery)) => {
+                    if let Err(e) = self.switch(on_battery).await {
+                        warn!("Failed to switch power source: {e}");
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => warn!("Failed to read the power source: {e}"),
+            }
+            match self.update_charger().await {
+                Ok(true) => {
+                    if let Err(e) = self.charger_changed().await {
+                        warn!("Failed to announce charger change: {e}");
+                    }
+                }
+                Ok(false) => (),
+                Err(e) => warn!("Failed to read the charger info: {e}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hardware::DeviceConfig;
+    use crate::path;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn transitions() {
+        let mut h = testing::start();
+
+        let base = path("/sys/class/power_supply/BAT1");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("type"), "Battery\n").await.expect("write");
+        let ac = path("/sys/class/power_supply/ACAD");
+        create_dir_all(&ac).await.expect("create_dir_all");
+        write(ac.join("type"), "Mains\n").await.expect("write");
+        write(ac.join("online"), "1\n").await.expect("write");
+
+        let mut config = DeviceConfig::default();
+        config.power_source = Some(PowerSourceConfig {
+            ac_preset: None,
+            battery_preset: Some(String::from("quiet")),
+        });
+        h.test.device_config.replace(Some(config));
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut monitor = PowerSourceMonitor::init(&connection, unbounded_channel().1)
+            .await
+            .expect("init");
+        assert_eq!(monitor.preset(true), Some("quiet"));
+        assert_eq!(monitor.preset(false), None);
+
+        // The initial state isn't a transition
+        assert_eq!(monitor.update().await.unwrap(), None);
+        assert_eq!(monitor.on_battery, Some(false));
+
+        write(ac.join("online"), "0\n").await.expect("write");
+        assert_eq!(monitor.update().await.unwrap(), Some(true));
+        assert_eq!(monitor.update().await.unwrap(), None);
+
+        write(ac.join("online"), "1\n").await.expect("write");
+        assert_eq!(monitor.update().await.unwrap(), Some(false));
+    }
+
+    #[tokio::test]
+    async fn charger() {
+        let mut h = testing::start();
+
+        let base = path("/sys/class/power_supply/BAT1");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("type"), "Battery\n").await.expect("write");
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut monitor = PowerSourceMonitor::init(&connection, unbounded_channel().1)
+            .await
+            .expect("init");
+        assert!(!monitor.update_charger().await.unwrap());
+        assert_eq!(monitor.charger, None);
+
+        let usb = path("/sys/class/power_supply/ucsi-source-psy-USBC000:001");
+        create_dir_all(&usb).await.expect("create_dir_all");
+        write(usb.join("type"), "USB\n").await.expect("write");
+        write(usb.join("online"), "1\n").await.expect("write");
+        write(usb.join("usb_type"), "C [PD] PD_PPS\n")
+            .await
+            .expect("write");
+        write(usb.join("voltage_now"), "20000000\n")
+            .await
+            .expect("write");
+        write(usb.join("current_max"), "2250000\n")
+            .await
+            .expect("write");
+        assert!(monitor.update_charger().await.unwrap());
+        let charger = monitor.charger.clone().unwrap();
+        assert_eq!(charger.usb_type, "PD");
+        assert_eq!(charger.voltage, 20000);
+        assert_eq!(charger.current, 2250);
+        assert_eq!(charger.power(), 45000);
+        assert!(!monitor.update_charger().await.unwrap());
+
+        // Renegotiating to a lower profile
+        write(usb.join("voltage_now"), "15000000\n")
+            .await
+            .expect("write");
+        write(usb.join("current_max"), "3000000\n")
+            .await
+            .expect("write");
+        assert!(monitor.update_charger().await.unwrap());
+        assert_eq!(monitor.charger.as_ref().unwrap().power(), 45000);
+
+        write(usb.join("online"), "0\n").await.expect("write");
+        assert!(monitor.update_charger().await.unwrap());
+        assert_eq!(monitor.charger, None);
+    }
+}
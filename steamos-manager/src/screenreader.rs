@@ -22,18 +22,29 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
 use std::sync::LazyLock;
+use std::time::Duration;
 use strum::{Display, EnumString};
-use tokio::fs::{read_to_string, write};
-use tracing::{error, info, trace, warn};
+use tokio::fs::{create_dir_all, read_to_string, try_exists, write};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::select;
+use tokio::sync::Notify;
+use tokio::time::{sleep, sleep_until, Instant};
+use tracing::{debug, error, info, trace, warn};
 #[cfg(not(test))]
 use xdg::BaseDirectories;
 use zbus::Connection;
 
+use crate::manager::user::{ScreenReader0, MANAGER_PATH};
 #[cfg(test)]
 use crate::path;
+use crate::policy::Guarded;
+use crate::process::script_output;
 use crate::systemd::SystemdUnit;
 use crate::uinput::UInputDevice;
+use crate::Service;
 
 #[cfg(test)]
 const TEST_ORCA_SETTINGS: &str = "../data/test-orca-settings.conf";
@@ -48,17 +59,30 @@ const TEST_VOICE_VARIANT: &str = "testvoicevariant";
 
 #[cfg(not(test))]
 const ORCA_SETTINGS: &str = "orca/user-settings.conf";
+const VOICE_DEFAULTS_FILE: &str = "steamos-manager/screen-reader-voices.json";
 const PITCH_SETTING: &str = "average-pitch";
 const RATE_SETTING: &str = "rate";
 const VOLUME_SETTING: &str = "gain";
 const FAMILY_SETTING: &str = "family";
 const VOICE_NAME_SETTING: &str = "name";
+const VOICE_LANG_SETTING: &str = "lang";
+const VOICE_DIALECT_SETTING: &str = "dialect";
+const VOICE_GENDER_SETTING: &str = "gender";
+const VOICE_VARIANT_SETTING: &str = "variant";
 const ENABLE_SETTING: &str = "enableSpeech";
 
 const A11Y_SETTING: &str = "org.gnome.desktop.a11y.applications";
 const SCREEN_READER_SETTING: &str = "screen-reader-enabled";
 const KEYBOARD_NAME: &str = "steamos-manager";
 
+const PACTL_PATH: &str = "/usr/bin/pactl";
+/// A single utterance raises a burst of stream events, which only need one check
+const SPEECH_DEBOUNCE: Duration = Duration::from_millis(100);
+/// How long to wait before watching again after pactl exits, e.g. when PipeWire restarts
+const PACTL_RESTART_DELAY: Duration = Duration::from_secs(5);
+
+static ENABLED_CHANGED: Notify = Notify::const_new();
+
 const PITCH_DEFAULT: f64 = 5.0;
 const RATE_DEFAULT: f64 = 50.0;
 const VOLUME_DEFAULT: f64 = 10.0;
@@ -104,9 +128,12 @@ pub(crate) struct OrcaManager<'dbus> {
     enabled: bool,
     mode: ScreenReaderMode,
     voice: String,
+    voice_locale: String,
     keyboard: UInputDevice,
     voices: HashMap<String, Voice>,
     voices_by_language: HashMap<String, Vec<String>>,
+    /// The voice last selected for each locale
+    voice_defaults: HashMap<String, String>,
 }
 
 fn default_map() -> Value {
@@ -124,14 +151,20 @@ impl<'dbus> OrcaManager<'dbus> {
             // Always start in browse mode for now, since we have no storage to remember this property
             mode: ScreenReaderMode::Browse,
             voice: String::new(),
+            voice_locale: String::new(),
             keyboard: UInputDevice::new()?,
             voices: HashMap::new(),
             voices_by_language: HashMap::new(),
+            voice_defaults: HashMap::new(),
         };
         let _ = manager
             .load_values()
             .await
             .inspect_err(|e| warn!("Failed to load orca configuration: {e}"));
+        let _ = manager
+            .load_voice_defaults()
+            .await
+            .inspect_err(|e| warn!("Failed to load screen reader voice defaults: {e}"));
         let a11ysettings = Settings::new(A11Y_SETTING);
         manager.enabled = a11ysettings.boolean(SCREEN_READER_SETTING);
         manager.keyboard.set_name(KEYBOARD_NAME.to_string())?;
@@ -152,6 +185,10 @@ impl<'dbus> OrcaManager<'dbus> {
             Ok(()) => trace!("Voice list loaded"),
             Err(e) => error!("Unable to init voice list: {e}"),
         }
+        let _ = manager
+            .restore_voice()
+            .await
+            .inspect_err(|e| warn!("Failed to restore screen reader voice: {e}"));
 
         Ok(manager)
     }
@@ -214,6 +251,20 @@ impl<'dbus> OrcaManager<'dbus> {
         Ok(path(ORCA_SETTINGS))
     }
 
+    #[cfg(not(test))]
+    fn voice_defaults_path() -> Result<PathBuf> {
+        let xdg_base = BaseDirectories::new();
+        Ok(xdg_base
+            .get_config_home()
+            .ok_or(anyhow!("No XDG_CONFIG_HOME found"))?
+            .join(VOICE_DEFAULTS_FILE))
+    }
+
+    #[cfg(test)]
+    fn voice_defaults_path() -> Result<PathBuf> {
+        Ok(path(VOICE_DEFAULTS_FILE))
+    }
+
     pub fn enabled(&self) -> bool {
         self.enabled
     }
@@ -240,6 +291,7 @@ impl<'dbus> OrcaManager<'dbus> {
             self.stop_orca().await?;
         }
         self.enabled = enable;
+        ENABLED_CHANGED.notify_one();
         Ok(())
     }
 
@@ -251,13 +303,60 @@ impl<'dbus> OrcaManager<'dbus> {
         let properties = self
             .voices
             .get(voice)
-            .ok_or(anyhow!("Invalid voice specified"))?;
-        self.set_orca_voice(properties).await?;
+            .ok_or(anyhow!("Invalid voice specified"))?
+            .clone();
+        self.set_orca_voice(&properties).await?;
         self.voice = voice.to_string();
+        self.voice_locale = properties.language.clone();
+        self.voice_defaults
+            .insert(properties.language, voice.to_string());
+        self.save_voice_defaults().await?;
         Self::reload_orca().await?;
         Ok(())
     }
 
+    pub fn voice_locale(&self) -> &str {
+        self.voice_locale.as_str()
+    }
+
+    /// Switch to the voice last used for a locale, or its first voice if none was
+    pub async fn set_voice_locale(&mut self, locale: &str) -> Result<()> {
+        let voice = self
+            .default_voice_for_locale(locale)
+            .ok_or(anyhow!("No voices available for locale {locale}"))?
+            .to_string();
+        self.set_voice(voice.as_str()).await
+    }
+
+    fn default_voice_for_locale(&self, locale: &str) -> Option<&str> {
+        self.voice_defaults
+            .get(locale)
+            .filter(|voice| self.voices.contains_key(voice.as_str()))
+            .or_else(|| self.voices_by_language.get(locale)?.first())
+            .map(String::as_str)
+    }
+
+    /// Make sure the voice loaded from the orca settings is still available, falling back to
+    /// the default voice for its locale if it isn't
+    async fn restore_voice(&mut self) -> Result<()> {
+        if self.voices.is_empty() || self.voices.contains_key(self.voice.as_str()) {
+            return Ok(());
+        }
+        let Some(voice) = self.default_voice_for_locale(self.voice_locale.as_str()) else {
+            return Ok(());
+        };
+        let voice = voice.to_string();
+        info!(
+            "Voice {} is no longer available, switching to {voice}",
+            self.voice
+        );
+        let properties = self.voices[voice.as_str()].clone();
+        self.set_orca_voice(&properties).await?;
+        self.voice = voice;
+        self.voice_locale = properties.language;
+        Ok(())
+    }
+
     pub fn pitch(&self) -> f64 {
         self.pitch
     }
@@ -498,6 +597,14 @@ impl<'dbus> OrcaManager<'dbus> {
             } else {
                 warn!("Unable to load default voice family name from orca user-settings.conf");
             }
+            let language = family
+                .get(VOICE_LANG_SETTING)
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            self.voice_locale = match family.get(VOICE_DIALECT_SETTING).and_then(Value::as_str) {
+                Some(dialect) if !dialect.is_empty() => format!("{language}-{dialect}"),
+                _ => language.to_string(),
+            };
         } else {
             warn!("Unable to load default voice family from orca user-settings.conf");
         }
@@ -550,10 +657,17 @@ impl<'dbus> OrcaManager<'dbus> {
         let mut_family = family.as_object_mut().ok_or(anyhow!(
             "orca user-settings.conf default voice family is not an object"
         ))?;
-        mut_family.insert("name".to_string(), voice.name.clone().into());
-        mut_family.insert("lang".to_string(), language.into());
-        mut_family.insert("variant".to_string(), voice.variant.clone().into());
-        mut_family.insert("dialect".to_string(), dialect.into());
+        mut_family.insert(VOICE_NAME_SETTING.to_string(), voice.name.clone().into());
+        mut_family.insert(VOICE_LANG_SETTING.to_string(), language.into());
+        mut_family.insert(
+            VOICE_VARIANT_SETTING.to_string(),
+            voice.variant.clone().into(),
+        );
+        mut_family.insert(VOICE_DIALECT_SETTING.to_string(), dialect.into());
+        // speech-dispatcher doesn't report a gender, but orca expects the key to exist
+        mut_family
+            .entry(VOICE_GENDER_SETTING)
+            .or_insert(Value::Null);
 
         // Set established property
         default_voice
@@ -567,6 +681,28 @@ impl<'dbus> OrcaManager<'dbus> {
         Ok(write(path, data.as_bytes()).await?)
     }
 
+    async fn load_voice_defaults(&mut self) -> Result<()> {
+        let path = Self::voice_defaults_path()?;
+        let data = match read_to_string(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Unable to read from {}", path.display()))
+            }
+        };
+        self.voice_defaults = serde_json::from_str(&data)?;
+        Ok(())
+    }
+
+    async fn save_voice_defaults(&self) -> Result<()> {
+        let path = Self::voice_defaults_path()?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+        let data = serde_json::to_string_pretty(&self.voice_defaults)?;
+        Ok(write(path, data.as_bytes()).await?)
+    }
+
     async fn set_orca_option(&self, option: &str, value: f64) -> Result<()> {
         if let Some(range) = VALID_SETTINGS.get(option) {
             ensure!(
@@ -626,6 +762,152 @@ impl<'dbus> OrcaManager<'dbus> {
     }
 }
 
+/// Whether any of the audio streams listed by `pactl --format=json list sink-inputs` is
+/// speech-dispatcher, or one of its output modules, playing back speech
+fn speech_active(sink_inputs: &str) -> Result<bool> {
+    let sink_inputs: Vec<Value> = serde_json::from_str(sink_inputs)?;
+    Ok(sink_inputs.iter().any(|sink_input| {
+        let properties = &sink_input["properties"];
+        let is_speech = properties["application.name"]
+            .as_str()
+            .is_some_and(|name| name.starts_with("speech-dispatcher"))
+            || properties["application.process.binary"]
+                .as_str()
+                .is_some_and(|binary| binary.starts_with("sd_"));
+        is_speech && !sink_input["corked"].as_bool().unwrap_or_default()
+    }))
+}
+
+/// Orca doesn't report when it's speaking, so follow the audio streams of speech-dispatcher
+/// instead, which all of its speech goes through
+pub(crate) struct SpeechMonitor {
+    session: Connection,
+}
+
+impl SpeechMonitor {
+    pub(crate) async fn init(session: &Connection) -> Result<SpeechMonitor> {
+        // The screen reader interface is only available in game mode
+        session
+            .object_server()
+            .interface::<_, Guarded<ScreenReader0>>(MANAGER_PATH)
+            .await?;
+        ensure!(try_exists(PACTL_PATH).await?, "pactl not found");
+        Ok(SpeechMonitor {
+            session: session.clone(),
+        })
+    }
+
+    async fn enabled(&self) -> bool {
+        match self
+            .session
+            .object_server()
+            .interface::<_, Guarded<ScreenReader0>>(MANAGER_PATH)
+            .await
+        {
+            Ok(interface) => interface.get().await.screen_reader_enabled(),
+            Err(_) => false,
+        }
+    }
+
+    async fn set_speaking(&self, speaking: bool) -> Result<()> {
+        let interface = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<ScreenReader0>>(MANAGER_PATH)
+            .await?;
+        interface
+            .get_mut()
+            .await
+            .set_speaking(speaking, interface.signal_emitter())
+            .await?;
+        Ok(())
+    }
+
+    async fn update(&self) {
+        let speaking = match script_output(PACTL_PATH, &["--format=json", "list", "sink-inputs"])
+            .await
+            .and_then(|output| speech_active(output.as_str()))
+        {
+            Ok(speaking) => speaking,
+            Err(e) => {
+                warn!("Failed to list audio streams: {e}");
+                return;
+            }
+        };
+        if let Err(e) = self.set_speaking(speaking).await {
+            warn!("Failed to update speaking state: {e}");
+        }
+    }
+
+    async fn watch(&self) -> Result<ExitStatus> {
+        let mut child = Command::new(PACTL_PATH)
+            .arg("subscribe")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(anyhow!("Couldn't read pactl output"))?;
+        let mut lines = BufReader::new(stdout).lines();
+
+        self.update().await;
+        let mut pending = None;
+        loop {
+            select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        break;
+                    };
+                    // e.g. "Event 'new' on sink-input #42"
+                    if !line.contains(" on sink-input ") {
+                        continue;
+                    }
+                    debug!("Got audio stream event {line}");
+                    if pending.is_none() {
+                        pending = Some(Instant::now() + SPEECH_DEBOUNCE);
+                    }
+                }
+                () = sleep_until(pending.unwrap_or_else(Instant::now)), if pending.is_some() => {
+                    pending = None;
+                    self.update().await;
+                }
+            }
+        }
+        Ok(child.wait().await?)
+    }
+}
+
+impl Service for SpeechMonitor {
+    const NAME: &'static str = "speech-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            if !self.enabled().await {
+                if let Err(e) = self.set_speaking(false).await {
+                    warn!("Failed to update speaking state: {e}");
+                }
+                ENABLED_CHANGED.notified().await;
+                continue;
+            }
+            select! {
+                result = self.watch() => {
+                    match result {
+                        Ok(status) => debug!("pactl exited: {status}"),
+                        Err(e) => warn!("Failed to watch audio streams: {e}"),
+                    }
+                    select! {
+                        () = sleep(PACTL_RESTART_DELAY) => (),
+                        () = ENABLED_CHANGED.notified() => (),
+                    }
+                }
+                () = ENABLED_CHANGED.notified() => (),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -684,6 +966,44 @@ mod test {
         assert_eq!(unit.active().await.unwrap(), false);
     }
 
+    #[test]
+    fn test_speech_active() {
+        let idle = r#"[
+            {
+                "index": 42,
+                "corked": false,
+                "properties": {"application.name": "Firefox"}
+            },
+            {
+                "index": 43,
+                "corked": true,
+                "properties": {"application.name": "speech-dispatcher-espeak-ng"}
+            }
+        ]"#;
+        assert!(!speech_active(idle).unwrap());
+
+        let speaking = r#"[
+            {
+                "index": 44,
+                "corked": false,
+                "properties": {"application.name": "speech-dispatcher-espeak-ng"}
+            }
+        ]"#;
+        assert!(speech_active(speaking).unwrap());
+
+        let module = r#"[
+            {
+                "index": 45,
+                "corked": false,
+                "properties": {"application.process.binary": "sd_espeak-ng"}
+            }
+        ]"#;
+        assert!(speech_active(module).unwrap());
+
+        assert!(!speech_active("[]").unwrap());
+        assert!(speech_active("").is_err());
+    }
+
     #[tokio::test]
     async fn test_pitch() {
         let mut h = testing::start();
@@ -792,6 +1112,83 @@ mod test {
         assert!(nofile_result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_voice_persistence() {
+        let mut h = testing::start();
+        copy(TEST_ORCA_SETTINGS, h.test.path().join(ORCA_SETTINGS))
+            .await
+            .unwrap();
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut manager = OrcaManager::new(&connection)
+            .await
+            .expect("OrcaManager::new");
+        manager.set_voice(TEST_VOICE_NAME).await.unwrap();
+        assert_eq!(manager.voice_locale(), TEST_VOICE_LANGUAGE);
+
+        let data = read_to_string(h.test.path().join(ORCA_SETTINGS))
+            .await
+            .unwrap();
+        let json: Value = serde_json::from_str(&data).unwrap();
+        let family = &json["profiles"]["default"]["voices"]["default"][FAMILY_SETTING];
+        assert_eq!(family[VOICE_NAME_SETTING], TEST_VOICE_NAME);
+        assert_eq!(family[VOICE_LANG_SETTING], TEST_VOICE_LANGUAGE);
+        assert_eq!(family[VOICE_DIALECT_SETTING], "");
+        assert_eq!(family[VOICE_VARIANT_SETTING], TEST_VOICE_VARIANT);
+        assert_eq!(family[VOICE_GENDER_SETTING], Value::Null);
+
+        let data = read_to_string(path(VOICE_DEFAULTS_FILE)).await.unwrap();
+        let defaults: HashMap<String, String> = serde_json::from_str(&data).unwrap();
+        assert_eq!(
+            defaults,
+            HashMap::from([(TEST_VOICE_LANGUAGE.to_string(), TEST_VOICE_NAME.to_string())])
+        );
+
+        let manager = OrcaManager::new(&connection)
+            .await
+            .expect("OrcaManager::new");
+        assert_eq!(manager.voice(), TEST_VOICE_NAME);
+        assert_eq!(manager.voice_locale(), TEST_VOICE_LANGUAGE);
+    }
+
+    #[tokio::test]
+    async fn test_voice_locale() {
+        let mut h = testing::start();
+        copy(TEST_ORCA_SETTINGS, h.test.path().join(ORCA_SETTINGS))
+            .await
+            .unwrap();
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut manager = OrcaManager::new(&connection)
+            .await
+            .expect("OrcaManager::new");
+        assert_eq!(manager.voice(), "");
+
+        manager.set_voice_locale(TEST_VOICE_LANGUAGE).await.unwrap();
+        assert_eq!(manager.voice(), TEST_VOICE_NAME);
+        assert_eq!(manager.voice_locale(), TEST_VOICE_LANGUAGE);
+        assert!(manager.set_voice_locale("nonexistent").await.is_err());
+        assert_eq!(manager.voice(), TEST_VOICE_NAME);
+
+        // A voice that has since been uninstalled is replaced by the default for its locale
+        let data = read_to_string(h.test.path().join(ORCA_SETTINGS))
+            .await
+            .unwrap();
+        let mut json: Value = serde_json::from_str(&data).unwrap();
+        json["profiles"]["default"]["voices"]["default"][FAMILY_SETTING][VOICE_NAME_SETTING] =
+            "uninstalled".into();
+        write(
+            h.test.path().join(ORCA_SETTINGS),
+            serde_json::to_string_pretty(&json).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let manager = OrcaManager::new(&connection)
+            .await
+            .expect("OrcaManager::new");
+        assert_eq!(manager.voice(), TEST_VOICE_NAME);
+        assert_eq!(manager.voice_locale(), TEST_VOICE_LANGUAGE);
+    }
+
     #[tokio::test]
     async fn test_read_next_word() {
         let mut h = testing::start();
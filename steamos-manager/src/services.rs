@@ -0,0 +1,278 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+//! Control of the systemd units whitelisted in the platform config
+
+use anyhow::{ensure, Result};
+use strum::{Display, EnumString};
+use tokio_stream::{StreamExt, StreamMap};
+use tracing::{debug, warn};
+use zbus::fdo::{PropertiesChangedStream, PropertiesProxy};
+use zbus::Connection;
+
+use crate::developer_mode::DeveloperFeature;
+use crate::manager::user::{Services1, Services1Signals, MANAGER_PATH};
+use crate::platform::{platform_config, PlatformConfig, ServiceConfig};
+use crate::policy::Guarded;
+use crate::systemd::{subscribe, SystemdUnit};
+use crate::Service;
+
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub(crate) enum UnitAction {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+}
+
+/// Whether a unit is already controlled through its own interface, which must then remain the
+/// only way to reach it so that it can't be toggled under a laxer authorization
+fn has_dedicated_interface(config: &PlatformConfig, unit: &str) -> bool {
+    let developer_mode = config.developer_mode.as_ref().is_some_and(|config| {
+        [
+            DeveloperFeature::Ssh,
+            DeveloperFeature::CoreDumps,
+            DeveloperFeature::PersistentJournal,
+        ]
+        .into_iter()
+        .any(|feature| config.unit(feature) == Some(unit))
+    });
+    let fan_control = matches!(
+        config.fan_control.as_ref(),
+        Some(ServiceConfig::Systemd(fan_control)) if fan_control == unit
+    );
+    developer_mode || fan_control
+}
+
+/// The whitelisted units, minus the ones that have a dedicated interface
+pub(crate) fn configured_units(config: &PlatformConfig) -> Vec<String> {
+    config
+        .managed_units
+        .iter()
+        .filter(|unit| {
+            let dedicated = has_dedicated_interface(config, unit);
+            if dedicated {
+                warn!("Not managing {unit}, which has a dedicated interface");
+            }
+            !dedicated
+        })
+        .cloned()
+        .collect()
+}
+
+async fn managed_units() -> Result<Vec<String>> {
+    let config = platform_config().await?;
+    Ok(config.as_ref().map(configured_units).unwrap_or_default())
+}
+
+/// The managed units that are installed on this system
+pub(crate) async fn available_units(connection: &Connection) -> Result<Vec<String>> {
+    let mut units = Vec::new();
+    for unit in managed_units().await? {
+        if SystemdUnit::exists(connection, unit.as_str()).await? {
+            units.push(unit);
+        }
+    }
+    Ok(units)
+}
+
+/// The name, active state and unit file state of each available managed unit
+pub(crate) async fn unit_states(connection: &Connection) -> Result<Vec<(String, String, String)>> {
+    let mut states = Vec::new();
+    for name in available_units(connection).await? {
+        let unit = SystemdUnit::new(connection.clone(), name.as_str()).await?;
+        let active = unit.active_state().await?;
+        let unit_file = unit.unit_file_state().await?;
+        states.push((name, active, unit_file));
+    }
+    Ok(states)
+}
+
+pub(crate) async fn run_unit_action(
+    connection: &Connection,
+    unit: &str,
+    action: UnitAction,
+) -> Result<()> {
+    ensure!(
+        managed_units().await?.iter().any(|managed| managed == unit),
+        "Unit {unit} is not managed"
+    );
+    let unit = SystemdUnit::new(connection.clone(), unit).await?;
+    match action {
+        UnitAction::Start => unit.start().await,
+        UnitAction::Stop => unit.stop().await,
+        UnitAction::Restart => unit.restart().await,
+        UnitAction::Enable => unit.enable().await.map(|_| ()),
+        UnitAction::Disable => unit.disable().await.map(|_| ()),
+    }
+}
+
+/// Relays active state changes of the managed units as `UnitStateChanged` signals
+pub(crate) struct UnitStateMonitor {
+    session: Connection,
+    units: StreamMap<String, PropertiesChangedStream>,
+}
+
+impl UnitStateMonitor {
+    pub(crate) async fn init(
+        session: &Connection,
+        system: &Connection,
+    ) -> Result<UnitStateMonitor> {
+        // There's nothing to relay to if no managed unit is installed
+        session
+            .object_server()
+            .interface::<_, Guarded<Services1>>(MANAGER_PATH)
+            .await?;
+        subscribe(system).await?;
+        let mut units = StreamMap::new();
+        for name in available_units(system).await? {
+            let unit = SystemdUnit::new(system.clone(), name.as_str()).await?;
+            let properties = PropertiesProxy::builder(system)
+                .destination("org.freedesktop.systemd1")?
+                .path(unit.path().to_owned())?
+                .build()
+                .await?;
+            units.insert(name, properties.receive_properties_changed().await?);
+        }
+        Ok(UnitStateMonitor {
+            session: session.clone(),
+            units,
+        })
+    }
+}
+
+impl Service for UnitStateMonitor {
+    const NAME: &'static str = "unit-state-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        while let Some((unit, signal)) = self.units.next().await {
+            let args = signal.args()?;
+            if args.interface_name() != UNIT_INTERFACE {
+                continue;
+            }
+            let Some(state) = args.changed_properties().get("ActiveState") else {
+                continue;
+            };
+            let state: &str = state.downcast_ref()?;
+            debug!("Unit {unit} is now {state}");
+            let interface = self
+                .session
+                .object_server()
+                .interface::<_, Guarded<Services1>>(MANAGER_PATH)
+                .await?;
+            interface
+                .signal_emitter()
+                .unit_state_changed(unit.as_str(), state)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::platform::DeveloperModeConfig;
+    use crate::systemd::test::{MockManager, MockUnit};
+    use crate::{enum_roundtrip, testing};
+    use std::str::FromStr;
+
+    #[test]
+    fn unit_action_roundtrip() {
+        enum_roundtrip!(UnitAction {
+            "start": str = Start,
+            "stop": str = Stop,
+            "restart": str = Restart,
+            "enable": str = Enable,
+            "disable": str = Disable,
+        });
+        assert!(UnitAction::from_str("mask").is_err());
+    }
+
+    #[test]
+    fn dedicated_units() {
+        let config = PlatformConfig {
+            fan_control: Some(ServiceConfig::Systemd(String::from(
+                "jupiter-fan-control.service",
+            ))),
+            developer_mode: Some(DeveloperModeConfig {
+                ssh: Some(String::from("sshd.service")),
+                ..DeveloperModeConfig::default()
+            }),
+            managed_units: vec![
+                String::from("sshd.service"),
+                String::from("jupiter-fan-control.service"),
+                String::from("avahi-daemon.service"),
+            ],
+            ..PlatformConfig::default()
+        };
+        assert_eq!(
+            configured_units(&config),
+            vec![String::from("avahi-daemon.service")]
+        );
+    }
+
+    #[tokio::test]
+    async fn managed_unit_actions() {
+        let mut h = testing::start();
+        let connection = h.new_dbus().await.expect("dbus");
+        connection
+            .request_name("org.freedesktop.systemd1")
+            .await
+            .expect("request_name");
+        let object_server = connection.object_server();
+        object_server
+            .at("/org/freedesktop/systemd1", MockManager::default())
+            .await
+            .expect("at");
+        let mut unit = MockUnit::default();
+        unit.active = String::from("inactive");
+        unit.unit_file = String::from("disabled");
+        object_server
+            .at("/org/freedesktop/systemd1/unit/sshd_2eservice", unit)
+            .await
+            .expect("at");
+
+        h.test.platform_config.replace(Some(PlatformConfig {
+            managed_units: vec![String::from("sshd.service")],
+            ..PlatformConfig::default()
+        }));
+
+        assert_eq!(
+            unit_states(&connection).await.unwrap(),
+            vec![(
+                String::from("sshd.service"),
+                String::from("inactive"),
+                String::from("disabled")
+            )]
+        );
+
+        run_unit_action(&connection, "sshd.service", UnitAction::Enable)
+            .await
+            .expect("enable");
+        run_unit_action(&connection, "sshd.service", UnitAction::Start)
+            .await
+            .expect("start");
+        assert_eq!(
+            unit_states(&connection).await.unwrap(),
+            vec![(
+                String::from("sshd.service"),
+                String::from("active"),
+                String::from("enabled")
+            )]
+        );
+
+        assert!(
+            run_unit_action(&connection, "systemd-coredump.socket", UnitAction::Stop)
+                .await
+                .is_err()
+        );
+    }
+}
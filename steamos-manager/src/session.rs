@@ -9,6 +9,7 @@
 #[cfg(test)]
 use anyhow::anyhow;
 use anyhow::{ensure, Result};
+use inotify::{EventStream, Inotify, WatchMask};
 use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::io::ErrorKind;
@@ -18,17 +19,22 @@ use tokio::fs::create_dir_all;
 use tokio::fs::{read_dir, remove_file, try_exists, write};
 use tokio::sync::mpsc::Sender;
 use tokio::sync::oneshot;
+use tokio_stream::StreamExt;
+use tracing::debug;
 use zbus::{fdo, Connection};
 
 use crate::daemon::user::{Command as DaemonCommand, UserCommand};
 use crate::manager::root::RootManagerProxy;
-use crate::path;
+use crate::manager::user::{SessionManagement1, MANAGER_PATH};
+use crate::policy::Guarded;
 use crate::systemd::SystemdUnit;
+use crate::{path, Service};
 
 const CONFIG_PREFIX: &str = "/etc/sddm.conf.d";
 const SESSION_CHECK_PATH: &str = "steamos.conf";
 const CONFIG_PATH: &str = "zz-steamos-autologin.conf";
 const TEMPORARY_CONFIG_PATH: &str = "zzt-steamos-temp-login.conf";
+const SESSION_PREFIXES: [&str; 2] = ["/usr/share/wayland-sessions/", "/usr/share/xsessions/"];
 
 #[derive(Default, Deserialize, Serialize, Display, EnumString, PartialEq, Debug, Copy, Clone)]
 #[strum(serialize_all = "snake_case")]
@@ -103,8 +109,13 @@ fn is_valid_desktop_session_name(path: &str) -> bool {
 
 pub(crate) async fn valid_desktop_sessions() -> Result<Vec<String>> {
     let mut sessions = Vec::new();
-    for dir in &["/usr/share/wayland-sessions/", "/usr/share/xsessions/"] {
-        let mut entries = read_dir(path(dir)).await?;
+    for dir in SESSION_PREFIXES {
+        // Systems without X11 or Wayland sessions may not have both directories
+        let mut entries = match read_dir(path(dir)).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             let Some(name) = path.file_name().and_then(OsStr::to_str) else {
@@ -119,11 +130,8 @@ pub(crate) async fn valid_desktop_sessions() -> Result<Vec<String>> {
     Ok(sessions)
 }
 
-pub(crate) async fn is_valid_desktop_session(session: &str) -> Result<bool> {
-    if !is_valid_desktop_session_name(session) {
-        return Ok(false);
-    }
-    for dir in &["/usr/share/wayland-sessions/", "/usr/share/xsessions/"] {
+async fn is_session_installed(session: &str) -> Result<bool> {
+    for dir in SESSION_PREFIXES {
         if try_exists(path(dir).join(session)).await? {
             return Ok(true);
         }
@@ -131,6 +139,13 @@ pub(crate) async fn is_valid_desktop_session(session: &str) -> Result<bool> {
     Ok(false)
 }
 
+pub(crate) async fn is_valid_desktop_session(session: &str) -> Result<bool> {
+    if !is_valid_desktop_session_name(session) {
+        return Ok(false);
+    }
+    is_session_installed(session).await
+}
+
 impl SessionManager {
     pub(crate) async fn new(
         connection: Connection,
@@ -179,9 +194,13 @@ impl SessionManager {
     }
 
     pub(crate) async fn switch_to_login_mode(&self, mode: LoginMode) -> Result<()> {
-        self.manager
-            .set_temporary_session(self.session_for_mode(mode).await?.as_str())
-            .await?;
+        let session = self.session_for_mode(mode).await?;
+        // Otherwise the display manager would fail to start it after logging out
+        ensure!(
+            is_session_installed(session.as_str()).await?,
+            "Session {session} is not installed"
+        );
+        self.manager.set_temporary_session(session.as_str()).await?;
         self.logout().await
     }
 
@@ -247,6 +266,70 @@ impl SessionManager {
     }
 }
 
+/// Keeps the cached list of desktop sessions up to date as sessions are installed or removed
+pub(crate) struct DesktopSessionMonitor {
+    session: Connection,
+    inotify: EventStream<[u8; 512]>,
+}
+
+impl DesktopSessionMonitor {
+    pub(crate) async fn init(session: &Connection) -> Result<DesktopSessionMonitor> {
+        // Sessions can only be switched on systems that are set up for it
+        session
+            .object_server()
+            .interface::<_, Guarded<SessionManagement1>>(MANAGER_PATH)
+            .await?;
+        let inotify = Inotify::init()?.into_event_stream([0; 512])?;
+        let mut watching = false;
+        for dir in SESSION_PREFIXES {
+            match inotify.watches().add(
+                path(dir),
+                WatchMask::CREATE | WatchMask::DELETE | WatchMask::MOVED_FROM | WatchMask::MOVED_TO,
+            ) {
+                Ok(_) => watching = true,
+                Err(e) => debug!("Not watching {dir} for desktop sessions: {e}"),
+            }
+        }
+        ensure!(watching, "No desktop session directories found");
+        Ok(DesktopSessionMonitor {
+            session: session.clone(),
+            inotify,
+        })
+    }
+
+    async fn update(&self) -> Result<()> {
+        let sessions = valid_desktop_sessions().await?;
+        let interface = self
+            .session
+            .object_server()
+            .interface::<_, Guarded<SessionManagement1>>(MANAGER_PATH)
+            .await?;
+        interface
+            .get_mut()
+            .await
+            .set_desktop_sessions(sessions, interface.signal_emitter())
+            .await?;
+        Ok(())
+    }
+}
+
+impl Service for DesktopSessionMonitor {
+    const NAME: &'static str = "desktop-session-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        self.update().await?;
+        while let Some(event) = self.inotify.next().await {
+            let event = event?;
+            debug!(
+                "Got desktop session event {:08x} for {:?}",
+                event.mask, event.name
+            );
+            self.update().await?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) mod root {
     use super::*;
 
@@ -294,6 +377,7 @@ mod test {
     use crate::testing;
     use std::sync::Arc;
     use std::time::Duration;
+    use tokio::fs::remove_dir_all;
     use tokio::spawn;
     use tokio::sync::mpsc::channel;
     use tokio::sync::Notify;
@@ -355,6 +439,10 @@ mod test {
             valid_desktop_sessions().await.unwrap(),
             &["plasma.desktop", "plasmax11.desktop"]
         );
+
+        // A missing directory is skipped
+        remove_dir_all(path("/usr/share/xsessions")).await.unwrap();
+        assert_eq!(valid_desktop_sessions().await.unwrap(), &["plasma.desktop"]);
     }
 
     #[tokio::test]
@@ -599,6 +687,9 @@ mod test {
         write(path("/usr/share/wayland-sessions/city17.desktop"), b"")
             .await
             .unwrap();
+        write(path("/usr/share/xsessions/plasmax11.desktop"), b"")
+            .await
+            .unwrap();
         write(
             path("/usr/share/wayland-sessions/gamescope-wayland.desktop"),
             b"",
@@ -639,6 +730,20 @@ mod test {
             unit.active = String::from("active");
         }
 
+        // A missing session fails without logging out
+        remove_file(path("/usr/share/xsessions/plasmax11.desktop"))
+            .await
+            .unwrap();
+        assert!(manager
+            .switch_to_login_mode(LoginMode::Desktop)
+            .await
+            .is_err());
+        assert_eq!(
+            root_manager.get().await.temporary_session,
+            "gamescope-wayland.desktop"
+        );
+        assert_eq!(unit.get().await.active, "active");
+
         task.abort();
     }
 }
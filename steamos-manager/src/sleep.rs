@@ -0,0 +1,384 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Result};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use strum::{Display, EnumString};
+use tokio::fs::{create_dir_all, read_to_string};
+
+use crate::{path, write_synced};
+
+const MEM_SLEEP_PATH: &str = "/sys/power/mem_sleep";
+const MEMINFO_PATH: &str = "/proc/meminfo";
+const SLEEP_CONF_DIR: &str = "/etc/systemd/sleep.conf.d";
+const SLEEP_CONF_FILE: &str = "60-steamos-manager.conf";
+
+/// Shortest and longest time, in seconds, that can be spent suspended before hibernating
+pub(crate) const MIN_HIBERNATE_DELAY: u32 = 60;
+pub(crate) const MAX_HIBERNATE_DELAY: u32 = 24 * 60 * 60;
+
+#[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum SleepMode {
+    /// Suspend-to-idle, which resumes quickly but uses the most power
+    #[strum(to_string = "s2idle")]
+    S2idle,
+    /// Standby, also known as power-on suspend
+    Shallow,
+    /// Suspend-to-RAM
+    Deep,
+}
+
+/// The settings in the sleep.conf drop-in. Settings that aren't in the drop-in keep systemd's
+/// defaults.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct SleepSettings {
+    /// Kernel sleep mode to suspend with, or the kernel's default
+    pub mode: Option<SleepMode>,
+    pub hibernation: bool,
+    pub suspend_then_hibernate: bool,
+    /// Seconds spent suspended before hibernating, or systemd's default if 0
+    pub hibernate_delay: u32,
+}
+
+impl Default for SleepSettings {
+    fn default() -> SleepSettings {
+        SleepSettings {
+            mode: None,
+            hibernation: true,
+            suspend_then_hibernate: true,
+            hibernate_delay: 0,
+        }
+    }
+}
+
+impl SleepSettings {
+    fn parse(contents: &str) -> Result<SleepSettings> {
+        let mut settings = SleepSettings::default();
+        let mut in_sleep_section = false;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_sleep_section = line == "[Sleep]";
+                continue;
+            }
+            if !in_sleep_section {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                bail!("Invalid sleep.conf line {line}");
+            };
+            let value = value.trim();
+            match key.trim() {
+                "MemorySleepMode" => {
+                    settings.mode = match value {
+                        "" => None,
+                        value => Some(value.parse()?),
+                    };
+                }
+                "AllowHibernation" => settings.hibernation = parse_bool(value)?,
+                "AllowSuspendThenHibernate" => {
+                    settings.suspend_then_hibernate = parse_bool(value)?;
+                }
+                "HibernateDelaySec" => {
+                    settings.hibernate_delay = value
+                        .parse()
+                        .map_err(|e| anyhow!("Invalid hibernate delay {value}: {e}"))?;
+                }
+                _ => (),
+            }
+        }
+        Ok(settings)
+    }
+
+    fn contents(&self) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let mut contents = format!(
+            "# Written by steamos-manager, changes will be overwritten\n\
+             [Sleep]\n\
+             AllowHibernation={}\n\
+             AllowSuspendThenHibernate={}\n",
+            yes_no(self.hibernation),
+            yes_no(self.suspend_then_hibernate)
+        );
+        if let Some(mode) = self.mode {
+            contents.push_str(format!("MemorySleepMode={mode}\n").as_str());
+        }
+        if self.hibernate_delay != 0 {
+            contents.push_str(format!("HibernateDelaySec={}\n", self.hibernate_delay).as_str());
+        }
+        contents
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value {
+        "yes" | "true" | "on" | "1" => Ok(true),
+        "no" | "false" | "off" | "0" => Ok(false),
+        value => bail!("Invalid boolean {value}"),
+    }
+}
+
+/// Split the contents of `mem_sleep`, returning the supported modes and the one in use, which
+/// the kernel marks with brackets
+fn parse_mem_sleep(contents: &str) -> (Vec<SleepMode>, Option<SleepMode>) {
+    let mut supported = Vec::new();
+    let mut current = None;
+    for mode in contents.split_whitespace() {
+        let (mode, active) = match mode.strip_prefix('[').and_then(|m| m.strip_suffix(']')) {
+            Some(mode) => (mode, true),
+            None => (mode, false),
+        };
+        // Ignore modes that are newer than us
+        let Ok(mode) = mode.parse() else {
+            continue;
+        };
+        supported.push(mode);
+        if active {
+            current = Some(mode);
+        }
+    }
+    (supported, current)
+}
+
+async fn read_mem_sleep() -> Result<(Vec<SleepMode>, Option<SleepMode>)> {
+    let contents = read_to_string(path(MEM_SLEEP_PATH))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    Ok(parse_mem_sleep(contents.as_str()))
+}
+
+pub(crate) async fn get_supported_sleep_modes() -> Result<Vec<SleepMode>> {
+    Ok(read_mem_sleep().await?.0)
+}
+
+/// The mode the next suspend will use, taking the drop-in into account
+pub(crate) async fn get_sleep_mode() -> Result<SleepMode> {
+    if let Some(mode) = get_sleep_settings().await?.mode {
+        return Ok(mode);
+    }
+    read_mem_sleep()
+        .await?
+        .1
+        .ok_or(anyhow!("Current sleep mode is unknown"))
+}
+
+pub(crate) async fn get_sleep_settings() -> Result<SleepSettings> {
+    match read_to_string(path(SLEEP_CONF_DIR).join(SLEEP_CONF_FILE)).await {
+        Ok(contents) => SleepSettings::parse(contents.as_str()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(SleepSettings::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn parse_meminfo(contents: &str) -> Result<HashMap<&str, u64>> {
+    let mut meminfo = HashMap::new();
+    for (key, value) in contents.lines().filter_map(|line| line.split_once(':')) {
+        let value = value.trim().trim_end_matches(" kB");
+        let value: u64 = value
+            .parse()
+            .map_err(|e| anyhow!("Invalid meminfo value {value}: {e}"))?;
+        meminfo.insert(key, value);
+    }
+    Ok(meminfo)
+}
+
+/// Whether there's enough free swap space to hold the memory in use, like systemd checks before
+/// hibernating
+pub(crate) async fn is_swap_adequate() -> Result<bool> {
+    let contents = read_to_string(path(MEMINFO_PATH)).await?;
+    let meminfo = parse_meminfo(contents.as_str())?;
+    let field = |name: &str| {
+        meminfo
+            .get(name)
+            .copied()
+            .ok_or(anyhow!("No {name} in meminfo"))
+    };
+    let anonymous = field("Active(anon)")? + field("Inactive(anon)")?;
+    Ok(field("SwapTotal")? > 0 && field("SwapFree")? >= anonymous)
+}
+
+pub(crate) async fn set_sleep_settings(settings: &SleepSettings) -> Result<()> {
+    if let Some(mode) = settings.mode {
+        ensure!(
+            get_supported_sleep_modes().await?.contains(&mode),
+            "Sleep mode {mode} is not supported"
+        );
+    }
+    ensure!(
+        settings.hibernate_delay == 0
+            || (MIN_HIBERNATE_DELAY..=MAX_HIBERNATE_DELAY).contains(&settings.hibernate_delay),
+        "Hibernate delay {} is out of range",
+        settings.hibernate_delay
+    );
+
+    // Only check the swap when hibernation is being turned on, so that other settings can still
+    // be changed on systems that can't hibernate
+    let current = get_sleep_settings().await?;
+    if (settings.hibernation && !current.hibernation)
+        || (settings.suspend_then_hibernate && !current.suspend_then_hibernate)
+    {
+        ensure!(
+            is_swap_adequate().await?,
+            "Not enough swap space to hibernate"
+        );
+    }
+
+    let dir = path(SLEEP_CONF_DIR);
+    create_dir_all(&dir).await?;
+    write_synced(dir.join(SLEEP_CONF_FILE), settings.contents().as_bytes()).await
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{enum_roundtrip, testing};
+    use std::str::FromStr;
+    use tokio::fs::write;
+
+    pub(crate) async fn create_nodes(swap: u64) -> Result<()> {
+        create_dir_all(path("/sys/power")).await?;
+        write(path(MEM_SLEEP_PATH), "s2idle [deep]\n").await?;
+        create_dir_all(path("/proc")).await?;
+        write(
+            path(MEMINFO_PATH),
+            format!(
+                "MemTotal:       15938004 kB\n\
+                 MemFree:         9382104 kB\n\
+                 Active(anon):    2097152 kB\n\
+                 Inactive(anon):  1048576 kB\n\
+                 SwapTotal:       {swap} kB\n\
+                 SwapFree:        {swap} kB\n"
+            ),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[test]
+    fn sleep_mode_roundtrip() {
+        enum_roundtrip!(SleepMode {
+            "s2idle": str = S2idle,
+            "shallow": str = Shallow,
+            "deep": str = Deep,
+        });
+        assert!(SleepMode::from_str("disk").is_err());
+    }
+
+    #[test]
+    fn mem_sleep_parsing() {
+        assert_eq!(
+            parse_mem_sleep("s2idle [deep]\n"),
+            (
+                vec![SleepMode::S2idle, SleepMode::Deep],
+                Some(SleepMode::Deep)
+            )
+        );
+        assert_eq!(
+            parse_mem_sleep("[s2idle] shallow future\n"),
+            (
+                vec![SleepMode::S2idle, SleepMode::Shallow],
+                Some(SleepMode::S2idle)
+            )
+        );
+        assert_eq!(parse_mem_sleep(""), (Vec::new(), None));
+    }
+
+    #[test]
+    fn settings_roundtrip() {
+        assert_eq!(SleepSettings::parse("").unwrap(), SleepSettings::default());
+
+        let settings = SleepSettings {
+            mode: Some(SleepMode::S2idle),
+            hibernation: false,
+            suspend_then_hibernate: true,
+            hibernate_delay: 3600,
+        };
+        assert_eq!(
+            SleepSettings::parse(settings.contents().as_str()).unwrap(),
+            settings
+        );
+        assert_eq!(
+            SleepSettings::parse(SleepSettings::default().contents().as_str()).unwrap(),
+            SleepSettings::default()
+        );
+
+        // Other sections are ignored
+        assert_eq!(
+            SleepSettings::parse("[Other]\nAllowHibernation=no\n").unwrap(),
+            SleepSettings::default()
+        );
+        assert!(SleepSettings::parse("[Sleep]\nAllowHibernation=maybe\n").is_err());
+        assert!(SleepSettings::parse("[Sleep]\nHibernateDelaySec=2h\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn sleep_settings() {
+        let _h = testing::start();
+
+        assert!(get_supported_sleep_modes().await.is_err());
+        create_nodes(0).await.expect("create_nodes");
+        assert_eq!(
+            get_supported_sleep_modes().await.unwrap(),
+            vec![SleepMode::S2idle, SleepMode::Deep]
+        );
+        assert_eq!(get_sleep_mode().await.unwrap(), SleepMode::Deep);
+        assert_eq!(
+            get_sleep_settings().await.unwrap(),
+            SleepSettings::default()
+        );
+        assert!(!is_swap_adequate().await.unwrap());
+
+        // Hibernation is allowed by default, so other settings can still be changed without swap
+        let mut settings = SleepSettings {
+            mode: Some(SleepMode::S2idle),
+            hibernate_delay: 1800,
+            ..SleepSettings::default()
+        };
+        set_sleep_settings(&settings)
+            .await
+            .expect("set_sleep_settings");
+        assert_eq!(get_sleep_settings().await.unwrap(), settings);
+        assert_eq!(get_sleep_mode().await.unwrap(), SleepMode::S2idle);
+
+        settings.hibernation = false;
+        settings.suspend_then_hibernate = false;
+        set_sleep_settings(&settings)
+            .await
+            .expect("set_sleep_settings");
+        assert_eq!(
+            read_to_string(path(SLEEP_CONF_DIR).join(SLEEP_CONF_FILE))
+                .await
+                .unwrap(),
+            "# Written by steamos-manager, changes will be overwritten\n\
+             [Sleep]\n\
+             AllowHibernation=no\n\
+             AllowSuspendThenHibernate=no\n\
+             MemorySleepMode=s2idle\n\
+             HibernateDelaySec=1800\n"
+        );
+
+        settings.hibernation = true;
+        assert!(set_sleep_settings(&settings).await.is_err());
+        create_nodes(8388608).await.expect("create_nodes");
+        assert!(is_swap_adequate().await.unwrap());
+        set_sleep_settings(&settings)
+            .await
+            .expect("set_sleep_settings");
+        assert_eq!(get_sleep_settings().await.unwrap(), settings);
+
+        settings.mode = Some(SleepMode::Shallow);
+        assert!(set_sleep_settings(&settings).await.is_err());
+        settings.mode = None;
+        settings.hibernate_delay = 30;
+        assert!(set_sleep_settings(&settings).await.is_err());
+        settings.hibernate_delay = MAX_HIBERNATE_DELAY + 1;
+        assert!(set_sleep_settings(&settings).await.is_err());
+    }
+}
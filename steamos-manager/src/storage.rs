@@ -0,0 +1,392 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs::{read_dir, read_to_string};
+use zbus::zvariant;
+
+use crate::path;
+
+const BLOCK_PREFIX: &str = "/sys/block";
+
+// Virtual block devices that aren't backed by physical storage
+const VIRTUAL_BLOCK_PREFIXES: &[&str] = &["loop", "ram", "zram", "dm-", "md", "sr"];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct StorageHealth {
+    /// Percentage of the rated endurance used, may exceed 100
+    pub wear_level: u32,
+    /// Temperature in °C, or NaN if unknown
+    pub temperature: f64,
+    /// Percentage of spare capacity remaining
+    pub available_spare: u32,
+    /// Whether the device reports its overall health as passing
+    pub passed: bool,
+}
+
+// Sizes in sysfs are always counted in 512 byte sectors
+const SECTOR_SIZE: u64 = 512;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct DeviceInfo {
+    /// Capacity in bytes
+    pub capacity: u64,
+    pub vendor: String,
+    pub model: String,
+    pub removable: bool,
+    /// Human-readable bus speed, or an empty string if unknown
+    pub speed: String,
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct FormatOptions {
+    pub filesystem: Option<String>,
+    pub encrypt: bool,
+    /// Cluster size in bytes
+    pub cluster_size: Option<u32>,
+}
+
+impl TryFrom<&HashMap<String, zvariant::OwnedValue>> for FormatOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(options: &HashMap<String, zvariant::OwnedValue>) -> Result<FormatOptions> {
+        let mut format_options = FormatOptions::default();
+        for (key, value) in options.iter() {
+            match key.as_str() {
+                "filesystem" => {
+                    format_options.filesystem = Some(<&str>::try_from(value)?.to_string());
+                }
+                "encrypt" => format_options.encrypt = bool::try_from(value)?,
+                "cluster_size" => format_options.cluster_size = Some(u32::try_from(value)?),
+                _ => bail!("Unknown format option {key}"),
+            }
+        }
+        Ok(format_options)
+    }
+}
+
+pub(crate) async fn list_block_devices() -> Result<Vec<String>> {
+    let mut devices = Vec::new();
+    let mut dir = read_dir(path(BLOCK_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if VIRTUAL_BLOCK_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            continue;
+        }
+        devices.push(name);
+    }
+    devices.sort();
+    Ok(devices)
+}
+
+async fn block_device_name(device: &str) -> Result<&str> {
+    let Some(name) = device.strip_prefix("/dev/") else {
+        bail!("Invalid block device {device}");
+    };
+    if !list_block_devices()
+        .await?
+        .iter()
+        .any(|known| known == name)
+    {
+        bail!("Invalid block device {device}");
+    }
+    Ok(name)
+}
+
+pub(crate) async fn validate_block_device(device: &str) -> Result<()> {
+    block_device_name(device).await.map(|_| ())
+}
+
+async fn read_attribute(base: &Path, attribute: &str) -> Option<String> {
+    read_to_string(base.join(attribute))
+        .await
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn sd_speed(ssr: &str) -> Option<String> {
+    // The SD status register is printed as 512 bits of hex, most significant first
+    let field = |bit: usize, len: usize| {
+        let start = (511 - bit) / 4;
+        ssr.get(start..start + len)
+            .and_then(|value| u32::from_str_radix(value, 16).ok())
+    };
+    let speed_class = match field(447, 2)? {
+        0 => 0,
+        1 => 2,
+        2 => 4,
+        3 => 6,
+        4 => 10,
+        _ => return None,
+    };
+    let mut speed = vec![format!("Class {speed_class}")];
+    match field(399, 1)? {
+        0 => (),
+        grade => speed.push(format!("U{grade}")),
+    }
+    match field(391, 2)? {
+        0 => (),
+        class => speed.push(format!("V{class}")),
+    }
+    Some(speed.join(", "))
+}
+
+pub(crate) async fn get_device_info(device: &str) -> Result<DeviceInfo> {
+    let name = block_device_name(device).await?;
+    let base = path(BLOCK_PREFIX).join(name);
+
+    let sectors: u64 = read_attribute(&base, "size")
+        .await
+        .ok_or(anyhow!("Error reading size of {device}"))?
+        .parse()
+        .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+    let mut info = DeviceInfo {
+        capacity: sectors * SECTOR_SIZE,
+        removable: read_attribute(&base, "removable").await.as_deref() == Some("1"),
+        ..DeviceInfo::default()
+    };
+
+    if name.starts_with("mmcblk") {
+        // SD card slots don't mark the medium as removable
+        if read_attribute(&base, "device/type").await.as_deref() == Some("SD") {
+            info.removable = true;
+        }
+        info.vendor = read_attribute(&base, "device/manfid")
+            .await
+            .unwrap_or_default();
+        info.model = read_attribute(&base, "device/name")
+            .await
+            .unwrap_or_default();
+        if let Some(ssr) = read_attribute(&base, "device/ssr").await {
+            info.speed = sd_speed(ssr.as_str()).unwrap_or_default();
+        }
+    } else {
+        info.vendor = read_attribute(&base, "device/vendor")
+            .await
+            .unwrap_or_default();
+        info.model = read_attribute(&base, "device/model")
+            .await
+            .unwrap_or_default();
+        // NVMe namespaces link to the controller, which links to the PCI device
+        if let (Some(link_speed), Some(link_width)) = (
+            read_attribute(&base, "device/device/current_link_speed").await,
+            read_attribute(&base, "device/device/current_link_width").await,
+        ) {
+            info.speed = format!("{link_speed} x{link_width}");
+        }
+    }
+
+    Ok(info)
+}
+
+pub(crate) fn parse_storage_health(output: &str) -> Result<StorageHealth> {
+    let mut wear_level = None;
+    let mut temperature = f64::NAN;
+    let mut available_spare = None;
+    let mut passed = None;
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "wear_level" => wear_level = Some(value.parse()?),
+            "temperature" => temperature = value.parse()?,
+            "available_spare" => available_spare = Some(value.parse()?),
+            "smart_passed" => passed = Some(value != "0"),
+            _ => (),
+        }
+    }
+
+    Ok(StorageHealth {
+        wear_level: wear_level.ok_or(anyhow!("Missing wear_level"))?,
+        temperature,
+        available_spare: available_spare.ok_or(anyhow!("Missing available_spare"))?,
+        passed: passed.ok_or(anyhow!("Missing smart_passed"))?,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        for device in ["loop0", "mmcblk0", "nvme0n1", "zram0"] {
+            create_dir_all(path(BLOCK_PREFIX).join(device)).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn device_info() {
+        let _h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        assert!(get_device_info("/dev/nvme0n1").await.is_err());
+        assert!(get_device_info("/dev/sda").await.is_err());
+
+        let base = path(BLOCK_PREFIX).join("nvme0n1");
+        create_dir_all(base.join("device/device"))
+            .await
+            .expect("create_dir_all");
+        write(base.join("size"), "1000215216\n")
+            .await
+            .expect("write");
+        write(base.join("removable"), "0\n").await.expect("write");
+        write(base.join("device/model"), "Example SSD 512GB   \n")
+            .await
+            .expect("write");
+        write(
+            base.join("device/device/current_link_speed"),
+            "8.0 GT/s PCIe\n",
+        )
+        .await
+        .expect("write");
+        write(base.join("device/device/current_link_width"), "4\n")
+            .await
+            .expect("write");
+        assert_eq!(
+            get_device_info("/dev/nvme0n1").await.unwrap(),
+            DeviceInfo {
+                capacity: 512110190592,
+                vendor: String::new(),
+                model: String::from("Example SSD 512GB"),
+                removable: false,
+                speed: String::from("8.0 GT/s PCIe x4"),
+            }
+        );
+
+        let base = path(BLOCK_PREFIX).join("mmcblk0");
+        create_dir_all(base.join("device"))
+            .await
+            .expect("create_dir_all");
+        write(base.join("size"), "124735488\n")
+            .await
+            .expect("write");
+        write(base.join("removable"), "0\n").await.expect("write");
+        write(base.join("device/type"), "SD\n")
+            .await
+            .expect("write");
+        write(base.join("device/manfid"), "0x000003\n")
+            .await
+            .expect("write");
+        write(base.join("device/name"), "SN64G\n")
+            .await
+            .expect("write");
+        write(
+            base.join("device/ssr"),
+            "0000000000000000040000000000301e000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\n",
+        )
+        .await
+        .expect("write");
+        assert_eq!(
+            get_device_info("/dev/mmcblk0").await.unwrap(),
+            DeviceInfo {
+                capacity: 63864569856,
+                vendor: String::from("0x000003"),
+                model: String::from("SN64G"),
+                removable: true,
+                speed: String::from("Class 10, U3, V30"),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_sd_speed() {
+        assert_eq!(
+            sd_speed("00000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000").as_deref(),
+            Some("Class 4")
+        );
+        assert_eq!(sd_speed(""), None);
+        assert_eq!(sd_speed("0000000000000000ff"), None);
+    }
+
+    #[tokio::test]
+    async fn enumerate_block_devices() {
+        let _h = testing::start();
+
+        assert!(list_block_devices().await.is_err());
+        create_nodes().await.expect("create_nodes");
+
+        assert_eq!(
+            list_block_devices().await.unwrap(),
+            vec![String::from("mmcblk0"), String::from("nvme0n1")]
+        );
+        assert!(validate_block_device("/dev/nvme0n1").await.is_ok());
+        assert!(validate_block_device("nvme0n1").await.is_err());
+        assert!(validate_block_device("/dev/zram0").await.is_err());
+        assert!(validate_block_device("/dev/../nvme0n1").await.is_err());
+    }
+
+    #[test]
+    fn format_options() {
+        let options = HashMap::from([
+            (
+                String::from("filesystem"),
+                zvariant::OwnedValue::from(zvariant::Str::from("ext4")),
+            ),
+            (String::from("encrypt"), zvariant::OwnedValue::from(true)),
+            (
+                String::from("cluster_size"),
+                zvariant::OwnedValue::from(4096_u32),
+            ),
+        ]);
+        assert_eq!(
+            FormatOptions::try_from(&options).unwrap(),
+            FormatOptions {
+                filesystem: Some(String::from("ext4")),
+                encrypt: true,
+                cluster_size: Some(4096),
+            }
+        );
+        assert_eq!(
+            FormatOptions::try_from(&HashMap::new()).unwrap(),
+            FormatOptions::default()
+        );
+
+        let options = HashMap::from([(String::from("encrypt"), zvariant::OwnedValue::from(1_u32))]);
+        assert!(FormatOptions::try_from(&options).is_err());
+        let options = HashMap::from([(String::from("compress"), zvariant::OwnedValue::from(true))]);
+        assert!(FormatOptions::try_from(&options).is_err());
+    }
+
+    #[test]
+    fn parse_health() {
+        assert_eq!(
+            parse_storage_health(
+                "wear_level=3\ntemperature=38.5\navailable_spare=100\nsmart_passed=1\n"
+            )
+            .unwrap(),
+            StorageHealth {
+                wear_level: 3,
+                temperature: 38.5,
+                available_spare: 100,
+                passed: true,
+            }
+        );
+
+        let health =
+            parse_storage_health("model=foo\nwear_level=120\navailable_spare=5\nsmart_passed=0")
+                .unwrap();
+        assert_eq!(health.wear_level, 120);
+        assert!(health.temperature.is_nan());
+        assert!(!health.passed);
+
+        assert!(parse_storage_health("").is_err());
+        assert!(parse_storage_health("wear_level=3\nsmart_passed=1").is_err());
+        assert!(parse_storage_health("wear_level=x\navailable_spare=100\nsmart_passed=1").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, Result};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::os::fd::OwnedFd;
+use tokio::net::unix::pipe;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
+use tracing::{debug, error, warn};
+use zbus::{zvariant, Connection};
+
+use crate::{wait_on_handle, Service};
+
+pub(crate) const INHIBIT_WHO: &str = "SteamOS Manager";
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+pub(crate) trait Login1Manager {
+    async fn inhibit(
+        &self,
+        what: &str,
+        who: &str,
+        why: &str,
+        mode: &str,
+    ) -> zbus::Result<zvariant::OwnedFd>;
+
+    async fn suspend(&self, interactive: bool) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    async fn idle_hint(&self) -> zbus::Result<bool>;
+}
+
+pub(crate) enum SuspendInhibitCommand {
+    Inhibit(String, oneshot::Sender<Result<OwnedFd>>),
+    ListHandles(oneshot::Sender<HashMap<String, u32>>),
+}
+
+struct Inhibitor {
+    count: u32,
+    // The logind inhibitor lock is released when this is dropped
+    _lock: zvariant::OwnedFd,
+}
+
+pub(crate) struct SuspendInhibitService {
+    proxy: Login1ManagerProxy<'static>,
+    channel: UnboundedReceiver<SuspendInhibitCommand>,
+    handle_set: JoinSet<String>,
+    inhibitors: HashMap<String, Inhibitor>,
+}
+
+impl SuspendInhibitService {
+    pub async fn new(
+        channel: UnboundedReceiver<SuspendInhibitCommand>,
+        system: &Connection,
+    ) -> Result<SuspendInhibitService> {
+        let proxy = Login1ManagerProxy::new(system).await?;
+        Ok(SuspendInhibitService {
+            proxy,
+            channel,
+            handle_set: JoinSet::new(),
+            inhibitors: HashMap::new(),
+        })
+    }
+
+    async fn inhibit(&mut self, identifier: String) -> Result<OwnedFd> {
+        match self.inhibitors.entry(identifier.clone()) {
+            Entry::Occupied(mut e) => e.get_mut().count += 1,
+            Entry::Vacant(e) => {
+                debug!("Taking suspend inhibitor lock for {identifier}");
+                let lock = self
+                    .proxy
+                    .inhibit("sleep", INHIBIT_WHO, identifier.as_str(), "block")
+                    .await?;
+                e.insert(Inhibitor {
+                    count: 1,
+                    _lock: lock,
+                });
+            }
+        }
+        let (send, recv) = pipe::pipe()?;
+        self.handle_set.spawn(wait_on_handle(recv, identifier));
+        Ok(send.into_blocking_fd()?)
+    }
+
+    fn release(&mut self, identifier: String) {
+        match self.inhibitors.entry(identifier) {
+            Entry::Occupied(e) if e.get().count == 1 => {
+                debug!("Releasing suspend inhibitor lock for {}", e.key());
+                e.remove();
+            }
+            Entry::Occupied(mut e) => e.get_mut().count -= 1,
+            Entry::Vacant(_) => (),
+        }
+    }
+
+    async fn handle_command(&mut self, command: SuspendInhibitCommand) {
+        match command {
+            SuspendInhibitCommand::Inhibit(identifier, reply) => {
+                let fd = self.inhibit(identifier).await;
+                let _ = reply.send(fd);
+            }
+            SuspendInhibitCommand::ListHandles(reply) => {
+                let handles = self
+                    .inhibitors
+                    .iter()
+                    .map(|(identifier, inhibitor)| (identifier.clone(), inhibitor.count))
+                    .collect();
+                let _ = reply.send(handles);
+            }
+        }
+    }
+}
+
+impl Service for SuspendInhibitService {
+    const NAME: &'static str = "suspend-inhibit";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            tokio::select! {
+                message = self.channel.recv() => {
+                    let Some(message) = message else {
+                        bail!("Suspend inhibit service channel broke");
+                    };
+                    self.handle_command(message).await;
+                },
+                Some(identifier) = self.handle_set.join_next(), if !self.handle_set.is_empty() => {
+                    match identifier {
+                        Ok(identifier) => self.release(identifier),
+                        Err(e) => warn!("Failed to get closed suspend inhibit handle: {e}"),
+                    }
+                },
+            }
+        }
+    }
+
+    async fn shutdown(&mut self) -> Result<()> {
+        if !self.inhibitors.is_empty() {
+            error!("Shutting down with active suspend inhibitors, releasing them");
+            self.inhibitors.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing;
+    use std::time::Duration;
+    use tokio::io::Interest;
+    use tokio::sync::mpsc::unbounded_channel;
+    use tokio::time::timeout;
+
+    async fn list_handles(service: &mut SuspendInhibitService) -> HashMap<String, u32> {
+        let (tx, rx) = oneshot::channel();
+        service
+            .handle_command(SuspendInhibitCommand::ListHandles(tx))
+            .await;
+        rx.await.unwrap()
+    }
+
+    async fn released(lock: &pipe::Receiver) -> bool {
+        timeout(Duration::from_millis(100), lock.ready(Interest::READABLE))
+            .await
+            .map(|ready| ready.unwrap().is_read_closed())
+            .unwrap_or(false)
+    }
+
+    fn fake_lock(service: &mut SuspendInhibitService, identifier: &str) -> pipe::Receiver {
+        let (send, recv) = pipe::pipe().unwrap();
+        service.inhibitors.insert(
+            identifier.to_string(),
+            Inhibitor {
+                count: 0,
+                _lock: send.into_blocking_fd().unwrap().into(),
+            },
+        );
+        recv
+    }
+
+    #[tokio::test]
+    async fn handle_refcounting() {
+        let mut h = testing::start();
+
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let (_tx, rx) = unbounded_channel();
+        let mut service = SuspendInhibitService::new(rx, &connection)
+            .await
+            .expect("service");
+
+        assert!(list_handles(&mut service).await.is_empty());
+
+        // There is no logind on the test bus, so new locks can't be taken
+        assert!(service.inhibit(String::from("update")).await.is_err());
+        assert!(list_handles(&mut service).await.is_empty());
+
+        let shaders_lock = fake_lock(&mut service, "shaders");
+        let cloud_lock = fake_lock(&mut service, "cloud");
+
+        let shaders1 = service.inhibit(String::from("shaders")).await.unwrap();
+        let shaders2 = service.inhibit(String::from("shaders")).await.unwrap();
+        let cloud = service.inhibit(String::from("cloud")).await.unwrap();
+        assert_eq!(
+            list_handles(&mut service).await,
+            [(String::from("shaders"), 2), (String::from("cloud"), 1)].into()
+        );
+
+        drop(shaders1);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier);
+        assert!(!released(&shaders_lock).await);
+        assert_eq!(
+            list_handles(&mut service).await,
+            [(String::from("shaders"), 1), (String::from("cloud"), 1)].into()
+        );
+
+        drop(shaders2);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier);
+        assert!(released(&shaders_lock).await);
+        assert_eq!(
+            list_handles(&mut service).await,
+            [(String::from("cloud"), 1)].into()
+        );
+
+        drop(cloud);
+        let identifier = service.handle_set.join_next().await.unwrap().unwrap();
+        service.release(identifier);
+        assert!(released(&cloud_lock).await);
+        assert!(list_handles(&mut service).await.is_empty());
+    }
+}
@@ -10,7 +10,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use strum::{Display, EnumString};
 use zbus::proxy::CacheProperties;
-use zbus::zvariant::OwnedObjectPath;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath};
 use zbus::{self, Connection};
 
 #[zbus::proxy(
@@ -64,6 +64,8 @@ trait SystemdManager {
     async fn reload(&self) -> zbus::Result<()>;
 
     async fn get_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+
+    async fn subscribe(&self) -> zbus::Result<()>;
 }
 
 #[derive(Display, EnumString, PartialEq, Debug, Copy, Clone)]
@@ -87,6 +89,13 @@ pub async fn daemon_reload(connection: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Ask systemd to send unit property changes, which it doesn't do unless someone subscribed
+pub async fn subscribe(connection: &Connection) -> Result<()> {
+    let proxy = SystemdManagerProxy::new(connection).await?;
+    proxy.subscribe().await?;
+    Ok(())
+}
+
 impl<'dbus> SystemdUnit<'dbus> {
     pub async fn exists(connection: &Connection, name: &str) -> Result<bool> {
         let manager = SystemdManagerProxy::new(connection).await?;
@@ -133,7 +142,6 @@ impl<'dbus> SystemdUnit<'dbus> {
         Ok(())
     }
 
-    #[allow(unused)]
     pub async fn enable(&self) -> Result<bool> {
         let manager = SystemdManagerProxy::new(&self.connection).await?;
         let (_, res) = manager
@@ -142,7 +150,6 @@ impl<'dbus> SystemdUnit<'dbus> {
         Ok(!res.is_empty())
     }
 
-    #[allow(unused)]
     pub async fn disable(&self) -> Result<bool> {
         let manager = SystemdManagerProxy::new(&self.connection).await?;
         let res = manager
@@ -168,7 +175,19 @@ impl<'dbus> SystemdUnit<'dbus> {
     }
 
     pub async fn active(&self) -> Result<bool> {
-        Ok(self.proxy.active_state().await? == "active")
+        Ok(self.active_state().await? == "active")
+    }
+
+    pub async fn active_state(&self) -> Result<String> {
+        Ok(self.proxy.active_state().await?)
+    }
+
+    pub async fn unit_file_state(&self) -> Result<String> {
+        Ok(self.proxy.unit_file_state().await?)
+    }
+
+    pub fn path(&self) -> &ObjectPath<'_> {
+        self.proxy.inner().path()
     }
 
     pub async fn enabled(&self) -> Result<EnableState> {
@@ -199,7 +218,6 @@ pub mod test {
     use std::collections::HashMap;
     use std::time::Duration;
     use tokio::time::sleep;
-    use zbus::zvariant::ObjectPath;
     use zbus::{fdo, ObjectServer};
 
     #[test]
@@ -379,6 +397,10 @@ pub mod test {
             Ok(())
         }
 
+        async fn subscribe(&self) -> fdo::Result<()> {
+            Ok(())
+        }
+
         async fn get_unit(&mut self, unit: &str) -> fdo::Result<OwnedObjectPath> {
             Ok(
                 ObjectPath::try_from(format!("/org/freedesktop/systemd1/unit/{}", escape(unit)))
@@ -0,0 +1,241 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::oneshot;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+use tracing::debug;
+
+use crate::gpu::get_gpu_frequency;
+use crate::power::{get_battery_power_now, get_cpu_frequency, tdp_limit_manager, TdpLimitManager};
+use crate::Service;
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct TelemetryConfig {
+    /// Sampling interval in seconds, or 0 to disable sampling
+    pub sample_interval: u64,
+    /// Maximum number of samples kept in the history
+    pub history_length: usize,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> TelemetryConfig {
+        TelemetryConfig {
+            sample_interval: 5,
+            history_length: 720,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) struct PowerSample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Battery discharge rate in W, or NaN if unknown
+    pub battery_power: f64,
+    /// TDP limit in W, or 0 if unknown
+    pub tdp_limit: u32,
+    /// CPU frequency in MHz, or 0 if unknown
+    pub cpu_frequency: u32,
+    /// GPU frequency in MHz, or 0 if unknown
+    pub gpu_frequency: u32,
+}
+
+pub(crate) enum TelemetryCommand {
+    Configure(TelemetryConfig),
+    GetSamples(u64, oneshot::Sender<Vec<PowerSample>>),
+}
+
+pub(crate) struct TelemetryService {
+    channel: UnboundedReceiver<TelemetryCommand>,
+    config: TelemetryConfig,
+    interval: Interval,
+    history: VecDeque<PowerSample>,
+    tdp_limit_manager: Option<Box<dyn TdpLimitManager>>,
+}
+
+fn sample_interval(config: &TelemetryConfig) -> Interval {
+    let mut interval = interval(Duration::from_secs(config.sample_interval.max(1)));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+impl TelemetryService {
+    pub async fn new(channel: UnboundedReceiver<TelemetryCommand>) -> TelemetryService {
+        let config = TelemetryConfig::default();
+        TelemetryService {
+            channel,
+            interval: sample_interval(&config),
+            config,
+            history: VecDeque::new(),
+            tdp_limit_manager: tdp_limit_manager().await.ok(),
+        }
+    }
+
+    fn configure(&mut self, config: TelemetryConfig) {
+        debug!(
+            "Sampling telemetry every {}s, keeping {} samples",
+            config.sample_interval, config.history_length
+        );
+        self.interval = sample_interval(&config);
+        self.config = config;
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.history.len() > self.config.history_length {
+            self.history.pop_front();
+        }
+    }
+
+    async fn sample(&mut self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default();
+        let battery_power = match get_battery_power_now().await {
+            Ok(microwatts) => microwatts as f64 / 1_000_000.0,
+            Err(_) => f64::NAN,
+        };
+        let tdp_limit = match self.tdp_limit_manager.as_ref() {
+            Some(manager) => manager.get_tdp_limit().await.unwrap_or(0),
+            None => 0,
+        };
+        self.history.push_back(PowerSample {
+            timestamp,
+            battery_power,
+            tdp_limit,
+            cpu_frequency: get_cpu_frequency().await.unwrap_or(0),
+            gpu_frequency: get_gpu_frequency().await.unwrap_or(0),
+        });
+        self.truncate();
+    }
+
+    fn get_samples(&self, since: u64) -> Vec<PowerSample> {
+        self.history
+            .iter()
+            .filter(|sample| sample.timestamp > since)
+            .copied()
+            .collect()
+    }
+
+    fn handle_command(&mut self, command: TelemetryCommand) {
+        match command {
+            TelemetryCommand::Configure(config) => self.configure(config),
+            TelemetryCommand::GetSamples(since, reply) => {
+                let _ = reply.send(self.get_samples(since));
+            }
+        }
+    }
+}
+
+impl Service for TelemetryService {
+    const NAME: &'static str = "telemetry";
+
+    async fn run(&mut self) -> Result<()> {
+        loop {
+            tokio::select! {
+                message = self.channel.recv() => {
+                    let Some(message) = message else {
+                        bail!("Telemetry service channel broke");
+                    };
+                    self.handle_command(message);
+                },
+                _ = self.interval.tick(), if self.config.sample_interval > 0 => {
+                    self.sample().await;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gpu::AMDGPU_HWMON_NAME;
+    use crate::power::{find_battery, find_hwmon};
+    use crate::{path, testing};
+    use tokio::fs::write;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    async fn get_samples(service: &mut TelemetryService, since: u64) -> Vec<PowerSample> {
+        let (tx, rx) = oneshot::channel();
+        service.handle_command(TelemetryCommand::GetSamples(since, tx));
+        rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn sample_history() {
+        let _h = testing::start();
+
+        crate::power::test::create_nodes()
+            .await
+            .expect("create_nodes");
+
+        let (_tx, rx) = unbounded_channel();
+        let mut service = TelemetryService::new(rx).await;
+        service.handle_command(TelemetryCommand::Configure(TelemetryConfig {
+            sample_interval: 1,
+            history_length: 3,
+        }));
+        assert!(get_samples(&mut service, 0).await.is_empty());
+
+        service.sample().await;
+        let samples = get_samples(&mut service, 0).await;
+        assert_eq!(samples.len(), 1);
+        assert!(samples[0].battery_power.is_nan());
+        assert_eq!(samples[0].tdp_limit, 0);
+        assert_eq!(samples[0].cpu_frequency, 0);
+        assert_eq!(samples[0].gpu_frequency, 0);
+
+        let battery = find_battery().await.expect("find_battery");
+        write(battery.join("power_now"), "12500000\n")
+            .await
+            .expect("write");
+        write(
+            path("/sys/devices/system/cpu/cpufreq/policy0/scaling_cur_freq"),
+            "2800000\n",
+        )
+        .await
+        .expect("write");
+        let hwmon = find_hwmon(AMDGPU_HWMON_NAME).await.expect("find_hwmon");
+        write(hwmon.join("freq1_input"), "1600000000\n")
+            .await
+            .expect("write");
+
+        service.sample().await;
+        let samples = get_samples(&mut service, 0).await;
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].battery_power, 12.5);
+        assert_eq!(samples[1].cpu_frequency, 2800);
+        assert_eq!(samples[1].gpu_frequency, 1600);
+
+        for _ in 0..3 {
+            service.sample().await;
+        }
+        let samples = get_samples(&mut service, 0).await;
+        assert_eq!(samples.len(), 3);
+        assert!(samples
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+
+        let since = samples[0].timestamp;
+        let newer = get_samples(&mut service, since).await;
+        assert!(newer.iter().all(|sample| sample.timestamp > since));
+        assert!(get_samples(&mut service, u64::MAX).await.is_empty());
+
+        service.handle_command(TelemetryCommand::Configure(TelemetryConfig {
+            sample_interval: 1,
+            history_length: 1,
+        }));
+        assert_eq!(get_samples(&mut service, 0).await.len(), 1);
+    }
+}
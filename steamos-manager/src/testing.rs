@@ -22,6 +22,7 @@ use tracing::subscriber::set_global_default;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter, Registry};
 use zbus::connection::{Builder, Connection};
+use zbus::fdo::IntrospectableProxy;
 use zbus::object_server::Interface;
 use zbus::zvariant::ObjectPath;
 use zbus::Address;
@@ -227,6 +228,35 @@ impl<'a> InterfaceIntrospection<'a> {
         Self::from_xml(remote_interface_string.as_bytes(), I::name().to_string())
     }
 
+    /// Introspect every interface exported on an object, as a client would see it
+    pub async fn from_object<'p, P>(
+        connection: &Connection,
+        path: P,
+    ) -> Result<Vec<InterfaceIntrospection<'static>>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<zbus::Error>,
+    {
+        let unique_name = connection
+            .unique_name()
+            .ok_or(anyhow!("Connection has no unique name"))?;
+        let xml = IntrospectableProxy::builder(connection)
+            .destination(unique_name.as_str())?
+            .path(path)?
+            .build()
+            .await?
+            .introspect()
+            .await?;
+        let node = Node::from_reader(xml.as_bytes())?;
+        Ok(node
+            .interfaces()
+            .iter()
+            .map(|interface| InterfaceIntrospection {
+                interface: interface.clone(),
+            })
+            .collect())
+    }
+
     pub async fn from_local<'p, P: AsRef<Path>, S: AsRef<str>>(
         path: P,
         interface: S,
@@ -252,6 +282,10 @@ impl<'a> InterfaceIntrospection<'a> {
         })
     }
 
+    pub fn name(&self) -> String {
+        self.interface.name().to_string()
+    }
+
     fn collect_methods(&self) -> HashMap<String, &Method<'_>> {
         let mut map = HashMap::new();
         for method in self.interface.methods() {
@@ -0,0 +1,766 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, ensure, Result};
+use input_linux::Key;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::process::script_output;
+use crate::uinput::UInputDevice;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.locale1",
+    default_service = "org.freedesktop.locale1",
+    default_path = "/org/freedesktop/locale1"
+)]
+pub(crate) trait Locale1 {
+    #[zbus(property, name = "X11Layout")]
+    fn x11_layout(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "X11Variant")]
+    fn x11_variant(&self) -> zbus::Result<String>;
+}
+
+const KEYBOARD_NAME: &str = "steamos-manager-text-input";
+const XKBCLI_PATH: &str = "/usr/bin/xkbcli";
+
+/// Offset between XKB keycodes and evdev ones
+const XKB_KEYCODE_OFFSET: u16 = 8;
+/// Evdev codes of keys only found on some layouts: the extra ISO key, and the JIS ro and yen keys
+const EXTRA_KEY_CODES: [u16; 3] = [86, 89, 124];
+
+/// How long the window in which keys are counted for rate limiting lasts
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+/// How many keys can be sent within the rate limiting window
+const RATE_LIMIT_KEYS: usize = 256;
+
+/// Names of the keys that can be used in key combinations
+const KEY_NAMES: [(&str, Key); 78] = [
+    ("ctrl", Key::LeftCtrl),
+    ("shift", Key::LeftShift),
+    ("alt", Key::LeftAlt),
+    ("super", Key::LeftMeta),
+    ("esc", Key::Esc),
+    ("enter", Key::Enter),
+    ("tab", Key::Tab),
+    ("space", Key::Space),
+    ("backspace", Key::Backspace),
+    ("insert", Key::Insert),
+    ("delete", Key::Delete),
+    ("home", Key::Home),
+    ("end", Key::End),
+    ("pageup", Key::PageUp),
+    ("pagedown", Key::PageDown),
+    ("up", Key::Up),
+    ("down", Key::Down),
+    ("left", Key::Left),
+    ("right", Key::Right),
+    ("f1", Key::F1),
+    ("f2", Key::F2),
+    ("f3", Key::F3),
+    ("f4", Key::F4),
+    ("f5", Key::F5),
+    ("f6", Key::F6),
+    ("f7", Key::F7),
+    ("f8", Key::F8),
+    ("f9", Key::F9),
+    ("f10", Key::F10),
+    ("f11", Key::F11),
+    ("f12", Key::F12),
+    ("a", Key::A),
+    ("b", Key::B),
+    ("c", Key::C),
+    ("d", Key::D),
+    ("e", Key::E),
+    ("f", Key::F),
+    ("g", Key::G),
+    ("h", Key::H),
+    ("i", Key::I),
+    ("j", Key::J),
+    ("k", Key::K),
+    ("l", Key::L),
+    ("m", Key::M),
+    ("n", Key::N),
+    ("o", Key::O),
+    ("p", Key::P),
+    ("q", Key::Q),
+    ("r", Key::R),
+    ("s", Key::S),
+    ("t", Key::T),
+    ("u", Key::U),
+    ("v", Key::V),
+    ("w", Key::W),
+    ("x", Key::X),
+    ("y", Key::Y),
+    ("z", Key::Z),
+    ("1", Key::Num1),
+    ("2", Key::Num2),
+    ("3", Key::Num3),
+    ("4", Key::Num4),
+    ("5", Key::Num5),
+    ("6", Key::Num6),
+    ("7", Key::Num7),
+    ("8", Key::Num8),
+    ("9", Key::Num9),
+    ("0", Key::Num0),
+    ("minus", Key::Minus),
+    ("equal", Key::Equal),
+    ("leftbrace", Key::LeftBrace),
+    ("rightbrace", Key::RightBrace),
+    ("semicolon", Key::Semicolon),
+    ("apostrophe", Key::Apostrophe),
+    ("grave", Key::Grave),
+    ("backslash", Key::Backslash),
+    ("comma", Key::Comma),
+    ("dot", Key::Dot),
+    ("slash", Key::Slash),
+];
+
+/// Keysym names of the printable ASCII characters that aren't named after themselves
+const ASCII_KEYSYMS: [(&str, char); 36] = [
+    ("space", ' '),
+    ("Return", '\n'),
+    ("Tab", '\t'),
+    ("exclam", '!'),
+    ("quotedbl", '"'),
+    ("numbersign", '#'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("apostrophe", '\''),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("asterisk", '*'),
+    ("plus", '+'),
+    ("comma", ','),
+    ("minus", '-'),
+    ("period", '.'),
+    ("slash", '/'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("less", '<'),
+    ("equal", '='),
+    ("greater", '>'),
+    ("question", '?'),
+    ("at", '@'),
+    ("bracketleft", '['),
+    ("backslash", '\\'),
+    ("bracketright", ']'),
+    ("asciicircum", '^'),
+    ("underscore", '_'),
+    ("grave", '`'),
+    ("braceleft", '{'),
+    ("bar", '|'),
+    ("braceright", '}'),
+    ("asciitilde", '~'),
+    ("EuroSign", '€'),
+];
+
+/// Keysym names of the Latin-1 characters, starting from U+00A0
+const LATIN1_KEYSYMS: [&str; 96] = [
+    "nobreakspace",
+    "exclamdown",
+    "cent",
+    "sterling",
+    "currency",
+    "yen",
+    "brokenbar",
+    "section",
+    "diaeresis",
+    "copyright",
+    "ordfeminine",
+    "guillemotleft",
+    "notsign",
+    "hyphen",
+    "registered",
+    "macron",
+    "degree",
+    "plusminus",
+    "twosuperior",
+    "threesuperior",
+    "acute",
+    "mu",
+    "paragraph",
+    "periodcentered",
+    "cedilla",
+    "onesuperior",
+    "masculine",
+    "guillemotright",
+    "onequarter",
+    "onehalf",
+    "threequarters",
+    "questiondown",
+    "Agrave",
+    "Aacute",
+    "Acircumflex",
+    "Atilde",
+    "Adiaeresis",
+    "Aring",
+    "AE",
+    "Ccedilla",
+    "Egrave",
+    "Eacute",
+    "Ecircumflex",
+    "Ediaeresis",
+    "Igrave",
+    "Iacute",
+    "Icircumflex",
+    "Idiaeresis",
+    "ETH",
+    "Ntilde",
+    "Ograve",
+    "Oacute",
+    "Ocircumflex",
+    "Otilde",
+    "Odiaeresis",
+    "multiply",
+    "Oslash",
+    "Ugrave",
+    "Uacute",
+    "Ucircumflex",
+    "Udiaeresis",
+    "Yacute",
+    "THORN",
+    "ssharp",
+    "agrave",
+    "aacute",
+    "acircumflex",
+    "atilde",
+    "adiaeresis",
+    "aring",
+    "ae",
+    "ccedilla",
+    "egrave",
+    "eacute",
+    "ecircumflex",
+    "ediaeresis",
+    "igrave",
+    "iacute",
+    "icircumflex",
+    "idiaeresis",
+    "eth",
+    "ntilde",
+    "ograve",
+    "oacute",
+    "ocircumflex",
+    "otilde",
+    "odiaeresis",
+    "division",
+    "oslash",
+    "ugrave",
+    "uacute",
+    "ucircumflex",
+    "udiaeresis",
+    "yacute",
+    "thorn",
+    "ydiaeresis",
+];
+
+/// The modifiers to hold while pressing a key, following the shift levels of XKB layouts
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) enum Level {
+    Base,
+    Shift,
+    AltGr,
+    ShiftAltGr,
+}
+
+impl Level {
+    fn from_index(index: usize) -> Option<Level> {
+        Some(match index {
+            0 => Level::Base,
+            1 => Level::Shift,
+            2 => Level::AltGr,
+            3 => Level::ShiftAltGr,
+            _ => return None,
+        })
+    }
+
+    fn shift(self) -> bool {
+        matches!(self, Level::Shift | Level::ShiftAltGr)
+    }
+
+    fn altgr(self) -> bool {
+        matches!(self, Level::AltGr | Level::ShiftAltGr)
+    }
+}
+
+/// The character a keysym types, e.g. `a`, `exclam`, `eacute` or `U20AC`
+fn keysym_char(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return c.is_ascii_alphanumeric().then_some(c);
+    }
+    if let Some(code) = name.strip_prefix('U') {
+        if let Ok(code) = u32::from_str_radix(code, 16) {
+            return char::from_u32(code);
+        }
+    }
+    if let Some((_, c)) = ASCII_KEYSYMS.iter().find(|(keysym, _)| *keysym == name) {
+        return Some(*c);
+    }
+    let offset = LATIN1_KEYSYMS.iter().position(|keysym| *keysym == name)?;
+    char::from_u32(0xa0 + u32::try_from(offset).ok()?)
+}
+
+/// The keys that can be used for typing, which the virtual keyboard is created with
+fn typing_keys() -> Vec<Key> {
+    KEY_NAMES
+        .iter()
+        .map(|(_, key)| *key)
+        .chain([Key::RightAlt])
+        .chain(
+            EXTRA_KEY_CODES
+                .iter()
+                .filter_map(|code| Key::from_code(*code).ok()),
+        )
+        .collect()
+}
+
+/// Which key types each character on a keyboard layout
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Keymap {
+    keys: HashMap<char, (Key, Level)>,
+}
+
+impl Keymap {
+    /// The US layout, for when the active layout can't be loaded
+    pub(crate) fn us() -> Keymap {
+        let keys = (' '..='~')
+            .chain(['\n', '\t'])
+            .filter_map(|c| {
+                let (key, shift) = character_key(c)?;
+                Some((c, (key, if shift { Level::Shift } else { Level::Base })))
+            })
+            .collect();
+        Keymap { keys }
+    }
+
+    /// Parse a keymap as printed by `xkbcli compile-keymap`. Only the first group of each key
+    /// is used, which belongs to the first of the configured layouts.
+    pub(crate) fn parse(keymap: &str) -> Result<Keymap> {
+        // e.g. "<AC01> = 38;" and "alias <AC12> = <BKSL>;"
+        let mut keycodes = HashMap::new();
+        for line in keymap.lines() {
+            let line = line.trim().trim_end_matches(';');
+            let (name, value) = match line.strip_prefix("alias ") {
+                Some(alias) => match alias.split_once('=') {
+                    Some((name, target)) => match keycodes.get(target.trim()) {
+                        Some(code) => (name, *code),
+                        None => continue,
+                    },
+                    None => continue,
+                },
+                None => {
+                    let Some((name, code)) = line.split_once('=') else {
+                        continue;
+                    };
+                    let Ok(code) = code.trim().parse::<u16>() else {
+                        continue;
+                    };
+                    (name, code)
+                }
+            };
+            let name = name.trim();
+            if name.starts_with('<') && name.ends_with('>') {
+                keycodes.insert(name.to_string(), value);
+            }
+        }
+
+        let usable = typing_keys();
+        let symbols = keymap
+            .split_once("xkb_symbols")
+            .map(|(_, symbols)| symbols)
+            .ok_or_else(|| anyhow!("Keymap has no symbols"))?;
+        let mut keys: HashMap<char, (Key, Level)> = HashMap::new();
+        // e.g. "key <AC01> { [ a, A ] };", or with "symbols[1]= [ a, A ]" among other fields
+        for entry in symbols.split("key <").skip(1) {
+            let Some((name, body)) = entry.split_once('>') else {
+                continue;
+            };
+            let body = body.split_once("};").map_or(body, |(body, _)| body);
+            let body = body
+                .split_once("symbols[1]")
+                .map_or(body, |(_, symbols)| symbols);
+            let Some(levels) = body
+                .split_once('[')
+                .and_then(|(_, levels)| levels.split_once(']'))
+                .map(|(levels, _)| levels)
+            else {
+                continue;
+            };
+            let Some(key) = keycodes
+                .get(format!("<{name}>").as_str())
+                .and_then(|code| code.checked_sub(XKB_KEYCODE_OFFSET))
+                .and_then(|code| Key::from_code(code).ok())
+                .filter(|key| usable.contains(key))
+            else {
+                continue;
+            };
+            for (index, keysym) in levels.split(',').enumerate() {
+                let (Some(level), Some(c)) = (Level::from_index(index), keysym_char(keysym.trim()))
+                else {
+                    continue;
+                };
+                // Prefer whichever key needs the fewest modifiers
+                keys.entry(c)
+                    .and_modify(|entry| {
+                        if level < entry.1 {
+                            *entry = (key, level);
+                        }
+                    })
+                    .or_insert((key, level));
+            }
+        }
+        ensure!(!keys.is_empty(), "Keymap has no usable keys");
+        Ok(Keymap { keys })
+    }
+
+    pub(crate) fn text_keys(&self, text: &str) -> Result<Vec<(Key, Level)>> {
+        text.chars()
+            .map(|c| {
+                self.keys
+                    .get(&c)
+                    .copied()
+                    .ok_or_else(|| anyhow!("Unsupported character {c:?}"))
+            })
+            .collect()
+    }
+}
+
+async fn load_keymap(layout: &str, variant: &str) -> Result<Keymap> {
+    let output = script_output(
+        XKBCLI_PATH,
+        &["compile-keymap", "--layout", layout, "--variant", variant],
+    )
+    .await?;
+    Keymap::parse(output.as_str())
+}
+
+/// The key that types a character on a US layout, and whether shift needs to be held for it
+fn character_key(c: char) -> Option<(Key, bool)> {
+    if c.is_ascii_alphanumeric() {
+        let name = c.to_ascii_lowercase().to_string();
+        let key = KEY_NAMES.iter().find(|(key_name, _)| *key_name == name)?.1;
+        return Some((key, c.is_ascii_uppercase()));
+    }
+    Some(match c {
+        ' ' => (Key::Space, false),
+        '\n' => (Key::Enter, false),
+        '\t' => (Key::Tab, false),
+        '-' => (Key::Minus, false),
+        '_' => (Key::Minus, true),
+        '=' => (Key::Equal, false),
+        '+' => (Key::Equal, true),
+        '[' => (Key::LeftBrace, false),
+        '{' => (Key::LeftBrace, true),
+        ']' => (Key::RightBrace, false),
+        '}' => (Key::RightBrace, true),
+        ';' => (Key::Semicolon, false),
+        ':' => (Key::Semicolon, true),
+        '\'' => (Key::Apostrophe, false),
+        '"' => (Key::Apostrophe, true),
+        '`' => (Key::Grave, false),
+        '~' => (Key::Grave, true),
+        '\\' => (Key::Backslash, false),
+        '|' => (Key::Backslash, true),
+        ',' => (Key::Comma, false),
+        '<' => (Key::Comma, true),
+        '.' => (Key::Dot, false),
+        '>' => (Key::Dot, true),
+        '/' => (Key::Slash, false),
+        '?' => (Key::Slash, true),
+        '!' => (Key::Num1, true),
+        '@' => (Key::Num2, true),
+        '#' => (Key::Num3, true),
+        '$' => (Key::Num4, true),
+        '%' => (Key::Num5, true),
+        '^' => (Key::Num6, true),
+        '&' => (Key::Num7, true),
+        '*' => (Key::Num8, true),
+        '(' => (Key::Num9, true),
+        ')' => (Key::Num0, true),
+        _ => return None,
+    })
+}
+
+pub(crate) fn combo_keys(names: &[impl AsRef<str>]) -> Result<Vec<Key>> {
+    ensure!(!names.is_empty(), "Empty key combination");
+    names
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            KEY_NAMES
+                .iter()
+                .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+                .map(|(_, key)| *key)
+                .ok_or_else(|| anyhow!("Unknown key {name}"))
+        })
+        .collect()
+}
+
+pub(crate) struct TextInput {
+    keyboard: UInputDevice,
+    window_start: Instant,
+    window_keys: usize,
+    keymap: Keymap,
+    /// The layout and variant the keymap was loaded for
+    layout: Option<(String, String)>,
+}
+
+impl TextInput {
+    pub(crate) fn new() -> Result<TextInput> {
+        let mut keyboard = UInputDevice::new()?;
+        keyboard.set_name(KEYBOARD_NAME.to_string())?;
+        keyboard.open(typing_keys().as_slice())?;
+        Ok(TextInput {
+            keyboard,
+            window_start: Instant::now(),
+            window_keys: 0,
+            keymap: Keymap::us(),
+            layout: None,
+        })
+    }
+
+    /// Type with the keymap of a keyboard layout, falling back to the US layout if it can't be
+    /// loaded
+    pub(crate) async fn use_layout(&mut self, layout: &str, variant: &str) {
+        if self
+            .layout
+            .as_ref()
+            .is_some_and(|(current, current_variant)| {
+                current == layout && current_variant == variant
+            })
+        {
+            return;
+        }
+        self.keymap = if layout.is_empty() {
+            Keymap::us()
+        } else {
+            match load_keymap(layout, variant).await {
+                Ok(keymap) => keymap,
+                Err(e) => {
+                    warn!("Failed to load the keymap of layout {layout}: {e}");
+                    Keymap::us()
+                }
+            }
+        };
+        self.layout = Some((layout.to_string(), variant.to_string()));
+    }
+
+    pub(crate) fn text_keys(&self, text: &str) -> Result<Vec<(Key, Level)>> {
+        self.keymap.text_keys(text)
+    }
+
+    /// Account for sending a number of keys, returning whether that stays within the rate limit
+    fn allow_at(&mut self, keys: usize, now: Instant) -> bool {
+        if now.duration_since(self.window_start) >= RATE_LIMIT_WINDOW {
+            self.window_start = now;
+            self.window_keys = 0;
+        }
+        if self.window_keys + keys > RATE_LIMIT_KEYS {
+            return false;
+        }
+        self.window_keys += keys;
+        true
+    }
+
+    pub(crate) fn allow(&mut self, keys: usize) -> bool {
+        self.allow_at(keys, Instant::now())
+    }
+
+    pub(crate) fn type_keys(&mut self, keys: &[(Key, Level)]) -> Result<()> {
+        for (key, level) in keys.iter().copied() {
+            if level.altgr() {
+                self.keyboard.key_down(Key::RightAlt)?;
+            }
+            if level.shift() {
+                self.keyboard.key_down(Key::LeftShift)?;
+            }
+            self.keyboard.key_press(key)?;
+            if level.shift() {
+                self.keyboard.key_up(Key::LeftShift)?;
+            }
+            if level.altgr() {
+                self.keyboard.key_up(Key::RightAlt)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn press_combo(&mut self, keys: &[Key]) -> Result<()> {
+        for key in keys.iter().copied() {
+            self.keyboard.key_down(key)?;
+        }
+        for key in keys.iter().rev().copied() {
+            self.keyboard.key_up(key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use input_linux::KeyState;
+
+    #[test]
+    fn keys() {
+        let keymap = Keymap::us();
+        assert_eq!(
+            keymap.text_keys("aB3!\n").unwrap(),
+            [
+                (Key::A, Level::Base),
+                (Key::B, Level::Shift),
+                (Key::Num3, Level::Base),
+                (Key::Num1, Level::Shift),
+                (Key::Enter, Level::Base)
+            ]
+        );
+        assert!(keymap.text_keys("é").is_err());
+        assert!(keymap.text_keys("").unwrap().is_empty());
+
+        assert_eq!(
+            combo_keys(&["Ctrl", "alt", "F4"]).unwrap(),
+            [Key::LeftCtrl, Key::LeftAlt, Key::F4]
+        );
+        assert!(combo_keys(&["ctrl", "hyper"]).is_err());
+        assert!(combo_keys(&[] as &[&str]).is_err());
+    }
+
+    #[test]
+    fn keysyms() {
+        assert_eq!(keysym_char("a"), Some('a'));
+        assert_eq!(keysym_char("Z"), Some('Z'));
+        assert_eq!(keysym_char("exclam"), Some('!'));
+        assert_eq!(keysym_char("nobreakspace"), Some('\u{a0}'));
+        assert_eq!(keysym_char("eacute"), Some('é'));
+        assert_eq!(keysym_char("ydiaeresis"), Some('ÿ'));
+        assert_eq!(keysym_char("U20AC"), Some('€'));
+        assert_eq!(keysym_char("dead_acute"), None);
+        assert_eq!(keysym_char("NoSymbol"), None);
+    }
+
+    #[test]
+    fn parse_keymap() {
+        let keymap = "xkb_keymap {
+xkb_keycodes \"(unnamed)\" {
+\tminimum = 8;
+\tmaximum = 255;
+\t<AE01>               = 10;
+\t<AD01>               = 24;
+\t<AD06>               = 29;
+\t<AC01>               = 38;
+\t<AB01>               = 52;
+\t<LSGT>               = 94;
+\t<RTRN>               = 36;
+\talias <AC12>         = <BKSL>;
+\tindicator 1 = \"Caps Lock\";
+};
+
+xkb_symbols \"(unnamed)\" {
+\tname[Group1]=\"German\";
+
+\tkey <AE01>               {\t[               1,          exclam,     onesuperior,      exclamdown ] };
+\tkey <AD01>               {
+\t\ttype= \"FOUR_LEVEL_SEMIALPHABETIC\",
+\t\tsymbols[1]= [               q,               Q,              at,     Greek_OMEGA ]
+\t};
+\tkey <AD06>               {\t[               z,               Z ] };
+\tkey <AC01>               {\t[               a,               A,              ae,              AE ] };
+\tkey <AB01>               {\t[               y,               Y ] };
+\tkey <LSGT>               {\t[            less,         greater,             bar ] };
+\tkey <RTRN>               {\t[          Return ] };
+\tkey <I147>               {\t[   XF86MenuKB ] };
+};
+};
+";
+        let keymap = Keymap::parse(keymap).unwrap();
+        assert_eq!(
+            keymap.text_keys("zyQ@!æ|\n").unwrap(),
+            [
+                (Key::Y, Level::Base),
+                (Key::Z, Level::Base),
+                (Key::Q, Level::Shift),
+                (Key::Q, Level::AltGr),
+                (Key::Num1, Level::Shift),
+                (Key::A, Level::AltGr),
+                (Key::from_code(86).unwrap(), Level::AltGr),
+                (Key::Enter, Level::Base),
+            ]
+        );
+        assert!(keymap.text_keys("b").is_err());
+        assert!(Keymap::parse("xkb_keymap {\n};").is_err());
+    }
+
+    #[test]
+    fn rate_limit() {
+        let mut input = TextInput::new().unwrap();
+        let start = Instant::now();
+        assert!(input.allow_at(RATE_LIMIT_KEYS - 1, start));
+        assert!(input.allow_at(1, start));
+        assert!(!input.allow_at(1, start + Duration::from_millis(500)));
+        assert!(input.allow_at(RATE_LIMIT_KEYS, start + RATE_LIMIT_WINDOW));
+        assert!(!input.allow_at(RATE_LIMIT_KEYS + 1, start + RATE_LIMIT_WINDOW * 3));
+    }
+
+    #[test]
+    fn type_text() {
+        let mut input = TextInput::new().unwrap();
+        input.type_keys(&input.text_keys("a!").unwrap()).unwrap();
+        input
+            .keyboard
+            .expect_key(Key::A, KeyState::PRESSED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input
+            .keyboard
+            .expect_key(Key::A, KeyState::RELEASED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input
+            .keyboard
+            .expect_key(Key::LeftShift, KeyState::PRESSED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input
+            .keyboard
+            .expect_key(Key::Num1, KeyState::PRESSED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input
+            .keyboard
+            .expect_key(Key::Num1, KeyState::RELEASED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input
+            .keyboard
+            .expect_key(Key::LeftShift, KeyState::RELEASED)
+            .unwrap();
+        input.keyboard.expect_sync().unwrap();
+        input.keyboard.expect_empty().unwrap();
+    }
+
+    #[test]
+    fn key_combo() {
+        let mut input = TextInput::new().unwrap();
+        input
+            .press_combo(&combo_keys(&["ctrl", "c"]).unwrap())
+            .unwrap();
+        for (key, state) in [
+            (Key::LeftCtrl, KeyState::PRESSED),
+            (Key::C, KeyState::PRESSED),
+            (Key::C, KeyState::RELEASED),
+            (Key::LeftCtrl, KeyState::RELEASED),
+        ] {
+            input.keyboard.expect_key(key, state).unwrap();
+            input.keyboard.expect_sync().unwrap();
+        }
+        input.keyboard.expect_empty().unwrap();
+    }
+}
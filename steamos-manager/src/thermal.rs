@@ -0,0 +1,366 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use strum::{Display, EnumString};
+use tokio::fs::{read_dir, read_to_string};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::{debug, warn};
+use zbus::Connection;
+
+use crate::manager::user::{Thermal1, MANAGER_PATH};
+use crate::path;
+use crate::policy::Guarded;
+use crate::power::HWMON_PREFIX;
+use crate::Service;
+
+const CPU_HWMON_NAMES: &[&str] = &["k10temp", "coretemp", "zenpower", "cpu_thermal"];
+const GPU_HWMON_NAMES: &[&str] = &["amdgpu", "radeon", "nouveau", "i915", "xe"];
+const SSD_HWMON_NAMES: &[&str] = &["nvme", "drivetemp"];
+
+#[derive(Display, EnumString, PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub(crate) enum SensorKind {
+    Cpu,
+    Gpu,
+    Battery,
+    Ssd,
+    Other,
+}
+
+impl SensorKind {
+    fn from_hwmon_name(name: &str) -> SensorKind {
+        if CPU_HWMON_NAMES.contains(&name) {
+            SensorKind::Cpu
+        } else if GPU_HWMON_NAMES.contains(&name) {
+            SensorKind::Gpu
+        } else if SSD_HWMON_NAMES.contains(&name) {
+            SensorKind::Ssd
+        } else if name.starts_with("BAT") || name.starts_with("battery") {
+            SensorKind::Battery
+        } else {
+            SensorKind::Other
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ThermalSensor {
+    pub name: String,
+    pub label: String,
+    pub kind: SensorKind,
+    input: PathBuf,
+}
+
+impl ThermalSensor {
+    pub(crate) async fn read(&self) -> Result<f64> {
+        // hwmon temperatures are in millidegrees Celsius
+        let value: i64 = read_to_string(&self.input)
+            .await
+            .map_err(|message| anyhow!("Error reading sysfs: {message}"))?
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("Error parsing value: {e}"))?;
+        Ok(value as f64 / 1000.0)
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct ThermalMonitorConfig {
+    /// Polling interval in seconds
+    pub poll_interval: u64,
+    /// Temperature thresholds in °C, keyed by sensor kind
+    pub thresholds: HashMap<String, Vec<u32>>,
+}
+
+impl Default for ThermalMonitorConfig {
+    fn default() -> ThermalMonitorConfig {
+        ThermalMonitorConfig {
+            poll_interval: 5,
+            thresholds: HashMap::new(),
+        }
+    }
+}
+
+pub(crate) struct ThermalMonitor {
+    session: Connection,
+    interval: Duration,
+    thresholds: HashMap<SensorKind, Vec<u32>>,
+    bands: HashMap<SensorKind, usize>,
+}
+
+pub(crate) async fn list_sensors() -> Result<Vec<ThermalSensor>> {
+    let mut sensors = Vec::new();
+    let mut dir = read_dir(path(HWMON_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let base = entry.path();
+        let Ok(name) = read_to_string(base.join("name")).await else {
+            continue;
+        };
+        let name = name.trim().to_string();
+        let kind = SensorKind::from_hwmon_name(name.as_str());
+
+        let mut inputs = Vec::new();
+        let mut attributes = read_dir(&base).await?;
+        while let Some(attribute) = attributes.next_entry().await? {
+            let file_name = attribute.file_name();
+            let Some(index) = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_prefix("temp"))
+                .and_then(|file_name| file_name.strip_suffix("_input"))
+                .and_then(|index| index.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            inputs.push(index);
+        }
+        inputs.sort_unstable();
+
+        for index in inputs {
+            let label = match read_to_string(base.join(format!("temp{index}_label"))).await {
+                Ok(label) => label.trim().to_string(),
+                Err(_) => format!("temp{index}"),
+            };
+            sensors.push(ThermalSensor {
+                name: name.clone(),
+                label,
+                kind,
+                input: base.join(format!("temp{index}_input")),
+            });
+        }
+    }
+    sensors.sort_by(|a, b| a.name.cmp(&b.name).then(a.label.cmp(&b.label)));
+    Ok(sensors)
+}
+
+pub(crate) async fn get_temperature(kind: SensorKind) -> Result<f64> {
+    let mut temperature = None;
+    for sensor in list_sensors().await? {
+        if sensor.kind != kind {
+            continue;
+        }
+        let value = sensor.read().await?;
+        temperature = Some(temperature.map_or(value, |t: f64| t.max(value)));
+    }
+    temperature.ok_or(anyhow!("No {kind} temperature sensor found"))
+}
+
+impl ThermalMonitor {
+    pub(crate) fn init(session: &Connection, config: &ThermalMonitorConfig) -> ThermalMonitor {
+        let mut thresholds = HashMap::new();
+        for (kind, values) in config.thresholds.iter() {
+            match SensorKind::from_str(kind.as_str()) {
+                Ok(SensorKind::Other) | Err(_) => {
+                    warn!("Ignoring thresholds for unknown sensor kind {kind}")
+                }
+                Ok(kind) => {
+                    let mut values = values.clone();
+                    values.sort_unstable();
+                    thresholds.insert(kind, values);
+                }
+            }
+        }
+        ThermalMonitor {
+            session: session.clone(),
+            interval: Duration::from_secs(config.poll_interval.max(1)),
+            thresholds,
+            bands: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn has_thresholds(&self) -> bool {
+        self.thresholds.values().any(|values| !values.is_empty())
+    }
+
+    fn band(thresholds: &[u32], temperature: f64) -> usize {
+        thresholds
+            .iter()
+            .filter(|threshold| temperature >= f64::from(**threshold))
+            .count()
+    }
+
+    async fn poll(&mut self) -> Vec<SensorKind> {
+        let mut crossed = Vec::new();
+        for (kind, thresholds) in self.thresholds.iter() {
+            let Ok(temperature) = get_temperature(*kind).await else {
+                continue;
+            };
+            let band = ThermalMonitor::band(thresholds, temperature);
+            match self.bands.insert(*kind, band) {
+                Some(old) if old != band => {
+                    debug!("{kind} temperature crossed a threshold: {temperature}°C");
+                    crossed.push(*kind);
+                }
+                _ => (),
+            }
+        }
+        crossed
+    }
+}
+
+impl Service for ThermalMonitor {
+    const NAME: &'static str = "thermal-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let mut interval = interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let crossed = self.poll().await;
+            if crossed.is_empty() {
+                continue;
+            }
+            // Thermal1 comes and goes with hotplugged sensors, so look it up every time
+            let Ok(thermal) = self
+                .session
+                .object_server()
+                .interface::<_, Guarded<Thermal1>>(MANAGER_PATH)
+                .await
+            else {
+                continue;
+            };
+            let ctx = thermal.signal_emitter();
+            for kind in crossed {
+                if let Err(e) = thermal.get().await.temperature_changed(kind, ctx).await {
+                    warn!("Failed to report {kind} temperature change: {e}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        let base = path(HWMON_PREFIX).join("hwmon2");
+        create_dir_all(&base).await?;
+        write(base.join("name"), "k10temp\n").await?;
+        write(base.join("temp1_input"), "45500\n").await?;
+        write(base.join("temp1_label"), "Tctl\n").await?;
+
+        let base = path(HWMON_PREFIX).join("hwmon4");
+        create_dir_all(&base).await?;
+        write(base.join("name"), "nvme\n").await?;
+        write(base.join("temp1_input"), "38850\n").await?;
+        write(base.join("temp1_label"), "Composite\n").await?;
+        write(base.join("temp2_input"), "41850\n").await?;
+        write(base.join("temp2_label"), "Sensor 1\n").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_sensors() {
+        let _h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        let base = path(HWMON_PREFIX).join("hwmon7");
+        create_dir_all(&base).await.expect("create_dir_all");
+        write(base.join("name"), "acpitz\n").await.expect("write");
+        write(base.join("temp1_input"), "-5000\n")
+            .await
+            .expect("write");
+
+        let sensors = list_sensors().await.expect("list_sensors");
+        let sensors: Vec<(String, String, SensorKind)> = sensors
+            .into_iter()
+            .map(|sensor| (sensor.name, sensor.label, sensor.kind))
+            .collect();
+        assert_eq!(
+            sensors,
+            vec![
+                (
+                    String::from("acpitz"),
+                    String::from("temp1"),
+                    SensorKind::Other
+                ),
+                (
+                    String::from("k10temp"),
+                    String::from("Tctl"),
+                    SensorKind::Cpu
+                ),
+                (
+                    String::from("nvme"),
+                    String::from("Composite"),
+                    SensorKind::Ssd
+                ),
+                (
+                    String::from("nvme"),
+                    String::from("Sensor 1"),
+                    SensorKind::Ssd
+                ),
+            ]
+        );
+
+        assert_eq!(get_temperature(SensorKind::Cpu).await.unwrap(), 45.5);
+        assert_eq!(get_temperature(SensorKind::Ssd).await.unwrap(), 41.85);
+        assert_eq!(get_temperature(SensorKind::Other).await.unwrap(), -5.0);
+        assert!(get_temperature(SensorKind::Gpu).await.is_err());
+    }
+
+    #[test]
+    fn sensor_kind_from_hwmon_name() {
+        assert_eq!(SensorKind::from_hwmon_name("k10temp"), SensorKind::Cpu);
+        assert_eq!(SensorKind::from_hwmon_name("amdgpu"), SensorKind::Gpu);
+        assert_eq!(SensorKind::from_hwmon_name("BAT1"), SensorKind::Battery);
+        assert_eq!(SensorKind::from_hwmon_name("nvme"), SensorKind::Ssd);
+        assert_eq!(SensorKind::from_hwmon_name("acpitz"), SensorKind::Other);
+    }
+
+    #[tokio::test]
+    async fn threshold_crossing() {
+        let mut h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        let config = ThermalMonitorConfig {
+            poll_interval: 1,
+            thresholds: HashMap::from([
+                (String::from("cpu"), vec![90, 50]),
+                (String::from("bogus"), vec![10]),
+            ]),
+        };
+        let connection = h.new_dbus().await.expect("new_dbus");
+        let mut monitor = ThermalMonitor::init(&connection, &config);
+        assert!(monitor.has_thresholds());
+        assert_eq!(monitor.thresholds.len(), 1);
+
+        // The first poll only records the current state
+        assert!(monitor.poll().await.is_empty());
+        assert!(monitor.poll().await.is_empty());
+
+        let base = path(HWMON_PREFIX).join("hwmon2");
+        write(base.join("temp1_input"), "49000\n")
+            .await
+            .expect("write");
+        assert!(monitor.poll().await.is_empty());
+
+        write(base.join("temp1_input"), "55000\n")
+            .await
+            .expect("write");
+        assert_eq!(monitor.poll().await, vec![SensorKind::Cpu]);
+        assert!(monitor.poll().await.is_empty());
+
+        write(base.join("temp1_input"), "95000\n")
+            .await
+            .expect("write");
+        assert_eq!(monitor.poll().await, vec![SensorKind::Cpu]);
+
+        write(base.join("temp1_input"), "40000\n")
+            .await
+            .expect("write");
+        assert_eq!(monitor.poll().await, vec![SensorKind::Cpu]);
+    }
+}
@@ -5,7 +5,8 @@
  * SPDX-License-Identifier: MIT
  */
 
-use anyhow::{anyhow, bail, ensure, Result};
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeSet, HashMap};
 use std::os::fd::AsFd;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -13,13 +14,21 @@ use tokio::io::unix::AsyncFd;
 use tokio::io::Interest;
 use tokio::select;
 use tokio::sync::mpsc::{channel, unbounded_channel, Receiver, Sender, UnboundedSender};
+use tokio::sync::watch;
 use tokio::task::{spawn, JoinHandle};
 use tokio::time::sleep;
-use tracing::debug;
+use tokio_stream::StreamExt;
+use tracing::{debug, error, warn};
 use udev::{Event, EventType, MonitorBuilder};
+use zbus::message::Header;
+use zbus::names::BusName;
 use zbus::object_server::{InterfaceRef, SignalEmitter};
-use zbus::{self, interface, Connection};
+use zbus::{self, fdo, interface, Connection, Proxy};
 
+use crate::cache::invalidate_sysfs_caches;
+use crate::cec::cec_changed;
+use crate::manager::user::{root_manager_proxy, update_hotplug_interfaces, Battery1};
+use crate::policy::Guarded;
 use crate::Service;
 
 const PATH: &str = "/com/steampowered/SteamOSManager1";
@@ -30,12 +39,44 @@ where
 {
     shutdown_sender: Sender<()>,
     shutdown_receiver: Option<Receiver<()>>,
-    udev_object: InterfaceRef<UdevDbusObject>,
+    udev_object: InterfaceRef<Guarded<UdevDbusObject>>,
+    connection: Connection,
+    proxy: Proxy<'static>,
+    power_supply: UnboundedSender<()>,
+    device_monitor: Option<JoinHandle<()>>,
 }
 
 struct UdevDbusObject
 where
-    Self: 'static + Send, {}
+    Self: 'static + Send,
+{
+    subscriptions: HashMap<u32, DeviceSubscription>,
+    next_token: u32,
+    /// What the device monitor has to listen for, or `None` while nobody is subscribed
+    filter: watch::Sender<Option<DeviceFilter>>,
+}
+
+#[derive(Debug)]
+struct DeviceSubscription {
+    sender: String,
+    subsystem: String,
+    tag: String,
+}
+
+/// The subsystems and tags the device monitor passes on. Empty sets let everything through.
+#[derive(Clone, Default, PartialEq, Debug)]
+struct DeviceFilter {
+    subsystems: BTreeSet<String>,
+    tags: BTreeSet<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct DeviceEvent {
+    action: String,
+    subsystem: String,
+    devpath: String,
+    tags: Vec<String>,
+}
 
 #[derive(Debug)]
 enum UdevEvent {
@@ -44,6 +85,109 @@ enum UdevEvent {
         port: String,
         count: u64,
     },
+    BatteryChanged,
+    PowerSupplyChanged,
+    CecChanged,
+    HardwareChanged,
+    Device(DeviceEvent),
+}
+
+impl DeviceSubscription {
+    fn matches(&self, event: &DeviceEvent) -> bool {
+        (self.subsystem.is_empty() || self.subsystem == event.subsystem)
+            && (self.tag.is_empty() || event.tags.contains(&self.tag))
+    }
+}
+
+impl Default for UdevDbusObject {
+    fn default() -> UdevDbusObject {
+        UdevDbusObject {
+            subscriptions: HashMap::new(),
+            next_token: 0,
+            filter: watch::channel(None).0,
+        }
+    }
+}
+
+impl UdevDbusObject {
+    fn remove_subscriber(&mut self, sender: &str) {
+        self.subscriptions
+            .retain(|_, subscription| subscription.sender != sender);
+        self.update_filter();
+    }
+
+    fn device_filter(&self) -> Option<DeviceFilter> {
+        if self.subscriptions.is_empty() {
+            return None;
+        }
+        // A subscription without a subsystem or tag takes every event, whatever the others want
+        let subscriptions = self.subscriptions.values();
+        let mut filter = DeviceFilter::default();
+        if subscriptions
+            .clone()
+            .all(|subscription| !subscription.subsystem.is_empty())
+        {
+            filter.subsystems = subscriptions
+                .clone()
+                .map(|subscription| subscription.subsystem.clone())
+                .collect();
+        }
+        if subscriptions
+            .clone()
+            .all(|subscription| !subscription.tag.is_empty())
+        {
+            filter.tags = subscriptions
+                .map(|subscription| subscription.tag.clone())
+                .collect();
+        }
+        Some(filter)
+    }
+
+    fn update_filter(&self) {
+        let filter = self.device_filter();
+        self.filter.send_if_modified(|current| {
+            if *current == filter {
+                return false;
+            }
+            *current = filter;
+            true
+        });
+    }
+
+    fn matching_subscribers(&self, event: &DeviceEvent) -> Vec<(u32, String)> {
+        self.subscriptions
+            .iter()
+            .filter(|(_, subscription)| subscription.matches(event))
+            .map(|(token, subscription)| (*token, subscription.sender.clone()))
+            .collect()
+    }
+}
+
+/// Send a device event only to the clients that subscribed to it
+async fn emit_device_event(
+    object: &InterfaceRef<Guarded<UdevDbusObject>>,
+    event: &DeviceEvent,
+) -> Result<()> {
+    let subscribers = object.get().await.matching_subscribers(event);
+    for (token, sender) in subscribers {
+        let emitter = object
+            .signal_emitter()
+            .clone()
+            .set_destination(BusName::try_from(sender.as_str())?);
+        // The subscriber may have just dropped off the bus, which shouldn't stop the others
+        if let Err(e) = UdevDbusObject::device_event(
+            &emitter,
+            token,
+            event.action.as_str(),
+            event.subsystem.as_str(),
+            event.devpath.as_str(),
+        )
+        .await
+        {
+            warn!("Failed to send device event to {sender}: {e}");
+        }
+    }
+    Ok(())
 }
 
 impl Service for UdevMonitor {
@@ -56,12 +200,32 @@ impl Service for UdevMonitor {
             .take()
             .ok_or(anyhow!("UdevMonitor cannot be run twice"))?;
         let mut handle = spawn(run_udev(ev_sender, shutdown_receiver));
+        let mut owner_changed = fdo::DBusProxy::new(&self.connection)
+            .await?
+            .receive_name_owner_changed()
+            .await?;
+        let mut filter = self.udev_object.get().await.filter.subscribe();
+        let current = filter.borrow_and_update().clone();
+        self.restart_device_monitor(current, &ev_sender);
 
         loop {
             let handle = &mut handle;
             let ev = tokio::select! {
                 r = handle => break r?,
                 r = ev_receiver.recv() => r.ok_or(anyhow!("udev event pipe broke"))?,
+                Ok(()) = filter.changed() => {
+                    let current = filter.borrow_and_update().clone();
+                    self.restart_device_monitor(current, &ev_sender);
+                    continue;
+                }
+                Some(signal) = owner_changed.next() => {
+                    // Subscriptions go away along with the client that made them
+                    let args = signal.args()?;
+                    if let (BusName::Unique(name), None) = (args.name(), args.new_owner().as_ref()) {
+                        self.udev_object.get_mut().await.remove_subscriber(name.as_str());
+                    }
+                    continue;
+                }
             };
             match ev {
                 UdevEvent::OverCurrent {
@@ -77,35 +241,148 @@ impl Service for UdevMonitor {
                     )
                     .await?;
                 }
+                UdevEvent::BatteryChanged => {
+                    let Ok(battery) = self
+                        .connection
+                        .object_server()
+                        .interface::<_, Guarded<Battery1>>(PATH)
+                        .await
+                    else {
+                        continue;
+                    };
+                    let ctx = battery.signal_emitter();
+                    battery.get().await.battery_changed(ctx).await;
+                }
+                UdevEvent::PowerSupplyChanged => {
+                    // Nobody may be listening if the power source monitor couldn't start
+                    let _ = self.power_supply.send(());
+                }
+                UdevEvent::CecChanged => cec_changed(),
+                UdevEvent::HardwareChanged => {
+                    invalidate_sysfs_caches();
+                    if let Err(e) =
+                        update_hotplug_interfaces(&self.proxy, self.connection.object_server())
+                            .await
+                    {
+                        warn!("Failed to update hotplugged interfaces: {e}");
+                    }
+                }
+                UdevEvent::Device(event) => {
+                    emit_device_event(&self.udev_object, &event).await?;
+                }
             }
         }
     }
 
     async fn shutdown(&mut self) -> Result<()> {
         let _ = self.shutdown_sender.send(()).await;
+        if let Some(task) = self.device_monitor.take() {
+            task.abort();
+        }
         Ok(())
     }
 }
 
 impl UdevMonitor {
-    pub async fn init(connection: &Connection) -> Result<UdevMonitor> {
+    /// Power supply events are passed on to `power_supply`, e.g. for the power source monitor
+    pub async fn init(
+        connection: &Connection,
+        system: &Connection,
+        power_supply: UnboundedSender<()>,
+    ) -> Result<UdevMonitor> {
         let object_server = connection.object_server();
-        ensure!(
-            object_server.at(PATH, UdevDbusObject {}).await?,
-            "Could not register UdevEvents1"
-        );
-        let udev_object: InterfaceRef<UdevDbusObject> = object_server.interface(PATH).await?;
+        // A restarted monitor keeps the subscriptions of the one it replaces
+        object_server
+            .at(PATH, Guarded::new(UdevDbusObject::default()))
+            .await?;
+        let udev_object: InterfaceRef<Guarded<UdevDbusObject>> =
+            object_server.interface(PATH).await?;
         let (shutdown_sender, shutdown_receiver) = channel(1);
         Ok(UdevMonitor {
             udev_object,
+            connection: connection.clone(),
+            proxy: root_manager_proxy(system).await?,
+            power_supply,
             shutdown_sender,
             shutdown_receiver: Some(shutdown_receiver),
+            device_monitor: None,
         })
     }
+
+    /// Replace the device monitor with one listening for what the subscribers want, or stop it
+    /// once they're all gone
+    fn restart_device_monitor(
+        &mut self,
+        filter: Option<DeviceFilter>,
+        tx: &UnboundedSender<UdevEvent>,
+    ) {
+        if let Some(task) = self.device_monitor.take() {
+            task.abort();
+        }
+        let Some(filter) = filter else {
+            return;
+        };
+        let tx = tx.clone();
+        self.device_monitor = Some(spawn(async move {
+            if let Err(e) = run_device_monitor(&filter, tx).await {
+                error!("Device event monitor failed: {e}");
+            }
+        }));
+    }
 }
 
 #[interface(name = "com.steampowered.SteamOSManager1.UdevEvents1")]
 impl UdevDbusObject {
+    async fn subscribe(
+        &mut self,
+        subsystem: &str,
+        tag: &str,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<u32> {
+        let sender = header
+            .sender()
+            .ok_or_else(|| fdo::Error::InvalidArgs(String::from("Unknown sender")))?;
+        self.next_token = self.next_token.wrapping_add(1);
+        let token = self.next_token;
+        self.subscriptions.insert(
+            token,
+            DeviceSubscription {
+                sender: sender.to_string(),
+                subsystem: subsystem.to_string(),
+                tag: tag.to_string(),
+            },
+        );
+        self.update_filter();
+        Ok(token)
+    }
+
+    async fn unsubscribe(
+        &mut self,
+        token: u32,
+        #[zbus(header)] header: Header<'_>,
+    ) -> fdo::Result<()> {
+        let sender = header.sender().map(|sender| sender.as_str());
+        match self.subscriptions.get(&token) {
+            Some(subscription) if Some(subscription.sender.as_str()) == sender => {
+                self.subscriptions.remove(&token);
+                self.update_filter();
+                Ok(())
+            }
+            _ => Err(fdo::Error::InvalidArgs(format!(
+                "No subscription with token {token}"
+            ))),
+        }
+    }
+
+    #[zbus(signal)]
+    async fn device_event(
+        signal_ctxt: &SignalEmitter<'_>,
+        token: u32,
+        action: &str,
+        subsystem: &str,
+        devpath: &str,
+    ) -> zbus::Result<()>;
+
     #[zbus(signal)]
     async fn usb_over_current(
         signal_ctxt: &SignalEmitter<'_>,
@@ -121,6 +398,18 @@ async fn run_udev(tx: UnboundedSender<UdevEvent>, mut shutdown_rx: Receiver<()>)
         .listen()?;
     let fd = AsyncFd::new(usb_monitor.as_fd())?;
     let mut iter = usb_monitor.iter();
+    let power_supply_monitor = MonitorBuilder::new()?
+        .match_subsystem("power_supply")?
+        .listen()?;
+    let power_supply_fd = AsyncFd::new(power_supply_monitor.as_fd())?;
+    let mut power_supply_iter = power_supply_monitor.iter();
+    let hotplug_monitor = MonitorBuilder::new()?
+        .match_subsystem("hwmon")?
+        .match_subsystem("net")?
+        .match_subsystem("cec")?
+        .listen()?;
+    let hotplug_fd = AsyncFd::new(hotplug_monitor.as_fd())?;
+    let mut hotplug_iter = hotplug_monitor.iter();
     loop {
         select! {
             guard = fd.ready(Interest::READABLE) => {
@@ -130,8 +419,51 @@ async fn run_udev(tx: UnboundedSender<UdevEvent>, mut shutdown_rx: Receiver<()>)
                 };
                 guard.clear_ready();
             },
+            guard = power_supply_fd.ready(Interest::READABLE) => {
+                let mut guard = guard?;
+                for ev in power_supply_iter.by_ref() {
+                    process_power_supply_event(&ev, &tx)?;
+                };
+                guard.clear_ready();
+            },
+            guard = hotplug_fd.ready(Interest::READABLE) => {
+                let mut guard = guard?;
+                for ev in hotplug_iter.by_ref() {
+                    process_hotplug_event(&ev, &tx)?;
+                };
+                guard.clear_ready();
+            },
             _ = shutdown_rx.recv() => break Ok(()),
             _ = fd.ready(Interest::ERROR) => bail!("Event poller encountered unknown flags"),
+            _ = power_supply_fd.ready(Interest::ERROR) => bail!("Event poller encountered unknown flags"),
+            _ = hotplug_fd.ready(Interest::ERROR) => bail!("Event poller encountered unknown flags"),
+        }
+    }
+}
+
+/// Pass on the device events subscribers asked for. Each subscriber's own subsystem and tag are
+/// checked again when the event is sent, as the filter covers all of them at once.
+async fn run_device_monitor(filter: &DeviceFilter, tx: UnboundedSender<UdevEvent>) -> Result<()> {
+    let mut builder = MonitorBuilder::new()?;
+    for subsystem in &filter.subsystems {
+        builder = builder.match_subsystem(subsystem)?;
+    }
+    for tag in &filter.tags {
+        builder = builder.match_tag(tag)?;
+    }
+    let monitor = builder.listen()?;
+    let fd = AsyncFd::new(monitor.as_fd())?;
+    let mut iter = monitor.iter();
+    loop {
+        select! {
+            guard = fd.ready(Interest::READABLE) => {
+                let mut guard = guard?;
+                for ev in iter.by_ref() {
+                    process_device_event(&ev, &tx)?;
+                };
+                guard.clear_ready();
+            },
+            _ = fd.ready(Interest::ERROR) => bail!("Event poller encountered unknown flags"),
         }
     }
 }
@@ -191,6 +523,67 @@ fn process_usb_event(ev: &Event, tx: &UnboundedSender<UdevEvent>) -> Result<()>
     Ok(())
 }
 
+fn process_power_supply_event(ev: &Event, tx: &UnboundedSender<UdevEvent>) -> Result<()> {
+    debug!("Got power supply event {ev:?}");
+    tx.send(UdevEvent::PowerSupplyChanged)?;
+    if matches!(ev.event_type(), EventType::Add | EventType::Remove) {
+        tx.send(UdevEvent::HardwareChanged)?;
+        return Ok(());
+    }
+    if ev.event_type() != EventType::Change {
+        return Ok(());
+    }
+    match ev.property_value("POWER_SUPPLY_TYPE") {
+        Some(supply_type) if supply_type == "Battery" => (),
+        _ => return Ok(()),
+    }
+    // Peripheral batteries, e.g. controllers, aren't exposed over Battery1
+    if ev
+        .property_value("POWER_SUPPLY_SCOPE")
+        .is_some_and(|scope| scope == "Device")
+    {
+        return Ok(());
+    }
+    tx.send(UdevEvent::BatteryChanged)?;
+    Ok(())
+}
+
+fn process_hotplug_event(ev: &Event, tx: &UnboundedSender<UdevEvent>) -> Result<()> {
+    debug!("Got hotplug event {ev:?}");
+    if !matches!(ev.event_type(), EventType::Add | EventType::Remove) {
+        return Ok(());
+    }
+    if ev.subsystem().is_some_and(|subsystem| subsystem == "cec") {
+        tx.send(UdevEvent::CecChanged)?;
+    } else {
+        tx.send(UdevEvent::HardwareChanged)?;
+    }
+    Ok(())
+}
+
+fn process_device_event(ev: &Event, tx: &UnboundedSender<UdevEvent>) -> Result<()> {
+    let tags = ev
+        .property_value("TAGS")
+        .map(|tags| {
+            tags.to_string_lossy()
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    tx.send(UdevEvent::Device(DeviceEvent {
+        action: ev.event_type().to_string(),
+        subsystem: ev
+            .subsystem()
+            .map(|subsystem| subsystem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        devpath: ev.devpath().to_string_lossy().to_string(),
+        tags,
+    }))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -206,12 +599,17 @@ mod test {
         let connection = handle.new_dbus().await.expect("new_dbus");
         sleep(Duration::from_millis(1)).await;
         let object_server = connection.object_server();
-        object_server.at(PATH, UdevDbusObject {}).await.expect("at");
+        object_server
+            .at(PATH, Guarded::new(UdevDbusObject::default()))
+            .await
+            .expect("at");
 
-        let remote =
-            testing::InterfaceIntrospection::from_remote::<UdevDbusObject, _>(&connection, PATH)
-                .await
-                .expect("remove");
+        let remote = testing::InterfaceIntrospection::from_remote::<Guarded<UdevDbusObject>, _>(
+            &connection,
+            PATH,
+        )
+        .await
+        .expect("remove");
         let local = testing::InterfaceIntrospection::from_local(
             "../data/interfaces/com.steampowered.SteamOSManager1.xml",
             UdevDbusObject::name().to_string(),
@@ -220,4 +618,112 @@ mod test {
         .expect("local");
         assert!(remote.compare(&local));
     }
+
+    #[zbus::proxy(
+        interface = "com.steampowered.SteamOSManager1.UdevEvents1",
+        default_path = "/com/steampowered/SteamOSManager1"
+    )]
+    trait UdevEvents {
+        fn subscribe(&self, subsystem: &str, tag: &str) -> zbus::Result<u32>;
+        fn unsubscribe(&self, token: u32) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        fn device_event(
+            &self,
+            token: u32,
+            action: &str,
+            subsystem: &str,
+            devpath: &str,
+        ) -> zbus::Result<()>;
+    }
+
+    #[tokio::test]
+    async fn device_subscriptions() {
+        let mut handle = testing::start();
+        let connection = handle.new_dbus().await.expect("new_dbus");
+        let address = handle.dbus_address().await.unwrap();
+        let object_server = connection.object_server();
+        object_server
+            .at(PATH, Guarded::new(UdevDbusObject::default()))
+            .await
+            .expect("at");
+        let object: InterfaceRef<Guarded<UdevDbusObject>> =
+            object_server.interface(PATH).await.expect("interface");
+        let mut filter = object.get().await.filter.subscribe();
+        assert_eq!(*filter.borrow_and_update(), None);
+        let name = connection.unique_name().unwrap().clone();
+
+        let client = zbus::connection::Builder::address(address)
+            .expect("address")
+            .build()
+            .await
+            .expect("build");
+        let proxy = UdevEventsProxy::new(&client, name.clone())
+            .await
+            .expect("proxy");
+        let mut events = proxy.receive_device_event().await.expect("receive");
+
+        let input = proxy.subscribe("input", "").await.expect("subscribe");
+        assert_eq!(
+            *filter.borrow_and_update(),
+            Some(DeviceFilter {
+                subsystems: BTreeSet::from([String::from("input")]),
+                tags: BTreeSet::new(),
+            })
+        );
+        let uaccess = proxy.subscribe("", "uaccess").await.expect("subscribe");
+        assert_ne!(input, uaccess);
+        // Neither the subsystem nor the tag can be filtered on for both subscriptions
+        assert_eq!(*filter.borrow_and_update(), Some(DeviceFilter::default()));
+
+        let event = DeviceEvent {
+            action: String::from("add"),
+            subsystem: String::from("input"),
+            devpath: String::from("/devices/virtual/input/input1"),
+            tags: vec![String::from("seat"), String::from("uaccess")],
+        };
+        emit_device_event(&object, &event).await.expect("emit");
+        let mut tokens = Vec::new();
+        for _ in 0..2 {
+            let signal = events.next().await.expect("next");
+            let args = signal.args().expect("args");
+            assert_eq!(args.action(), &"add");
+            assert_eq!(args.devpath(), &"/devices/virtual/input/input1");
+            tokens.push(*args.token());
+        }
+        tokens.sort_unstable();
+        assert_eq!(tokens, vec![input, uaccess]);
+
+        let usb = DeviceEvent {
+            action: String::from("remove"),
+            subsystem: String::from("usb"),
+            devpath: String::from("/devices/pci0000:00/usb1/1-1"),
+            tags: Vec::new(),
+        };
+        assert!(object.get().await.matching_subscribers(&usb).is_empty());
+
+        // Only the client that subscribed can unsubscribe
+        let other = UdevEventsProxy::new(&connection, name.clone())
+            .await
+            .expect("proxy");
+        assert!(other.unsubscribe(uaccess).await.is_err());
+        assert!(proxy.unsubscribe(uaccess + input).await.is_err());
+        proxy.unsubscribe(uaccess).await.expect("unsubscribe");
+        assert_eq!(
+            object.get().await.matching_subscribers(&event),
+            vec![(input, client.unique_name().unwrap().to_string())]
+        );
+        assert_eq!(
+            filter.borrow_and_update().as_ref().map(|f| &f.subsystems),
+            Some(&BTreeSet::from([String::from("input")]))
+        );
+
+        object
+            .get_mut()
+            .await
+            .remove_subscriber(client.unique_name().unwrap().as_str());
+        assert!(object.get().await.subscriptions.is_empty());
+        // Nobody is left to listen for device events
+        assert_eq!(*filter.borrow_and_update(), None);
+    }
 }
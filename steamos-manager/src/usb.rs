@@ -0,0 +1,269 @@
+/*
+ * Copyright © 2024 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Result};
+use tokio::fs::{read_dir, read_to_string};
+use tracing::error;
+
+use crate::path;
+use crate::platform::UsbAuthorizationConfig;
+use crate::power::write_sysfs;
+
+const USB_DEVICES_PREFIX: &str = "/sys/bus/usb/devices";
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct UsbDevice {
+    /// The sysfs name of the device, e.g. 1-1.2
+    pub name: String,
+    /// The `vendor:product` ID of the device
+    pub id: String,
+    pub product: String,
+    pub authorized: bool,
+}
+
+async fn read_usb_attribute(device: &str, attribute: &str) -> Result<String> {
+    let contents = read_to_string(path(USB_DEVICES_PREFIX).join(device).join(attribute))
+        .await
+        .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+    Ok(contents.trim().to_string())
+}
+
+async fn write_usb_attribute(device: &str, attribute: &str, value: &str) -> Result<()> {
+    write_sysfs(
+        path(USB_DEVICES_PREFIX).join(device).join(attribute),
+        value.as_bytes(),
+    )
+    .await
+    .inspect_err(|message| error!("Error writing to sysfs file: {message}"))
+}
+
+/// Root hubs, one per host controller, are named usbN
+fn is_root_hub(name: &str) -> bool {
+    name.strip_prefix("usb")
+        .is_some_and(|bus| !bus.is_empty() && bus.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Devices are named after their bus and port path, e.g. 1-1.2, while their
+/// interfaces have a configuration and interface number appended, e.g. 1-1.2:1.0
+fn is_device(name: &str) -> bool {
+    name.split_once('-')
+        .is_some_and(|(bus, _)| !bus.is_empty() && bus.chars().all(|c| c.is_ascii_digit()))
+        && !name.contains(':')
+}
+
+async fn list_usb_entries(filter: fn(&str) -> bool) -> Result<Vec<String>> {
+    let mut entries = Vec::new();
+    let mut dir = read_dir(path(USB_DEVICES_PREFIX)).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if filter(name.as_str()) {
+            entries.push(name);
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+pub(crate) async fn list_host_controllers() -> Result<Vec<String>> {
+    list_usb_entries(is_root_hub).await
+}
+
+pub(crate) async fn list_usb_devices() -> Result<Vec<UsbDevice>> {
+    let mut devices = Vec::new();
+    for name in list_usb_entries(is_device).await? {
+        let (Ok(vendor), Ok(product_id)) = (
+            read_usb_attribute(name.as_str(), "idVendor").await,
+            read_usb_attribute(name.as_str(), "idProduct").await,
+        ) else {
+            continue;
+        };
+        let product = read_usb_attribute(name.as_str(), "product")
+            .await
+            .unwrap_or_default();
+        // The device may have been unplugged since the directory was listed
+        let Ok(authorized) = read_usb_attribute(name.as_str(), "authorized").await else {
+            continue;
+        };
+        let authorized = authorized != "0";
+        devices.push(UsbDevice {
+            name,
+            id: format!("{vendor}:{product_id}"),
+            product,
+            authorized,
+        });
+    }
+    Ok(devices)
+}
+
+/// The USB generation a device is connected at, based on its negotiated speed
+pub(crate) async fn get_usb_tier(device: &str) -> Result<String> {
+    let speed = read_usb_attribute(device, "speed").await?;
+    let tier = match speed.as_str() {
+        "1.5" | "12" => "USB 1.1",
+        "480" => "USB 2.0",
+        "5000" => "USB 3.2 Gen 1",
+        "10000" => "USB 3.2 Gen 2",
+        "20000" => "USB 3.2 Gen 2x2",
+        _ => return Ok(format!("{speed} Mbit/s")),
+    };
+    Ok(tier.to_string())
+}
+
+/// Whether newly connected devices are authorized automatically on every host controller
+pub(crate) async fn get_usb_authorized_default() -> Result<bool> {
+    let controllers = list_host_controllers().await?;
+    ensure!(!controllers.is_empty(), "No USB host controllers found");
+    for controller in controllers {
+        if read_usb_attribute(controller.as_str(), "authorized_default").await? != "1" {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// When not authorizing by default, internal devices such as the built-in controller are still
+/// authorized, as nothing could authorize them again without input
+pub(crate) async fn set_usb_authorized_default(authorized: bool) -> Result<()> {
+    let controllers = list_host_controllers().await?;
+    ensure!(!controllers.is_empty(), "No USB host controllers found");
+    let value = if authorized { "1" } else { "2" };
+    for controller in controllers {
+        write_usb_attribute(controller.as_str(), "authorized_default", value).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn set_usb_device_authorized(
+    device: &str,
+    authorized: bool,
+    config: &UsbAuthorizationConfig,
+) -> Result<()> {
+    let Some(usb_device) = list_usb_devices()
+        .await?
+        .into_iter()
+        .find(|usb_device| usb_device.name == device)
+    else {
+        bail!("Invalid USB device {device}");
+    };
+    ensure!(
+        authorized || !config.is_protected(usb_device.id.as_str()),
+        "USB device {device} ({}) may not be deauthorized",
+        usb_device.id
+    );
+    write_usb_attribute(device, "authorized", if authorized { "1" } else { "0" }).await
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::testing;
+    use tokio::fs::{create_dir_all, write};
+
+    async fn create_device(name: &str, id: &str, product: Option<&str>) -> Result<()> {
+        let base = path(USB_DEVICES_PREFIX).join(name);
+        create_dir_all(&base).await?;
+        let (vendor, product_id) = id.split_once(':').unwrap();
+        write(base.join("idVendor"), format!("{vendor}\n")).await?;
+        write(base.join("idProduct"), format!("{product_id}\n")).await?;
+        if let Some(product) = product {
+            write(base.join("product"), format!("{product}\n")).await?;
+        }
+        write(base.join("authorized"), "1\n").await?;
+        Ok(())
+    }
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        for controller in ["usb1", "usb2"] {
+            create_device(controller, "1d6b:0002", Some("xHCI Host Controller")).await?;
+            write(
+                path(USB_DEVICES_PREFIX)
+                    .join(controller)
+                    .join("authorized_default"),
+                "1\n",
+            )
+            .await?;
+        }
+        create_device("3-3", "28de:1205", Some("Steam Deck Controller")).await?;
+        create_device("1-1", "046d:c52b", None).await?;
+        // An interface of the above device, which isn't a device itself
+        create_dir_all(path(USB_DEVICES_PREFIX).join("1-1:1.0")).await?;
+        Ok(())
+    }
+
+    #[test]
+    fn names() {
+        assert!(is_root_hub("usb1"));
+        assert!(is_root_hub("usb12"));
+        assert!(!is_root_hub("usb"));
+        assert!(!is_root_hub("1-1"));
+        assert!(is_device("1-1"));
+        assert!(is_device("3-1.2.4"));
+        assert!(!is_device("1-1:1.0"));
+        assert!(!is_device("usb1"));
+    }
+
+    #[tokio::test]
+    async fn authorization() {
+        let _h = testing::start();
+
+        create_nodes().await.expect("create_nodes");
+        assert_eq!(list_host_controllers().await.unwrap(), ["usb1", "usb2"]);
+        assert_eq!(
+            list_usb_devices().await.unwrap(),
+            [
+                UsbDevice {
+                    name: String::from("1-1"),
+                    id: String::from("046d:c52b"),
+                    product: String::new(),
+                    authorized: true,
+                },
+                UsbDevice {
+                    name: String::from("3-3"),
+                    id: String::from("28de:1205"),
+                    product: String::from("Steam Deck Controller"),
+                    authorized: true,
+                },
+            ]
+        );
+
+        assert!(get_usb_authorized_default().await.unwrap());
+        set_usb_authorized_default(false)
+            .await
+            .expect("set_usb_authorized_default");
+        assert!(!get_usb_authorized_default().await.unwrap());
+        assert_eq!(
+            read_usb_attribute("usb2", "authorized_default")
+                .await
+                .unwrap(),
+            "2"
+        );
+
+        let config = UsbAuthorizationConfig {
+            protected_devices: vec![String::from("28DE:1205")],
+        };
+        set_usb_device_authorized("1-1", false, &config)
+            .await
+            .expect("set_usb_device_authorized");
+        assert!(!list_usb_devices().await.unwrap()[0].authorized);
+        set_usb_device_authorized("1-1", true, &config)
+            .await
+            .expect("set_usb_device_authorized");
+        assert!(list_usb_devices().await.unwrap()[0].authorized);
+
+        assert!(set_usb_device_authorized("3-3", false, &config)
+            .await
+            .is_err());
+        assert!(list_usb_devices().await.unwrap()[1].authorized);
+        assert!(set_usb_device_authorized("usb1", false, &config)
+            .await
+            .is_err());
+        assert!(set_usb_device_authorized("1-1:1.0", false, &config)
+            .await
+            .is_err());
+    }
+}
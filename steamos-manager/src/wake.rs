@@ -0,0 +1,225 @@
+/*
+ * Copyright © 2025 Valve Software
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use anyhow::{anyhow, bail, ensure, Result};
+use std::path::PathBuf;
+use strum::{Display, EnumString};
+use tokio::fs::{read_to_string, try_exists};
+use tracing::error;
+
+use crate::hardware::{device_config, WakeSourcesConfig};
+use crate::path;
+use crate::power::write_sysfs;
+
+const WAKEUP_ENABLED: &str = "enabled";
+const WAKEUP_DISABLED: &str = "disabled";
+
+#[derive(Display, EnumString, PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum WakeSource {
+    /// Wake-on-LAN, e.g. through the dock's ethernet port
+    Lan,
+    /// Input from connected USB devices
+    Usb,
+    /// Opening the lid
+    Lid,
+    /// Pressing the power button
+    PowerButton,
+}
+
+impl WakeSource {
+    pub(crate) const ALL: [WakeSource; 4] = [
+        WakeSource::Lan,
+        WakeSource::Usb,
+        WakeSource::Lid,
+        WakeSource::PowerButton,
+    ];
+
+    fn devices(self, config: &WakeSourcesConfig) -> &[String] {
+        match self {
+            WakeSource::Lan => &config.lan,
+            WakeSource::Usb => &config.usb,
+            WakeSource::Lid => &config.lid,
+            WakeSource::PowerButton => &config.power_button,
+        }
+    }
+}
+
+async fn wake_sources_config() -> Result<WakeSourcesConfig> {
+    let config = device_config().await?;
+    config
+        .as_ref()
+        .and_then(|config| config.wake_sources.clone())
+        .ok_or(anyhow!("No wake sources configured"))
+}
+
+/// The `power/wakeup` attributes of the devices of a wake source that are currently present
+async fn wakeup_paths(config: &WakeSourcesConfig, source: WakeSource) -> Result<Vec<PathBuf>> {
+    let devices = source.devices(config);
+    ensure!(!devices.is_empty(), "Wake source {source} is not supported");
+    let mut paths = Vec::new();
+    for device in devices {
+        let wakeup = path(device).join("power/wakeup");
+        if try_exists(&wakeup).await? {
+            paths.push(wakeup);
+        }
+    }
+    ensure!(
+        !paths.is_empty(),
+        "No device for wake source {source} is present"
+    );
+    Ok(paths)
+}
+
+pub(crate) async fn available_wake_sources() -> Result<Vec<WakeSource>> {
+    let config = wake_sources_config().await?;
+    Ok(WakeSource::ALL
+        .into_iter()
+        .filter(|source| !source.devices(&config).is_empty())
+        .collect())
+}
+
+pub(crate) async fn get_wake_source_enabled(source: WakeSource) -> Result<bool> {
+    let config = wake_sources_config().await?;
+    for wakeup in wakeup_paths(&config, source).await? {
+        let contents = read_to_string(&wakeup)
+            .await
+            .map_err(|message| anyhow!("Error reading sysfs: {message}"))?;
+        match contents.trim() {
+            WAKEUP_ENABLED => return Ok(true),
+            WAKEUP_DISABLED => (),
+            value => bail!("Unknown wakeup state {value}"),
+        }
+    }
+    Ok(false)
+}
+
+pub(crate) async fn set_wake_source_enabled(source: WakeSource, enabled: bool) -> Result<()> {
+    let config = wake_sources_config().await?;
+    let value = if enabled {
+        WAKEUP_ENABLED
+    } else {
+        WAKEUP_DISABLED
+    };
+    for wakeup in wakeup_paths(&config, source).await? {
+        write_sysfs(wakeup, value.as_bytes())
+            .await
+            .inspect_err(|message| error!("Error writing to sysfs file: {message}"))?;
+    }
+    Ok(())
+}
+
+/// The available wake sources that are currently able to wake the device up
+pub(crate) async fn enabled_wake_sources() -> Result<Vec<WakeSource>> {
+    let mut enabled = Vec::new();
+    for source in available_wake_sources().await? {
+        // Sources whose devices aren't connected right now can't wake anything up
+        if get_wake_source_enabled(source).await.unwrap_or(false) {
+            enabled.push(source);
+        }
+    }
+    Ok(enabled)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::hardware::DeviceConfig;
+    use crate::{enum_roundtrip, testing};
+    use std::str::FromStr;
+    use tokio::fs::{create_dir_all, remove_dir_all, write};
+
+    pub(crate) const DOCK_ETHERNET: &str = "/sys/bus/usb/devices/3-1.4";
+    pub(crate) const USB_CONTROLLER: &str = "/sys/bus/pci/devices/0000:04:00.3";
+    pub(crate) const POWER_BUTTON: &str = "/sys/devices/LNXSYSTM:00/LNXPWRBN:00";
+
+    pub(crate) fn test_config() -> WakeSourcesConfig {
+        WakeSourcesConfig {
+            lan: vec![String::from(DOCK_ETHERNET)],
+            usb: vec![String::from(USB_CONTROLLER)],
+            lid: Vec::new(),
+            power_button: vec![String::from(POWER_BUTTON)],
+        }
+    }
+
+    pub(crate) async fn create_nodes() -> Result<()> {
+        for (device, state) in [
+            (DOCK_ETHERNET, WAKEUP_DISABLED),
+            (USB_CONTROLLER, WAKEUP_ENABLED),
+            (POWER_BUTTON, WAKEUP_ENABLED),
+        ] {
+            let power = path(device).join("power");
+            create_dir_all(&power).await?;
+            write(power.join("wakeup"), format!("{state}\n")).await?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn wake_source_roundtrip() {
+        enum_roundtrip!(WakeSource {
+            "lan": str = Lan,
+            "usb": str = Usb,
+            "lid": str = Lid,
+            "power_button": str = PowerButton,
+        });
+        assert!(WakeSource::from_str("rtc").is_err());
+    }
+
+    #[tokio::test]
+    async fn wake_sources() {
+        let h = testing::start();
+
+        assert!(available_wake_sources().await.is_err());
+        assert!(get_wake_source_enabled(WakeSource::Lan).await.is_err());
+
+        create_nodes().await.expect("create_nodes");
+        h.test.device_config.replace(Some(DeviceConfig {
+            wake_sources: Some(test_config()),
+            ..DeviceConfig::default()
+        }));
+        assert_eq!(
+            available_wake_sources().await.unwrap(),
+            vec![WakeSource::Lan, WakeSource::Usb, WakeSource::PowerButton]
+        );
+        assert_eq!(
+            enabled_wake_sources().await.unwrap(),
+            vec![WakeSource::Usb, WakeSource::PowerButton]
+        );
+        assert!(get_wake_source_enabled(WakeSource::Lid).await.is_err());
+        assert!(set_wake_source_enabled(WakeSource::Lid, true)
+            .await
+            .is_err());
+
+        set_wake_source_enabled(WakeSource::Lan, true)
+            .await
+            .expect("set_wake_source_enabled");
+        assert_eq!(
+            read_to_string(path(DOCK_ETHERNET).join("power/wakeup"))
+                .await
+                .unwrap(),
+            WAKEUP_ENABLED
+        );
+        set_wake_source_enabled(WakeSource::Usb, false)
+            .await
+            .expect("set_wake_source_enabled");
+        assert_eq!(
+            enabled_wake_sources().await.unwrap(),
+            vec![WakeSource::Lan, WakeSource::PowerButton]
+        );
+
+        // Unplugging the dock takes its ethernet adapter away
+        remove_dir_all(path(DOCK_ETHERNET)).await.unwrap();
+        assert!(get_wake_source_enabled(WakeSource::Lan).await.is_err());
+        assert!(set_wake_source_enabled(WakeSource::Lan, true)
+            .await
+            .is_err());
+        assert_eq!(
+            enabled_wake_sources().await.unwrap(),
+            vec![WakeSource::PowerButton]
+        );
+    }
+}
@@ -10,9 +10,12 @@ use config::builder::AsyncState;
 use config::{ConfigBuilder, FileFormat};
 use nix::sys::stat::{self, Mode};
 use num_enum::TryFromPrimitive;
+use serde::Deserialize;
 use std::ffi::OsStr;
 use std::fs::Permissions;
+use std::future::Future;
 use std::io::ErrorKind;
+use std::marker::PhantomData;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -21,14 +24,20 @@ use strum::{Display, EnumString};
 use tempfile::Builder as TempFileBuilder;
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tracing::error;
+use tokio::time::timeout;
+use tokio_stream::StreamExt;
+use tracing::{debug, error};
 use udev::{Event, EventType};
-use zbus::Connection;
+use zbus::fdo::PropertiesChanged;
+use zbus::message::Type as MessageType;
+use zbus::object_server::{Interface, SignalEmitter};
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{Connection, MatchRule, Message, MessageStream, Proxy};
 
 use crate::process::{run_script, script_output};
 use crate::systemd::{daemon_reload, SystemdUnit};
 use crate::udev::single_poll;
-use crate::{path, read_config_directory};
+use crate::{path, read_config_directory, Service};
 
 const OVERRIDE_CONTENTS: &str = "[Service]
 ExecStart=
@@ -90,6 +99,157 @@ pub enum WifiBackend {
     WPASupplicant = 1,
 }
 
+/// Statistics for the active Wi-Fi link. Empty strings and zeroes mean there is no
+/// active link.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub(crate) struct WifiLink {
+    pub ssid: String,
+    pub bssid: String,
+    /// Frequency in MHz
+    pub frequency: u32,
+    /// Signal strength in dBm
+    pub signal: i32,
+    /// Transmit bitrate in Mbit/s
+    pub tx_bitrate: f64,
+    /// Receive bitrate in Mbit/s
+    pub rx_bitrate: f64,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub(crate) struct WifiInfoMonitorConfig {
+    /// How long to wait for further changes from NetworkManager before refreshing the link
+    /// information, in milliseconds
+    pub refresh_delay: u64,
+}
+
+impl Default for WifiInfoMonitorConfig {
+    fn default() -> WifiInfoMonitorConfig {
+        WifiInfoMonitorConfig {
+            refresh_delay: 1000,
+        }
+    }
+}
+
+/// Receives the link information gathered by [`WifiInfoMonitor`]
+pub(crate) trait WifiLinkReceiver: Interface {
+    fn update_link(
+        &mut self,
+        link: WifiLink,
+        ctx: &SignalEmitter<'_>,
+    ) -> impl Future<Output = zbus::Result<()>> + Send;
+}
+
+/// Refreshes the link information whenever NetworkManager reports a change to a Wi-Fi device or
+/// access point, instead of polling for it
+pub(crate) struct WifiInfoMonitor<I: WifiLinkReceiver> {
+    session: Connection,
+    system: Connection,
+    /// Object path of the receiving interface, which is looked up on every refresh since it
+    /// comes and goes along with the Wi-Fi hardware
+    path: OwnedObjectPath,
+    proxy: Proxy<'static>,
+    delay: Duration,
+    receiver: PhantomData<I>,
+}
+
+impl<I: WifiLinkReceiver> WifiInfoMonitor<I> {
+    pub(crate) fn init(
+        session: &Connection,
+        system: &Connection,
+        path: OwnedObjectPath,
+        proxy: Proxy<'static>,
+        config: &WifiInfoMonitorConfig,
+    ) -> WifiInfoMonitor<I> {
+        WifiInfoMonitor {
+            session: session.clone(),
+            system: system.clone(),
+            path,
+            proxy,
+            delay: Duration::from_millis(config.refresh_delay),
+            receiver: PhantomData,
+        }
+    }
+
+    async fn refresh(&self) -> Result<()> {
+        let Ok(info) = self
+            .session
+            .object_server()
+            .interface::<_, I>(&self.path)
+            .await
+        else {
+            return Ok(());
+        };
+        let (ssid, bssid, frequency, signal, tx_bitrate, rx_bitrate): (
+            String,
+            String,
+            u32,
+            i32,
+            f64,
+            f64,
+        ) = self.proxy.call("GetWifiLink", &()).await?;
+        let link = WifiLink {
+            ssid,
+            bssid,
+            frequency,
+            signal,
+            tx_bitrate,
+            rx_bitrate,
+        };
+        info.get_mut()
+            .await
+            .update_link(link, info.signal_emitter())
+            .await?;
+        Ok(())
+    }
+}
+
+fn is_wifi_change(message: &Message) -> bool {
+    let Some(signal) = PropertiesChanged::from_message(message.clone()) else {
+        return false;
+    };
+    signal.args().is_ok_and(|args| {
+        matches!(
+            args.interface_name().as_str(),
+            "org.freedesktop.NetworkManager.Device"
+                | "org.freedesktop.NetworkManager.Device.Wireless"
+                | "org.freedesktop.NetworkManager.AccessPoint"
+        )
+    })
+}
+
+impl<I: WifiLinkReceiver> Service for WifiInfoMonitor<I> {
+    const NAME: &'static str = "wifi-info-monitor";
+
+    async fn run(&mut self) -> Result<()> {
+        let rule = MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .sender("org.freedesktop.NetworkManager")?
+            .interface("org.freedesktop.DBus.Properties")?
+            .member("PropertiesChanged")?
+            .build();
+        let mut changes = MessageStream::for_match_rule(rule, &self.system, None).await?;
+
+        loop {
+            if let Err(e) = self.refresh().await {
+                debug!("Failed to update Wi-Fi link information: {e}");
+            }
+            // Wait for a change, then let a burst of them settle before refreshing
+            loop {
+                match changes.next().await {
+                    Some(Ok(message)) if is_wifi_change(&message) => break,
+                    Some(_) => (),
+                    None => bail!("NetworkManager signal stream broke"),
+                }
+            }
+            let _ = timeout(self.delay, async {
+                while changes.next().await.is_some() {}
+            })
+            .await;
+        }
+    }
+}
+
 pub(crate) async fn setup_iwd_config(want_override: bool) -> std::io::Result<()> {
     // Copy override.conf file into place or out of place depending
     // on install value
@@ -242,6 +402,63 @@ pub(crate) async fn list_wifi_interfaces() -> Result<Vec<String>> {
         .collect())
 }
 
+fn parse_bitrate(value: &str) -> Option<f64> {
+    let (rate, unit) = value.split_once(' ')?;
+    if !unit.starts_with("MBit/s") {
+        return None;
+    }
+    rate.parse().ok()
+}
+
+fn parse_wifi_link(output: &str) -> Result<Option<WifiLink>> {
+    let mut lines = output.lines();
+    let Some(first) = lines.next() else {
+        bail!("Empty link information");
+    };
+    let Some(bssid) = first.trim().strip_prefix("Connected to ") else {
+        if first.trim() == "Not connected." {
+            return Ok(None);
+        }
+        bail!("Unknown link information: {first}");
+    };
+    let mut link = WifiLink {
+        bssid: bssid
+            .split_once(' ')
+            .map_or(bssid, |(bssid, _)| bssid)
+            .to_string(),
+        ..WifiLink::default()
+    };
+    for line in lines {
+        let Some((key, value)) = line.trim().split_once(": ") else {
+            continue;
+        };
+        match key {
+            "SSID" => link.ssid = value.to_string(),
+            "freq" => link.frequency = value.parse::<f64>()?.round() as u32,
+            "signal" => {
+                link.signal = value
+                    .split_once(' ')
+                    .map_or(value, |(signal, _)| signal)
+                    .parse()?
+            }
+            "rx bitrate" => link.rx_bitrate = parse_bitrate(value).unwrap_or_default(),
+            "tx bitrate" => link.tx_bitrate = parse_bitrate(value).unwrap_or_default(),
+            _ => (),
+        }
+    }
+    Ok(Some(link))
+}
+
+pub(crate) async fn get_wifi_link() -> Result<WifiLink> {
+    for iface in list_wifi_interfaces().await? {
+        let output = script_output("/usr/bin/iw", &["dev", iface.as_str(), "link"]).await?;
+        if let Some(link) = parse_wifi_link(output.as_str())? {
+            return Ok(link);
+        }
+    }
+    Ok(WifiLink::default())
+}
+
 pub(crate) async fn get_wifi_power_management_state() -> Result<WifiPowerManagement> {
     let mut found_any = false;
     for iface in list_wifi_interfaces().await? {
@@ -500,6 +717,55 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_wifi_link() {
+        let h = testing::start();
+
+        fn process_output(executable: &OsStr, args: &[&OsStr]) -> Result<(i32, String)> {
+            ensure!(executable.to_string_lossy() == "/usr/bin/iw", "Not iw");
+            ensure!(args[0] == "dev", "Not dev");
+            if args.len() < 2 {
+                return Ok((0, String::from("Interface eth0\nInterface eth1")));
+            }
+            ensure!(args[2] == "link", "Not link");
+            match args[1].to_str() {
+                Some("eth0") => Ok((0, String::from("Not connected.\n"))),
+                Some("eth1") => Ok((
+                    0,
+                    String::from(
+                        "Connected to 12:34:56:78:9a:bc (on eth1)
+\tSSID: Steam Deck
+\tfreq: 5180.0
+\tRX: 123456 bytes (789 packets)
+\tTX: 65432 bytes (321 packets)
+\tsignal: -52 dBm
+\trx bitrate: 866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2
+\ttx bitrate: 780.0 MBit/s VHT-MCS 8 80MHz VHT-NSS 2
+",
+                    ),
+                )),
+                _ => bail!("Unknown interface"),
+            }
+        }
+        h.test.process_cb.set(process_output);
+
+        assert_eq!(
+            get_wifi_link().await.expect("get_wifi_link"),
+            WifiLink {
+                ssid: String::from("Steam Deck"),
+                bssid: String::from("12:34:56:78:9a:bc"),
+                frequency: 5180,
+                signal: -52,
+                tx_bitrate: 780.0,
+                rx_bitrate: 866.7,
+            }
+        );
+
+        assert_eq!(parse_wifi_link("Not connected.").unwrap(), None);
+        assert!(parse_wifi_link("").is_err());
+        assert!(parse_wifi_link("Connected to 12:34:56:78:9a:bc\n\tsignal: strong").is_err());
+    }
+
     #[tokio::test]
     async fn test_power_management_multi_iface() {
         let h = testing::start();